@@ -0,0 +1,77 @@
+//! Chunking, keyword search, vector search, and RRF merge benchmarks.
+//!
+//! Vector search runs at 10k and 100k chunks by default; set
+//! `GHOST_BENCH_1M=1` to add the 1M-chunk vault (needs ~2 GB of RAM and
+//! several minutes to build). See `ghost_lib::perf` for comparing runs
+//! against a saved baseline.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ghost_lib::perf::{self, BenchVault};
+
+fn vault_sizes() -> Vec<usize> {
+    let mut sizes = vec![10_000, 100_000];
+    if std::env::var_os("GHOST_BENCH_1M").is_some() {
+        sizes.push(1_000_000);
+    }
+    sizes
+}
+
+fn chunking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunking");
+    for words in [1_000, 10_000, 100_000] {
+        let text = perf::sample_text(words, 1);
+        group.throughput(Throughput::Elements(words as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(words), &text, |b, text| {
+            b.iter(|| perf::chunk(text))
+        });
+    }
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    for chunks in vault_sizes() {
+        let vault = BenchVault::build(chunks).expect("build benchmark vault");
+
+        let mut group = c.benchmark_group("fts_query");
+        for query in perf::SAMPLE_QUERIES {
+            group.bench_with_input(
+                BenchmarkId::new(chunks.to_string(), query),
+                query,
+                |b, q| b.iter(|| vault.fts_query(q, 50).unwrap()),
+            );
+        }
+        group.finish();
+
+        if !vault.vec_enabled() {
+            eprintln!("sqlite-vec unavailable — skipping vector search benchmarks");
+            continue;
+        }
+        let mut group = c.benchmark_group("vec_search");
+        if chunks >= 100_000 {
+            group.sample_size(10);
+        }
+        let mut seed = 0;
+        group.bench_function(BenchmarkId::from_parameter(chunks), |b| {
+            b.iter(|| {
+                seed += 1;
+                vault.vec_query(seed, 50).unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+fn rrf_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rrf_merge");
+    for len in [10, 100, 1_000] {
+        let fts = perf::sample_ranking(len, 2);
+        let vec = perf::sample_ranking(len, 3);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &len, |b, _| {
+            b.iter(|| perf::rrf(&fts, &vec))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, chunking, search, rrf_merge);
+criterion_main!(benches);
@@ -0,0 +1,154 @@
+//! Tool approval gate — pauses agent runs until the user approves or denies a tool call.
+//!
+//! Flow:
+//! 1. `AgentExecutor` hits a tool call that is not auto-approved
+//! 2. It registers a pending approval here and emits `tool_approval_required` (AG-UI custom event)
+//! 3. The frontend calls `respond_tool_approval` with the user's decision
+//! 4. The waiting executor resumes (approve / always allow) or skips the tool (deny)
+//!
+//! Pending approvals that never get a response are resolved as denied after
+//! `AgentConfig::approval_timeout_ms`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// The user's answer to an approval request.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// Run the tool this one time.
+    Approve,
+    /// Run the tool and persist an "allow" policy for it.
+    AlwaysAllow,
+    /// Skip the tool call.
+    Deny,
+}
+
+/// Registry of tool calls waiting for a user decision.
+pub struct ApprovalGate {
+    pending: Mutex<HashMap<String, oneshot::Sender<ApprovalDecision>>>,
+    next_id: AtomicU64,
+}
+
+impl ApprovalGate {
+    /// Create an empty approval gate.
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Register a new pending approval.
+    ///
+    /// Returns the approval ID (sent to the frontend) and the receiver the
+    /// executor awaits on.
+    pub fn register(&self) -> (String, oneshot::Receiver<ApprovalDecision>) {
+        let id = format!("approval-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), tx);
+        (id, rx)
+    }
+
+    /// Deliver a decision to a pending approval.
+    ///
+    /// Returns false if the approval is unknown or already resolved.
+    pub fn respond(&self, approval_id: &str, decision: ApprovalDecision) -> bool {
+        let sender = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(approval_id);
+        match sender {
+            Some(tx) => tx.send(decision).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Drop a pending approval without a decision (e.g. after a timeout).
+    pub fn cancel(&self, approval_id: &str) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(approval_id);
+    }
+
+    /// IDs of all approvals still waiting for a decision.
+    pub fn pending_ids(&self) -> Vec<String> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for ApprovalGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_register_and_respond() {
+        let gate = ApprovalGate::new();
+        let (id, rx) = gate.register();
+        assert_eq!(gate.pending_ids(), vec![id.clone()]);
+
+        assert!(gate.respond(&id, ApprovalDecision::Approve));
+        assert_eq!(rx.await.unwrap(), ApprovalDecision::Approve);
+        assert!(gate.pending_ids().is_empty());
+    }
+
+    #[test]
+    fn test_respond_unknown_id() {
+        let gate = ApprovalGate::new();
+        assert!(!gate.respond("approval-999", ApprovalDecision::Deny));
+    }
+
+    #[test]
+    fn test_respond_twice_fails() {
+        let gate = ApprovalGate::new();
+        let (id, _rx) = gate.register();
+        assert!(gate.respond(&id, ApprovalDecision::Deny));
+        assert!(!gate.respond(&id, ApprovalDecision::Approve));
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        let gate = ApprovalGate::new();
+        let (a, _) = gate.register();
+        let (b, _) = gate.register();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_cancel_removes_pending() {
+        let gate = ApprovalGate::new();
+        let (id, _rx) = gate.register();
+        gate.cancel(&id);
+        assert!(gate.pending_ids().is_empty());
+    }
+
+    #[test]
+    fn test_decision_serde() {
+        let d: ApprovalDecision = serde_json::from_str("\"always_allow\"").unwrap();
+        assert_eq!(d, ApprovalDecision::AlwaysAllow);
+        assert_eq!(
+            serde_json::to_string(&ApprovalDecision::Deny).unwrap(),
+            "\"deny\""
+        );
+    }
+}
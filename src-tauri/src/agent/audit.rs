@@ -0,0 +1,168 @@
+//! Shell command audit log.
+//!
+//! Every command the agent tries to run through `ghost_run_command` is
+//! recorded here — including ones blocked by the command policy — with its
+//! working directory, outcome, exit code, and (truncated, redacted) output.
+//! The log is append-only from the agent's side and surfaced to the user via
+//! the `get_agent_audit_log` command.
+
+use serde::Serialize;
+
+use super::tools::redact_secrets;
+use crate::db::Database;
+use crate::error::Result;
+
+/// Longest output stored per entry (in characters).
+const MAX_AUDIT_OUTPUT: usize = 4000;
+
+/// One recorded command execution.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandAuditEntry {
+    pub id: i64,
+    pub command: String,
+    pub working_dir: String,
+    /// "completed", "failed", "timeout", or "blocked".
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub output: String,
+    pub duration_ms: i64,
+    pub created_at: String,
+}
+
+/// Initialize the audit table in the database.
+pub fn initialize_audit_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS agent_command_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                command TEXT NOT NULL,
+                working_dir TEXT NOT NULL,
+                status TEXT NOT NULL,
+                exit_code INTEGER,
+                output TEXT NOT NULL DEFAULT '',
+                duration_ms INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_agent_command_audit_created
+                ON agent_command_audit(created_at);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Record a command execution. Secrets in the command and its output are
+/// redacted before storing. Returns the entry ID.
+pub fn record_command(
+    db: &Database,
+    command: &str,
+    working_dir: &str,
+    status: &str,
+    exit_code: Option<i32>,
+    output: &str,
+    duration_ms: i64,
+) -> Result<i64> {
+    let command = redact_secrets(command);
+    let output: String = redact_secrets(output)
+        .chars()
+        .take(MAX_AUDIT_OUTPUT)
+        .collect();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO agent_command_audit
+                (command, working_dir, status, exit_code, output, duration_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![command, working_dir, status, exit_code, output, duration_ms],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+}
+
+/// Most recent audit entries, newest first.
+pub fn get_audit_log(db: &Database, limit: usize) -> Result<Vec<CommandAuditEntry>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, command, working_dir, status, exit_code, output, duration_ms, created_at
+             FROM agent_command_audit
+             ORDER BY id DESC
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![limit as i64], |row| {
+            Ok(CommandAuditEntry {
+                id: row.get(0)?,
+                command: row.get(1)?,
+                working_dir: row.get(2)?,
+                status: row.get(3)?,
+                exit_code: row.get(4)?,
+                output: row.get(5)?,
+                duration_ms: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })?;
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_test_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        initialize_audit_schema(&db).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_record_and_list() {
+        let db = setup_test_db();
+        record_command(&db, "ls", "/home/u", "completed", Some(0), "a\nb", 12).unwrap();
+        record_command(&db, "rm -rf /", "/home/u", "blocked", None, "", 0).unwrap();
+
+        let log = get_audit_log(&db, 10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].command, "rm -rf /");
+        assert_eq!(log[0].status, "blocked");
+        assert_eq!(log[0].exit_code, None);
+        assert_eq!(log[1].exit_code, Some(0));
+        assert_eq!(log[1].output, "a\nb");
+
+        assert_eq!(get_audit_log(&db, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_output_truncated() {
+        let db = setup_test_db();
+        let long = "é".repeat(MAX_AUDIT_OUTPUT + 10);
+        record_command(&db, "cat big", "/", "completed", Some(0), &long, 1).unwrap();
+        let log = get_audit_log(&db, 1).unwrap();
+        assert_eq!(log[0].output.chars().count(), MAX_AUDIT_OUTPUT);
+    }
+
+    #[test]
+    fn test_secrets_redacted() {
+        let db = setup_test_db();
+        record_command(
+            &db,
+            "GITHUB_TOKEN=ghp_abc123 gh repo list",
+            "/",
+            "completed",
+            Some(0),
+            "Straße\nAPI_KEY=hunter2\nusing sk-proj-abcdef",
+            3,
+        )
+        .unwrap();
+        let log = get_audit_log(&db, 1).unwrap();
+        assert!(!log[0].command.contains("ghp_abc123"));
+        assert!(log[0].command.contains("gh repo list"));
+        assert!(!log[0].output.contains("hunter2"));
+        assert!(!log[0].output.contains("sk-proj-abcdef"));
+        assert!(log[0].output.starts_with("Straße\n"));
+    }
+}
@@ -15,6 +15,8 @@
 //!
 //! ZERO external dependencies — no Ollama, no server, no network after first download.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::embeddings::hardware::HardwareInfo;
@@ -62,6 +64,122 @@ pub struct AgentConfig {
     /// Prevents runaway tool-calling loops.
     #[serde(default = "default_max_tool_calls_per_run")]
     pub max_tool_calls_per_run: usize,
+
+    /// Maximum tokens generated across all iterations of a run (0 = no limit).
+    #[serde(default = "default_max_run_tokens")]
+    pub max_run_tokens: usize,
+
+    /// Wall-clock limit for a run in seconds (0 = no limit).
+    #[serde(default = "default_max_run_secs")]
+    pub max_run_secs: u64,
+
+    /// Per-tool permission overrides, keyed by tool name.
+    /// Tools without an entry fall back to risk-based approval.
+    #[serde(default)]
+    pub tool_permissions: HashMap<String, ToolPermission>,
+
+    /// How long to wait for the user to answer an approval prompt
+    /// before the tool call is denied, in milliseconds.
+    #[serde(default = "default_approval_timeout_ms")]
+    pub approval_timeout_ms: u64,
+
+    /// Limits applied to `ghost_run_command`.
+    #[serde(default)]
+    pub command_policy: CommandPolicy,
+
+    /// Treat the machine as offline; `offline` guardrails apply.
+    #[serde(default)]
+    pub offline_mode: bool,
+
+    /// Rules checked before every tool call (see [`super::safety::check_guardrails`]).
+    #[serde(default = "default_guardrails")]
+    pub guardrails: Vec<GuardrailRule>,
+}
+
+/// Safety policy for agent shell commands.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandPolicy {
+    /// Extra command patterns to refuse (case-insensitive substring match),
+    /// on top of the built-in deny list.
+    #[serde(default)]
+    pub denied_commands: Vec<String>,
+    /// Directories commands may run in (including subdirectories).
+    /// Empty = the user's home directory.
+    #[serde(default)]
+    pub allowed_working_dirs: Vec<String>,
+    /// Commands are killed after this many seconds.
+    #[serde(default = "default_command_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Maximum stdout bytes returned to the agent (stderr gets half).
+    #[serde(default = "default_max_command_output_bytes")]
+    pub max_output_bytes: usize,
+}
+
+impl Default for CommandPolicy {
+    fn default() -> Self {
+        Self {
+            denied_commands: Vec::new(),
+            allowed_working_dirs: Vec::new(),
+            timeout_secs: default_command_timeout_secs(),
+            max_output_bytes: default_max_command_output_bytes(),
+        }
+    }
+}
+
+/// A user-defined rule evaluated before every tool call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GuardrailRule {
+    pub name: String,
+    /// Tools the rule covers: exact names, prefixes ending in `*`, or
+    /// `@network` for tools that reach the network. Empty = every tool.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(flatten)]
+    pub condition: GuardrailCondition,
+    pub action: GuardrailAction,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// When a [`GuardrailRule`] applies to a covered tool call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "when", rename_all = "snake_case")]
+pub enum GuardrailCondition {
+    Always,
+    /// A path argument lies outside all of `directories`.
+    OutsideDirectories {
+        directories: Vec<String>,
+    },
+    /// Offline mode is on.
+    Offline,
+    /// The regex matches `argument` (all arguments as JSON when unset).
+    ArgumentMatches {
+        pattern: String,
+        #[serde(default)]
+        argument: Option<String>,
+    },
+}
+
+/// What a matching [`GuardrailRule`] does to the call.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardrailAction {
+    /// Prompt the user even when the tool would run unprompted.
+    Ask,
+    /// Refuse the call.
+    Deny,
+}
+
+/// Per-tool permission policy set by the user.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolPermission {
+    /// Always run without prompting.
+    Allow,
+    /// Never run.
+    Deny,
+    /// Prompt every time the tool is not auto-approved.
+    Ask,
 }
 
 fn default_agent_model() -> String {
@@ -96,6 +214,33 @@ fn default_tool_timeout_ms() -> u64 {
 fn default_max_tool_calls_per_run() -> usize {
     20
 }
+fn default_max_run_tokens() -> usize {
+    32_768
+}
+fn default_max_run_secs() -> u64 {
+    600
+}
+fn default_approval_timeout_ms() -> u64 {
+    120_000
+}
+fn default_true() -> bool {
+    true
+}
+fn default_guardrails() -> Vec<GuardrailRule> {
+    vec![GuardrailRule {
+        name: "Block network tools offline".into(),
+        tools: vec!["@network".into()],
+        condition: GuardrailCondition::Offline,
+        action: GuardrailAction::Deny,
+        enabled: true,
+    }]
+}
+fn default_command_timeout_secs() -> u64 {
+    30
+}
+fn default_max_command_output_bytes() -> usize {
+    10_000
+}
 
 impl Default for AgentConfig {
     fn default() -> Self {
@@ -109,6 +254,93 @@ impl Default for AgentConfig {
             skills_dir: default_skills_dir(),
             tool_timeout_ms: default_tool_timeout_ms(),
             max_tool_calls_per_run: default_max_tool_calls_per_run(),
+            max_run_tokens: default_max_run_tokens(),
+            max_run_secs: default_max_run_secs(),
+            tool_permissions: HashMap::new(),
+            approval_timeout_ms: default_approval_timeout_ms(),
+            command_policy: CommandPolicy::default(),
+            offline_mode: false,
+            guardrails: default_guardrails(),
+        }
+    }
+}
+
+/// A per-run budget of [`AgentConfig`].
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLimit {
+    Iterations,
+    ToolCalls,
+    Tokens,
+    WallClock,
+}
+
+/// What an agent run has used so far.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct RunUsage {
+    /// Completed ReAct iterations.
+    pub iterations: usize,
+    pub tool_calls: usize,
+    /// Tokens generated.
+    pub tokens: usize,
+    pub elapsed_ms: u64,
+}
+
+/// A budget a run ran out of (the `budget_exceeded` AG-UI event payload).
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub limit: BudgetLimit,
+    /// The configured maximum (seconds for `wall_clock`).
+    pub max: u64,
+    pub usage: RunUsage,
+}
+
+impl AgentConfig {
+    /// Get the user's permission policy for a tool (defaults to `Ask`).
+    pub fn tool_permission(&self, tool_name: &str) -> ToolPermission {
+        self.tool_permissions
+            .get(tool_name)
+            .copied()
+            .unwrap_or(ToolPermission::Ask)
+    }
+
+    /// The first budget `usage` has used up, if any. Checked before each
+    /// iteration; token and wall-clock budgets of 0 are unlimited.
+    pub fn check_budget(&self, usage: &RunUsage) -> Option<BudgetExceeded> {
+        let exceeded = |limit, max: u64| {
+            Some(BudgetExceeded {
+                limit,
+                max,
+                usage: *usage,
+            })
+        };
+        if usage.iterations >= self.max_iterations {
+            return exceeded(BudgetLimit::Iterations, self.max_iterations as u64);
+        }
+        if usage.tool_calls >= self.max_tool_calls_per_run {
+            return exceeded(BudgetLimit::ToolCalls, self.max_tool_calls_per_run as u64);
+        }
+        if self.max_run_tokens > 0 && usage.tokens >= self.max_run_tokens {
+            return exceeded(BudgetLimit::Tokens, self.max_run_tokens as u64);
+        }
+        if self.max_run_secs > 0 && usage.elapsed_ms >= self.max_run_secs * 1000 {
+            return exceeded(BudgetLimit::WallClock, self.max_run_secs);
+        }
+        None
+    }
+
+    /// Config of a sub-agent spawned by a run with this config: half the
+    /// steps, tool calls and time, a quarter of the tokens, and at most
+    /// `max_iterations` steps when the parent asks for fewer.
+    pub fn subagent_config(&self, max_iterations: Option<usize>) -> AgentConfig {
+        let half_iterations = (self.max_iterations / 2).max(1);
+        AgentConfig {
+            max_iterations: max_iterations.map_or(half_iterations, |n| n.clamp(1, half_iterations)),
+            max_tool_calls_per_run: (self.max_tool_calls_per_run / 2).max(1),
+            // 0 stays unlimited; other limits don't round down to it
+            max_run_tokens: self.max_run_tokens.div_ceil(4),
+            max_run_secs: self.max_run_secs.div_ceil(2),
+            ..self.clone()
         }
     }
 }
@@ -222,6 +454,9 @@ pub fn resolve_agent_model(config: &AgentConfig, hardware: &HardwareInfo) -> (St
     if config.agent_model == "auto" {
         let (tier, ctx) = recommend_agent_model(hardware);
         (tier.model_id.to_string(), ctx)
+    } else if let Some(custom) = crate::chat::models::custom_model(&config.agent_model) {
+        // Imported model — use the context window chosen at import
+        (custom.id, custom.context_window as usize)
     } else {
         // User specified a model — use their context window config
         (config.agent_model.clone(), config.context_window)
@@ -242,6 +477,44 @@ mod tests {
         assert_eq!(config.tool_timeout_ms, 30000);
         assert_eq!(config.max_tool_calls_per_run, 20);
         assert!(config.auto_approve_safe);
+        assert!(config.tool_permissions.is_empty());
+        assert_eq!(config.approval_timeout_ms, 120_000);
+        assert_eq!(config.command_policy.timeout_secs, 30);
+        assert_eq!(config.command_policy.max_output_bytes, 10_000);
+        assert!(config.command_policy.allowed_working_dirs.is_empty());
+    }
+
+    #[test]
+    fn test_command_policy_defaults_when_missing() {
+        let config: AgentConfig = serde_json::from_str(r#"{"agent_model": "auto"}"#).unwrap();
+        assert_eq!(config.command_policy, CommandPolicy::default());
+    }
+
+    #[test]
+    fn test_tool_permission_lookup() {
+        let mut config = AgentConfig::default();
+        assert_eq!(
+            config.tool_permission("ghost_run_command"),
+            ToolPermission::Ask
+        );
+        config
+            .tool_permissions
+            .insert("ghost_run_command".into(), ToolPermission::Allow);
+        assert_eq!(
+            config.tool_permission("ghost_run_command"),
+            ToolPermission::Allow
+        );
+    }
+
+    #[test]
+    fn test_tool_permissions_serde() {
+        let json = r#"{"tool_permissions": {"ghost_write_file": "deny"}}"#;
+        let config: AgentConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.tool_permission("ghost_write_file"),
+            ToolPermission::Deny
+        );
+        assert_eq!(config.approval_timeout_ms, 120_000);
     }
 
     #[test]
@@ -337,4 +610,84 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_subagent_config() {
+        let config = AgentConfig {
+            max_iterations: 10,
+            max_tool_calls_per_run: 20,
+            max_run_tokens: 32_000,
+            max_run_secs: 0,
+            ..Default::default()
+        };
+        let child = config.subagent_config(None);
+        assert_eq!(child.max_iterations, 5);
+        assert_eq!(child.max_tool_calls_per_run, 10);
+        assert_eq!(child.max_run_tokens, 8_000);
+        assert_eq!(child.max_run_secs, 0);
+        assert_eq!(config.subagent_config(Some(2)).max_iterations, 2);
+        assert_eq!(config.subagent_config(Some(50)).max_iterations, 5);
+        assert_eq!(config.subagent_config(Some(0)).max_iterations, 1);
+    }
+
+    #[test]
+    fn test_check_budget() {
+        let config = AgentConfig {
+            max_iterations: 5,
+            max_tool_calls_per_run: 10,
+            max_run_tokens: 1000,
+            max_run_secs: 60,
+            ..Default::default()
+        };
+        let usage = RunUsage {
+            iterations: 2,
+            tool_calls: 3,
+            tokens: 400,
+            elapsed_ms: 10_000,
+        };
+        assert!(config.check_budget(&usage).is_none());
+
+        let over = |usage: RunUsage| config.check_budget(&usage).map(|e| e.limit);
+        assert_eq!(
+            over(RunUsage {
+                iterations: 5,
+                ..usage
+            }),
+            Some(BudgetLimit::Iterations)
+        );
+        assert_eq!(
+            over(RunUsage {
+                tool_calls: 10,
+                ..usage
+            }),
+            Some(BudgetLimit::ToolCalls)
+        );
+        assert_eq!(
+            over(RunUsage {
+                tokens: 1000,
+                ..usage
+            }),
+            Some(BudgetLimit::Tokens)
+        );
+        let late = config
+            .check_budget(&RunUsage {
+                elapsed_ms: 60_000,
+                ..usage
+            })
+            .unwrap();
+        assert_eq!(late.limit, BudgetLimit::WallClock);
+        assert_eq!(late.max, 60);
+
+        let unlimited = AgentConfig {
+            max_run_tokens: 0,
+            max_run_secs: 0,
+            ..config
+        };
+        let heavy = RunUsage {
+            tokens: usize::MAX,
+            elapsed_ms: u64::MAX,
+            ..usage
+        };
+        assert!(unlimited.check_budget(&heavy).is_none());
+    }
 }
@@ -0,0 +1,350 @@
+//! Conversation-to-note distillation.
+//!
+//! Turns a chat into a Markdown note (summary, decisions, action items,
+//! references) written by the local model, saves it into a watched folder so
+//! it is indexed like any other document, and links the two: the note's
+//! front matter names the conversation, and the conversation records the
+//! note's path.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::memory::{self, Message};
+use super::Citation;
+use crate::chat::ChatMessage;
+use crate::error::{GhostError, Result};
+use crate::AppState;
+
+/// Token budget for the generated note.
+const DISTILL_MAX_TOKENS: usize = 1024;
+
+/// Transcript characters sent to the model; longer chats keep their end.
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+
+const DISTILL_PROMPT: &str = "You turn a chat transcript into a concise Markdown note \
+for the user's knowledge base. Write these sections, omitting any that would be empty:\n\
+## Summary\n2-4 sentences on what the conversation was about and what came out of it.\n\
+## Decisions\nBullet list of decisions made.\n\
+## Action Items\nChecklist (`- [ ] ...`) of follow-ups, with owners or dates when stated.\n\
+## References\nBullet list of files, links, people, or resources mentioned.\n\
+Use only facts from the transcript. Do not add a title. Output only the Markdown.";
+
+/// A note written by [`distill_conversation`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DistilledNote {
+    pub conversation_id: i64,
+    pub title: String,
+    /// Where the note was saved.
+    pub path: String,
+    pub markdown: String,
+}
+
+/// User and assistant turns as plain text, trimmed to the most recent
+/// [`MAX_TRANSCRIPT_CHARS`].
+fn transcript(messages: &[Message]) -> String {
+    let turns: Vec<String> = messages
+        .iter()
+        .filter(|m| !m.content.trim().is_empty())
+        .filter_map(|m| match m.role.as_str() {
+            "user" => Some(format!("User: {}", m.content.trim())),
+            "assistant" => Some(format!("Assistant: {}", m.content.trim())),
+            _ => None,
+        })
+        .collect();
+
+    let mut kept = Vec::new();
+    let mut chars = 0;
+    for turn in turns.iter().rev() {
+        chars += turn.chars().count();
+        if chars > MAX_TRANSCRIPT_CHARS && !kept.is_empty() {
+            break;
+        }
+        kept.push(turn.as_str());
+    }
+    kept.reverse();
+    kept.join("\n\n")
+}
+
+/// Files cited by the conversation's answers, as (filename, path), deduplicated.
+fn cited_files(messages: &[Message]) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    for citations in messages.iter().filter_map(|m| m.citations.as_deref()) {
+        let Ok(citations) = serde_json::from_str::<Vec<Citation>>(citations) else {
+            continue;
+        };
+        for citation in citations {
+            if !files.iter().any(|(_, path)| *path == citation.path) {
+                files.push((citation.filename, citation.path));
+            }
+        }
+    }
+    files
+}
+
+/// Strip wrappers small models add around Markdown: code fences and a title.
+fn clean_model_output(text: &str) -> String {
+    let mut text = text.trim();
+    if let Some(rest) = text.strip_prefix("```") {
+        // Drop the opening fence line (```markdown) and the closing fence
+        text = rest.split_once('\n').map_or("", |(_, body)| body);
+        text = text.trim_end().strip_suffix("```").unwrap_or(text);
+    }
+    let text = text.trim();
+    let body = match text.strip_prefix("# ") {
+        Some(rest) => rest.split_once('\n').map_or("", |(_, body)| body).trim(),
+        None => text,
+    };
+    body.to_string()
+}
+
+/// File name for a note: the conversation title made filesystem-safe.
+fn note_file_name(title: &str, date: &str) -> String {
+    let slug: String = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let slug: String = slug.chars().take(80).collect();
+    if slug.is_empty() {
+        format!("Conversation {}.md", date)
+    } else {
+        format!("{} {}.md", slug, date)
+    }
+}
+
+/// Resolve where to save the note. `destination` is either a folder (the
+/// note gets a name from the title, never overwriting) or a `.md` file path
+/// that must not exist yet.
+fn resolve_note_path(destination: &Path, title: &str, date: &str) -> Result<PathBuf> {
+    let is_file = destination
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"));
+    if is_file {
+        if destination.exists() {
+            return Err(GhostError::Agent(format!(
+                "{} already exists; choose a new file name",
+                destination.display()
+            )));
+        }
+        return Ok(destination.to_path_buf());
+    }
+
+    let name = note_file_name(title, date);
+    let mut path = destination.join(&name);
+    let stem = name.trim_end_matches(".md");
+    let mut n = 2;
+    while path.exists() {
+        path = destination.join(format!("{} ({}).md", stem, n));
+        n += 1;
+    }
+    Ok(path)
+}
+
+/// Assemble the note: front matter linking the conversation, the model's
+/// sections, and the files the conversation actually cited.
+fn render_note(
+    title: &str,
+    conversation_id: i64,
+    date: &str,
+    body: &str,
+    cited: &[(String, String)],
+) -> String {
+    let mut note = format!(
+        "---\ntitle: \"{}\"\nsource: ghost-conversation\nconversation_id: {}\ncreated: {}\n---\n\n# {}\n\n{}\n",
+        title.replace('\\', "\\\\").replace('"', "\\\""),
+        conversation_id,
+        date,
+        title,
+        body
+    );
+    if !cited.is_empty() {
+        note.push_str("\n## Sources\n");
+        for (filename, path) in cited {
+            note.push_str(&format!("- [{}](<{}>)\n", filename, path));
+        }
+    }
+    note.push_str(&format!(
+        "\n---\n*Distilled from Ghost conversation #{} on {}.*\n",
+        conversation_id, date
+    ));
+    note
+}
+
+/// Distill a conversation into a Markdown note saved at `destination`
+/// (a folder or `.md` path inside the watched directories).
+pub async fn distill_conversation(
+    state: &AppState,
+    conversation_id: i64,
+    destination: &str,
+) -> Result<DistilledNote> {
+    let conversation = memory::get_conversation(&state.db, conversation_id)?
+        .ok_or_else(|| GhostError::Agent(format!("Conversation {} not found", conversation_id)))?;
+    let messages = memory::get_messages(&state.db, conversation_id, None)?;
+    let transcript = transcript(&messages);
+    if transcript.is_empty() {
+        return Err(GhostError::Agent(
+            "The conversation has no messages to distill".into(),
+        ));
+    }
+
+    let roots = state
+        .settings
+        .lock()
+        .map_err(|e| GhostError::Agent(e.to_string()))?
+        .watched_directories
+        .clone();
+    let destination =
+        super::safety::resolve_sandboxed_path(destination, &roots).map_err(GhostError::Agent)?;
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let path = resolve_note_path(&destination, &conversation.title, &date)?;
+
+    let prompt = [
+        ChatMessage {
+            role: "system".into(),
+            content: DISTILL_PROMPT.into(),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: format!(
+                "Conversation: {}\n\n<transcript>\n{}\n</transcript>",
+                conversation.title, transcript
+            ),
+        },
+    ];
+    let response = state.chat_engine.chat(&prompt, DISTILL_MAX_TOKENS).await?;
+    let body = clean_model_output(&response.content);
+    if body.is_empty() {
+        return Err(GhostError::Agent("The model returned an empty note".into()));
+    }
+
+    let markdown = render_note(
+        &conversation.title,
+        conversation_id,
+        &date,
+        &body,
+        &cited_files(&messages),
+    );
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, &markdown).await?;
+
+    let path_str = path.to_string_lossy().into_owned();
+    memory::set_conversation_note(&state.db, conversation_id, &path_str)?;
+    // Index now so the note is searchable without waiting for the watcher
+    if let Err(e) = crate::indexer::index_file(&state.db, &state.embedding_engine, &path).await {
+        tracing::warn!("Distilled note saved but not indexed yet: {}", e);
+    }
+
+    Ok(DistilledNote {
+        conversation_id,
+        title: conversation.title,
+        path: path_str,
+        markdown,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str, citations: Option<&str>) -> Message {
+        Message {
+            id: 0,
+            conversation_id: 1,
+            role: role.into(),
+            content: content.into(),
+            created_at: String::new(),
+            tool_calls: None,
+            tool_result: None,
+            model: None,
+            citations: citations.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_transcript_and_citations() {
+        let citation = r#"[{"index":1,"chunk_id":3,"document_id":2,"path":"/docs/plan.md","filename":"plan.md","snippet":"..."}]"#;
+        let messages = vec![
+            message("system", "You are Ghost", None),
+            message("user", "What's the launch plan?", None),
+            message("tool", "{\"results\": []}", None),
+            message("assistant", "Launch on May 3.", Some(citation)),
+            message("assistant", "Per plan.md, yes.", Some(citation)),
+        ];
+        assert_eq!(
+            transcript(&messages),
+            "User: What's the launch plan?\n\nAssistant: Launch on May 3.\n\nAssistant: Per plan.md, yes."
+        );
+        assert_eq!(
+            cited_files(&messages),
+            vec![("plan.md".to_string(), "/docs/plan.md".to_string())]
+        );
+
+        // Long chats keep their most recent turns
+        let long = vec![
+            message("user", &"old ".repeat(MAX_TRANSCRIPT_CHARS), None),
+            message("user", "latest question", None),
+        ];
+        assert_eq!(transcript(&long), "User: latest question");
+    }
+
+    #[test]
+    fn test_clean_model_output() {
+        assert_eq!(
+            clean_model_output("```markdown\n# Title\n## Summary\nDone.\n```"),
+            "## Summary\nDone."
+        );
+        assert_eq!(
+            clean_model_output("  ## Decisions\n- Ship\n"),
+            "## Decisions\n- Ship"
+        );
+    }
+
+    #[test]
+    fn test_note_path_and_rendering() {
+        assert_eq!(
+            note_file_name("Q3 launch: plan/budget?", "2026-03-01"),
+            "Q3 launch plan budget 2026-03-01.md"
+        );
+        assert_eq!(
+            note_file_name("???", "2026-03-01"),
+            "Conversation 2026-03-01.md"
+        );
+
+        let dir = std::env::temp_dir().join("ghost_test_distill");
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = resolve_note_path(&dir, "Plan", "2026-03-01").unwrap();
+        assert_eq!(first, dir.join("Plan 2026-03-01.md"));
+        std::fs::write(&first, "existing").unwrap();
+        // Never overwrites: folders get a numbered name, file paths are refused
+        assert_eq!(
+            resolve_note_path(&dir, "Plan", "2026-03-01").unwrap(),
+            dir.join("Plan 2026-03-01 (2).md")
+        );
+        assert!(resolve_note_path(&first, "Plan", "2026-03-01").is_err());
+        std::fs::remove_dir_all(&dir).ok();
+
+        let note = render_note(
+            "Say \"hi\"",
+            7,
+            "2026-03-01",
+            "## Summary\nGreeting.",
+            &[("plan.md".into(), "/docs/plan.md".into())],
+        );
+        assert!(note.starts_with("---\ntitle: \"Say \\\"hi\\\"\"\n"));
+        assert!(note.contains("conversation_id: 7\n"));
+        assert!(note.contains("- [plan.md](</docs/plan.md>)"));
+        assert!(note.contains("conversation #7"));
+    }
+}
@@ -12,7 +12,11 @@
 //! Uses the SAME Qwen2.5-Instruct GGUF models from the chat model registry,
 //! with Hermes 2 Pro tool-calling format + GBNF grammar-constrained generation.
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use serde::Deserialize;
@@ -25,10 +29,17 @@ use llama_cpp_2::model::{AddBos, ChatTemplateResult, LlamaChatMessage, LlamaMode
 #[cfg(desktop)]
 use llama_cpp_2::sampling::LlamaSampler;
 
-use super::config::{self, AgentConfig};
+use super::approval::ApprovalDecision;
+use super::config::{
+    self, AgentConfig, BudgetExceeded, BudgetLimit, GuardrailAction, RunUsage, ToolPermission,
+};
+use super::plan::{self, AgentPlan, PlannedStep};
 use super::safety::{self, RiskLevel};
+use super::skills::Skill;
 use super::tools::{self, RegisteredTool};
-use super::{AgentChatMessage, AgentRunResult, ExecutedToolCall, ToolCall, ToolCallFunction};
+use super::{
+    AgentChatMessage, AgentRunResult, Citation, ExecutedToolCall, ToolCall, ToolCallFunction,
+};
 use crate::chat::ChatMessage;
 use crate::error::GhostError;
 use crate::protocols::agui::{AgUiEvent, AgUiEventBus};
@@ -64,14 +75,66 @@ struct ParsedToolCallFunction {
     arguments: serde_json::Value,
 }
 
+/// Arguments of `ghost_spawn_subagent`.
+#[derive(Debug, Deserialize)]
+struct SubagentParams {
+    task: String,
+    #[serde(default)]
+    instructions: Option<String>,
+    #[serde(default)]
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    max_iterations: Option<usize>,
+}
+
+/// A sub-agent to run for a tool call.
+struct SubagentTask {
+    /// The child's user message.
+    task: String,
+    /// Extra instructions for its system prompt.
+    instructions: Option<String>,
+    tools: Vec<RegisteredTool>,
+    max_iterations: Option<usize>,
+    /// The skill the sub-agent runs, if any.
+    skill: Option<String>,
+}
+
+/// What a scoped run (a sub-agent or a skill) is limited to.
+struct RunScope {
+    /// The run that spawned this one, for sub-agents.
+    parent_run_id: Option<String>,
+    system_prompt: String,
+    tools: Vec<RegisteredTool>,
+    config: AgentConfig,
+    /// The parent's attachment scope for `ghost_search`.
+    search_scope: Option<HashSet<i64>>,
+}
+
+/// Numbers sub-agent run IDs.
+static NEXT_SUBAGENT: AtomicU64 = AtomicU64::new(1);
+
 /// The agent executor — runs ReAct loops with native tool calling.
 pub struct AgentExecutor {
     state: Arc<AppState>,
+    /// Calls held back in plan mode; `None` for normal runs.
+    planned_steps: Option<Mutex<Vec<PlannedStep>>>,
 }
 
 impl AgentExecutor {
     pub fn new(state: Arc<AppState>) -> Self {
-        Self { state }
+        Self {
+            state,
+            planned_steps: None,
+        }
+    }
+
+    /// An executor for plan mode: only safe tools run, other calls become
+    /// steps of an [`AgentPlan`] emitted as a `plan_ready` CUSTOM event.
+    pub fn planner(state: Arc<AppState>) -> Self {
+        Self {
+            state,
+            planned_steps: Some(Mutex::new(Vec::new())),
+        }
     }
 
     /// Execute an agent run with the ReAct loop.
@@ -84,16 +147,32 @@ impl AgentExecutor {
         messages: &[ChatMessage],
         conversation_id: Option<i64>,
         event_bus: &AgUiEventBus,
+    ) -> Result<AgentRunResult, GhostError> {
+        self.run_scoped(run_id, messages, conversation_id, None, event_bus)
+            .await
+    }
+
+    /// [`Self::run`], limited to the tools and prompt of `scoped` when set.
+    async fn run_scoped(
+        &self,
+        run_id: &str,
+        messages: &[ChatMessage],
+        conversation_id: Option<i64>,
+        scoped: Option<&RunScope>,
+        event_bus: &AgUiEventBus,
     ) -> Result<AgentRunResult, GhostError> {
         let start = Instant::now();
 
         // Get agent config
-        let agent_config = self
-            .state
-            .settings
-            .lock()
-            .map(|s| s.agent_config.clone())
-            .unwrap_or_default();
+        let agent_config = match scoped {
+            Some(scoped) => scoped.config.clone(),
+            None => self
+                .state
+                .settings
+                .lock()
+                .map(|s| s.agent_config.clone())
+                .unwrap_or_default(),
+        };
 
         // Resolve model
         let (model_id, context_window) =
@@ -108,23 +187,93 @@ impl AgentExecutor {
         );
 
         // 1. Emit RUN_STARTED
-        event_bus.emit(AgUiEvent::run_started(run_id));
+        match scoped.and_then(|s| s.parent_run_id.as_deref()) {
+            Some(parent_run_id) => {
+                event_bus.emit(AgUiEvent::child_run_started(run_id, parent_run_id))
+            }
+            None => event_bus.emit(AgUiEvent::run_started(run_id)),
+        }
 
         // 2. Collect available tools
-        let registered_tools = tools::collect_all_tools(&self.state.mcp_client).await;
+        let registered_tools = match scoped {
+            Some(scoped) => scoped.tools.clone(),
+            None => {
+                let mut registered_tools = tools::collect_all_tools(&self.state.mcp_client).await;
+                let a2a_agents = self
+                    .state
+                    .settings
+                    .lock()
+                    .map(|s| s.a2a_agents.clone())
+                    .unwrap_or_default();
+                registered_tools.extend(tools::a2a_delegate_tool(&a2a_agents));
+                tracing::info!(
+                    "Agent has {} tools available ({} built-in, {} external)",
+                    registered_tools.len(),
+                    tools::builtin_tools().len(),
+                    registered_tools.len() - tools::builtin_tools().len()
+                );
+                registered_tools
+            }
+        };
         let tool_definitions = tools::to_tool_definitions(&registered_tools);
 
-        tracing::info!(
-            "Agent has {} tools available ({} built-in, {} external)",
-            registered_tools.len(),
-            tools::builtin_tools().len(),
-            registered_tools.len() - tools::builtin_tools().len()
-        );
+        // 3. Build system prompt. Attached documents and folders limit
+        // retrieval to the conversation's project; sub-agents inherit the
+        // parent's.
+        let (system_prompt, scope) = match scoped {
+            Some(scoped) => (scoped.system_prompt.clone(), scoped.search_scope.clone()),
+            None => {
+                let mut system_prompt = build_system_prompt(&self.state, messages);
+                let (attachments, scope) = match conversation_id {
+                    Some(id) => (
+                        super::memory::list_attachments(&self.state.db, id)?,
+                        super::memory::attachment_scope(&self.state.db, id)?,
+                    ),
+                    None => (Vec::new(), None),
+                };
+                system_prompt.push_str(&attachments_prompt(&attachments));
+                if self.planned_steps.is_some() {
+                    system_prompt.push_str(plan::PLAN_MODE_PROMPT);
+                }
+                (system_prompt, scope)
+            }
+        };
 
-        // 3. Build system prompt
-        let system_prompt = build_system_prompt(&self.state, messages);
+        // 4. Serialize tools to OpenAI-compatible JSON for the chat template
+        let tools_json = if tool_definitions.is_empty() {
+            None
+        } else {
+            let json_str = serde_json::to_string(&tool_definitions)
+                .map_err(|e| GhostError::Agent(format!("Failed to serialize tools: {}", e)))?;
+            Some(json_str)
+        };
+
+        // Drop the oldest messages when the history outgrows the context window,
+        // counting real tokens for the prompt, tool schemas and reply
+        let reserved = crate::chat::tokens::count_tokens(&system_prompt, &model_id)
+            + tools_json
+                .as_deref()
+                .map_or(0, |json| crate::chat::tokens::count_tokens(json, &model_id))
+            + crate::chat::tokens::reply_reserve(agent_config.max_tokens, context_window);
+        let (history, context) = crate::chat::tokens::fit_history(
+            messages,
+            &model_id,
+            context_window.saturating_sub(reserved),
+        );
+        if context.truncated() {
+            event_bus.emit(AgUiEvent::custom(
+                run_id,
+                "context_truncated",
+                json!({
+                    "dropped_messages": context.dropped_messages,
+                    "prompt_tokens": context.prompt_tokens,
+                    "budget": context.budget,
+                    "context_window": context_window,
+                }),
+            ));
+        }
 
-        // 4. Build initial conversation
+        // 5. Build initial conversation
         let mut conversation: Vec<AgentChatMessage> = Vec::new();
 
         // System message
@@ -135,7 +284,7 @@ impl AgentExecutor {
         });
 
         // User messages
-        for msg in messages {
+        for msg in &history {
             conversation.push(AgentChatMessage {
                 role: msg.role.clone(),
                 content: msg.content.clone(),
@@ -143,50 +292,50 @@ impl AgentExecutor {
             });
         }
 
-        // 5. Serialize tools to OpenAI-compatible JSON for the chat template
-        let tools_json = if tool_definitions.is_empty() {
-            None
-        } else {
-            let json_str = serde_json::to_string(&tool_definitions)
-                .map_err(|e| GhostError::Agent(format!("Failed to serialize tools: {}", e)))?;
-            Some(json_str)
-        };
-
         // 6. ReAct loop
         let mut iterations = 0;
         let mut all_tool_calls: Vec<ExecutedToolCall> = Vec::new();
-        let mut final_content = String::new();
+        let final_content: String;
+        let mut tokens_used = 0;
+        let mut budget_exceeded = None;
 
         loop {
-            iterations += 1;
-
-            if iterations > agent_config.max_iterations {
+            // Enforce the run's budgets before every iteration
+            let usage = RunUsage {
+                iterations,
+                tool_calls: all_tool_calls.len(),
+                tokens: tokens_used,
+                elapsed_ms: start.elapsed().as_millis() as u64,
+            };
+            if let Some(exceeded) = agent_config.check_budget(&usage) {
                 tracing::warn!(
-                    "Agent hit max iterations ({}), forcing response",
-                    agent_config.max_iterations
-                );
-                event_bus.emit(AgUiEvent::custom(
+                    "Agent run {} exceeded its {:?} budget ({}), finishing early",
                     run_id,
-                    "max_iterations_reached",
-                    json!({"iterations": iterations - 1}),
-                ));
-                break;
-            }
-
-            // Guard against runaway tool-calling loops
-            if all_tool_calls.len() >= agent_config.max_tool_calls_per_run {
-                tracing::warn!(
-                    "Agent hit max tool calls ({}/{}), forcing response",
-                    all_tool_calls.len(),
-                    agent_config.max_tool_calls_per_run
+                    exceeded.limit,
+                    exceeded.max
                 );
                 event_bus.emit(AgUiEvent::custom(
                     run_id,
-                    "max_tool_calls_reached",
-                    json!({"tool_calls": all_tool_calls.len()}),
+                    "budget_exceeded",
+                    serde_json::to_value(&exceeded).unwrap_or_default(),
                 ));
+                final_content = self
+                    .best_effort_answer(
+                        &model_id,
+                        &conversation,
+                        &agent_config,
+                        context_window,
+                        &exceeded,
+                        &all_tool_calls,
+                    )
+                    .await;
+                let msg_id = format!("msg-{}-final", &run_id[..8.min(run_id.len())]);
+                self.stream_text(run_id, &msg_id, &final_content, event_bus)
+                    .await;
+                budget_exceeded = Some(exceeded);
                 break;
             }
+            iterations += 1;
 
             // Emit step started
             let step_name = if iterations == 1 {
@@ -213,6 +362,7 @@ impl AgentExecutor {
 
             match response {
                 Ok(resp) => {
+                    tokens_used += generated_tokens(&resp, &model_id);
                     let has_tool_calls = !resp.tool_calls.is_empty();
                     let has_content = !resp.content.trim().is_empty();
 
@@ -252,6 +402,7 @@ impl AgentExecutor {
                                         &tc.function.arguments,
                                         &registered_tools,
                                         &agent_config,
+                                        scope.as_ref(),
                                         event_bus,
                                     )
                                 })
@@ -284,6 +435,7 @@ impl AgentExecutor {
                                             result: error_msg,
                                             duration_ms: 0,
                                             risk_level: RiskLevel::Safe,
+                                            citations: Vec::new(),
                                         });
                                     }
                                 }
@@ -298,6 +450,7 @@ impl AgentExecutor {
                                         &tc.function.arguments,
                                         &registered_tools,
                                         &agent_config,
+                                        scope.as_ref(),
                                         event_bus,
                                     )
                                     .await;
@@ -325,6 +478,7 @@ impl AgentExecutor {
                                             result: error_msg,
                                             duration_ms: 0,
                                             risk_level: RiskLevel::Safe,
+                                            citations: Vec::new(),
                                         });
                                     }
                                 }
@@ -374,12 +528,14 @@ impl AgentExecutor {
         }
 
         let duration = start.elapsed();
+        let citations = collect_citations(&all_tool_calls, &final_content);
 
-        // Save to conversation memory if conversation_id provided
-        if let Some(conv_id) = conversation_id {
+        // Save to conversation memory if conversation_id provided. Plans are
+        // saved by the run that carries them out.
+        if let Some(conv_id) = conversation_id.filter(|_| self.planned_steps.is_none()) {
             // Save user message
             if let Some(last_user) = messages.iter().rev().find(|m| m.role == "user") {
-                let _ = super::memory::add_message(
+                let saved = super::memory::add_message(
                     &self.state.db,
                     conv_id,
                     "user",
@@ -388,6 +544,14 @@ impl AgentExecutor {
                     None,
                     None,
                 );
+                // "Remind me to ..." in chat becomes a reminder
+                if saved.is_ok() {
+                    if let Err(e) =
+                        crate::reminders::scan_message(&self.state.db, conv_id, &last_user.content)
+                    {
+                        tracing::warn!("Failed to scan message for reminders: {}", e);
+                    }
+                }
             }
 
             // Save tool calls as JSON
@@ -398,7 +562,7 @@ impl AgentExecutor {
             };
 
             // Save assistant response
-            let _ = super::memory::add_message(
+            let message_id = super::memory::add_message(
                 &self.state.db,
                 conv_id,
                 "assistant",
@@ -407,6 +571,24 @@ impl AgentExecutor {
                 None,
                 Some(&model_id),
             );
+            if let (Ok(message_id), false) = (message_id, citations.is_empty()) {
+                if let Ok(citations_json) = serde_json::to_string(&citations) {
+                    let _ = super::memory::set_message_citations(
+                        &self.state.db,
+                        message_id,
+                        &citations_json,
+                    );
+                }
+            }
+        }
+
+        // Emit the sources behind the answer
+        if !citations.is_empty() {
+            event_bus.emit(AgUiEvent::custom(
+                run_id,
+                "citations",
+                json!({ "citations": citations }),
+            ));
         }
 
         // Emit generation stats
@@ -421,6 +603,23 @@ impl AgentExecutor {
             }),
         ));
 
+        // Hand the plan over for approval
+        if let (Some(steps), None) = (&self.planned_steps, scoped) {
+            let steps = std::mem::take(&mut *steps.lock().unwrap_or_else(|e| e.into_inner()));
+            let plan = AgentPlan {
+                plan_id: format!("plan-{}", run_id),
+                run_id: run_id.into(),
+                summary: final_content.clone(),
+                steps,
+            };
+            event_bus.emit(AgUiEvent::custom(
+                run_id,
+                "plan_ready",
+                serde_json::to_value(&plan).unwrap_or_default(),
+            ));
+            plan::store(plan, messages, conversation_id);
+        }
+
         // Emit RUN_FINISHED
         event_bus.emit(AgUiEvent::run_finished(run_id));
 
@@ -430,9 +629,51 @@ impl AgentExecutor {
             tool_calls_executed: all_tool_calls,
             duration_ms: duration.as_millis() as u64,
             model: model_id,
+            citations,
+            budget_exceeded,
         })
     }
 
+    /// Answer as well as possible once a budget has run out: one last
+    /// generation without tools, capped at [`FINAL_ANSWER_MAX_TOKENS`]. Past
+    /// the wall-clock limit the model isn't asked again and the user gets a
+    /// note on what was done instead.
+    async fn best_effort_answer(
+        &self,
+        model_id: &str,
+        conversation: &[AgentChatMessage],
+        config: &AgentConfig,
+        context_window: usize,
+        exceeded: &BudgetExceeded,
+        tool_calls: &[ExecutedToolCall],
+    ) -> String {
+        if exceeded.limit != BudgetLimit::WallClock {
+            let mut conversation = conversation.to_vec();
+            conversation.push(AgentChatMessage {
+                role: "user".into(),
+                content: format!(
+                    "You have used up your {}. Don't call any more tools: answer my request \
+                     now with what you have found so far, and say briefly what is left undone.",
+                    budget_description(exceeded.limit)
+                ),
+                tool_calls: None,
+            });
+            let config = AgentConfig {
+                max_tokens: config.max_tokens.min(FINAL_ANSWER_MAX_TOKENS),
+                ..config.clone()
+            };
+            match self
+                .generate_native(model_id, &conversation, None, &config, context_window)
+                .await
+            {
+                Ok(resp) if !resp.content.trim().is_empty() => return resp.content,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Best-effort answer failed: {}", e),
+            }
+        }
+        fallback_answer(exceeded.limit, tool_calls)
+    }
+
     /// Run native llama.cpp inference with grammar-constrained tool calling.
     ///
     /// 1. Loads the model (from HF cache or downloads once)
@@ -503,6 +744,11 @@ impl AgentExecutor {
     async fn ensure_model_downloaded(
         profile: &crate::chat::models::ModelProfile,
     ) -> Result<std::path::PathBuf, GhostError> {
+        // Imported models are used in place
+        if let Some(custom) = crate::chat::models::custom_model(profile.id) {
+            return Ok(std::path::PathBuf::from(custom.path));
+        }
+
         let repo_id = profile.repo_id.to_string();
         let gguf_file = profile.gguf_file.to_string();
 
@@ -525,6 +771,13 @@ impl AgentExecutor {
 
         // Download (first time only)
         tracing::info!("Downloading agent model: {}/{}", repo_id, gguf_file);
+        crate::downloads::manager()
+            .ensure_allowed()
+            .map_err(GhostError::Agent)?;
+        if let Ok(path) = crate::downloads::hf_download(&repo_id, &gguf_file).await {
+            tracing::info!("Agent model ready: {}", path.display());
+            return Ok(path);
+        }
 
         let path = tokio::task::spawn_blocking(move || {
             let api = hf_hub::api::sync::Api::new()
@@ -953,6 +1206,7 @@ impl AgentExecutor {
     }
 
     /// Execute a single tool call with safety checks and AG-UI events.
+    #[allow(clippy::too_many_arguments)]
     async fn execute_tool_call(
         &self,
         run_id: &str,
@@ -960,6 +1214,7 @@ impl AgentExecutor {
         arguments: &serde_json::Value,
         registered_tools: &[RegisteredTool],
         config: &AgentConfig,
+        scope: Option<&HashSet<i64>>,
         event_bus: &AgUiEventBus,
     ) -> Result<ExecutedToolCall, String> {
         let start = Instant::now();
@@ -982,31 +1237,106 @@ impl AgentExecutor {
         let args_str = serde_json::to_string(arguments).unwrap_or_default();
         event_bus.emit(AgUiEvent::tool_call_args(run_id, &tool_call_id, &args_str));
 
-        // Check approval
-        if !auto_approve {
-            // Emit a custom event requesting approval
-            let description = safety::describe_action(tool_name, arguments);
+        // User guardrails: every hit is reported; a deny hit blocks the call
+        // and an ask hit forces a prompt
+        let hits = safety::check_guardrails(
+            tool_name,
+            arguments,
+            &config.guardrails,
+            config.offline_mode,
+        );
+        for hit in &hits {
+            tracing::info!(
+                "Guardrail '{}' hit by '{}' ({:?}): {}",
+                hit.rule,
+                tool_name,
+                hit.action,
+                hit.reason
+            );
             event_bus.emit(AgUiEvent::custom(
                 run_id,
-                "tool_approval_required",
+                "guardrail_hit",
                 json!({
                     "tool_call_id": tool_call_id,
                     "tool_name": tool_name,
-                    "arguments": arguments,
-                    "risk_level": risk,
-                    "description": description,
+                    "rule": hit.rule,
+                    "action": hit.action,
+                    "reason": hit.reason,
                 }),
             ));
+        }
+        let guardrail_denial = hits
+            .iter()
+            .find(|hit| hit.action == GuardrailAction::Deny)
+            .map(|hit| {
+                format!(
+                    "Tool '{}' is blocked by the guardrail '{}': {}. Skipped.",
+                    tool_name, hit.rule, hit.reason
+                )
+            });
+        let guardrail_ask = hits.iter().any(|hit| hit.action == GuardrailAction::Ask);
+
+        // Plan mode: record anything that isn't read-only instead of running
+        // it. Skill tools run, as their sub-agent's calls are recorded too.
+        if let (Some(steps), None) = (&self.planned_steps, &guardrail_denial) {
+            let delegates = tools::find_tool(registered_tools, tool_name)
+                .is_some_and(|tool| tool.source.starts_with("skill:"));
+            if risk != RiskLevel::Safe && !delegates {
+                let description = safety::describe_action(tool_name, arguments);
+                let result = format!(
+                    "Not run (plan mode): {}. Assume it succeeds and continue planning.",
+                    description
+                );
+                steps
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(PlannedStep {
+                        tool_name: tool_name.into(),
+                        arguments: arguments.clone(),
+                        risk_level: risk,
+                        description,
+                    });
+                event_bus.emit(AgUiEvent::tool_call_end(
+                    run_id,
+                    &tool_call_id,
+                    Some(&result),
+                ));
+                return Ok(ExecutedToolCall {
+                    name: tool_name.into(),
+                    arguments: arguments.clone(),
+                    result,
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    risk_level: risk,
+                    citations: Vec::new(),
+                });
+            }
+        }
 
-            // For now, auto-deny dangerous operations that need approval
-            // TODO: Implement bidirectional approval flow via AG-UI
-            let deny_msg = format!(
-                "Tool '{}' requires user approval (risk: {:?}). Action: {}. Skipped for safety.",
-                tool_name,
-                risk,
-                safety::describe_action(tool_name, arguments)
-            );
+        // Check guardrails, per-tool policy, then risk-based approval
+        let permission = self.tool_permission(config, tool_name);
+        let denial = match permission {
+            _ if guardrail_denial.is_some() => guardrail_denial,
+            ToolPermission::Deny => Some(format!(
+                "Tool '{}' is blocked by your tool permissions. Skipped.",
+                tool_name
+            )),
+            ToolPermission::Allow if !guardrail_ask => None,
+            ToolPermission::Ask if auto_approve && !guardrail_ask => None,
+            ToolPermission::Allow | ToolPermission::Ask => {
+                self.request_approval(
+                    run_id,
+                    &tool_call_id,
+                    tool_name,
+                    arguments,
+                    risk,
+                    config,
+                    event_bus,
+                )
+                .await
+            }
+        };
 
+        if let Some(deny_msg) = denial {
             event_bus.emit(AgUiEvent::tool_call_end(
                 run_id,
                 &tool_call_id,
@@ -1019,22 +1349,76 @@ impl AgentExecutor {
                 result: deny_msg,
                 duration_ms: start.elapsed().as_millis() as u64,
                 risk_level: risk,
+                citations: Vec::new(),
             });
         }
 
         // Execute the tool
+        let mut citations = Vec::new();
         let result = if let Some(tool) = tools::find_tool(registered_tools, tool_name) {
-            if tool.source == "builtin" {
+            if tool.source == "builtin" && tool_name == "ghost_spawn_subagent" {
+                self.spawn_subagent(
+                    run_id,
+                    arguments,
+                    registered_tools,
+                    config,
+                    scope,
+                    event_bus,
+                )
+                .await
+            } else if let Some(skill_name) = tool.source.strip_prefix("skill:") {
+                self.run_skill_tool(
+                    run_id,
+                    skill_name,
+                    arguments,
+                    registered_tools,
+                    config,
+                    scope,
+                    event_bus,
+                )
+                .await
+            } else if tool.source == "builtin" && tool_name == "ghost_search" {
+                // Keep the retrieved chunks so the answer can cite them
+                tools::search_with_citations(arguments, &self.state, scope)
+                    .await
+                    .map(|(text, found)| {
+                        citations = found;
+                        text
+                    })
+            } else if tool.source == "builtin" {
                 // Execute built-in tool
                 tools::execute_builtin_tool(tool_name, arguments, &self.state).await
             } else if tool.source.starts_with("mcp:") {
                 // Execute MCP tool
                 let server_name = tool.source.strip_prefix("mcp:").unwrap_or("");
-                self.state
-                    .mcp_client
-                    .call_tool(server_name, tool_name, Some(arguments.clone()))
-                    .await
-                    .map_err(|e| format!("MCP tool error: {}", e))
+                crate::protocols::mcp_trace::call_traced(
+                    &self.state,
+                    Some(run_id),
+                    server_name,
+                    tool_name,
+                    Some(arguments.clone()),
+                )
+                .await
+                .map_err(|e| {
+                    // Throttled calls come back as JSON the model can act on
+                    match e.downcast_ref::<crate::protocols::mcp_limits::CallLimitError>() {
+                        Some(limit) => limit.to_tool_result(),
+                        None => format!("MCP tool error: {}", e),
+                    }
+                })
+            } else if let Some(plugin_id) = tool.source.strip_prefix("plugin:") {
+                // Execute WASM plugin tool (CPU-bound, off the async runtime)
+                let (plugin_id, name, args) = (
+                    plugin_id.to_string(),
+                    tool_name.to_string(),
+                    arguments.clone(),
+                );
+                tokio::task::spawn_blocking(move || {
+                    crate::wasm_plugins::call_tool(&plugin_id, &name, &args)
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string()))
             } else {
                 Err(format!("Unknown tool source: {}", tool.source))
             }
@@ -1043,9 +1427,18 @@ impl AgentExecutor {
         };
 
         let duration_ms = start.elapsed().as_millis() as u64;
+        crate::analytics::track(
+            &self.state.db,
+            crate::analytics::UsageFeature::ToolCall,
+            Some(tool_name),
+        );
 
         match result {
             Ok(result_text) => {
+                // Render any UI the result declares; the model gets the rest
+                let result_text =
+                    crate::protocols::a2ui::emit_tool_ui(run_id, result_text, event_bus);
+
                 // Truncate result if too long (prevent context explosion)
                 let truncated = if result_text.len() > 8000 {
                     format!(
@@ -1076,6 +1469,7 @@ impl AgentExecutor {
                     result: truncated,
                     duration_ms,
                     risk_level: risk,
+                    citations,
                 })
             }
             Err(e) => {
@@ -1092,11 +1486,321 @@ impl AgentExecutor {
                     result: error_msg,
                     duration_ms,
                     risk_level: risk,
+                    citations: Vec::new(),
                 })
             }
         }
     }
 
+    /// Run a skill as its own run, limited to the tools it requires.
+    pub async fn run_skill(
+        &self,
+        run_id: &str,
+        skill: &Skill,
+        args: &HashMap<String, String>,
+        event_bus: &AgUiEventBus,
+    ) -> Result<AgentRunResult, GhostError> {
+        let prompt = skill.render_prompt(args).map_err(GhostError::Agent)?;
+        let mut all_tools = tools::collect_all_tools(&self.state.mcp_client).await;
+        let a2a_agents = self
+            .state
+            .settings
+            .lock()
+            .map(|s| s.a2a_agents.clone())
+            .unwrap_or_default();
+        all_tools.extend(tools::a2a_delegate_tool(&a2a_agents));
+        let skill_tools = tools::subagent_tools(&all_tools, skill_required_tools(skill))
+            .map_err(GhostError::Agent)?;
+
+        let messages = vec![ChatMessage {
+            role: "user".into(),
+            content: prompt,
+        }];
+        let run = RunScope {
+            parent_run_id: None,
+            system_prompt: build_system_prompt(&self.state, &messages),
+            tools: skill_tools,
+            config: self
+                .state
+                .settings
+                .lock()
+                .map(|s| s.agent_config.clone())
+                .unwrap_or_default(),
+            search_scope: None,
+        };
+        tracing::info!("Agent run {} runs skill '{}'", run_id, skill.name);
+        self.run_scoped(run_id, &messages, None, Some(&run), event_bus)
+            .await
+    }
+
+    /// Handle a `ghost_spawn_subagent` call.
+    async fn spawn_subagent(
+        &self,
+        run_id: &str,
+        arguments: &serde_json::Value,
+        registered_tools: &[RegisteredTool],
+        config: &AgentConfig,
+        scope: Option<&HashSet<i64>>,
+        event_bus: &AgUiEventBus,
+    ) -> Result<String, String> {
+        let params: SubagentParams = serde_json::from_value(arguments.clone())
+            .map_err(|e| format!("Invalid sub-agent arguments: {}", e))?;
+        let task = params.task.trim();
+        if task.is_empty() {
+            return Err("The sub-agent task is empty".into());
+        }
+        let sub = SubagentTask {
+            task: task.to_string(),
+            instructions: params.instructions,
+            tools: tools::subagent_tools(registered_tools, params.tools.as_deref())?,
+            max_iterations: params.max_iterations,
+            skill: None,
+        };
+        self.run_subagent(run_id, sub, config, scope, event_bus)
+            .await
+    }
+
+    /// Handle a call to a skill's tool: run the skill as a sub-agent.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_skill_tool(
+        &self,
+        run_id: &str,
+        skill_name: &str,
+        arguments: &serde_json::Value,
+        registered_tools: &[RegisteredTool],
+        config: &AgentConfig,
+        scope: Option<&HashSet<i64>>,
+        event_bus: &AgUiEventBus,
+    ) -> Result<String, String> {
+        let registry = super::skills::loaded();
+        let skill = registry
+            .get(skill_name)
+            .filter(|s| s.enabled)
+            .ok_or_else(|| format!("Skill '{}' is no longer available", skill_name))?;
+        let args = match arguments {
+            serde_json::Value::Object(map) => super::skills::string_args(map.clone()),
+            _ => HashMap::new(),
+        };
+        let sub = SubagentTask {
+            task: skill.render_prompt(&args)?,
+            instructions: None,
+            tools: tools::subagent_tools(registered_tools, skill_required_tools(skill))?,
+            max_iterations: None,
+            skill: Some(skill.name.clone()),
+        };
+        self.run_subagent(run_id, sub, config, scope, event_bus)
+            .await
+    }
+
+    /// Run a sub-agent under `run_id` and return its final answer.
+    ///
+    /// The child is a separate run whose RUN_STARTED names this one as its
+    /// parent; it gets the given tools and a budget from
+    /// [`AgentConfig::subagent_config`], and isn't saved to memory.
+    /// `subagent_started` and `subagent_finished` are emitted on the parent.
+    /// Boxed because the child run executes tool calls in turn.
+    fn run_subagent<'a>(
+        &'a self,
+        run_id: &'a str,
+        sub: SubagentTask,
+        config: &'a AgentConfig,
+        scope: Option<&'a HashSet<i64>>,
+        event_bus: &'a AgUiEventBus,
+    ) -> Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>> {
+        Box::pin(async move {
+            let tool_names: Vec<String> = sub
+                .tools
+                .iter()
+                .map(|t| t.definition.function.name.clone())
+                .collect();
+            let child_run_id = format!(
+                "{}-sub{}",
+                run_id,
+                NEXT_SUBAGENT.fetch_add(1, Ordering::Relaxed)
+            );
+            let messages = vec![ChatMessage {
+                role: "user".into(),
+                content: sub.task.clone(),
+            }];
+            let mut system_prompt = build_system_prompt(&self.state, &messages);
+            system_prompt.push_str(&subagent_prompt(sub.instructions.as_deref()));
+            let child_config = config.subagent_config(sub.max_iterations);
+
+            event_bus.emit(AgUiEvent::custom(
+                run_id,
+                "subagent_started",
+                json!({
+                    "child_run_id": child_run_id,
+                    "task": sub.task,
+                    "skill": sub.skill,
+                    "tools": tool_names,
+                    "max_iterations": child_config.max_iterations,
+                }),
+            ));
+            tracing::info!(
+                "Agent run {} spawned sub-agent {} ({} tools)",
+                run_id,
+                child_run_id,
+                tool_names.len()
+            );
+
+            let child = RunScope {
+                parent_run_id: Some(run_id.to_string()),
+                system_prompt,
+                tools: sub.tools,
+                config: child_config,
+                search_scope: scope.cloned(),
+            };
+            let outcome = self
+                .run_scoped(&child_run_id, &messages, None, Some(&child), event_bus)
+                .await;
+
+            match outcome {
+                Ok(result) => {
+                    event_bus.emit(AgUiEvent::custom(
+                        run_id,
+                        "subagent_finished",
+                        json!({
+                            "child_run_id": child_run_id,
+                            "iterations": result.iterations,
+                            "tool_calls": result.tool_calls_executed.len(),
+                            "duration_ms": result.duration_ms,
+                            "budget_exceeded": result.budget_exceeded,
+                        }),
+                    ));
+                    Ok(result.content)
+                }
+                Err(e) => {
+                    event_bus.emit(AgUiEvent::custom(
+                        run_id,
+                        "subagent_finished",
+                        json!({
+                            "child_run_id": child_run_id,
+                            "error": e.to_string(),
+                        }),
+                    ));
+                    Err(format!("Sub-agent failed: {}", e))
+                }
+            }
+        })
+    }
+
+    /// Resolve the permission policy for a tool.
+    ///
+    /// An explicit policy in the run's config wins; otherwise the live settings
+    /// are consulted so "always allow" answers given mid-run apply immediately.
+    fn tool_permission(&self, config: &AgentConfig, tool_name: &str) -> ToolPermission {
+        match config.tool_permission(tool_name) {
+            ToolPermission::Ask => self
+                .state
+                .settings
+                .lock()
+                .map(|s| s.agent_config.tool_permission(tool_name))
+                .unwrap_or(ToolPermission::Ask),
+            explicit => explicit,
+        }
+    }
+
+    /// Pause the run until the user answers an approval prompt.
+    ///
+    /// Returns `None` if the tool may run, or the denial message otherwise.
+    #[allow(clippy::too_many_arguments)]
+    async fn request_approval(
+        &self,
+        run_id: &str,
+        tool_call_id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        risk: RiskLevel,
+        config: &AgentConfig,
+        event_bus: &AgUiEventBus,
+    ) -> Option<String> {
+        let description = safety::describe_action(tool_name, arguments);
+        let denied = |reason: &str| {
+            format!(
+                "Tool '{}' requires user approval (risk: {:?}). Action: {}. {}",
+                tool_name, risk, description, reason
+            )
+        };
+
+        // Nobody is listening for the prompt — deny instead of hanging the run.
+        if event_bus.subscriber_count() == 0 {
+            return Some(denied(
+                "No client available to approve. Skipped for safety.",
+            ));
+        }
+
+        let (approval_id, rx) = self.state.tool_approvals.register();
+        event_bus.emit(AgUiEvent::custom(
+            run_id,
+            "tool_approval_required",
+            json!({
+                "approval_id": approval_id,
+                "tool_call_id": tool_call_id,
+                "tool_name": tool_name,
+                "arguments": arguments,
+                "risk_level": risk,
+                "description": description,
+                "timeout_ms": config.approval_timeout_ms,
+            }),
+        ));
+        tracing::info!(
+            "Waiting for approval of '{}' ({}, risk={:?})",
+            tool_name,
+            approval_id,
+            risk
+        );
+
+        let timeout = std::time::Duration::from_millis(config.approval_timeout_ms);
+        let decision = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(decision)) => Some(decision),
+            Ok(Err(_)) => None,
+            Err(_) => {
+                self.state.tool_approvals.cancel(&approval_id);
+                None
+            }
+        };
+
+        event_bus.emit(AgUiEvent::custom(
+            run_id,
+            "tool_approval_resolved",
+            json!({
+                "approval_id": approval_id,
+                "tool_call_id": tool_call_id,
+                "tool_name": tool_name,
+                "decision": decision,
+            }),
+        ));
+
+        match decision {
+            Some(ApprovalDecision::AlwaysAllow) => {
+                self.persist_always_allow(tool_name);
+                None
+            }
+            Some(ApprovalDecision::Approve) => None,
+            Some(ApprovalDecision::Deny) => Some(denied("Denied by user.")),
+            None => Some(denied("No response received. Skipped for safety.")),
+        }
+    }
+
+    /// Persist an "always allow" decision for a tool in the agent config.
+    fn persist_always_allow(&self, tool_name: &str) {
+        let Ok(mut settings) = self.state.settings.lock() else {
+            return;
+        };
+        settings
+            .agent_config
+            .tool_permissions
+            .insert(tool_name.to_string(), ToolPermission::Allow);
+        if let Err(e) = settings.save(&crate::get_settings_path()) {
+            tracing::warn!(
+                "Failed to persist tool permission for '{}': {}",
+                tool_name,
+                e
+            );
+        }
+    }
+
     /// Stream text content via AG-UI events (word-chunking simulation).
     async fn stream_text(
         &self,
@@ -1129,6 +1833,110 @@ impl AgentExecutor {
     }
 }
 
+/// Token cap of the answer generated after a budget runs out.
+const FINAL_ANSWER_MAX_TOKENS: usize = 512;
+
+/// Tokens a generation produced, counting its tool calls.
+fn generated_tokens(resp: &LlmResponse, model_id: &str) -> usize {
+    let tool_calls = if resp.tool_calls.is_empty() {
+        0
+    } else {
+        let json = serde_json::to_string(&resp.tool_calls).unwrap_or_default();
+        crate::chat::tokens::count_tokens(&json, model_id)
+    };
+    crate::chat::tokens::count_tokens(&resp.content, model_id) + tool_calls
+}
+
+fn budget_description(limit: BudgetLimit) -> &'static str {
+    match limit {
+        BudgetLimit::Iterations => "reasoning steps",
+        BudgetLimit::ToolCalls => "tool call budget",
+        BudgetLimit::Tokens => "token budget",
+        BudgetLimit::WallClock => "time limit",
+    }
+}
+
+/// What the user gets when the model can't give a final answer.
+fn fallback_answer(limit: BudgetLimit, tool_calls: &[ExecutedToolCall]) -> String {
+    let mut answer = format!(
+        "I stopped before finishing because this run used up its {}.",
+        budget_description(limit)
+    );
+    if !tool_calls.is_empty() {
+        let mut names: Vec<&str> = Vec::new();
+        for call in tool_calls {
+            if !names.contains(&call.name.as_str()) {
+                names.push(&call.name);
+            }
+        }
+        answer.push_str(&format!(" Tools used so far: {}.", names.join(", ")));
+    }
+    answer.push_str(" Try a narrower request, or raise the agent's limits in settings.");
+    answer
+}
+
+/// Gather the chunks retrieved by `ghost_search` during a run into numbered citations.
+///
+/// Chunks returned by several searches are listed once, in first-seen order.
+/// A citation is marked `referenced` when the answer mentions its file.
+fn collect_citations(tool_calls: &[ExecutedToolCall], answer: &str) -> Vec<Citation> {
+    let answer = answer.to_lowercase();
+    let mut citations: Vec<Citation> = Vec::new();
+    for citation in tool_calls.iter().flat_map(|tc| &tc.citations) {
+        if citations.iter().any(|c| c.chunk_id == citation.chunk_id) {
+            continue;
+        }
+        let mut citation = citation.clone();
+        citation.index = citations.len() + 1;
+        citation.referenced =
+            !citation.filename.is_empty() && answer.contains(&citation.filename.to_lowercase());
+        citations.push(citation);
+    }
+    citations
+}
+
+/// The tools a skill's run is limited to; `None` (all) when it names none.
+fn skill_required_tools(skill: &Skill) -> Option<&[String]> {
+    Some(skill.required_tools.as_slice()).filter(|t| !t.is_empty())
+}
+
+/// Prompt section telling a sub-agent its role, with the parent's
+/// instructions if it gave any.
+fn subagent_prompt(instructions: Option<&str>) -> String {
+    let mut prompt = String::from(
+        "<subagent>\n\
+         You are a sub-agent: another Ghost agent handed you the task in the user message. \
+         Work only on that task and finish with a self-contained answer; it is passed back \
+         to that agent, not shown to the user.\n",
+    );
+    if let Some(instructions) = instructions.map(str::trim).filter(|i| !i.is_empty()) {
+        prompt.push_str(instructions);
+        prompt.push('\n');
+    }
+    prompt.push_str("</subagent>\n\n");
+    prompt
+}
+
+/// Prompt section naming the documents and folders attached to the
+/// conversation; empty when there are none.
+fn attachments_prompt(attachments: &[super::memory::Attachment]) -> String {
+    if attachments.is_empty() {
+        return String::new();
+    }
+    let list: Vec<String> = attachments
+        .iter()
+        .map(|a| format!("- {} ({})", a.path, a.kind))
+        .collect();
+    format!(
+        "<attachments>\n\
+         This conversation is about these attached sources; ghost_search only \
+         returns documents from them:\n{}\n\
+         Answer from them, and say so when they don't cover the question.\n\
+         </attachments>\n\n",
+        list.join("\n")
+    )
+}
+
 /// Build the agent system prompt with context about available tools and skills.
 ///
 /// Uses XML-tagged sections for clear structure (per Anthropic best practices 2026).
@@ -1173,8 +1981,12 @@ pub(crate) fn build_system_prompt(state: &AppState, messages: &[ChatMessage]) ->
          - NEVER fabricate file paths, file contents, or directory listings. Always verify with tools.\n\
          - NEVER guess what a file contains. Use ghost_read_file to check.\n\
          - If you don't know something and can't find it with tools, say so honestly.\n\
+         - When ghost_search reports low retrieval confidence, tell the user the answer isn't in their files \
+         instead of guessing, and offer a web search if a web search tool is available.\n\
          - Do not invent tool names that don't exist. Only use the tools provided.\n\
          - When reporting search results, quote actual snippets from the results.\n\
+         - For \"list all / which files\" questions, use ghost_collect_documents and present its tables; \
+         never list files from memory.\n\
          - Do not make assumptions about the user's system beyond what tools reveal.\n\
          </constraints>\n\n",
     );
@@ -1190,17 +2002,37 @@ pub(crate) fn build_system_prompt(state: &AppState, messages: &[ChatMessage]) ->
         ));
     }
 
-    // Skills context
-    let skills_dir = state
-        .settings
-        .lock()
-        .map(|s| s.agent_config.skills_dir.clone())
-        .unwrap_or_default();
+    // Response language — detected from the latest user message
+    let language = messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .and_then(|m| super::language::detect(&m.content));
+    if let Some(language) = language {
+        prompt.push_str(&format!(
+            "<language>\n\
+             The user is writing in {0}. Answer in {0}, even when the documents you find are in \
+             another language; use ghost_translate for passages you quote from them.\n\
+             </language>\n\n",
+            language.name
+        ));
+    }
 
-    if !skills_dir.is_empty() {
-        let mut registry = super::skills::SkillRegistry::new();
-        registry.load_from_directory(std::path::Path::new(&skills_dir));
+    // Focus context — the user scoped this session to some sources
+    if let Some(focus) = crate::search::focus::active() {
+        prompt.push_str(&format!(
+            "<focus>\n\
+             The user has focused this session on {}. Searches only return these \
+             documents; if the answer is not among them, say so rather than looking \
+             elsewhere.\n\
+             </focus>\n\n",
+            focus.describe()
+        ));
+    }
 
+    // Skills context
+    let registry = super::skills::loaded();
+    if registry.count() > 0 {
         let last_user_msg = messages
             .iter()
             .rev()
@@ -1227,28 +2059,7 @@ mod tests {
 
     // --- Helper: build a minimal AppState for testing ---
     fn test_app_state() -> Arc<AppState> {
-        crate::ensure_tls_provider();
-        let db = crate::db::Database::open_in_memory().unwrap();
-        let hardware = crate::embeddings::hardware::HardwareInfo {
-            cpu_cores: 4,
-            has_avx2: false,
-            has_neon: false,
-            gpu_backend: None,
-            total_ram_mb: 8192,
-            available_ram_mb: 4096,
-        };
-        let chat_engine = crate::chat::ChatEngine::new(hardware.clone(), "qwen2.5-0.5b".into());
-        let settings = crate::settings::Settings::default();
-
-        Arc::new(AppState {
-            db,
-            embedding_engine: crate::embeddings::EmbeddingEngine::none(),
-            chat_engine,
-            hardware,
-            settings: std::sync::Mutex::new(settings),
-            mcp_client: crate::protocols::mcp_client::McpClientManager::new(),
-            agui_event_bus: crate::protocols::agui::AgUiEventBus::new(32),
-        })
+        crate::test_app_state()
     }
 
     // ==========================================
@@ -1332,6 +2143,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_system_prompt_matches_user_language() {
+        let state = test_app_state();
+        let messages = vec![ChatMessage {
+            role: "user".into(),
+            content: "¿Dónde están las facturas de mi coche?".into(),
+        }];
+        let prompt = build_system_prompt(&state, &messages);
+        assert!(
+            prompt.contains("The user is writing in Spanish"),
+            "Should ask for an answer in the user's language"
+        );
+
+        let messages = vec![ChatMessage {
+            role: "user".into(),
+            content: "test".into(),
+        }];
+        let prompt = build_system_prompt(&state, &messages);
+        assert!(
+            !prompt.contains("<language>"),
+            "Should not guess a language from one word"
+        );
+    }
+
     #[test]
     fn test_system_prompt_has_xml_structure() {
         let state = test_app_state();
@@ -1620,6 +2455,7 @@ mod tests {
                 &json!({}),
                 &registered_tools,
                 &config,
+                None,
                 event_bus,
             )
             .await;
@@ -1633,6 +2469,50 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_plan_mode_records_writes() {
+        let state = test_app_state();
+        let event_bus = &state.agui_event_bus;
+        let executor = AgentExecutor::planner(state.clone());
+        let config = AgentConfig::default();
+        let registered_tools = crate::agent::tools::builtin_tools();
+        let path = std::env::temp_dir().join(format!("ghost_test_plan_{}.txt", std::process::id()));
+
+        let executed = executor
+            .execute_tool_call(
+                "run-test",
+                "ghost_write_file",
+                &json!({ "path": path.to_string_lossy(), "content": "hi" }),
+                &registered_tools,
+                &config,
+                None,
+                event_bus,
+            )
+            .await
+            .unwrap();
+        assert!(executed.result.starts_with("Not run (plan mode)"));
+        assert!(!path.exists());
+
+        // Read-only tools still run
+        let executed = executor
+            .execute_tool_call(
+                "run-test",
+                "ghost_index_status",
+                &json!({}),
+                &registered_tools,
+                &config,
+                None,
+                event_bus,
+            )
+            .await
+            .unwrap();
+        assert!(executed.result.contains("Indexed"));
+
+        let steps = executor.planned_steps.as_ref().unwrap().lock().unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].tool_name, "ghost_write_file");
+    }
+
     #[tokio::test]
     async fn test_execute_tool_call_index_status() {
         let state = test_app_state();
@@ -1649,6 +2529,7 @@ mod tests {
                 &json!({}),
                 &registered_tools,
                 &config,
+                None,
                 event_bus,
             )
             .await;
@@ -1682,6 +2563,7 @@ mod tests {
                 &json!({"command": "ls"}),
                 &registered_tools,
                 &config,
+                None,
                 event_bus,
             )
             .await;
@@ -1696,6 +2578,121 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_execute_tool_call_policy_deny() {
+        let state = test_app_state();
+        let event_bus = &state.agui_event_bus;
+        let executor = AgentExecutor::new(state.clone());
+
+        let mut config = AgentConfig::default();
+        config
+            .tool_permissions
+            .insert("ghost_index_status".into(), ToolPermission::Deny);
+        let registered_tools = crate::agent::tools::builtin_tools();
+
+        let executed = executor
+            .execute_tool_call(
+                "run-test",
+                "ghost_index_status",
+                &json!({}),
+                &registered_tools,
+                &config,
+                None,
+                event_bus,
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            executed.result.contains("blocked by your tool permissions"),
+            "Denied tool should not run: {}",
+            executed.result
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_call_user_denies() {
+        let state = test_app_state();
+        let executor = AgentExecutor::new(state.clone());
+        let mut rx = state.agui_event_bus.subscribe();
+
+        // Simulated frontend: answer the first approval prompt with "deny".
+        let responder_state = state.clone();
+        let responder = tokio::spawn(async move {
+            while let Ok(event) = rx.recv().await {
+                if let crate::protocols::agui::EventPayload::CustomEvent { name, value } =
+                    event.payload
+                {
+                    if name == "tool_approval_required" {
+                        let id = value["approval_id"].as_str().unwrap().to_string();
+                        assert!(responder_state
+                            .tool_approvals
+                            .respond(&id, ApprovalDecision::Deny));
+                        return true;
+                    }
+                }
+            }
+            false
+        });
+
+        let registered_tools = crate::agent::tools::builtin_tools();
+        let executed = executor
+            .execute_tool_call(
+                "run-test",
+                "ghost_run_command",
+                &json!({"command": "ls"}),
+                &registered_tools,
+                &AgentConfig::default(),
+                None,
+                &state.agui_event_bus,
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            responder.await.unwrap(),
+            "Approval prompt should be emitted"
+        );
+        assert!(
+            executed.result.contains("Denied by user"),
+            "User denial should skip the tool: {}",
+            executed.result
+        );
+        assert!(state.tool_approvals.pending_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_call_approval_timeout() {
+        let state = test_app_state();
+        let executor = AgentExecutor::new(state.clone());
+        let _rx = state.agui_event_bus.subscribe();
+
+        let config = AgentConfig {
+            approval_timeout_ms: 50,
+            ..Default::default()
+        };
+        let registered_tools = crate::agent::tools::builtin_tools();
+        let executed = executor
+            .execute_tool_call(
+                "run-test",
+                "ghost_run_command",
+                &json!({"command": "ls"}),
+                &registered_tools,
+                &config,
+                None,
+                &state.agui_event_bus,
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            executed.result.contains("No response received"),
+            "Unanswered approval should deny: {}",
+            executed.result
+        );
+        assert!(state.tool_approvals.pending_ids().is_empty());
+    }
+
     #[tokio::test]
     async fn test_execute_tool_call_search_empty_db() {
         let state = test_app_state();
@@ -1712,6 +2709,7 @@ mod tests {
                 &json!({"query": "test document"}),
                 &registered_tools,
                 &config,
+                None,
                 event_bus,
             )
             .await;
@@ -1744,6 +2742,7 @@ mod tests {
                 &json!({}),
                 &registered_tools,
                 &config,
+                None,
                 event_bus,
             )
             .await
@@ -1867,6 +2866,7 @@ mod tests {
             result: "Found 3 results".into(),
             duration_ms: 50,
             risk_level: RiskLevel::Safe,
+            citations: Vec::new(),
         }])
         .unwrap();
 
@@ -1946,6 +2946,69 @@ mod tests {
         assert!(!json_str.contains("tool_calls"));
     }
 
+    #[test]
+    fn test_subagent_prompt() {
+        let prompt = subagent_prompt(None);
+        assert!(prompt.starts_with("<subagent>"));
+        assert!(prompt.ends_with("</subagent>\n\n"));
+        assert!(subagent_prompt(Some("Answer in one table.")).contains("Answer in one table."));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_subagent_rejects_bad_arguments() {
+        let state = test_app_state();
+        let event_bus = &state.agui_event_bus;
+        let executor = AgentExecutor::new(state.clone());
+        let config = AgentConfig::default();
+        let registered_tools = crate::agent::tools::builtin_tools();
+
+        for (arguments, expected) in [
+            (json!({ "task": "  " }), "task is empty"),
+            (
+                json!({ "task": "x", "tools": ["nonexistent"] }),
+                "not available",
+            ),
+            (json!({}), "Invalid sub-agent arguments"),
+        ] {
+            let executed = executor
+                .execute_tool_call(
+                    "run-test",
+                    "ghost_spawn_subagent",
+                    &arguments,
+                    &registered_tools,
+                    &config,
+                    None,
+                    event_bus,
+                )
+                .await
+                .unwrap();
+            assert!(executed.result.contains(expected), "{}", executed.result);
+        }
+    }
+
+    #[test]
+    fn test_fallback_answer() {
+        let call = |name: &str| ExecutedToolCall {
+            name: name.into(),
+            arguments: json!({}),
+            result: String::new(),
+            duration_ms: 0,
+            risk_level: RiskLevel::Safe,
+            citations: Vec::new(),
+        };
+        let answer = fallback_answer(
+            BudgetLimit::WallClock,
+            &[
+                call("ghost_search"),
+                call("ghost_read_file"),
+                call("ghost_search"),
+            ],
+        );
+        assert!(answer.contains("time limit"));
+        assert!(answer.contains("Tools used so far: ghost_search, ghost_read_file."));
+        assert!(!fallback_answer(BudgetLimit::Tokens, &[]).contains("Tools used"));
+    }
+
     #[test]
     fn test_agent_run_result_serialization() {
         let result = AgentRunResult {
@@ -1957,9 +3020,12 @@ mod tests {
                 result: "Found 5 results".into(),
                 duration_ms: 100,
                 risk_level: RiskLevel::Safe,
+                citations: Vec::new(),
             }],
             duration_ms: 1500,
             model: "qwen2.5-3b".into(),
+            citations: Vec::new(),
+            budget_exceeded: None,
         };
 
         let json_str = serde_json::to_string(&result).unwrap();
@@ -1980,6 +3046,7 @@ mod tests {
             result: "file1\nfile2".into(),
             duration_ms: 50,
             risk_level: RiskLevel::Dangerous,
+            citations: Vec::new(),
         };
 
         let json_str = serde_json::to_string(&tc).unwrap();
@@ -1989,4 +3056,61 @@ mod tests {
             json_str
         );
     }
+
+    fn citation(chunk_id: i64, filename: &str) -> Citation {
+        Citation {
+            index: 0,
+            chunk_id,
+            document_id: 1,
+            path: format!("/home/user/docs/{}", filename),
+            filename: filename.into(),
+            snippet: "snippet".into(),
+            referenced: false,
+        }
+    }
+
+    #[test]
+    fn test_collect_citations() {
+        let search = |citations| ExecutedToolCall {
+            name: "ghost_search".into(),
+            arguments: json!({"query": "lease"}),
+            result: String::new(),
+            duration_ms: 10,
+            risk_level: RiskLevel::Safe,
+            citations,
+        };
+        let calls = vec![
+            search(vec![citation(7, "lease.pdf"), citation(3, "budget.xlsx")]),
+            search(vec![citation(3, "budget.xlsx"), citation(9, "notes.md")]),
+        ];
+
+        let citations = collect_citations(&calls, "According to Lease.pdf, pets are allowed.");
+        let ids: Vec<i64> = citations.iter().map(|c| c.chunk_id).collect();
+        assert_eq!(ids, vec![7, 3, 9]);
+        assert_eq!(
+            citations.iter().map(|c| c.index).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(citations[0].referenced);
+        assert!(!citations[1].referenced);
+
+        assert!(collect_citations(&[], "answer").is_empty());
+    }
+
+    #[test]
+    fn test_executed_tool_call_omits_empty_citations() {
+        let mut tc = ExecutedToolCall {
+            name: "ghost_read_file".into(),
+            arguments: json!({"path": "/tmp/a"}),
+            result: "a".into(),
+            duration_ms: 1,
+            risk_level: RiskLevel::Safe,
+            citations: Vec::new(),
+        };
+        assert!(!serde_json::to_string(&tc).unwrap().contains("citations"));
+
+        tc.citations.push(citation(1, "a.md"));
+        let value = serde_json::to_value(&tc).unwrap();
+        assert_eq!(value["citations"][0]["chunk_id"], 1);
+    }
 }
@@ -0,0 +1,623 @@
+//! Knowledge graph over the vault.
+//!
+//! The local model reads indexed chunks and lists the entities they mention
+//! (people, organizations, projects, places, ...) and how those relate
+//! ("Alice — manages → Apollo"). The result is a lightweight graph in the
+//! vault: entities, edges, and the chunks each came from, so every fact can
+//! be traced back to a file. Extraction runs as a background job, resumes
+//! where it stopped, and re-reads chunks that changed since (re-indexing
+//! replaces a file's chunks, which drops their graph facts).
+//!
+//! [`query`] finds entities by name and walks their neighbourhood; it backs
+//! the `query_knowledge_graph` command and the `ghost_query_knowledge_graph`
+//! agent tool.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::ChatMessage;
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+use crate::AppState;
+
+/// Token budget for one chunk's extraction.
+const EXTRACT_MAX_TOKENS: usize = 512;
+
+/// Chunk characters sent to the model.
+const MAX_CHUNK_CHARS: usize = 4000;
+
+/// Entities kept per chunk; small models sometimes list every noun.
+const MAX_ENTITIES_PER_CHUNK: usize = 20;
+
+/// Longest entity name accepted.
+const MAX_NAME_CHARS: usize = 80;
+
+/// Chunks fetched per extraction batch.
+const BATCH_SIZE: usize = 50;
+
+/// Deepest neighbourhood walked by [`query`].
+pub const MAX_DEPTH: usize = 2;
+
+/// Most edges returned by [`query`].
+const MAX_EDGES: usize = 200;
+
+const EXTRACT_PROMPT: &str = "You extract a knowledge graph from a passage. List the \
+specific named entities it mentions (people, organizations, projects, products, places, \
+events, documents) and the relations stated between them. Reply with JSON only, in this \
+shape:\n\
+{\"entities\": [{\"name\": \"Alice Chen\", \"type\": \"person\"}], \
+\"relations\": [{\"source\": \"Alice Chen\", \"relation\": \"manages\", \"target\": \"Apollo\"}]}\n\
+Use names exactly as written. Relations are short verbs or verb phrases. Leave out \
+generic nouns, dates, and amounts. If there is nothing to extract, reply with empty lists.";
+
+/// JSON schema of an [`Extraction`]; replies are constrained to it.
+fn extraction_schema() -> serde_json::Value {
+    let string = serde_json::json!({ "type": "string" });
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "entities": {
+                "type": "array",
+                "maxItems": MAX_ENTITIES_PER_CHUNK,
+                "items": {
+                    "type": "object",
+                    "properties": { "name": string, "type": string },
+                    "required": ["name", "type"],
+                },
+            },
+            "relations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": { "source": string, "relation": string, "target": string },
+                    "required": ["source", "relation", "target"],
+                },
+            },
+        },
+        "required": ["entities", "relations"],
+    })
+}
+
+/// Initialize the knowledge graph tables in the database.
+pub fn initialize_knowledge_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS kg_entities (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                -- Lowercased, whitespace-collapsed name used for matching
+                key TEXT NOT NULL UNIQUE,
+                kind TEXT NOT NULL DEFAULT 'thing'
+            );
+
+            CREATE TABLE IF NOT EXISTS kg_mentions (
+                entity_id INTEGER NOT NULL REFERENCES kg_entities(id) ON DELETE CASCADE,
+                chunk_id INTEGER NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+                PRIMARY KEY (entity_id, chunk_id)
+            ) WITHOUT ROWID;
+
+            CREATE TABLE IF NOT EXISTS kg_edges (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_id INTEGER NOT NULL REFERENCES kg_entities(id) ON DELETE CASCADE,
+                target_id INTEGER NOT NULL REFERENCES kg_entities(id) ON DELETE CASCADE,
+                relation TEXT NOT NULL,
+                chunk_id INTEGER NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+                UNIQUE(source_id, target_id, relation, chunk_id)
+            );
+
+            -- Chunks already read, including those with nothing to extract
+            CREATE TABLE IF NOT EXISTS kg_processed_chunks (
+                chunk_id INTEGER PRIMARY KEY REFERENCES chunks(id) ON DELETE CASCADE,
+                processed_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_kg_mentions_chunk ON kg_mentions(chunk_id);
+            CREATE INDEX IF NOT EXISTS idx_kg_edges_source ON kg_edges(source_id);
+            CREATE INDEX IF NOT EXISTS idx_kg_edges_target ON kg_edges(target_id);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// An entity as listed by the model.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExtractedEntity {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: String,
+}
+
+/// A relation as listed by the model.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExtractedRelation {
+    pub source: String,
+    pub relation: String,
+    pub target: String,
+}
+
+/// What the model found in one chunk.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Extraction {
+    #[serde(default)]
+    pub entities: Vec<ExtractedEntity>,
+    #[serde(default)]
+    pub relations: Vec<ExtractedRelation>,
+}
+
+/// Matching key for an entity name.
+fn entity_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Entity name with whitespace collapsed and stray quotes removed.
+fn clean_name(name: &str) -> Option<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = name.trim_matches(|c: char| c == '"' || c == '\'' || c == '.');
+    (!name.is_empty() && name.chars().count() <= MAX_NAME_CHARS).then(|| name.to_string())
+}
+
+/// Parse the model's reply: the outermost JSON object, tolerating code
+/// fences and chatter around it. Names are cleaned, duplicates dropped, and
+/// relation endpoints added as entities when the model forgot to list them.
+fn parse_extraction(text: &str) -> Option<Extraction> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    let raw: Extraction = serde_json::from_str(text.get(start..=end)?).ok()?;
+
+    let mut entities: Vec<ExtractedEntity> = Vec::new();
+    let mut add = |name: &str, kind: &str| {
+        let name = clean_name(name)?;
+        let key = entity_key(&name);
+        if !entities.iter().any(|e| entity_key(&e.name) == key) {
+            if entities.len() >= MAX_ENTITIES_PER_CHUNK {
+                return None;
+            }
+            let kind = kind.trim().to_lowercase();
+            entities.push(ExtractedEntity {
+                name: name.clone(),
+                kind: if kind.is_empty() {
+                    "thing".into()
+                } else {
+                    kind
+                },
+            });
+        }
+        Some(name)
+    };
+    for entity in &raw.entities {
+        add(&entity.name, &entity.kind);
+    }
+    let mut relations = Vec::new();
+    for relation in &raw.relations {
+        let verb = relation.relation.trim().to_lowercase();
+        if verb.is_empty() || verb.chars().count() > MAX_NAME_CHARS {
+            continue;
+        }
+        let (Some(source), Some(target)) = (add(&relation.source, ""), add(&relation.target, ""))
+        else {
+            continue;
+        };
+        if entity_key(&source) == entity_key(&target) {
+            continue;
+        }
+        let relation = ExtractedRelation {
+            source,
+            relation: verb,
+            target,
+        };
+        if !relations.contains(&relation) {
+            relations.push(relation);
+        }
+    }
+    Some(Extraction {
+        entities,
+        relations,
+    })
+}
+
+/// Store a chunk's extraction and mark the chunk as processed.
+pub fn store_extraction(db: &Database, chunk_id: i64, extraction: &Extraction) -> Result<()> {
+    db.with_transaction(|conn| {
+        let mut ids: HashMap<String, i64> = HashMap::new();
+        for entity in &extraction.entities {
+            let key = entity_key(&entity.name);
+            let id: i64 = conn.query_row(
+                "INSERT INTO kg_entities (name, key, kind) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET
+                     kind = CASE WHEN kind = 'thing' THEN excluded.kind ELSE kind END
+                 RETURNING id",
+                rusqlite::params![entity.name, key, entity.kind],
+                |row| row.get(0),
+            )?;
+            conn.execute(
+                "INSERT OR IGNORE INTO kg_mentions (entity_id, chunk_id) VALUES (?1, ?2)",
+                rusqlite::params![id, chunk_id],
+            )?;
+            ids.insert(key, id);
+        }
+        for relation in &extraction.relations {
+            let (Some(source), Some(target)) = (
+                ids.get(&entity_key(&relation.source)),
+                ids.get(&entity_key(&relation.target)),
+            ) else {
+                continue;
+            };
+            conn.execute(
+                "INSERT OR IGNORE INTO kg_edges (source_id, target_id, relation, chunk_id)
+                 VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![source, target, relation.relation, chunk_id],
+            )?;
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO kg_processed_chunks (chunk_id) VALUES (?1)",
+            rusqlite::params![chunk_id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Chunks not read yet, oldest first.
+fn pending_chunks(db: &Database, limit: usize) -> Result<Vec<(i64, String)>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.content FROM chunks c
+             WHERE NOT EXISTS (SELECT 1 FROM kg_processed_chunks p WHERE p.chunk_id = c.id)
+             ORDER BY c.id LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// Drop entities no longer mentioned by any chunk.
+fn prune_orphans(db: &Database) -> Result<usize> {
+    db.with_conn(|conn| {
+        Ok(conn.execute(
+            "DELETE FROM kg_entities
+             WHERE NOT EXISTS (SELECT 1 FROM kg_mentions m WHERE m.entity_id = kg_entities.id)",
+            [],
+        )?)
+    })
+}
+
+/// Ask the local model for the entities and relations in one chunk.
+async fn extract_chunk(state: &AppState, content: &str) -> Result<Extraction> {
+    let content: String = content.chars().take(MAX_CHUNK_CHARS).collect();
+    let prompt = [
+        ChatMessage {
+            role: "system".into(),
+            content: EXTRACT_PROMPT.into(),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: format!("<passage>\n{}\n</passage>", content),
+        },
+    ];
+    let response = state
+        .chat_engine
+        .chat_constrained(&prompt, &extraction_schema(), EXTRACT_MAX_TOKENS)
+        .await?;
+    // Unparseable replies count as "nothing found" so the chunk isn't retried forever
+    Ok(parse_extraction(&response.content).unwrap_or_default())
+}
+
+/// Size of the graph and how much of the vault has been read.
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeStats {
+    pub entity_count: i64,
+    pub edge_count: i64,
+    pub processed_chunks: i64,
+    pub pending_chunks: i64,
+}
+
+/// Graph size and extraction progress.
+pub fn stats(db: &Database) -> Result<KnowledgeStats> {
+    db.with_read_conn(|conn| {
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0));
+        let processed_chunks = count(
+            "SELECT COUNT(*) FROM kg_processed_chunks p
+             WHERE EXISTS (SELECT 1 FROM chunks c WHERE c.id = p.chunk_id)",
+        )?;
+        Ok(KnowledgeStats {
+            entity_count: count("SELECT COUNT(*) FROM kg_entities")?,
+            edge_count: count("SELECT COUNT(*) FROM kg_edges")?,
+            processed_chunks,
+            pending_chunks: count("SELECT COUNT(*) FROM chunks")? - processed_chunks,
+        })
+    })
+}
+
+/// Read every pending chunk with the local model. Progress is saved per
+/// chunk, so an interrupted run continues where it stopped.
+pub async fn build(state: &AppState) -> Result<KnowledgeStats> {
+    let mut processed = 0usize;
+    loop {
+        let batch = pending_chunks(&state.db, BATCH_SIZE)?;
+        if batch.is_empty() {
+            break;
+        }
+        for (chunk_id, content) in batch {
+            let extraction = extract_chunk(state, &content).await.map_err(|e| {
+                GhostError::Agent(format!(
+                    "Knowledge extraction stopped after {} chunks: {}",
+                    processed, e
+                ))
+            })?;
+            store_extraction(&state.db, chunk_id, &extraction)?;
+            processed += 1;
+        }
+    }
+    prune_orphans(&state.db)?;
+    tracing::info!("Knowledge graph: read {} chunks", processed);
+    stats(&state.db)
+}
+
+/// An entity in a query result.
+#[derive(Debug, Clone, Serialize)]
+pub struct Entity {
+    pub id: i64,
+    pub name: String,
+    pub kind: String,
+    /// Chunks that mention it.
+    pub mentions: i64,
+}
+
+/// A relation in a query result, with the chunk that states it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Edge {
+    pub source_id: i64,
+    pub target_id: i64,
+    pub source: String,
+    pub relation: String,
+    pub target: String,
+    pub chunk_id: i64,
+    pub path: String,
+}
+
+/// The neighbourhood of the entities matching a query.
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeGraph {
+    /// Entities whose name matched the query.
+    pub matches: Vec<i64>,
+    pub entities: Vec<Entity>,
+    pub edges: Vec<Edge>,
+}
+
+impl KnowledgeGraph {
+    /// Plain-text listing for the agent.
+    pub fn describe(&self) -> String {
+        if self.entities.is_empty() {
+            return "No matching entities in the knowledge graph.".into();
+        }
+        let mut lines: Vec<String> = self
+            .entities
+            .iter()
+            .filter(|e| self.matches.contains(&e.id))
+            .map(|e| {
+                format!(
+                    "{} ({}, mentioned in {} passages)",
+                    e.name, e.kind, e.mentions
+                )
+            })
+            .collect();
+        if self.edges.is_empty() {
+            lines.push("No known relations.".into());
+        }
+        for edge in &self.edges {
+            let file = edge.path.rsplit(['/', '\\']).next().unwrap_or(&edge.path);
+            lines.push(format!(
+                "{} — {} → {} [{}]",
+                edge.source, edge.relation, edge.target, file
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Entities whose name contains `text`, exact matches and the most
+/// mentioned first, and the relations within `depth` hops of them.
+pub fn query(db: &Database, text: &str, depth: usize, limit: usize) -> Result<KnowledgeGraph> {
+    let key = entity_key(text);
+    if key.is_empty() {
+        return Err(GhostError::InvalidQuery(
+            "Empty knowledge graph query".into(),
+        ));
+    }
+    let depth = depth.clamp(1, MAX_DEPTH);
+    let limit = limit.max(1);
+
+    db.with_read_conn(|conn| {
+        let entity_row = |row: &rusqlite::Row<'_>| {
+            Ok(Entity {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+                mentions: row.get(3)?,
+            })
+        };
+        let pattern = format!("%{}%", key.replace('%', "\\%").replace('_', "\\_"));
+        let mut stmt = conn.prepare(
+            "SELECT e.id, e.name, e.kind,
+                    (SELECT COUNT(*) FROM kg_mentions m WHERE m.entity_id = e.id) AS mentions
+             FROM kg_entities e WHERE e.key LIKE ?1 ESCAPE '\\'
+             ORDER BY e.key = ?2 DESC, mentions DESC, e.name LIMIT ?3",
+        )?;
+        let seeds = stmt
+            .query_map(rusqlite::params![pattern, key, limit as i64], entity_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let matches: Vec<i64> = seeds.iter().map(|e| e.id).collect();
+
+        let mut edge_stmt = conn.prepare(
+            "SELECT g.source_id, g.target_id, s.name, g.relation, t.name, g.chunk_id, d.path
+             FROM kg_edges g
+             JOIN kg_entities s ON s.id = g.source_id
+             JOIN kg_entities t ON t.id = g.target_id
+             JOIN chunks c ON c.id = g.chunk_id
+             JOIN documents d ON d.id = c.document_id
+             WHERE g.source_id = ?1 OR g.target_id = ?1
+             ORDER BY g.id",
+        )?;
+        let mut seen: HashSet<i64> = matches.iter().copied().collect();
+        let mut frontier = matches.clone();
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut edge_keys: HashSet<(i64, i64, String)> = HashSet::new();
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for id in frontier {
+                let rows = edge_stmt.query_map(rusqlite::params![id], |row| {
+                    Ok(Edge {
+                        source_id: row.get(0)?,
+                        target_id: row.get(1)?,
+                        source: row.get(2)?,
+                        relation: row.get(3)?,
+                        target: row.get(4)?,
+                        chunk_id: row.get(5)?,
+                        path: row.get(6)?,
+                    })
+                })?;
+                for edge in rows {
+                    let edge = edge?;
+                    // One edge per fact, however many chunks state it
+                    if edges.len() >= MAX_EDGES
+                        || !edge_keys.insert((
+                            edge.source_id,
+                            edge.target_id,
+                            edge.relation.clone(),
+                        ))
+                    {
+                        continue;
+                    }
+                    for other in [edge.source_id, edge.target_id] {
+                        if seen.insert(other) {
+                            next.push(other);
+                        }
+                    }
+                    edges.push(edge);
+                }
+            }
+            frontier = next;
+        }
+
+        let mut entities = seeds;
+        let mut by_id = conn.prepare(
+            "SELECT e.id, e.name, e.kind,
+                    (SELECT COUNT(*) FROM kg_mentions m WHERE m.entity_id = e.id)
+             FROM kg_entities e WHERE e.id = ?1",
+        )?;
+        let mut others: Vec<i64> = seen
+            .into_iter()
+            .filter(|id| !matches.contains(id))
+            .collect();
+        others.sort_unstable();
+        for id in others {
+            entities.push(by_id.query_row(rusqlite::params![id], entity_row)?);
+        }
+        Ok(KnowledgeGraph {
+            matches,
+            entities,
+            edges,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extraction() {
+        let reply = "Sure! ```json\n{\"entities\": [{\"name\": \"Alice  Chen\", \"type\": \"Person\"}, \
+            {\"name\": \"alice chen\", \"type\": \"person\"}, {\"name\": \"\", \"type\": \"x\"}], \
+            \"relations\": [{\"source\": \"Alice Chen\", \"relation\": \"Manages\", \"target\": \"Apollo\"}, \
+            {\"source\": \"Apollo\", \"relation\": \"is\", \"target\": \"apollo\"}]}\n```";
+        let extraction = parse_extraction(reply).unwrap();
+        assert_eq!(
+            extraction.entities,
+            vec![
+                ExtractedEntity {
+                    name: "Alice Chen".into(),
+                    kind: "person".into()
+                },
+                ExtractedEntity {
+                    name: "Apollo".into(),
+                    kind: "thing".into()
+                },
+            ]
+        );
+        assert_eq!(
+            extraction.relations,
+            vec![ExtractedRelation {
+                source: "Alice Chen".into(),
+                relation: "manages".into(),
+                target: "Apollo".into(),
+            }]
+        );
+        assert_eq!(parse_extraction("no json here"), None);
+    }
+
+    #[test]
+    fn test_extraction_schema() {
+        let schema = extraction_schema();
+        let reply = r#"{"entities":[{"name":"Alice","type":"person"}],"relations":[]}"#;
+        assert!(crate::chat::structured::parse_reply(reply, &schema).is_ok());
+        assert!(crate::chat::structured::parse_reply(r#"{"entities":[]}"#, &schema).is_err());
+    }
+
+    #[test]
+    fn test_store_and_query() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_knowledge_schema(&db).unwrap();
+        let doc = db
+            .upsert_document(
+                "/docs/team.md",
+                "team.md",
+                Some("md"),
+                10,
+                "h",
+                "2026-01-01",
+            )
+            .unwrap();
+        let first = db.insert_chunk(doc, 0, "Alice manages Apollo.", 4).unwrap();
+        let second = db.insert_chunk(doc, 1, "Apollo uses Postgres.", 4).unwrap();
+        assert_eq!(pending_chunks(&db, 10).unwrap().len(), 2);
+
+        let parse = |s: &str| parse_extraction(s).unwrap();
+        store_extraction(
+            &db,
+            first,
+            &parse(r#"{"entities":[{"name":"Alice","type":"person"}],"relations":[{"source":"Alice","relation":"manages","target":"Apollo"}]}"#),
+        )
+        .unwrap();
+        store_extraction(
+            &db,
+            second,
+            &parse(r#"{"entities":[{"name":"Apollo","type":"project"}],"relations":[{"source":"Apollo","relation":"uses","target":"Postgres"}]}"#),
+        )
+        .unwrap();
+        assert!(pending_chunks(&db, 10).unwrap().is_empty());
+
+        let near = query(&db, "alice", 1, 10).unwrap();
+        assert_eq!(near.edges.len(), 1);
+        assert_eq!(near.entities.len(), 2);
+        // Kind is filled in once a chunk names it
+        assert_eq!(near.entities[1].kind, "project");
+        let far = query(&db, "Alice", 2, 10).unwrap();
+        assert_eq!(far.edges.len(), 2);
+        assert!(far
+            .describe()
+            .contains("Apollo — uses → Postgres [team.md]"));
+
+        let stats = stats(&db).unwrap();
+        assert_eq!((stats.entity_count, stats.edge_count), (3, 2));
+        assert_eq!(stats.pending_chunks, 0);
+        assert!(query(&db, "  ", 1, 10).is_err());
+    }
+}
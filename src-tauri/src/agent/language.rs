@@ -0,0 +1,248 @@
+//! Response language matching and translation.
+//!
+//! Each turn, [`detect`] guesses the language of the user's message — by
+//! script for non-Latin writing systems, by common function words for Latin
+//! ones — and the system prompt asks the model to answer in it. Retrieved
+//! chunks in other languages can be translated with [`translate`] (the
+//! `ghost_translate` tool), which runs on the local chat model.
+
+use serde::Serialize;
+
+use crate::chat::ChatMessage;
+use crate::error::{GhostError, Result};
+use crate::AppState;
+
+/// Longest text translated in one call, in characters.
+pub const MAX_TRANSLATE_CHARS: usize = 6000;
+
+/// Function words needed before a Latin-script guess is trusted.
+const MIN_STOPWORD_HITS: usize = 2;
+
+const TRANSLATE_PROMPT: &str = "You are a translator. Translate the user's text into the \
+requested language. Keep the meaning, tone, names, numbers, and formatting (Markdown, line \
+breaks). Reply with the translation only — no notes, no quotes around it.";
+
+/// A detected language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Language {
+    /// ISO 639-1 code.
+    pub code: &'static str,
+    /// English name, as used in prompts.
+    pub name: &'static str,
+}
+
+const fn lang(code: &'static str, name: &'static str) -> Language {
+    Language { code, name }
+}
+
+/// Languages told apart by their script alone.
+fn script_language(c: char) -> Option<Language> {
+    Some(match c as u32 {
+        0x3040..=0x30FF => lang("ja", "Japanese"),
+        0xAC00..=0xD7AF | 0x1100..=0x11FF => lang("ko", "Korean"),
+        0x4E00..=0x9FFF => lang("zh", "Chinese"),
+        0x0400..=0x04FF => lang("ru", "Russian"),
+        0x0370..=0x03FF => lang("el", "Greek"),
+        0x0590..=0x05FF => lang("he", "Hebrew"),
+        0x0600..=0x06FF => lang("ar", "Arabic"),
+        0x0900..=0x097F => lang("hi", "Hindi"),
+        0x0E00..=0x0E7F => lang("th", "Thai"),
+        _ => return None,
+    })
+}
+
+/// Frequent function words of Latin-script languages.
+const STOPWORDS: &[(Language, &[&str])] = &[
+    (
+        lang("en", "English"),
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "what", "my", "you", "with", "for",
+            "this", "that", "how", "where", "do", "have", "can", "it",
+        ],
+    ),
+    (
+        lang("es", "Spanish"),
+        &[
+            "el", "la", "los", "las", "de", "que", "y", "en", "es", "un", "una", "por", "para",
+            "con", "mis", "qué", "dónde", "cómo", "del", "está",
+        ],
+    ),
+    (
+        lang("fr", "French"),
+        &[
+            "le", "la", "les", "de", "des", "et", "est", "un", "une", "que", "pour", "dans", "mes",
+            "où", "avec", "sur", "du", "je", "quel", "quels",
+        ],
+    ),
+    (
+        lang("de", "German"),
+        &[
+            "der", "die", "das", "und", "ist", "ein", "eine", "nicht", "mit", "ich", "meine", "wo",
+            "wie", "was", "den", "für", "auf", "sind", "zu", "von",
+        ],
+    ),
+    (
+        lang("pt", "Portuguese"),
+        &[
+            "o", "os", "as", "de", "que", "e", "em", "um", "uma", "para", "com", "meus", "onde",
+            "não", "do", "da", "está", "são", "qual", "como",
+        ],
+    ),
+    (
+        lang("it", "Italian"),
+        &[
+            "il", "lo", "gli", "di", "che", "e", "è", "un", "una", "per", "con", "non", "dove",
+            "come", "del", "della", "sono", "miei", "quali", "nel",
+        ],
+    ),
+    (
+        lang("nl", "Dutch"),
+        &[
+            "de", "het", "een", "en", "van", "is", "niet", "met", "ik", "mijn", "waar", "hoe",
+            "wat", "zijn", "voor", "op", "dat", "te", "ook", "welke",
+        ],
+    ),
+];
+
+/// Guess the language of `text`, or `None` when it is too short or mixed to
+/// tell.
+pub fn detect(text: &str) -> Option<Language> {
+    // Non-Latin scripts: majority of script-specific letters
+    let mut scripts: Vec<(Language, usize)> = Vec::new();
+    let mut letters = 0usize;
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        if let Some(language) = script_language(c) {
+            match scripts.iter_mut().find(|(l, _)| *l == language) {
+                Some((_, count)) => *count += 1,
+                None => scripts.push((language, 1)),
+            }
+        }
+    }
+    // Kanji next to kana is Japanese, not Chinese
+    if scripts.iter().any(|(l, _)| l.code == "ja") {
+        let han: usize = scripts
+            .iter()
+            .filter(|(l, _)| l.code == "zh")
+            .map(|(_, n)| *n)
+            .sum();
+        scripts.retain(|(l, _)| l.code != "zh");
+        for (l, n) in scripts.iter_mut() {
+            if l.code == "ja" {
+                *n += han;
+            }
+        }
+    }
+    if let Some((language, count)) = scripts.iter().max_by_key(|(_, n)| *n) {
+        if *count * 2 >= letters {
+            return Some(*language);
+        }
+    }
+
+    // Latin scripts: count function words
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mut best: Option<(Language, usize)> = None;
+    for (language, stopwords) in STOPWORDS {
+        let hits = words
+            .iter()
+            .filter(|w| stopwords.contains(&w.as_str()))
+            .count();
+        match best {
+            Some((_, top)) if top >= hits => {}
+            _ => best = Some((*language, hits)),
+        }
+    }
+    best.filter(|(_, hits)| *hits >= MIN_STOPWORD_HITS)
+        .map(|(language, _)| language)
+}
+
+/// A translated text.
+#[derive(Debug, Clone, Serialize)]
+pub struct Translation {
+    pub text: String,
+    /// Detected language of the input, if recognised.
+    pub source_language: Option<Language>,
+    pub target_language: String,
+    /// Whether the input was cut to [`MAX_TRANSLATE_CHARS`].
+    pub truncated: bool,
+}
+
+/// Translate `text` into `target` (a language name like "Spanish") with the
+/// local chat model.
+pub async fn translate(state: &AppState, text: &str, target: &str) -> Result<Translation> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err(GhostError::Agent("No target language given".into()));
+    }
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(GhostError::Agent("Nothing to translate".into()));
+    }
+    let truncated = text.chars().count() > MAX_TRANSLATE_CHARS;
+    let text: String = text.chars().take(MAX_TRANSLATE_CHARS).collect();
+    let source_language = detect(&text);
+    if source_language.is_some_and(|l| l.name.eq_ignore_ascii_case(target)) {
+        return Ok(Translation {
+            text,
+            source_language,
+            target_language: target.to_string(),
+            truncated,
+        });
+    }
+
+    let prompt = [
+        ChatMessage {
+            role: "system".into(),
+            content: TRANSLATE_PROMPT.into(),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: format!("Translate into {}:\n\n{}", target, text),
+        },
+    ];
+    // Translations run about as long as the input; leave headroom
+    let max_tokens = (text.chars().count() / 2).clamp(128, 4096);
+    let response = state.chat_engine.chat(&prompt, max_tokens).await?;
+    Ok(Translation {
+        text: response.content.trim().to_string(),
+        source_language,
+        target_language: target.to_string(),
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(text: &str) -> Option<&'static str> {
+        detect(text).map(|l| l.code)
+    }
+
+    #[test]
+    fn test_detect_latin_languages() {
+        assert_eq!(code("Where is the invoice for my car?"), Some("en"));
+        assert_eq!(code("¿Dónde está la factura de mi coche?"), Some("es"));
+        assert_eq!(code("Où est la facture pour ma voiture ?"), Some("fr"));
+        assert_eq!(code("Wo ist die Rechnung für mein Auto?"), Some("de"));
+        assert_eq!(code("Onde estão os meus recibos de 2023?"), Some("pt"));
+        // Too short to tell
+        assert_eq!(code("invoice"), None);
+        assert_eq!(code(""), None);
+    }
+
+    #[test]
+    fn test_detect_scripts() {
+        assert_eq!(code("我的发票在哪里？"), Some("zh"));
+        assert_eq!(code("請求書はどこにありますか"), Some("ja"));
+        assert_eq!(code("내 영수증은 어디에 있나요?"), Some("ko"));
+        assert_eq!(code("Где мой счёт за машину?"), Some("ru"));
+        assert_eq!(code("أين فاتورتي؟"), Some("ar"));
+        // A foreign name in an English sentence doesn't switch languages
+        assert_eq!(code("What did Дмитрий say about the budget?"), Some("en"));
+    }
+}
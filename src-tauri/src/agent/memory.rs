@@ -5,12 +5,17 @@
 //! - Multiple conversations with metadata
 //! - Full message history with roles
 //! - FTS5 search across past conversations
+//! - Documents and folders attached to a conversation, which scope its
+//!   retrieval to one project
 //! - Automatic conversation summarization (future)
 
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 
 use crate::db::Database;
-use crate::error::Result;
+use crate::error::{GhostError, Result};
+use crate::search::focus::escape_like;
 
 /// A conversation with metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +27,12 @@ pub struct Conversation {
     pub message_count: i64,
     /// Summary of the conversation (populated after several messages).
     pub summary: Option<String>,
+    /// Path of the Markdown note distilled from this conversation.
+    #[serde(default)]
+    pub note_path: Option<String>,
+    /// Whether this conversation may use the remote chat provider.
+    #[serde(default)]
+    pub allow_cloud: bool,
 }
 
 /// A single message in a conversation.
@@ -38,6 +49,18 @@ pub struct Message {
     pub tool_result: Option<String>,
     /// Model used to generate this message.
     pub model: Option<String>,
+    /// Sources cited by this message (JSON array of `Citation`).
+    pub citations: Option<String>,
+}
+
+/// A document or folder pinned to a conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub conversation_id: i64,
+    pub path: String,
+    /// "document" or "folder".
+    pub kind: String,
+    pub created_at: String,
 }
 
 /// Initialize conversation tables in the database.
@@ -50,7 +73,9 @@ pub fn initialize_memory_schema(db: &Database) -> Result<()> {
                 title TEXT NOT NULL DEFAULT 'New Conversation',
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 updated_at TEXT NOT NULL DEFAULT (datetime('now')),
-                summary TEXT
+                summary TEXT,
+                note_path TEXT,
+                allow_cloud INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS messages (
@@ -62,12 +87,23 @@ pub fn initialize_memory_schema(db: &Database) -> Result<()> {
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 tool_calls TEXT,
                 tool_result TEXT,
-                model TEXT
+                model TEXT,
+                citations TEXT
             );
 
             CREATE INDEX IF NOT EXISTS idx_messages_conversation
                 ON messages(conversation_id, created_at);
 
+            -- Documents and folders a conversation's searches are limited to
+            CREATE TABLE IF NOT EXISTS conversation_attachments (
+                conversation_id INTEGER NOT NULL
+                    REFERENCES conversations(id) ON DELETE CASCADE,
+                path TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (conversation_id, path)
+            );
+
             -- FTS5 for searching across conversations
             CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
                 content,
@@ -91,6 +127,36 @@ pub fn initialize_memory_schema(db: &Database) -> Result<()> {
             END;
             ",
         )?;
+
+        // Vaults created before citations were tracked lack the column.
+        let has_citations: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('messages') WHERE name = 'citations'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_citations == 0 {
+            conn.execute_batch("ALTER TABLE messages ADD COLUMN citations TEXT;")?;
+        }
+        // ...and vaults created before conversation notes lack note_path.
+        let has_note_path: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('conversations') WHERE name = 'note_path'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_note_path == 0 {
+            conn.execute_batch("ALTER TABLE conversations ADD COLUMN note_path TEXT;")?;
+        }
+        // ...and before the cloud switch lack allow_cloud (local only).
+        let has_allow_cloud: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('conversations') WHERE name = 'allow_cloud'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_allow_cloud == 0 {
+            conn.execute_batch(
+                "ALTER TABLE conversations ADD COLUMN allow_cloud INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
         Ok(())
     })
 }
@@ -141,6 +207,17 @@ pub fn add_message(
     })
 }
 
+/// Attach citation metadata (JSON array) to a stored message.
+pub fn set_message_citations(db: &Database, message_id: i64, citations_json: &str) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE messages SET citations = ?1 WHERE id = ?2",
+            rusqlite::params![citations_json, message_id],
+        )?;
+        Ok(())
+    })
+}
+
 /// Get messages for a conversation, ordered chronologically.
 pub fn get_messages(
     db: &Database,
@@ -153,7 +230,7 @@ pub fn get_messages(
             None => String::new(),
         };
         let sql = format!(
-            "SELECT id, conversation_id, role, content, created_at, tool_calls, tool_result, model
+            "SELECT id, conversation_id, role, content, created_at, tool_calls, tool_result, model, citations
              FROM messages WHERE conversation_id = ?1
              ORDER BY created_at ASC {}",
             limit_clause
@@ -169,6 +246,7 @@ pub fn get_messages(
                 tool_calls: row.get(5)?,
                 tool_result: row.get(6)?,
                 model: row.get(7)?,
+                citations: row.get(8)?,
             })
         })?;
         let mut results = Vec::new();
@@ -179,26 +257,34 @@ pub fn get_messages(
     })
 }
 
+impl Conversation {
+    /// Columns read by [`Conversation::from_row`].
+    const COLUMNS: &'static str = "c.id, c.title, c.created_at, c.updated_at, c.summary,
+        (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id), c.note_path,
+        c.allow_cloud";
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Conversation {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            created_at: row.get(2)?,
+            updated_at: row.get(3)?,
+            summary: row.get(4)?,
+            message_count: row.get(5)?,
+            note_path: row.get(6)?,
+            allow_cloud: row.get(7)?,
+        })
+    }
+}
+
 /// List all conversations, most recent first.
 pub fn list_conversations(db: &Database, limit: usize) -> Result<Vec<Conversation>> {
     db.with_conn(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT c.id, c.title, c.created_at, c.updated_at, c.summary,
-                    (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id)
-             FROM conversations c
-             ORDER BY c.updated_at DESC
-             LIMIT ?1",
-        )?;
-        let rows = stmt.query_map(rusqlite::params![limit as i64], |row| {
-            Ok(Conversation {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                created_at: row.get(2)?,
-                updated_at: row.get(3)?,
-                summary: row.get(4)?,
-                message_count: row.get(5)?,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM conversations c ORDER BY c.updated_at DESC LIMIT ?1",
+            Conversation::COLUMNS
+        ))?;
+        let rows = stmt.query_map(rusqlite::params![limit as i64], Conversation::from_row)?;
         let mut results = Vec::new();
         for row in rows {
             results.push(row?);
@@ -207,6 +293,53 @@ pub fn list_conversations(db: &Database, limit: usize) -> Result<Vec<Conversatio
     })
 }
 
+/// Get a single conversation by ID.
+pub fn get_conversation(db: &Database, conversation_id: i64) -> Result<Option<Conversation>> {
+    db.with_conn(|conn| {
+        let result = conn.query_row(
+            &format!(
+                "SELECT {} FROM conversations c WHERE c.id = ?1",
+                Conversation::COLUMNS
+            ),
+            rusqlite::params![conversation_id],
+            Conversation::from_row,
+        );
+        match result {
+            Ok(v) => Ok(Some(v)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Link a conversation to the note distilled from it.
+pub fn set_conversation_note(db: &Database, conversation_id: i64, note_path: &str) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE conversations SET note_path = ?1 WHERE id = ?2",
+            rusqlite::params![note_path, conversation_id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Switch a conversation between local only and allowing the remote provider.
+pub fn set_conversation_cloud(db: &Database, conversation_id: i64, allow: bool) -> Result<()> {
+    db.with_conn(|conn| {
+        let updated = conn.execute(
+            "UPDATE conversations SET allow_cloud = ?1 WHERE id = ?2",
+            rusqlite::params![allow, conversation_id],
+        )?;
+        if updated == 0 {
+            return Err(GhostError::Agent(format!(
+                "Conversation {} not found",
+                conversation_id
+            )));
+        }
+        Ok(())
+    })
+}
+
 /// Delete a conversation and all its messages.
 pub fn delete_conversation(db: &Database, conversation_id: i64) -> Result<()> {
     db.with_conn(|conn| {
@@ -234,7 +367,7 @@ pub fn search_conversations(db: &Database, query: &str, limit: usize) -> Result<
     db.with_conn(|conn| {
         let mut stmt = conn.prepare(
             "SELECT m.id, m.conversation_id, m.role, m.content, m.created_at,
-                    m.tool_calls, m.tool_result, m.model
+                    m.tool_calls, m.tool_result, m.model, m.citations
              FROM messages_fts fts
              JOIN messages m ON m.id = fts.rowid
              WHERE messages_fts MATCH ?1
@@ -251,6 +384,7 @@ pub fn search_conversations(db: &Database, query: &str, limit: usize) -> Result<
                 tool_calls: row.get(5)?,
                 tool_result: row.get(6)?,
                 model: row.get(7)?,
+                citations: row.get(8)?,
             })
         })?;
         let mut results = Vec::new();
@@ -267,7 +401,7 @@ pub fn search_conversations(db: &Database, query: &str, limit: usize) -> Result<
 pub fn get_recent_context(db: &Database, limit: usize) -> Result<Vec<Message>> {
     db.with_conn(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT id, conversation_id, role, content, created_at, tool_calls, tool_result, model
+            "SELECT id, conversation_id, role, content, created_at, tool_calls, tool_result, model, citations
              FROM messages
              ORDER BY created_at DESC
              LIMIT ?1",
@@ -282,6 +416,7 @@ pub fn get_recent_context(db: &Database, limit: usize) -> Result<Vec<Message>> {
                 tool_calls: row.get(5)?,
                 tool_result: row.get(6)?,
                 model: row.get(7)?,
+                citations: row.get(8)?,
             })
         })?;
         let mut results = Vec::new();
@@ -292,6 +427,125 @@ pub fn get_recent_context(db: &Database, limit: usize) -> Result<Vec<Message>> {
     })
 }
 
+/// `LIKE` pattern for the documents under a folder.
+fn folder_pattern(folder: &str) -> String {
+    format!(
+        "{}{}%",
+        escape_like(folder),
+        escape_like(std::path::MAIN_SEPARATOR_STR)
+    )
+}
+
+/// Pin an indexed document, or a folder containing indexed documents, to a
+/// conversation.
+pub fn attach_to_conversation(
+    db: &Database,
+    conversation_id: i64,
+    path: &str,
+) -> Result<Attachment> {
+    let path = path.trim().trim_end_matches(['/', '\\']);
+    if path.is_empty() {
+        return Err(GhostError::Agent("No path to attach".into()));
+    }
+    if get_conversation(db, conversation_id)?.is_none() {
+        return Err(GhostError::Agent(format!(
+            "Conversation {} not found",
+            conversation_id
+        )));
+    }
+    db.with_conn(|conn| {
+        let is_document: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM documents WHERE path = ?1)",
+            rusqlite::params![path],
+            |row| row.get(0),
+        )?;
+        let kind = if is_document {
+            "document"
+        } else {
+            let has_documents: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM documents WHERE path LIKE ?1 ESCAPE '\\')",
+                rusqlite::params![folder_pattern(path)],
+                |row| row.get(0),
+            )?;
+            if !has_documents {
+                return Err(GhostError::Agent(format!("Nothing indexed at {}", path)));
+            }
+            "folder"
+        };
+        conn.execute(
+            "INSERT OR IGNORE INTO conversation_attachments (conversation_id, path, kind)
+             VALUES (?1, ?2, ?3)",
+            rusqlite::params![conversation_id, path, kind],
+        )?;
+        Ok(conn.query_row(
+            "SELECT conversation_id, path, kind, created_at FROM conversation_attachments
+             WHERE conversation_id = ?1 AND path = ?2",
+            rusqlite::params![conversation_id, path],
+            attachment_from_row,
+        )?)
+    })
+}
+
+fn attachment_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        conversation_id: row.get(0)?,
+        path: row.get(1)?,
+        kind: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+/// Unpin a document or folder. Returns whether it was attached.
+pub fn detach_from_conversation(db: &Database, conversation_id: i64, path: &str) -> Result<bool> {
+    let path = path.trim().trim_end_matches(['/', '\\']);
+    db.with_conn(|conn| {
+        let removed = conn.execute(
+            "DELETE FROM conversation_attachments WHERE conversation_id = ?1 AND path = ?2",
+            rusqlite::params![conversation_id, path],
+        )?;
+        Ok(removed > 0)
+    })
+}
+
+/// Documents and folders pinned to a conversation, in the order added.
+pub fn list_attachments(db: &Database, conversation_id: i64) -> Result<Vec<Attachment>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT conversation_id, path, kind, created_at FROM conversation_attachments
+             WHERE conversation_id = ?1 ORDER BY rowid",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![conversation_id], attachment_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// IDs of the documents a conversation's searches are limited to, or
+/// `None` when nothing is attached.
+pub fn attachment_scope(db: &Database, conversation_id: i64) -> Result<Option<HashSet<i64>>> {
+    let attachments = list_attachments(db, conversation_id)?;
+    if attachments.is_empty() {
+        return Ok(None);
+    }
+    db.with_read_conn(|conn| {
+        let mut by_path = conn.prepare("SELECT id FROM documents WHERE path = ?1")?;
+        let mut under_folder =
+            conn.prepare("SELECT id FROM documents WHERE path LIKE ?1 ESCAPE '\\'")?;
+        let id = |row: &rusqlite::Row<'_>| row.get::<_, i64>(0);
+        let mut documents = HashSet::new();
+        for attachment in &attachments {
+            let rows = if attachment.kind == "folder" {
+                under_folder.query_map(rusqlite::params![folder_pattern(&attachment.path)], id)?
+            } else {
+                by_path.query_map(rusqlite::params![attachment.path], id)?
+            };
+            for id in rows {
+                documents.insert(id?);
+            }
+        }
+        Ok(Some(documents))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,6 +598,55 @@ mod tests {
         assert_eq!(convs.len(), 2);
     }
 
+    #[test]
+    fn test_conversation_note_link() {
+        let db = setup_test_db();
+        let id = create_conversation(&db, "Planning").unwrap();
+        assert!(get_conversation(&db, id)
+            .unwrap()
+            .unwrap()
+            .note_path
+            .is_none());
+
+        set_conversation_note(&db, id, "/notes/planning.md").unwrap();
+        let conv = get_conversation(&db, id).unwrap().unwrap();
+        assert_eq!(conv.note_path.as_deref(), Some("/notes/planning.md"));
+        assert!(get_conversation(&db, id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_conversation_cloud_switch() {
+        let db = setup_test_db();
+        let id = create_conversation(&db, "Research").unwrap();
+        // Local only unless switched
+        assert!(!get_conversation(&db, id).unwrap().unwrap().allow_cloud);
+        set_conversation_cloud(&db, id, true).unwrap();
+        assert!(get_conversation(&db, id).unwrap().unwrap().allow_cloud);
+        set_conversation_cloud(&db, id, false).unwrap();
+        assert!(!get_conversation(&db, id).unwrap().unwrap().allow_cloud);
+        assert!(set_conversation_cloud(&db, id + 1, true).is_err());
+    }
+
+    #[test]
+    fn test_message_citations() {
+        let db = setup_test_db();
+        // Schema init is idempotent (the citations migration runs once)
+        initialize_memory_schema(&db).unwrap();
+        let conv_id = create_conversation(&db, "Cited").unwrap();
+        let msg_id =
+            add_message(&db, conv_id, "assistant", "See notes.md", None, None, None).unwrap();
+        assert!(get_messages(&db, conv_id, None).unwrap()[0]
+            .citations
+            .is_none());
+
+        set_message_citations(&db, msg_id, r#"[{"chunk_id":1}]"#).unwrap();
+        let messages = get_messages(&db, conv_id, None).unwrap();
+        assert_eq!(
+            messages[0].citations.as_deref(),
+            Some(r#"[{"chunk_id":1}]"#)
+        );
+    }
+
     #[test]
     fn test_delete_conversation() {
         let db = setup_test_db();
@@ -417,4 +720,49 @@ mod tests {
         assert!(messages[0].tool_calls.is_some());
         assert!(messages[1].tool_result.is_some());
     }
+
+    #[test]
+    fn test_conversation_attachments() {
+        let db = setup_test_db();
+        let conv_id = create_conversation(&db, "Thesis").unwrap();
+        let sep = std::path::MAIN_SEPARATOR;
+        let folder = format!("{sep}thesis");
+        let chapter = format!("{sep}thesis{sep}ch1.md");
+        let other = format!("{sep}notes{sep}todo.md");
+        let ids: Vec<i64> = [&chapter, &format!("{sep}thesis{sep}refs.bib"), &other]
+            .iter()
+            .map(|p| {
+                db.upsert_document(p, "f", Some("md"), 1, p, "2026-01-01")
+                    .unwrap()
+            })
+            .collect();
+
+        assert_eq!(attachment_scope(&db, conv_id).unwrap(), None);
+        let attached = attach_to_conversation(&db, conv_id, &format!("{folder}{sep}")).unwrap();
+        assert_eq!(
+            (attached.path.as_str(), attached.kind.as_str()),
+            (folder.as_str(), "folder")
+        );
+        assert_eq!(
+            attach_to_conversation(&db, conv_id, &other).unwrap().kind,
+            "document"
+        );
+        assert!(attach_to_conversation(&db, conv_id, "/nowhere").is_err());
+        assert!(attach_to_conversation(&db, conv_id + 1, &other).is_err());
+        assert_eq!(list_attachments(&db, conv_id).unwrap().len(), 2);
+        assert_eq!(
+            attachment_scope(&db, conv_id).unwrap(),
+            Some(ids.iter().copied().collect())
+        );
+
+        assert!(detach_from_conversation(&db, conv_id, &other).unwrap());
+        assert!(!detach_from_conversation(&db, conv_id, &other).unwrap());
+        assert_eq!(
+            attachment_scope(&db, conv_id).unwrap(),
+            Some(ids[..2].iter().copied().collect())
+        );
+        // Attachments go with the conversation
+        delete_conversation(&db, conv_id).unwrap();
+        assert!(list_attachments(&db, conv_id).unwrap().is_empty());
+    }
 }
@@ -16,11 +16,19 @@
 //! Hardware-adaptive: auto-selects the best local GGUF model based on
 //! detected RAM/VRAM, with user-configurable overrides.
 
+pub mod approval;
+pub mod audit;
 pub mod config;
+pub mod distill;
 pub mod executor;
+pub mod knowledge;
+pub mod language;
 pub mod memory;
+pub mod plan;
+pub mod rename;
 pub mod safety;
 pub mod skills;
+pub mod summarize;
 pub mod tools;
 
 use serde::{Deserialize, Serialize};
@@ -91,6 +99,26 @@ pub struct AgentRunResult {
     pub duration_ms: u64,
     /// Model used for this run.
     pub model: String,
+    /// Vault sources retrieved by `ghost_search` during the run.
+    pub citations: Vec<Citation>,
+    /// The budget that cut the run short; the content is then a best-effort answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_exceeded: Option<config::BudgetExceeded>,
+}
+
+/// A vault chunk the agent retrieved while answering.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// 1-based source number within the run (0 until the run assigns it).
+    pub index: usize,
+    pub chunk_id: i64,
+    pub document_id: i64,
+    pub path: String,
+    pub filename: String,
+    pub snippet: String,
+    /// Whether the final answer mentions this source's file.
+    #[serde(default)]
+    pub referenced: bool,
 }
 
 /// Record of a tool call that was executed.
@@ -106,4 +134,7 @@ pub struct ExecutedToolCall {
     pub duration_ms: u64,
     /// Risk level assessed by safety layer.
     pub risk_level: safety::RiskLevel,
+    /// Chunks returned by a `ghost_search` call.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
 }
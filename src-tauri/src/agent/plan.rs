@@ -0,0 +1,155 @@
+//! Plan mode — agent runs that propose changes instead of making them.
+//!
+//! A run by [`super::executor::AgentExecutor::planner`] sees the usual tools,
+//! but only safe (read-only) calls execute: every other call is recorded as a
+//! [`PlannedStep`] and answered with a note that it didn't run. The model's
+//! final answer is the plan. It is emitted as a `plan_ready` CUSTOM event and
+//! kept here until the user approves it with `execute_plan`, which starts a
+//! real run asked to carry it out, or discards it.
+
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+
+use serde::Serialize;
+
+use super::safety::RiskLevel;
+use crate::chat::ChatMessage;
+
+/// Plans kept waiting for approval; the oldest are dropped past this.
+const MAX_PENDING_PLANS: usize = 20;
+
+/// System prompt section of plan-mode runs.
+pub const PLAN_MODE_PROMPT: &str = "<plan_mode>\n\
+     You are planning, not acting. Tools that only read run as usual; calls that would \
+     change files, run commands or contact other services are recorded for the plan and \
+     not run. Look around with read-only tools if it helps, make the calls the task \
+     needs, and assume they succeed. Then answer with a short numbered plan: one step per \
+     line, naming the tool each step uses and what it changes.\n\
+     </plan_mode>\n\n";
+
+/// A tool call the plan would make.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedStep {
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+    pub risk_level: RiskLevel,
+    /// Human-readable action (see [`super::safety::describe_action`]).
+    pub description: String,
+}
+
+/// The outcome of a plan-mode run.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentPlan {
+    pub plan_id: String,
+    /// The plan-mode run that produced it.
+    pub run_id: String,
+    /// The model's plan, as written.
+    pub summary: String,
+    /// Calls held back, in the order the model made them.
+    pub steps: Vec<PlannedStep>,
+}
+
+/// A plan and the request it answers.
+struct PendingPlan {
+    plan: AgentPlan,
+    messages: Vec<ChatMessage>,
+    conversation_id: Option<i64>,
+}
+
+static PENDING: LazyLock<Mutex<VecDeque<PendingPlan>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// Keep a plan until it is approved or discarded.
+pub fn store(plan: AgentPlan, messages: &[ChatMessage], conversation_id: Option<i64>) {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    pending.retain(|p| p.plan.plan_id != plan.plan_id);
+    if pending.len() >= MAX_PENDING_PLANS {
+        pending.pop_front();
+    }
+    pending.push_back(PendingPlan {
+        plan,
+        messages: messages.to_vec(),
+        conversation_id,
+    });
+}
+
+/// Remove a pending plan. Returns the messages of a run carrying it out and
+/// the conversation it belongs to.
+pub fn approve(plan_id: &str) -> Result<(Vec<ChatMessage>, Option<i64>), String> {
+    let pending = take(plan_id)?;
+    Ok((
+        execution_messages(&pending.plan, &pending.messages),
+        pending.conversation_id,
+    ))
+}
+
+/// Drop a pending plan.
+pub fn discard(plan_id: &str) -> Result<(), String> {
+    take(plan_id).map(|_| ())
+}
+
+fn take(plan_id: &str) -> Result<PendingPlan, String> {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let index = pending
+        .iter()
+        .position(|p| p.plan.plan_id == plan_id)
+        .ok_or_else(|| format!("No pending plan '{}'", plan_id))?;
+    Ok(pending.remove(index).expect("index is in range"))
+}
+
+/// The original request, the plan as the assistant's answer, and the go-ahead.
+fn execution_messages(plan: &AgentPlan, messages: &[ChatMessage]) -> Vec<ChatMessage> {
+    let mut messages = messages.to_vec();
+    messages.push(ChatMessage {
+        role: "assistant".into(),
+        content: plan.summary.clone(),
+    });
+    messages.push(ChatMessage {
+        role: "user".into(),
+        content: "The plan is approved. Carry it out now, step by step.".into(),
+    });
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(plan_id: &str) -> AgentPlan {
+        AgentPlan {
+            plan_id: plan_id.into(),
+            run_id: "run-1".into(),
+            summary: "1. Rename notes.txt with ghost_rename_file".into(),
+            steps: vec![PlannedStep {
+                tool_name: "ghost_rename_file".into(),
+                arguments: serde_json::json!({ "path": "/tmp/notes.txt", "new_name": "a.txt" }),
+                risk_level: RiskLevel::Moderate,
+                description: "Rename /tmp/notes.txt".into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_approve_plan() {
+        let request = vec![ChatMessage {
+            role: "user".into(),
+            content: "Tidy my notes".into(),
+        }];
+        store(plan("plan-test-approve"), &request, Some(7));
+
+        let (messages, conversation_id) = approve("plan-test-approve").unwrap();
+        assert_eq!(conversation_id, Some(7));
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["user", "assistant", "user"]);
+        assert!(messages[1].content.contains("ghost_rename_file"));
+        // A plan runs once
+        assert!(approve("plan-test-approve").is_err());
+    }
+
+    #[test]
+    fn test_discard_plan() {
+        store(plan("plan-test-discard"), &[], None);
+        discard("plan-test-discard").unwrap();
+        assert!(discard("plan-test-discard").is_err());
+    }
+}
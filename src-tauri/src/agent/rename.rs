@@ -0,0 +1,370 @@
+//! Descriptive file name suggestions.
+//!
+//! Proposes names like "2024-03 ACME Electricity Bill.pdf" for files saved
+//! as "Scan_0001.pdf" or "IMG_2041.jpg", from the text Ghost extracted. A
+//! folder can be processed in one batch, which by default only looks at files
+//! whose names say nothing about their content. Suggesting never touches the
+//! file; renaming is a separate, approval-gated step that keeps the index in
+//! sync.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::chat::ChatMessage;
+use crate::error::{GhostError, Result};
+use crate::AppState;
+
+/// Characters of document text shown to the model.
+const MAX_EXCERPT_CHARS: usize = 3000;
+
+/// Longest suggested name, without the extension.
+const MAX_NAME_CHARS: usize = 80;
+
+/// Files processed by one batch suggestion.
+const MAX_BATCH_FILES: usize = 20;
+
+const SUGGEST_MAX_TOKENS: usize = 48;
+
+const SUGGEST_PROMPT: &str = "You name files for a personal knowledge base. Given the \
+current file name and an excerpt of the file's content, reply with ONE descriptive file name \
+of 3-8 words that says what the document is (e.g. \"2024-03 ACME Electricity Bill\", \
+\"Lease Agreement 12 Oak Street\"). Start with the document's date as YYYY-MM or YYYY-MM-DD \
+when the content states one. No extension, no quotes, no explanation.";
+
+/// Words that make up camera, scanner, and download default names.
+const GENERIC_WORDS: &[&str] = &[
+    "scan",
+    "scanned",
+    "img",
+    "image",
+    "dsc",
+    "dscn",
+    "dcim",
+    "pxl",
+    "photo",
+    "pic",
+    "document",
+    "doc",
+    "file",
+    "untitled",
+    "unnamed",
+    "new",
+    "copy",
+    "download",
+    "screenshot",
+    "screen",
+    "shot",
+    "at",
+    "capture",
+    "page",
+    "print",
+    "export",
+    "output",
+    "attachment",
+    "att",
+    "final",
+    "draft",
+    "v",
+];
+
+/// A proposed new name for one file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilenameSuggestion {
+    pub path: String,
+    pub current_name: String,
+    /// Suggested file name, including the original extension.
+    pub suggested_name: String,
+}
+
+/// Whether a file name is a device or app default that says nothing about
+/// the content (`Scan_0001`, `IMG_20240301_1200`, `Untitled (3)`).
+fn is_generic_name(stem: &str) -> bool {
+    let lower = stem.to_lowercase();
+    // Split into runs of letters, ignoring digits and punctuation
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    words.iter().all(|w| GENERIC_WORDS.contains(w))
+}
+
+/// Make a model-suggested name safe to use as a file name, keeping `extension`.
+fn sanitize_file_name(raw: &str, extension: Option<&str>) -> Option<String> {
+    let line = raw.lines().find(|l| !l.trim().is_empty())?;
+    let mut name = line
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`' | '*'))
+        .trim();
+    // Models sometimes echo the extension back
+    if let Some(ext) = extension {
+        let suffix = format!(".{}", ext.to_lowercase());
+        if name.to_lowercase().ends_with(&suffix) {
+            name = &name[..name.len() - suffix.len()];
+        }
+    }
+
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let stem: String = cleaned.chars().take(MAX_NAME_CHARS).collect();
+    let stem = stem.trim().trim_matches('.').trim();
+    if stem.is_empty() {
+        return None;
+    }
+    Some(match extension {
+        Some(ext) => format!("{}.{}", stem, ext),
+        None => stem.to_string(),
+    })
+}
+
+/// Check a user- or model-chosen name before renaming: a single path
+/// component, keeping the file's extension when none is given.
+fn validate_new_name(new_name: &str, extension: Option<&str>) -> Result<String> {
+    let name = new_name.trim();
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\'])
+        || name.chars().any(char::is_control)
+    {
+        return Err(GhostError::Agent(format!(
+            "Invalid file name: '{}'",
+            new_name
+        )));
+    }
+    let has_extension = Path::new(name).extension().is_some();
+    Ok(match extension {
+        Some(ext) if !has_extension => format!("{}.{}", name, ext),
+        _ => name.to_string(),
+    })
+}
+
+fn watched_roots(state: &AppState) -> Result<Vec<String>> {
+    Ok(state
+        .settings
+        .lock()
+        .map_err(|e| GhostError::Agent(e.to_string()))?
+        .watched_directories
+        .clone())
+}
+
+/// The start of a file's text: from the index when it has been indexed,
+/// otherwise extracted now.
+async fn excerpt(state: &AppState, path: &Path) -> Result<String> {
+    let indexed = state
+        .db
+        .get_document_by_path(&path.to_string_lossy())?
+        .map(|(id, _)| state.db.get_document_chunks(id))
+        .transpose()?
+        .filter(|chunks| !chunks.is_empty());
+    let text = match indexed {
+        Some(chunks) => chunks
+            .into_iter()
+            .map(|(_, content)| content)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => {
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || crate::indexer::extractor::extract_text(&path))
+                .await
+                .map_err(|e| GhostError::Agent(e.to_string()))??
+        }
+    };
+    Ok(text.chars().take(MAX_EXCERPT_CHARS).collect())
+}
+
+/// Ask the chat model for a descriptive name for one file.
+async fn suggest_for(state: &AppState, path: &Path) -> Result<FilenameSuggestion> {
+    let current_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path.extension().and_then(|e| e.to_str());
+    let text = excerpt(state, path).await?;
+    if text.trim().is_empty() {
+        return Err(GhostError::Agent(format!(
+            "No text to name {} from",
+            current_name
+        )));
+    }
+
+    let prompt = [
+        ChatMessage {
+            role: "system".into(),
+            content: SUGGEST_PROMPT.into(),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: format!(
+                "Current name: {}\n\n<content>\n{}\n</content>",
+                current_name, text
+            ),
+        },
+    ];
+    let response = state.chat_engine.chat(&prompt, SUGGEST_MAX_TOKENS).await?;
+    let suggested_name = sanitize_file_name(&response.content, extension)
+        .ok_or_else(|| GhostError::Agent("The model returned no usable name".into()))?;
+
+    Ok(FilenameSuggestion {
+        path: path.to_string_lossy().into_owned(),
+        current_name,
+        suggested_name,
+    })
+}
+
+/// Suggest a descriptive name for a file inside the watched directories.
+pub async fn suggest_filename(state: &AppState, path: &str) -> Result<FilenameSuggestion> {
+    let path = super::safety::resolve_sandboxed_path(path, &watched_roots(state)?)
+        .map_err(GhostError::Agent)?;
+    if !path.is_file() {
+        return Err(GhostError::Agent(format!("Not a file: {}", path.display())));
+    }
+    suggest_for(state, &path).await
+}
+
+/// Suggest names for the files directly inside `folder` (up to
+/// [`MAX_BATCH_FILES`]). Unless `include_all` is set, only files with
+/// generic names are considered. Files that can't be named are skipped.
+pub async fn suggest_folder_filenames(
+    state: &AppState,
+    folder: &str,
+    include_all: bool,
+) -> Result<Vec<FilenameSuggestion>> {
+    let folder = super::safety::resolve_sandboxed_path(folder, &watched_roots(state)?)
+        .map_err(GhostError::Agent)?;
+    if !folder.is_dir() {
+        return Err(GhostError::Agent(format!(
+            "Not a folder: {}",
+            folder.display()
+        )));
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&folder)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let supported = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(crate::indexer::extractor::is_supported_extension);
+            let generic = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(is_generic_name);
+            supported && (include_all || generic)
+        })
+        .collect();
+    files.sort();
+    files.truncate(MAX_BATCH_FILES);
+
+    let mut suggestions = Vec::new();
+    for path in files {
+        match suggest_for(state, &path).await {
+            Ok(suggestion) if suggestion.suggested_name != suggestion.current_name => {
+                suggestions.push(suggestion)
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("No name suggestion for {}: {}", path.display(), e),
+        }
+    }
+    Ok(suggestions)
+}
+
+/// Rename a file inside the watched directories to `new_name` (same folder,
+/// never overwriting) and move its index entry along. Returns the new path.
+pub async fn rename_file(state: &AppState, path: &str, new_name: &str) -> Result<String> {
+    let old_path = super::safety::resolve_sandboxed_path(path, &watched_roots(state)?)
+        .map_err(GhostError::Agent)?;
+    if !old_path.is_file() {
+        return Err(GhostError::Agent(format!(
+            "Not a file: {}",
+            old_path.display()
+        )));
+    }
+    let extension = old_path.extension().and_then(|e| e.to_str());
+    let name = validate_new_name(new_name, extension)?;
+    let new_path = old_path.with_file_name(&name);
+    if new_path.exists() {
+        return Err(GhostError::Agent(format!(
+            "{} already exists; choose another name",
+            new_path.display()
+        )));
+    }
+
+    tokio::fs::rename(&old_path, &new_path).await?;
+    tracing::info!("Renamed {} -> {}", old_path.display(), new_path.display());
+
+    // Drop the old entry (under either spelling of the path) and index the new one
+    for old in [path.to_string(), old_path.to_string_lossy().into_owned()] {
+        if let Some((doc_id, _)) = state.db.get_document_by_path(&old)? {
+            state.db.delete_document(doc_id)?;
+        }
+    }
+    if let Err(e) = crate::indexer::index_file(&state.db, &state.embedding_engine, &new_path).await
+    {
+        tracing::warn!("Renamed file not indexed yet: {}", e);
+    }
+
+    Ok(new_path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_generic_name() {
+        for name in [
+            "Scan_0001",
+            "IMG_20240301_1200",
+            "DSC01234",
+            "Untitled (3)",
+            "document-final-v2",
+            "Screenshot 2024-03-01 at 10.15.22",
+            "20240301",
+        ] {
+            assert!(is_generic_name(name), "{} should be generic", name);
+        }
+        for name in ["Lease Agreement", "scan_of_passport", "budget-2024"] {
+            assert!(!is_generic_name(name), "{} should not be generic", name);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(
+            sanitize_file_name("\"2024-03 ACME Bill.pdf\"\n", Some("pdf")).as_deref(),
+            Some("2024-03 ACME Bill.pdf")
+        );
+        assert_eq!(
+            sanitize_file_name("\nInvoice: 12/34   Acme", Some("PDF")).as_deref(),
+            Some("Invoice 12 34 Acme.PDF")
+        );
+        assert_eq!(sanitize_file_name("notes", None).as_deref(), Some("notes"));
+        assert!(sanitize_file_name("  \n\"\" ", Some("pdf")).is_none());
+    }
+
+    #[test]
+    fn test_validate_new_name() {
+        assert_eq!(
+            validate_new_name("Lease 2024", Some("pdf")).unwrap(),
+            "Lease 2024.pdf"
+        );
+        assert_eq!(
+            validate_new_name("Lease 2024.docx", Some("pdf")).unwrap(),
+            "Lease 2024.docx"
+        );
+        assert!(validate_new_name("../escape", Some("pdf")).is_err());
+        assert!(validate_new_name("..", None).is_err());
+        assert!(validate_new_name("  ", None).is_err());
+    }
+}
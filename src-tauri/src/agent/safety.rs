@@ -7,11 +7,22 @@
 //!
 //! Safe tools auto-execute. Moderate/Dangerous tools can be configured
 //! to require user approval via A2UI Action Preview.
+//!
+//! The file and document tools (`ghost_read_file`, `ghost_write_file`,
+//! `ghost_read_document`, `ghost_edit_document`) are additionally confined to
+//! the watched directories by [`resolve_sandboxed_path`].
+//!
+//! User-defined guardrails ([`GuardrailRule`]) are checked before every tool
+//! call by [`check_guardrails`]; a hit can deny the call or force a prompt.
+
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use super::config::{CommandPolicy, GuardrailAction, GuardrailCondition, GuardrailRule};
+
 /// Risk level for a tool execution.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RiskLevel {
     /// Read-only, no side effects. Auto-approved.
@@ -32,6 +43,22 @@ pub fn classify_risk(tool_name: &str, arguments: &serde_json::Value) -> RiskLeve
             RiskLevel::Safe
         }
 
+        // Read-only, but can see every conversation, tool call and chunk
+        "ghost_sql_query" => RiskLevel::Moderate,
+        "ghost_collect_documents" => RiskLevel::Safe,
+
+        // Sandboxed to the watched directories
+        "ghost_read_document" => RiskLevel::Safe,
+        "ghost_edit_document" => RiskLevel::Moderate,
+        // Writes a new note in a watched folder, never overwriting
+        "ghost_distill_conversation" => RiskLevel::Moderate,
+        // Reads content to propose names; renaming stays in the same folder
+        "ghost_suggest_filename" => RiskLevel::Safe,
+        "ghost_rename_file" => RiskLevel::Moderate,
+        // Runs on the local model; touches nothing
+        "ghost_translate" => RiskLevel::Safe,
+        "ghost_query_knowledge_graph" => RiskLevel::Safe,
+
         // Built-in moderate tools (file writes)
         "ghost_write_file" => {
             // Check if writing to sensitive locations
@@ -54,6 +81,13 @@ pub fn classify_risk(tool_name: &str, arguments: &serde_json::Value) -> RiskLeve
             RiskLevel::Dangerous // All commands are at least Dangerous
         }
 
+        // Delegation to a user-configured A2A agent: the task text leaves Ghost,
+        // but only to peers the user explicitly added.
+        "ghost_delegate_task" => RiskLevel::Moderate,
+
+        // A local child run; each of its tool calls is classified on its own.
+        "ghost_spawn_subagent" => RiskLevel::Safe,
+
         // MCP external tools — classify by name heuristics
         name => classify_external_tool(name, arguments),
     }
@@ -127,6 +161,331 @@ fn is_sensitive_path(path: &str) -> bool {
     sensitive_patterns.iter().any(|p| lower.contains(p))
 }
 
+/// Resolve a path for the sandboxed document tools.
+///
+/// The path is canonicalized (resolving `..` and symlinks) and must land
+/// inside one of `allowed_roots` (the watched directories). Paths that don't
+/// exist yet are resolved through their nearest existing ancestor, so new
+/// files can be created but symlinks can't be used to escape the sandbox.
+/// Sensitive files are rejected even inside a watched directory.
+pub fn resolve_sandboxed_path(path: &str, allowed_roots: &[String]) -> Result<PathBuf, String> {
+    let requested = Path::new(path);
+    if !requested.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path));
+    }
+    let resolved = resolve_path(requested)?;
+
+    let inside = allowed_roots
+        .iter()
+        .filter_map(|root| Path::new(root).canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if !inside {
+        return Err(format!(
+            "{} is outside the watched directories; add its folder in Settings to allow access",
+            path
+        ));
+    }
+    if is_sensitive_path(&resolved.to_string_lossy()) {
+        return Err(format!("Access to sensitive file denied: {}", path));
+    }
+    Ok(resolved)
+}
+
+/// Canonicalize a path that may not exist yet through its nearest existing
+/// ancestor, so `..` and symlinks can't hide where it points.
+fn resolve_path(requested: &Path) -> Result<PathBuf, String> {
+    // Walk up to the nearest existing ancestor, remembering the missing tail.
+    let mut existing = requested.to_path_buf();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.file_name(), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                missing.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => return Err(format!("Invalid path: {}", requested.display())),
+        }
+    }
+    let mut resolved = existing
+        .canonicalize()
+        .map_err(|e| format!("Cannot resolve {}: {}", requested.display(), e))?;
+    for name in missing.iter().rev() {
+        resolved.push(name);
+    }
+    Ok(resolved)
+}
+
+/// A guardrail that matched a tool call (the `guardrail_hit` event payload).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GuardrailHit {
+    pub rule: String,
+    pub action: GuardrailAction,
+    pub reason: String,
+}
+
+/// Evaluate the enabled `rules` against a tool call, returning every hit.
+pub fn check_guardrails(
+    tool_name: &str,
+    arguments: &serde_json::Value,
+    rules: &[GuardrailRule],
+    offline_mode: bool,
+) -> Vec<GuardrailHit> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled && rule_covers(rule, tool_name, arguments))
+        .filter_map(|rule| {
+            guardrail_reason(&rule.condition, arguments, offline_mode).map(|reason| GuardrailHit {
+                rule: rule.name.clone(),
+                action: rule.action,
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn rule_covers(rule: &GuardrailRule, tool_name: &str, arguments: &serde_json::Value) -> bool {
+    rule.tools.is_empty()
+        || rule.tools.iter().any(|pattern| match pattern.as_str() {
+            "@network" => is_network_tool(tool_name, arguments),
+            _ => match pattern.strip_suffix('*') {
+                Some(prefix) => tool_name.starts_with(prefix),
+                None => tool_name == pattern,
+            },
+        })
+}
+
+/// Why `condition` holds for the call, or `None` when it doesn't.
+fn guardrail_reason(
+    condition: &GuardrailCondition,
+    arguments: &serde_json::Value,
+    offline_mode: bool,
+) -> Option<String> {
+    match condition {
+        GuardrailCondition::Always => Some("the tool is restricted".into()),
+        GuardrailCondition::Offline => offline_mode.then(|| "offline mode is on".into()),
+        GuardrailCondition::OutsideDirectories { directories } => {
+            let home = dirs::home_dir().unwrap_or_default();
+            let expand = |p: &str| match p.strip_prefix('~') {
+                Some(rest) => home.join(rest.trim_start_matches(['/', '\\'])),
+                None => PathBuf::from(p),
+            };
+            let roots: Vec<PathBuf> = directories
+                .iter()
+                .filter_map(|d| expand(d).canonicalize().ok())
+                .collect();
+            path_arguments(arguments).into_iter().find_map(|path| {
+                let requested = expand(path);
+                let inside = requested.is_absolute()
+                    && resolve_path(&requested)
+                        .is_ok_and(|resolved| roots.iter().any(|root| resolved.starts_with(root)));
+                (!inside).then(|| format!("{} is outside the allowed directories", path))
+            })
+        }
+        GuardrailCondition::ArgumentMatches { pattern, argument } => {
+            let regex = match regex::Regex::new(pattern) {
+                Ok(regex) => regex,
+                // Fail closed: a broken rule shouldn't silently stop guarding
+                Err(e) => return Some(format!("its pattern is invalid ({})", e)),
+            };
+            let text = match argument {
+                Some(name) => match arguments.get(name)? {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                },
+                None => arguments.to_string(),
+            };
+            regex.is_match(&text).then(|| match argument {
+                Some(name) => format!("'{}' matches /{}/", name, pattern),
+                None => format!("the arguments match /{}/", pattern),
+            })
+        }
+    }
+}
+
+/// String arguments that name a file or directory (`path`, `working_dir`, ...).
+fn path_arguments(arguments: &serde_json::Value) -> Vec<&str> {
+    arguments
+        .as_object()
+        .map(|args| {
+            args.iter()
+                .filter(|(key, _)| {
+                    key.ends_with("path") || key.ends_with("dir") || key.ends_with("directory")
+                })
+                .filter_map(|(_, value)| value.as_str())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a tool call reaches the network (the `@network` guardrail selector).
+pub fn is_network_tool(tool_name: &str, arguments: &serde_json::Value) -> bool {
+    match tool_name {
+        "ghost_delegate_task" => true,
+        "ghost_run_command" => {
+            let command = arguments
+                .get("command")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_lowercase();
+            [
+                "curl",
+                "wget",
+                "ssh",
+                "scp",
+                "rsync",
+                "git clone",
+                "git fetch",
+                "git pull",
+                "git push",
+                "npm install",
+                "pip install",
+                "ping",
+            ]
+            .iter()
+            .any(|p| command.contains(p))
+        }
+        name if name.starts_with("ghost_") => false,
+        name => {
+            let lower = name.to_lowercase();
+            [
+                "fetch", "http", "web", "browse", "url", "download", "upload", "email", "mail",
+                "slack", "request",
+            ]
+            .iter()
+            .any(|p| lower.contains(p))
+        }
+    }
+}
+
+/// Commands `ghost_run_command` refuses outright, even with approval.
+/// Matched anywhere in the command (see [`command_matches`]).
+const BLOCKED_COMMANDS: &[&str] = &[
+    "rm -rf /",
+    "rm -fr /",
+    "rm -rf ~",
+    "rm -fr ~",
+    "rm -rf $home",
+    ":(){",
+    "of=/dev/sd",
+    "of=/dev/nvme",
+    "of=/dev/disk",
+    "> /dev/sd",
+    "> /dev/nvme",
+    "format c:",
+    "del /s /q c:\\",
+];
+
+/// Programs `ghost_run_command` refuses to run, even with approval. Matched
+/// against the program of each pipeline segment, so `rg reboot` or
+/// `cat src/shutdown.rs` still run. `mkfs` also covers `mkfs.ext4` etc.
+const BLOCKED_PROGRAMS: &[&str] = &["mkfs", "shutdown", "reboot", "poweroff"];
+
+/// Wrappers that run the next word as the program.
+const COMMAND_PREFIXES: &[&str] = &["sudo", "doas", "exec", "nohup", "env", "command"];
+
+/// The program run by each `;`, `&&`, `||` or `|` segment of `cmd`, without
+/// its directory (`/sbin/reboot` -> `reboot`).
+fn segment_programs(cmd: &str) -> impl Iterator<Item = &str> {
+    cmd.split(|c: char| matches!(c, ';' | '&' | '|' | '\n' | '(' | ')' | '`'))
+        .filter_map(|segment| {
+            segment.split_whitespace().find(|word| {
+                !COMMAND_PREFIXES.contains(word) && !word.starts_with('-') && !word.contains('=')
+            })
+        })
+        .map(|program| program.rsplit('/').next().unwrap_or(program))
+}
+
+/// The blocked program `cmd` runs, if any.
+fn blocked_program(cmd: &str) -> Option<&'static str> {
+    segment_programs(cmd).find_map(|program| {
+        BLOCKED_PROGRAMS.iter().copied().find(|blocked| {
+            program == *blocked
+                || program
+                    .strip_prefix(blocked)
+                    .is_some_and(|r| r.starts_with('.'))
+        })
+    })
+}
+
+/// Whether `pattern` occurs in `cmd`. Patterns ending in a path root
+/// (`/`, `~`) only match the root itself, so `rm -rf /` doesn't block
+/// `rm -rf /tmp/build`.
+fn command_matches(cmd: &str, pattern: &str) -> bool {
+    if !(pattern.ends_with('/') || pattern.ends_with('~')) {
+        return cmd.contains(pattern);
+    }
+    cmd.match_indices(pattern)
+        .any(|(i, _)| match cmd[i + pattern.len()..].chars().next() {
+            None => true,
+            Some(c) => c.is_whitespace() || matches!(c, '*' | ';' | '&' | '|'),
+        })
+}
+
+/// Check a shell command against the command policy.
+///
+/// Rejects commands on the built-in or user deny list, and working
+/// directories outside the allowed roots (home directory by default).
+/// Returns the canonical working directory to run in.
+pub fn check_command_policy(
+    command: &str,
+    working_dir: &str,
+    policy: &CommandPolicy,
+) -> Result<PathBuf, String> {
+    let normalized = command
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if let Some(pattern) = BLOCKED_COMMANDS
+        .iter()
+        .copied()
+        .find(|p| command_matches(&normalized, p))
+        .or_else(|| blocked_program(&normalized))
+    {
+        return Err(format!("Command blocked by safety policy ({})", pattern));
+    }
+    if let Some(pattern) = policy
+        .denied_commands
+        .iter()
+        .find(|p| !p.trim().is_empty() && normalized.contains(&p.trim().to_lowercase()))
+    {
+        return Err(format!("Command blocked by your deny list ({})", pattern));
+    }
+
+    let home = dirs::home_dir().unwrap_or_default();
+    let expand = |p: &str| match p.strip_prefix('~') {
+        Some(rest) => home.join(rest.trim_start_matches(['/', '\\'])),
+        None => PathBuf::from(p),
+    };
+    let cwd = expand(working_dir)
+        .canonicalize()
+        .map_err(|_| format!("Working directory does not exist: {}", working_dir))?;
+    if !cwd.is_dir() {
+        return Err(format!("Not a directory: {}", working_dir));
+    }
+
+    let roots: Vec<PathBuf> = if policy.allowed_working_dirs.is_empty() {
+        vec![home]
+    } else {
+        policy
+            .allowed_working_dirs
+            .iter()
+            .map(|p| expand(p))
+            .collect()
+    };
+    let allowed = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| cwd.starts_with(root));
+    if !allowed {
+        return Err(format!(
+            "Working directory {} is outside the allowed directories",
+            cwd.display()
+        ));
+    }
+    Ok(cwd)
+}
+
 /// Check if a shell command is destructive.
 fn is_destructive_command(cmd: &str) -> bool {
     let lower = cmd.to_lowercase();
@@ -316,6 +675,91 @@ pub fn describe_action(tool_name: &str, arguments: &serde_json::Value) -> String
             format!("Run command: {}", command)
         }
         "ghost_index_status" => "Check indexing status".into(),
+        "ghost_collect_documents" => {
+            match arguments
+                .get("query")
+                .and_then(|v| v.as_str())
+                .filter(|q| !q.trim().is_empty())
+            {
+                Some(query) => format!("List your documents about: \"{}\"", query),
+                None => "List your documents".into(),
+            }
+        }
+        "ghost_sql_query" => {
+            let query = arguments
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            format!("Query the vault database: {}", query)
+        }
+        "ghost_read_document" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            format!("Read document: {}", path)
+        }
+        "ghost_edit_document" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            match arguments.get("edits").and_then(|v| v.as_array()) {
+                Some(edits) => format!("Apply {} edit(s) to: {}", edits.len(), path),
+                None => format!("Write document: {}", path),
+            }
+        }
+        "ghost_distill_conversation" => {
+            let destination = arguments
+                .get("destination")
+                .and_then(|v| v.as_str())
+                .unwrap_or("the first watched folder");
+            format!("Save a note of this conversation to: {}", destination)
+        }
+        "ghost_suggest_filename" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            format!("Suggest file names for: {}", path)
+        }
+        "ghost_translate" => {
+            let target = arguments
+                .get("target_language")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            format!("Translate text into {}", target)
+        }
+        "ghost_query_knowledge_graph" => {
+            let query = arguments
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            format!("Look up '{}' in the knowledge graph", query)
+        }
+        "ghost_rename_file" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            let new_name = arguments
+                .get("new_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            format!("Rename {} to \"{}\"", path, new_name)
+        }
+        "ghost_delegate_task" => {
+            let agent = arguments
+                .get("agent")
+                .and_then(|v| v.as_str())
+                .unwrap_or("...");
+            let task = arguments.get("task").and_then(|v| v.as_str()).unwrap_or("");
+            format!("Delegate to agent '{}': {}", agent, task)
+        }
+        "ghost_spawn_subagent" => {
+            let task = arguments.get("task").and_then(|v| v.as_str()).unwrap_or("");
+            format!("Spawn a sub-agent: {}", task)
+        }
         _ => format!(
             "Execute tool '{}' with arguments: {}",
             tool_name,
@@ -347,6 +791,10 @@ mod tests {
             classify_risk("ghost_index_status", &json!({})),
             RiskLevel::Safe
         );
+        assert_eq!(
+            classify_risk("ghost_spawn_subagent", &json!({"task": "summarize"})),
+            RiskLevel::Safe
+        );
     }
 
     #[test]
@@ -358,6 +806,10 @@ mod tests {
             ),
             RiskLevel::Moderate
         );
+        assert_eq!(
+            classify_risk("ghost_sql_query", &json!({"query": "SELECT 1"})),
+            RiskLevel::Moderate
+        );
     }
 
     #[test]
@@ -431,6 +883,142 @@ mod tests {
         assert!(!is_destructive_command("docker ps"));
     }
 
+    #[test]
+    fn test_sandboxed_path() {
+        let root = std::env::temp_dir().join(format!("ghost_test_sandbox_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        std::fs::write(root.join("notes/a.md"), "hi").unwrap();
+        let roots = vec![root.join("notes").to_string_lossy().to_string()];
+        let path = |p: &str| root.join(p).to_string_lossy().to_string();
+
+        // Existing and not-yet-created files inside the root are allowed
+        assert!(resolve_sandboxed_path(&path("notes/a.md"), &roots).is_ok());
+        assert!(resolve_sandboxed_path(&path("notes/new/b.md"), &roots).is_ok());
+        // Traversal out of the root is rejected
+        assert!(resolve_sandboxed_path(&path("notes/../outside.md"), &roots).is_err());
+        assert!(resolve_sandboxed_path(&path("notes/new/../../x.md"), &roots).is_err());
+        assert!(resolve_sandboxed_path(&path("other.md"), &roots).is_err());
+        // Relative paths and sensitive files are rejected
+        assert!(resolve_sandboxed_path("notes/a.md", &roots).is_err());
+        assert!(resolve_sandboxed_path(&path("notes/.env"), &roots).is_err());
+        // No watched directories → nothing is allowed
+        assert!(resolve_sandboxed_path(&path("notes/a.md"), &[]).is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&root, root.join("notes/escape")).unwrap();
+            assert!(resolve_sandboxed_path(&path("notes/escape/other.md"), &roots).is_err());
+        }
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_command_policy_deny_list() {
+        let policy = CommandPolicy {
+            allowed_working_dirs: vec![std::env::temp_dir().to_string_lossy().to_string()],
+            denied_commands: vec!["git push".into()],
+            ..Default::default()
+        };
+        let tmp = std::env::temp_dir().to_string_lossy().to_string();
+
+        assert!(check_command_policy("ls -la", &tmp, &policy).is_ok());
+        assert!(check_command_policy("rm -rf ./build", &tmp, &policy).is_ok());
+        assert!(check_command_policy("rm  -rf   /", &tmp, &policy).is_err());
+        assert!(check_command_policy("sudo rm -rf /*", &tmp, &policy).is_err());
+        assert!(check_command_policy("rm -rf ~ ", &tmp, &policy).is_err());
+        assert!(check_command_policy("mkfs.ext4 /dev/sda1", &tmp, &policy).is_err());
+        assert!(check_command_policy("dd if=/dev/zero of=/dev/sda", &tmp, &policy).is_err());
+        assert!(check_command_policy("sudo shutdown -h now", &tmp, &policy).is_err());
+        assert!(check_command_policy("make && /sbin/reboot", &tmp, &policy).is_err());
+        assert!(check_command_policy("echo hi | poweroff", &tmp, &policy).is_err());
+        // Blocked program names as arguments are fine
+        assert!(check_command_policy("git log --grep shutdown", &tmp, &policy).is_ok());
+        assert!(check_command_policy("cat src/shutdown.rs", &tmp, &policy).is_ok());
+        assert!(check_command_policy("rg reboot | head", &tmp, &policy).is_ok());
+        assert!(check_command_policy("ls mkfs-notes", &tmp, &policy).is_ok());
+        // User deny list
+        assert!(check_command_policy("GIT PUSH origin main", &tmp, &policy)
+            .unwrap_err()
+            .contains("deny list"));
+    }
+
+    #[test]
+    fn test_command_policy_working_dir() {
+        let root = std::env::temp_dir().join(format!("ghost_test_cwd_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("project")).unwrap();
+        let policy = CommandPolicy {
+            allowed_working_dirs: vec![root.join("project").to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        let dir = |p: &str| root.join(p).to_string_lossy().to_string();
+
+        assert!(check_command_policy("ls", &dir("project"), &policy).is_ok());
+        assert!(check_command_policy("ls", &dir("project/.."), &policy).is_err());
+        assert!(check_command_policy("ls", &dir("missing"), &policy).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_guardrails() {
+        let root = std::env::temp_dir().join(format!("ghost_test_guard_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("project")).unwrap();
+        let rules = vec![
+            GuardrailRule {
+                name: "project only".into(),
+                tools: vec!["ghost_write_*".into()],
+                condition: GuardrailCondition::OutsideDirectories {
+                    directories: vec![root.join("project").to_string_lossy().to_string()],
+                },
+                action: GuardrailAction::Deny,
+                enabled: true,
+            },
+            GuardrailRule {
+                name: "no force push".into(),
+                tools: vec!["ghost_run_command".into()],
+                condition: GuardrailCondition::ArgumentMatches {
+                    pattern: r"push\s+(-f|--force)".into(),
+                    argument: Some("command".into()),
+                },
+                action: GuardrailAction::Ask,
+                enabled: true,
+            },
+        ];
+        let write = |p: &str| json!({"path": root.join(p).to_string_lossy(), "content": ""});
+
+        assert!(
+            check_guardrails("ghost_write_file", &write("project/a.md"), &rules, false).is_empty()
+        );
+        let hits = check_guardrails("ghost_write_file", &write("project/../a.md"), &rules, false);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].action, GuardrailAction::Deny);
+        // Other tools aren't covered
+        assert!(check_guardrails("ghost_read_file", &write("a.md"), &rules, false).is_empty());
+
+        let push = |cmd: &str| json!({"command": cmd});
+        let hits = check_guardrails("ghost_run_command", &push("git push -f"), &rules, false);
+        assert_eq!(hits[0].rule, "no force push");
+        assert!(check_guardrails("ghost_run_command", &push("git push"), &rules, false).is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_offline_guardrail() {
+        let rules = crate::agent::config::AgentConfig::default().guardrails;
+        let fetch = json!({"url": "https://example.com"});
+        assert!(check_guardrails("fetch_url", &fetch, &rules, false).is_empty());
+        assert_eq!(check_guardrails("fetch_url", &fetch, &rules, true).len(), 1);
+        assert!(check_guardrails("ghost_search", &json!({}), &rules, true).is_empty());
+        let curl = json!({"command": "curl https://example.com"});
+        assert_eq!(
+            check_guardrails("ghost_run_command", &curl, &rules, true).len(),
+            1
+        );
+    }
+
     #[test]
     fn test_describe_action() {
         let desc = describe_action("ghost_search", &json!({"query": "test"}));
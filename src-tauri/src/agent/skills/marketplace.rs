@@ -0,0 +1,355 @@
+//! Skill marketplace — community skills from a curated index.
+//!
+//! The index is a JSON file fetched over HTTPS (typically from a git repo's
+//! raw files) listing each skill's metadata and the location of its SKILL.md.
+//! Installing downloads the SKILL.md, checks its SHA-256 against the index
+//! and its Ed25519 signature against the trusted keys, then writes it to
+//! `<skills_dir>/<name>/SKILL.md`, where the registry picks it up. The hash
+//! only proves the download matches the index, so skills without a trusted
+//! signature are refused unless the user allows unsigned installs.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::Engine;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::Skill;
+use crate::agent::safety::{self, RiskLevel};
+use crate::error::{GhostError, Result};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Largest index accepted, in bytes.
+const MAX_INDEX_BYTES: usize = 1024 * 1024;
+/// Largest SKILL.md accepted, in bytes.
+const MAX_SKILL_BYTES: usize = 256 * 1024;
+
+/// Where community skills come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketplaceConfig {
+    /// HTTPS URL of the skill index. Empty disables the marketplace.
+    #[serde(default = "default_index_url")]
+    pub index_url: String,
+    /// Base64 Ed25519 public keys trusted to sign skills.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+    /// Install skills that aren't signed by a trusted key, checking only
+    /// their SHA-256 against the index.
+    #[serde(default)]
+    pub allow_unsigned: bool,
+}
+
+fn default_index_url() -> String {
+    "https://raw.githubusercontent.com/ghostapp-ai/skills/main/index.json".into()
+}
+
+impl Default for MarketplaceConfig {
+    fn default() -> Self {
+        Self {
+            index_url: default_index_url(),
+            trusted_keys: Vec::new(),
+            allow_unsigned: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SkillIndex {
+    #[serde(default)]
+    skills: Vec<IndexEntry>,
+}
+
+/// A skill listed in the index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub required_tools: Vec<String>,
+    /// Capabilities the author declares, e.g. "network" or "filesystem:write".
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Location of the SKILL.md, absolute or relative to the index.
+    pub url: String,
+    /// SHA-256 (hex) of the SKILL.md.
+    pub sha256: String,
+    /// Base64 Ed25519 signature of the SKILL.md.
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// A marketplace skill as shown before installing.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketplaceSkill {
+    #[serde(flatten)]
+    pub entry: IndexEntry,
+    /// Highest risk among the required tools.
+    pub risk_level: RiskLevel,
+    pub signed: bool,
+    /// Whether `<skills_dir>/<name>/SKILL.md` exists.
+    pub installed: bool,
+}
+
+fn error(message: impl Into<String>) -> GhostError {
+    GhostError::Agent(message.into())
+}
+
+/// Fetch the index and describe its skills.
+pub async fn list(config: &MarketplaceConfig, skills_dir: &Path) -> Result<Vec<MarketplaceSkill>> {
+    let (_, entries) = fetch_index(config).await?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let installed =
+                skill_dir(skills_dir, &entry.name).is_ok_and(|dir| dir.join("SKILL.md").exists());
+            MarketplaceSkill {
+                risk_level: risk_level(&entry.required_tools),
+                signed: entry.signature.is_some(),
+                installed,
+                entry,
+            }
+        })
+        .collect())
+}
+
+/// Download, verify and install the skill `name`, replacing an installed copy.
+pub async fn install(config: &MarketplaceConfig, skills_dir: &Path, name: &str) -> Result<Skill> {
+    let (index_url, entries) = fetch_index(config).await?;
+    let entry = entries
+        .into_iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| error(format!("The marketplace has no skill '{}'", name)))?;
+    let dir = skill_dir(skills_dir, &entry.name)?;
+
+    let url = index_url
+        .join(&entry.url)
+        .map_err(|e| error(format!("Invalid skill URL '{}': {}", entry.url, e)))?;
+    let content = get_bytes(&url, MAX_SKILL_BYTES).await?;
+    verify(&entry, &content, config)?;
+
+    let text = String::from_utf8(content).map_err(|_| error("The skill is not UTF-8 text"))?;
+    let (frontmatter, _) = super::parse_frontmatter(&text).map_err(error)?;
+    if frontmatter.name.as_deref().is_some_and(|n| n != entry.name) {
+        return Err(error(format!(
+            "The skill file is named differently from its index entry '{}'",
+            entry.name
+        )));
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("SKILL.md");
+    let tmp = dir.join("SKILL.md.tmp");
+    std::fs::write(&tmp, &text)?;
+    std::fs::rename(&tmp, &path)?;
+    tracing::info!(
+        "Installed skill '{}' {} by {}",
+        entry.name,
+        entry.version,
+        entry.author
+    );
+
+    super::refresh();
+    super::parse_skill_file(&path).map_err(error)
+}
+
+async fn fetch_index(config: &MarketplaceConfig) -> Result<(Url, Vec<IndexEntry>)> {
+    if config.index_url.trim().is_empty() {
+        return Err(error("No skill marketplace is configured"));
+    }
+    let url = Url::parse(config.index_url.trim())
+        .map_err(|e| error(format!("Invalid marketplace URL: {}", e)))?;
+    let body = get_bytes(&url, MAX_INDEX_BYTES).await?;
+    let index: SkillIndex = serde_json::from_slice(&body)
+        .map_err(|e| error(format!("Invalid marketplace index: {}", e)))?;
+    Ok((url, index.skills))
+}
+
+/// GET `url` over HTTPS, refusing bodies over `max_bytes`.
+async fn get_bytes(url: &Url, max_bytes: usize) -> Result<Vec<u8>> {
+    if url.scheme() != "https" {
+        return Err(error(format!("Refusing to fetch {} over plain HTTP", url)));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let mut response = client.get(url.clone()).send().await?;
+    if !response.status().is_success() {
+        return Err(error(format!("{} returned {}", url, response.status())));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len > max_bytes as u64)
+    {
+        return Err(error(format!("{} is too large", url)));
+    }
+    // Content-Length is optional; enforce the limit while reading
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(error(format!("{} is too large", url)));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Check `content` against the entry's hash and its signature, which may
+/// only be missing when unsigned installs are allowed.
+fn verify(entry: &IndexEntry, content: &[u8], config: &MarketplaceConfig) -> Result<()> {
+    let digest = hex::encode(Sha256::digest(content));
+    if !digest.eq_ignore_ascii_case(entry.sha256.trim()) {
+        return Err(error(format!(
+            "The download of '{}' doesn't match its SHA-256 in the index",
+            entry.name
+        )));
+    }
+    let trusted_keys = &config.trusted_keys;
+    let signature = match entry.signature.as_deref() {
+        Some(signature) if !trusted_keys.is_empty() => signature,
+        _ if config.allow_unsigned => return Ok(()),
+        None => {
+            return Err(error(format!(
+                "'{}' is not signed; allow unsigned skills to install it",
+                entry.name
+            )))
+        }
+        Some(_) => {
+            return Err(error(
+                "No trusted skill signing keys are configured; add one to skill_marketplace.trusted_keys",
+            ))
+        }
+    };
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let signature = b64
+        .decode(signature.trim())
+        .map_err(|_| error(format!("'{}' has a malformed signature", entry.name)))?;
+    let trusted = trusted_keys.iter().any(|key| {
+        b64.decode(key.trim()).is_ok_and(|key| {
+            ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, key)
+                .verify(content, &signature)
+                .is_ok()
+        })
+    });
+    if trusted {
+        Ok(())
+    } else {
+        Err(error(format!(
+            "'{}' is not signed by a trusted key",
+            entry.name
+        )))
+    }
+}
+
+/// The directory a skill installs into. Names are restricted to letters,
+/// digits, `-` and `_` so they can't escape the skills directory.
+fn skill_dir(skills_dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(error(format!("Invalid skill name '{}'", name)));
+    }
+    Ok(skills_dir.join(name))
+}
+
+/// Highest risk of calling the given tools.
+fn risk_level(tools: &[String]) -> RiskLevel {
+    tools
+        .iter()
+        .map(|tool| safety::classify_risk(tool, &serde_json::json!({})))
+        .max()
+        .unwrap_or(RiskLevel::Safe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::KeyPair;
+
+    const CONTENT: &[u8] = b"---\nname: summarize\n---\nSummarize {{input}}.";
+
+    fn entry(content: &[u8]) -> IndexEntry {
+        IndexEntry {
+            name: "summarize".into(),
+            description: String::new(),
+            author: "ana".into(),
+            version: "1.0.0".into(),
+            required_tools: vec![],
+            permissions: vec![],
+            url: "summarize/SKILL.md".into(),
+            sha256: hex::encode(Sha256::digest(content)),
+            signature: None,
+        }
+    }
+
+    fn config(trusted_keys: Vec<String>, allow_unsigned: bool) -> MarketplaceConfig {
+        MarketplaceConfig {
+            trusted_keys,
+            allow_unsigned,
+            ..MarketplaceConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_verify_hash() {
+        let unsigned = config(vec![], true);
+        assert!(verify(&entry(CONTENT), CONTENT, &unsigned).is_ok());
+        assert!(verify(&entry(CONTENT), b"tampered", &unsigned).is_err());
+        // Unsigned skills are refused by default
+        assert!(verify(&entry(CONTENT), CONTENT, &MarketplaceConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key = ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let trusted = config(vec![b64.encode(key.public_key().as_ref())], false);
+
+        let mut signed = entry(CONTENT);
+        assert!(verify(&signed, CONTENT, &trusted).is_err());
+        signed.signature = Some(b64.encode(key.sign(CONTENT).as_ref()));
+        assert!(verify(&signed, CONTENT, &trusted).is_ok());
+        // A signature means nothing without a trusted key to check it against
+        assert!(verify(&signed, CONTENT, &MarketplaceConfig::default()).is_err());
+
+        let other = ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let other = ring::signature::Ed25519KeyPair::from_pkcs8(other.as_ref()).unwrap();
+        let untrusted = config(vec![b64.encode(other.public_key().as_ref())], false);
+        assert!(verify(&signed, CONTENT, &untrusted).is_err());
+    }
+
+    #[test]
+    fn test_skill_dir() {
+        let dir = Path::new("/skills");
+        assert_eq!(
+            skill_dir(dir, "code-review").unwrap(),
+            dir.join("code-review")
+        );
+        assert!(skill_dir(dir, "../etc").is_err());
+        assert!(skill_dir(dir, "").is_err());
+    }
+
+    #[test]
+    fn test_risk_level() {
+        assert_eq!(risk_level(&[]), RiskLevel::Safe);
+        let tools = vec!["ghost_search".to_string(), "ghost_run_command".to_string()];
+        assert_eq!(risk_level(&tools), RiskLevel::Dangerous);
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_refused() {
+        let url = Url::parse("http://example.com/index.json").unwrap();
+        assert!(get_bytes(&url, 10).await.is_err());
+    }
+}
@@ -0,0 +1,756 @@
+//! Skills system — SKILL.md parser and registry.
+//!
+//! Ghost skills use the Agent Skills standard (Markdown + YAML frontmatter).
+//! Skills define specialized knowledge, instructions, and tool schemas
+//! that enhance the agent's capabilities.
+//!
+//! Skills are loaded from:
+//! 1. Built-in skills (bundled with Ghost)
+//! 2. User skills (~/.ghost/skills/)
+//! 3. Project skills (.ghost/skills/ in workspace)
+//!
+//! Three-tier loading:
+//! - Metadata (~100 tokens): name, description, triggers
+//! - Instructions (<5000 tokens): full system prompt
+//! - Resources: additional context files
+//!
+//! The configured directory is loaded once by [`configure`] and reloaded
+//! whenever one of its SKILL.md files changes (desktop only, through the
+//! indexer's file watcher). Skills declaring `parameters` are templates:
+//! their `{{placeholders}}` are filled in by [`Skill::render_prompt`], and the
+//! agent can call each enabled skill as a `skill_<name>` tool. Community
+//! skills are installed from the [`marketplace`].
+
+pub mod marketplace;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+/// A loaded skill definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Skill {
+    /// Unique skill name (from filename or frontmatter).
+    pub name: String,
+    /// Short description.
+    pub description: String,
+    /// Trigger patterns — keywords/phrases that activate this skill.
+    pub triggers: Vec<String>,
+    /// The full instruction text (system prompt addition).
+    pub instructions: String,
+    /// Source path of the SKILL.md file.
+    pub source: String,
+    /// Whether this skill is currently enabled.
+    pub enabled: bool,
+    /// Tool schemas defined by this skill (optional).
+    pub tools: Vec<SkillTool>,
+    /// Parameters of the skill's template.
+    #[serde(default)]
+    pub parameters: Vec<SkillParameter>,
+    /// Tools the skill needs; a run of the skill may only use these.
+    #[serde(default)]
+    pub required_tools: Vec<String>,
+}
+
+/// A template parameter declared in a skill's frontmatter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// JSON Schema type of the value ("string", "number", "boolean"...).
+    #[serde(rename = "type", default = "default_parameter_type")]
+    pub param_type: String,
+    #[serde(default = "default_true")]
+    pub required: bool,
+    /// Value used when the parameter isn't given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+}
+
+fn default_parameter_type() -> String {
+    "string".into()
+}
+
+/// A tool defined within a skill.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillTool {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// Name of the free-form prompt argument appended to every skill prompt.
+pub const PROMPT_INPUT_ARG: &str = "input";
+
+impl Skill {
+    /// Name of the agent tool that runs this skill: `skill_` and the skill
+    /// name, with characters other than letters, digits and `_` replaced.
+    pub fn tool_name(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("skill_{}", name)
+    }
+
+    /// Names of `{{placeholder}}` arguments used in the instructions, in order of first use.
+    pub fn prompt_arguments(&self) -> Vec<String> {
+        placeholders(&self.instructions)
+    }
+
+    /// The template's parameters: the declared ones, then any undeclared
+    /// placeholders as required strings.
+    pub fn template_parameters(&self) -> Vec<SkillParameter> {
+        let mut parameters = self.parameters.clone();
+        for name in self.prompt_arguments() {
+            if !parameters.iter().any(|p| p.name == name) {
+                parameters.push(SkillParameter {
+                    name,
+                    description: String::new(),
+                    param_type: default_parameter_type(),
+                    required: true,
+                    default: None,
+                });
+            }
+        }
+        parameters
+    }
+
+    /// JSON Schema of the skill's arguments, for calling it as a tool.
+    pub fn input_schema(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for parameter in self.template_parameters() {
+            let mut property = serde_json::json!({ "type": parameter.param_type });
+            if !parameter.description.is_empty() {
+                property["description"] = parameter.description.clone().into();
+            }
+            if let Some(default) = &parameter.default {
+                property["default"] = default.clone();
+            } else if parameter.required {
+                required.push(parameter.name.clone());
+            }
+            properties.insert(parameter.name, property);
+        }
+        if !properties.contains_key(PROMPT_INPUT_ARG) {
+            properties.insert(
+                PROMPT_INPUT_ARG.into(),
+                serde_json::json!({
+                    "type": "string",
+                    "description": "The task or content to apply the skill to"
+                }),
+            );
+        }
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+
+    /// Render the skill as a reusable prompt (e.g. for MCP `prompts/get`).
+    ///
+    /// Substitutes the template's parameters (missing optional ones take
+    /// their default, or stay empty) and appends the optional `input`
+    /// argument as the task when the instructions don't use it.
+    pub fn render_prompt(&self, args: &HashMap<String, String>) -> Result<String, String> {
+        let mut text = self.instructions.clone();
+        for parameter in self.template_parameters() {
+            let value = match (args.get(&parameter.name), &parameter.default) {
+                (Some(value), _) => value.clone(),
+                (None, Some(default)) => value_to_string(default.clone()),
+                (None, None) if parameter.required => {
+                    return Err(format!("Missing required argument '{}'", parameter.name));
+                }
+                (None, None) => String::new(),
+            };
+            text = text
+                .replace(&format!("{{{{{}}}}}", parameter.name), &value)
+                .replace(&format!("{{{{ {} }}}}", parameter.name), &value);
+        }
+        if !self.instructions.contains("{{input}}") && !self.instructions.contains("{{ input }}") {
+            if let Some(input) = args.get(PROMPT_INPUT_ARG).filter(|v| !v.trim().is_empty()) {
+                text.push_str("\n\n## Task\n\n");
+                text.push_str(input);
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// Names of the `{{placeholder}}`s in `instructions`, in order of first use.
+fn placeholders(instructions: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = instructions;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if valid && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Template arguments from JSON values (e.g. tool call arguments); strings
+/// are used as-is, other values as JSON.
+pub fn string_args(
+    args: impl IntoIterator<Item = (String, serde_json::Value)>,
+) -> HashMap<String, String> {
+    args.into_iter()
+        .map(|(k, v)| (k, value_to_string(v)))
+        .collect()
+}
+
+fn value_to_string(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// YAML frontmatter parsed from a SKILL.md file.
+#[derive(Debug, Clone, Deserialize)]
+struct SkillFrontmatter {
+    name: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    triggers: Vec<String>,
+    #[serde(default)]
+    tools: Vec<SkillToolDef>,
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default)]
+    parameters: Vec<SkillParameter>,
+    #[serde(default)]
+    required_tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SkillToolDef {
+    name: String,
+    description: Option<String>,
+    parameters: Option<serde_json::Value>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The skill registry — holds all loaded skills.
+#[derive(Debug, Clone, Default)]
+pub struct SkillRegistry {
+    skills: HashMap<String, Skill>,
+}
+
+impl SkillRegistry {
+    pub fn new() -> Self {
+        Self {
+            skills: HashMap::new(),
+        }
+    }
+
+    /// Load skills from a directory (scans for SKILL.md files).
+    pub fn load_from_directory(&mut self, dir: &Path) -> Vec<String> {
+        let mut loaded = Vec::new();
+
+        if !dir.exists() {
+            tracing::debug!("Skills directory does not exist: {}", dir.display());
+            return loaded;
+        }
+
+        // Scan for SKILL.md files (direct children and one level deep)
+
+        // Direct SKILL.md files in dir
+        if let Ok(skill) = parse_skill_file(&dir.join("SKILL.md")) {
+            self.skills.insert(skill.name.clone(), skill.clone());
+            loaded.push(skill.name);
+        }
+
+        // Subdirectory SKILL.md files
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    let skill_file = path.join("SKILL.md");
+                    if skill_file.exists() {
+                        match parse_skill_file(&skill_file) {
+                            Ok(skill) => {
+                                loaded.push(skill.name.clone());
+                                self.skills.insert(skill.name.clone(), skill);
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Failed to parse skill {}: {}",
+                                    skill_file.display(),
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        loaded
+    }
+
+    /// Get all enabled skills.
+    pub fn enabled_skills(&self) -> Vec<&Skill> {
+        self.skills.values().filter(|s| s.enabled).collect()
+    }
+
+    /// Find skills matching a user query (by triggers).
+    pub fn match_skills(&self, query: &str) -> Vec<&Skill> {
+        let lower = query.to_lowercase();
+        self.skills
+            .values()
+            .filter(|s| s.enabled && s.triggers.iter().any(|t| lower.contains(&t.to_lowercase())))
+            .collect()
+    }
+
+    /// Get a skill by name.
+    pub fn get(&self, name: &str) -> Option<&Skill> {
+        self.skills.get(name)
+    }
+
+    /// Get all skills (including disabled).
+    pub fn all_skills(&self) -> Vec<&Skill> {
+        self.skills.values().collect()
+    }
+
+    /// Count of loaded skills.
+    pub fn count(&self) -> usize {
+        self.skills.len()
+    }
+
+    /// Build system prompt additions from matched skills.
+    pub fn build_prompt_for_query(&self, query: &str) -> String {
+        let matched = self.match_skills(query);
+        if matched.is_empty() {
+            return String::new();
+        }
+
+        let mut prompt = String::from("\n\n## Active Skills\n\n");
+        for skill in matched {
+            prompt.push_str(&format!("### {}\n{}\n\n", skill.name, skill.instructions));
+        }
+        prompt
+    }
+}
+
+/// The skills of the configured directory.
+#[derive(Default)]
+struct LoadedSkills {
+    dir: String,
+    registry: SkillRegistry,
+    /// Bumped by [`configure`]; watchers of an older directory stop.
+    generation: u64,
+}
+
+static LOADED: LazyLock<RwLock<LoadedSkills>> =
+    LazyLock::new(|| RwLock::new(LoadedSkills::default()));
+
+/// Load the skills of `skills_dir` and watch it for changes. Does nothing
+/// when the directory is already loaded.
+pub fn configure(skills_dir: &str) {
+    let generation = {
+        let mut loaded = LOADED.write().unwrap_or_else(|e| e.into_inner());
+        if loaded.generation > 0 && loaded.dir == skills_dir {
+            return;
+        }
+        loaded.dir = skills_dir.to_string();
+        loaded.generation += 1;
+        loaded.generation
+    };
+    reload(skills_dir, generation);
+    #[cfg(desktop)]
+    watch(skills_dir, generation);
+}
+
+/// The loaded skills.
+pub fn loaded() -> SkillRegistry {
+    LOADED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .registry
+        .clone()
+}
+
+/// Re-read the configured directory now, e.g. after installing a skill.
+pub fn refresh() {
+    let (dir, generation) = {
+        let loaded = LOADED.read().unwrap_or_else(|e| e.into_inner());
+        (loaded.dir.clone(), loaded.generation)
+    };
+    reload(&dir, generation);
+}
+
+/// Re-read `dir`, unless another directory was configured meanwhile.
+fn reload(dir: &str, generation: u64) {
+    let mut registry = SkillRegistry::new();
+    if !dir.is_empty() {
+        registry.load_from_directory(Path::new(dir));
+    }
+    let mut loaded = LOADED.write().unwrap_or_else(|e| e.into_inner());
+    if loaded.generation == generation {
+        tracing::info!("Loaded {} skill(s) from {}", registry.count(), dir);
+        loaded.registry = registry;
+    }
+}
+
+/// Reload the skills whenever a SKILL.md in `dir` changes.
+#[cfg(desktop)]
+fn watch(dir: &str, generation: u64) {
+    let path = std::path::PathBuf::from(dir);
+    if dir.is_empty() || !path.is_dir() {
+        return;
+    }
+    let rx = match crate::indexer::watcher::start_watching(vec![path]) {
+        Ok(rx) => rx,
+        Err(e) => {
+            tracing::warn!("Skills in {} won't hot-reload: {}", dir, e);
+            return;
+        }
+    };
+    let dir = dir.to_string();
+    std::thread::spawn(move || {
+        // Dropping the receiver stops the watcher
+        for events in rx {
+            let current = LOADED.read().is_ok_and(|l| l.generation == generation);
+            if !current {
+                break;
+            }
+            let skill_changed = events
+                .iter()
+                .any(|e| e.path().file_name().is_some_and(|n| n == "SKILL.md"));
+            if skill_changed {
+                reload(&dir, generation);
+            }
+        }
+    });
+}
+
+/// Parse a SKILL.md file into a Skill struct.
+fn parse_skill_file(path: &Path) -> Result<Skill, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let (frontmatter, body) = parse_frontmatter(&content)?;
+
+    let name = frontmatter.name.unwrap_or_else(|| {
+        // Derive name from parent directory
+        path.parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unnamed".into())
+    });
+
+    let tools = frontmatter
+        .tools
+        .into_iter()
+        .map(|t| SkillTool {
+            name: t.name,
+            description: t.description.unwrap_or_default(),
+            parameters: t
+                .parameters
+                .unwrap_or_else(|| serde_json::json!({"type": "object", "properties": {}})),
+        })
+        .collect();
+
+    Ok(Skill {
+        name,
+        description: frontmatter.description.unwrap_or_default(),
+        triggers: frontmatter.triggers,
+        instructions: body,
+        source: path.to_string_lossy().to_string(),
+        enabled: frontmatter.enabled,
+        tools,
+        parameters: frontmatter.parameters,
+        required_tools: frontmatter.required_tools,
+    })
+}
+
+/// Parse YAML frontmatter from a Markdown file.
+///
+/// Expects the format:
+/// ```text
+/// ---
+/// name: my-skill
+/// description: Does something
+/// triggers: [keyword1, keyword2]
+/// parameters:
+///   - name: language
+///     description: Language of the code
+///     required: false
+///     default: Rust
+/// required_tools: [ghost_read_file]
+/// ---
+/// # Instructions
+/// ...
+/// ```
+fn parse_frontmatter(content: &str) -> Result<(SkillFrontmatter, String), String> {
+    let trimmed = content.trim();
+
+    if !trimmed.starts_with("---") {
+        // No frontmatter — treat entire content as instructions
+        return Ok((
+            SkillFrontmatter {
+                name: None,
+                description: None,
+                triggers: Vec::new(),
+                tools: Vec::new(),
+                enabled: true,
+                parameters: Vec::new(),
+                required_tools: Vec::new(),
+            },
+            trimmed.to_string(),
+        ));
+    }
+
+    // Find the closing ---
+    let after_first = &trimmed[3..];
+    let closing = after_first
+        .find("\n---")
+        .ok_or("Missing closing --- in frontmatter")?;
+
+    let yaml_str = &after_first[..closing].trim();
+    let body = after_first[closing + 4..].trim().to_string();
+
+    let frontmatter: SkillFrontmatter =
+        serde_yaml::from_str(yaml_str).map_err(|e| format!("Invalid YAML frontmatter: {}", e))?;
+
+    Ok((frontmatter, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frontmatter_basic() {
+        let content = r#"---
+name: test-skill
+description: A test skill
+triggers: [test, testing]
+---
+# Instructions
+
+Do the thing.
+"#;
+        let (fm, body) = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.name, Some("test-skill".into()));
+        assert_eq!(fm.description, Some("A test skill".into()));
+        assert_eq!(fm.triggers, vec!["test", "testing"]);
+        assert!(body.contains("Do the thing"));
+    }
+
+    #[test]
+    fn test_parse_frontmatter_no_frontmatter() {
+        let content = "# Just Instructions\n\nDo stuff.";
+        let (fm, body) = parse_frontmatter(content).unwrap();
+        assert!(fm.name.is_none());
+        assert!(body.contains("Do stuff"));
+    }
+
+    #[test]
+    fn test_parse_frontmatter_with_tools() {
+        let content = r#"---
+name: git-skill
+description: Git operations
+triggers: [git, commit, branch]
+tools:
+  - name: git_status
+    description: Get git status
+  - name: git_commit
+    description: Create a commit
+    parameters:
+      type: object
+      properties:
+        message:
+          type: string
+      required: [message]
+---
+# Git Skill
+
+Help with git operations.
+"#;
+        let (fm, _body) = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.tools.len(), 2);
+        assert_eq!(fm.tools[0].name, "git_status");
+        assert!(fm.tools[1].parameters.is_some());
+    }
+
+    #[test]
+    fn test_skill_registry() {
+        let registry = SkillRegistry::new();
+        assert_eq!(registry.count(), 0);
+        assert!(registry.enabled_skills().is_empty());
+    }
+
+    #[test]
+    fn test_match_skills() {
+        let mut registry = SkillRegistry::new();
+        registry.skills.insert(
+            "git".into(),
+            Skill {
+                name: "git".into(),
+                description: "Git helper".into(),
+                triggers: vec!["git".into(), "commit".into()],
+                instructions: "Help with git".into(),
+                source: "test".into(),
+                enabled: true,
+                tools: vec![],
+                parameters: vec![],
+                required_tools: vec![],
+            },
+        );
+
+        let matched = registry.match_skills("How do I git commit?");
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "git");
+
+        let matched = registry.match_skills("What is the weather?");
+        assert!(matched.is_empty());
+    }
+
+    #[test]
+    fn test_build_prompt() {
+        let mut registry = SkillRegistry::new();
+        registry.skills.insert(
+            "rust".into(),
+            Skill {
+                name: "rust".into(),
+                description: "Rust programming".into(),
+                triggers: vec!["rust".into(), "cargo".into()],
+                instructions: "Use idiomatic Rust patterns.".into(),
+                source: "test".into(),
+                enabled: true,
+                tools: vec![],
+                parameters: vec![],
+                required_tools: vec![],
+            },
+        );
+
+        let prompt = registry.build_prompt_for_query("help me with rust code");
+        assert!(prompt.contains("rust"));
+        assert!(prompt.contains("idiomatic Rust"));
+
+        let prompt = registry.build_prompt_for_query("make me a sandwich");
+        assert!(prompt.is_empty());
+    }
+
+    fn prompt_skill(instructions: &str) -> Skill {
+        Skill {
+            name: "review".into(),
+            description: "Code review".into(),
+            triggers: vec![],
+            instructions: instructions.into(),
+            source: "test".into(),
+            enabled: true,
+            tools: vec![],
+            parameters: vec![],
+            required_tools: vec![],
+        }
+    }
+
+    #[test]
+    fn test_prompt_arguments() {
+        let skill = prompt_skill(
+            "Review {{language}} code for {{ focus }}. Use {{language}} idioms. {{bad name}}",
+        );
+        assert_eq!(skill.prompt_arguments(), vec!["language", "focus"]);
+        assert!(prompt_skill("No placeholders")
+            .prompt_arguments()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_render_prompt() {
+        let skill = prompt_skill("Review {{language}} code for {{ focus }}.");
+        let mut args = HashMap::new();
+        args.insert("language".to_string(), "Rust".to_string());
+        assert!(skill.render_prompt(&args).unwrap_err().contains("focus"));
+
+        args.insert("focus".to_string(), "safety".to_string());
+        args.insert("input".to_string(), "fn main() {}".to_string());
+        let text = skill.render_prompt(&args).unwrap();
+        assert!(text.starts_with("Review Rust code for safety."));
+        assert!(text.ends_with("## Task\n\nfn main() {}"));
+    }
+
+    #[test]
+    fn test_parse_frontmatter_with_parameters() {
+        let content = r#"---
+name: code-review
+parameters:
+  - name: language
+    description: Language of the code
+  - name: depth
+    type: integer
+    required: false
+    default: 2
+required_tools: [ghost_read_file]
+---
+Review {{language}} code, {{depth}} levels deep.
+"#;
+        let (fm, _body) = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.parameters.len(), 2);
+        assert!(fm.parameters[0].required);
+        assert_eq!(fm.parameters[0].param_type, "string");
+        assert_eq!(fm.parameters[1].param_type, "integer");
+        assert_eq!(fm.parameters[1].default, Some(serde_json::json!(2)));
+        assert_eq!(fm.required_tools, vec!["ghost_read_file"]);
+    }
+
+    #[test]
+    fn test_render_prompt_with_declared_parameters() {
+        let mut skill = prompt_skill("Review {{language}} code for {{focus}} ({{ note }}).");
+        skill.parameters = vec![
+            SkillParameter {
+                name: "language".into(),
+                description: String::new(),
+                param_type: "string".into(),
+                required: false,
+                default: Some(serde_json::json!("Rust")),
+            },
+            SkillParameter {
+                name: "note".into(),
+                description: String::new(),
+                param_type: "string".into(),
+                required: false,
+                default: None,
+            },
+        ];
+        let args = string_args([("focus".to_string(), serde_json::json!("safety"))]);
+        assert_eq!(
+            skill.render_prompt(&args).unwrap(),
+            "Review Rust code for safety ()."
+        );
+        assert!(skill.render_prompt(&HashMap::new()).is_err());
+
+        let schema = skill.input_schema();
+        assert_eq!(schema["required"], serde_json::json!(["focus"]));
+        assert_eq!(schema["properties"]["language"]["default"], "Rust");
+        assert!(schema["properties"]["input"].is_object());
+    }
+
+    #[test]
+    fn test_tool_name() {
+        let mut skill = prompt_skill("");
+        skill.name = "code-review v2".into();
+        assert_eq!(skill.tool_name(), "skill_code_review_v2");
+    }
+}
@@ -0,0 +1,365 @@
+//! Document summaries written by the local model.
+//!
+//! A document's indexed chunks are grouped into sections that fit the
+//! model's prompt, each section is summarized (map), and the partial
+//! summaries are combined until one text remains (reduce), which is then
+//! written in the requested [`SummaryStyle`]. Summaries are cached in
+//! `document_summaries` by the document's content hash, so they survive
+//! re-indexing an unchanged file and are recomputed once it changes. Search
+//! results carry the cached summary of their document as a richer snippet.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat::ChatMessage;
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+use crate::AppState;
+
+/// Characters of document text sent to the model per call.
+const MAX_SECTION_CHARS: usize = 6000;
+
+/// Characters of a document read at all; the rest is left out.
+const MAX_DOCUMENT_CHARS: usize = 200_000;
+
+/// Token budget of each partial summary.
+const MAP_MAX_TOKENS: usize = 256;
+
+/// Reduce rounds before the remaining text is summarized as is.
+const MAX_REDUCE_ROUNDS: usize = 4;
+
+const MAP_PROMPT: &str = "You summarize one part of a longer document. Write a dense \
+summary of this part in at most 6 sentences: its main points, names, numbers, dates and \
+conclusions. Use only facts from the text. Output only the summary.";
+
+/// How the final summary is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryStyle {
+    /// Two or three sentences.
+    Brief,
+    /// Several paragraphs covering each main point.
+    Detailed,
+    /// A bullet list of key points.
+    Bullets,
+}
+
+impl SummaryStyle {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Brief => "brief",
+            Self::Detailed => "detailed",
+            Self::Bullets => "bullets",
+        }
+    }
+
+    fn instructions(self) -> &'static str {
+        match self {
+            Self::Brief => "Summarize the document in 2-3 sentences: what it is and its key point.",
+            Self::Detailed => {
+                "Summarize the document in a few short paragraphs covering each main point, \
+                 with the important names, numbers and dates."
+            }
+            Self::Bullets => {
+                "Summarize the document as 3-8 Markdown bullet points (`- ...`), one key \
+                 point each."
+            }
+        }
+    }
+
+    fn max_tokens(self) -> usize {
+        match self {
+            Self::Brief => 128,
+            Self::Detailed => 768,
+            Self::Bullets => 384,
+        }
+    }
+}
+
+/// A document summary.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub document_id: i64,
+    pub style: SummaryStyle,
+    pub summary: String,
+    pub model: Option<String>,
+    /// Whether it came from the cache.
+    pub cached: bool,
+    pub created_at: String,
+}
+
+/// Initialize the summary cache table in the database.
+pub fn initialize_summaries_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS document_summaries (
+                -- documents.hash of the text that was summarized
+                content_hash TEXT NOT NULL,
+                style TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                model TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (content_hash, style)
+            );
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Summarize a document, reusing the cached summary of its current content.
+pub async fn summarize_document(
+    state: &AppState,
+    document_id: i64,
+    style: SummaryStyle,
+) -> Result<Summary> {
+    let hash = document_hash(&state.db, document_id)?;
+    if let Some(summary) = cached(&state.db, document_id, &hash, style)? {
+        return Ok(summary);
+    }
+
+    let mut text_chars = 0;
+    let parts: Vec<String> = state
+        .db
+        .get_document_chunks(document_id)?
+        .into_iter()
+        .map(|(_, content)| content)
+        .filter(|content| !content.trim().is_empty())
+        .take_while(|content| {
+            text_chars += content.chars().count();
+            text_chars <= MAX_DOCUMENT_CHARS
+        })
+        .collect();
+    if parts.is_empty() {
+        return Err(GhostError::Agent(format!(
+            "Document {} has no indexed text to summarize",
+            document_id
+        )));
+    }
+
+    // Map-reduce until the text fits one prompt
+    let mut sections = group_sections(&parts, MAX_SECTION_CHARS);
+    let mut rounds = 0;
+    while sections.len() > 1 && rounds < MAX_REDUCE_ROUNDS {
+        let mut partials = Vec::with_capacity(sections.len());
+        for section in &sections {
+            let (partial, _) = complete(state, MAP_PROMPT, section, MAP_MAX_TOKENS).await?;
+            partials.push(partial);
+        }
+        sections = group_sections(&partials, MAX_SECTION_CHARS);
+        rounds += 1;
+    }
+    let text: String = sections
+        .join("\n\n")
+        .chars()
+        .take(MAX_SECTION_CHARS)
+        .collect();
+
+    let prompt = format!(
+        "{} Use only facts from the text. Output only the summary.",
+        style.instructions()
+    );
+    let (summary, model) = complete(state, &prompt, &text, style.max_tokens()).await?;
+    if summary.is_empty() {
+        return Err(GhostError::Agent(
+            "The model returned an empty summary".into(),
+        ));
+    }
+    tracing::info!(
+        "Summarized document {} ({} chunks, {} reduce rounds)",
+        document_id,
+        parts.len(),
+        rounds
+    );
+    store(&state.db, &hash, style, &summary, &model)?;
+    cached(&state.db, document_id, &hash, style)?
+        .ok_or_else(|| GhostError::Agent("The summary could not be saved".into()))
+}
+
+/// Cached summaries of the given documents' current content, preferring
+/// the brief style, keyed by document ID.
+pub fn cached_summaries(db: &Database, document_ids: &[i64]) -> Result<HashMap<i64, String>> {
+    if document_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    db.with_read_conn(|conn| {
+        let placeholders = vec!["?"; document_ids.len()].join(",");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT d.id, s.summary FROM documents d \
+             JOIN document_summaries s ON s.content_hash = d.hash \
+             WHERE d.id IN ({}) \
+             ORDER BY s.style = 'brief' DESC, s.created_at DESC",
+            placeholders
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(document_ids), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut summaries = HashMap::new();
+        for row in rows {
+            let (id, summary) = row?;
+            summaries.entry(id).or_insert(summary);
+        }
+        Ok(summaries)
+    })
+}
+
+fn document_hash(db: &Database, document_id: i64) -> Result<String> {
+    db.with_read_conn(|conn| {
+        match conn.query_row(
+            "SELECT hash FROM documents WHERE id = ?1",
+            [document_id],
+            |row| row.get(0),
+        ) {
+            Ok(hash) => Ok(hash),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(GhostError::Agent(format!(
+                "Document {} not found",
+                document_id
+            ))),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+fn cached(
+    db: &Database,
+    document_id: i64,
+    hash: &str,
+    style: SummaryStyle,
+) -> Result<Option<Summary>> {
+    db.with_read_conn(|conn| {
+        let result = conn.query_row(
+            "SELECT summary, model, created_at FROM document_summaries \
+             WHERE content_hash = ?1 AND style = ?2",
+            rusqlite::params![hash, style.as_str()],
+            |row| {
+                Ok(Summary {
+                    document_id,
+                    style,
+                    summary: row.get(0)?,
+                    model: row.get(1)?,
+                    cached: true,
+                    created_at: row.get(2)?,
+                })
+            },
+        );
+        match result {
+            Ok(summary) => Ok(Some(summary)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    })
+}
+
+/// Save a summary and drop those of content no document has anymore.
+fn store(db: &Database, hash: &str, style: SummaryStyle, summary: &str, model: &str) -> Result<()> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO document_summaries (content_hash, style, summary, model) \
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![hash, style.as_str(), summary, model],
+        )?;
+        conn.execute(
+            "DELETE FROM document_summaries \
+             WHERE content_hash NOT IN (SELECT hash FROM documents)",
+            [],
+        )?;
+        Ok(())
+    })
+}
+
+/// Join consecutive parts into sections of at most `max_chars`; longer
+/// parts are cut.
+fn group_sections(parts: &[String], max_chars: usize) -> Vec<String> {
+    let mut sections: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for part in parts {
+        let part: String = part.trim().chars().take(max_chars).collect();
+        let chars = part.chars().count();
+        if current_chars > 0 && current_chars + 2 + chars > max_chars {
+            sections.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if current_chars > 0 {
+            current.push_str("\n\n");
+            current_chars += 2;
+        }
+        current.push_str(&part);
+        current_chars += chars;
+    }
+    if current_chars > 0 {
+        sections.push(current);
+    }
+    sections
+}
+
+/// One model call: `instructions` as the system prompt, `text` as the input.
+/// Returns the reply and the model that wrote it.
+async fn complete(
+    state: &AppState,
+    instructions: &str,
+    text: &str,
+    max_tokens: usize,
+) -> Result<(String, String)> {
+    let prompt = [
+        ChatMessage {
+            role: "system".into(),
+            content: instructions.into(),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: format!("<document>\n{}\n</document>", text),
+        },
+    ];
+    let response = state.chat_engine.chat(&prompt, max_tokens).await?;
+    Ok((response.content.trim().to_string(), response.model_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_sections() {
+        let parts: Vec<String> = ["aaaa", "bbbb", "cccc", "dddddddddddd"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let sections = group_sections(&parts, 10);
+        assert_eq!(sections, ["aaaa\n\nbbbb", "cccc", "dddddddddd"]);
+        assert!(group_sections(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_summary_cache() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_summaries_schema(&db).unwrap();
+        let doc = db
+            .upsert_document("/docs/a.md", "a.md", Some("md"), 10, "h1", "2026-01-01")
+            .unwrap();
+        store(&db, "h1", SummaryStyle::Bullets, "- point", "qwen").unwrap();
+        store(&db, "h1", SummaryStyle::Brief, "Short.", "qwen").unwrap();
+
+        let summary = cached(&db, doc, "h1", SummaryStyle::Bullets)
+            .unwrap()
+            .unwrap();
+        assert!(summary.cached);
+        assert_eq!(summary.summary, "- point");
+        // Search snippets prefer the brief summary
+        assert_eq!(cached_summaries(&db, &[doc]).unwrap()[&doc], "Short.");
+
+        // Changed content: the old summaries no longer apply and are pruned
+        db.upsert_document("/docs/a.md", "a.md", Some("md"), 12, "h2", "2026-01-02")
+            .unwrap();
+        assert!(cached(&db, doc, "h2", SummaryStyle::Brief)
+            .unwrap()
+            .is_none());
+        assert!(cached_summaries(&db, &[doc]).unwrap().is_empty());
+        store(&db, "h2", SummaryStyle::Brief, "New.", "qwen").unwrap();
+        assert!(cached(&db, doc, "h1", SummaryStyle::Brief)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -10,10 +10,12 @@
 //! grammar-constrained tool calling.
 
 use serde_json::json;
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use super::{AgentTool, AgentToolFunction};
+use super::{AgentTool, AgentToolFunction, Citation};
 use crate::protocols::mcp_client::{McpClientManager, ToolInfo};
+use crate::protocols::A2aAgentEntry;
 
 /// A registered tool with its source information.
 #[derive(Debug, Clone)]
@@ -40,9 +42,49 @@ pub async fn collect_all_tools(mcp_client: &McpClientManager) -> Vec<RegisteredT
         tools.push(mcp_tool_to_registered(&server_name, &tool_info));
     }
 
+    // 3. WASM plugin tools
+    tools.extend(crate::wasm_plugins::registered_tools());
+
+    // 4. Skills, as tools running them
+    let skill_tools = skill_tools(&super::skills::loaded(), &tools);
+    tools.extend(skill_tools);
+
     tools
 }
 
+/// A tool for each enabled skill whose required tools are all in `available`.
+pub fn skill_tools(
+    registry: &super::skills::SkillRegistry,
+    available: &[RegisteredTool],
+) -> Vec<RegisteredTool> {
+    let mut skills = registry.enabled_skills();
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+    skills
+        .into_iter()
+        .filter(|skill| {
+            skill
+                .required_tools
+                .iter()
+                .all(|name| find_tool(available, name).is_some())
+        })
+        .map(|skill| RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: skill.tool_name(),
+                    description: format!(
+                        "Run the '{}' skill as a sub-task: {}",
+                        skill.name, skill.description
+                    ),
+                    parameters: skill.input_schema(),
+                },
+            },
+            source: format!("skill:{}", skill.name),
+            requires_approval: false,
+        })
+        .collect()
+}
+
 /// Ghost's built-in tools available to the agent.
 pub fn builtin_tools() -> Vec<RegisteredTool> {
     vec![
@@ -77,7 +119,7 @@ pub fn builtin_tools() -> Vec<RegisteredTool> {
                 tool_type: "function".into(),
                 function: AgentToolFunction {
                     name: "ghost_read_file".into(),
-                    description: "Read the text content of a file. Use after ghost_search finds a relevant file, or when the user provides a specific file path. Returns up to 100KB of text. Do NOT guess file paths — use ghost_search or ghost_list_directory first to discover them.".into(),
+                    description: "Read the text content of a file. Use after ghost_search finds a relevant file, or when the user provides a specific file path. Returns up to 100KB of text. Only files inside the user's watched folders can be read. Do NOT guess file paths — use ghost_search or ghost_list_directory first to discover them.".into(),
                     parameters: json!({
                         "type": "object",
                         "properties": {
@@ -135,7 +177,7 @@ pub fn builtin_tools() -> Vec<RegisteredTool> {
                 tool_type: "function".into(),
                 function: AgentToolFunction {
                     name: "ghost_write_file".into(),
-                    description: "Write text content to a file. Creates the file if it doesn't exist, overwrites if it does. Creates parent directories automatically. Only use when the user explicitly asks to create or modify a file. Requires approval for sensitive paths.".into(),
+                    description: "Write text content to a file. Creates the file if it doesn't exist, overwrites if it does. Creates parent directories automatically. Only files inside the user's watched folders can be written. Only use when the user explicitly asks to create or modify a file. Requires approval for sensitive paths.".into(),
                     parameters: json!({
                         "type": "object",
                         "properties": {
@@ -155,12 +197,84 @@ pub fn builtin_tools() -> Vec<RegisteredTool> {
             source: "builtin".into(),
             requires_approval: true, // Writing files is a destructive operation
         },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_read_document".into(),
+                    description: "Read a document inside the user's watched folders with line numbers, optionally only a line range. Use before ghost_edit_document so edits quote the exact current text. Files outside the watched folders are refused.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to a file inside a watched folder"
+                            },
+                            "start_line": {
+                                "type": "integer",
+                                "description": "First line to return (1-based, default: 1)"
+                            },
+                            "end_line": {
+                                "type": "integer",
+                                "description": "Last line to return (inclusive, default: end of file)"
+                            }
+                        },
+                        "required": ["path"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false,
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_edit_document".into(),
+                    description: "Edit or create a document inside the user's watched folders. Pass `edits` (each replaces one exact, unique snippet of the current text) to change part of a file, or `content` to write the whole file. Only use when the user asks to change a document. Files outside the watched folders are refused.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to a file inside a watched folder"
+                            },
+                            "edits": {
+                                "type": "array",
+                                "description": "Replacements applied in order",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "find": {
+                                            "type": "string",
+                                            "description": "Exact text to replace (must occur exactly once)"
+                                        },
+                                        "replace": {
+                                            "type": "string",
+                                            "description": "Replacement text"
+                                        }
+                                    },
+                                    "required": ["find", "replace"]
+                                }
+                            },
+                            "content": {
+                                "type": "string",
+                                "description": "Full new file content (instead of edits)"
+                            }
+                        },
+                        "required": ["path"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: true,
+        },
         RegisteredTool {
             definition: AgentTool {
                 tool_type: "function".into(),
                 function: AgentToolFunction {
                     name: "ghost_run_command".into(),
-                    description: "Execute a shell command on the user's system. DANGEROUS: always requires explicit user approval. ONLY use when the user explicitly asks to run a command or perform a system operation. NEVER use proactively or to gather information that other tools can provide. Commands time out after 30 seconds. Explain what the command does before executing.".into(),
+                    description: "Execute a shell command on the user's system. DANGEROUS: always requires explicit user approval. ONLY use when the user explicitly asks to run a command or perform a system operation. NEVER use proactively or to gather information that other tools can provide. Commands are refused in protected locations, time out after 30 seconds by default, and are recorded in an audit log. Explain what the command does before executing.".into(),
                     parameters: json!({
                         "type": "object",
                         "properties": {
@@ -180,9 +294,307 @@ pub fn builtin_tools() -> Vec<RegisteredTool> {
             source: "builtin".into(),
             requires_approval: true, // Always require approval for shell commands
         },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_collect_documents".into(),
+                    description: "List every indexed document about a topic and/or of a file type and year, grouped by year, file type, or folder. Use for enumeration questions like \"all PDFs about taxes from 2023\" or \"which spreadsheets do I have per folder\". Returns Markdown tables — present them as tables and do NOT add files that are not listed.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Topic to match (optional; omit to list all documents matching extension/year)"
+                            },
+                            "group_by": {
+                                "type": "string",
+                                "enum": ["year", "type", "folder"],
+                                "description": "How to group the documents (default: year)"
+                            },
+                            "extension": {
+                                "type": "string",
+                                "description": "File extension without the dot (e.g. pdf, docx, md)"
+                            },
+                            "year": {
+                                "type": "integer",
+                                "description": "Year the file was last modified (e.g. 2023)"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Maximum documents to return (default: 100, max: 500)",
+                                "default": 100
+                            }
+                        },
+                        "required": []
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false,
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_sql_query".into(),
+                    description: "Run a read-only SQL SELECT over Ghost's vault database for counts, breakdowns, and other analytics. Tables: documents(id, path, filename, extension, size_bytes, indexed_at, modified_at), chunks(id, document_id, chunk_index, content, has_embedding), conversations(id, title, created_at, updated_at), messages(id, conversation_id, role, content, created_at). Use ghost_search instead for finding content by meaning.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "A single SQLite SELECT statement (e.g. SELECT extension, COUNT(*) FROM documents GROUP BY extension)"
+                            }
+                        },
+                        "required": ["query"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false,
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_distill_conversation".into(),
+                    description: "Turn a chat conversation into a Markdown note (summary, decisions, action items, references) saved in a watched folder and linked back to the conversation. Only use when the user asks to save, summarize, or take notes from a conversation.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "conversation_id": {
+                                "type": "integer",
+                                "description": "Conversation to distill (default: the most recent one)"
+                            },
+                            "destination": {
+                                "type": "string",
+                                "description": "Absolute folder or .md file path inside a watched folder (default: the first watched folder)"
+                            }
+                        },
+                        "required": []
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: true, // Writes a new file
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_suggest_filename".into(),
+                    description: "Suggest descriptive names for files with meaningless names (e.g. Scan_0001.pdf, IMG_2041.jpg) based on their content. Pass a file, or a folder to get suggestions for its generically named files. Does not rename anything; use ghost_rename_file after the user picks a name.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to a file or folder inside a watched folder"
+                            },
+                            "all_files": {
+                                "type": "boolean",
+                                "description": "For a folder, suggest names for every file instead of only generically named ones (default: false)"
+                            }
+                        },
+                        "required": ["path"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false,
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_rename_file".into(),
+                    description: "Rename a file inside the user's watched folders, keeping it in the same folder and never overwriting another file. The extension is kept when new_name has none. Only use when the user asks to rename a file.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Absolute path to the file to rename"
+                            },
+                            "new_name": {
+                                "type": "string",
+                                "description": "New file name (no folder), e.g. \"2024-03 ACME Electricity Bill.pdf\""
+                            }
+                        },
+                        "required": ["path", "new_name"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: true, // Renames a user file
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_translate".into(),
+                    description: "Translate text (e.g. a search snippet or document passage written in another language) into a target language, locally. Use when quoting sources that are not in the user's language.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "text": {
+                                "type": "string",
+                                "description": "Text to translate (up to ~6000 characters)"
+                            },
+                            "target_language": {
+                                "type": "string",
+                                "description": "Language to translate into, e.g. \"Spanish\" — normally the user's language"
+                            }
+                        },
+                        "required": ["text", "target_language"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false,
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_query_knowledge_graph".into(),
+                    description: "Look up a person, organization, project, or place in the knowledge graph built from the user's files: how it relates to other entities, and which file states each relation. Use for 'who works on X', 'what is Y connected to' questions.".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Entity name or part of it, e.g. \"Apollo\""
+                            },
+                            "depth": {
+                                "type": "integer",
+                                "description": "Hops to follow from the matching entities (1-2, default 1)"
+                            }
+                        },
+                        "required": ["query"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false,
+        },
+        RegisteredTool {
+            definition: AgentTool {
+                tool_type: "function".into(),
+                function: AgentToolFunction {
+                    name: "ghost_spawn_subagent".into(),
+                    description: "Hand a self-contained sub-task to a sub-agent with its own instructions, a subset of your tools and a smaller budget; its final answer comes back as the result. Use to split a large request into focused parts, e.g. \"summarize each of these three reports\".".into(),
+                    parameters: json!({
+                        "type": "object",
+                        "properties": {
+                            "task": {
+                                "type": "string",
+                                "description": "The sub-task, written so it can be done without this conversation"
+                            },
+                            "instructions": {
+                                "type": "string",
+                                "description": "Extra instructions for the sub-agent, e.g. the answer format you need"
+                            },
+                            "tools": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Names of the tools the sub-agent may use (default: all of yours)"
+                            },
+                            "max_iterations": {
+                                "type": "integer",
+                                "description": "Most reasoning steps the sub-agent may take"
+                            }
+                        },
+                        "required": ["task"]
+                    }),
+                },
+            },
+            source: "builtin".into(),
+            requires_approval: false, // Its tool calls are checked one by one
+        },
     ]
 }
 
+/// The tools a `ghost_spawn_subagent` child may use: the parent's, or the
+/// named subset. Sub-agents can't spawn sub-agents of their own or run skills.
+pub fn subagent_tools(
+    parent_tools: &[RegisteredTool],
+    requested: Option<&[String]>,
+) -> Result<Vec<RegisteredTool>, String> {
+    let available = parent_tools.iter().filter(|t| {
+        t.definition.function.name != "ghost_spawn_subagent" && !t.source.starts_with("skill:")
+    });
+    let Some(requested) = requested.filter(|r| !r.is_empty()) else {
+        return Ok(available.cloned().collect());
+    };
+    if let Some(unknown) = requested.iter().find(|name| {
+        !available
+            .clone()
+            .any(|t| &t.definition.function.name == *name)
+    }) {
+        return Err(format!("Tool '{}' is not available to sub-agents", unknown));
+    }
+    Ok(available
+        .filter(|t| requested.contains(&t.definition.function.name))
+        .cloned()
+        .collect())
+}
+
+/// Build the `ghost_delegate_task` tool for the enabled A2A agents.
+///
+/// Returns `None` when no agent is configured, so small models don't see a
+/// tool they cannot use.
+pub fn a2a_delegate_tool(agents: &[A2aAgentEntry]) -> Option<RegisteredTool> {
+    let enabled: Vec<&A2aAgentEntry> = agents.iter().filter(|a| a.enabled).collect();
+    if enabled.is_empty() {
+        return None;
+    }
+
+    let names: Vec<&str> = enabled.iter().map(|a| a.name.as_str()).collect();
+    let roster = enabled
+        .iter()
+        .map(|a| {
+            if a.description.is_empty() {
+                format!("'{}'", a.name)
+            } else {
+                format!("'{}' ({})", a.name, a.description)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Some(RegisteredTool {
+        definition: AgentTool {
+            tool_type: "function".into(),
+            function: AgentToolFunction {
+                name: "ghost_delegate_task".into(),
+                description: format!(
+                    "Delegate a self-contained subtask to another AI agent via the A2A protocol and return its answer. Available agents: {}. Only use when the subtask matches an agent's speciality; include all context the agent needs in the task text.",
+                    roster
+                ),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "agent": {
+                            "type": "string",
+                            "enum": names,
+                            "description": "Name of the agent to delegate to"
+                        },
+                        "task": {
+                            "type": "string",
+                            "description": "Complete description of the subtask"
+                        }
+                    },
+                    "required": ["agent", "task"]
+                }),
+            },
+        },
+        source: "builtin".into(),
+        requires_approval: true, // Task text is sent to another agent
+    })
+}
+
 /// Convert an MCP tool to a registered tool.
 fn mcp_tool_to_registered(server_name: &str, tool_info: &ToolInfo) -> RegisteredTool {
     let parameters = tool_info
@@ -230,50 +642,19 @@ pub async fn execute_builtin_tool(
     state: &Arc<crate::AppState>,
 ) -> Result<String, String> {
     match name {
-        "ghost_search" => {
-            let query = arguments
-                .get("query")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing 'query' argument")?;
-            let limit = arguments
-                .get("limit")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(10) as usize;
-
-            let results =
-                crate::search::hybrid_search(&state.db, &state.embedding_engine, query, limit)
-                    .await
-                    .map_err(|e| format!("Search failed: {}", e))?;
-
-            if results.is_empty() {
-                Ok("No results found.".into())
-            } else {
-                let formatted: Vec<String> = results
-                    .iter()
-                    .enumerate()
-                    .map(|(i, r)| {
-                        format!(
-                            "{}. [{}] {} (score: {:.2})\n   {}",
-                            i + 1,
-                            r.filename,
-                            r.path,
-                            r.score,
-                            r.snippet.chars().take(200).collect::<String>()
-                        )
-                    })
-                    .collect();
-                Ok(formatted.join("\n\n"))
-            }
-        }
+        "ghost_search" => search_with_citations(arguments, state, None)
+            .await
+            .map(|(text, _)| text),
 
         "ghost_read_file" => {
             let path = arguments
                 .get("path")
                 .and_then(|v| v.as_str())
                 .ok_or("Missing 'path' argument")?;
+            let path = sandboxed_path(path, state)?;
 
             // Safety: only read files, max 100KB
-            let content = tokio::fs::read_to_string(path)
+            let content = tokio::fs::read_to_string(&path)
                 .await
                 .map_err(|e| format!("Failed to read file: {}", e))?;
 
@@ -347,21 +728,22 @@ pub async fn execute_builtin_tool(
                 .get("content")
                 .and_then(|v| v.as_str())
                 .ok_or("Missing 'content' argument")?;
+            let path = sandboxed_path(path, state)?;
 
             // Create parent directories if needed
-            if let Some(parent) = std::path::Path::new(path).parent() {
+            if let Some(parent) = path.parent() {
                 tokio::fs::create_dir_all(parent)
                     .await
                     .map_err(|e| format!("Failed to create directories: {}", e))?;
             }
 
-            tokio::fs::write(path, content)
+            tokio::fs::write(&path, content)
                 .await
                 .map_err(|e| format!("Failed to write file: {}", e))?;
 
             Ok(format!(
                 "File written successfully: {} ({} bytes)",
-                path,
+                path.display(),
                 content.len()
             ))
         }
@@ -388,33 +770,36 @@ pub async fn execute_builtin_tool(
                 .and_then(|v| v.as_str())
                 .unwrap_or("~");
 
-            let home = dirs::home_dir()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            let cwd = working_dir.replace('~', &home);
-
-            // Verify working directory exists
-            if !std::path::Path::new(&cwd).is_dir() {
-                return Err(format!("Working directory does not exist: {}", cwd));
-            }
+            let policy = state
+                .settings
+                .lock()
+                .map_err(|e| e.to_string())?
+                .agent_config
+                .command_policy
+                .clone();
+            let cwd = match super::safety::check_command_policy(command, working_dir, &policy) {
+                Ok(cwd) => cwd,
+                Err(e) => {
+                    audit_command(state, command, working_dir, "blocked", None, &e, 0);
+                    return Err(e);
+                }
+            };
+            let cwd_display = cwd.to_string_lossy().to_string();
 
-            // Execute with timeout (30 seconds default)
             // Use platform-appropriate shell
             #[cfg(target_os = "windows")]
-            let child = tokio::process::Command::new("cmd")
-                .arg("/C")
-                .arg(command)
-                .current_dir(&cwd)
-                .env_remove("GITHUB_TOKEN")
-                .env_remove("GH_TOKEN")
-                .env_remove("AWS_SECRET_ACCESS_KEY")
-                .env_remove("OPENAI_API_KEY")
-                .env_remove("ANTHROPIC_API_KEY")
-                .output();
+            let mut cmd = {
+                let mut cmd = tokio::process::Command::new("cmd");
+                cmd.arg("/C");
+                cmd
+            };
             #[cfg(not(target_os = "windows"))]
-            let child = tokio::process::Command::new("sh")
-                .arg("-c")
+            let mut cmd = {
+                let mut cmd = tokio::process::Command::new("sh");
+                cmd.arg("-c");
+                cmd
+            };
+            let child = cmd
                 .arg(command)
                 .current_dir(&cwd)
                 .env_remove("GITHUB_TOKEN")
@@ -422,49 +807,44 @@ pub async fn execute_builtin_tool(
                 .env_remove("AWS_SECRET_ACCESS_KEY")
                 .env_remove("OPENAI_API_KEY")
                 .env_remove("ANTHROPIC_API_KEY")
+                // Dropping the future on timeout kills the process
+                .kill_on_drop(true)
                 .output();
 
-            let output = tokio::time::timeout(std::time::Duration::from_secs(30), child)
-                .await
-                .map_err(|_| {
-                    format!(
-                        "Command timed out after 30 seconds: {}",
-                        &command[..command.len().min(100)]
-                    )
-                })?
-                .map_err(|e| format!("Failed to execute command: {}", e))?;
+            let started = std::time::Instant::now();
+            let timeout = std::time::Duration::from_secs(policy.timeout_secs.max(1));
+            let output = match tokio::time::timeout(timeout, child).await {
+                Ok(Ok(output)) => output,
+                Ok(Err(e)) => {
+                    let e = format!("Failed to execute command: {}", e);
+                    let elapsed = started.elapsed().as_millis() as i64;
+                    audit_command(state, command, &cwd_display, "failed", None, &e, elapsed);
+                    return Err(e);
+                }
+                Err(_) => {
+                    let e = format!(
+                        "Command timed out after {} seconds: {}",
+                        timeout.as_secs(),
+                        command.chars().take(100).collect::<String>()
+                    );
+                    let elapsed = started.elapsed().as_millis() as i64;
+                    audit_command(state, command, &cwd_display, "timeout", None, &e, elapsed);
+                    return Err(e);
+                }
+            };
+            let elapsed = started.elapsed().as_millis() as i64;
 
             let stdout = String::from_utf8_lossy(&output.stdout);
             let stderr = String::from_utf8_lossy(&output.stderr);
 
+            // Limit output to prevent context overflow
             let mut result = String::new();
             if !stdout.is_empty() {
-                // Limit output to prevent context overflow (safe UTF-8 truncation)
-                let truncated = if stdout.len() > 10000 {
-                    let safe_end = (0..=10000)
-                        .rev()
-                        .find(|&i| stdout.is_char_boundary(i))
-                        .unwrap_or(0);
-                    format!(
-                        "{}...\n[Truncated: {} bytes total]",
-                        &stdout[..safe_end],
-                        stdout.len()
-                    )
-                } else {
-                    stdout.to_string()
-                };
+                let truncated = truncate_output(&stdout, policy.max_output_bytes);
                 result.push_str(&format!("stdout:\n{}", redact_secrets(&truncated)));
             }
             if !stderr.is_empty() {
-                let truncated = if stderr.len() > 5000 {
-                    let safe_end = (0..=5000)
-                        .rev()
-                        .find(|&i| stderr.is_char_boundary(i))
-                        .unwrap_or(0);
-                    format!("{}...\n[Truncated]", &stderr[..safe_end])
-                } else {
-                    stderr.to_string()
-                };
+                let truncated = truncate_output(&stderr, policy.max_output_bytes / 2);
                 result.push_str(&format!("\nstderr:\n{}", redact_secrets(&truncated)));
             }
             if result.is_empty() {
@@ -474,18 +854,530 @@ pub async fn execute_builtin_tool(
                 );
             }
 
+            let status = if output.status.success() {
+                "completed"
+            } else {
+                "failed"
+            };
+            audit_command(
+                state,
+                command,
+                &cwd_display,
+                status,
+                output.status.code(),
+                &result,
+                elapsed,
+            );
+
+            Ok(result)
+        }
+
+        "ghost_delegate_task" => {
+            let agent_name = arguments
+                .get("agent")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'agent' argument")?;
+            let task = arguments
+                .get("task")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'task' argument")?;
+
+            let agent = state
+                .settings
+                .lock()
+                .map_err(|e| e.to_string())?
+                .a2a_agents
+                .iter()
+                .find(|a| a.enabled && a.name == agent_name)
+                .cloned()
+                .ok_or_else(|| format!("No enabled A2A agent named '{}'", agent_name))?;
+
+            tracing::info!(
+                "Delegating task to A2A agent '{}' ({})",
+                agent.name,
+                agent.url
+            );
+            crate::protocols::a2a::delegate_task(&agent.url, task)
+                .await
+                .map_err(|e| format!("Delegation to '{}' failed: {}", agent.name, e))
+        }
+
+        "ghost_collect_documents" => {
+            let request = crate::search::aggregate::CollectionRequest {
+                query: arguments
+                    .get("query")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                group_by: arguments
+                    .get("group_by")
+                    .and_then(|v| v.as_str())
+                    .and_then(crate::search::aggregate::GroupBy::parse)
+                    .unwrap_or_default(),
+                extension: arguments
+                    .get("extension")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                year: arguments
+                    .get("year")
+                    .and_then(|v| v.as_i64())
+                    .map(|y| y as i32),
+                limit: arguments
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|l| l as usize),
+            };
+            let collection =
+                crate::search::aggregate::collect(&state.db, &state.embedding_engine, &request)
+                    .await
+                    .map_err(|e| format!("Collection failed: {}", e))?;
+            Ok(crate::search::aggregate::format_table(&collection))
+        }
+
+        "ghost_sql_query" => {
+            let query = arguments
+                .get("query")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'query' argument")?;
+
+            // Runs until the query's watchdog fires; keep it off the runtime
+            let query = query.to_string();
+            let state = state.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                crate::db::query::run_readonly_sql(&state.db, &query, Some(50))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+            Ok(crate::db::query::format_result(&result))
+        }
+
+        "ghost_distill_conversation" => {
+            let conversation_id = match arguments.get("conversation_id").and_then(|v| v.as_i64()) {
+                Some(id) => id,
+                None => super::memory::list_conversations(&state.db, 1)
+                    .map_err(|e| e.to_string())?
+                    .first()
+                    .map(|c| c.id)
+                    .ok_or("No conversations to distill")?,
+            };
+            let destination = match arguments.get("destination").and_then(|v| v.as_str()) {
+                Some(destination) => destination.to_string(),
+                None => state
+                    .settings
+                    .lock()
+                    .map_err(|e| e.to_string())?
+                    .watched_directories
+                    .first()
+                    .cloned()
+                    .ok_or("No watched folder to save the note in; pass 'destination'")?,
+            };
+
+            let note = super::distill::distill_conversation(state, conversation_id, &destination)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "Saved note for conversation \"{}\" to {}\n\n{}",
+                note.title, note.path, note.markdown
+            ))
+        }
+
+        "ghost_suggest_filename" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'path' argument")?;
+            let suggestions = if std::path::Path::new(path).is_dir() {
+                let all_files = arguments
+                    .get("all_files")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                super::rename::suggest_folder_filenames(state, path, all_files)
+                    .await
+                    .map_err(|e| e.to_string())?
+            } else {
+                vec![super::rename::suggest_filename(state, path)
+                    .await
+                    .map_err(|e| e.to_string())?]
+            };
+            if suggestions.is_empty() {
+                return Ok("No files with generic names to rename.".into());
+            }
+            Ok(suggestions
+                .iter()
+                .map(|s| format!("{} -> {}\n  ({})", s.current_name, s.suggested_name, s.path))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+
+        "ghost_rename_file" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'path' argument")?;
+            let new_name = arguments
+                .get("new_name")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'new_name' argument")?;
+            let new_path = super::rename::rename_file(state, path, new_name)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Renamed {} to {}", path, new_path))
+        }
+
+        "ghost_translate" => {
+            let text = arguments
+                .get("text")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'text' argument")?;
+            let target = arguments
+                .get("target_language")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'target_language' argument")?;
+            let translation = super::language::translate(state, text, target)
+                .await
+                .map_err(|e| e.to_string())?;
+            let mut result = translation.text;
+            if translation.truncated {
+                result.push_str(&format!(
+                    "\n\n[Only the first {} characters were translated]",
+                    super::language::MAX_TRANSLATE_CHARS
+                ));
+            }
             Ok(result)
         }
 
+        "ghost_query_knowledge_graph" => {
+            let query = arguments
+                .get("query")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'query' argument")?;
+            let depth = arguments.get("depth").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+            let graph =
+                super::knowledge::query(&state.db, query, depth, 10).map_err(|e| e.to_string())?;
+            Ok(graph.describe())
+        }
+
+        "ghost_read_document" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'path' argument")?;
+            let path = sandboxed_path(path, state)?;
+            let start = arguments
+                .get("start_line")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1)
+                .max(1) as usize;
+            let end = arguments
+                .get("end_line")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as usize);
+            if end.is_some_and(|end| end < start) {
+                return Err("'end_line' must not be before 'start_line'".into());
+            }
+
+            let content = tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|e| format!("Failed to read document: {}", e))?;
+            Ok(number_lines(&path.to_string_lossy(), &content, start, end))
+        }
+
+        "ghost_edit_document" => {
+            let path = arguments
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing 'path' argument")?;
+            let path = sandboxed_path(path, state)?;
+
+            let new_content = match (
+                arguments.get("edits").and_then(|v| v.as_array()),
+                arguments.get("content").and_then(|v| v.as_str()),
+            ) {
+                (Some(edits), None) => {
+                    let edits: Vec<(String, String)> = edits
+                        .iter()
+                        .map(|e| {
+                            let find = e.get("find").and_then(|v| v.as_str());
+                            let replace = e.get("replace").and_then(|v| v.as_str());
+                            match (find, replace) {
+                                (Some(f), Some(r)) => Ok((f.to_string(), r.to_string())),
+                                _ => Err("Each edit needs 'find' and 'replace'".to_string()),
+                            }
+                        })
+                        .collect::<Result<_, _>>()?;
+                    let original = tokio::fs::read_to_string(&path)
+                        .await
+                        .map_err(|e| format!("Failed to read document: {}", e))?;
+                    apply_edits(&original, &edits)?
+                }
+                (None, Some(content)) => content.to_string(),
+                _ => return Err("Pass either 'edits' or 'content'".into()),
+            };
+
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| format!("Failed to create directories: {}", e))?;
+            }
+            // Write to a temp file, then rename, so a failed write never truncates the document.
+            let tmp = path.with_extension("ghost-tmp");
+            tokio::fs::write(&tmp, &new_content)
+                .await
+                .map_err(|e| format!("Failed to write document: {}", e))?;
+            if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+                let _ = tokio::fs::remove_file(&tmp).await;
+                return Err(format!("Failed to write document: {}", e));
+            }
+
+            Ok(format!(
+                "Document saved: {} ({} lines, {} bytes)",
+                path.display(),
+                new_content.lines().count(),
+                new_content.len()
+            ))
+        }
+
+        "ghost_spawn_subagent" => Err("Sub-agents can only be spawned during an agent run".into()),
+
         _ => Err(format!("Unknown built-in tool: {}", name)),
     }
 }
 
+/// Run `ghost_search`, returning the formatted results and the retrieved chunks.
+/// `scope` limits retrieval to a conversation's attached documents.
+pub(crate) async fn search_with_citations(
+    arguments: &serde_json::Value,
+    state: &Arc<crate::AppState>,
+    scope: Option<&HashSet<i64>>,
+) -> Result<(String, Vec<Citation>), String> {
+    let query = arguments
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'query' argument")?;
+    let limit = arguments
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(10) as usize;
+
+    let results = crate::search::hybrid_search_scoped(
+        &state.db,
+        &state.embedding_engine,
+        query,
+        limit,
+        None,
+        scope,
+    )
+    .await
+    .map_err(|e| format!("Search failed: {}", e))?;
+
+    let confidence = crate::search::confidence::assess(query, &results);
+    let mut output = if results.is_empty() {
+        "No results found.".to_string()
+    } else {
+        let formatted: Vec<String> = results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let label = match r.section.as_ref().or(r.symbol.as_ref()) {
+                    Some(section) => format!("{} — {}", r.filename, section),
+                    None => r.filename.clone(),
+                };
+                format!(
+                    "{}. [{}] {} (score: {:.2})\n   {}",
+                    i + 1,
+                    label,
+                    r.path,
+                    r.score,
+                    r.snippet.chars().take(200).collect::<String>()
+                )
+            })
+            .collect();
+        formatted.join("\n\n")
+    };
+
+    let web_tools: Vec<String> = state
+        .mcp_client
+        .all_tools()
+        .await
+        .into_iter()
+        .filter(|(_, t)| {
+            crate::search::confidence::is_web_search_tool(&t.name, t.description.as_deref())
+        })
+        .map(|(_, t)| t.name)
+        .collect();
+    output.push_str(&confidence_note(&confidence, &web_tools));
+
+    let citations = results
+        .into_iter()
+        .map(|r| Citation {
+            index: 0,
+            chunk_id: r.chunk_id,
+            document_id: r.document_id,
+            path: r.path,
+            filename: r.filename,
+            snippet: r.snippet,
+            referenced: false,
+        })
+        .collect();
+    Ok((output, citations))
+}
+
+/// Record a shell command in the audit log (failures are logged, not fatal).
+fn audit_command(
+    state: &crate::AppState,
+    command: &str,
+    working_dir: &str,
+    status: &str,
+    exit_code: Option<i32>,
+    output: &str,
+    duration_ms: i64,
+) {
+    if let Err(e) = super::audit::record_command(
+        &state.db,
+        command,
+        working_dir,
+        status,
+        exit_code,
+        output,
+        duration_ms,
+    ) {
+        tracing::warn!("Failed to record command audit entry: {}", e);
+    }
+}
+
+/// Truncate command output to `max_bytes` on a UTF-8 boundary.
+fn truncate_output(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let safe_end = (0..=max_bytes)
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0);
+    format!(
+        "{}...\n[Truncated: {} bytes total]",
+        &text[..safe_end],
+        text.len()
+    )
+}
+
+/// Retrieval confidence footer for `ghost_search` results.
+///
+/// When confidence is low the note tells the model, in plain words, not to
+/// answer from the results and to offer a web search if one is available.
+fn confidence_note(
+    confidence: &crate::search::confidence::RetrievalConfidence,
+    web_tools: &[String],
+) -> String {
+    let mut note = format!(
+        "\n\n[Retrieval confidence: {:?} ({:.0}%)",
+        confidence.level,
+        confidence.score * 100.0
+    );
+    if !confidence.missing_terms.is_empty() {
+        note.push_str(&format!(
+            " — not found: {}",
+            confidence.missing_terms.join(", ")
+        ));
+    }
+    note.push(']');
+
+    if confidence.is_insufficient() {
+        note.push_str(
+            "\nThe user's files do not appear to contain this information. \
+             Tell the user you couldn't find it in their files. \
+             Do not answer from these results or from memory as if it came from their files.",
+        );
+        match web_tools.first() {
+            Some(tool) => note.push_str(&format!(
+                "\nOffer to search the web instead with the `{}` tool (ask before calling it).",
+                tool
+            )),
+            None => note.push_str(
+                "\nYou may offer to answer from general knowledge, clearly labeled as not from their files.",
+            ),
+        }
+    }
+    note
+}
+
+/// Resolve a file or document tool path against the watched directories.
+fn sandboxed_path(path: &str, state: &crate::AppState) -> Result<std::path::PathBuf, String> {
+    let roots = state
+        .settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .watched_directories
+        .clone();
+    super::safety::resolve_sandboxed_path(path, &roots)
+}
+
+/// Max lines returned by a single `ghost_read_document` call.
+const MAX_DOCUMENT_LINES: usize = 400;
+
+/// Render a line range with 1-based line numbers.
+fn number_lines(path: &str, content: &str, start: usize, end: Option<usize>) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let total = lines.len();
+    let end = end
+        .unwrap_or(total)
+        .min(total)
+        .min(start.saturating_add(MAX_DOCUMENT_LINES - 1))
+        .max(start - 1);
+    if start > total {
+        return format!("{} has {} lines.", path, total);
+    }
+
+    let mut out = format!("{} (lines {}-{} of {})\n", path, start, end, total);
+    for (i, line) in lines[start - 1..end].iter().enumerate() {
+        out.push_str(&format!("{:>5} | {}\n", start + i, line));
+    }
+    if end < total {
+        out.push_str(&format!(
+            "[{} more lines — pass start_line={} to continue]",
+            total - end,
+            end + 1
+        ));
+    }
+    out
+}
+
+/// Apply find/replace edits in order. Each `find` must occur exactly once.
+fn apply_edits(original: &str, edits: &[(String, String)]) -> Result<String, String> {
+    if edits.is_empty() {
+        return Err("No edits given".into());
+    }
+    let mut text = original.to_string();
+    for (i, (find, replace)) in edits.iter().enumerate() {
+        if find.is_empty() {
+            return Err(format!("Edit {}: 'find' is empty", i + 1));
+        }
+        match text.matches(find.as_str()).count() {
+            1 => text = text.replacen(find.as_str(), replace, 1),
+            0 => {
+                return Err(format!(
+                    "Edit {}: text not found — read the document again and quote it exactly",
+                    i + 1
+                ))
+            }
+            n => {
+                return Err(format!(
+                    "Edit {}: text occurs {} times — include more surrounding text",
+                    i + 1,
+                    n
+                ))
+            }
+        }
+    }
+    Ok(text)
+}
+
 /// Redact potential secrets from tool output.
 ///
 /// Matches common patterns for API keys, tokens, passwords, and secrets
-/// to prevent them from leaking into the LLM context.
-fn redact_secrets(text: &str) -> String {
+/// to prevent them from leaking into the LLM context or the audit log.
+pub(super) fn redact_secrets(text: &str) -> String {
     // Patterns: key=value, key: value, "key": "value" where key suggests a secret
     let secret_key_patterns = [
         "api_key",
@@ -508,7 +1400,8 @@ fn redact_secrets(text: &str) -> String {
     for pattern in &secret_key_patterns {
         // Match: PATTERN=value (env var style)
         let env_prefix = format!("{}=", pattern.to_uppercase());
-        if let Some(pos) = result.to_uppercase().find(&env_prefix.to_uppercase()) {
+        // ASCII uppercasing keeps byte offsets valid in `result`
+        if let Some(pos) = result.to_ascii_uppercase().find(&env_prefix) {
             let start = pos + env_prefix.len();
             if let Some(end) = result[start..].find(|c: char| c.is_whitespace() || c == '\n') {
                 let end = start + end;
@@ -559,6 +1452,84 @@ mod tests {
         assert!(tools.len() >= 4);
     }
 
+    #[test]
+    fn test_a2a_delegate_tool() {
+        assert!(a2a_delegate_tool(&[]).is_none());
+
+        let agents = vec![
+            A2aAgentEntry {
+                name: "researcher".into(),
+                url: "http://localhost:9000".into(),
+                description: "web research".into(),
+                enabled: true,
+            },
+            A2aAgentEntry {
+                name: "disabled".into(),
+                url: "http://localhost:9001".into(),
+                description: String::new(),
+                enabled: false,
+            },
+        ];
+        let tool = a2a_delegate_tool(&agents).unwrap();
+        assert_eq!(tool.definition.function.name, "ghost_delegate_task");
+        assert!(tool.definition.function.description.contains("researcher"));
+        assert!(!tool.definition.function.description.contains("disabled"));
+        assert_eq!(
+            tool.definition.function.parameters["properties"]["agent"]["enum"],
+            json!(["researcher"])
+        );
+    }
+
+    #[test]
+    fn test_subagent_tools() {
+        let tools = builtin_tools();
+        let all = subagent_tools(&tools, None).unwrap();
+        assert_eq!(all.len(), tools.len() - 1);
+        assert!(find_tool(&all, "ghost_spawn_subagent").is_none());
+
+        let requested = vec!["ghost_search".to_string(), "ghost_read_file".to_string()];
+        let subset = subagent_tools(&tools, Some(&requested)).unwrap();
+        assert_eq!(subset.len(), 2);
+
+        let nested = vec!["ghost_spawn_subagent".to_string()];
+        assert!(subagent_tools(&tools, Some(&nested)).is_err());
+        let unknown = vec!["nonexistent".to_string()];
+        assert!(subagent_tools(&tools, Some(&unknown)).is_err());
+    }
+
+    #[test]
+    fn test_skill_tools() {
+        let dir =
+            std::env::temp_dir().join(format!("ghost_test_skill_tools_{}", std::process::id()));
+        for (name, required) in [
+            ("summarize", "ghost_read_file"),
+            ("deploy", "kubectl_apply"),
+        ] {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+            std::fs::write(
+                dir.join(name).join("SKILL.md"),
+                format!(
+                    "---\nname: {}\ndescription: d\nrequired_tools: [{}]\n---\nDo {{{{what}}}}.",
+                    name, required
+                ),
+            )
+            .unwrap();
+        }
+        let mut registry = crate::agent::skills::SkillRegistry::new();
+        registry.load_from_directory(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let tools = skill_tools(&registry, &builtin_tools());
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].definition.function.name, "skill_summarize");
+        assert_eq!(tools[0].source, "skill:summarize");
+        assert_eq!(
+            tools[0].definition.function.parameters["required"],
+            json!(["what"])
+        );
+        assert!(subagent_tools(&tools, None).unwrap().is_empty());
+    }
+
     #[test]
     fn test_find_tool() {
         let tools = builtin_tools();
@@ -591,6 +1562,112 @@ mod tests {
         assert_eq!(registered.source, "mcp:filesystem");
     }
 
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(truncate_output("short", 10), "short");
+        let out = truncate_output("ééééé", 5);
+        assert_eq!(out, "éé...\n[Truncated: 10 bytes total]");
+    }
+
+    #[test]
+    fn test_confidence_note() {
+        use crate::search::confidence::assess;
+
+        let note = confidence_note(&assess("passport number", &[]), &[]);
+        assert!(note.contains("Retrieval confidence: None"));
+        assert!(note.contains("couldn't find it"));
+        assert!(note.contains("general knowledge"));
+
+        let note = confidence_note(
+            &assess("passport number", &[]),
+            &["brave_web_search".to_string()],
+        );
+        assert!(note.contains("`brave_web_search`"));
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let doc = "# Title\n\nDraft text.\n\n## Notes\nDraft text.\n";
+        let edited = apply_edits(
+            doc,
+            &[
+                ("# Title".into(), "# Final Title".into()),
+                ("## Notes\nDraft".into(), "## Notes\nDone".into()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            edited,
+            "# Final Title\n\nDraft text.\n\n## Notes\nDone text.\n"
+        );
+
+        // Ambiguous and missing snippets are rejected
+        assert!(apply_edits(doc, &[("Draft text.".into(), "x".into())])
+            .unwrap_err()
+            .contains("2 times"));
+        assert!(apply_edits(doc, &[("missing".into(), "x".into())])
+            .unwrap_err()
+            .contains("not found"));
+        assert!(apply_edits(doc, &[]).is_err());
+    }
+
+    #[test]
+    fn test_number_lines() {
+        let out = number_lines("/n.md", "a\nb\nc\n", 2, None);
+        assert_eq!(out, "/n.md (lines 2-3 of 3)\n    2 | b\n    3 | c\n");
+        assert_eq!(number_lines("/n.md", "a\n", 5, None), "/n.md has 1 lines.");
+        let out = number_lines("/n.md", "a\nb\nc\n", 1, Some(1));
+        assert!(out.ends_with("[2 more lines — pass start_line=2 to continue]"));
+        // Inverted ranges and huge starts don't panic
+        let out = number_lines("/n.md", "a\nb\nc\n", 3, Some(1));
+        assert!(out.starts_with("/n.md (lines 3-2 of 3)\n"));
+        assert_eq!(
+            number_lines("/n.md", "a\n", usize::MAX, None),
+            "/n.md has 1 lines."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_tools_stay_in_watched_dirs() {
+        let dir =
+            std::env::temp_dir().join(format!("ghost_test_file_tools_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("notes")).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let state = crate::test_app_state();
+        state.settings.lock().unwrap().watched_directories =
+            vec![dir.join("notes").to_string_lossy().to_string()];
+
+        let inside = dir.join("notes").join("a.md").to_string_lossy().to_string();
+        let outside = dir.join("secret.txt").to_string_lossy().to_string();
+        std::fs::write(&outside, "top secret").unwrap();
+
+        execute_builtin_tool(
+            "ghost_write_file",
+            &json!({"path": inside, "content": "hello"}),
+            &state,
+        )
+        .await
+        .unwrap();
+        let read = execute_builtin_tool("ghost_read_file", &json!({"path": inside}), &state).await;
+        assert_eq!(read.unwrap(), "hello");
+
+        assert!(
+            execute_builtin_tool("ghost_read_file", &json!({"path": outside}), &state)
+                .await
+                .is_err()
+        );
+        assert!(execute_builtin_tool(
+            "ghost_write_file",
+            &json!({"path": outside, "content": "overwritten"}),
+            &state,
+        )
+        .await
+        .is_err());
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "top secret");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(0), "0 B");
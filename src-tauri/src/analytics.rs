@@ -0,0 +1,246 @@
+//! Local usage analytics — feature counters that never leave the device.
+//!
+//! Counts searches, chats, agent runs, tool calls, and indexing events per day
+//! in the vault database. Nothing is transmitted anywhere: the data is only
+//! surfaced through the `get_usage_dashboard` command, and users can export a
+//! report manually (e.g. to attach to a bug report) or wipe it at any time.
+//!
+//! Privacy: only feature names and tool names are stored — never queries,
+//! file paths, or message content.
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// A tracked feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageFeature {
+    Search,
+    Chat,
+    AgentRun,
+    ToolCall,
+    IndexFile,
+    IndexDirectory,
+}
+
+impl UsageFeature {
+    /// Stable identifier stored in the database.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::Chat => "chat",
+            Self::AgentRun => "agent_run",
+            Self::ToolCall => "tool_call",
+            Self::IndexFile => "index_file",
+            Self::IndexDirectory => "index_directory",
+        }
+    }
+}
+
+/// Usage count for one feature (or one tool).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureCount {
+    pub name: String,
+    pub count: i64,
+}
+
+/// Usage count for one feature on one day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// Day in `YYYY-MM-DD` (UTC).
+    pub day: String,
+    pub feature: String,
+    pub count: i64,
+}
+
+/// Aggregated usage for the dashboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageDashboard {
+    /// Number of days covered (counted back from today).
+    pub days: u32,
+    /// Totals per feature over the period, most used first.
+    pub totals: Vec<FeatureCount>,
+    /// Per-day breakdown, oldest first.
+    pub daily: Vec<DailyUsage>,
+    /// Most-called agent tools over the period.
+    pub top_tools: Vec<FeatureCount>,
+}
+
+/// Initialize usage analytics tables in the database.
+pub fn initialize_usage_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS usage_counters (
+                day TEXT NOT NULL,
+                feature TEXT NOT NULL,
+                detail TEXT NOT NULL DEFAULT '',
+                count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (day, feature, detail)
+            );
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Increment the counter for a feature (and optional detail, e.g. a tool name).
+pub fn record(db: &Database, feature: UsageFeature, detail: Option<&str>) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO usage_counters (day, feature, detail, count)
+             VALUES (date('now'), ?1, ?2, 1)
+             ON CONFLICT(day, feature, detail) DO UPDATE SET count = count + 1",
+            rusqlite::params![feature.as_str(), detail.unwrap_or("")],
+        )?;
+        Ok(())
+    })
+}
+
+/// Record usage, logging instead of failing — analytics must never break a feature.
+pub fn track(db: &Database, feature: UsageFeature, detail: Option<&str>) {
+    if let Err(e) = record(db, feature, detail) {
+        tracing::debug!("Usage tracking failed for {}: {}", feature.as_str(), e);
+    }
+}
+
+/// Build the usage dashboard for the last `days` days (including today).
+pub fn get_dashboard(db: &Database, days: u32) -> Result<UsageDashboard> {
+    let days = days.max(1);
+    let since = format!("-{} days", days - 1);
+
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT feature, SUM(count) AS total FROM usage_counters
+             WHERE day >= date('now', ?1)
+             GROUP BY feature ORDER BY total DESC, feature",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![since], |row| {
+            Ok(FeatureCount {
+                name: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
+        let mut totals = Vec::new();
+        for row in rows {
+            totals.push(row?);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT day, feature, SUM(count) FROM usage_counters
+             WHERE day >= date('now', ?1)
+             GROUP BY day, feature ORDER BY day, feature",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![since], |row| {
+            Ok(DailyUsage {
+                day: row.get(0)?,
+                feature: row.get(1)?,
+                count: row.get(2)?,
+            })
+        })?;
+        let mut daily = Vec::new();
+        for row in rows {
+            daily.push(row?);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT detail, SUM(count) AS total FROM usage_counters
+             WHERE day >= date('now', ?1) AND feature = ?2 AND detail != ''
+             GROUP BY detail ORDER BY total DESC, detail LIMIT 10",
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![since, UsageFeature::ToolCall.as_str()],
+            |row| {
+                Ok(FeatureCount {
+                    name: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            },
+        )?;
+        let mut top_tools = Vec::new();
+        for row in rows {
+            top_tools.push(row?);
+        }
+
+        Ok(UsageDashboard {
+            days,
+            totals,
+            daily,
+            top_tools,
+        })
+    })
+}
+
+/// Delete all recorded usage data.
+pub fn clear(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute("DELETE FROM usage_counters", [])?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        initialize_usage_schema(&db).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_record_increments_counter() {
+        let db = test_db();
+        record(&db, UsageFeature::Search, None).unwrap();
+        record(&db, UsageFeature::Search, None).unwrap();
+        record(&db, UsageFeature::Chat, None).unwrap();
+
+        let dash = get_dashboard(&db, 7).unwrap();
+        assert_eq!(dash.totals[0].name, "search");
+        assert_eq!(dash.totals[0].count, 2);
+        assert_eq!(dash.totals[1].name, "chat");
+        assert_eq!(dash.daily.len(), 2);
+    }
+
+    #[test]
+    fn test_top_tools() {
+        let db = test_db();
+        record(&db, UsageFeature::ToolCall, Some("ghost_search")).unwrap();
+        record(&db, UsageFeature::ToolCall, Some("ghost_search")).unwrap();
+        record(&db, UsageFeature::ToolCall, Some("ghost_read_file")).unwrap();
+
+        let dash = get_dashboard(&db, 30).unwrap();
+        assert_eq!(dash.top_tools.len(), 2);
+        assert_eq!(dash.top_tools[0].name, "ghost_search");
+        assert_eq!(dash.top_tools[0].count, 2);
+        // Tool calls are also counted as a feature total
+        assert_eq!(dash.totals[0].name, "tool_call");
+        assert_eq!(dash.totals[0].count, 3);
+    }
+
+    #[test]
+    fn test_empty_dashboard() {
+        let db = test_db();
+        let dash = get_dashboard(&db, 0).unwrap();
+        assert_eq!(dash.days, 1);
+        assert!(dash.totals.is_empty());
+        assert!(dash.top_tools.is_empty());
+    }
+
+    #[test]
+    fn test_clear() {
+        let db = test_db();
+        record(&db, UsageFeature::IndexFile, None).unwrap();
+        clear(&db).unwrap();
+        assert!(get_dashboard(&db, 7).unwrap().totals.is_empty());
+    }
+
+    #[test]
+    fn test_track_without_schema_does_not_panic() {
+        let db = Database::open_in_memory().unwrap();
+        track(&db, UsageFeature::Chat, None);
+    }
+}
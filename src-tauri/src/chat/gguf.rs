@@ -0,0 +1,293 @@
+//! Minimal GGUF header reader for validating and describing imported models.
+//!
+//! Reads the key/value metadata and tensor shapes (not the weights) to report
+//! the architecture, layer count, trained context length, quantization and
+//! parameter count. Supports GGUF v2 and v3.
+
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{GhostError, Result};
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+/// Longest string accepted in metadata; guards against corrupt length fields.
+const MAX_STRING_LEN: u64 = 1 << 24;
+
+/// Metadata probed from a GGUF file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GgufInfo {
+    /// Model architecture (e.g. "qwen3", "llama").
+    pub architecture: String,
+    /// `general.name`, when the file sets it.
+    pub name: Option<String>,
+    /// Transformer layers (`<arch>.block_count`).
+    pub n_layers: u32,
+    /// Context length the model was trained with.
+    pub context_length: Option<u32>,
+    /// Dominant weight quantization (e.g. "Q4_K_M").
+    pub quantization: String,
+    /// Total parameters across all tensors.
+    pub parameter_count: u64,
+}
+
+impl GgufInfo {
+    /// Parameter count as shown in the registry ("0.6B", "7.6B", "135M").
+    pub fn parameters_label(&self) -> String {
+        let count = self.parameter_count as f64;
+        if count >= 1e9 {
+            format!("{:.1}B", count / 1e9)
+        } else {
+            format!("{:.0}M", count / 1e6)
+        }
+    }
+}
+
+/// A metadata value; only the types the probe needs are kept.
+enum Value {
+    Uint(u64),
+    Str(String),
+    Other,
+}
+
+/// Read the GGUF header of `path`. Fails if the file is not a GGUF model.
+pub fn probe(path: &Path) -> Result<GgufInfo> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| GhostError::Chat(format!("Cannot open {}: {}", path.display(), e)))?;
+    read_info(&mut BufReader::new(file)).map_err(|e| {
+        GhostError::Chat(format!(
+            "{} is not a valid GGUF model: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn read_info(reader: &mut impl Read) -> std::result::Result<GgufInfo, String> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|e| e.to_string())?;
+    if &magic != GGUF_MAGIC {
+        return Err("missing GGUF magic".into());
+    }
+    let version = read_u32(reader)?;
+    if !(2..=3).contains(&version) {
+        return Err(format!("unsupported GGUF version {}", version));
+    }
+    let tensor_count = read_u64(reader)?;
+    let kv_count = read_u64(reader)?;
+
+    let mut info = GgufInfo::default();
+    let mut file_type = None;
+    let mut block_counts = Vec::new();
+    let mut context_lengths = Vec::new();
+    for _ in 0..kv_count {
+        let key = read_string(reader)?;
+        let value_type = read_u32(reader)?;
+        let value = read_value(reader, value_type)?;
+        match (key.as_str(), value) {
+            ("general.architecture", Value::Str(arch)) => info.architecture = arch,
+            ("general.name", Value::Str(name)) => info.name = Some(name),
+            ("general.file_type", Value::Uint(t)) => file_type = Some(t),
+            (k, Value::Uint(n)) if k.ends_with(".block_count") => {
+                block_counts.push((k.trim_end_matches(".block_count").to_string(), n))
+            }
+            (k, Value::Uint(n)) if k.ends_with(".context_length") => {
+                context_lengths.push((k.trim_end_matches(".context_length").to_string(), n))
+            }
+            _ => {}
+        }
+    }
+    if info.architecture.is_empty() {
+        return Err("no general.architecture in metadata".into());
+    }
+    let for_arch = |pairs: &[(String, u64)]| {
+        pairs
+            .iter()
+            .find(|(arch, _)| *arch == info.architecture)
+            .map(|(_, n)| *n as u32)
+    };
+    info.n_layers = for_arch(&block_counts).unwrap_or(0);
+    info.context_length = for_arch(&context_lengths);
+
+    for _ in 0..tensor_count {
+        read_string(reader)?;
+        let n_dims = read_u32(reader)?;
+        let mut elements: u64 = 1;
+        for _ in 0..n_dims {
+            elements = elements.saturating_mul(read_u64(reader)?);
+        }
+        read_u32(reader)?; // tensor type
+        read_u64(reader)?; // data offset
+        info.parameter_count = info.parameter_count.saturating_add(elements);
+    }
+
+    info.quantization = file_type
+        .map(|t| file_type_name(t).map_or_else(|| format!("type {}", t), str::to_string))
+        .unwrap_or_else(|| "unknown".into());
+    Ok(info)
+}
+
+/// llama.cpp `LLAMA_FTYPE_*` names.
+fn file_type_name(file_type: u64) -> Option<&'static str> {
+    Some(match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        19 => "IQ2_XXS",
+        20 => "IQ2_XS",
+        21 => "Q2_K_S",
+        22 => "IQ3_XS",
+        23 => "IQ3_XXS",
+        24 => "IQ1_S",
+        25 => "IQ4_NL",
+        26 => "IQ3_S",
+        27 => "IQ3_M",
+        28 => "IQ2_S",
+        29 => "IQ2_M",
+        30 => "IQ4_XS",
+        31 => "IQ1_M",
+        32 => "BF16",
+        _ => return None,
+    })
+}
+
+fn read_value(reader: &mut impl Read, value_type: u32) -> std::result::Result<Value, String> {
+    Ok(match value_type {
+        0 | 1 | 7 => Value::Uint(read_bytes::<1>(reader)?[0] as u64),
+        2 | 3 => Value::Uint(u16::from_le_bytes(read_bytes(reader)?) as u64),
+        4 | 5 => Value::Uint(read_u32(reader)? as u64),
+        6 => {
+            read_bytes::<4>(reader)?;
+            Value::Other
+        }
+        8 => Value::Str(read_string(reader)?),
+        9 => {
+            let item_type = read_u32(reader)?;
+            let len = read_u64(reader)?;
+            for _ in 0..len {
+                read_value(reader, item_type)?;
+            }
+            Value::Other
+        }
+        10 | 11 => Value::Uint(read_u64(reader)?),
+        12 => {
+            read_bytes::<8>(reader)?;
+            Value::Other
+        }
+        t => return Err(format!("unknown metadata type {}", t)),
+    })
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> std::result::Result<[u8; N], String> {
+    let mut buf = [0u8; N];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("truncated header: {}", e))?;
+    Ok(buf)
+}
+
+fn read_u32(reader: &mut impl Read) -> std::result::Result<u32, String> {
+    Ok(u32::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_u64(reader: &mut impl Read) -> std::result::Result<u64, String> {
+    Ok(u64::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_string(reader: &mut impl Read) -> std::result::Result<String, String> {
+    let len = read_u64(reader)?;
+    if len > MAX_STRING_LEN {
+        return Err(format!("string of {} bytes", len));
+    }
+    let mut buf = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("truncated header: {}", e))?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(buf: &mut Vec<u8>, s: &str) {
+        buf.extend((s.len() as u64).to_le_bytes());
+        buf.extend(s.as_bytes());
+    }
+
+    fn kv_u32(buf: &mut Vec<u8>, key: &str, value: u32) {
+        string(buf, key);
+        buf.extend(4u32.to_le_bytes());
+        buf.extend(value.to_le_bytes());
+    }
+
+    fn tensor(buf: &mut Vec<u8>, name: &str, dims: &[u64]) {
+        string(buf, name);
+        buf.extend((dims.len() as u32).to_le_bytes());
+        for d in dims {
+            buf.extend(d.to_le_bytes());
+        }
+        buf.extend(12u32.to_le_bytes());
+        buf.extend(0u64.to_le_bytes());
+    }
+
+    fn sample_gguf() -> Vec<u8> {
+        let mut buf = b"GGUF".to_vec();
+        buf.extend(3u32.to_le_bytes());
+        buf.extend(2u64.to_le_bytes()); // tensors
+        buf.extend(6u64.to_le_bytes()); // kv pairs
+        string(&mut buf, "general.architecture");
+        buf.extend(8u32.to_le_bytes());
+        string(&mut buf, "qwen3");
+        string(&mut buf, "general.name");
+        buf.extend(8u32.to_le_bytes());
+        string(&mut buf, "Tiny Qwen");
+        kv_u32(&mut buf, "general.file_type", 15);
+        kv_u32(&mut buf, "qwen3.block_count", 28);
+        kv_u32(&mut buf, "qwen3.context_length", 32768);
+        // An array value (like the tokenizer vocabulary) is skipped
+        string(&mut buf, "tokenizer.ggml.tokens");
+        buf.extend(9u32.to_le_bytes());
+        buf.extend(8u32.to_le_bytes());
+        buf.extend(2u64.to_le_bytes());
+        string(&mut buf, "a");
+        string(&mut buf, "b");
+        tensor(&mut buf, "token_embd.weight", &[1024, 1000]);
+        tensor(&mut buf, "output_norm.weight", &[1024]);
+        buf
+    }
+
+    #[test]
+    fn test_read_info() {
+        let info = read_info(&mut sample_gguf().as_slice()).unwrap();
+        assert_eq!(info.architecture, "qwen3");
+        assert_eq!(info.name.as_deref(), Some("Tiny Qwen"));
+        assert_eq!(info.n_layers, 28);
+        assert_eq!(info.context_length, Some(32768));
+        assert_eq!(info.quantization, "Q4_K_M");
+        assert_eq!(info.parameter_count, 1_025_024);
+        assert_eq!(info.parameters_label(), "1M");
+    }
+
+    #[test]
+    fn test_read_info_rejects_non_gguf() {
+        assert!(read_info(&mut b"PK\x03\x04 not a model".as_slice()).is_err());
+        let mut truncated = sample_gguf();
+        truncated.truncate(40);
+        assert!(read_info(&mut truncated.as_slice()).is_err());
+    }
+}
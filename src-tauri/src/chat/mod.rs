@@ -2,13 +2,25 @@
 //!
 //! Ghost automatically detects hardware, selects the best model, and downloads it.
 //! GPU acceleration is detected at runtime via llama.cpp (Vulkan/CUDA/Metal).
-//! Fallback chain: Native (llama.cpp) → Ollama → None.
-
+//! Fallback chain: Native (llama.cpp) → local OpenAI-compatible server
+//! (Ollama, LM Studio, llama-server, vLLM; see [`openai_compat`]) → None.
+//! When RAM allows, a small "fast" model runs beside the main one and answers
+//! trivial requests (see [`router`]). On CPU, the same small model can serve
+//! as the draft for speculative decoding of the main model's replies.
+//! A remote provider can be enabled as an opt-in backend (see [`remote`]).
+//! Replies can be constrained to a JSON schema (see [`structured`]).
+
+pub mod gguf;
 #[cfg(desktop)]
 pub mod inference;
 pub mod models;
 #[cfg(desktop)]
 pub mod native;
+pub mod openai_compat;
+pub mod remote;
+pub mod router;
+pub mod structured;
+pub mod tokens;
 
 use std::sync::Mutex;
 
@@ -43,6 +55,45 @@ pub struct ChatStatus {
     pub error: Option<String>,
     pub device: String,
     pub download_progress: Option<DownloadProgress>,
+    /// True when the model is loaded and warmed up — chat will respond immediately.
+    pub ready: bool,
+    /// Expected wait before the first token of a reply, in milliseconds.
+    /// Measured by the warm-up run once ready; estimated (download + load) otherwise.
+    pub expected_latency_ms: Option<u64>,
+    /// Small model loaded beside the main one for trivial requests, if any.
+    pub fast_model_id: Option<String>,
+    /// Speculative decoding stats, present while a draft model is loaded.
+    pub speculative: Option<SpeculativeStats>,
+}
+
+/// Speculative decoding statistics since the draft model was loaded.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpeculativeStats {
+    pub draft_model_id: String,
+    /// Tokens proposed by the draft model.
+    pub drafted_tokens: u64,
+    /// Proposed tokens the main model kept.
+    pub accepted_tokens: u64,
+    /// Share of drafted tokens kept (0-1); higher means bigger speedups.
+    pub acceptance_rate: f64,
+}
+
+impl SpeculativeStats {
+    fn new(draft_model_id: &str) -> Self {
+        Self {
+            draft_model_id: draft_model_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add one generation's counts.
+    pub fn record(&mut self, drafted: u64, accepted: u64) {
+        self.drafted_tokens += drafted;
+        self.accepted_tokens += accepted;
+        if self.drafted_tokens > 0 {
+            self.acceptance_rate = self.accepted_tokens as f64 / self.drafted_tokens as f64;
+        }
+    }
 }
 
 /// Chat generation response.
@@ -52,6 +103,10 @@ pub struct ChatResponse {
     pub tokens_generated: usize,
     pub duration_ms: u64,
     pub model_id: String,
+    /// Whether the fast or the main model answered, and why.
+    pub route: router::RouteDecision,
+    /// Set when older messages were dropped to fit the context window.
+    pub context: Option<tokens::ContextBudget>,
 }
 
 /// Unified chat engine with runtime GPU auto-detection.
@@ -68,6 +123,21 @@ pub struct ChatEngine {
     error: Mutex<Option<String>>,
     hardware: HardwareInfo,
     download_progress: std::sync::Arc<Mutex<Option<DownloadProgress>>>,
+    /// First-token latency measured by the post-load warm-up run.
+    #[cfg(desktop)]
+    first_token_ms: Mutex<Option<u64>>,
+    /// Small model for trivial requests, loaded after the main model.
+    #[cfg(desktop)]
+    fast: Mutex<Option<std::sync::Arc<native::NativeChatEngine>>>,
+    /// Fast model setting: "auto", "none", or a registry model ID.
+    fast_model_setting: String,
+    /// Draft model for speculative decoding (often the same as `fast`).
+    #[cfg(desktop)]
+    draft: Mutex<Option<std::sync::Arc<native::NativeChatEngine>>>,
+    /// Whether to load a draft model when the main model runs on CPU.
+    speculative_decoding: bool,
+    #[cfg(desktop)]
+    speculative_stats: Mutex<SpeculativeStats>,
 }
 
 impl ChatEngine {
@@ -81,9 +151,33 @@ impl ChatEngine {
             error: Mutex::new(None),
             hardware,
             download_progress: std::sync::Arc::new(Mutex::new(None)),
+            #[cfg(desktop)]
+            first_token_ms: Mutex::new(None),
+            #[cfg(desktop)]
+            fast: Mutex::new(None),
+            fast_model_setting: "auto".into(),
+            #[cfg(desktop)]
+            draft: Mutex::new(None),
+            speculative_decoding: true,
+            #[cfg(desktop)]
+            speculative_stats: Mutex::new(SpeculativeStats::default()),
         }
     }
 
+    /// Choose the fast model loaded beside the main one ("auto", "none", or
+    /// a registry model ID). Takes effect on the next load.
+    pub fn with_fast_model(mut self, setting: &str) -> Self {
+        self.fast_model_setting = setting.to_string();
+        self
+    }
+
+    /// Turn speculative decoding with a draft model on or off. Takes effect
+    /// on the next load.
+    pub fn with_speculative_decoding(mut self, enabled: bool) -> Self {
+        self.speculative_decoding = enabled;
+        self
+    }
+
     /// Load the active model. Downloads from HuggingFace Hub on first run.
     /// On mobile, this is a no-op (mobile uses Ollama only).
     pub async fn load_model(&self) {
@@ -147,14 +241,36 @@ impl ChatEngine {
         let progress = self.download_progress.clone();
         match native::NativeChatEngine::load(profile, progress.clone()).await {
             Ok(engine) => {
+                // Warm up before publishing the engine so "ready" means instant replies.
+                if let Some(ref mut p) = *progress.lock().unwrap_or_else(|e| e.into_inner()) {
+                    p.phase = "warming_up".into();
+                }
+                let first_token_ms = match engine.warm_up() {
+                    Ok(ms) => Some(ms),
+                    Err(e) => {
+                        tracing::warn!("Warm-up generation failed for {}: {}", profile.name, e);
+                        None
+                    }
+                };
+                *self
+                    .first_token_ms
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = first_token_ms;
+
                 tracing::info!(
-                    "Chat engine ready: {} on {} (gpu={})",
+                    "Chat engine ready: {} on {} (gpu={}, first_token={:?}ms)",
                     profile.name,
                     engine.gpu_backend(),
-                    engine.is_gpu_active()
+                    engine.is_gpu_active(),
+                    first_token_ms
                 );
+                let gpu_active = engine.is_gpu_active();
                 *self.native.lock().unwrap_or_else(|e| e.into_inner()) = Some(engine);
                 *self.error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                self.load_fast_model(profile, progress.clone()).await;
+                if self.speculative_decoding && !gpu_active {
+                    self.load_draft_model(profile, progress.clone()).await;
+                }
             }
             Err(e) => {
                 tracing::error!("Failed to load {}: {}", profile.name, e);
@@ -169,6 +285,70 @@ impl ChatEngine {
             .unwrap_or_else(|e| e.into_inner()) = None;
     }
 
+    /// Load the fast model beside `main` when RAM permits. Failures only
+    /// disable routing; the main model keeps answering everything.
+    #[cfg(desktop)]
+    async fn load_fast_model(
+        &self,
+        main: &models::ModelProfile,
+        progress: std::sync::Arc<Mutex<Option<DownloadProgress>>>,
+    ) {
+        *self.fast.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let Some(profile) =
+            router::select_fast_model(&self.hardware, main, &self.fast_model_setting)
+        else {
+            return;
+        };
+        match native::NativeChatEngine::load(profile, progress).await {
+            Ok(engine) => {
+                tracing::info!("Fast model ready: {} beside {}", profile.name, main.name);
+                *self.fast.lock().unwrap_or_else(|e| e.into_inner()) =
+                    Some(std::sync::Arc::new(engine));
+            }
+            Err(e) => tracing::warn!("Failed to load fast model {}: {}", profile.name, e),
+        }
+    }
+
+    /// Load the smallest model of `main`'s family as the speculative decoding
+    /// draft, sharing the fast model when it is the same one.
+    #[cfg(desktop)]
+    async fn load_draft_model(
+        &self,
+        main: &models::ModelProfile,
+        progress: std::sync::Arc<Mutex<Option<DownloadProgress>>>,
+    ) {
+        let Some(profile) = router::select_fast_model(&self.hardware, main, "auto") else {
+            return;
+        };
+        let shared = self
+            .fast
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|engine| engine.model_id() == profile.id)
+            .cloned();
+        let engine = match shared {
+            Some(engine) => engine,
+            None => match native::NativeChatEngine::load(profile, progress).await {
+                Ok(engine) => std::sync::Arc::new(engine),
+                Err(e) => {
+                    tracing::warn!("Failed to load draft model {}: {}", profile.name, e);
+                    return;
+                }
+            },
+        };
+        tracing::info!(
+            "Speculative decoding on: {} drafts for {}",
+            profile.name,
+            main.name
+        );
+        *self
+            .speculative_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = SpeculativeStats::new(profile.id);
+        *self.draft.lock().unwrap_or_else(|e| e.into_inner()) = Some(engine);
+    }
+
     /// Switch to a different model.
     pub async fn switch_model(&self, model_id: &str) -> Result<()> {
         let profile = models::find_model(model_id)
@@ -185,6 +365,12 @@ impl ChatEngine {
         #[cfg(desktop)]
         {
             *self.native.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            *self.fast.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            *self.draft.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            *self
+                .first_token_ms
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = None;
         }
         *self
             .active_model_id
@@ -221,6 +407,32 @@ impl ChatEngine {
             .lock()
             .unwrap_or_else(|e| e.into_inner())
             .clone();
+        #[cfg(desktop)]
+        let first_token_ms = *self
+            .first_token_ms
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let estimated_ms = models::find_model(&model_id)
+            .map(|p| models::estimate_ready_ms(p, models::is_model_cached(p)));
+        #[cfg(desktop)]
+        let fast_model_id = self
+            .fast
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|engine| engine.model_id().to_string());
+        #[cfg(desktop)]
+        let speculative = self
+            .draft
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+            .then(|| {
+                self.speculative_stats
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone()
+            });
 
         #[cfg(desktop)]
         if let Some(ref engine) = *native {
@@ -233,9 +445,14 @@ impl ChatEngine {
                 error: None,
                 device: engine.gpu_backend().to_string(),
                 download_progress: None,
+                ready: true,
+                expected_latency_ms: first_token_ms,
+                fast_model_id,
+                speculative,
             };
         }
 
+        let server = openai_compat::current();
         if loading {
             let model_name = models::find_model(&model_id)
                 .map(|p| p.name.to_string())
@@ -249,17 +466,25 @@ impl ChatEngine {
                 error: None,
                 device: "detecting".into(),
                 download_progress: progress,
+                ready: false,
+                expected_latency_ms: estimated_ms,
+                fast_model_id: None,
+                speculative: None,
             }
-        } else if check_ollama_sync() {
+        } else if server.is_reachable() {
             ChatStatus {
                 available: true,
-                backend: "ollama".into(),
-                model_id: "ollama-default".into(),
-                model_name: "Ollama".into(),
+                backend: server.backend_name().into(),
+                model_id: server.model.clone(),
+                model_name: server.model,
                 loading: false,
                 error: None,
                 device: "external".into(),
                 download_progress: None,
+                ready: true,
+                expected_latency_ms: None,
+                fast_model_id: None,
+                speculative: None,
             }
         } else {
             let model_name = models::find_model(&model_id)
@@ -274,38 +499,88 @@ impl ChatEngine {
                 error,
                 device: "none".into(),
                 download_progress: None,
+                ready: false,
+                expected_latency_ms: estimated_ms,
+                fast_model_id: None,
+                speculative: None,
             }
         }
     }
 
     /// Generate a chat response.
+    ///
+    /// Trivial requests go to the fast model when one is loaded; everything
+    /// else uses the main model.
     pub async fn chat(&self, messages: &[ChatMessage], max_tokens: usize) -> Result<ChatResponse> {
         let start = std::time::Instant::now();
 
         // Try native engine first (desktop only)
         #[cfg(desktop)]
         {
+            let (fitted, context) = self.fit_context(messages, max_tokens);
+            let messages = fitted.as_slice();
+            let context = context.filter(tokens::ContextBudget::truncated);
+            let decision = router::route(messages);
+            if decision.route == router::ModelRoute::Fast {
+                let fast = self.fast.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(ref engine) = *fast {
+                    let content = engine.generate(messages, max_tokens)?;
+                    let duration = start.elapsed();
+                    let token_count = content.split_whitespace().count();
+                    return Ok(ChatResponse {
+                        content,
+                        tokens_generated: token_count,
+                        duration_ms: duration.as_millis() as u64,
+                        model_id: engine.model_id().to_string(),
+                        route: decision,
+                        context,
+                    });
+                }
+            }
+
             let model_id = self
                 .active_model_id
                 .lock()
                 .unwrap_or_else(|e| e.into_inner())
                 .clone();
+            let draft = self.draft.lock().unwrap_or_else(|e| e.into_inner()).clone();
             let native = self.native.lock().unwrap_or_else(|e| e.into_inner());
             if let Some(ref engine) = *native {
-                let content = engine.generate(messages, max_tokens)?;
+                let content = match draft {
+                    Some(draft) => {
+                        let output = engine.generate_speculative(&draft, messages, max_tokens)?;
+                        self.speculative_stats
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .record(output.drafted_tokens, output.accepted_tokens);
+                        output.text
+                    }
+                    None => engine.generate(messages, max_tokens)?,
+                };
                 let duration = start.elapsed();
                 let token_count = content.split_whitespace().count();
+                let route = match decision.route {
+                    router::ModelRoute::Smart => decision,
+                    router::ModelRoute::Fast => {
+                        router::RouteDecision::smart("no fast model loaded")
+                    }
+                    // `route` never picks the cloud; remote chats don't get here
+                    router::ModelRoute::Cloud => router::RouteDecision::smart("local model"),
+                };
                 return Ok(ChatResponse {
                     content,
                     tokens_generated: token_count,
                     duration_ms: duration.as_millis() as u64,
                     model_id,
+                    route,
+                    context,
                 });
             }
         }
 
-        // Fall back to Ollama
-        let content = ollama_chat(messages, max_tokens).await?;
+        // Fall back to the local OpenAI-compatible server
+        let server = openai_compat::current();
+        let content = openai_compat::chat(&server, messages, max_tokens).await?;
         let duration = start.elapsed();
         let token_count = content.split_whitespace().count();
 
@@ -313,10 +588,145 @@ impl ChatEngine {
             content,
             tokens_generated: token_count,
             duration_ms: duration.as_millis() as u64,
-            model_id: "ollama".into(),
+            route: router::RouteDecision::smart(format!("{} fallback", server.backend_name())),
+            model_id: server.model,
+            context: None,
+        })
+    }
+
+    /// Generate a reply that is JSON matching `schema`, parsed and checked.
+    pub async fn chat_structured(
+        &self,
+        messages: &[ChatMessage],
+        schema: &serde_json::Value,
+        max_tokens: usize,
+    ) -> Result<structured::StructuredResponse> {
+        let response = self.chat_constrained(messages, schema, max_tokens).await?;
+        Ok(structured::StructuredResponse {
+            value: structured::parse_reply(&response.content, schema)?,
+            tokens_generated: response.tokens_generated,
+            duration_ms: response.duration_ms,
+            model_id: response.model_id,
+        })
+    }
+
+    /// Generate a reply constrained to `schema`, unparsed.
+    ///
+    /// The main native model samples under a grammar compiled from the
+    /// schema, so its reply is valid JSON unless `max_tokens` cuts it off;
+    /// the fallback server is asked for it via `response_format`.
+    pub async fn chat_constrained(
+        &self,
+        messages: &[ChatMessage],
+        schema: &serde_json::Value,
+        max_tokens: usize,
+    ) -> Result<ChatResponse> {
+        structured::check_schema(schema)?;
+        let start = std::time::Instant::now();
+        let messages = structured::with_schema_instruction(messages, schema);
+
+        #[cfg(desktop)]
+        {
+            let grammar = structured::schema_to_grammar(schema)?;
+            let (fitted, context) = self.fit_context(&messages, max_tokens);
+            let model_id = self
+                .active_model_id
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            let native = self.native.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(ref engine) = *native {
+                let content = engine.generate_constrained(&fitted, max_tokens, Some(&grammar))?;
+                return Ok(ChatResponse {
+                    tokens_generated: content.split_whitespace().count(),
+                    content,
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    model_id,
+                    route: router::RouteDecision::smart("structured output"),
+                    context: context.filter(tokens::ContextBudget::truncated),
+                });
+            }
+        }
+
+        let server = openai_compat::current();
+        let content =
+            openai_compat::chat_structured(&server, &messages, max_tokens, schema).await?;
+        Ok(ChatResponse {
+            tokens_generated: content.split_whitespace().count(),
+            content,
+            duration_ms: start.elapsed().as_millis() as u64,
+            route: router::RouteDecision::smart(format!("{} fallback", server.backend_name())),
+            model_id: server.model,
+            context: None,
         })
     }
 
+    /// Trim `messages` to the native model's context window, leaving room
+    /// for the reply. Unchanged (and no budget) when no model is loaded.
+    #[cfg(desktop)]
+    fn fit_context(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: usize,
+    ) -> (Vec<ChatMessage>, Option<tokens::ContextBudget>) {
+        let context_size = match *self.native.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(ref engine) => engine.context_size(),
+            None => return (messages.to_vec(), None),
+        };
+        let model_id = self
+            .active_model_id
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let budget = context_size.saturating_sub(tokens::reply_reserve(max_tokens, context_size));
+        let (fitted, context) = tokens::fit_history(messages, &model_id, budget);
+        (fitted, Some(context))
+    }
+
+    /// Generate a chat response, using the remote provider when the caller
+    /// allows cloud use and one is configured; otherwise as [`Self::chat`].
+    pub async fn chat_with_cloud(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: usize,
+        allow_cloud: bool,
+    ) -> Result<ChatResponse> {
+        let Some(remote) = remote::active().filter(|_| allow_cloud) else {
+            return self.chat(messages, max_tokens).await;
+        };
+        let start = std::time::Instant::now();
+        let reply = remote::chat(&remote, messages, max_tokens).await?;
+        Ok(ChatResponse {
+            tokens_generated: reply
+                .tokens_generated
+                .unwrap_or_else(|| reply.content.split_whitespace().count()),
+            content: reply.content,
+            duration_ms: start.elapsed().as_millis() as u64,
+            model_id: remote.model_name().to_string(),
+            route: router::RouteDecision {
+                route: router::ModelRoute::Cloud,
+                reason: format!("cloud allowed ({:?})", remote.provider),
+            },
+            context: None,
+        })
+    }
+
+    /// IDs of the main model and any fast or draft models loaded beside it.
+    pub fn loaded_model_ids(&self) -> Vec<String> {
+        let mut ids = vec![self
+            .active_model_id
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()];
+        #[cfg(desktop)]
+        for engine in [&self.fast, &self.draft] {
+            if let Some(ref engine) = *engine.lock().unwrap_or_else(|e| e.into_inner()) {
+                ids.push(engine.model_id().to_string());
+            }
+        }
+        ids
+    }
+
     /// Get list of available models with runtime status.
     pub fn available_models(&self) -> Vec<models::ModelInfo> {
         let active = self
@@ -332,59 +742,3 @@ impl ChatEngine {
         models::recommend_model(&self.hardware).id.to_string()
     }
 }
-
-/// Quick synchronous check if Ollama is reachable.
-fn check_ollama_sync() -> bool {
-    std::net::TcpStream::connect_timeout(
-        &std::net::SocketAddr::from(([127, 0, 0, 1], 11434)),
-        std::time::Duration::from_millis(100),
-    )
-    .is_ok()
-}
-
-/// Chat with Ollama via HTTP API.
-async fn ollama_chat(messages: &[ChatMessage], max_tokens: usize) -> Result<String> {
-    let client = reqwest::Client::new();
-
-    let body = serde_json::json!({
-        "model": "qwen2.5:0.5b",
-        "messages": messages,
-        "stream": false,
-        "options": {
-            "num_predict": max_tokens,
-        }
-    });
-
-    let response = client
-        .post("http://localhost:11434/api/chat")
-        .json(&body)
-        .timeout(std::time::Duration::from_secs(120))
-        .send()
-        .await
-        .map_err(|e| GhostError::Chat(format!("Ollama unavailable: {}", e)))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        return Err(GhostError::Chat(format!(
-            "Ollama returned {}: {}",
-            status, body
-        )));
-    }
-
-    #[derive(Deserialize)]
-    struct OllamaResponse {
-        message: OllamaMessage,
-    }
-    #[derive(Deserialize)]
-    struct OllamaMessage {
-        content: String,
-    }
-
-    let result: OllamaResponse = response
-        .json()
-        .await
-        .map_err(|e| GhostError::Chat(format!("Failed to parse Ollama response: {}", e)))?;
-
-    Ok(result.message.content)
-}
@@ -0,0 +1,288 @@
+//! Hugging Face Hub browser for GGUF chat models.
+//!
+//! Searches the Hub for GGUF repos, keeps the files that fit this machine's
+//! RAM, and reports size, quantization and license so the user can pick one.
+//! Picked files are downloaded through the resumable download manager and
+//! registered as imported models (see [`super::import_custom_model`]).
+
+use serde::{Deserialize, Serialize};
+
+use crate::embeddings::hardware::HardwareInfo;
+use crate::error::{GhostError, Result};
+
+const HUB_API: &str = "https://huggingface.co/api/models";
+/// Repos fetched per search (each needs one detail request for file sizes).
+const SEARCH_LIMIT: usize = 12;
+/// RAM kept free for the OS and the app (MB), as for built-in models.
+const HEADROOM_MB: u64 = 512;
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+
+/// A GGUF file in a Hub repo.
+#[derive(Debug, Clone, Serialize)]
+pub struct HubFile {
+    pub filename: String,
+    pub size_mb: u64,
+    /// Quantization parsed from the filename (e.g. "Q4_K_M").
+    pub quantization: Option<String>,
+}
+
+/// A Hub repo with GGUF files that fit this machine.
+#[derive(Debug, Clone, Serialize)]
+pub struct HubModel {
+    pub repo_id: String,
+    pub license: Option<String>,
+    pub downloads: u64,
+    pub likes: u64,
+    /// Files that fit in available RAM, smallest first.
+    pub files: Vec<HubFile>,
+    /// Largest fitting file with a 4-bit or better quantization, if any.
+    pub recommended_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoSummary {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoDetails {
+    id: String,
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    likes: u64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, rename = "cardData")]
+    card_data: Option<serde_json::Value>,
+    #[serde(default)]
+    siblings: Vec<Sibling>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sibling {
+    rfilename: String,
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+/// Search the Hub for GGUF models matching `query` that fit `hardware`.
+///
+/// Results keep the Hub's download ranking; repos with no fitting file are
+/// dropped.
+pub async fn search_hub_models(query: &str, hardware: &HardwareInfo) -> Result<Vec<HubModel>> {
+    let client = reqwest::Client::new();
+    let limit = SEARCH_LIMIT.to_string();
+    let summaries: Vec<RepoSummary> = get_json(client.get(HUB_API).query(&[
+        ("search", query.trim()),
+        ("filter", "gguf"),
+        ("sort", "downloads"),
+        ("direction", "-1"),
+        ("limit", limit.as_str()),
+    ]))
+    .await?;
+
+    let details = futures::future::join_all(summaries.iter().map(|repo| {
+        get_json::<RepoDetails>(
+            client
+                .get(format!("{}/{}", HUB_API, repo.id))
+                .query(&[("blobs", "true")]),
+        )
+    }))
+    .await;
+
+    let max_size_mb = max_model_size_mb(hardware);
+    Ok(details
+        .into_iter()
+        .filter_map(|result| match result {
+            Ok(details) => to_hub_model(details, max_size_mb),
+            Err(e) => {
+                tracing::debug!("Skipping Hub repo: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+    let response = request
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| GhostError::Chat(format!("Hugging Face Hub unavailable: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(GhostError::Chat(format!(
+            "Hugging Face Hub returned {}",
+            response.status()
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| GhostError::Chat(format!("Failed to parse Hub response: {}", e)))
+}
+
+/// Largest GGUF (MB) whose weights, KV cache and headroom fit in available RAM.
+fn max_model_size_mb(hardware: &HardwareInfo) -> u64 {
+    let mut size = hardware.available_ram_mb.saturating_sub(HEADROOM_MB);
+    while size > 0 && super::estimated_ram_mb(size) + HEADROOM_MB > hardware.available_ram_mb {
+        size -= size.div_ceil(64).max(1);
+    }
+    size
+}
+
+fn to_hub_model(details: RepoDetails, max_size_mb: u64) -> Option<HubModel> {
+    let mut files: Vec<HubFile> = details
+        .siblings
+        .iter()
+        .filter(|s| s.rfilename.to_lowercase().ends_with(".gguf"))
+        // Split shards and multimodal projectors can't be loaded as one model
+        .filter(|s| !is_shard(&s.rfilename) && !s.rfilename.to_lowercase().contains("mmproj"))
+        .filter_map(|s| {
+            let size_mb = s.size? / 1_048_576;
+            (size_mb > 0 && size_mb <= max_size_mb).then(|| HubFile {
+                filename: s.rfilename.clone(),
+                size_mb,
+                quantization: quantization_from_filename(&s.rfilename),
+            })
+        })
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    files.sort_by_key(|f| f.size_mb);
+
+    let recommended_file = files
+        .iter()
+        .rev()
+        .find(|f| f.quantization.as_deref().is_some_and(is_good_quantization))
+        .map(|f| f.filename.clone());
+    let license = details
+        .card_data
+        .as_ref()
+        .and_then(|card| card["license"].as_str().map(str::to_string))
+        .or_else(|| {
+            details
+                .tags
+                .iter()
+                .find_map(|t| t.strip_prefix("license:").map(str::to_string))
+        });
+    Some(HubModel {
+        repo_id: details.id,
+        license,
+        downloads: details.downloads,
+        likes: details.likes,
+        files,
+        recommended_file,
+    })
+}
+
+/// Whether a filename is part of a split GGUF ("-00001-of-00003.gguf").
+fn is_shard(filename: &str) -> bool {
+    filename
+        .to_lowercase()
+        .trim_end_matches(".gguf")
+        .rsplit_once("-of-")
+        .is_some_and(|(_, total)| total.len() == 5 && total.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Quantization label in a GGUF filename ("qwen2.5-7b-instruct-q4_k_m.gguf" → "Q4_K_M").
+fn quantization_from_filename(filename: &str) -> Option<String> {
+    let stem = filename
+        .rsplit('/')
+        .next()
+        .unwrap_or(filename)
+        .trim_end_matches(".gguf")
+        .trim_end_matches(".GGUF");
+    stem.split(['-', '.']).map(str::to_uppercase).find(|part| {
+        let digits = part
+            .strip_prefix("IQ")
+            .or_else(|| part.strip_prefix('Q'))
+            .or_else(|| part.strip_prefix("BF"))
+            .or_else(|| part.strip_prefix('F'));
+        digits.is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+    })
+}
+
+/// 4-bit and up K-quants/legacy quants keep chat quality close to full precision.
+fn is_good_quantization(quantization: &str) -> bool {
+    let bits = quantization
+        .trim_start_matches("IQ")
+        .trim_start_matches('Q')
+        .chars()
+        .next()
+        .and_then(|c| c.to_digit(10));
+    bits.is_some_and(|b| (4..=8).contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sibling(name: &str, mb: u64) -> Sibling {
+        Sibling {
+            rfilename: name.into(),
+            size: Some(mb * 1_048_576),
+        }
+    }
+
+    #[test]
+    fn test_quantization_from_filename() {
+        assert_eq!(
+            quantization_from_filename("qwen2.5-7b-instruct-q4_k_m.gguf").as_deref(),
+            Some("Q4_K_M")
+        );
+        assert_eq!(
+            quantization_from_filename("Qwen3-4B-Q8_0.gguf").as_deref(),
+            Some("Q8_0")
+        );
+        assert_eq!(
+            quantization_from_filename("Llama-3.2-3B-Instruct-IQ4_XS.gguf").as_deref(),
+            Some("IQ4_XS")
+        );
+        assert_eq!(
+            quantization_from_filename("model-f16.gguf").as_deref(),
+            Some("F16")
+        );
+        assert_eq!(quantization_from_filename("model.gguf"), None);
+    }
+
+    #[test]
+    fn test_to_hub_model_filters_by_size() {
+        let details = RepoDetails {
+            id: "org/Model-GGUF".into(),
+            downloads: 10,
+            likes: 2,
+            tags: vec!["gguf".into(), "license:mit".into()],
+            card_data: None,
+            siblings: vec![
+                sibling("README.md", 1),
+                sibling("model-Q2_K.gguf", 900),
+                sibling("model-Q4_K_M.gguf", 1500),
+                sibling("model-Q8_0.gguf", 2600),
+                sibling("model-F16-00001-of-00002.gguf", 1000),
+                sibling("mmproj-model-f16.gguf", 600),
+            ],
+        };
+        let model = to_hub_model(details, 2000).unwrap();
+        let names: Vec<&str> = model.files.iter().map(|f| f.filename.as_str()).collect();
+        assert_eq!(names, ["model-Q2_K.gguf", "model-Q4_K_M.gguf"]);
+        assert_eq!(model.recommended_file.as_deref(), Some("model-Q4_K_M.gguf"));
+        assert_eq!(model.license.as_deref(), Some("mit"));
+    }
+
+    #[test]
+    fn test_max_model_size_fits_ram() {
+        let hardware = HardwareInfo {
+            cpu_cores: 4,
+            has_avx2: true,
+            has_neon: false,
+            gpu_backend: None,
+            total_ram_mb: 16384,
+            available_ram_mb: 8192,
+        };
+        let max = max_model_size_mb(&hardware);
+        assert!(max > 4000);
+        assert!(crate::chat::models::estimated_ram_mb(max) + HEADROOM_MB <= 8192);
+    }
+}
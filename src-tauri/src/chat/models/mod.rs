@@ -0,0 +1,819 @@
+//! Model registry for Ghost chat engine.
+//!
+//! Defines available models with hardware requirements and auto-selection logic.
+//! All models use the Qwen2.5-Instruct family (ChatML format, Apache 2.0 license).
+
+pub mod hub;
+
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::embeddings::hardware::HardwareInfo;
+use crate::error::{GhostError, Result};
+
+/// A downloadable chat model profile.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelProfile {
+    /// Unique identifier (e.g., "qwen2.5-0.5b").
+    pub id: &'static str,
+    /// Human-readable name.
+    pub name: &'static str,
+    /// Short description for the UI.
+    pub description: &'static str,
+    /// HuggingFace repo containing the GGUF weights.
+    pub repo_id: &'static str,
+    /// GGUF filename within the repo.
+    pub gguf_file: &'static str,
+    /// HuggingFace repo containing the tokenizer.
+    pub tokenizer_repo: &'static str,
+    /// Approximate download size in MB.
+    pub size_mb: u64,
+    /// Minimum available RAM to run comfortably (MB).
+    pub min_ram_mb: u64,
+    /// Parameter count string (e.g., "0.5B").
+    pub parameters: &'static str,
+    /// Quality tier: 1=basic, 2=good, 3=better, 4=best.
+    pub quality_tier: u8,
+    /// Number of transformer layers in the model architecture.
+    /// Used for smart GPU layer allocation (partial offload).
+    pub n_layers: u32,
+    /// Model family identifier for grouping in UI.
+    pub family: &'static str,
+    /// Whether this model supports thinking mode (/think, /no_think).
+    pub supports_thinking: bool,
+}
+
+/// All available models, ordered from smallest to largest.
+///
+/// Qwen3 models are preferred (better tool calling, thinking mode, multilingual).
+/// Qwen2.5 kept as fallback for lower RAM requirements and proven stability.
+pub const MODEL_REGISTRY: &[ModelProfile] = &[
+    // ── Qwen3 family (recommended — superior tool calling + thinking mode) ──
+    ModelProfile {
+        id: "qwen3-0.6b",
+        name: "Qwen3 0.6B",
+        description: "Ultra-fast with thinking mode. Ideal for low-end hardware and phones.",
+        repo_id: "Qwen/Qwen3-0.6B-GGUF",
+        gguf_file: "Qwen3-0.6B-Q8_0.gguf",
+        tokenizer_repo: "Qwen/Qwen3-0.6B",
+        size_mb: 639,
+        min_ram_mb: 1024,
+        parameters: "0.6B",
+        quality_tier: 1,
+        n_layers: 28,
+        family: "qwen3",
+        supports_thinking: true,
+    },
+    ModelProfile {
+        id: "qwen3-1.7b",
+        name: "Qwen3 1.7B",
+        description: "Great balance of speed and quality with thinking mode.",
+        repo_id: "Qwen/Qwen3-1.7B-GGUF",
+        gguf_file: "Qwen3-1.7B-Q8_0.gguf",
+        tokenizer_repo: "Qwen/Qwen3-1.7B",
+        size_mb: 1830,
+        min_ram_mb: 2560,
+        parameters: "1.7B",
+        quality_tier: 2,
+        n_layers: 28,
+        family: "qwen3",
+        supports_thinking: true,
+    },
+    ModelProfile {
+        id: "qwen3-4b",
+        name: "Qwen3 4B",
+        description: "Strong reasoning and tool calling. Recommended for 8GB+ systems.",
+        repo_id: "Qwen/Qwen3-4B-GGUF",
+        gguf_file: "Qwen3-4B-Q4_K_M.gguf",
+        tokenizer_repo: "Qwen/Qwen3-4B",
+        size_mb: 2500,
+        min_ram_mb: 4096,
+        parameters: "4B",
+        quality_tier: 3,
+        n_layers: 36,
+        family: "qwen3",
+        supports_thinking: true,
+    },
+    ModelProfile {
+        id: "qwen3-8b",
+        name: "Qwen3 8B",
+        description: "Best quality. Thinking mode + superior tool calling. Needs 10GB+ RAM.",
+        repo_id: "Qwen/Qwen3-8B-GGUF",
+        gguf_file: "Qwen3-8B-Q4_K_M.gguf",
+        tokenizer_repo: "Qwen/Qwen3-8B",
+        size_mb: 5030,
+        min_ram_mb: 10240,
+        parameters: "8B",
+        quality_tier: 4,
+        n_layers: 36,
+        family: "qwen3",
+        supports_thinking: true,
+    },
+    // ── Qwen2.5 family (legacy — proven stability, lower RAM requirements) ──
+    ModelProfile {
+        id: "qwen2.5-0.5b",
+        name: "Qwen2.5 0.5B",
+        description: "Ultra-fast, basic quality. Ideal for low-end hardware.",
+        repo_id: "Qwen/Qwen2.5-0.5B-Instruct-GGUF",
+        gguf_file: "qwen2.5-0.5b-instruct-q4_k_m.gguf",
+        tokenizer_repo: "Qwen/Qwen2.5-0.5B-Instruct",
+        size_mb: 400,
+        min_ram_mb: 1024,
+        parameters: "0.5B",
+        quality_tier: 1,
+        n_layers: 24,
+        family: "qwen2.5",
+        supports_thinking: false,
+    },
+    ModelProfile {
+        id: "qwen2.5-1.5b",
+        name: "Qwen2.5 1.5B",
+        description: "Good balance of speed and quality. Recommended for most PCs.",
+        repo_id: "Qwen/Qwen2.5-1.5B-Instruct-GGUF",
+        gguf_file: "qwen2.5-1.5b-instruct-q4_k_m.gguf",
+        tokenizer_repo: "Qwen/Qwen2.5-1.5B-Instruct",
+        size_mb: 1000,
+        min_ram_mb: 2048,
+        parameters: "1.5B",
+        quality_tier: 2,
+        n_layers: 28,
+        family: "qwen2.5",
+        supports_thinking: false,
+    },
+    ModelProfile {
+        id: "qwen2.5-3b",
+        name: "Qwen2.5 3B",
+        description: "Higher quality responses. Needs 4GB+ free RAM.",
+        repo_id: "Qwen/Qwen2.5-3B-Instruct-GGUF",
+        gguf_file: "qwen2.5-3b-instruct-q4_k_m.gguf",
+        tokenizer_repo: "Qwen/Qwen2.5-3B-Instruct",
+        size_mb: 2000,
+        min_ram_mb: 4096,
+        parameters: "3B",
+        quality_tier: 3,
+        n_layers: 36,
+        family: "qwen2.5",
+        supports_thinking: false,
+    },
+    ModelProfile {
+        id: "qwen2.5-7b",
+        name: "Qwen2.5 7B",
+        description: "Best quality. Needs 8GB+ free RAM and patience.",
+        repo_id: "Qwen/Qwen2.5-7B-Instruct-GGUF",
+        gguf_file: "qwen2.5-7b-instruct-q3_k_m.gguf",
+        tokenizer_repo: "Qwen/Qwen2.5-7B-Instruct",
+        size_mb: 3810,
+        min_ram_mb: 8192,
+        parameters: "7B",
+        quality_tier: 4,
+        n_layers: 28,
+        family: "qwen2.5",
+        supports_thinking: false,
+    },
+];
+
+/// Find a model by ID (built-in or imported). Returns None if not found.
+pub fn find_model(id: &str) -> Option<&'static ModelProfile> {
+    MODEL_REGISTRY.iter().find(|m| m.id == id).or_else(|| {
+        CUSTOM_MODELS
+            .read()
+            .ok()?
+            .iter()
+            .find(|(custom, _)| custom.id == id)
+            .map(|(_, profile)| *profile)
+    })
+}
+
+/// Family of every imported model.
+pub const CUSTOM_FAMILY: &str = "custom";
+
+/// A local GGUF the user imported, persisted in settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomModel {
+    /// Registry ID ("custom-" + slug of the name).
+    pub id: String,
+    pub name: String,
+    /// Absolute path to the GGUF file.
+    pub path: String,
+    /// Context window to run the model with, in tokens.
+    pub context_window: u32,
+    /// Parameter count label (e.g. "7.6B").
+    pub parameters: String,
+    /// Weight quantization (e.g. "Q4_K_M").
+    pub quantization: String,
+    pub architecture: String,
+    pub n_layers: u32,
+    pub size_mb: u64,
+}
+
+impl CustomModel {
+    /// Describe a probed GGUF as a custom model.
+    pub fn from_gguf(
+        path: &std::path::Path,
+        name: &str,
+        context_window: u32,
+        info: &super::gguf::GgufInfo,
+    ) -> Self {
+        let size_mb = std::fs::metadata(path)
+            .map(|m| m.len() / 1_048_576)
+            .unwrap_or(0);
+        Self {
+            id: custom_model_id(name),
+            name: name.trim().to_string(),
+            path: path.to_string_lossy().to_string(),
+            context_window,
+            parameters: info.parameters_label(),
+            quantization: info.quantization.clone(),
+            architecture: info.architecture.clone(),
+            n_layers: info.n_layers,
+            size_mb,
+        }
+    }
+
+    /// A static registry profile for this model.
+    ///
+    /// Leaked once per import so it can be returned from [`find_model`] like
+    /// the built-in profiles; imports are rare and few.
+    fn leak_profile(&self) -> &'static ModelProfile {
+        let leak = |s: String| -> &'static str { Box::leak(s.into_boxed_str()) };
+        Box::leak(Box::new(ModelProfile {
+            id: leak(self.id.clone()),
+            name: leak(self.name.clone()),
+            description: leak(format!(
+                "Imported {} model ({}, {}).",
+                self.architecture, self.parameters, self.quantization
+            )),
+            repo_id: "",
+            gguf_file: leak(self.path.clone()),
+            tokenizer_repo: "",
+            size_mb: self.size_mb,
+            min_ram_mb: estimated_ram_mb(self.size_mb),
+            parameters: leak(self.parameters.clone()),
+            quality_tier: 2,
+            n_layers: self.n_layers,
+            family: CUSTOM_FAMILY,
+            supports_thinking: self.architecture == "qwen3",
+        }))
+    }
+}
+
+/// Smallest context window an imported model may be given.
+const MIN_CUSTOM_CONTEXT: u32 = 512;
+/// Context window used when the import does not choose one.
+const DEFAULT_CUSTOM_CONTEXT: u32 = 4096;
+
+/// Validate a local GGUF and describe it as a new custom model.
+///
+/// `context_window` defaults to 4096 and is capped at the length the model
+/// was trained with. Names must not collide with a registered model.
+pub fn import_custom_model(
+    path: &std::path::Path,
+    name: &str,
+    context_window: Option<u32>,
+) -> Result<CustomModel> {
+    if name.trim().is_empty() {
+        return Err(GhostError::Chat("Model name is required".into()));
+    }
+    if !path.is_file() {
+        return Err(GhostError::Chat(format!(
+            "{} is not a file",
+            path.display()
+        )));
+    }
+    let is_gguf = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gguf"));
+    if !is_gguf {
+        return Err(GhostError::Chat(format!(
+            "{} is not a .gguf file",
+            path.display()
+        )));
+    }
+    let id = custom_model_id(name);
+    if find_model(&id).is_some() {
+        return Err(GhostError::Chat(format!(
+            "A model named \"{}\" already exists",
+            name.trim()
+        )));
+    }
+
+    let info = super::gguf::probe(path)?;
+    let trained = info.context_length.unwrap_or(u32::MAX);
+    let context_window = context_window
+        .unwrap_or(DEFAULT_CUSTOM_CONTEXT)
+        .min(trained)
+        .max(MIN_CUSTOM_CONTEXT);
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    Ok(CustomModel::from_gguf(&path, name, context_window, &info))
+}
+
+/// RAM needed to run a GGUF of `size_mb`: weights plus KV cache and runtime overhead.
+pub fn estimated_ram_mb(size_mb: u64) -> u64 {
+    size_mb + size_mb / 4 + 256
+}
+
+/// Registry ID for an imported model name.
+pub fn custom_model_id(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug: Vec<&str> = slug.split('-').filter(|part| !part.is_empty()).collect();
+    format!("custom-{}", slug.join("-"))
+}
+
+/// Imported models with their leaked registry profiles.
+static CUSTOM_MODELS: LazyLock<RwLock<Vec<(CustomModel, &'static ModelProfile)>>> =
+    LazyLock::new(Default::default);
+
+/// Register imported models (from Settings) beside the built-in registry.
+pub fn register_custom_models(models: &[CustomModel]) {
+    let Ok(mut registered) = CUSTOM_MODELS.write() else {
+        return;
+    };
+    let previous = std::mem::take(&mut *registered);
+    *registered = models
+        .iter()
+        .map(|custom| {
+            // Reuse the profile of an unchanged import instead of leaking another
+            let profile = previous
+                .iter()
+                .find(|(old, _)| old == custom)
+                .map(|(_, profile)| *profile)
+                .unwrap_or_else(|| custom.leak_profile());
+            (custom.clone(), profile)
+        })
+        .collect();
+}
+
+/// The imported model with this ID, if any.
+pub fn custom_model(id: &str) -> Option<CustomModel> {
+    CUSTOM_MODELS
+        .read()
+        .ok()?
+        .iter()
+        .find(|(custom, _)| custom.id == id)
+        .map(|(custom, _)| custom.clone())
+}
+
+/// Registry profiles of all imported models.
+fn custom_profiles() -> Vec<&'static ModelProfile> {
+    CUSTOM_MODELS
+        .read()
+        .map(|models| models.iter().map(|(_, profile)| *profile).collect())
+        .unwrap_or_default()
+}
+
+/// Check if GPU offload is available at runtime via llama.cpp.
+///
+/// This detects actual GPU availability (Vulkan, CUDA, Metal) — not just
+/// compile-time feature flags. Returns true if llama.cpp can offload to GPU.
+/// On mobile, always returns false (llama.cpp not available).
+pub fn has_gpu_runtime() -> bool {
+    #[cfg(desktop)]
+    {
+        // Check if any GPU-type device is reported by llama.cpp backends
+        let devices = llama_cpp_2::list_llama_ggml_backend_devices();
+        devices.iter().any(|d| {
+            matches!(
+                d.device_type,
+                llama_cpp_2::LlamaBackendDeviceType::Gpu
+                    | llama_cpp_2::LlamaBackendDeviceType::IntegratedGpu
+            )
+        })
+    }
+
+    #[cfg(not(desktop))]
+    {
+        false
+    }
+}
+
+/// Recommend the best model that fits the available hardware.
+///
+/// Strategy:
+/// - Prefers Qwen3 family (better tool calling, thinking mode, multilingual)
+/// - CPU-only (no GPU detected at runtime): cap at quality tier 2 for interactive speed
+/// - GPU available: pick the largest that fits in RAM
+/// - Always leave 512MB headroom for the OS and app
+pub fn recommend_model(hardware: &HardwareInfo) -> &'static ModelProfile {
+    let available = hardware.available_ram_mb;
+    let has_gpu = has_gpu_runtime();
+
+    // CPU-only: cap at tier 2 for acceptable inference speed.
+    // 3B+ on CPU takes 10+ seconds per response which feels broken.
+    let max_quality_tier: u8 = if has_gpu { 4 } else { 2 };
+
+    // Prefer Qwen3 models (first in registry), fall back to Qwen2.5
+    MODEL_REGISTRY
+        .iter()
+        .filter(|m| m.family == "qwen3") // Prefer Qwen3
+        .rev() // Start from largest
+        .find(|m| available >= m.min_ram_mb + 512 && m.quality_tier <= max_quality_tier)
+        .or_else(|| {
+            // Fall back to Qwen2.5 if no Qwen3 model fits
+            MODEL_REGISTRY
+                .iter()
+                .filter(|m| m.family == "qwen2.5")
+                .rev()
+                .find(|m| available >= m.min_ram_mb + 512 && m.quality_tier <= max_quality_tier)
+        })
+        .unwrap_or(&MODEL_REGISTRY[0]) // Absolute fallback: smallest Qwen3
+}
+
+/// Check if a model's GGUF file exists in the HuggingFace Hub cache
+/// (or, for an imported model, at its local path).
+pub fn is_model_cached(profile: &ModelProfile) -> bool {
+    if profile.family == CUSTOM_FAMILY {
+        return std::path::Path::new(profile.gguf_file).exists();
+    }
+    let cache_base = get_hf_cache_dir();
+    let repo_name = profile.repo_id.replace('/', "--");
+    let snapshots = cache_base
+        .join(format!("models--{}", repo_name))
+        .join("snapshots");
+
+    if let Ok(entries) = std::fs::read_dir(&snapshots) {
+        for entry in entries.flatten() {
+            if entry.path().join(profile.gguf_file).exists() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Assumed download throughput for first-run load estimates (MB/s).
+const ESTIMATED_DOWNLOAD_MBPS: u64 = 10;
+/// Assumed disk + mmap throughput when loading a cached GGUF (MB/s).
+const ESTIMATED_LOAD_MBPS: u64 = 500;
+/// Baseline first-token latency once a model is loaded but not yet measured (ms).
+const ESTIMATED_FIRST_TOKEN_MS: u64 = 300;
+
+/// Estimate how long until a model answers its first token, in milliseconds.
+///
+/// Rough prediction for the UI before a warm-up measurement exists:
+/// download time (if not cached) + load time + a baseline first-token latency.
+pub fn estimate_ready_ms(profile: &ModelProfile, cached: bool) -> u64 {
+    let download_ms = if cached {
+        0
+    } else {
+        profile.size_mb * 1000 / ESTIMATED_DOWNLOAD_MBPS
+    };
+    let load_ms = profile.size_mb * 1000 / ESTIMATED_LOAD_MBPS;
+    download_ms + load_ms + ESTIMATED_FIRST_TOKEN_MS
+}
+
+/// Get the HuggingFace Hub cache directory.
+pub fn get_hf_cache_dir() -> std::path::PathBuf {
+    // Respect HF environment variables
+    if let Ok(cache) = std::env::var("HF_HUB_CACHE") {
+        return std::path::PathBuf::from(cache);
+    }
+    if let Ok(home) = std::env::var("HF_HOME") {
+        return std::path::PathBuf::from(home).join("hub");
+    }
+    // Default cache location
+    dirs::home_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join(".cache")
+        .join("huggingface")
+        .join("hub")
+}
+
+/// Model info enriched with runtime status (for the frontend).
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub size_mb: u64,
+    pub min_ram_mb: u64,
+    pub parameters: String,
+    pub quality_tier: u8,
+    pub family: String,
+    pub supports_thinking: bool,
+    pub downloaded: bool,
+    pub active: bool,
+    pub recommended: bool,
+    pub fits_hardware: bool,
+}
+
+/// Build a list of all available models with runtime status.
+pub fn list_models(hardware: &HardwareInfo, active_model_id: &str) -> Vec<ModelInfo> {
+    let recommended = recommend_model(hardware);
+
+    MODEL_REGISTRY
+        .iter()
+        .chain(custom_profiles())
+        .map(|profile| ModelInfo {
+            id: profile.id.to_string(),
+            name: profile.name.to_string(),
+            description: profile.description.to_string(),
+            size_mb: profile.size_mb,
+            min_ram_mb: profile.min_ram_mb,
+            parameters: profile.parameters.to_string(),
+            quality_tier: profile.quality_tier,
+            family: profile.family.to_string(),
+            supports_thinking: profile.supports_thinking,
+            downloaded: is_model_cached(profile),
+            active: profile.id == active_model_id,
+            recommended: profile.id == recommended.id,
+            fits_hardware: hardware.available_ram_mb >= profile.min_ram_mb + 512,
+        })
+        .collect()
+}
+
+/// A chat model GGUF found in the HuggingFace cache.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadedModel {
+    pub id: String,
+    pub name: String,
+    /// Cached GGUF path (latest revision).
+    pub path: String,
+    /// Bytes on disk across all cached revisions.
+    pub size_bytes: u64,
+    /// Loaded by the chat engine or used by the agent; cannot be deleted.
+    pub in_use: bool,
+}
+
+/// GGUF files for a model in every cached snapshot of its repo.
+fn cached_gguf_paths(
+    cache_base: &std::path::Path,
+    profile: &ModelProfile,
+) -> Vec<std::path::PathBuf> {
+    let snapshots = cache_base
+        .join(format!("models--{}", profile.repo_id.replace('/', "--")))
+        .join("snapshots");
+    let Ok(entries) = std::fs::read_dir(&snapshots) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path().join(profile.gguf_file))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Registry models downloaded into the HuggingFace cache, with disk usage.
+///
+/// `in_use` lists model IDs that are loaded or configured and must be kept.
+pub fn list_downloaded_models(in_use: &[String]) -> Vec<DownloadedModel> {
+    downloaded_models_in(&get_hf_cache_dir(), in_use)
+}
+
+fn downloaded_models_in(cache_base: &std::path::Path, in_use: &[String]) -> Vec<DownloadedModel> {
+    MODEL_REGISTRY
+        .iter()
+        .filter_map(|profile| {
+            let paths = cached_gguf_paths(cache_base, profile);
+            let latest = paths
+                .iter()
+                .max_by_key(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())?;
+            Some(DownloadedModel {
+                id: profile.id.to_string(),
+                name: profile.name.to_string(),
+                path: latest.to_string_lossy().to_string(),
+                size_bytes: paths
+                    .iter()
+                    .filter_map(|p| std::fs::metadata(p).ok())
+                    .map(|m| m.len())
+                    .sum(),
+                in_use: in_use.iter().any(|id| id == profile.id),
+            })
+        })
+        .collect()
+}
+
+/// Delete a downloaded model's GGUF from the cache. Returns bytes freed.
+///
+/// Refuses models in `in_use`. Only the GGUF is removed — the tokenizer and
+/// other small repo files stay, so a re-download only fetches the weights.
+pub fn delete_downloaded_model(id: &str, in_use: &[String]) -> Result<u64> {
+    delete_downloaded_in(&get_hf_cache_dir(), id, in_use)
+}
+
+fn delete_downloaded_in(cache_base: &std::path::Path, id: &str, in_use: &[String]) -> Result<u64> {
+    let profile =
+        find_model(id).ok_or_else(|| GhostError::Chat(format!("Unknown model: {}", id)))?;
+    if in_use.iter().any(|active| active == profile.id) {
+        return Err(GhostError::Chat(format!(
+            "{} is in use; switch to another model before deleting it",
+            profile.name
+        )));
+    }
+    let paths = cached_gguf_paths(cache_base, profile);
+    if paths.is_empty() {
+        return Err(GhostError::Chat(format!(
+            "{} is not downloaded",
+            profile.name
+        )));
+    }
+
+    let mut freed = 0;
+    for path in paths {
+        freed += std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        // hf-hub snapshots are symlinks into blobs/ (possibly shared by
+        // several revisions); remove the blob too
+        if path.is_symlink() {
+            if let Ok(blob) = std::fs::canonicalize(&path) {
+                match std::fs::remove_file(&blob) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        return Err(GhostError::Chat(format!(
+                            "Failed to delete {}: {}",
+                            blob.display(),
+                            e
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        std::fs::remove_file(&path)
+            .map_err(|e| GhostError::Chat(format!("Failed to delete {}: {}", path.display(), e)))?;
+    }
+    tracing::info!("Deleted model {} ({} bytes freed)", profile.id, freed);
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_ready_ms_cached_is_faster() {
+        let profile = find_model("qwen2.5-0.5b").unwrap();
+        let cached = estimate_ready_ms(profile, true);
+        let uncached = estimate_ready_ms(profile, false);
+        assert!(cached >= ESTIMATED_FIRST_TOKEN_MS);
+        assert!(uncached > cached);
+    }
+
+    #[test]
+    fn test_estimate_ready_ms_scales_with_size() {
+        let small = find_model("qwen2.5-0.5b").unwrap();
+        let large = find_model("qwen2.5-7b").unwrap();
+        assert!(estimate_ready_ms(large, true) > estimate_ready_ms(small, true));
+    }
+
+    #[test]
+    fn test_model_registry_not_empty() {
+        assert!(!MODEL_REGISTRY.is_empty());
+    }
+
+    #[test]
+    fn test_model_registry_has_both_families() {
+        let qwen3_count = MODEL_REGISTRY
+            .iter()
+            .filter(|m| m.family == "qwen3")
+            .count();
+        let qwen25_count = MODEL_REGISTRY
+            .iter()
+            .filter(|m| m.family == "qwen2.5")
+            .count();
+        assert!(qwen3_count >= 4, "Expected at least 4 Qwen3 models");
+        assert!(qwen25_count >= 4, "Expected at least 4 Qwen2.5 models");
+    }
+
+    #[test]
+    fn test_find_model() {
+        assert!(find_model("qwen3-0.6b").is_some());
+        assert!(find_model("qwen2.5-0.5b").is_some());
+        assert!(find_model("nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_qwen3_models_have_thinking() {
+        for m in MODEL_REGISTRY.iter().filter(|m| m.family == "qwen3") {
+            assert!(m.supports_thinking, "{} should support thinking", m.id);
+        }
+        for m in MODEL_REGISTRY.iter().filter(|m| m.family == "qwen2.5") {
+            assert!(!m.supports_thinking, "{} should not support thinking", m.id);
+        }
+    }
+
+    #[test]
+    fn test_recommend_low_ram() {
+        let hw = HardwareInfo {
+            cpu_cores: 2,
+            has_avx2: false,
+            has_neon: false,
+            gpu_backend: None,
+            total_ram_mb: 2048,
+            available_ram_mb: 1500,
+        };
+        let model = recommend_model(&hw);
+        // Should recommend smallest Qwen3 (0.6B)
+        assert_eq!(model.id, "qwen3-0.6b");
+    }
+
+    #[test]
+    fn test_recommend_high_ram() {
+        let hw = HardwareInfo {
+            cpu_cores: 16,
+            has_avx2: true,
+            has_neon: false,
+            gpu_backend: None,
+            total_ram_mb: 32768,
+            available_ram_mb: 24000,
+        };
+        let model = recommend_model(&hw);
+        // Without GPU at runtime, caps at tier 2 — Qwen3-1.7B
+        // With GPU, would select Qwen3-8B
+        if has_gpu_runtime() {
+            assert_eq!(model.id, "qwen3-8b");
+        } else {
+            assert_eq!(model.id, "qwen3-1.7b");
+        }
+    }
+
+    #[test]
+    fn test_custom_model_registry() {
+        assert_eq!(
+            custom_model_id("  My Llama 3.1 (8B)! "),
+            "custom-my-llama-3.1-8b"
+        );
+
+        let info = crate::chat::gguf::GgufInfo {
+            architecture: "llama".into(),
+            n_layers: 32,
+            quantization: "Q4_K_M".into(),
+            parameter_count: 8_030_000_000,
+            ..Default::default()
+        };
+        let custom = CustomModel::from_gguf(
+            std::path::Path::new("/models/llama.gguf"),
+            "Test Import Llama",
+            8192,
+            &info,
+        );
+        assert_eq!(custom.parameters, "8.0B");
+        register_custom_models(std::slice::from_ref(&custom));
+
+        let profile = find_model("custom-test-import-llama").unwrap();
+        assert_eq!(profile.family, CUSTOM_FAMILY);
+        assert_eq!(profile.n_layers, 32);
+        assert!(!is_model_cached(profile));
+        assert_eq!(custom_model(&custom.id), Some(custom.clone()));
+
+        // Re-registering an unchanged import reuses its profile
+        register_custom_models(std::slice::from_ref(&custom));
+        assert!(std::ptr::eq(find_model(&custom.id).unwrap(), profile));
+
+        register_custom_models(&[]);
+        assert!(find_model(&custom.id).is_none());
+    }
+
+    #[test]
+    fn test_list_and_delete_downloaded_models() {
+        let cache =
+            std::env::temp_dir().join(format!("ghost_test_model_cache_{}", std::process::id()));
+        let profile = find_model("qwen3-0.6b").unwrap();
+        let snapshot = cache
+            .join("models--Qwen--Qwen3-0.6B-GGUF")
+            .join("snapshots")
+            .join("abc123");
+        std::fs::create_dir_all(&snapshot).unwrap();
+        std::fs::write(snapshot.join(profile.gguf_file), vec![0u8; 1024]).unwrap();
+
+        let downloaded = downloaded_models_in(&cache, &[]);
+        assert_eq!(downloaded.len(), 1);
+        assert_eq!(downloaded[0].id, "qwen3-0.6b");
+        assert_eq!(downloaded[0].size_bytes, 1024);
+
+        // The active model is protected
+        let active = vec!["qwen3-0.6b".to_string()];
+        assert!(downloaded_models_in(&cache, &active)[0].in_use);
+        assert!(delete_downloaded_in(&cache, "qwen3-0.6b", &active).is_err());
+
+        assert_eq!(
+            delete_downloaded_in(&cache, "qwen3-0.6b", &[]).unwrap(),
+            1024
+        );
+        assert!(downloaded_models_in(&cache, &[]).is_empty());
+        assert!(delete_downloaded_in(&cache, "qwen3-0.6b", &[]).is_err());
+
+        let _ = std::fs::remove_dir_all(&cache);
+    }
+
+    #[test]
+    fn test_models_quality_tiers_valid() {
+        for m in MODEL_REGISTRY {
+            assert!(
+                m.quality_tier >= 1 && m.quality_tier <= 4,
+                "{} has invalid quality tier {}",
+                m.id,
+                m.quality_tier
+            );
+        }
+    }
+}
@@ -3,14 +3,19 @@
 //! Supports any GGUF model from the registry (Qwen2.5-Instruct family).
 //! Runtime GPU auto-detection: Vulkan (NVIDIA/AMD/Intel), Metal (macOS), CUDA.
 //! Falls back to CPU transparently if no GPU is available.
+//! With a smaller draft model of the same family, generation can run
+//! speculatively: the draft proposes a few tokens and the main model checks
+//! them in one batch.
 
 use std::sync::{Arc, OnceLock};
 
+use llama_cpp_2::context::LlamaContext;
 use llama_cpp_2::llama_backend::LlamaBackend;
 use llama_cpp_2::llama_batch::LlamaBatch;
 use llama_cpp_2::model::params::LlamaModelParams;
 use llama_cpp_2::model::{AddBos, LlamaModel};
 use llama_cpp_2::sampling::LlamaSampler;
+use llama_cpp_2::token::LlamaToken;
 
 use super::inference::InferenceProfile;
 use super::models::ModelProfile;
@@ -42,10 +47,51 @@ const DEFAULT_TEMPERATURE: f32 = 0.7;
 const DEFAULT_TOP_P: f32 = 0.9;
 const DEFAULT_SEED: u32 = 42;
 
+/// Tokens the draft model proposes per verification step.
+const DRAFT_TOKENS: usize = 4;
+
 /// Qwen2.5 ChatML template tokens.
 const IM_START: &str = "<|im_start|>";
 const IM_END: &str = "<|im_end|>";
 
+/// Text and draft statistics from [`NativeChatEngine::generate_speculative`].
+pub struct SpeculativeOutput {
+    pub text: String,
+    /// Tokens proposed by the draft model.
+    pub drafted_tokens: u64,
+    /// Proposed tokens the main model kept.
+    pub accepted_tokens: u64,
+}
+
+/// Decode `tokens` at positions starting from `start`, `batch_size` at a time.
+///
+/// The last chunk stays in `batch` with logits for its final token, ready
+/// for sampling at `batch.n_tokens() - 1`.
+fn decode_tokens(
+    ctx: &mut LlamaContext<'_>,
+    batch: &mut LlamaBatch,
+    tokens: &[LlamaToken],
+    start: usize,
+    batch_size: u32,
+) -> Result<()> {
+    batch.clear();
+    let last = tokens.len().saturating_sub(1);
+    for (i, &token) in tokens.iter().enumerate() {
+        let is_last = i == last;
+        batch
+            .add(token, (start + i) as i32, &[0], is_last)
+            .map_err(|e| GhostError::Chat(format!("Batch add failed: {}", e)))?;
+        if batch.n_tokens() as u32 >= batch_size || is_last {
+            ctx.decode(batch)
+                .map_err(|e| GhostError::Chat(format!("Prefill decode failed: {}", e)))?;
+            if !is_last {
+                batch.clear();
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Native chat engine powered by llama.cpp with runtime GPU auto-detection.
 ///
 /// Unlike the previous Candle engine, this one:
@@ -83,9 +129,17 @@ impl NativeChatEngine {
             profile.gguf_file,
         );
 
-        // Download model files from HuggingFace Hub (cached after first download)
-        let (model_path, _tokenizer_path) =
-            Self::download_model_files(profile, progress.clone()).await?;
+        // Imported models load from their own path; registry models come from
+        // HuggingFace Hub (cached after first download)
+        let custom = super::models::custom_model(profile.id);
+        let model_path = match custom {
+            Some(ref custom) => std::path::PathBuf::from(&custom.path),
+            None => {
+                Self::download_model_files(profile, progress.clone())
+                    .await?
+                    .0
+            }
+        };
 
         // Update progress: loading model into memory
         if let Ok(mut p) = progress.lock() {
@@ -103,7 +157,11 @@ impl NativeChatEngine {
         // InferenceProfile detects GPU VRAM, CPU cores, RAM, and computes
         // optimal parameters: n_gpu_layers, threads, batch size, context
         // window, KV cache type, mlock, flash attention — all automatically.
-        let inf_profile = InferenceProfile::auto(profile.size_mb, profile.n_layers);
+        let mut inf_profile = InferenceProfile::auto(profile.size_mb, profile.n_layers);
+        // Imported models run with the context window chosen at import
+        if let Some(ref custom) = custom {
+            inf_profile.n_ctx = custom.context_window;
+        }
 
         // Determine GPU backend name for display
         let gpu_backend_name = inf_profile
@@ -191,6 +249,11 @@ impl NativeChatEngine {
         &self.gpu_backend_name
     }
 
+    /// Context window size in tokens.
+    pub fn context_size(&self) -> usize {
+        self.profile.n_ctx as usize
+    }
+
     /// Whether GPU offload is active.
     pub fn is_gpu_active(&self) -> bool {
         self.n_gpu_layers > 0
@@ -200,6 +263,17 @@ impl NativeChatEngine {
     ///
     /// Uses proper KV cache clearing between calls — no model reload needed.
     pub fn generate(&self, messages: &[ChatMessage], max_tokens: usize) -> Result<String> {
+        self.generate_constrained(messages, max_tokens, None)
+    }
+
+    /// [`Self::generate`], limited to text the GBNF `grammar` (root rule
+    /// `root`) accepts when set.
+    pub fn generate_constrained(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: usize,
+        grammar: Option<&str>,
+    ) -> Result<String> {
         let max_tokens = max_tokens.min(2048);
 
         let prompt = Self::format_chat_prompt(messages, self.supports_thinking);
@@ -234,15 +308,9 @@ impl NativeChatEngine {
             .new_context(&self.backend, ctx_params)
             .map_err(|e| GhostError::Chat(format!("Failed to create context: {}", e)))?;
 
-        // Setup sampler
-        let mut sampler = if self.temperature <= 0.01 {
-            LlamaSampler::greedy()
-        } else {
-            LlamaSampler::chain_simple([
-                LlamaSampler::temp(self.temperature),
-                LlamaSampler::top_p(self.top_p, 1),
-                LlamaSampler::dist(DEFAULT_SEED),
-            ])
+        let mut sampler = match grammar {
+            Some(grammar) => self.grammar_sampler(grammar)?,
+            None => self.sampler(),
         };
 
         // Prefill: submit prompt tokens in chunks of batch size
@@ -309,6 +377,201 @@ impl NativeChatEngine {
         Ok(cleaned.trim().to_string())
     }
 
+    /// Generate like [`Self::generate`], with `draft` proposing tokens ahead.
+    ///
+    /// Each step the draft model greedily proposes up to [`DRAFT_TOKENS`]
+    /// tokens and this model decodes them in one batch. Every emitted token
+    /// is still sampled from this model, so output quality is unchanged; a
+    /// proposal only saves work when this model would have picked it too.
+    /// `draft` must share this model's vocabulary (same family).
+    pub fn generate_speculative(
+        &self,
+        draft: &NativeChatEngine,
+        messages: &[ChatMessage],
+        max_tokens: usize,
+    ) -> Result<SpeculativeOutput> {
+        let max_tokens = max_tokens.min(2048);
+        let prompt = Self::format_chat_prompt(messages, self.supports_thinking);
+        let mut tokens = self
+            .model
+            .str_to_token(&prompt, AddBos::Never)
+            .map_err(|e| GhostError::Chat(format!("Tokenization failed: {}", e)))?;
+
+        let n_ctx = self.profile.n_ctx.min(draft.profile.n_ctx) as usize;
+        if tokens.len() + DRAFT_TOKENS >= n_ctx {
+            return Err(GhostError::Chat(format!(
+                "Prompt too long: {} tokens (max {})",
+                tokens.len(),
+                n_ctx - DRAFT_TOKENS
+            )));
+        }
+
+        let mut ctx = self
+            .model
+            .new_context(&self.backend, self.profile.context_params(None))
+            .map_err(|e| GhostError::Chat(format!("Failed to create context: {}", e)))?;
+        let mut draft_ctx = draft
+            .model
+            .new_context(&draft.backend, draft.profile.context_params(None))
+            .map_err(|e| GhostError::Chat(format!("Failed to create draft context: {}", e)))?;
+        let mut sampler = self.sampler();
+        let mut draft_sampler = LlamaSampler::greedy();
+
+        let batch_size = self.profile.n_batch.max(DRAFT_TOKENS as u32 + 1);
+        let mut batch = LlamaBatch::new(batch_size as usize, 1);
+        let mut draft_batch = LlamaBatch::new(draft.profile.n_batch as usize, 1);
+
+        // Prefill the main model; the draft catches up on its first step
+        decode_tokens(&mut ctx, &mut batch, &tokens, 0, self.profile.n_batch)?;
+        let mut next = sampler.sample(&ctx, batch.n_tokens() - 1);
+        sampler.accept(next);
+
+        let mut decoder = encoding_rs::UTF_8.new_decoder();
+        let mut output = String::new();
+        let mut emit = |token: LlamaToken, output: &mut String| match self.model.token_to_piece(
+            token,
+            &mut decoder,
+            true,
+            None,
+        ) {
+            Ok(piece) => output.push_str(&piece),
+            Err(e) => tracing::warn!("Token decode error: {}", e),
+        };
+        let mut generated = 0;
+        let mut drafted = 0u64;
+        let mut accepted = 0u64;
+        // Tokens in the draft's KV cache (a prefix of `tokens`)
+        let mut draft_len = 0;
+
+        while !self.model.is_eog_token(next) {
+            // `next` is sampled but not yet decoded by the main model
+            emit(next, &mut output);
+            tokens.push(next);
+            generated += 1;
+            if generated >= max_tokens || tokens.len() + DRAFT_TOKENS >= n_ctx {
+                break;
+            }
+
+            // Draft: catch up on accepted tokens, then propose greedily
+            decode_tokens(
+                &mut draft_ctx,
+                &mut draft_batch,
+                &tokens[draft_len..],
+                draft_len,
+                draft.profile.n_batch,
+            )?;
+            draft_len = tokens.len();
+            let mut proposal = Vec::with_capacity(DRAFT_TOKENS);
+            let mut draft_cached = 0;
+            while proposal.len() < DRAFT_TOKENS {
+                let token = draft_sampler.sample(&draft_ctx, draft_batch.n_tokens() - 1);
+                if draft.model.is_eog_token(token) {
+                    break;
+                }
+                proposal.push(token);
+                if proposal.len() < DRAFT_TOKENS {
+                    draft_batch.clear();
+                    draft_batch
+                        .add(token, (draft_len + draft_cached) as i32, &[0], true)
+                        .map_err(|e| GhostError::Chat(format!("Batch add failed: {}", e)))?;
+                    draft_ctx
+                        .decode(&mut draft_batch)
+                        .map_err(|e| GhostError::Chat(format!("Draft decode failed: {}", e)))?;
+                    draft_cached += 1;
+                }
+            }
+
+            // Main model: decode `next` plus the proposal in one batch
+            let base = tokens.len() - 1;
+            batch.clear();
+            for (i, &token) in std::iter::once(&next).chain(&proposal).enumerate() {
+                batch
+                    .add(token, (base + i) as i32, &[0], true)
+                    .map_err(|e| GhostError::Chat(format!("Batch add failed: {}", e)))?;
+            }
+            ctx.decode(&mut batch)
+                .map_err(|e| GhostError::Chat(format!("Verify decode failed: {}", e)))?;
+            drafted += proposal.len() as u64;
+
+            // Keep proposed tokens while they match what the main model samples
+            let mut n_accepted = 0;
+            loop {
+                let token = sampler.sample(&ctx, n_accepted as i32);
+                sampler.accept(token);
+                if proposal.get(n_accepted) != Some(&token) || generated >= max_tokens {
+                    next = token;
+                    break;
+                }
+                emit(token, &mut output);
+                tokens.push(token);
+                generated += 1;
+                n_accepted += 1;
+            }
+            accepted += n_accepted as u64;
+            if generated >= max_tokens {
+                break;
+            }
+
+            // Drop rejected proposals from both KV caches
+            ctx.clear_kv_cache_seq(Some(0), Some((base + 1 + n_accepted) as u32), None)
+                .map_err(|e| GhostError::Chat(format!("KV cache trim failed: {}", e)))?;
+            let draft_keep = draft_len + n_accepted.min(draft_cached);
+            draft_ctx
+                .clear_kv_cache_seq(Some(0), Some(draft_keep as u32), None)
+                .map_err(|e| GhostError::Chat(format!("KV cache trim failed: {}", e)))?;
+            draft_len = draft_keep;
+        }
+
+        tracing::debug!(
+            "Speculative generation: {} tokens, {}/{} draft tokens accepted ({})",
+            generated,
+            accepted,
+            drafted,
+            draft.model_name
+        );
+
+        Ok(SpeculativeOutput {
+            text: strip_think_blocks(&output).trim().to_string(),
+            drafted_tokens: drafted,
+            accepted_tokens: accepted,
+        })
+    }
+
+    /// Sampler for this engine's temperature and top-p.
+    fn sampler(&self) -> LlamaSampler {
+        if self.temperature <= 0.01 {
+            LlamaSampler::greedy()
+        } else {
+            LlamaSampler::chain_simple([
+                LlamaSampler::temp(self.temperature),
+                LlamaSampler::top_p(self.top_p, 1),
+                LlamaSampler::dist(DEFAULT_SEED),
+            ])
+        }
+    }
+
+    /// [`Self::sampler`] behind a grammar constraint, so only tokens the
+    /// grammar allows can be picked.
+    fn grammar_sampler(&self, grammar: &str) -> Result<LlamaSampler> {
+        let constraint = LlamaSampler::grammar(&self.model, grammar, "root")
+            .map_err(|e| GhostError::Chat(format!("Invalid grammar: {}", e)))?;
+        Ok(LlamaSampler::chain_simple([constraint, self.sampler()]))
+    }
+
+    /// Run a tiny one-token generation to prime the context, KV cache and GPU kernels.
+    ///
+    /// Returns the measured first-token latency in milliseconds, which is what a
+    /// short chat request will roughly cost from now on.
+    pub fn warm_up(&self) -> Result<u64> {
+        let start = std::time::Instant::now();
+        let messages = [ChatMessage {
+            role: "user".into(),
+            content: "Hi".into(),
+        }];
+        self.generate(&messages, 1)?;
+        Ok(start.elapsed().as_millis() as u64)
+    }
+
     /// Format messages into ChatML prompt format.
     ///
     /// For Qwen3 models: adds `/no_think` to system prompt for fast chat responses.
@@ -387,6 +650,20 @@ impl NativeChatEngine {
             None
         };
 
+        // Fetch the GGUF through the resumable download manager; hf-hub then finds it cached.
+        if !already_cached {
+            if let Err(e) = crate::downloads::manager().ensure_allowed() {
+                // Clearing progress also stops the monitor thread.
+                if let Ok(mut p) = progress.lock() {
+                    *p = None;
+                }
+                return Err(GhostError::Chat(e));
+            }
+            if let Err(e) = crate::downloads::hf_download(&repo_id, &gguf_file).await {
+                tracing::warn!("Resumable download failed ({}), falling back to hf-hub", e);
+            }
+        }
+
         // Run sync HF Hub downloads in a blocking task
         let result = tokio::task::spawn_blocking(move || {
             let api = hf_hub::api::sync::Api::new().map_err(|e| {
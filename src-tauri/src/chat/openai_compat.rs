@@ -0,0 +1,256 @@
+//! Fallback chat backend for local OpenAI-compatible servers.
+//!
+//! Ollama, LM Studio, llama-server and vLLM all serve
+//! `/v1/chat/completions` and `/v1/models`, so one client covers them; only
+//! the base URL and model name differ. Defaults to Ollama on localhost:11434.
+
+use std::net::ToSocketAddrs;
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::ChatMessage;
+use crate::error::{GhostError, Result};
+
+/// Ollama's default port, used to label the backend in the status.
+const OLLAMA_PORT: u16 = 11434;
+/// How long the reachability probe waits for a TCP connection.
+const PROBE_TIMEOUT_MS: u64 = 100;
+/// Request timeout for chat completions.
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// Where the fallback chat server lives and which model to ask for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalServerSettings {
+    /// API base including `/v1` (e.g. "http://localhost:1234/v1" for LM Studio).
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    /// Model name as the server knows it (see `/v1/models`).
+    #[serde(default = "default_model")]
+    pub model: String,
+    /// Sent as a bearer token when set (vLLM `--api-key`, llama-server `--api-key`).
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+fn default_base_url() -> String {
+    "http://localhost:11434/v1".into()
+}
+fn default_model() -> String {
+    "qwen2.5:0.5b".into()
+}
+
+impl Default for LocalServerSettings {
+    fn default() -> Self {
+        Self {
+            base_url: default_base_url(),
+            model: default_model(),
+            api_key: None,
+        }
+    }
+}
+
+impl LocalServerSettings {
+    fn base(&self) -> &str {
+        self.base_url.trim().trim_end_matches('/')
+    }
+
+    /// Backend label for `ChatStatus`: "ollama" on Ollama's port, else "openai_compat".
+    pub fn backend_name(&self) -> &'static str {
+        match reqwest::Url::parse(self.base()).ok().and_then(|u| u.port()) {
+            Some(OLLAMA_PORT) => "ollama",
+            _ => "openai_compat",
+        }
+    }
+
+    /// Quick synchronous check that something is listening at the base URL.
+    pub fn is_reachable(&self) -> bool {
+        let Ok(url) = reqwest::Url::parse(self.base()) else {
+            return false;
+        };
+        let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+            return false;
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let Ok(mut addrs) = (host, port).to_socket_addrs() else {
+            return false;
+        };
+        addrs.any(|addr| {
+            std::net::TcpStream::connect_timeout(
+                &addr,
+                std::time::Duration::from_millis(PROBE_TIMEOUT_MS),
+            )
+            .is_ok()
+        })
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.api_key.as_deref().filter(|k| !k.is_empty()) {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+}
+
+static SETTINGS: LazyLock<RwLock<LocalServerSettings>> = LazyLock::new(Default::default);
+
+/// Set the fallback server settings (from Settings).
+pub fn configure(settings: &LocalServerSettings) {
+    if let Ok(mut current) = SETTINGS.write() {
+        *current = settings.clone();
+    }
+}
+
+/// The configured fallback server.
+pub fn current() -> LocalServerSettings {
+    SETTINGS.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Chat with the server via `/v1/chat/completions`.
+pub async fn chat(
+    settings: &LocalServerSettings,
+    messages: &[ChatMessage],
+    max_tokens: usize,
+) -> Result<String> {
+    complete(
+        settings,
+        serde_json::json!({
+            "model": settings.model,
+            "messages": messages,
+            "stream": false,
+            "max_tokens": max_tokens,
+        }),
+    )
+    .await
+}
+
+/// [`chat`], asking the server for JSON matching `schema`. Servers that
+/// ignore `response_format` may still answer with free text.
+pub async fn chat_structured(
+    settings: &LocalServerSettings,
+    messages: &[ChatMessage],
+    max_tokens: usize,
+    schema: &serde_json::Value,
+) -> Result<String> {
+    complete(
+        settings,
+        serde_json::json!({
+            "model": settings.model,
+            "messages": messages,
+            "stream": false,
+            "max_tokens": max_tokens,
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": { "name": "response", "schema": schema, "strict": true },
+            },
+        }),
+    )
+    .await
+}
+
+/// POST a chat completion request and return the reply text.
+async fn complete(settings: &LocalServerSettings, body: serde_json::Value) -> Result<String> {
+    let client = reqwest::Client::new();
+    let response = settings
+        .request(client.post(format!("{}/chat/completions", settings.base())))
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| GhostError::Chat(format!("{} unavailable: {}", settings.backend_name(), e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(GhostError::Chat(format!(
+            "{} returned {}: {}",
+            settings.backend_name(),
+            status,
+            body
+        )));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| GhostError::Chat(format!("Failed to parse chat response: {}", e)))?;
+    body["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| GhostError::Chat("Chat response had no message content".into()))
+}
+
+/// Model names the server offers, from `/v1/models`.
+pub async fn list_models(settings: &LocalServerSettings) -> Result<Vec<String>> {
+    let client = reqwest::Client::new();
+    let response = settings
+        .request(client.get(format!("{}/models", settings.base())))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| GhostError::Chat(format!("{} unavailable: {}", settings.backend_name(), e)))?;
+    if !response.status().is_success() {
+        return Err(GhostError::Chat(format!(
+            "{} returned {} for /models",
+            settings.backend_name(),
+            response.status()
+        )));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| GhostError::Chat(format!("Failed to parse model list: {}", e)))?;
+    Ok(parse_model_ids(&body))
+}
+
+fn parse_model_ids(body: &serde_json::Value) -> Vec<String> {
+    body["data"]
+        .as_array()
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|m| m["id"].as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(base_url: &str) -> LocalServerSettings {
+        LocalServerSettings {
+            base_url: base_url.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(LocalServerSettings::default().backend_name(), "ollama");
+        assert_eq!(
+            server("http://localhost:1234/v1/").backend_name(),
+            "openai_compat"
+        );
+        assert_eq!(server("not a url").backend_name(), "openai_compat");
+        assert_eq!(
+            server("http://localhost:1234/v1/").base(),
+            "http://localhost:1234/v1"
+        );
+    }
+
+    #[test]
+    fn test_parse_model_ids() {
+        let body = serde_json::json!({
+            "object": "list",
+            "data": [{ "id": "qwen2.5-7b-instruct", "object": "model" }, { "id": "llama-3.2-3b" }]
+        });
+        assert_eq!(
+            parse_model_ids(&body),
+            ["qwen2.5-7b-instruct", "llama-3.2-3b"]
+        );
+        assert!(parse_model_ids(&serde_json::json!({})).is_empty());
+    }
+}
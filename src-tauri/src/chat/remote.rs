@@ -0,0 +1,277 @@
+//! Opt-in remote LLM providers (OpenAI, Anthropic, OpenRouter).
+//!
+//! Off by default. Even when configured, a request only leaves the machine
+//! if the caller allows cloud use for it (per conversation), so local-only
+//! remains the default for every chat.
+
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::ChatMessage;
+use crate::error::{GhostError, Result};
+
+/// Anthropic Messages API version header.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Request timeout for remote providers.
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// A hosted chat provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteProvider {
+    #[default]
+    OpenAi,
+    Anthropic,
+    OpenRouter,
+}
+
+impl RemoteProvider {
+    fn default_base_url(self) -> &'static str {
+        match self {
+            Self::OpenAi => "https://api.openai.com/v1",
+            Self::Anthropic => "https://api.anthropic.com/v1",
+            Self::OpenRouter => "https://openrouter.ai/api/v1",
+        }
+    }
+
+    fn default_model(self) -> &'static str {
+        match self {
+            Self::OpenAi => "gpt-4o-mini",
+            Self::Anthropic => "claude-3-5-haiku-latest",
+            Self::OpenRouter => "openrouter/auto",
+        }
+    }
+}
+
+/// Remote provider settings. Disabled until the user enables it and sets a key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteLlmSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub provider: RemoteProvider,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Model name at the provider; empty uses the provider's default.
+    #[serde(default)]
+    pub model: String,
+    /// Override the provider's API base URL (e.g. a proxy).
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl RemoteLlmSettings {
+    /// Enabled and has an API key.
+    pub fn is_configured(&self) -> bool {
+        self.enabled
+            && self
+                .api_key
+                .as_deref()
+                .is_some_and(|k| !k.trim().is_empty())
+    }
+
+    /// The model requests are sent to.
+    pub fn model_name(&self) -> &str {
+        if self.model.trim().is_empty() {
+            self.provider.default_model()
+        } else {
+            self.model.trim()
+        }
+    }
+
+    fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .filter(|u| !u.trim().is_empty())
+            .unwrap_or(self.provider.default_base_url())
+            .trim_end_matches('/')
+    }
+}
+
+static SETTINGS: LazyLock<RwLock<RemoteLlmSettings>> = LazyLock::new(Default::default);
+
+/// Set the remote provider settings (from Settings).
+pub fn configure(settings: &RemoteLlmSettings) {
+    if let Ok(mut current) = SETTINGS.write() {
+        *current = settings.clone();
+    }
+}
+
+/// The configured provider, if it is enabled and has a key.
+pub fn active() -> Option<RemoteLlmSettings> {
+    SETTINGS
+        .read()
+        .ok()
+        .map(|s| s.clone())
+        .filter(RemoteLlmSettings::is_configured)
+}
+
+/// A reply from a remote provider.
+pub struct RemoteReply {
+    pub content: String,
+    /// Completion tokens reported by the provider.
+    pub tokens_generated: Option<usize>,
+}
+
+/// Send a chat request to the configured provider.
+pub async fn chat(
+    settings: &RemoteLlmSettings,
+    messages: &[ChatMessage],
+    max_tokens: usize,
+) -> Result<RemoteReply> {
+    let api_key = settings
+        .api_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| GhostError::Chat("Remote provider has no API key".into()))?;
+    let client = reqwest::Client::new();
+    let request = match settings.provider {
+        RemoteProvider::Anthropic => client
+            .post(format!("{}/messages", settings.base_url()))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&anthropic_body(settings.model_name(), messages, max_tokens)),
+        RemoteProvider::OpenAi | RemoteProvider::OpenRouter => client
+            .post(format!("{}/chat/completions", settings.base_url()))
+            .bearer_auth(api_key)
+            .json(&serde_json::json!({
+                "model": settings.model_name(),
+                "messages": messages,
+                "max_tokens": max_tokens,
+            })),
+    };
+
+    let response = request
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| GhostError::Chat(format!("Remote provider unavailable: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(GhostError::Chat(format!(
+            "Remote provider returned {}: {}",
+            status, body
+        )));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| GhostError::Chat(format!("Failed to parse remote response: {}", e)))?;
+    parse_reply(settings.provider, &body)
+}
+
+/// Anthropic takes the system prompt separately and only user/assistant turns.
+fn anthropic_body(model: &str, messages: &[ChatMessage], max_tokens: usize) -> serde_json::Value {
+    let system: Vec<&str> = messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let turns: Vec<serde_json::Value> = messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| {
+            let role = if m.role == "assistant" {
+                "assistant"
+            } else {
+                "user"
+            };
+            serde_json::json!({ "role": role, "content": m.content })
+        })
+        .collect();
+    let mut body = serde_json::json!({
+        "model": model,
+        "max_tokens": max_tokens,
+        "messages": turns,
+    });
+    if !system.is_empty() {
+        body["system"] = system.join("\n\n").into();
+    }
+    body
+}
+
+fn parse_reply(provider: RemoteProvider, body: &serde_json::Value) -> Result<RemoteReply> {
+    let (content, tokens) = match provider {
+        RemoteProvider::Anthropic => (
+            body["content"].as_array().map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|b| b["type"] == "text")
+                    .filter_map(|b| b["text"].as_str())
+                    .collect::<String>()
+            }),
+            body["usage"]["output_tokens"].as_u64(),
+        ),
+        RemoteProvider::OpenAi | RemoteProvider::OpenRouter => (
+            body["choices"][0]["message"]["content"]
+                .as_str()
+                .map(str::to_string),
+            body["usage"]["completion_tokens"].as_u64(),
+        ),
+    };
+    let content =
+        content.ok_or_else(|| GhostError::Chat("Remote response had no message content".into()))?;
+    Ok(RemoteReply {
+        content,
+        tokens_generated: tokens.map(|t| t as usize),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            role: role.into(),
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn test_is_configured() {
+        let mut settings = RemoteLlmSettings::default();
+        assert!(!settings.is_configured());
+        settings.api_key = Some("sk-test".into());
+        assert!(!settings.is_configured());
+        settings.enabled = true;
+        assert!(settings.is_configured());
+        assert_eq!(settings.model_name(), "gpt-4o-mini");
+        assert_eq!(settings.base_url(), "https://api.openai.com/v1");
+    }
+
+    #[test]
+    fn test_anthropic_body_splits_system() {
+        let body = anthropic_body(
+            "claude",
+            &[message("system", "Be brief."), message("user", "Hi")],
+            64,
+        );
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["messages"][0]["role"], "user");
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let openai = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "Hello" } }],
+            "usage": { "completion_tokens": 2 }
+        });
+        let reply = parse_reply(RemoteProvider::OpenAi, &openai).unwrap();
+        assert_eq!(reply.content, "Hello");
+        assert_eq!(reply.tokens_generated, Some(2));
+
+        let anthropic = serde_json::json!({
+            "content": [{ "type": "text", "text": "Hi " }, { "type": "text", "text": "there" }],
+            "usage": { "output_tokens": 3 }
+        });
+        let reply = parse_reply(RemoteProvider::Anthropic, &anthropic).unwrap();
+        assert_eq!(reply.content, "Hi there");
+
+        assert!(parse_reply(RemoteProvider::OpenAi, &serde_json::json!({})).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Fast/smart model routing for the chat engine.
+//!
+//! When RAM allows, a small "fast" model is loaded beside the main ("smart")
+//! model. Short, simple requests (greetings, one-line facts) go to the fast
+//! model; anything that looks like reasoning, code, or a long conversation
+//! stays on the main model.
+
+use serde::Serialize;
+
+use super::models::{ModelProfile, MODEL_REGISTRY};
+use super::ChatMessage;
+use crate::embeddings::hardware::HardwareInfo;
+
+/// RAM kept free for the OS and the app when loading both models (MB).
+const HEADROOM_MB: u64 = 512;
+/// Longest user message (in words) still considered trivial.
+const MAX_TRIVIAL_WORDS: usize = 24;
+/// Conversations longer than this many messages stay on the main model.
+const MAX_TRIVIAL_TURNS: usize = 6;
+
+/// Words that signal a request needs the larger model.
+const COMPLEX_MARKERS: &[&str] = &[
+    "analyze",
+    "analyse",
+    "compare",
+    "debug",
+    "explain",
+    "implement",
+    "plan",
+    "prove",
+    "reason",
+    "refactor",
+    "step by step",
+    "summarize",
+    "summarise",
+    "translate",
+    "why",
+    "write",
+];
+
+/// Which loaded model answers a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelRoute {
+    /// The small model loaded for trivial requests.
+    Fast,
+    /// The main chat model.
+    Smart,
+    /// The opt-in remote provider (see [`super::remote`]).
+    Cloud,
+}
+
+/// The routing decision for one request, reported in `ChatResponse`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteDecision {
+    pub route: ModelRoute,
+    /// Short explanation of why this route was picked.
+    pub reason: String,
+}
+
+impl RouteDecision {
+    /// Route to the main model.
+    pub fn smart(reason: impl Into<String>) -> Self {
+        Self {
+            route: ModelRoute::Smart,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Pick the fast model to load beside `main`, if any.
+///
+/// `setting` is "auto" (smallest model of the main model's family), "none"
+/// (routing off), or a registry model ID. Returns `None` when the fast model
+/// would not be smaller than the main one or both do not fit in RAM.
+pub fn select_fast_model(
+    hardware: &HardwareInfo,
+    main: &ModelProfile,
+    setting: &str,
+) -> Option<&'static ModelProfile> {
+    let candidate = match setting {
+        "none" | "" => return None,
+        "auto" => MODEL_REGISTRY.iter().find(|m| m.family == main.family)?,
+        id => super::models::find_model(id)?,
+    };
+    if candidate.id == main.id || candidate.size_mb >= main.size_mb {
+        return None;
+    }
+    let needed = main.min_ram_mb + candidate.min_ram_mb + HEADROOM_MB;
+    if hardware.available_ram_mb < needed {
+        tracing::info!(
+            "Not loading fast model {}: needs {}MB beside {}, {}MB available",
+            candidate.name,
+            needed,
+            main.name,
+            hardware.available_ram_mb
+        );
+        return None;
+    }
+    Some(candidate)
+}
+
+/// Decide whether a request is simple enough for the fast model.
+pub fn route(messages: &[ChatMessage]) -> RouteDecision {
+    let Some(last) = messages.iter().rev().find(|m| m.role == "user") else {
+        return RouteDecision::smart("no user message");
+    };
+    let turns = messages.iter().filter(|m| m.role != "system").count();
+    if turns > MAX_TRIVIAL_TURNS {
+        return RouteDecision::smart("long conversation");
+    }
+    let text = last.content.to_lowercase();
+    let words = text.split_whitespace().count();
+    if words > MAX_TRIVIAL_WORDS {
+        return RouteDecision::smart(format!("{} words", words));
+    }
+    if text.contains("```") || text.lines().count() > 3 {
+        return RouteDecision::smart("code or multi-line input");
+    }
+    if let Some(marker) = COMPLEX_MARKERS.iter().find(|m| contains_word(&text, m)) {
+        return RouteDecision::smart(format!("asks to {}", marker));
+    }
+    RouteDecision {
+        route: ModelRoute::Fast,
+        reason: format!("short request ({} words)", words),
+    }
+}
+
+/// Whether `phrase` occurs in `text` on word boundaries.
+fn contains_word(text: &str, phrase: &str) -> bool {
+    text.match_indices(phrase).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat::models::find_model;
+
+    fn user(content: &str) -> ChatMessage {
+        ChatMessage {
+            role: "user".into(),
+            content: content.into(),
+        }
+    }
+
+    fn hardware(available_ram_mb: u64) -> HardwareInfo {
+        HardwareInfo {
+            cpu_cores: 8,
+            has_avx2: true,
+            has_neon: false,
+            gpu_backend: None,
+            total_ram_mb: available_ram_mb * 2,
+            available_ram_mb,
+        }
+    }
+
+    #[test]
+    fn test_route_trivial_to_fast() {
+        let decision = route(&[user("hi, what's the capital of France?")]);
+        assert_eq!(decision.route, ModelRoute::Fast);
+    }
+
+    #[test]
+    fn test_route_complex_to_smart() {
+        assert_eq!(
+            route(&[user("Why does my build fail?")]).route,
+            ModelRoute::Smart
+        );
+        assert_eq!(
+            route(&[user("fix this\n```rust\nfn main() {}\n```")]).route,
+            ModelRoute::Smart
+        );
+        assert_eq!(route(&[user(&"word ".repeat(40))]).route, ModelRoute::Smart);
+        let long: Vec<ChatMessage> = (0..8).map(|_| user("ok")).collect();
+        assert_eq!(route(&long).route, ModelRoute::Smart);
+        assert_eq!(route(&[]).route, ModelRoute::Smart);
+        // Markers only count as whole words
+        assert_eq!(route(&[user("who wrote this")]).route, ModelRoute::Fast);
+    }
+
+    #[test]
+    fn test_select_fast_model() {
+        let main = find_model("qwen3-4b").unwrap();
+        let fast = select_fast_model(&hardware(16384), main, "auto").unwrap();
+        assert_eq!(fast.id, "qwen3-0.6b");
+        assert!(select_fast_model(&hardware(4096), main, "auto").is_none());
+        assert!(select_fast_model(&hardware(16384), main, "none").is_none());
+        // The smallest model has nothing smaller to route to
+        let smallest = find_model("qwen3-0.6b").unwrap();
+        assert!(select_fast_model(&hardware(16384), smallest, "auto").is_none());
+    }
+}
@@ -0,0 +1,200 @@
+//! Structured output — chat replies constrained to a JSON schema.
+//!
+//! On the native engine the schema is compiled to a GBNF grammar (llama.cpp's
+//! own converter) and sampling can only produce text the grammar accepts.
+//! The fallback server gets the schema as an OpenAI `response_format`, which
+//! not every server enforces, so replies are parsed and checked against the
+//! schema's basic keywords (`type`, `required`, `properties`, `items`, `enum`)
+//! either way. Used by the `chat_send_structured` command and by extraction
+//! jobs such as the knowledge graph.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ChatMessage;
+use crate::error::{GhostError, Result};
+
+/// A reply parsed as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredResponse {
+    pub value: Value,
+    pub tokens_generated: usize,
+    pub duration_ms: u64,
+    pub model_id: String,
+}
+
+/// Reject schemas that aren't JSON objects.
+pub fn check_schema(schema: &Value) -> Result<()> {
+    if schema.is_object() {
+        Ok(())
+    } else {
+        Err(GhostError::InvalidQuery(
+            "The JSON schema must be an object".into(),
+        ))
+    }
+}
+
+/// Compile `schema` to a GBNF grammar whose root rule is `root`.
+#[cfg(desktop)]
+pub fn schema_to_grammar(schema: &Value) -> Result<String> {
+    llama_cpp_2::json_schema_to_grammar(&schema.to_string())
+        .map_err(|e| GhostError::InvalidQuery(format!("Unsupported JSON schema: {}", e)))
+}
+
+/// `messages` with the schema added to the system prompt, so the model knows
+/// what the fields mean and not only their shape.
+pub fn with_schema_instruction(messages: &[ChatMessage], schema: &Value) -> Vec<ChatMessage> {
+    let instruction = format!(
+        "Reply with a single JSON value that matches this JSON schema, and nothing else:\n{}",
+        schema
+    );
+    let mut messages = messages.to_vec();
+    match messages.first_mut().filter(|m| m.role == "system") {
+        Some(system) => {
+            system.content.push_str("\n\n");
+            system.content.push_str(&instruction);
+        }
+        None => messages.insert(
+            0,
+            ChatMessage {
+                role: "system".into(),
+                content: instruction,
+            },
+        ),
+    }
+    messages
+}
+
+/// Parse a reply and check it against `schema`. Code fences are tolerated.
+pub fn parse_reply(text: &str, schema: &Value) -> Result<Value> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+        .unwrap_or(text)
+        .trim();
+    let value: Value = serde_json::from_str(text).map_err(|e| {
+        GhostError::Chat(format!(
+            "The reply is not valid JSON ({}); it may have been cut off by max_tokens",
+            e
+        ))
+    })?;
+    conforms(&value, schema, "$")
+        .map_err(|e| GhostError::Chat(format!("The reply doesn't match the schema: {}", e)))?;
+    Ok(value)
+}
+
+/// Check `value` against the basic keywords of `schema`. `path` names the
+/// value in errors.
+fn conforms(value: &Value, schema: &Value, path: &str) -> std::result::Result<(), String> {
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return Err(format!("{} is not one of the allowed values", path));
+        }
+    }
+    let type_ok = |name: &str| match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    };
+    let type_matches = match schema.get("type") {
+        Some(Value::String(name)) => type_ok(name),
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).any(type_ok),
+        _ => true,
+    };
+    if !type_matches {
+        return Err(format!("{} should be of type {}", path, schema["type"]));
+    }
+
+    if let Some(object) = value.as_object() {
+        for key in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(key) {
+                return Err(format!("{} is missing '{}'", path, key));
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (key, property) in properties {
+                if let Some(field) = object.get(key) {
+                    conforms(field, property, &format!("{}.{}", path, key))?;
+                }
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            conforms(item, items, &format!("{}[{}]", path, i))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string" },
+                "priority": { "enum": ["low", "high"] },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["title"]
+        })
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let value = parse_reply(
+            "```json\n{\"title\": \"Ship it\", \"tags\": [\"work\"]}\n```",
+            &schema(),
+        )
+        .unwrap();
+        assert_eq!(value["title"], "Ship it");
+
+        assert!(parse_reply("{\"title\": \"cut of", &schema()).is_err());
+        assert!(parse_reply("{\"tags\": []}", &schema()).is_err());
+        assert!(parse_reply("{\"title\": \"a\", \"tags\": [1]}", &schema()).is_err());
+        assert!(parse_reply("{\"title\": \"a\", \"priority\": \"urgent\"}", &schema()).is_err());
+    }
+
+    #[test]
+    fn test_with_schema_instruction() {
+        let user = ChatMessage {
+            role: "user".into(),
+            content: "Make a task".into(),
+        };
+        let messages = with_schema_instruction(std::slice::from_ref(&user), &schema());
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert!(messages[0].content.contains("\"required\""));
+
+        let system = ChatMessage {
+            role: "system".into(),
+            content: "Be brief.".into(),
+        };
+        let messages = with_schema_instruction(&[system, user], &schema());
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].content.starts_with("Be brief."));
+    }
+
+    #[test]
+    fn test_check_schema() {
+        assert!(check_schema(&schema()).is_ok());
+        assert!(check_schema(&json!("object")).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Token counting and context-window budgeting.
+//!
+//! Counts use the model's own `tokenizer.json`, which is downloaded into the
+//! HuggingFace cache beside the GGUF, so history is trimmed by what the model
+//! actually sees rather than by message count. Until that file is cached,
+//! counts fall back to a conservative byte-based estimate.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use serde::Serialize;
+use tokenizers::Tokenizer;
+
+use super::models::{self, ModelProfile};
+use super::ChatMessage;
+
+/// Event emitted when older messages are dropped to fit the context window.
+pub const CONTEXT_TRUNCATED_EVENT: &str = "chat://context-truncated";
+
+/// Chat-template tokens around each message (`<|im_start|>role\n` … `<|im_end|>\n`).
+const MESSAGE_OVERHEAD_TOKENS: usize = 5;
+/// Bytes per token assumed without a tokenizer (BPE averages closer to 4).
+const ESTIMATED_BYTES_PER_TOKEN: usize = 3;
+
+/// Loaded tokenizers by model ID. Only successful loads are cached, so a
+/// tokenizer downloaded later is picked up on the next count.
+static TOKENIZERS: LazyLock<Mutex<HashMap<&'static str, Arc<Tokenizer>>>> =
+    LazyLock::new(Default::default);
+
+/// How a conversation was fitted into the context window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextBudget {
+    /// Tokens in the messages that were kept.
+    pub prompt_tokens: usize,
+    /// Tokens the messages may use (context window minus reserved tokens).
+    pub budget: usize,
+    /// Oldest messages dropped to fit.
+    pub dropped_messages: usize,
+}
+
+impl ContextBudget {
+    /// Whether any history was dropped.
+    pub fn truncated(&self) -> bool {
+        self.dropped_messages > 0
+    }
+}
+
+/// Count the tokens `model_id`'s tokenizer produces for `text`.
+///
+/// Falls back to an estimate for unknown models or when the tokenizer has
+/// not been downloaded yet.
+pub fn count_tokens(text: &str, model_id: &str) -> usize {
+    match tokenizer(model_id).and_then(|t| t.encode(text, false).ok()) {
+        Some(encoding) => encoding.len(),
+        None => estimate_tokens(text),
+    }
+}
+
+/// Tokens a message takes in the prompt, including chat-template markers.
+pub fn message_tokens(message: &ChatMessage, model_id: &str) -> usize {
+    count_tokens(&message.content, model_id) + MESSAGE_OVERHEAD_TOKENS
+}
+
+/// Tokens to set aside for the reply: `max_tokens`, capped at a quarter of
+/// the window so a large reply limit never crowds out the whole history.
+pub fn reply_reserve(max_tokens: usize, context_window: usize) -> usize {
+    max_tokens.min(context_window / 4)
+}
+
+/// Drop the oldest messages until the rest fit in `budget` tokens.
+///
+/// System messages and the latest message are always kept, even when they
+/// alone exceed the budget — the model then reports the overflow itself.
+pub fn fit_history(
+    messages: &[ChatMessage],
+    model_id: &str,
+    budget: usize,
+) -> (Vec<ChatMessage>, ContextBudget) {
+    fit_history_with(messages, budget, |m| message_tokens(m, model_id))
+}
+
+fn fit_history_with(
+    messages: &[ChatMessage],
+    budget: usize,
+    count: impl Fn(&ChatMessage) -> usize,
+) -> (Vec<ChatMessage>, ContextBudget) {
+    let counts: Vec<usize> = messages.iter().map(count).collect();
+    let mut total: usize = counts.iter().sum();
+    let mut keep = vec![true; messages.len()];
+    let last = messages.len().saturating_sub(1);
+    for (i, message) in messages.iter().enumerate().take(last) {
+        if total <= budget {
+            break;
+        }
+        if message.role != "system" {
+            keep[i] = false;
+            total -= counts[i];
+        }
+    }
+
+    let kept: Vec<ChatMessage> = messages
+        .iter()
+        .zip(&keep)
+        .filter(|(_, &k)| k)
+        .map(|(m, _)| m.clone())
+        .collect();
+    let dropped_messages = messages.len() - kept.len();
+    if dropped_messages > 0 {
+        tracing::warn!(
+            "Context truncated: dropped {} oldest messages to fit {} tokens ({} kept)",
+            dropped_messages,
+            budget,
+            total
+        );
+    }
+    (
+        kept,
+        ContextBudget {
+            prompt_tokens: total,
+            budget,
+            dropped_messages,
+        },
+    )
+}
+
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(ESTIMATED_BYTES_PER_TOKEN)
+}
+
+fn tokenizer(model_id: &str) -> Option<Arc<Tokenizer>> {
+    let profile = models::find_model(model_id)?;
+    let mut cache = TOKENIZERS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(tokenizer) = cache.get(profile.id) {
+        return Some(tokenizer.clone());
+    }
+    let path = cached_tokenizer_path(profile)?;
+    match Tokenizer::from_file(&path) {
+        Ok(tokenizer) => {
+            let tokenizer = Arc::new(tokenizer);
+            cache.insert(profile.id, tokenizer.clone());
+            Some(tokenizer)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to load tokenizer {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// `tokenizer.json` for a model in the HuggingFace cache, if downloaded.
+fn cached_tokenizer_path(profile: &ModelProfile) -> Option<PathBuf> {
+    let snapshots = models::get_hf_cache_dir()
+        .join(format!(
+            "models--{}",
+            profile.tokenizer_repo.replace('/', "--")
+        ))
+        .join("snapshots");
+    std::fs::read_dir(snapshots)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join("tokenizer.json"))
+        .find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            role: role.into(),
+            content: content.into(),
+        }
+    }
+
+    fn words(message: &ChatMessage) -> usize {
+        message.content.split_whitespace().count()
+    }
+
+    #[test]
+    fn test_count_tokens_estimates_without_tokenizer() {
+        assert_eq!(count_tokens("", "no-such-model"), 0);
+        assert_eq!(count_tokens("abcdef", "no-such-model"), 2);
+        assert_eq!(count_tokens("abcdefg", "no-such-model"), 3);
+    }
+
+    #[test]
+    fn test_fit_history_keeps_everything_within_budget() {
+        let messages = [message("user", "one two"), message("assistant", "three")];
+        let (kept, budget) = fit_history_with(&messages, 10, words);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(budget.prompt_tokens, 3);
+        assert!(!budget.truncated());
+    }
+
+    #[test]
+    fn test_fit_history_drops_oldest_but_keeps_system_and_latest() {
+        let messages = [
+            message("system", "be brief"),
+            message("user", "a b c d"),
+            message("assistant", "e f g"),
+            message("user", "h i"),
+        ];
+        let (kept, budget) = fit_history_with(&messages, 7, words);
+        let contents: Vec<&str> = kept.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["be brief", "e f g", "h i"]);
+        assert_eq!(budget.dropped_messages, 1);
+        assert_eq!(budget.prompt_tokens, 7);
+
+        // Even a hopeless budget keeps the system prompt and the question
+        let (kept, budget) = fit_history_with(&messages, 1, words);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(budget.dropped_messages, 2);
+    }
+
+    #[test]
+    fn test_reply_reserve() {
+        assert_eq!(reply_reserve(512, 4096), 512);
+        assert_eq!(reply_reserve(4096, 8192), 2048);
+    }
+}
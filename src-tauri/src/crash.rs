@@ -0,0 +1,230 @@
+//! Crash detection and the last-run error report.
+//!
+//! While Ghost runs, a sentinel file sits in the app data directory; it is
+//! removed on a clean exit. A panic hook writes the panic message, location,
+//! backtrace and the most recent log lines to disk as it happens. On the next
+//! launch a leftover sentinel means the previous session crashed, and what was
+//! captured becomes the last crash report, which the user can read with
+//! `get_last_crash_report` and attach to an issue. Everything stays local.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Present while a session runs.
+const SENTINEL_FILE: &str = "session.running";
+/// Written by the panic hook during the current session.
+const PANIC_FILE: &str = "panic.json";
+/// Report of the last crashed session, kept until dismissed.
+const REPORT_FILE: &str = "last_crash.json";
+
+/// Log lines captured with a panic.
+const RECENT_LOG_LINES: usize = 50;
+
+/// A panic captured by the hook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanicDetails {
+    pub message: String,
+    /// "file:line:column" of the panic.
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub recent_logs: Vec<String>,
+    pub occurred_at: String,
+}
+
+/// What is known about the last session that didn't exit cleanly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// When the crashed session started.
+    pub session_started_at: Option<String>,
+    /// When the crash was noticed (the next launch).
+    pub detected_at: String,
+    /// The panic, if one was captured. Without it the process was killed or
+    /// crashed in native code (llama.cpp, a driver).
+    pub panic: Option<PanicDetails>,
+}
+
+/// App data directory, set by [`install`].
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Check how the previous session ended, then mark this one as running and
+/// install the panic hook. Returns a report if the previous session crashed.
+pub fn install(data_dir: &Path) -> Option<CrashReport> {
+    let crashed = match check_previous_session(data_dir) {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::warn!("Failed to check the previous session: {}", e);
+            None
+        }
+    };
+    if let Err(e) = start_session(data_dir) {
+        tracing::warn!("Failed to write the session sentinel: {}", e);
+    }
+    let _ = DATA_DIR.set(data_dir.to_path_buf());
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(dir) = DATA_DIR.get() {
+            let details = PanicDetails {
+                message: panic_message(info.payload()),
+                location: info
+                    .location()
+                    .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+                thread: std::thread::current().name().map(str::to_string),
+                backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+                recent_logs: crate::recent_log_lines(RECENT_LOG_LINES),
+                occurred_at: chrono::Utc::now().to_rfc3339(),
+            };
+            // Nothing sensible to do if this fails while panicking
+            let _ = write_panic(dir, &details);
+        }
+        previous(info);
+    }));
+    crashed
+}
+
+/// Record a clean exit: the next launch won't report a crash.
+pub fn mark_clean_exit() {
+    if let Some(dir) = DATA_DIR.get() {
+        let _ = std::fs::remove_file(dir.join(SENTINEL_FILE));
+        let _ = std::fs::remove_file(dir.join(PANIC_FILE));
+    }
+}
+
+/// Report of the last crashed session, if it hasn't been dismissed.
+pub fn last_report(data_dir: &Path) -> Option<CrashReport> {
+    let json = std::fs::read_to_string(data_dir.join(REPORT_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Delete the last crash report.
+pub fn dismiss_report(data_dir: &Path) -> Result<()> {
+    match std::fs::remove_file(data_dir.join(REPORT_FILE)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Turn a leftover sentinel (and panic, if any) into the last crash report.
+fn check_previous_session(data_dir: &Path) -> Result<Option<CrashReport>> {
+    let sentinel = data_dir.join(SENTINEL_FILE);
+    let panic_path = data_dir.join(PANIC_FILE);
+    if !sentinel.exists() {
+        // A panic caught by its task in a session that still exited cleanly
+        let _ = std::fs::remove_file(&panic_path);
+        return Ok(None);
+    }
+    let session_started_at = std::fs::read_to_string(&sentinel)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    let panic = std::fs::read_to_string(&panic_path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let report = CrashReport {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        session_started_at,
+        detected_at: chrono::Utc::now().to_rfc3339(),
+        panic,
+    };
+    std::fs::write(
+        data_dir.join(REPORT_FILE),
+        serde_json::to_string_pretty(&report)?,
+    )?;
+    let _ = std::fs::remove_file(&panic_path);
+    Ok(Some(report))
+}
+
+fn start_session(data_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    std::fs::write(
+        data_dir.join(SENTINEL_FILE),
+        chrono::Utc::now().to_rfc3339(),
+    )?;
+    Ok(())
+}
+
+fn write_panic(data_dir: &Path, details: &PanicDetails) -> Result<()> {
+    std::fs::write(
+        data_dir.join(PANIC_FILE),
+        serde_json::to_string_pretty(details)?,
+    )?;
+    Ok(())
+}
+
+/// Text of a panic payload. (Takes the payload rather than the hook info,
+/// whose type was renamed after the crate's MSRV.)
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ghost_crash_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_clean_exit_reports_nothing() {
+        let dir = temp_dir("clean");
+        assert!(check_previous_session(&dir).unwrap().is_none());
+        start_session(&dir).unwrap();
+        std::fs::remove_file(dir.join(SENTINEL_FILE)).unwrap();
+        assert!(check_previous_session(&dir).unwrap().is_none());
+        assert!(last_report(&dir).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_crash_with_panic_is_reported() {
+        let dir = temp_dir("panic");
+        start_session(&dir).unwrap();
+        let details = PanicDetails {
+            message: "index out of bounds".into(),
+            location: Some("src/indexer/mod.rs:10:5".into()),
+            thread: Some("main".into()),
+            backtrace: "0: ghost_lib::run".into(),
+            recent_logs: vec!["[info] Indexing".into()],
+            occurred_at: "2026-01-01T00:00:00Z".into(),
+        };
+        write_panic(&dir, &details).unwrap();
+
+        let report = check_previous_session(&dir).unwrap().unwrap();
+        assert!(report.session_started_at.is_some());
+        assert_eq!(report.panic.unwrap().message, "index out of bounds");
+        assert!(!dir.join(PANIC_FILE).exists());
+        // Kept until dismissed
+        assert!(last_report(&dir).is_some());
+        dismiss_report(&dir).unwrap();
+        assert!(last_report(&dir).is_none());
+        dismiss_report(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_crash_without_panic_is_reported() {
+        let dir = temp_dir("killed");
+        start_session(&dir).unwrap();
+        let report = check_previous_session(&dir).unwrap().unwrap();
+        assert!(report.panic.is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,189 @@
+//! Optional at-rest encryption of vault databases.
+//!
+//! An encrypted vault is a SQLCipher database keyed with a 256-bit key that
+//! Argon2id derives from the user's passphrase. The salt and cost parameters
+//! are stored next to the database (`ghost_vault.key.json`); the passphrase
+//! and the key never touch the disk. A passphrase can only be set when a
+//! vault is created. Settings and logs stay unencrypted. Key escrow and
+//! syncing keys between devices are left to Ghost Pro.
+
+use std::path::{Path, PathBuf};
+
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GhostError, Result};
+
+/// Shortest passphrase accepted, in characters.
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// Raw SQLCipher key derived from a passphrase.
+pub type VaultKey = [u8; 32];
+
+/// Key derivation parameters of an encrypted vault.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyParams {
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KeyParams {
+    /// Fresh random salt with Argon2's recommended costs.
+    fn generate() -> Self {
+        let defaults = Params::default();
+        Self {
+            salt: SaltString::generate(&mut OsRng).as_str().to_string(),
+            memory_kib: defaults.m_cost(),
+            iterations: defaults.t_cost(),
+            parallelism: defaults.p_cost(),
+        }
+    }
+
+    /// Derive the database key. Deliberately slow (tens of milliseconds).
+    pub fn derive_key(&self, passphrase: &str) -> Result<VaultKey> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| GhostError::Vault(format!("Invalid key parameters: {}", e)))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), self.salt.as_bytes(), &mut key)
+            .map_err(|e| GhostError::Vault(format!("Key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+}
+
+/// Where the key parameters of the database at `db_path` are kept.
+pub fn params_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("key.json")
+}
+
+/// Whether the database at `db_path` is encrypted.
+pub fn is_encrypted(db_path: &Path) -> bool {
+    params_path(db_path).exists()
+}
+
+/// Whether SQLite was built with SQLCipher (the `encryption` feature).
+/// Without it `PRAGMA key` is silently ignored, so this must be checked
+/// before creating an encrypted vault.
+pub fn is_supported() -> bool {
+    Connection::open_in_memory()
+        .and_then(|conn| conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0)))
+        .is_ok()
+}
+
+/// Reject passphrases too short to be worth deriving a key from.
+pub fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(GhostError::Vault(format!(
+            "Passphrases must be at least {} characters",
+            MIN_PASSPHRASE_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// Set up encryption for a database that doesn't exist yet: write fresh key
+/// parameters and return the key to create it with.
+pub fn create_key(db_path: &Path, passphrase: &str) -> Result<VaultKey> {
+    check_passphrase(passphrase)?;
+    if !is_supported() {
+        return Err(GhostError::Vault(
+            "This build of Ghost has no encryption support".into(),
+        ));
+    }
+    if db_path.exists() {
+        return Err(GhostError::Vault("Only new vaults can be encrypted".into()));
+    }
+    let params = KeyParams::generate();
+    let path = params_path(db_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&params)?)?;
+    crate::isolation::make_private(&path);
+    params.derive_key(passphrase)
+}
+
+/// Derive the key of an encrypted database from its passphrase. A wrong
+/// passphrase only shows when the database is opened with the key.
+pub fn unlock_key(db_path: &Path, passphrase: &str) -> Result<VaultKey> {
+    let json = std::fs::read_to_string(params_path(db_path))
+        .map_err(|_| GhostError::Vault("This vault is not encrypted".into()))?;
+    let params: KeyParams = serde_json::from_str(&json)?;
+    params.derive_key(passphrase)
+}
+
+/// Key a newly opened connection. Must run before any other statement.
+pub(super) fn apply_key(conn: &Connection, key: &VaultKey) -> Result<()> {
+    conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", hex::encode(key)))?;
+    // SQLCipher reads the file lazily; fail here rather than on the first query
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|e| match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::NotADatabase) => GhostError::Vault("Wrong passphrase".into()),
+        _ => e.into(),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn fast_params() -> KeyParams {
+        KeyParams {
+            memory_kib: 64,
+            iterations: 1,
+            ..KeyParams::generate()
+        }
+    }
+
+    #[test]
+    fn test_derive_key_depends_on_passphrase_and_salt() {
+        let params = fast_params();
+        let key = params.derive_key("correct horse").unwrap();
+        assert_eq!(key, params.derive_key("correct horse").unwrap());
+        assert_ne!(key, params.derive_key("correct horsf").unwrap());
+        let other_salt = KeyParams {
+            salt: SaltString::generate(&mut OsRng).as_str().to_string(),
+            ..params
+        };
+        assert_ne!(key, other_salt.derive_key("correct horse").unwrap());
+    }
+
+    #[test]
+    fn test_check_passphrase() {
+        assert!(check_passphrase("short").is_err());
+        assert!(check_passphrase("long enough").is_ok());
+    }
+
+    #[test]
+    fn test_encrypted_database_needs_the_key() {
+        if !is_supported() {
+            return;
+        }
+        let path =
+            std::env::temp_dir().join(format!("ghost_test_cipher_{}.db", std::process::id()));
+        let key = fast_params().derive_key("correct horse").unwrap();
+        let db = Database::open_with_key(&path, Some(&key)).unwrap();
+        db.upsert_document("/a.txt", "a.txt", None, 1, "h", "2026-02-18T00:00:00Z")
+            .unwrap();
+        drop(db);
+
+        let wrong = fast_params().derive_key("wrong horse").unwrap();
+        assert!(Database::open_with_key(&path, Some(&wrong)).is_err());
+        assert!(Database::open(&path).is_err());
+        let db = Database::open_with_key(&path, Some(&key)).unwrap();
+        assert_eq!(db.get_stats().unwrap().document_count, 1);
+
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Connection pragmas and on-demand vault maintenance.
+//!
+//! [`apply_pragmas`] puts every writer connection in WAL mode with
+//! `synchronous=NORMAL` before the schema is created. The maintenance
+//! operations — [`vacuum`], [`integrity_check`], and [`optimize_fts`] — are for
+//! vaults that have grown to several GB: each reports the database size
+//! before and after so users can see what was reclaimed.
+
+use std::time::Instant;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::Database;
+use crate::error::Result;
+
+/// Most problems listed by an integrity check.
+const MAX_INTEGRITY_ERRORS: usize = 100;
+
+/// Set the journal mode and performance pragmas on a writer connection.
+///
+/// Runs before the schema is created, so `page_size` applies to new vaults.
+pub fn apply_pragmas(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        -- 4KB page size matches OS page size for optimal IO (new vaults only)
+        PRAGMA page_size=4096;
+        -- Enable WAL mode for concurrent reads
+        PRAGMA journal_mode=WAL;
+        PRAGMA foreign_keys=ON;
+
+        -- Performance PRAGMAs (2-5x speedup for reads/writes)
+        -- synchronous=NORMAL is safe with WAL mode (data survives process crash, not power loss)
+        PRAGMA synchronous=NORMAL;
+        -- 16MB page cache (vs default 2MB) — keeps hot pages in memory
+        PRAGMA cache_size=-16000;
+        -- 256MB mmap for read-heavy workloads — OS page cache handles eviction
+        PRAGMA mmap_size=268435456;
+        -- Keep temp tables and indices in RAM (faster sorts, GROUP BY, etc.)
+        PRAGMA temp_store=MEMORY;
+        -- 5s busy timeout to avoid SQLITE_BUSY under concurrent access
+        PRAGMA busy_timeout=5000;
+        ",
+    )?;
+    Ok(())
+}
+
+/// Size of the vault database.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DbSizeStats {
+    /// Main database file, in bytes (0 for in-memory databases).
+    pub file_bytes: u64,
+    /// Write-ahead log, in bytes.
+    pub wal_bytes: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    /// Unused pages that VACUUM would return to the OS.
+    pub freelist_count: i64,
+}
+
+/// Result of a maintenance operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceReport {
+    pub operation: String,
+    pub before: DbSizeStats,
+    pub after: DbSizeStats,
+    /// Bytes freed on disk (database plus WAL); negative if it grew.
+    pub reclaimed_bytes: i64,
+    pub duration_ms: u64,
+}
+
+/// Result of an integrity check.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    /// Whether SQLite and the full-text index both reported no problems.
+    pub ok: bool,
+    /// Whether this was the faster `quick_check` (skips index consistency).
+    pub quick: bool,
+    /// Problems reported by SQLite (empty when ok).
+    pub errors: Vec<String>,
+    /// Whether the full-text index matches the chunks table.
+    pub fts_ok: bool,
+    pub stats: DbSizeStats,
+    pub duration_ms: u64,
+}
+
+fn file_len(path: &std::path::Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Current database size.
+pub fn size_stats(db: &Database) -> Result<DbSizeStats> {
+    let (file_bytes, wal_bytes) = match db.path() {
+        Some(path) => {
+            let wal = format!("{}-wal", path.display());
+            (file_len(&path), file_len(std::path::Path::new(&wal)))
+        }
+        None => (0, 0),
+    };
+    db.with_conn(|conn| {
+        let pragma = |name: &str| -> rusqlite::Result<i64> {
+            conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+        };
+        Ok(DbSizeStats {
+            file_bytes,
+            wal_bytes,
+            page_size: pragma("page_size")?,
+            page_count: pragma("page_count")?,
+            freelist_count: pragma("freelist_count")?,
+        })
+    })
+}
+
+/// Run `operation` on the writer and report the size change around it.
+fn measured<F>(db: &Database, operation: &str, f: F) -> Result<MaintenanceReport>
+where
+    F: FnOnce(&Connection) -> Result<()>,
+{
+    let started = Instant::now();
+    let before = size_stats(db)?;
+    db.with_conn(|conn| {
+        f(conn)?;
+        // Fold the WAL back in so the file sizes reflect the result
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)")?;
+        Ok(())
+    })?;
+    let after = size_stats(db)?;
+    let on_disk = |s: &DbSizeStats| (s.file_bytes + s.wal_bytes) as i64;
+
+    let report = MaintenanceReport {
+        operation: operation.to_string(),
+        reclaimed_bytes: on_disk(&before) - on_disk(&after),
+        before,
+        after,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    tracing::info!(
+        "{} finished in {} ms ({} bytes reclaimed)",
+        operation,
+        report.duration_ms,
+        report.reclaimed_bytes
+    );
+    Ok(report)
+}
+
+/// Rebuild the database file, returning free pages to the OS.
+///
+/// Takes the write lock for the duration; on a multi-GB vault this can take
+/// minutes, so run it off the async runtime.
+pub fn vacuum(db: &Database) -> Result<MaintenanceReport> {
+    measured(db, "VACUUM", |conn| {
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        Ok(())
+    })
+}
+
+/// Merge the full-text index's segments and refresh query-planner statistics.
+pub fn optimize_fts(db: &Database) -> Result<MaintenanceReport> {
+    measured(db, "FTS optimize", |conn| {
+        conn.execute_batch(
+            "INSERT INTO chunks_fts(chunks_fts) VALUES('optimize');
+             PRAGMA optimize;",
+        )?;
+        Ok(())
+    })
+}
+
+/// Check the database for corruption, including the full-text index.
+///
+/// `quick` runs `quick_check`, which skips verifying that indexes match their
+/// tables and is much faster on large vaults.
+pub fn integrity_check(db: &Database, quick: bool) -> Result<IntegrityReport> {
+    let started = Instant::now();
+    let pragma = if quick {
+        "quick_check"
+    } else {
+        "integrity_check"
+    };
+    let (errors, fts_ok) = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(&format!("PRAGMA {}({})", pragma, MAX_INTEGRITY_ERRORS))?;
+        let errors: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|line| line != "ok")
+            .collect();
+        // FTS5 raises SQLITE_CORRUPT_VTAB when the index disagrees with its content
+        let fts = conn.execute(
+            "INSERT INTO chunks_fts(chunks_fts) VALUES('integrity-check')",
+            [],
+        );
+        if let Err(e) = &fts {
+            tracing::warn!("Full-text index integrity check failed: {}", e);
+        }
+        Ok((errors, fts.is_ok()))
+    })?;
+
+    Ok(IntegrityReport {
+        ok: errors.is_empty() && fts_ok,
+        quick,
+        errors,
+        fts_ok,
+        stats: size_stats(db)?,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance_reclaims_space() {
+        let path =
+            std::env::temp_dir().join(format!("ghost_test_maintenance_{}.db", std::process::id()));
+        let db = Database::open(&path).unwrap();
+        let mode: String = db
+            .with_conn(|conn| Ok(conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?))
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        let doc = db
+            .upsert_document(
+                "/big.txt",
+                "big.txt",
+                Some("txt"),
+                1,
+                "h",
+                "2026-02-18T00:00:00Z",
+            )
+            .unwrap();
+        let text = "lorem ipsum dolor ".repeat(500);
+        for i in 0..200 {
+            db.insert_chunk(doc, i, &text, 1500).unwrap();
+        }
+        db.delete_document(doc).unwrap();
+
+        let report = integrity_check(&db, false).unwrap();
+        assert!(report.ok, "{:?}", report.errors);
+        assert!(report.stats.freelist_count > 0);
+
+        let report = vacuum(&db).unwrap();
+        assert_eq!(report.after.freelist_count, 0);
+        let allocated = |s: &DbSizeStats| s.page_size * s.page_count;
+        assert!(allocated(&report.after) < allocated(&report.before));
+        assert!(report.reclaimed_bytes > 0);
+        assert!(optimize_fts(&db).is_ok());
+
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+        }
+    }
+}
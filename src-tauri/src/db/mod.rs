@@ -1,9 +1,14 @@
+pub mod encryption;
+pub mod maintenance;
+pub mod query;
 pub mod schema;
+pub mod vec_migration;
 
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
 
 use crate::error::{GhostError, Result};
 
@@ -30,32 +35,86 @@ fn sanitize_fts5_query(query: &str) -> String {
         .join(" ")
 }
 
+/// Longest tag name accepted, in characters.
+const MAX_TAG_CHARS: usize = 64;
+
+/// Normalize a tag name: trimmed, lowercase, inner whitespace collapsed.
+/// Returns `None` for empty, overlong, or control-character names.
+pub fn normalize_tag(name: &str) -> Option<String> {
+    let tag = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_CHARS || tag.chars().any(char::is_control) {
+        return None;
+    }
+    Some(tag.to_lowercase())
+}
+
+/// Entry point signature of a SQLite extension.
+type ExtensionInit = unsafe extern "C" fn(
+    *mut rusqlite::ffi::sqlite3,
+    *mut *mut std::ffi::c_char,
+    *const rusqlite::ffi::sqlite3_api_routines,
+) -> std::ffi::c_int;
+
+/// sqlite-vec's entry point (the crate declares it without arguments).
+fn vec_extension_init() -> ExtensionInit {
+    unsafe {
+        std::mem::transmute::<*const (), ExtensionInit>(sqlite_vec::sqlite3_vec_init as *const ())
+    }
+}
+
+/// Read-only connections opened next to the writer for file databases.
+const READ_POOL_SIZE: usize = 4;
+
+/// Error for a connection whose mutex was poisoned by a panicking holder.
+fn lock_poisoned<T>(e: PoisonError<T>) -> GhostError {
+    GhostError::Database(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(1),
+        Some(format!("Lock poisoned: {}", e)),
+    ))
+}
+
 /// Thread-safe database wrapper.
+///
+/// Holds one writer connection plus a small pool of read-only connections.
+/// With WAL mode, readers see the last committed state while the writer is
+/// busy, so searches don't queue behind indexing.
 pub struct Database {
+    /// The single writer: all inserts, updates and transactions go through it.
     conn: Mutex<Connection>,
+    /// Read-only connections for searches and lookups. Empty for in-memory
+    /// databases, which can't be shared between connections; reads then use
+    /// the writer.
+    readers: Vec<Mutex<Connection>>,
+    /// Database file, `None` when in memory. Changes when another vault's
+    /// database is swapped in.
+    path: Mutex<Option<PathBuf>>,
+    /// Where the next reader search starts when all readers are busy.
+    next_reader: AtomicUsize,
     /// Whether sqlite-vec extension was loaded successfully.
-    vec_enabled: bool,
+    /// Can flip to true at runtime via [`Database::retry_enable_vec`].
+    vec_enabled: AtomicBool,
+    /// Why sqlite-vec is unavailable, when it failed to load.
+    vec_error: Mutex<Option<String>>,
+    /// Set on the placeholder of an encrypted vault that isn't unlocked yet.
+    locked: AtomicBool,
 }
 
 impl Database {
     /// Register sqlite-vec auto-extension (must be called before opening connections).
     fn register_vec_extension() {
         unsafe {
-            rusqlite::ffi::sqlite3_auto_extension(Some(std::mem::transmute::<
-                *const (),
-                unsafe extern "C" fn(
-                    *mut rusqlite::ffi::sqlite3,
-                    *mut *mut std::ffi::c_char,
-                    *const rusqlite::ffi::sqlite3_api_routines,
-                ) -> std::ffi::c_int,
-            >(
-                sqlite_vec::sqlite3_vec_init as *const (),
-            )));
+            rusqlite::ffi::sqlite3_auto_extension(Some(vec_extension_init()));
         }
     }
 
     /// Open or create the ghost vault database.
     pub fn open(path: &PathBuf) -> Result<Self> {
+        Self::open_with_key(path, None)
+    }
+
+    /// Open or create a vault database, encrypted with `key` if given
+    /// (see [`encryption`]).
+    pub fn open_with_key(path: &PathBuf, key: Option<&encryption::VaultKey>) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -64,15 +123,39 @@ impl Database {
         Self::register_vec_extension();
 
         let conn = Connection::open(path)?;
+        if let Some(key) = key {
+            encryption::apply_key(&conn, key)?;
+        }
+        maintenance::apply_pragmas(&conn)?;
         schema::initialize_schema(&conn)?;
 
+        // Readers open after the schema exists (and WAL is on)
+        let readers = (0..READ_POOL_SIZE)
+            .map(|_| Self::open_reader(path, key).map(Mutex::new))
+            .collect::<Result<Vec<_>>>()?;
+
         // Test if sqlite-vec loaded correctly
-        let vec_enabled = Self::try_load_vec(&conn);
+        Ok(Self::with_vec_status(conn, readers, Some(path.clone())))
+    }
 
-        Ok(Self {
-            conn: Mutex::new(conn),
-            vec_enabled,
-        })
+    /// Open a read-only connection to the database file.
+    fn open_reader(path: &Path, key: Option<&encryption::VaultKey>) -> Result<Connection> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX
+                | OpenFlags::SQLITE_OPEN_URI,
+        )?;
+        if let Some(key) = key {
+            encryption::apply_key(&conn, key)?;
+        }
+        conn.execute_batch(
+            "PRAGMA cache_size=-8000;
+             PRAGMA mmap_size=268435456;
+             PRAGMA temp_store=MEMORY;
+             PRAGMA busy_timeout=5000;",
+        )?;
+        Ok(conn)
     }
 
     /// Open an in-memory database (for testing).
@@ -81,38 +164,160 @@ impl Database {
         Self::register_vec_extension();
 
         let conn = Connection::open_in_memory()?;
+        maintenance::apply_pragmas(&conn)?;
         schema::initialize_schema(&conn)?;
 
-        let vec_enabled = Self::try_load_vec(&conn);
+        Ok(Self::with_vec_status(conn, Vec::new(), None))
+    }
 
+    /// Stand-in for the encrypted vault at `path` until it is unlocked: every
+    /// query fails with [`GhostError::VaultLocked`]. [`Database::replace_with`]
+    /// swaps in the unlocked database.
+    pub fn locked(path: &Path) -> Result<Self> {
+        let readers = (0..READ_POOL_SIZE)
+            .map(|_| Connection::open_in_memory().map(Mutex::new))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
         Ok(Self {
-            conn: Mutex::new(conn),
-            vec_enabled,
+            conn: Mutex::new(Connection::open_in_memory()?),
+            readers,
+            path: Mutex::new(Some(path.to_path_buf())),
+            next_reader: AtomicUsize::new(0),
+            vec_enabled: AtomicBool::new(false),
+            vec_error: Mutex::new(Some("The vault is locked".into())),
+            locked: AtomicBool::new(true),
         })
     }
 
+    /// Whether this is the placeholder of an encrypted vault not unlocked yet.
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// Wrap initialized connections, recording whether sqlite-vec works on the writer.
+    fn with_vec_status(
+        conn: Connection,
+        readers: Vec<Mutex<Connection>>,
+        path: Option<PathBuf>,
+    ) -> Self {
+        let vec = Self::try_load_vec(&conn);
+        Self {
+            conn: Mutex::new(conn),
+            readers,
+            path: Mutex::new(path),
+            next_reader: AtomicUsize::new(0),
+            vec_enabled: AtomicBool::new(vec.is_ok()),
+            vec_error: Mutex::new(vec.err()),
+            locked: AtomicBool::new(false),
+        }
+    }
+
     /// Test if sqlite-vec is working and initialize vector tables.
-    fn try_load_vec(conn: &Connection) -> bool {
+    /// Returns the reason on failure.
+    fn try_load_vec(conn: &Connection) -> std::result::Result<(), String> {
         // Test if sqlite-vec is working
         match conn.query_row("SELECT vec_version()", [], |row| row.get::<_, String>(0)) {
             Ok(version) => {
                 tracing::info!("sqlite-vec {} loaded successfully", version);
-                if let Err(e) = schema::initialize_vec_table(conn) {
+                schema::initialize_vec_table(conn).map_err(|e| {
                     tracing::warn!("Failed to create chunks_vec table: {}", e);
-                    return false;
-                }
-                true
+                    format!("Failed to create chunks_vec table: {}", e)
+                })
             }
             Err(e) => {
                 tracing::warn!("sqlite-vec not available: {} — vector search disabled", e);
-                false
+                Err(format!("sqlite-vec not available: {}", e))
             }
         }
     }
 
+    /// Database file, `None` when in memory.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Swap in the connections of `other`, so everything holding this
+    /// `Database` continues on the other file (switching vaults). Waits for
+    /// the writer and every reader to be idle. The old connections close.
+    pub fn replace_with(&self, other: Database) -> Result<()> {
+        if self.readers.len() != other.readers.len() {
+            return Err(GhostError::Vault(
+                "Can't swap an in-memory database with a file database".into(),
+            ));
+        }
+        // Same order as with_read_conn callers that fall back to the writer
+        let mut conn = self.conn.lock().map_err(lock_poisoned)?;
+        let mut readers = self
+            .readers
+            .iter()
+            .map(|r| r.lock().map_err(lock_poisoned))
+            .collect::<Result<Vec<_>>>()?;
+
+        let Database {
+            conn: new_conn,
+            readers: new_readers,
+            path,
+            vec_enabled,
+            vec_error,
+            locked,
+            ..
+        } = other;
+        *conn = new_conn.into_inner().unwrap_or_else(|e| e.into_inner());
+        for (slot, reader) in readers.iter_mut().zip(new_readers) {
+            **slot = reader.into_inner().unwrap_or_else(|e| e.into_inner());
+        }
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) =
+            path.into_inner().unwrap_or_else(|e| e.into_inner());
+        self.vec_enabled
+            .store(vec_enabled.into_inner(), Ordering::Relaxed);
+        *self.vec_error.lock().unwrap_or_else(|e| e.into_inner()) =
+            vec_error.into_inner().unwrap_or_else(|e| e.into_inner());
+        self.locked.store(locked.into_inner(), Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Check if vector search is available.
     pub fn is_vec_enabled(&self) -> bool {
-        self.vec_enabled
+        self.vec_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Why vector search is unavailable (`None` when it works).
+    pub fn vec_error(&self) -> Option<String> {
+        self.vec_error.lock().ok().and_then(|e| e.clone())
+    }
+
+    /// Try again to load sqlite-vec and create the vector table on the open
+    /// connection, without restarting. Returns whether vector search is now enabled.
+    pub fn retry_enable_vec(&self) -> Result<bool> {
+        if self.is_vec_enabled() {
+            return Ok(true);
+        }
+        Self::register_vec_extension();
+        let outcome = self.with_conn(|conn| {
+            Self::init_vec_on(conn);
+            Ok(Self::try_load_vec(conn))
+        })?;
+        if outcome.is_ok() {
+            for reader in &self.readers {
+                Self::init_vec_on(&*reader.lock().map_err(lock_poisoned)?);
+            }
+        }
+
+        let enabled = outcome.is_ok();
+        self.vec_enabled.store(enabled, Ordering::Relaxed);
+        if let Ok(mut error) = self.vec_error.lock() {
+            *error = outcome.err();
+        }
+        Ok(enabled)
+    }
+
+    /// Initialize sqlite-vec on an already open connection. The auto-extension
+    /// only applies to new connections (sqlite-vec is statically linked).
+    fn init_vec_on(conn: &Connection) {
+        let rc =
+            unsafe { vec_extension_init()(conn.handle(), std::ptr::null_mut(), std::ptr::null()) };
+        if rc != rusqlite::ffi::SQLITE_OK {
+            tracing::warn!("sqlite-vec init returned error code {}", rc);
+        }
     }
 
     /// Perform a WAL checkpoint for clean shutdown.
@@ -126,17 +331,39 @@ impl Database {
         })
     }
 
-    /// Execute a closure with access to the database connection.
+    /// Execute a closure with access to the writer connection.
+    ///
+    /// Use this for anything that writes, or that must see the effects of a
+    /// write it is about to make. Pure reads should use [`Database::with_read_conn`].
     pub fn with_conn<F, T>(&self, f: F) -> Result<T>
     where
         F: FnOnce(&Connection) -> Result<T>,
     {
-        let conn = self.conn.lock().map_err(|e| {
-            GhostError::Database(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(1),
-                Some(format!("Lock poisoned: {}", e)),
-            ))
-        })?;
+        if self.is_locked() {
+            return Err(GhostError::VaultLocked);
+        }
+        let conn = self.conn.lock().map_err(lock_poisoned)?;
+        f(&conn)
+    }
+
+    /// Execute a read-only closure on a pooled reader connection, so it runs
+    /// alongside writes instead of waiting for the writer. Statements that
+    /// try to write fail with `SQLITE_READONLY`.
+    pub fn with_read_conn<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T>,
+    {
+        if self.readers.is_empty() || self.is_locked() {
+            return self.with_conn(f);
+        }
+        // Take the first idle reader; if all are busy, wait on one in turn
+        let conn = match self.readers.iter().find_map(|r| r.try_lock().ok()) {
+            Some(conn) => conn,
+            None => {
+                let i = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+                self.readers[i].lock().map_err(lock_poisoned)?
+            }
+        };
         f(&conn)
     }
 
@@ -147,12 +374,10 @@ impl Database {
     where
         F: FnOnce(&Connection) -> Result<T>,
     {
-        let conn = self.conn.lock().map_err(|e| {
-            GhostError::Database(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(1),
-                Some(format!("Lock poisoned: {}", e)),
-            ))
-        })?;
+        if self.is_locked() {
+            return Err(GhostError::VaultLocked);
+        }
+        let conn = self.conn.lock().map_err(lock_poisoned)?;
         conn.execute_batch("BEGIN IMMEDIATE")?;
         match f(&conn) {
             Ok(result) => {
@@ -237,11 +462,12 @@ impl Database {
     pub fn delete_document(&self, document_id: i64) -> Result<()> {
         self.with_conn(|conn| {
             // Delete embeddings first (vec table doesn't support FK CASCADE)
-            if self.vec_enabled {
+            if self.is_vec_enabled() {
                 conn.execute(
                     "DELETE FROM chunks_vec WHERE chunk_id IN (SELECT id FROM chunks WHERE document_id = ?1)",
                     rusqlite::params![document_id],
                 )?;
+                vec_migration::delete_shadow_embeddings(conn, document_id)?;
             }
             // CASCADE will delete chunks + trigger FTS5 cleanup
             conn.execute(
@@ -289,11 +515,16 @@ impl Database {
             return Ok(vec![]);
         }
 
-        self.with_conn(|conn| {
+        self.fts_match(&sanitized, limit)
+    }
+
+    /// FTS5 search with a prepared MATCH expression (already sanitized).
+    pub fn fts_match(&self, expression: &str, limit: usize) -> Result<Vec<(i64, f64)>> {
+        self.with_read_conn(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT rowid, rank FROM chunks_fts WHERE chunks_fts MATCH ?1 ORDER BY rank LIMIT ?2",
             )?;
-            let rows = stmt.query_map(rusqlite::params![sanitized, limit as i64], |row| {
+            let rows = stmt.query_map(rusqlite::params![expression, limit as i64], |row| {
                 Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?))
             })?;
             let mut results = Vec::new();
@@ -304,11 +535,37 @@ impl Database {
         })
     }
 
+    /// Indexed (stemmed) terms starting with `first` whose length is within
+    /// `min_len..=max_len`, with the number of chunks containing each.
+    pub fn vocabulary_terms(
+        &self,
+        first: char,
+        min_len: usize,
+        max_len: usize,
+    ) -> Result<Vec<(String, i64)>> {
+        let start = first.to_string();
+        let end = char::from_u32(first as u32 + 1)
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| char::MAX.to_string());
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT term, doc FROM chunks_fts_vocab
+                 WHERE term >= ?1 AND term < ?2 AND length(term) BETWEEN ?3 AND ?4",
+            )?;
+            let rows = stmt.query_map(
+                rusqlite::params![start, end, min_len as i64, max_len as i64],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
     /// Get chunk details by ID.
     pub fn get_chunk_with_document(&self, chunk_id: i64) -> Result<Option<ChunkWithDocument>> {
-        self.with_conn(|conn| {
+        self.with_read_conn(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT c.id, c.content, c.chunk_index, d.id, d.path, d.filename, d.extension
+                "SELECT c.id, c.content, c.chunk_index, d.id, d.path, d.filename, d.extension,
+                        c.section, c.symbol, c.language
                  FROM chunks c
                  JOIN documents d ON c.document_id = d.id
                  WHERE c.id = ?1",
@@ -322,6 +579,9 @@ impl Database {
                     path: row.get(4)?,
                     filename: row.get(5)?,
                     extension: row.get(6)?,
+                    section: row.get(7)?,
+                    symbol: row.get(8)?,
+                    language: row.get(9)?,
                 })
             });
             // Distinguish "no rows" from real errors
@@ -350,9 +610,241 @@ impl Database {
         })
     }
 
+    /// Store the sender and send date of an email document.
+    pub fn set_email_metadata(
+        &self,
+        document_id: i64,
+        sender: Option<&str>,
+        sent_at: Option<&str>,
+    ) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE documents SET email_from = ?1, email_date = ?2 WHERE id = ?3",
+                rusqlite::params![sender, sent_at, document_id],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// IDs of email documents matching `from:` / `before:` filters, newest first.
+    ///
+    /// `sender` is a case-insensitive substring of the From header; `before`
+    /// is an ISO date prefix (`2024`, `2024-03`, `2024-03-05`) the send date
+    /// must sort strictly before.
+    pub fn filter_documents_by_email(
+        &self,
+        sender: Option<&str>,
+        before: Option<&str>,
+    ) -> Result<Vec<i64>> {
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id FROM documents
+                 WHERE (?1 IS NULL OR email_from LIKE '%' || ?1 || '%')
+                   AND (?2 IS NULL OR email_date < ?2)
+                   AND (email_from IS NOT NULL OR email_date IS NOT NULL)
+                 ORDER BY email_date DESC",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![sender, before], |row| row.get(0))?;
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+    }
+
+    /// Replace the event times of a calendar document.
+    pub fn set_document_events(
+        &self,
+        document_id: i64,
+        events: &[crate::indexer::extractor::EventTime],
+    ) -> Result<()> {
+        self.with_transaction(|conn| {
+            conn.execute(
+                "DELETE FROM document_events WHERE document_id = ?1",
+                rusqlite::params![document_id],
+            )?;
+            for event in events {
+                conn.execute(
+                    "INSERT INTO document_events (document_id, starts_at, ends_at)
+                     VALUES (?1, ?2, ?3)",
+                    rusqlite::params![document_id, event.starts_at, event.ends_at],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// IDs of calendar documents with an event during the `on:` date, an ISO
+    /// date prefix (`2025`, `2025-03`, `2025-03-12`), earliest event first.
+    pub fn filter_documents_by_event_date(&self, on: &str) -> Result<Vec<i64>> {
+        self.with_read_conn(|conn| {
+            // An event overlaps the period if it starts by its end and ends after its start
+            let mut stmt = conn.prepare(
+                "SELECT document_id FROM document_events
+                 WHERE substr(starts_at, 1, length(?1)) <= ?1
+                   AND substr(ends_at, 1, length(?1)) >= ?1
+                 GROUP BY document_id
+                 ORDER BY MIN(starts_at)",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![on], |row| row.get(0))?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// ID of a document's first chunk.
+    pub fn first_chunk_id(&self, document_id: i64) -> Result<Option<i64>> {
+        self.with_read_conn(|conn| {
+            let result = conn.query_row(
+                "SELECT id FROM chunks WHERE document_id = ?1 ORDER BY chunk_index LIMIT 1",
+                rusqlite::params![document_id],
+                |row| row.get(0),
+            );
+            match result {
+                Ok(v) => Ok(Some(v)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    /// Tag a document. `source` records who added the tag ("user" or
+    /// "plugin:<id>"); re-adding an existing tag keeps its original source.
+    pub fn add_document_tag(&self, document_id: i64, tag: &str, source: &str) -> Result<()> {
+        let tag = normalize_tag(tag)
+            .ok_or_else(|| GhostError::InvalidQuery(format!("Invalid tag '{}'", tag)))?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+                rusqlite::params![tag],
+            )?;
+            conn.execute(
+                "INSERT OR IGNORE INTO document_tags (document_id, tag_id, source)
+                 SELECT ?1, id, ?3 FROM tags WHERE name = ?2",
+                rusqlite::params![document_id, tag, source],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Remove every tag a given source added to a document.
+    pub fn clear_document_tags(&self, document_id: i64, source: &str) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM document_tags WHERE document_id = ?1 AND source = ?2",
+                rusqlite::params![document_id, source],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Remove a tag from a document, whoever added it. Tags no longer used by
+    /// any document are deleted. Returns whether the document had the tag.
+    pub fn remove_document_tag(&self, document_id: i64, tag: &str) -> Result<bool> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(false);
+        };
+        self.with_transaction(|conn| {
+            let removed = conn.execute(
+                "DELETE FROM document_tags WHERE document_id = ?1
+                 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+                rusqlite::params![document_id, tag],
+            )?;
+            conn.execute(
+                "DELETE FROM tags WHERE name = ?1
+                 AND NOT EXISTS (SELECT 1 FROM document_tags WHERE tag_id = tags.id)",
+                rusqlite::params![tag],
+            )?;
+            Ok(removed > 0)
+        })
+    }
+
+    /// All tags in use with their document counts, sorted by name.
+    pub fn list_tags(&self) -> Result<Vec<TagCount>> {
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.name, COUNT(dt.document_id) FROM tags t
+                 JOIN document_tags dt ON dt.tag_id = t.id
+                 GROUP BY t.id ORDER BY t.name",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(TagCount {
+                    name: row.get(0)?,
+                    document_count: row.get(1)?,
+                })
+            })?;
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+    }
+
+    /// Chunk counts per detected language, most common first.
+    pub fn language_counts(&self) -> Result<Vec<LanguageCount>> {
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT language, COUNT(*) FROM chunks
+                 WHERE language IS NOT NULL
+                 GROUP BY language ORDER BY COUNT(*) DESC, language",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(LanguageCount {
+                    language: row.get(0)?,
+                    chunk_count: row.get(1)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// IDs of documents carrying every one of `tags` (normalized names),
+    /// most recently indexed first.
+    pub fn filter_documents_by_tags(&self, tags: &[String]) -> Result<Vec<i64>> {
+        if tags.is_empty() {
+            return Ok(vec![]);
+        }
+        self.with_read_conn(|conn| {
+            let placeholders = vec!["?"; tags.len()].join(", ");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT d.id FROM documents d
+                 JOIN document_tags dt ON dt.document_id = d.id
+                 JOIN tags t ON t.id = dt.tag_id
+                 WHERE t.name IN ({})
+                 GROUP BY d.id HAVING COUNT(DISTINCT t.id) = {}
+                 ORDER BY d.indexed_at DESC, d.id DESC",
+                placeholders,
+                tags.len()
+            ))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(tags), |row| row.get(0))?;
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+    }
+
+    /// Tags of a document, sorted by name.
+    pub fn get_document_tags(&self, document_id: i64) -> Result<Vec<String>> {
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.name FROM document_tags dt JOIN tags t ON t.id = dt.tag_id
+                 WHERE dt.document_id = ?1 ORDER BY t.name",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![document_id], |row| row.get(0))?;
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+    }
+
     /// Get total document and chunk counts.
     pub fn get_stats(&self) -> Result<DbStats> {
-        self.with_conn(|conn| {
+        self.with_read_conn(|conn| {
             let doc_count: i64 =
                 conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))?;
             let chunk_count: i64 =
@@ -372,7 +864,7 @@ impl Database {
 
     /// Get recently indexed documents, ordered by indexed_at descending.
     pub fn get_recent_documents(&self, limit: usize) -> Result<Vec<RecentDocument>> {
-        self.with_conn(|conn| {
+        self.with_read_conn(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT path, filename, extension, size_bytes, indexed_at \
                  FROM documents ORDER BY indexed_at DESC LIMIT ?1",
@@ -394,6 +886,63 @@ impl Database {
         })
     }
 
+    /// List indexed documents ordered by ID, with their chunk counts.
+    pub fn list_documents(&self, offset: usize, limit: usize) -> Result<Vec<DocumentSummary>> {
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT d.id, d.path, d.filename, d.extension, d.size_bytes, d.indexed_at, \
+                 (SELECT COUNT(*) FROM chunks c WHERE c.document_id = d.id) \
+                 FROM documents d ORDER BY d.id LIMIT ?1 OFFSET ?2",
+            )?;
+            let rows = stmt.query_map(
+                rusqlite::params![limit as i64, offset as i64],
+                DocumentSummary::from_row,
+            )?;
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+    }
+
+    /// Get a single document summary by ID.
+    pub fn get_document(&self, document_id: i64) -> Result<Option<DocumentSummary>> {
+        self.with_read_conn(|conn| {
+            let result = conn.query_row(
+                "SELECT d.id, d.path, d.filename, d.extension, d.size_bytes, d.indexed_at, \
+                 (SELECT COUNT(*) FROM chunks c WHERE c.document_id = d.id) \
+                 FROM documents d WHERE d.id = ?1",
+                rusqlite::params![document_id],
+                DocumentSummary::from_row,
+            );
+            // Distinguish "no rows" from real errors
+            match result {
+                Ok(v) => Ok(Some(v)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    /// Get all chunks of a document as (chunk_index, content), in order.
+    pub fn get_document_chunks(&self, document_id: i64) -> Result<Vec<(i32, String)>> {
+        self.with_read_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT chunk_index, content FROM chunks \
+                 WHERE document_id = ?1 ORDER BY chunk_index",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![document_id], |row| {
+                Ok((row.get::<_, i32>(0)?, row.get::<_, String>(1)?))
+            })?;
+            let mut results = Vec::new();
+            for row in rows {
+                results.push(row?);
+            }
+            Ok(results)
+        })
+    }
+
     // --- Vector operations (sqlite-vec) ---
 
     /// Insert an embedding vector for a chunk with partition key and metadata.
@@ -407,7 +956,7 @@ impl Database {
         extension: Option<&str>,
         embedding: &[f32],
     ) -> Result<()> {
-        if !self.vec_enabled {
+        if !self.is_vec_enabled() {
             return Err(GhostError::Search("sqlite-vec not loaded".into()));
         }
         self.with_conn(|conn| {
@@ -425,7 +974,7 @@ impl Database {
 
     /// Delete all embeddings for chunks belonging to a document.
     pub fn delete_embeddings_for_document(&self, document_id: i64) -> Result<()> {
-        if !self.vec_enabled {
+        if !self.is_vec_enabled() {
             return Ok(());
         }
         self.with_conn(|conn| {
@@ -433,6 +982,7 @@ impl Database {
                 "DELETE FROM chunks_vec WHERE chunk_id IN (SELECT id FROM chunks WHERE document_id = ?1)",
                 rusqlite::params![document_id],
             )?;
+            vec_migration::delete_shadow_embeddings(conn, document_id)?;
             Ok(())
         })
     }
@@ -454,21 +1004,33 @@ impl Database {
         limit: usize,
         extension_filter: Option<&str>,
     ) -> Result<Vec<(i64, f64)>> {
-        if !self.vec_enabled {
+        self.knn_search("chunks_vec", query_embedding, limit, extension_filter)
+    }
+
+    /// KNN search over a named vec0 table (the live index or a migration shadow).
+    fn knn_search(
+        &self,
+        table: &str,
+        query_embedding: &[f32],
+        limit: usize,
+        extension_filter: Option<&str>,
+    ) -> Result<Vec<(i64, f64)>> {
+        if !self.is_vec_enabled() {
             return Ok(vec![]);
         }
-        self.with_conn(|conn| {
+        self.with_read_conn(|conn| {
             let blob = query_embedding
                 .iter()
                 .flat_map(|f| f.to_le_bytes())
                 .collect::<Vec<u8>>();
 
             if let Some(ext) = extension_filter {
-                let mut stmt = conn.prepare(
-                    "SELECT chunk_id, distance FROM chunks_vec \
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT chunk_id, distance FROM {} \
                      WHERE embedding MATCH ?1 AND k = ?2 AND extension = ?3 \
                      ORDER BY distance",
-                )?;
+                    table
+                ))?;
                 let rows = stmt.query_map(rusqlite::params![blob, limit as i64, ext], |row| {
                     Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?))
                 })?;
@@ -478,10 +1040,11 @@ impl Database {
                 }
                 Ok(results)
             } else {
-                let mut stmt = conn.prepare(
-                    "SELECT chunk_id, distance FROM chunks_vec \
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT chunk_id, distance FROM {} \
                      WHERE embedding MATCH ?1 ORDER BY distance LIMIT ?2",
-                )?;
+                    table
+                ))?;
                 let rows = stmt.query_map(rusqlite::params![blob, limit as i64], |row| {
                     Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?))
                 })?;
@@ -504,6 +1067,26 @@ pub struct ChunkWithDocument {
     pub path: String,
     pub filename: String,
     pub extension: Option<String>,
+    /// Section title (e-book chapter) the chunk belongs to.
+    pub section: Option<String>,
+    /// Code symbol the chunk defines.
+    pub symbol: Option<String>,
+    /// Detected language (ISO 639-1).
+    pub language: Option<String>,
+}
+
+/// A tag and how many documents carry it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub document_count: i64,
+}
+
+/// A detected language and how many chunks are written in it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub chunk_count: i64,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -523,10 +1106,93 @@ pub struct RecentDocument {
     pub indexed_at: String,
 }
 
+/// An indexed document with its chunk count.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DocumentSummary {
+    pub id: i64,
+    pub path: String,
+    pub filename: String,
+    pub extension: Option<String>,
+    pub size_bytes: i64,
+    pub indexed_at: String,
+    pub chunk_count: i64,
+}
+
+impl DocumentSummary {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            filename: row.get(2)?,
+            extension: row.get(3)?,
+            size_bytes: row.get(4)?,
+            indexed_at: row.get(5)?,
+            chunk_count: row.get(6)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_readers_run_alongside_writer() {
+        let path = std::env::temp_dir().join(format!("ghost_test_pool_{}.db", std::process::id()));
+        let db = Database::open(&path).unwrap();
+        assert_eq!(db.readers.len(), READ_POOL_SIZE);
+
+        db.with_transaction(|conn| {
+            conn.execute(
+                "INSERT INTO documents (path, filename, size_bytes, hash, modified_at) \
+                 VALUES ('/a.txt', 'a.txt', 1, 'h', '2026-02-18T00:00:00Z')",
+                [],
+            )?;
+            // The writer is held mid-transaction; reads see the committed state
+            assert_eq!(db.get_stats()?.document_count, 0);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(db.get_stats().unwrap().document_count, 1);
+
+        // Readers can't write
+        assert!(db
+            .with_read_conn(|conn| Ok(conn.execute("DELETE FROM documents", [])?))
+            .is_err());
+
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+        }
+    }
+
+    #[test]
+    fn test_replace_with_switches_files() {
+        let dir = std::env::temp_dir();
+        let first = dir.join(format!("ghost_test_swap_a_{}.db", std::process::id()));
+        let second = dir.join(format!("ghost_test_swap_b_{}.db", std::process::id()));
+        let db = Database::open(&first).unwrap();
+        db.upsert_document("/a.txt", "a.txt", None, 1, "h", "2026-02-18T00:00:00Z")
+            .unwrap();
+
+        db.replace_with(Database::open(&second).unwrap()).unwrap();
+        assert_eq!(db.path(), Some(second.clone()));
+        assert_eq!(db.get_stats().unwrap().document_count, 0);
+        assert!(db
+            .replace_with(Database::open_in_memory().unwrap())
+            .is_err());
+
+        db.replace_with(Database::open(&first).unwrap()).unwrap();
+        assert_eq!(db.get_stats().unwrap().document_count, 1);
+
+        drop(db);
+        for path in [&first, &second] {
+            for suffix in ["", "-wal", "-shm"] {
+                std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+            }
+        }
+    }
+
     #[test]
     fn test_upsert_and_search() {
         let db = Database::open_in_memory().unwrap();
@@ -555,4 +1221,227 @@ mod tests {
         let stats = db.get_stats().unwrap();
         assert_eq!(stats.document_count, 0);
     }
+
+    #[test]
+    fn test_list_documents_and_chunks() {
+        let db = Database::open_in_memory().unwrap();
+        let doc_id = db
+            .upsert_document(
+                "/test/notes.md",
+                "notes.md",
+                Some("md"),
+                42,
+                "def456",
+                "2026-02-18T00:00:00Z",
+            )
+            .unwrap();
+        db.insert_chunk(doc_id, 1, "second part", 2).unwrap();
+        db.insert_chunk(doc_id, 0, "first part", 2).unwrap();
+
+        let docs = db.list_documents(0, 10).unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].chunk_count, 2);
+        assert!(db.list_documents(1, 10).unwrap().is_empty());
+
+        let doc = db.get_document(doc_id).unwrap().unwrap();
+        assert_eq!(doc.filename, "notes.md");
+        assert!(db.get_document(doc_id + 100).unwrap().is_none());
+
+        let chunks = db.get_document_chunks(doc_id).unwrap();
+        assert_eq!(chunks[0], (0, "first part".to_string()));
+        assert_eq!(chunks[1].0, 1);
+    }
+
+    #[test]
+    fn test_document_tags() {
+        assert_eq!(normalize_tag("  Tax   Return "), Some("tax return".into()));
+        assert_eq!(normalize_tag(" "), None);
+        assert_eq!(normalize_tag(&"x".repeat(65)), None);
+
+        let db = Database::open_in_memory().unwrap();
+        let doc_id = db
+            .upsert_document("/test/a.pdf", "a.pdf", Some("pdf"), 1, "h", "2026-01-01")
+            .unwrap();
+        db.add_document_tag(doc_id, "Invoice", "plugin:invoices")
+            .unwrap();
+        db.add_document_tag(doc_id, "invoice", "user").unwrap();
+        db.add_document_tag(doc_id, "2024", "plugin:invoices")
+            .unwrap();
+        assert!(db.add_document_tag(doc_id, "", "user").is_err());
+        assert_eq!(
+            db.get_document_tags(doc_id).unwrap(),
+            vec!["2024", "invoice"]
+        );
+
+        db.clear_document_tags(doc_id, "plugin:invoices").unwrap();
+        assert!(db.get_document_tags(doc_id).unwrap().is_empty());
+
+        // Tags follow their document
+        db.add_document_tag(doc_id, "keep", "user").unwrap();
+        db.delete_document(doc_id).unwrap();
+        assert!(db.get_document_tags(doc_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tag_collections() {
+        let db = Database::open_in_memory().unwrap();
+        let a = db
+            .upsert_document("/a.pdf", "a.pdf", Some("pdf"), 1, "ha", "2026-01-01")
+            .unwrap();
+        let b = db
+            .upsert_document("/b.pdf", "b.pdf", Some("pdf"), 1, "hb", "2026-01-01")
+            .unwrap();
+        db.add_document_tag(a, "Taxes", "user").unwrap();
+        db.add_document_tag(a, "2024", "user").unwrap();
+        db.add_document_tag(b, "taxes", "plugin:invoices").unwrap();
+
+        let tags: Vec<(String, i64)> = db
+            .list_tags()
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.name, t.document_count))
+            .collect();
+        assert_eq!(tags, vec![("2024".into(), 1), ("taxes".into(), 2)]);
+
+        let mut taxes = db.filter_documents_by_tags(&["taxes".into()]).unwrap();
+        taxes.sort();
+        assert_eq!(taxes, vec![a, b]);
+        assert_eq!(
+            db.filter_documents_by_tags(&["taxes".into(), "2024".into()])
+                .unwrap(),
+            vec![a]
+        );
+        assert!(db.filter_documents_by_tags(&[]).unwrap().is_empty());
+
+        // Removing works whoever added the tag; unused tags disappear
+        assert!(db.remove_document_tag(b, "TAXES").unwrap());
+        assert!(!db.remove_document_tag(b, "taxes").unwrap());
+        assert!(db.remove_document_tag(a, "2024").unwrap());
+        let names: Vec<String> = db
+            .list_tags()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["taxes"]);
+    }
+
+    #[test]
+    fn test_filter_documents_by_email() {
+        let db = Database::open_in_memory().unwrap();
+        let mut ids = Vec::new();
+        for (i, (sender, date)) in [
+            ("Alice <alice@example.com>", "2024-03-05T17:30:00Z"),
+            ("Bob <bob@example.com>", "2023-12-04T09:15:00Z"),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let id = db
+                .upsert_document(
+                    &format!("/mail/{}.eml", i),
+                    &format!("{}.eml", i),
+                    Some("eml"),
+                    10,
+                    &format!("hash{}", i),
+                    "2026-02-18T00:00:00Z",
+                )
+                .unwrap();
+            db.set_email_metadata(id, Some(sender), Some(date)).unwrap();
+            ids.push(id);
+        }
+        // Non-email documents never match email filters
+        db.upsert_document(
+            "/a.txt",
+            "a.txt",
+            Some("txt"),
+            1,
+            "h",
+            "2026-02-18T00:00:00Z",
+        )
+        .unwrap();
+
+        assert_eq!(
+            db.filter_documents_by_email(Some("ALICE"), None).unwrap(),
+            vec![ids[0]]
+        );
+        assert_eq!(
+            db.filter_documents_by_email(None, Some("2024")).unwrap(),
+            vec![ids[1]]
+        );
+        assert_eq!(
+            db.filter_documents_by_email(None, Some("2024-03-06"))
+                .unwrap(),
+            ids
+        );
+        assert!(db
+            .filter_documents_by_email(Some("bob"), Some("2023-12-04"))
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(db.first_chunk_id(ids[0]).unwrap(), None);
+        let chunk_id = db.insert_chunk(ids[0], 0, "hello", 1).unwrap();
+        assert_eq!(db.first_chunk_id(ids[0]).unwrap(), Some(chunk_id));
+    }
+
+    #[test]
+    fn test_filter_documents_by_event_date() {
+        use crate::indexer::extractor::EventTime;
+        let db = Database::open_in_memory().unwrap();
+        let event = |start: &str, end: &str| EventTime {
+            starts_at: start.into(),
+            ends_at: end.into(),
+        };
+        let work = db
+            .upsert_document(
+                "/cal/work.ics",
+                "work.ics",
+                Some("ics"),
+                1,
+                "h1",
+                "2026-01-01",
+            )
+            .unwrap();
+        db.set_document_events(
+            work,
+            &[
+                event("2025-03-12T10:00:00", "2025-03-12T10:59:59"),
+                event("2025-04-02T09:00:00", "2025-04-02T09:29:59"),
+            ],
+        )
+        .unwrap();
+        let trip = db
+            .upsert_document(
+                "/cal/trip.ics",
+                "trip.ics",
+                Some("ics"),
+                1,
+                "h2",
+                "2026-01-01",
+            )
+            .unwrap();
+        db.set_document_events(trip, &[event("2025-03-10T00:00:00", "2025-03-14T23:59:59")])
+            .unwrap();
+
+        // Multi-day events match every day they span
+        assert_eq!(
+            db.filter_documents_by_event_date("2025-03-12").unwrap(),
+            vec![trip, work]
+        );
+        assert_eq!(
+            db.filter_documents_by_event_date("2025-04").unwrap(),
+            vec![work]
+        );
+        assert!(db
+            .filter_documents_by_event_date("2025-03-15")
+            .unwrap()
+            .is_empty());
+
+        // Re-indexing replaces the events
+        db.set_document_events(work, &[]).unwrap();
+        assert!(db
+            .filter_documents_by_event_date("2025-04")
+            .unwrap()
+            .is_empty());
+    }
 }
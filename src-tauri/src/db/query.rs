@@ -0,0 +1,272 @@
+//! Read-only SQL console over the vault database.
+//!
+//! Lets power users (and the agent) run ad-hoc analytics over documents,
+//! chunks, and conversations. Guards:
+//! - Only a single `SELECT` / `WITH` statement is accepted
+//! - SQLite must report the prepared statement as read-only
+//! - Results are capped at [`MAX_ROWS`] rows
+//! - Queries are interrupted after [`QUERY_TIMEOUT`]
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use rusqlite::types::ValueRef;
+use serde::Serialize;
+
+use super::Database;
+use crate::error::{GhostError, Result};
+
+/// Default number of rows returned when the caller doesn't specify a limit.
+pub const DEFAULT_ROWS: usize = 200;
+
+/// Hard cap on returned rows.
+pub const MAX_ROWS: usize = 1000;
+
+/// Queries running longer than this are interrupted.
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest text value returned per cell (longer values are truncated).
+const MAX_CELL_CHARS: usize = 2000;
+
+/// Result of a read-only query.
+#[derive(Debug, Clone, Serialize)]
+pub struct SqlQueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// True if more rows were available than returned.
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// Validate that a query starts with SELECT/WITH. Returns the query with
+/// trailing semicolons removed. A second statement is rejected when the
+/// query is prepared, since `;` may also appear inside string literals.
+fn validate(query: &str) -> Result<&str> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    if query.is_empty() {
+        return Err(GhostError::InvalidQuery("Query is empty".into()));
+    }
+    let first_word = query
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if first_word != "select" && first_word != "with" {
+        return Err(GhostError::InvalidQuery(
+            "Only SELECT (or WITH ... SELECT) queries are allowed".into(),
+        ));
+    }
+    Ok(query)
+}
+
+/// Convert a SQLite value into JSON for the frontend / agent.
+fn to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => {
+            let text = String::from_utf8_lossy(t);
+            if text.chars().count() > MAX_CELL_CHARS {
+                let cut: String = text.chars().take(MAX_CELL_CHARS).collect();
+                format!("{}…", cut).into()
+            } else {
+                text.into_owned().into()
+            }
+        }
+        ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()).into(),
+    }
+}
+
+/// Run a read-only query against the vault.
+pub fn run_readonly_sql(
+    db: &Database,
+    query: &str,
+    max_rows: Option<usize>,
+) -> Result<SqlQueryResult> {
+    let query = validate(query)?;
+    let max_rows = max_rows.unwrap_or(DEFAULT_ROWS).clamp(1, MAX_ROWS);
+
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(query).map_err(|e| match e {
+            rusqlite::Error::MultipleStatement => {
+                GhostError::InvalidQuery("Only a single statement is allowed".into())
+            }
+            e => GhostError::Database(e),
+        })?;
+        if !stmt.readonly() {
+            return Err(GhostError::InvalidQuery(
+                "Query would modify the database".into(),
+            ));
+        }
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+        // Watchdog: interrupt the query if it runs past the timeout.
+        let interrupt = conn.get_interrupt_handle();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let watchdog = std::thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = done_rx.recv_timeout(QUERY_TIMEOUT) {
+                interrupt.interrupt();
+            }
+        });
+
+        let start = Instant::now();
+        let collected = (|| -> rusqlite::Result<(Vec<Vec<serde_json::Value>>, bool)> {
+            let mut rows = stmt.query([])?;
+            let mut out = Vec::new();
+            while let Some(row) = rows.next()? {
+                if out.len() == max_rows {
+                    return Ok((out, true));
+                }
+                let mut values = Vec::with_capacity(columns.len());
+                for i in 0..columns.len() {
+                    values.push(to_json(row.get_ref(i)?));
+                }
+                out.push(values);
+            }
+            Ok((out, false))
+        })();
+
+        let _ = done_tx.send(());
+        let _ = watchdog.join();
+
+        let (rows, truncated) = collected.map_err(|e| match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::OperationInterrupted) => GhostError::InvalidQuery(format!(
+                "Query exceeded the {}s time limit",
+                QUERY_TIMEOUT.as_secs()
+            )),
+            _ => GhostError::Database(e),
+        })?;
+
+        Ok(SqlQueryResult {
+            columns,
+            rows,
+            truncated,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        })
+    })
+}
+
+/// Render a query result as a compact text table (for the agent).
+pub fn format_result(result: &SqlQueryResult) -> String {
+    if result.rows.is_empty() {
+        return format!("No rows. Columns: {}", result.columns.join(", "));
+    }
+    let mut out = result.columns.join(" | ");
+    for row in &result.rows {
+        out.push('\n');
+        let cells: Vec<String> = row
+            .iter()
+            .map(|v| match v {
+                serde_json::Value::String(s) => s.replace('\n', " "),
+                other => other.to_string(),
+            })
+            .collect();
+        out.push_str(&cells.join(" | "));
+    }
+    if result.truncated {
+        out.push_str(&format!(
+            "\n[Truncated to {} rows — add a LIMIT or aggregate]",
+            result.rows.len()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        let doc = db
+            .upsert_document(
+                "/test/a.txt",
+                "a.txt",
+                Some("txt"),
+                10,
+                "h1",
+                "2026-02-18T00:00:00Z",
+            )
+            .unwrap();
+        db.insert_chunk(doc, 0, "alpha", 1).unwrap();
+        db.insert_chunk(doc, 1, "beta", 1).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_select_returns_rows() {
+        let db = test_db();
+        let result = run_readonly_sql(
+            &db,
+            "SELECT chunk_index, content FROM chunks ORDER BY chunk_index;",
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["chunk_index", "content"]);
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[0][1], "alpha");
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn test_row_limit() {
+        let db = test_db();
+        let result = run_readonly_sql(&db, "SELECT * FROM chunks", Some(1)).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn test_rejects_writes() {
+        let db = test_db();
+        assert!(run_readonly_sql(&db, "DELETE FROM chunks", None).is_err());
+        assert!(run_readonly_sql(&db, "PRAGMA journal_mode=DELETE", None).is_err());
+        assert!(run_readonly_sql(
+            &db,
+            "WITH x AS (SELECT 1) DELETE FROM chunks WHERE id IN (SELECT * FROM x)",
+            None
+        )
+        .is_err());
+        assert!(matches!(
+            run_readonly_sql(&db, "SELECT 1; DELETE FROM chunks", None),
+            Err(GhostError::InvalidQuery(_))
+        ));
+        assert!(run_readonly_sql(&db, "   ", None).is_err());
+        // Nothing was deleted
+        let result = run_readonly_sql(&db, "SELECT COUNT(*) FROM chunks", None).unwrap();
+        assert_eq!(result.rows[0][0], 2);
+    }
+
+    #[test]
+    fn test_semicolon_in_string_literal() {
+        let db = test_db();
+        let result = run_readonly_sql(
+            &db,
+            "SELECT 'a;b' AS s, COUNT(*) FROM chunks WHERE content LIKE '%;%';",
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.rows[0][0], "a;b");
+    }
+
+    #[test]
+    fn test_with_select_allowed() {
+        let db = test_db();
+        let result = run_readonly_sql(
+            &db,
+            "WITH c AS (SELECT content FROM chunks) SELECT * FROM c",
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_format_result() {
+        let db = test_db();
+        let result =
+            run_readonly_sql(&db, "SELECT content FROM chunks ORDER BY chunk_index", None).unwrap();
+        assert_eq!(format_result(&result), "content\nalpha\nbeta");
+    }
+}
@@ -2,6 +2,10 @@ use rusqlite::Connection;
 
 use crate::error::Result;
 
+/// Vault schema version, stored in `PRAGMA user_version`.
+/// Bump when a migration changes the table layout.
+pub const SCHEMA_VERSION: i64 = 11;
+
 /// Initialize the database schema with all required tables.
 pub fn initialize_schema(conn: &Connection) -> Result<()> {
     conn.execute_batch(
@@ -15,7 +19,10 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
             size_bytes INTEGER,
             hash TEXT NOT NULL,
             indexed_at TEXT NOT NULL DEFAULT (datetime('now')),
-            modified_at TEXT NOT NULL
+            modified_at TEXT NOT NULL,
+            -- Email metadata (.eml/.mbox): sender and ISO 8601 send date
+            email_from TEXT,
+            email_date TEXT
         );
 
         -- Chunks table: document split into embeddable pieces
@@ -26,17 +33,26 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
             content TEXT NOT NULL,
             token_count INTEGER,
             has_embedding INTEGER NOT NULL DEFAULT 0,
+            -- Section title (e-book chapter) the chunk belongs to
+            section TEXT,
+            -- Code symbol defined in the chunk (function, class, impl)
+            symbol TEXT,
+            -- Detected language, ISO 639-1 (version 9)
+            language TEXT,
             UNIQUE(document_id, chunk_index)
         );
 
-        -- FTS5 virtual table for keyword search
+        -- FTS5 virtual table for keyword search (diacritics folded since version 9)
         CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
             content,
             content=chunks,
             content_rowid=id,
-            tokenize='porter unicode61'
+            tokenize='porter unicode61 remove_diacritics 2'
         );
 
+        -- Indexed terms, for typo-tolerant search (version 7)
+        CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts_vocab USING fts5vocab(chunks_fts, 'row');
+
         -- Triggers to keep FTS5 in sync with chunks table
         CREATE TRIGGER IF NOT EXISTS chunks_ai AFTER INSERT ON chunks BEGIN
             INSERT INTO chunks_fts(rowid, content) VALUES (new.id, new.content);
@@ -51,35 +67,131 @@ pub fn initialize_schema(conn: &Connection) -> Result<()> {
             INSERT INTO chunks_fts(rowid, content) VALUES (new.id, new.content);
         END;
 
+        -- Re-embedding migration state (at most one row while a migration runs)
+        CREATE TABLE IF NOT EXISTS embedding_migration (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            target_backend TEXT NOT NULL,
+            target_model TEXT,
+            dimensions INTEGER NOT NULL,
+            cursor INTEGER NOT NULL DEFAULT 0,
+            migrated INTEGER NOT NULL DEFAULT 0,
+            paused INTEGER NOT NULL DEFAULT 0,
+            started_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Tags: assigned by the user or by WASM plugins (version 5)
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE
+        );
+
+        CREATE TABLE IF NOT EXISTS document_tags (
+            document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            -- Who added the tag: 'user' or 'plugin:<id>'
+            source TEXT NOT NULL DEFAULT 'user',
+            PRIMARY KEY (document_id, tag_id)
+        );
+
+        -- Documents opened from search results, a ranking signal (version 8)
+        CREATE TABLE IF NOT EXISTS result_opens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            chunk_id INTEGER,
+            query TEXT,
+            opened_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Calendar event times (.ics), local wall-clock, end inclusive (version 10)
+        CREATE TABLE IF NOT EXISTS document_events (
+            document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+            starts_at TEXT NOT NULL,
+            ends_at TEXT NOT NULL
+        );
+
         -- Indexes for performance
         CREATE INDEX IF NOT EXISTS idx_documents_path ON documents(path);
         CREATE INDEX IF NOT EXISTS idx_documents_hash ON documents(hash);
         CREATE INDEX IF NOT EXISTS idx_chunks_document_id ON chunks(document_id);
         CREATE INDEX IF NOT EXISTS idx_chunks_has_embedding ON chunks(has_embedding);
-
-        -- Enable WAL mode for concurrent reads
-        PRAGMA journal_mode=WAL;
-        PRAGMA foreign_keys=ON;
-
-        -- Performance PRAGMAs (2-5x speedup for reads/writes)
-        -- synchronous=NORMAL is safe with WAL mode (data survives process crash, not power loss)
-        PRAGMA synchronous=NORMAL;
-        -- 16MB page cache (vs default 2MB) — keeps hot pages in memory
-        PRAGMA cache_size=-16000;
-        -- 256MB mmap for read-heavy workloads — OS page cache handles eviction
-        PRAGMA mmap_size=268435456;
-        -- Keep temp tables and indices in RAM (faster sorts, GROUP BY, etc.)
-        PRAGMA temp_store=MEMORY;
-        -- 5s busy timeout to avoid SQLITE_BUSY under concurrent access
-        PRAGMA busy_timeout=5000;
-        -- 4KB page size matches OS page size for optimal IO
-        PRAGMA page_size=4096;
+        CREATE INDEX IF NOT EXISTS idx_document_tags_tag_id ON document_tags(tag_id);
+        CREATE INDEX IF NOT EXISTS idx_result_opens_document_id ON result_opens(document_id);
+        CREATE INDEX IF NOT EXISTS idx_document_events_document_id ON document_events(document_id);
+        CREATE INDEX IF NOT EXISTS idx_document_events_starts_at ON document_events(starts_at);
         ",
     )?;
 
+    migrate_added_columns(conn)?;
+    migrate_fts_tokenizer(conn)?;
+
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    Ok(())
+}
+
+/// Columns added after the first release: email metadata (version 3),
+/// chunk section titles (version 4), code symbols (version 6), chunk
+/// languages (version 9), and the re-embedding target model (version 11).
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("documents", "email_from"),
+    ("documents", "email_date"),
+    ("chunks", "section"),
+    ("chunks", "symbol"),
+    ("chunks", "language"),
+    ("embedding_migration", "target_model"),
+];
+
+/// Add columns missing from vaults created by older schema versions.
+fn migrate_added_columns(conn: &Connection) -> Result<()> {
+    for (table, column) in ADDED_COLUMNS {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+            [table, column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} TEXT", table, column))?;
+        }
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_documents_email_date ON documents(email_date);",
+    )?;
+    Ok(())
+}
+
+/// Rebuild the keyword index of older vaults with diacritic folding, so
+/// "cancion" matches "canción" (version 9). FTS5 tokenizers are fixed at
+/// creation, so the table is recreated and refilled from `chunks`.
+fn migrate_fts_tokenizer(conn: &Connection) -> Result<()> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE name = 'chunks_fts'",
+        [],
+        |row| row.get(0),
+    )?;
+    if sql.contains("remove_diacritics") {
+        return Ok(());
+    }
+    tracing::info!("Rebuilding keyword index with diacritic folding");
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS chunks_fts_vocab;
+         DROP TABLE chunks_fts;
+         CREATE VIRTUAL TABLE chunks_fts USING fts5(
+             content,
+             content=chunks,
+             content_rowid=id,
+             tokenize='porter unicode61 remove_diacritics 2'
+         );
+         CREATE VIRTUAL TABLE chunks_fts_vocab USING fts5vocab(chunks_fts, 'row');
+         INSERT INTO chunks_fts(chunks_fts) VALUES('rebuild');",
+    )?;
     Ok(())
 }
 
+/// Read the schema version recorded in the vault.
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
 /// Initialize the sqlite-vec virtual table for vector search.
 /// Must be called AFTER loading the sqlite-vec extension.
 /// Dimensions default to 384 (all-MiniLM-L6-v2) for native engine,
@@ -100,21 +212,26 @@ pub fn initialize_vec_table(conn: &Connection) -> Result<()> {
 pub fn initialize_vec_table_with_dims(conn: &Connection, dimensions: usize) -> Result<()> {
     // Check if we need to migrate from old schema (no partition key)
     migrate_vec_table_if_needed(conn)?;
+    create_vec_table(conn, "chunks_vec", dimensions)?;
+    tracing::info!(
+        "sqlite-vec chunks_vec table initialized ({}D, partition_key=document_id, metadata=extension)",
+        dimensions
+    );
+    Ok(())
+}
 
+/// Create a vec0 table with the standard chunk layout under the given name.
+pub fn create_vec_table(conn: &Connection, name: &str, dimensions: usize) -> Result<()> {
     let sql = format!(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS chunks_vec USING vec0(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS {} USING vec0(
             chunk_id INTEGER PRIMARY KEY,
             document_id INTEGER PARTITION KEY,
             extension TEXT,
             embedding FLOAT[{}]
         );",
-        dimensions
+        name, dimensions
     );
     conn.execute_batch(&sql)?;
-    tracing::info!(
-        "sqlite-vec chunks_vec table initialized ({}D, partition_key=document_id, metadata=extension)",
-        dimensions
-    );
     Ok(())
 }
 
@@ -193,4 +310,118 @@ mod tests {
             .unwrap();
         assert_eq!(count, 1);
     }
+
+    #[test]
+    fn test_email_columns_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Version 2 documents table, without email columns
+        conn.execute_batch(
+            "CREATE TABLE documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL UNIQUE,
+                filename TEXT NOT NULL,
+                extension TEXT,
+                size_bytes INTEGER,
+                hash TEXT NOT NULL,
+                indexed_at TEXT NOT NULL DEFAULT (datetime('now')),
+                modified_at TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute_batch("SELECT email_from, email_date FROM documents")
+            .unwrap();
+
+        // Running again on an up-to-date vault is a no-op
+        initialize_schema(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_section_column_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Version 3 chunks table, without the section and symbol columns
+        conn.execute_batch(
+            "CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id INTEGER NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                token_count INTEGER,
+                has_embedding INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(document_id, chunk_index)
+            );",
+        )
+        .unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute_batch("SELECT section, symbol FROM chunks")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fts_tokenizer_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Version 8 keyword index, without diacritic folding
+        conn.execute_batch(
+            "CREATE TABLE chunks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id INTEGER NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                token_count INTEGER,
+                has_embedding INTEGER NOT NULL DEFAULT 0,
+                UNIQUE(document_id, chunk_index)
+            );
+            CREATE VIRTUAL TABLE chunks_fts USING fts5(
+                content, content=chunks, content_rowid=id, tokenize='porter unicode61'
+            );
+            INSERT INTO chunks (document_id, chunk_index, content) VALUES (1, 0, 'la canción');
+            INSERT INTO chunks_fts(chunks_fts) VALUES('rebuild');",
+        )
+        .unwrap();
+        initialize_schema(&conn).unwrap();
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM chunks_fts WHERE chunks_fts MATCH 'cancion'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+        conn.execute_batch("SELECT language FROM chunks").unwrap();
+    }
+
+    #[test]
+    fn test_target_model_column_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Version 10 migration state, without the target model
+        conn.execute_batch(
+            "CREATE TABLE embedding_migration (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                target_backend TEXT NOT NULL,
+                dimensions INTEGER NOT NULL,
+                cursor INTEGER NOT NULL DEFAULT 0,
+                migrated INTEGER NOT NULL DEFAULT 0,
+                paused INTEGER NOT NULL DEFAULT 0,
+                started_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            INSERT INTO embedding_migration (id, target_backend, dimensions)
+                VALUES (1, 'ollama', 768);",
+        )
+        .unwrap();
+        initialize_schema(&conn).unwrap();
+        let model: Option<String> = conn
+            .query_row("SELECT target_model FROM embedding_migration", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert!(model.is_none());
+    }
+
+    #[test]
+    fn test_schema_version_recorded() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        initialize_schema(&conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
 }
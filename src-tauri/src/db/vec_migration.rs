@@ -0,0 +1,331 @@
+//! Vector index migration — re-embedding the vault with a different model.
+//!
+//! sqlite-vec tables have a fixed dimension, so switching embedding models
+//! means building a new index. The new embeddings go into a shadow table
+//! ([`SHADOW_TABLE`]) while the live `chunks_vec` keeps serving search.
+//! Chunks are migrated in ascending id order; the persisted cursor is the
+//! last migrated chunk id, which lets a migration resume after a restart and
+//! lets search read each chunk from exactly one of the two indexes.
+//!
+//! When every chunk is migrated, the shadow replaces `chunks_vec` in one transaction.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::Database;
+use crate::error::{GhostError, Result};
+
+/// Name of the vec0 table the migration writes to.
+pub const SHADOW_TABLE: &str = "chunks_vec_next";
+
+/// Persisted state of a running (or paused) migration.
+#[derive(Debug, Clone, Serialize)]
+pub struct VecMigration {
+    /// Backend producing the new embeddings ("native", "ollama").
+    pub target_backend: String,
+    /// Registry ID of the target model; unset for migrations started before
+    /// the model registry existed.
+    pub target_model: Option<String>,
+    pub dimensions: usize,
+    /// Id of the last migrated chunk.
+    pub cursor: i64,
+    /// Chunks migrated so far.
+    pub migrated: i64,
+    pub paused: bool,
+    pub started_at: String,
+}
+
+impl VecMigration {
+    /// The model ID to resolve the target from, falling back to the backend
+    /// name for older migrations.
+    pub fn target_id(&self) -> &str {
+        self.target_model.as_deref().unwrap_or(&self.target_backend)
+    }
+}
+
+/// A chunk to re-embed.
+#[derive(Debug, Clone)]
+pub struct PendingChunk {
+    pub chunk_id: i64,
+    pub document_id: i64,
+    pub extension: Option<String>,
+    pub content: String,
+}
+
+fn shadow_exists(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+        [SHADOW_TABLE],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Remove a document's rows from the shadow index (no-op without a migration).
+pub(super) fn delete_shadow_embeddings(conn: &Connection, document_id: i64) -> Result<()> {
+    if shadow_exists(conn)? {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE chunk_id IN (SELECT id FROM chunks WHERE document_id = ?1)",
+                SHADOW_TABLE
+            ),
+            rusqlite::params![document_id],
+        )?;
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Start a migration to a new embedding model.
+    pub fn begin_vec_migration(
+        &self,
+        target_backend: &str,
+        target_model: &str,
+        dimensions: usize,
+    ) -> Result<()> {
+        if !self.is_vec_enabled() {
+            return Err(GhostError::Search("sqlite-vec not loaded".into()));
+        }
+        self.with_transaction(|conn| {
+            let existing: i64 =
+                conn.query_row("SELECT COUNT(*) FROM embedding_migration", [], |row| {
+                    row.get(0)
+                })?;
+            if existing > 0 {
+                return Err(GhostError::Search(
+                    "A re-embedding migration is already in progress".into(),
+                ));
+            }
+            conn.execute_batch(&format!("DROP TABLE IF EXISTS {}", SHADOW_TABLE))?;
+            super::schema::create_vec_table(conn, SHADOW_TABLE, dimensions)?;
+            conn.execute(
+                "INSERT INTO embedding_migration (id, target_backend, target_model, dimensions)
+                 VALUES (1, ?1, ?2, ?3)",
+                rusqlite::params![target_backend, target_model, dimensions as i64],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Current migration, if one is running or paused.
+    pub fn get_vec_migration(&self) -> Result<Option<VecMigration>> {
+        self.with_conn(|conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT target_backend, target_model, dimensions, cursor, migrated, paused,
+                            started_at
+                     FROM embedding_migration WHERE id = 1",
+                    [],
+                    |row| {
+                        Ok(VecMigration {
+                            target_backend: row.get(0)?,
+                            target_model: row.get(1)?,
+                            dimensions: row.get::<_, i64>(2)? as usize,
+                            cursor: row.get(3)?,
+                            migrated: row.get(4)?,
+                            paused: row.get::<_, i64>(5)? != 0,
+                            started_at: row.get(6)?,
+                        })
+                    },
+                )
+                .optional()?)
+        })
+    }
+
+    /// Persist the paused flag.
+    pub fn set_vec_migration_paused(&self, paused: bool) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE embedding_migration SET paused = ?1 WHERE id = 1",
+                rusqlite::params![paused as i64],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Next batch of chunks after the cursor, in id order.
+    pub fn chunks_after(&self, cursor: i64, limit: usize) -> Result<Vec<PendingChunk>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT c.id, c.document_id, d.extension, c.content
+                 FROM chunks c JOIN documents d ON d.id = c.document_id
+                 WHERE c.id > ?1 ORDER BY c.id LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![cursor, limit as i64], |row| {
+                Ok(PendingChunk {
+                    chunk_id: row.get(0)?,
+                    document_id: row.get(1)?,
+                    extension: row.get(2)?,
+                    content: row.get(3)?,
+                })
+            })?;
+            let mut chunks = Vec::new();
+            for row in rows {
+                chunks.push(row?);
+            }
+            Ok(chunks)
+        })
+    }
+
+    /// Store a batch of new embeddings and advance the cursor atomically.
+    pub fn store_migrated_embeddings(
+        &self,
+        chunks: &[PendingChunk],
+        embeddings: &[Vec<f32>],
+    ) -> Result<()> {
+        let Some(cursor) = chunks.iter().map(|c| c.chunk_id).max() else {
+            return Ok(());
+        };
+        self.with_transaction(|conn| {
+            let sql = format!(
+                "INSERT OR REPLACE INTO {}(chunk_id, document_id, extension, embedding) VALUES (?1, ?2, ?3, ?4)",
+                SHADOW_TABLE
+            );
+            for (chunk, embedding) in chunks.iter().zip(embeddings) {
+                let blob = embedding
+                    .iter()
+                    .flat_map(|f| f.to_le_bytes())
+                    .collect::<Vec<u8>>();
+                conn.execute(
+                    &sql,
+                    rusqlite::params![chunk.chunk_id, chunk.document_id, chunk.extension, blob],
+                )?;
+            }
+            conn.execute(
+                "UPDATE embedding_migration SET cursor = ?1, migrated = migrated + ?2 WHERE id = 1",
+                rusqlite::params![cursor, chunks.len() as i64],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// KNN search over the migrated chunks (the shadow index).
+    pub fn vec_search_migrated(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        extension_filter: Option<&str>,
+    ) -> Result<Vec<(i64, f64)>> {
+        self.knn_search(SHADOW_TABLE, query_embedding, limit, extension_filter)
+    }
+
+    /// Replace the live index with the fully migrated shadow index.
+    pub fn finish_vec_migration(&self) -> Result<()> {
+        let migration = self
+            .get_vec_migration()?
+            .ok_or_else(|| GhostError::Search("No re-embedding migration in progress".into()))?;
+        self.with_transaction(|conn| {
+            conn.execute_batch("DROP TABLE IF EXISTS chunks_vec")?;
+            super::schema::create_vec_table(conn, "chunks_vec", migration.dimensions)?;
+            conn.execute_batch(&format!(
+                "INSERT INTO chunks_vec(chunk_id, document_id, extension, embedding)
+                     SELECT chunk_id, document_id, extension, embedding FROM {0}
+                     WHERE chunk_id IN (SELECT id FROM chunks);
+                 DROP TABLE {0};
+                 UPDATE chunks SET has_embedding = (id IN (SELECT chunk_id FROM chunks_vec));
+                 DELETE FROM embedding_migration;",
+                SHADOW_TABLE
+            ))?;
+            Ok(())
+        })
+    }
+
+    /// Abandon a migration, keeping the live index as-is.
+    pub fn cancel_vec_migration(&self) -> Result<()> {
+        self.with_transaction(|conn| {
+            conn.execute_batch(&format!(
+                "DROP TABLE IF EXISTS {};
+                 DELETE FROM embedding_migration;",
+                SHADOW_TABLE
+            ))?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_with_chunks(n: usize) -> Database {
+        let db = Database::open_in_memory().unwrap();
+        let doc = db
+            .upsert_document(
+                "/t/a.md",
+                "a.md",
+                Some("md"),
+                1,
+                "h",
+                "2026-01-01T00:00:00Z",
+            )
+            .unwrap();
+        for i in 0..n {
+            db.insert_chunk(doc, i as i32, &format!("chunk {}", i), 2)
+                .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn test_migration_lifecycle() {
+        let db = db_with_chunks(3);
+        if !db.is_vec_enabled() {
+            return;
+        }
+        db.begin_vec_migration("ollama", "nomic-embed-text", 4)
+            .unwrap();
+        assert!(db
+            .begin_vec_migration("ollama", "nomic-embed-text", 4)
+            .is_err());
+
+        let batch = db.chunks_after(0, 2).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].extension.as_deref(), Some("md"));
+        let embeddings = vec![vec![1.0, 0.0, 0.0, 0.0], vec![0.0, 1.0, 0.0, 0.0]];
+        db.store_migrated_embeddings(&batch, &embeddings).unwrap();
+
+        let state = db.get_vec_migration().unwrap().unwrap();
+        assert_eq!(state.target_model.as_deref(), Some("nomic-embed-text"));
+        assert_eq!(state.target_id(), "nomic-embed-text");
+        assert_eq!(state.migrated, 2);
+        assert_eq!(state.cursor, batch[1].chunk_id);
+        assert!(!state.paused);
+
+        let hits = db
+            .vec_search_migrated(&[1.0, 0.0, 0.0, 0.0], 5, None)
+            .unwrap();
+        assert_eq!(hits[0].0, batch[0].chunk_id);
+
+        let rest = db.chunks_after(state.cursor, 10).unwrap();
+        assert_eq!(rest.len(), 1);
+        db.store_migrated_embeddings(&rest, &[vec![0.0, 0.0, 1.0, 0.0]])
+            .unwrap();
+
+        db.finish_vec_migration().unwrap();
+        assert!(db.get_vec_migration().unwrap().is_none());
+        let stats = db.get_stats().unwrap();
+        assert_eq!(stats.embedded_chunk_count, 3);
+        // The live index now has the new dimensions
+        let hits = db.vec_search(&[0.0, 0.0, 1.0, 0.0], 1).unwrap();
+        assert_eq!(hits[0].0, rest[0].chunk_id);
+    }
+
+    #[test]
+    fn test_cancel_and_pause() {
+        let db = db_with_chunks(1);
+        if !db.is_vec_enabled() {
+            return;
+        }
+        db.begin_vec_migration("native", "all-minilm-l6-v2", 4)
+            .unwrap();
+        db.set_vec_migration_paused(true).unwrap();
+        assert!(db.get_vec_migration().unwrap().unwrap().paused);
+        db.cancel_vec_migration().unwrap();
+        assert!(db.get_vec_migration().unwrap().is_none());
+        db.with_conn(|conn| {
+            assert!(!shadow_exists(conn)?);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
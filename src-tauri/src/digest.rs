@@ -0,0 +1,462 @@
+//! "What's new in my files" digests.
+//!
+//! On a daily or weekly schedule (see [`DigestSettings`]) a background job
+//! collects the documents indexed since the previous digest, asks the local
+//! model for a short overview, and saves it as a conversation titled
+//! "Digest — <date>" followed by links to the files. The `digests` table
+//! records each run and the conversation it produced, so the next digest
+//! starts where this one ended. New digests are announced once through the
+//! OS notification center when `notify` is on.
+
+use chrono::{Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::agent::memory;
+use crate::chat::ChatMessage;
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+use crate::AppState;
+
+/// Documents listed in one digest; the rest are only counted.
+const MAX_DIGEST_DOCUMENTS: usize = 40;
+
+/// Characters of each document shown to the model.
+const MAX_EXCERPT_CHARS: usize = 300;
+
+/// Token budget of the overview.
+const DIGEST_MAX_TOKENS: usize = 400;
+
+/// Storage format of SQLite's `datetime('now')` (UTC).
+const SQL_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const DIGEST_PROMPT: &str = "You write a short digest of the files that are new or changed \
+on the user's computer. Group related files, say what each group is about, and point out \
+anything that looks important or time-sensitive. Refer to files by name. Use at most 6 \
+sentences or bullet points, and only facts from the list.";
+
+/// How often a digest is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestSchedule {
+    Off,
+    Daily,
+    Weekly,
+}
+
+/// Digest preferences. Off by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSettings {
+    #[serde(default = "default_schedule")]
+    pub schedule: DigestSchedule,
+    /// Local hour (0-23) from which the day's digest is written.
+    #[serde(default = "default_hour")]
+    pub hour: u32,
+    /// Announce new digests in the OS notification center.
+    #[serde(default = "default_notify")]
+    pub notify: bool,
+}
+
+fn default_schedule() -> DigestSchedule {
+    DigestSchedule::Off
+}
+fn default_hour() -> u32 {
+    8
+}
+fn default_notify() -> bool {
+    true
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            schedule: default_schedule(),
+            hour: default_hour(),
+            notify: default_notify(),
+        }
+    }
+}
+
+/// A digest run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub id: i64,
+    /// The conversation holding the digest; `None` when nothing was new.
+    pub conversation_id: Option<i64>,
+    pub title: String,
+    /// Documents indexed in `(since, until]` (UTC) are covered.
+    pub since: String,
+    pub until: String,
+    pub document_count: usize,
+    pub created_at: String,
+}
+
+/// A document indexed since the last digest.
+#[derive(Debug, Clone)]
+struct NewDocument {
+    id: i64,
+    path: String,
+    filename: String,
+}
+
+/// Initialize the digests table in the database.
+pub fn initialize_digest_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS digests (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                conversation_id INTEGER
+                    REFERENCES conversations(id) ON DELETE SET NULL,
+                title TEXT NOT NULL,
+                since TEXT NOT NULL,
+                until TEXT NOT NULL,
+                document_count INTEGER NOT NULL,
+                notified INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Whether the schedule calls for a digest now.
+pub fn is_due(db: &Database, settings: &DigestSettings) -> Result<bool> {
+    let last: Option<String> = db.with_read_conn(|conn| {
+        Ok(conn.query_row("SELECT MAX(created_at) FROM digests", [], |row| row.get(0))?)
+    })?;
+    let last = last
+        .and_then(|t| NaiveDateTime::parse_from_str(&t, SQL_FORMAT).ok())
+        .map(|t| {
+            Utc.from_utc_datetime(&t)
+                .with_timezone(&Local)
+                .naive_local()
+        });
+    Ok(due_at(settings, last, Local::now().naive_local()))
+}
+
+/// Whether a digest is due at local time `now`, the last one written at `last`.
+fn due_at(settings: &DigestSettings, last: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+    let days = match settings.schedule {
+        DigestSchedule::Off => return false,
+        DigestSchedule::Daily => 1,
+        DigestSchedule::Weekly => 7,
+    };
+    if now.hour() < settings.hour.min(23) {
+        return false;
+    }
+    match last {
+        Some(last) => (now.date() - last.date()).num_days() >= days,
+        None => true,
+    }
+}
+
+/// Write a digest of the documents indexed since the previous one (or, for
+/// the first digest, within the last schedule period).
+pub async fn generate(state: &AppState) -> Result<Digest> {
+    let settings = state
+        .settings
+        .lock()
+        .map_err(|e| GhostError::Agent(e.to_string()))?
+        .digest
+        .clone();
+    let until = Utc::now().naive_utc();
+    let since = match last_until(&state.db)? {
+        Some(since) => since,
+        None => {
+            let days = if settings.schedule == DigestSchedule::Weekly {
+                7
+            } else {
+                1
+            };
+            (until - chrono::Duration::days(days))
+                .format(SQL_FORMAT)
+                .to_string()
+        }
+    };
+    let until = until.format(SQL_FORMAT).to_string();
+    let (documents, total) = new_documents(&state.db, &since, &until)?;
+    let title = format!("Digest — {}", Local::now().format("%Y-%m-%d"));
+
+    if documents.is_empty() {
+        let id = record(&state.db, None, &title, &since, &until, 0, true)?;
+        return get_digest(&state.db, id);
+    }
+
+    let summaries = crate::agent::summarize::cached_summaries(
+        &state.db,
+        &documents.iter().map(|d| d.id).collect::<Vec<_>>(),
+    )?;
+    let mut listing = String::new();
+    for doc in &documents {
+        let excerpt = match summaries.get(&doc.id) {
+            Some(summary) => summary.clone(),
+            None => state
+                .db
+                .get_document_chunks(doc.id)?
+                .into_iter()
+                .next()
+                .map(|(_, content)| content)
+                .unwrap_or_default(),
+        };
+        let excerpt: String = excerpt
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(MAX_EXCERPT_CHARS)
+            .collect();
+        listing.push_str(&format!("- {} ({}): {}\n", doc.filename, doc.path, excerpt));
+    }
+    if total > documents.len() {
+        listing.push_str(&format!("...and {} more files.\n", total - documents.len()));
+    }
+
+    let prompt = [
+        ChatMessage {
+            role: "system".into(),
+            content: DIGEST_PROMPT.into(),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: format!("{} new or changed files:\n{}", total, listing),
+        },
+    ];
+    let response = state.chat_engine.chat(&prompt, DIGEST_MAX_TOKENS).await?;
+    let content = format!(
+        "{}\n\n{}",
+        response.content.trim(),
+        file_links(&documents, total)
+    );
+
+    let conversation_id = memory::create_conversation(&state.db, &title)?;
+    memory::add_message(
+        &state.db,
+        conversation_id,
+        "assistant",
+        &content,
+        None,
+        None,
+        Some(&response.model_id),
+    )?;
+    let id = record(
+        &state.db,
+        Some(conversation_id),
+        &title,
+        &since,
+        &until,
+        total,
+        false,
+    )?;
+    tracing::info!("Wrote digest {} ({} new documents)", id, total);
+    get_digest(&state.db, id)
+}
+
+/// Markdown list linking the digest's files.
+fn file_links(documents: &[NewDocument], total: usize) -> String {
+    let mut links = String::from("**Files**\n");
+    for doc in documents {
+        links.push_str(&format!("- [{}](<{}>)\n", doc.filename, doc.path));
+    }
+    if total > documents.len() {
+        links.push_str(&format!("- …and {} more\n", total - documents.len()));
+    }
+    links
+}
+
+/// Recent digests, newest first.
+pub fn list_digests(db: &Database, limit: usize) -> Result<Vec<Digest>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM digests ORDER BY id DESC LIMIT ?1",
+            DIGEST_COLUMNS
+        ))?;
+        let rows = stmt.query_map([limit as i64], from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// Digests with new files not announced yet; they are marked announced.
+pub fn take_unnotified(db: &Database) -> Result<Vec<Digest>> {
+    db.with_transaction(|conn| {
+        let digests = {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM digests WHERE notified = 0 ORDER BY id",
+                DIGEST_COLUMNS
+            ))?;
+            let rows = stmt.query_map([], from_row)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        conn.execute("UPDATE digests SET notified = 1 WHERE notified = 0", [])?;
+        Ok(digests)
+    })
+}
+
+const DIGEST_COLUMNS: &str = "id, conversation_id, title, since, until, document_count, created_at";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Digest> {
+    Ok(Digest {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        title: row.get(2)?,
+        since: row.get(3)?,
+        until: row.get(4)?,
+        document_count: row.get::<_, i64>(5)? as usize,
+        created_at: row.get(6)?,
+    })
+}
+
+fn get_digest(db: &Database, id: i64) -> Result<Digest> {
+    db.with_read_conn(|conn| {
+        Ok(conn.query_row(
+            &format!("SELECT {} FROM digests WHERE id = ?1", DIGEST_COLUMNS),
+            [id],
+            from_row,
+        )?)
+    })
+}
+
+fn last_until(db: &Database) -> Result<Option<String>> {
+    db.with_read_conn(|conn| {
+        Ok(conn.query_row("SELECT MAX(until) FROM digests", [], |row| row.get(0))?)
+    })
+}
+
+/// Documents indexed in `(since, until]`, newest first, and how many there are.
+fn new_documents(db: &Database, since: &str, until: &str) -> Result<(Vec<NewDocument>, usize)> {
+    db.with_read_conn(|conn| {
+        let total: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM documents WHERE indexed_at > ?1 AND indexed_at <= ?2",
+            [since, until],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(
+            "SELECT id, path, filename FROM documents
+             WHERE indexed_at > ?1 AND indexed_at <= ?2
+             ORDER BY indexed_at DESC LIMIT ?3",
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![since, until, MAX_DIGEST_DOCUMENTS as i64],
+            |row| {
+                Ok(NewDocument {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    filename: row.get(2)?,
+                })
+            },
+        )?;
+        Ok((rows.collect::<rusqlite::Result<Vec<_>>>()?, total as usize))
+    })
+}
+
+fn record(
+    db: &Database,
+    conversation_id: Option<i64>,
+    title: &str,
+    since: &str,
+    until: &str,
+    document_count: usize,
+    notified: bool,
+) -> Result<i64> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO digests (conversation_id, title, since, until, document_count, notified)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                conversation_id,
+                title,
+                since,
+                until,
+                document_count as i64,
+                notified
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, SQL_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_due_at() {
+        let daily = DigestSettings {
+            schedule: DigestSchedule::Daily,
+            hour: 8,
+            notify: true,
+        };
+        assert!(due_at(&daily, None, at("2026-03-02 09:00:00")));
+        // Not before the configured hour
+        assert!(!due_at(&daily, None, at("2026-03-02 07:59:00")));
+        assert!(!due_at(
+            &daily,
+            Some(at("2026-03-02 08:30:00")),
+            at("2026-03-02 20:00:00")
+        ));
+        assert!(due_at(
+            &daily,
+            Some(at("2026-03-01 23:00:00")),
+            at("2026-03-02 08:00:00")
+        ));
+
+        let weekly = DigestSettings {
+            schedule: DigestSchedule::Weekly,
+            ..daily.clone()
+        };
+        assert!(!due_at(
+            &weekly,
+            Some(at("2026-03-01 08:00:00")),
+            at("2026-03-07 09:00:00")
+        ));
+        assert!(due_at(
+            &weekly,
+            Some(at("2026-03-01 08:00:00")),
+            at("2026-03-08 09:00:00")
+        ));
+        assert!(!due_at(
+            &DigestSettings::default(),
+            None,
+            at("2026-03-02 09:00:00")
+        ));
+    }
+
+    #[test]
+    fn test_new_documents_and_notifications() {
+        let db = Database::open_in_memory().unwrap();
+        memory::initialize_memory_schema(&db).unwrap();
+        initialize_digest_schema(&db).unwrap();
+        db.upsert_document(
+            "/notes/plan.md",
+            "plan.md",
+            Some("md"),
+            10,
+            "h1",
+            "2026-01-01",
+        )
+        .unwrap();
+
+        let (docs, total) =
+            new_documents(&db, "2000-01-01 00:00:00", "2999-01-01 00:00:00").unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(docs[0].filename, "plan.md");
+        assert!(file_links(&docs, total).contains("[plan.md](</notes/plan.md>)"));
+        let (docs, _) = new_documents(&db, "2999-01-01 00:00:00", "2999-01-02 00:00:00").unwrap();
+        assert!(docs.is_empty());
+
+        let conversation = memory::create_conversation(&db, "Digest").unwrap();
+        record(&db, Some(conversation), "Digest", "a", "b", 1, false).unwrap();
+        record(&db, None, "Digest", "b", "c", 0, true).unwrap();
+        assert_eq!(last_until(&db).unwrap().as_deref(), Some("c"));
+        assert_eq!(list_digests(&db, 10).unwrap().len(), 2);
+
+        let announced = take_unnotified(&db).unwrap();
+        assert_eq!(announced.len(), 1);
+        assert_eq!(announced[0].conversation_id, Some(conversation));
+        assert!(take_unnotified(&db).unwrap().is_empty());
+    }
+}
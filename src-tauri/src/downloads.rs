@@ -0,0 +1,525 @@
+//! Download manager for models and runtimes.
+//!
+//! All large downloads (GGUF/embedding models from HuggingFace, Node/uv
+//! runtime archives) go through [`manager()`], which adds:
+//! - Resume: data is streamed into a partial file and continued with an HTTP
+//!   `Range` request after a crash, network drop, or app restart
+//! - Bandwidth cap: an optional KB/s limit shared by all running downloads
+//! - Metered-connection detection: downloads are refused on metered networks
+//!   unless the user allows it in Settings
+//! - A single list of active downloads for the UI (`list_active_downloads`)
+//!
+//! HuggingFace files are written in the hf-hub cache layout
+//! (`models--org--name/{blobs,snapshots,refs}`), so `hf_hub::api::sync::Api`
+//! finds them as cached afterwards.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// User-configurable download limits, persisted in Settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DownloadSettings {
+    /// Maximum download speed in KB/s shared by all downloads (0 = unlimited).
+    #[serde(default)]
+    pub bandwidth_limit_kbps: u64,
+    /// Allow downloads while on a metered connection (mobile hotspot, capped plans).
+    #[serde(default)]
+    pub allow_metered: bool,
+}
+
+/// What a download is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadKind {
+    Model,
+    Runtime,
+}
+
+/// A download in progress, for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveDownload {
+    /// Destination path (one download per destination).
+    pub id: String,
+    pub label: String,
+    pub kind: DownloadKind,
+    pub url: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    /// Bytes already on disk when this run started (non-zero = resumed).
+    pub resumed_from: u64,
+    /// Average speed of this run in bytes/s.
+    pub bytes_per_sec: u64,
+    pub started_at: String,
+}
+
+/// Registry of active downloads and the shared limits.
+#[derive(Default)]
+pub struct DownloadManager {
+    active: Mutex<HashMap<String, ActiveDownload>>,
+    bandwidth_limit_kbps: AtomicU64,
+    allow_metered: AtomicBool,
+}
+
+static MANAGER: LazyLock<DownloadManager> = LazyLock::new(DownloadManager::default);
+
+/// The global download manager.
+pub fn manager() -> &'static DownloadManager {
+    &MANAGER
+}
+
+/// Partial-file path used while `dest` is downloading.
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".incomplete");
+    dest.with_file_name(name)
+}
+
+/// Total size from a `Content-Range: bytes 100-199/1000` header.
+fn content_range_total(header: &str) -> Option<u64> {
+    header.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+/// How long to sleep so `bytes` transferred over `elapsed` stays under `limit` bytes/s.
+fn throttle_delay(bytes: u64, elapsed: Duration, limit: u64) -> Duration {
+    if limit == 0 {
+        return Duration::ZERO;
+    }
+    let target = Duration::from_secs_f64(bytes as f64 / limit as f64);
+    target.saturating_sub(elapsed)
+}
+
+impl DownloadManager {
+    /// Apply the user's download settings.
+    pub fn configure(&self, settings: &DownloadSettings) {
+        self.bandwidth_limit_kbps
+            .store(settings.bandwidth_limit_kbps, Ordering::SeqCst);
+        self.allow_metered
+            .store(settings.allow_metered, Ordering::SeqCst);
+    }
+
+    /// Downloads currently running.
+    pub fn list_active(&self) -> Vec<ActiveDownload> {
+        let mut downloads: Vec<ActiveDownload> = self
+            .active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        downloads.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        downloads
+    }
+
+    /// Refuse to start a download on a metered connection unless allowed.
+    pub fn ensure_allowed(&self) -> Result<(), String> {
+        if self.allow_metered.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if is_metered_connection() == Some(true) {
+            return Err(
+                "You're on a metered connection. Allow downloads on metered networks in \
+                 Settings to continue."
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Per-download limit in bytes/s: the shared cap split across running downloads.
+    fn current_limit(&self) -> u64 {
+        let kbps = self.bandwidth_limit_kbps.load(Ordering::SeqCst);
+        if kbps == 0 {
+            return 0;
+        }
+        let running = self.active.lock().map(|a| a.len().max(1)).unwrap_or(1) as u64;
+        (kbps * 1024 / running).max(1)
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut ActiveDownload)) {
+        if let Ok(mut active) = self.active.lock() {
+            if let Some(entry) = active.get_mut(id) {
+                f(entry);
+            }
+        }
+    }
+
+    /// Download `url` to `dest`, resuming a previous partial download if present.
+    ///
+    /// Returns the final file size in bytes.
+    pub async fn download(
+        &self,
+        label: &str,
+        kind: DownloadKind,
+        url: &str,
+        dest: &Path,
+    ) -> Result<u64, String> {
+        self.ensure_allowed()?;
+
+        let id = dest.to_string_lossy().to_string();
+        {
+            let mut active = self.active.lock().map_err(|e| e.to_string())?;
+            if active.contains_key(&id) {
+                return Err(format!("{} is already downloading", label));
+            }
+            active.insert(
+                id.clone(),
+                ActiveDownload {
+                    id: id.clone(),
+                    label: label.to_string(),
+                    kind,
+                    url: url.to_string(),
+                    downloaded_bytes: 0,
+                    total_bytes: None,
+                    resumed_from: 0,
+                    bytes_per_sec: 0,
+                    started_at: chrono::Utc::now().to_rfc3339(),
+                },
+            );
+        }
+
+        let result = self.run_download(&id, url, dest).await;
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(&id);
+        }
+        result
+    }
+
+    async fn run_download(&self, id: &str, url: &str, dest: &Path) -> Result<u64, String> {
+        tracing::info!("Downloading {} -> {}", url, dest.display());
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let partial = partial_path(dest);
+        let existing = tokio::fs::metadata(&partial)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        let client = reqwest::Client::new();
+        let mut request = client.get(url);
+        if existing > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+        }
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+            // The partial file already holds the whole body.
+            tokio::fs::rename(&partial, dest)
+                .await
+                .map_err(|e| format!("Failed to finalize download: {}", e))?;
+            return Ok(existing);
+        }
+        if !status.is_success() {
+            return Err(format!("HTTP {} for {}", status, url));
+        }
+
+        // 206 = server honoured the range; 200 = start over.
+        let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+        let offset = if resumed { existing } else { 0 };
+        let total = if resumed {
+            response
+                .headers()
+                .get(reqwest::header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(content_range_total)
+        } else {
+            response.content_length()
+        };
+        if resumed {
+            tracing::info!("Resuming {} at {} bytes", url, offset);
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&partial)
+            .await
+            .map_err(|e| format!("Failed to open {}: {}", partial.display(), e))?;
+
+        self.update(id, |d| {
+            d.resumed_from = offset;
+            d.downloaded_bytes = offset;
+            d.total_bytes = total;
+        });
+
+        let started = Instant::now();
+        let mut received: u64 = 0;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Download interrupted (will resume next time): {}", e))?
+        {
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+            received += chunk.len() as u64;
+
+            let elapsed = started.elapsed();
+            self.update(id, |d| {
+                d.downloaded_bytes = offset + received;
+                d.bytes_per_sec = (received as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+            });
+
+            let delay = throttle_delay(received, elapsed, self.current_limit());
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        drop(file);
+
+        let size = offset + received;
+        if let Some(total) = total {
+            if size != total {
+                return Err(format!(
+                    "Download incomplete: {} of {} bytes (will resume next time)",
+                    size, total
+                ));
+            }
+        }
+        tokio::fs::rename(&partial, dest)
+            .await
+            .map_err(|e| format!("Failed to finalize download: {}", e))?;
+
+        tracing::info!("Downloaded {} bytes to {}", size, dest.display());
+        Ok(size)
+    }
+}
+
+// --- HuggingFace ---
+
+fn hf_repo_dir(repo_id: &str) -> PathBuf {
+    crate::chat::models::get_hf_cache_dir().join(format!("models--{}", repo_id.replace('/', "--")))
+}
+
+/// Whether a HuggingFace file is already in the hf-hub cache.
+pub fn is_hf_cached(repo_id: &str, filename: &str) -> bool {
+    cached_hf_file(&hf_repo_dir(repo_id), filename).is_some()
+}
+
+/// Path of an already-cached HuggingFace file, if any.
+fn cached_hf_file(repo_dir: &Path, filename: &str) -> Option<PathBuf> {
+    let commit = std::fs::read_to_string(repo_dir.join("refs").join("main")).ok()?;
+    let path = repo_dir
+        .join("snapshots")
+        .join(commit.trim())
+        .join(filename);
+    path.exists().then_some(path)
+}
+
+/// Download a file from a HuggingFace model repo into the hf-hub cache.
+///
+/// Resumable and bandwidth-limited; returns the cached path. Already-cached
+/// files are returned without touching the network.
+pub async fn hf_download(repo_id: &str, filename: &str) -> Result<PathBuf, String> {
+    let repo_dir = hf_repo_dir(repo_id);
+    if let Some(path) = cached_hf_file(&repo_dir, filename) {
+        return Ok(path);
+    }
+
+    // Resolve the commit and blob etag without following the CDN redirect.
+    let url = format!(
+        "https://huggingface.co/{}/resolve/main/{}",
+        repo_id, filename
+    );
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?;
+    let head = client
+        .head(&url)
+        .send()
+        .await
+        .map_err(|e| format!("HuggingFace request failed: {}", e))?;
+    if !(head.status().is_success() || head.status().is_redirection()) {
+        return Err(format!("HTTP {} for {}", head.status(), url));
+    }
+    let header = |name: &str| {
+        head.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim_matches('"').to_string())
+    };
+    let commit = header("x-repo-commit").ok_or("HuggingFace response has no commit id")?;
+    let etag = header("x-linked-etag")
+        .or_else(|| header("etag"))
+        .ok_or("HuggingFace response has no etag")?;
+
+    let blob = repo_dir.join("blobs").join(&etag);
+    manager()
+        .download(
+            &format!("{}/{}", repo_id, filename),
+            DownloadKind::Model,
+            &url,
+            &blob,
+        )
+        .await?;
+
+    let snapshot = repo_dir.join("snapshots").join(&commit).join(filename);
+    if let Some(parent) = snapshot.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::rename(&blob, &snapshot)
+        .map_err(|e| format!("Failed to cache {}: {}", filename, e))?;
+    std::fs::create_dir_all(repo_dir.join("refs")).map_err(|e| e.to_string())?;
+    std::fs::write(repo_dir.join("refs").join("main"), &commit).map_err(|e| e.to_string())?;
+    Ok(snapshot)
+}
+
+// --- Metered connections ---
+
+/// Parse `busctl get-property ... Metered` output (`u 1`) from NetworkManager.
+///
+/// NMMetered: 1 = yes, 3 = guess-yes, 2/4 = no, 0 = unknown.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nm_metered(output: &str) -> Option<bool> {
+    match output.split_whitespace().nth(1)?.parse::<u32>().ok()? {
+        1 | 3 => Some(true),
+        2 | 4 => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a Windows `NetworkCostType` (Unrestricted, Fixed, Variable, Unknown).
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_windows_cost(output: &str) -> Option<bool> {
+    match output.trim() {
+        "Fixed" | "Variable" => Some(true),
+        "Unrestricted" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the active network connection is metered (`None` = unknown).
+pub fn is_metered_connection() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("busctl")
+            .args([
+                "get-property",
+                "org.freedesktop.NetworkManager",
+                "/org/freedesktop/NetworkManager",
+                "org.freedesktop.NetworkManager",
+                "Metered",
+            ])
+            .output()
+            .ok()?;
+        parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let output = std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime] | Out-Null; \
+                 [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile().GetConnectionCost().NetworkCostType",
+            ])
+            .output()
+            .ok()?;
+        parse_windows_cost(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            partial_path(Path::new("/cache/blobs/abc123")),
+            PathBuf::from("/cache/blobs/abc123.incomplete")
+        );
+        assert_eq!(
+            partial_path(Path::new("/r/node.tar.gz")),
+            PathBuf::from("/r/node.tar.gz.incomplete")
+        );
+    }
+
+    #[test]
+    fn test_content_range_total() {
+        assert_eq!(content_range_total("bytes 100-199/1000"), Some(1000));
+        assert_eq!(content_range_total("bytes 0-0/*"), None);
+        assert_eq!(content_range_total("garbage"), None);
+    }
+
+    #[test]
+    fn test_throttle_delay() {
+        // Unlimited
+        assert_eq!(
+            throttle_delay(10_000_000, Duration::ZERO, 0),
+            Duration::ZERO
+        );
+        // 1 MB at 512 KB/s should take 2s; 0.5s elapsed → sleep 1.5s
+        let delay = throttle_delay(1024 * 1024, Duration::from_millis(500), 512 * 1024);
+        assert_eq!(delay, Duration::from_millis(1500));
+        // Already slower than the cap
+        assert_eq!(
+            throttle_delay(1024, Duration::from_secs(5), 1024),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_parse_metered() {
+        assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 3"), Some(true));
+        assert_eq!(parse_nm_metered("u 4"), Some(false));
+        assert_eq!(parse_nm_metered("u 0"), None);
+        assert_eq!(parse_nm_metered(""), None);
+        assert_eq!(parse_windows_cost("Fixed\r\n"), Some(true));
+        assert_eq!(parse_windows_cost("Unrestricted"), Some(false));
+        assert_eq!(parse_windows_cost(""), None);
+    }
+
+    #[test]
+    fn test_cached_hf_file() {
+        let dir = std::env::temp_dir().join(format!("ghost_test_hf_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("refs")).unwrap();
+        std::fs::create_dir_all(dir.join("snapshots/abc")).unwrap();
+        std::fs::write(dir.join("refs/main"), "abc").unwrap();
+        assert!(cached_hf_file(&dir, "model.gguf").is_none());
+        std::fs::write(dir.join("snapshots/abc/model.gguf"), "x").unwrap();
+        assert_eq!(
+            cached_hf_file(&dir, "model.gguf"),
+            Some(dir.join("snapshots/abc/model.gguf"))
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_configure_and_limit() {
+        let manager = DownloadManager::default();
+        assert_eq!(manager.current_limit(), 0);
+        manager.configure(&DownloadSettings {
+            bandwidth_limit_kbps: 100,
+            allow_metered: true,
+        });
+        assert_eq!(manager.current_limit(), 100 * 1024);
+        assert!(manager.ensure_allowed().is_ok());
+        assert!(manager.list_active().is_empty());
+    }
+}
@@ -0,0 +1,463 @@
+//! Near-duplicate file detection.
+//!
+//! Each document gets a MinHash signature over the word shingles of its
+//! chunks. Locality-sensitive hashing (signature bands) finds candidate pairs
+//! without comparing every document to every other; pairs whose estimated
+//! Jaccard similarity reaches [`SIMILARITY_THRESHOLD`] are merged into
+//! duplicate groups. Copies, re-saved exports, and lightly edited versions of
+//! the same file end up in one group.
+//!
+//! The scan runs as a background job (at most once a day, or on request) and
+//! stores the groups in the vault. [`report`] lists them with the space the
+//! extra copies take and which copy to keep: the newest one outside an
+//! archive folder.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::search::ranking::is_archive_path;
+
+/// Estimated Jaccard similarity at which two documents count as duplicates.
+pub const SIMILARITY_THRESHOLD: f64 = 0.85;
+
+/// Hours between automatic scans.
+pub const SCAN_INTERVAL_HOURS: u32 = 24;
+
+/// MinHash values per signature.
+const SIGNATURE_LEN: usize = 64;
+
+/// LSH bands; each band covers `SIGNATURE_LEN / BANDS` values.
+const BANDS: usize = 16;
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 3;
+
+/// Documents with fewer shingles are too short to compare meaningfully.
+const MIN_SHINGLES: usize = 8;
+
+type Signature = [u32; SIGNATURE_LEN];
+
+/// Initialize the duplicate tables in the database.
+pub fn initialize_duplicates_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS duplicate_scans (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                scanned_at TEXT NOT NULL DEFAULT (datetime('now')),
+                document_count INTEGER NOT NULL,
+                group_count INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS duplicate_groups (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                -- Lowest pairwise similarity that joined the group
+                similarity REAL NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS duplicate_group_members (
+                group_id INTEGER NOT NULL REFERENCES duplicate_groups(id) ON DELETE CASCADE,
+                document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                PRIMARY KEY (group_id, document_id)
+            ) WITHOUT ROWID;
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Word shingles of a text, hashed.
+fn shingles(text: &str, out: &mut Vec<u64>) {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    for window in words.windows(SHINGLE_WORDS) {
+        let mut hasher = DefaultHasher::new();
+        window.hash(&mut hasher);
+        out.push(hasher.finish());
+    }
+}
+
+/// SplitMix64 finalizer, used to derive the signature's hash functions.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// MinHash signature of a set of shingle hashes.
+fn signature(shingles: &[u64]) -> Signature {
+    let mut sig = [u32::MAX; SIGNATURE_LEN];
+    for &shingle in shingles {
+        for (i, slot) in sig.iter_mut().enumerate() {
+            let h = mix(shingle ^ mix(i as u64 + 1)) as u32;
+            if h < *slot {
+                *slot = h;
+            }
+        }
+    }
+    sig
+}
+
+/// Estimated Jaccard similarity of two signatures.
+fn similarity(a: &Signature, b: &Signature) -> f64 {
+    let same = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    same as f64 / SIGNATURE_LEN as f64
+}
+
+/// Union-find root with path halving.
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Group near-identical signatures. Returns each group's members (indices
+/// into `signatures`) and its lowest joining similarity.
+fn group_signatures(signatures: &[Signature]) -> Vec<(Vec<usize>, f64)> {
+    let rows = SIGNATURE_LEN / BANDS;
+    let mut parent: Vec<usize> = (0..signatures.len()).collect();
+    let mut weakest: HashMap<usize, f64> = HashMap::new();
+
+    for band in 0..BANDS {
+        let mut buckets: HashMap<&[u32], Vec<usize>> = HashMap::new();
+        for (i, sig) in signatures.iter().enumerate() {
+            buckets
+                .entry(&sig[band * rows..(band + 1) * rows])
+                .or_default()
+                .push(i);
+        }
+        for members in buckets.values().filter(|m| m.len() > 1) {
+            for (n, &a) in members.iter().enumerate() {
+                for &b in &members[n + 1..] {
+                    let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+                    if ra == rb {
+                        continue;
+                    }
+                    let score = similarity(&signatures[a], &signatures[b]);
+                    if score < SIMILARITY_THRESHOLD {
+                        continue;
+                    }
+                    parent[rb] = ra;
+                    let joined = [weakest.remove(&ra), weakest.remove(&rb), Some(score)]
+                        .into_iter()
+                        .flatten()
+                        .fold(1.0, f64::min);
+                    weakest.insert(ra, joined);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..signatures.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+    let mut groups: Vec<(Vec<usize>, f64)> = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(r, members)| (members, weakest.get(&r).copied().unwrap_or(1.0)))
+        .collect();
+    groups.sort_by_key(|(members, _)| members[0]);
+    groups
+}
+
+/// Outcome of a scan.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanSummary {
+    pub documents_scanned: usize,
+    pub group_count: usize,
+}
+
+/// Detect near-duplicate documents and replace the stored groups.
+pub fn scan(db: &Database) -> Result<ScanSummary> {
+    let mut ids: Vec<i64> = Vec::new();
+    let mut signatures: Vec<Signature> = Vec::new();
+    db.with_read_conn(|conn| {
+        let mut stmt = conn
+            .prepare("SELECT document_id, content FROM chunks ORDER BY document_id, chunk_index")?;
+        let mut rows = stmt.query([])?;
+        let mut current: Option<i64> = None;
+        let mut hashes: Vec<u64> = Vec::new();
+        let mut flush = |id: Option<i64>, hashes: &mut Vec<u64>| {
+            hashes.sort_unstable();
+            hashes.dedup();
+            if let Some(id) = id.filter(|_| hashes.len() >= MIN_SHINGLES) {
+                ids.push(id);
+                signatures.push(signature(hashes));
+            }
+            hashes.clear();
+        };
+        while let Some(row) = rows.next()? {
+            let document_id: i64 = row.get(0)?;
+            if current != Some(document_id) {
+                flush(current, &mut hashes);
+                current = Some(document_id);
+            }
+            shingles(&row.get::<_, String>(1)?, &mut hashes);
+        }
+        flush(current, &mut hashes);
+        Ok(())
+    })?;
+
+    let groups = group_signatures(&signatures);
+    db.with_transaction(|conn| {
+        conn.execute("DELETE FROM duplicate_groups", [])?;
+        for (members, score) in &groups {
+            conn.execute(
+                "INSERT INTO duplicate_groups (similarity) VALUES (?1)",
+                rusqlite::params![score],
+            )?;
+            let group_id = conn.last_insert_rowid();
+            for &i in members {
+                conn.execute(
+                    "INSERT INTO duplicate_group_members (group_id, document_id) VALUES (?1, ?2)",
+                    rusqlite::params![group_id, ids[i]],
+                )?;
+            }
+        }
+        conn.execute("DELETE FROM duplicate_scans", [])?;
+        conn.execute(
+            "INSERT INTO duplicate_scans (document_count, group_count) VALUES (?1, ?2)",
+            rusqlite::params![ids.len() as i64, groups.len() as i64],
+        )?;
+        Ok(())
+    })?;
+
+    Ok(ScanSummary {
+        documents_scanned: ids.len(),
+        group_count: groups.len(),
+    })
+}
+
+/// Whether the last scan is older than [`SCAN_INTERVAL_HOURS`].
+pub fn is_due(db: &Database) -> Result<bool> {
+    db.with_read_conn(|conn| {
+        let recent: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM duplicate_scans WHERE scanned_at > datetime('now', ?1)",
+            rusqlite::params![format!("-{} hours", SCAN_INTERVAL_HOURS)],
+            |row| row.get(0),
+        )?;
+        Ok(recent == 0)
+    })
+}
+
+/// One file in a duplicate group.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateFile {
+    pub document_id: i64,
+    pub path: String,
+    pub filename: String,
+    pub size_bytes: Option<i64>,
+    pub modified_at: String,
+    /// The copy to keep; the others are candidates to clean up.
+    pub keep: bool,
+}
+
+/// Files with (nearly) the same content.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub id: i64,
+    pub similarity: f64,
+    /// Bytes taken by every copy but the kept one.
+    pub wasted_bytes: i64,
+    pub files: Vec<DuplicateFile>,
+}
+
+/// Duplicate groups from the last scan, biggest waste first.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReport {
+    /// UTC, `YYYY-MM-DD HH:MM:SS`; `None` before the first scan.
+    pub scanned_at: Option<String>,
+    pub documents_scanned: i64,
+    pub wasted_bytes: i64,
+    /// Files that could be removed, over all groups.
+    pub candidate_count: usize,
+    pub groups: Vec<DuplicateGroup>,
+}
+
+/// Order files so the one to keep comes first: outside archive folders,
+/// then most recently modified, then shortest path.
+fn choose_kept(files: &mut [DuplicateFile]) {
+    files.sort_by(|a, b| {
+        is_archive_path(&a.path)
+            .cmp(&is_archive_path(&b.path))
+            .then_with(|| b.modified_at.cmp(&a.modified_at))
+            .then_with(|| a.path.len().cmp(&b.path.len()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    for (i, file) in files.iter_mut().enumerate() {
+        file.keep = i == 0;
+    }
+}
+
+/// The stored duplicate groups, at most `limit` of them. Files removed from
+/// the vault since the scan are left out.
+pub fn report(db: &Database, limit: usize) -> Result<DuplicateReport> {
+    let (scanned_at, documents_scanned, rows) = db.with_read_conn(|conn| {
+        let last = conn
+            .query_row(
+                "SELECT scanned_at, document_count FROM duplicate_scans ORDER BY id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+            )
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?;
+        let mut stmt = conn.prepare(
+            "SELECT g.id, g.similarity, d.id, d.path, d.filename, d.size_bytes, d.modified_at
+             FROM duplicate_groups g
+             JOIN duplicate_group_members m ON m.group_id = g.id
+             JOIN documents d ON d.id = m.document_id
+             ORDER BY g.id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, f64>(1)?,
+                    DuplicateFile {
+                        document_id: row.get(2)?,
+                        path: row.get(3)?,
+                        filename: row.get(4)?,
+                        size_bytes: row.get(5)?,
+                        modified_at: row.get(6)?,
+                        keep: false,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let (scanned_at, count) = last.unzip();
+        Ok((scanned_at, count.unwrap_or(0), rows))
+    })?;
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    for (id, similarity, file) in rows {
+        match groups.last_mut() {
+            Some(group) if group.id == id => group.files.push(file),
+            _ => groups.push(DuplicateGroup {
+                id,
+                similarity,
+                wasted_bytes: 0,
+                files: vec![file],
+            }),
+        }
+    }
+    groups.retain(|g| g.files.len() > 1);
+    for group in &mut groups {
+        choose_kept(&mut group.files);
+        group.wasted_bytes = group.files[1..]
+            .iter()
+            .map(|f| f.size_bytes.unwrap_or(0))
+            .sum();
+    }
+    groups.sort_by(|a, b| b.wasted_bytes.cmp(&a.wasted_bytes).then(a.id.cmp(&b.id)));
+
+    let wasted_bytes = groups.iter().map(|g| g.wasted_bytes).sum();
+    let candidate_count = groups.iter().map(|g| g.files.len() - 1).sum();
+    groups.truncate(limit);
+    Ok(DuplicateReport {
+        scanned_at,
+        documents_scanned,
+        wasted_bytes,
+        candidate_count,
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Quarterly budget review for the household. Rent is nine hundred, \
+        groceries about three hundred, utilities one hundred and twenty, and savings \
+        take whatever is left at the end of each month after the car payment.";
+
+    fn add_document(db: &Database, path: &str, size: i64, modified: &str, text: &str) -> i64 {
+        let id = db
+            .upsert_document(
+                path,
+                path.rsplit('/').next().unwrap(),
+                Some("md"),
+                size,
+                path,
+                modified,
+            )
+            .unwrap();
+        db.insert_chunk(id, 0, text, 40).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_signature_similarity() {
+        let sig = |text: &str| {
+            let mut hashes = Vec::new();
+            shingles(text, &mut hashes);
+            signature(&hashes)
+        };
+        let edited = TEXT.replace("nine hundred", "nine hundred fifty");
+        assert_eq!(similarity(&sig(TEXT), &sig(TEXT)), 1.0);
+        assert!(similarity(&sig(TEXT), &sig(&edited)) > 0.6);
+        assert!(
+            similarity(
+                &sig(TEXT),
+                &sig("Meeting notes about the launch plan for May")
+            ) < 0.2
+        );
+    }
+
+    #[test]
+    fn test_scan_and_report() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_duplicates_schema(&db).unwrap();
+        assert!(is_due(&db).unwrap());
+        assert!(report(&db, 10).unwrap().scanned_at.is_none());
+
+        let original = add_document(&db, "/docs/budget.md", 500, "2026-03-01", TEXT);
+        let copy = add_document(&db, "/docs/old/budget copy.md", 400, "2026-01-01", TEXT);
+        add_document(&db, "/archive/budget.md", 300, "2026-05-01", TEXT);
+        add_document(
+            &db,
+            "/docs/plan.md",
+            200,
+            "2026-03-01",
+            "Launch plan: ship the beta in May, collect feedback through June, \
+             and prepare the public release for the autumn conference season.",
+        );
+
+        let summary = scan(&db).unwrap();
+        assert_eq!(summary.documents_scanned, 4);
+        assert_eq!(summary.group_count, 1);
+        assert!(!is_due(&db).unwrap());
+
+        let report = report(&db, 10).unwrap();
+        assert!(report.scanned_at.is_some());
+        assert_eq!(report.groups.len(), 1);
+        let group = &report.groups[0];
+        assert_eq!(group.files.len(), 3);
+        // The newest copy is archived, so the newest one elsewhere is kept
+        assert!(group.files[0].keep);
+        assert_eq!(group.files[0].document_id, original);
+        assert_eq!(group.wasted_bytes, 700);
+        assert_eq!(report.wasted_bytes, 700);
+        assert_eq!(report.candidate_count, 2);
+
+        // Deleted files drop out of the report
+        db.delete_document(copy).unwrap();
+        let report = super::report(&db, 10).unwrap();
+        assert_eq!(report.groups[0].files.len(), 2);
+        assert_eq!(report.wasted_bytes, 300);
+    }
+}
@@ -0,0 +1,305 @@
+//! Embedding caches.
+//!
+//! - [`QueryCache`]: an in-memory LRU of query embeddings inside
+//!   [`EmbeddingEngine`], keyed by backend and normalized query text, so
+//!   repeated and refined searches skip the model.
+//! - The `embedding_cache` table: chunk embeddings keyed by the SHA-256 of
+//!   the chunk text and the model that produced them. Re-indexing a file
+//!   whose content is unchanged (or only partly changed) reuses them through
+//!   [`embed_chunks`] instead of recomputing.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::EmbeddingEngine;
+use crate::db::Database;
+use crate::error::Result;
+
+/// Query embeddings kept in memory.
+pub const QUERY_CACHE_CAPACITY: usize = 512;
+
+/// Chunk embeddings kept on disk; the least recently used are pruned.
+const MAX_STORED_EMBEDDINGS: i64 = 100_000;
+
+/// Normalize a query for cache lookups: trimmed, whitespace collapsed.
+pub fn normalize_query(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Least-recently-used cache of query embeddings.
+#[derive(Debug)]
+pub struct QueryCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (Vec<f32>, u64)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl QueryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            entries: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Look up an embedding, marking it as recently used.
+    pub fn get(&mut self, key: &str) -> Option<Vec<f32>> {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some((embedding, used)) => {
+                *used = self.tick;
+                self.hits += 1;
+                Some(embedding.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Add an embedding, evicting the least recently used one when full.
+    pub fn insert(&mut self, key: String, embedding: Vec<f32>) {
+        self.tick += 1;
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (embedding, self.tick));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// (hits, misses) since startup.
+    pub fn counters(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+}
+
+/// Cache statistics for the settings panel.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingCacheStats {
+    pub query_entries: usize,
+    pub query_hits: u64,
+    pub query_misses: u64,
+    /// Chunk embeddings stored on disk.
+    pub stored_embeddings: i64,
+}
+
+/// Initialize the chunk embedding cache table.
+pub fn initialize_embedding_cache_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS embedding_cache (
+                content_hash TEXT NOT NULL,
+                -- Backend and dimensions, e.g. 'native:384'
+                model TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                used_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (content_hash, model)
+            );
+            CREATE INDEX IF NOT EXISTS idx_embedding_cache_used_at
+                ON embedding_cache(used_at);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// SHA-256 of a chunk's text, hex encoded.
+pub fn content_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+fn to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Cached embeddings for the given content hashes.
+pub fn lookup(db: &Database, model: &str, hashes: &[String]) -> Result<HashMap<String, Vec<f32>>> {
+    if hashes.is_empty() {
+        return Ok(HashMap::new());
+    }
+    db.with_conn(|conn| {
+        let mut found = HashMap::new();
+        let mut select = conn.prepare_cached(
+            "SELECT embedding FROM embedding_cache WHERE content_hash = ?1 AND model = ?2",
+        )?;
+        let mut touch = conn.prepare_cached(
+            "UPDATE embedding_cache SET used_at = datetime('now')
+             WHERE content_hash = ?1 AND model = ?2",
+        )?;
+        for hash in hashes {
+            if found.contains_key(hash) {
+                continue;
+            }
+            let blob: Option<Vec<u8>> = select
+                .query_row(rusqlite::params![hash, model], |row| row.get(0))
+                .map(Some)
+                .or_else(|e| match e {
+                    rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                    e => Err(e),
+                })?;
+            if let Some(blob) = blob {
+                touch.execute(rusqlite::params![hash, model])?;
+                found.insert(hash.clone(), from_blob(&blob));
+            }
+        }
+        Ok(found)
+    })
+}
+
+/// Store embeddings by content hash, pruning the oldest beyond the cap.
+pub fn store(db: &Database, model: &str, entries: &[(String, Vec<f32>)]) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    db.with_transaction(|conn| {
+        for (hash, embedding) in entries {
+            conn.execute(
+                "INSERT OR REPLACE INTO embedding_cache (content_hash, model, embedding)
+                 VALUES (?1, ?2, ?3)",
+                rusqlite::params![hash, model, to_blob(embedding)],
+            )?;
+        }
+        conn.execute(
+            "DELETE FROM embedding_cache WHERE rowid IN (
+                SELECT rowid FROM embedding_cache ORDER BY used_at DESC LIMIT -1 OFFSET ?1)",
+            rusqlite::params![MAX_STORED_EMBEDDINGS],
+        )?;
+        Ok(())
+    })
+}
+
+/// Number of stored chunk embeddings.
+pub fn stored_count(db: &Database) -> Result<i64> {
+    db.with_read_conn(|conn| {
+        Ok(conn.query_row("SELECT COUNT(*) FROM embedding_cache", [], |row| row.get(0))?)
+    })
+}
+
+/// Delete every stored chunk embedding.
+pub fn clear(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute("DELETE FROM embedding_cache", [])?;
+        Ok(())
+    })
+}
+
+/// Embed chunk texts, reusing stored embeddings for content seen before.
+/// Cache failures fall back to embedding everything.
+pub async fn embed_chunks(
+    db: &Database,
+    engine: &EmbeddingEngine,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let model = engine.model_key();
+    let hashes: Vec<String> = texts.iter().map(|t| content_hash(t)).collect();
+    let mut cached = lookup(db, &model, &hashes).unwrap_or_else(|e| {
+        tracing::debug!("Embedding cache unavailable: {}", e);
+        HashMap::new()
+    });
+
+    let missing: Vec<usize> = (0..texts.len())
+        .filter(|&i| !cached.contains_key(&hashes[i]))
+        .collect();
+    if !missing.is_empty() {
+        let batch: Vec<String> = missing.iter().map(|&i| texts[i].clone()).collect();
+        let embeddings = engine.embed_batch(&batch).await?;
+        // A fallback backend may have answered; only cache the expected shape
+        let dimensions = engine.dimensions();
+        let fresh: Vec<(String, Vec<f32>)> = missing
+            .iter()
+            .zip(embeddings)
+            .map(|(&i, embedding)| (hashes[i].clone(), embedding))
+            .collect();
+        let storable: Vec<(String, Vec<f32>)> = fresh
+            .iter()
+            .filter(|(_, e)| e.len() == dimensions)
+            .cloned()
+            .collect();
+        if let Err(e) = store(db, &model, &storable) {
+            tracing::debug!("Embedding cache not updated: {}", e);
+        }
+        cached.extend(fresh);
+    }
+    tracing::debug!(
+        "Embedded {} chunk(s), {} from cache",
+        texts.len(),
+        texts.len() - missing.len()
+    );
+
+    hashes
+        .iter()
+        .map(|h| {
+            cached.get(h).cloned().ok_or_else(|| {
+                crate::error::GhostError::Embedding(
+                    "Embedding backend returned too few vectors".into(),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_cache_evicts_least_recently_used() {
+        let mut cache = QueryCache::new(2);
+        cache.insert("a".into(), vec![1.0]);
+        cache.insert("b".into(), vec![2.0]);
+        assert_eq!(cache.get("a"), Some(vec![1.0]));
+        cache.insert("c".into(), vec![3.0]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(vec![1.0]));
+        assert_eq!(cache.get("c"), Some(vec![3.0]));
+        assert_eq!(cache.counters(), (3, 1));
+        assert_eq!(normalize_query("  tax\n  return "), "tax return");
+    }
+
+    #[test]
+    fn test_stored_embeddings_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_embedding_cache_schema(&db).unwrap();
+        let hash = content_hash("hello world");
+        store(&db, "native:3", &[(hash.clone(), vec![0.5, -1.0, 2.25])]).unwrap();
+
+        let found = lookup(&db, "native:3", &[hash.clone(), content_hash("other")]).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[&hash], vec![0.5, -1.0, 2.25]);
+        // Embeddings from another model are never mixed in
+        assert!(lookup(&db, "ollama:768", &[hash]).unwrap().is_empty());
+        assert_eq!(stored_count(&db).unwrap(), 1);
+        clear(&db).unwrap();
+        assert_eq!(stored_count(&db).unwrap(), 0);
+    }
+}
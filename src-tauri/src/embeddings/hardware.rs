@@ -419,6 +419,124 @@ impl HardwareInfo {
     }
 }
 
+/// Where the machine draws power from.
+#[derive(Debug, Clone, Copy, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSource {
+    /// Plugged in (desktops without a battery included).
+    Ac,
+    Battery,
+    Unknown,
+}
+
+/// Detect the current power source. Spawns a process on macOS and Windows,
+/// so call it from a blocking context and not in tight loops.
+pub fn power_source() -> PowerSource {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        return power_source_linux(std::path::Path::new("/sys/class/power_supply"));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        return std::process::Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .ok()
+            .map(|o| parse_pmset_source(&String::from_utf8_lossy(&o.stdout)))
+            .unwrap_or(PowerSource::Unknown);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // BatteryStatus 1 = discharging; no output = no battery
+        let output = std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_Battery | Select-Object -First 1).BatteryStatus",
+            ])
+            .output();
+        return match output
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        {
+            Some(status) if status == "1" => PowerSource::Battery,
+            Some(_) => PowerSource::Ac,
+            None => PowerSource::Unknown,
+        };
+    }
+
+    #[allow(unreachable_code)]
+    PowerSource::Unknown
+}
+
+/// Power source from sysfs: on battery when a battery is discharging and no
+/// mains adapter is online.
+#[cfg(any(target_os = "linux", target_os = "android", test))]
+fn power_source_linux(root: &std::path::Path) -> PowerSource {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return PowerSource::Unknown;
+    };
+    let read = |dir: &std::path::Path, name: &str| {
+        std::fs::read_to_string(dir.join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut discharging = false;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_str() {
+            "Mains" | "USB" if read(&dir, "online") == "1" => return PowerSource::Ac,
+            "Battery" if read(&dir, "status") == "Discharging" => discharging = true,
+            _ => {}
+        }
+    }
+    if discharging {
+        PowerSource::Battery
+    } else {
+        PowerSource::Ac
+    }
+}
+
+/// Power source from `pmset -g batt` ("Now drawing from 'AC Power'").
+#[cfg(any(target_os = "macos", test))]
+fn parse_pmset_source(output: &str) -> PowerSource {
+    if output.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else if output.contains("'AC Power'") {
+        PowerSource::Ac
+    } else {
+        PowerSource::Unknown
+    }
+}
+
+/// Hottest CPU thermal zone in °C, where the OS exposes one (Linux, Android).
+pub fn cpu_temperature_c() -> Option<f32> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        return max_thermal_zone_c(std::path::Path::new("/sys/class/thermal"));
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+/// Highest `thermal_zone*/temp` reading (millidegrees) under `root`.
+#[cfg(any(target_os = "linux", target_os = "android", test))]
+fn max_thermal_zone_c(root: &std::path::Path) -> Option<f32> {
+    std::fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|e| std::fs::read_to_string(e.path().join("temp")).ok())
+        .filter_map(|t| t.trim().parse::<i64>().ok())
+        // Disabled sensors report 0 or negative values
+        .filter(|&milli| milli > 0)
+        .map(|milli| milli as f32 / 1000.0)
+        .reduce(f32::max)
+}
+
 /// Get the default model storage directory.
 pub fn models_dir() -> Result<std::path::PathBuf> {
     let data_dir = dirs::data_dir()
@@ -448,6 +566,48 @@ mod tests {
         assert!(dir.exists());
     }
 
+    #[test]
+    fn test_power_source_linux() {
+        let root = std::env::temp_dir().join(format!("ghost_power_{}", std::process::id()));
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            let dir = root.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            for (file, value) in files {
+                std::fs::write(dir.join(file), value).unwrap();
+            }
+        };
+        supply(
+            "BAT0",
+            &[("type", "Battery\n"), ("status", "Discharging\n")],
+        );
+        supply("AC", &[("type", "Mains\n"), ("online", "0\n")]);
+        assert_eq!(power_source_linux(&root), PowerSource::Battery);
+        supply("AC", &[("online", "1\n")]);
+        assert_eq!(power_source_linux(&root), PowerSource::Ac);
+        assert_eq!(
+            power_source_linux(&root.join("missing")),
+            PowerSource::Unknown
+        );
+
+        supply("thermal_zone0", &[("temp", "45000\n")]);
+        supply("thermal_zone1", &[("temp", "71500\n")]);
+        assert_eq!(max_thermal_zone_c(&root), Some(71.5));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_parse_pmset_source() {
+        assert_eq!(
+            parse_pmset_source("Now drawing from 'Battery Power'\n -InternalBattery-0 80%"),
+            PowerSource::Battery
+        );
+        assert_eq!(
+            parse_pmset_source("Now drawing from 'AC Power'"),
+            PowerSource::Ac
+        );
+        assert_eq!(parse_pmset_source(""), PowerSource::Unknown);
+    }
+
     #[test]
     fn test_device_selection_cpu() {
         let info = HardwareInfo::detect();
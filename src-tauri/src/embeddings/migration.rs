@@ -0,0 +1,299 @@
+//! Background re-embedding job for switching embedding models.
+//!
+//! The job walks every chunk in batches, embeds it with the target model,
+//! and writes the result to the shadow vector index (see
+//! [`crate::db::vec_migration`]). Progress is persisted after every batch,
+//! so the job can be paused, resumed, or picked up again after a restart.
+//! Search keeps working throughout: until the swap, migrated chunks are read
+//! from the new index and the rest from the old one.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::models::{self, EmbeddingModel};
+use super::EmbeddingEngine;
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+
+/// Chunks embedded per batch.
+const BATCH_SIZE: usize = 32;
+
+/// Pause between batches so indexing and search stay responsive.
+const BATCH_PAUSE: Duration = Duration::from_millis(50);
+
+/// Re-embedding progress for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReembedStatus {
+    /// "idle", "running", or "paused".
+    pub state: String,
+    pub target_backend: Option<String>,
+    /// Registry ID of the target model.
+    pub target_model: Option<String>,
+    pub dimensions: usize,
+    pub migrated: i64,
+    pub total: i64,
+    pub started_at: Option<String>,
+    /// Last error (the job pauses itself when embedding fails).
+    pub error: Option<String>,
+}
+
+/// How a run of the job ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReembedOutcome {
+    /// Every chunk was migrated and the new index is live.
+    Completed(&'static EmbeddingModel),
+    /// Paused by the user or after an error; resume continues from the cursor.
+    Paused,
+}
+
+/// Handle to the (single) re-embedding job.
+#[derive(Default)]
+pub struct ReembedJob {
+    running: AtomicBool,
+    pause_requested: AtomicBool,
+    error: Mutex<Option<String>>,
+}
+
+impl ReembedJob {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the job loop is currently running.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Ask a running job to stop after the current batch.
+    pub fn request_pause(&self) {
+        self.pause_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Current progress.
+    pub fn status(&self, db: &Database) -> Result<ReembedStatus> {
+        let migration = db.get_vec_migration()?;
+        let total = db.get_stats()?.chunk_count;
+        let error = self.error.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        Ok(match migration {
+            Some(m) => {
+                let target = models::find_target(m.target_id());
+                ReembedStatus {
+                    state: if self.is_running() {
+                        "running"
+                    } else {
+                        "paused"
+                    }
+                    .to_string(),
+                    target_backend: target.map(|t| t.backend().to_string()),
+                    target_model: target.map(|t| t.id.to_string()),
+                    dimensions: m.dimensions,
+                    migrated: m.migrated.min(total),
+                    total,
+                    started_at: Some(m.started_at),
+                    error,
+                }
+            }
+            None => ReembedStatus {
+                state: "idle".to_string(),
+                target_backend: None,
+                target_model: None,
+                dimensions: 0,
+                migrated: 0,
+                total,
+                started_at: None,
+                error,
+            },
+        })
+    }
+
+    /// Begin migrating the vault to `target`. Call [`ReembedJob::run`] afterwards.
+    pub async fn start(
+        &self,
+        db: &Database,
+        engine: &EmbeddingEngine,
+        target: &'static EmbeddingModel,
+    ) -> Result<()> {
+        engine.prepare_model(target).await?;
+        db.begin_vec_migration(&target.backend().to_string(), target.id, target.dimensions)?;
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        Ok(())
+    }
+
+    /// Run the migration until it completes or is paused.
+    ///
+    /// Returns an error if no migration is in progress or the job is already running.
+    pub async fn run(&self, db: &Database, engine: &EmbeddingEngine) -> Result<ReembedOutcome> {
+        let migration = db
+            .get_vec_migration()?
+            .ok_or_else(|| GhostError::Embedding("No re-embedding migration in progress".into()))?;
+        let target = models::find_target(migration.target_id()).ok_or_else(|| {
+            GhostError::Embedding(format!(
+                "Unknown embedding model '{}'",
+                migration.target_id()
+            ))
+        })?;
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err(GhostError::Embedding(
+                "Re-embedding is already running".into(),
+            ));
+        }
+        self.pause_requested.store(false, Ordering::SeqCst);
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        db.set_vec_migration_paused(false)?;
+
+        let result = self.run_batches(db, engine, target, migration.cursor).await;
+        self.running.store(false, Ordering::SeqCst);
+
+        match result {
+            Ok(ReembedOutcome::Completed(model)) => {
+                engine.activate_model(model);
+                Ok(ReembedOutcome::Completed(model))
+            }
+            Ok(ReembedOutcome::Paused) => {
+                db.set_vec_migration_paused(true)?;
+                Ok(ReembedOutcome::Paused)
+            }
+            Err(e) => {
+                tracing::warn!("Re-embedding paused after error: {}", e);
+                *self.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e.to_string());
+                db.set_vec_migration_paused(true)?;
+                Ok(ReembedOutcome::Paused)
+            }
+        }
+    }
+
+    async fn run_batches(
+        &self,
+        db: &Database,
+        engine: &EmbeddingEngine,
+        target: &'static EmbeddingModel,
+        mut cursor: i64,
+    ) -> Result<ReembedOutcome> {
+        // After a restart the target model has to be loaded again
+        engine.prepare_model(target).await?;
+        loop {
+            if self.pause_requested.swap(false, Ordering::SeqCst) {
+                tracing::info!("Re-embedding paused at chunk {}", cursor);
+                return Ok(ReembedOutcome::Paused);
+            }
+
+            // Held on battery or when hot; a pause request still gets through
+            crate::indexer::throttle::wait_while_throttled(
+                |_| {},
+                || self.pause_requested.load(Ordering::SeqCst),
+            )
+            .await;
+            let batch = db.chunks_after(cursor, BATCH_SIZE)?;
+            let Some(last) = batch.last() else {
+                db.finish_vec_migration()?;
+                tracing::info!("Re-embedding complete — {} index is now live", target.name);
+                return Ok(ReembedOutcome::Completed(target));
+            };
+            cursor = last.chunk_id;
+
+            let texts: Vec<String> = batch.iter().map(|c| c.content.clone()).collect();
+            let embeddings = engine.embed_batch_for(target, &texts).await?;
+            if embeddings.len() != batch.len() {
+                return Err(GhostError::Embedding(format!(
+                    "Expected {} embeddings, got {}",
+                    batch.len(),
+                    embeddings.len()
+                )));
+            }
+            db.store_migrated_embeddings(&batch, &embeddings)?;
+
+            tokio::time::sleep(BATCH_PAUSE).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::ollama::OllamaEngine;
+
+    /// An engine whose Ollama backend is a closed local port, so the tests
+    /// don't depend on whether Ollama runs on this machine.
+    fn engine_without_ollama() -> EmbeddingEngine {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        EmbeddingEngine::none()
+            .with_ollama(OllamaEngine::new().with_base_url(format!("http://{}", addr)))
+    }
+
+    #[test]
+    fn test_idle_status() {
+        let db = Database::open_in_memory().unwrap();
+        let job = ReembedJob::new();
+        let status = job.status(&db).unwrap();
+        assert_eq!(status.state, "idle");
+        assert!(status.target_backend.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_start_requires_available_backend() {
+        let db = Database::open_in_memory().unwrap();
+        let job = ReembedJob::new();
+        let engine = engine_without_ollama();
+        let ollama = models::find_target("ollama").unwrap();
+        assert!(job.start(&db, &engine, ollama).await.is_err());
+        assert!(db.get_vec_migration().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_pauses_on_embedding_error() {
+        let db = Database::open_in_memory().unwrap();
+        if !db.is_vec_enabled() {
+            return;
+        }
+        let doc = db
+            .upsert_document(
+                "/t/a.md",
+                "a.md",
+                Some("md"),
+                1,
+                "h",
+                "2026-01-01T00:00:00Z",
+            )
+            .unwrap();
+        db.insert_chunk(doc, 0, "hello", 1).unwrap();
+        db.begin_vec_migration("ollama", "nomic-embed-text", 768)
+            .unwrap();
+
+        // Target unavailable: the job pauses itself with the error.
+        let job = ReembedJob::new();
+        let outcome = job.run(&db, &engine_without_ollama()).await.unwrap();
+        assert_eq!(outcome, ReembedOutcome::Paused);
+        let status = job.status(&db).unwrap();
+        assert_eq!(status.state, "paused");
+        assert_eq!(status.target_backend.as_deref(), Some("ollama"));
+        assert_eq!(status.target_model.as_deref(), Some("nomic-embed-text"));
+        assert!(status.error.is_some());
+        assert!(db.get_vec_migration().unwrap().unwrap().paused);
+    }
+
+    #[test]
+    fn test_status_resolves_legacy_migrations() {
+        let db = Database::open_in_memory().unwrap();
+        if !db.is_vec_enabled() {
+            return;
+        }
+        // Started before the registry: only the backend name was stored
+        db.begin_vec_migration("native", "", 384).unwrap();
+        db.with_conn(|conn| {
+            conn.execute("UPDATE embedding_migration SET target_model = NULL", [])?;
+            Ok(())
+        })
+        .unwrap();
+        let status = ReembedJob::new().status(&db).unwrap();
+        assert_eq!(status.target_backend.as_deref(), Some("native"));
+        assert_eq!(
+            status.target_model.as_deref(),
+            Some(models::DEFAULT_MODEL_ID)
+        );
+    }
+}
@@ -8,14 +8,21 @@
 //! The engine uses **deferred loading** — the app starts instantly with FTS5-only,
 //! then loads the native model in the background. This prevents blocking the UI
 //! during model download (~23MB) or loading (~200ms cached).
+//!
+//! Which model runs natively is chosen from the registry in [`models`];
+//! switching models re-embeds the vault (see [`migration`]).
 
+pub mod cache;
 pub mod hardware;
+pub mod migration;
+pub mod models;
 pub mod native;
 pub mod ollama;
 
 use std::sync::Mutex;
 
 use crate::error::{GhostError, Result};
+use models::EmbeddingModel;
 
 /// The active AI backend for the status bar and diagnostics.
 #[derive(Debug, Clone, serde::Serialize, PartialEq)]
@@ -28,6 +35,17 @@ pub enum AiBackend {
     None,
 }
 
+impl AiBackend {
+    /// Parse a backend name as produced by `Display` ("native", "ollama").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "native" => Some(AiBackend::Native),
+            "ollama" => Some(AiBackend::Ollama),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for AiBackend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -55,11 +73,16 @@ pub struct AiStatus {
 /// Uses interior mutability (Mutex) to allow background loading.
 pub struct EmbeddingEngine {
     native: Mutex<Option<native::NativeEngine>>,
+    /// Registry entry the native slot runs (or will run once loaded).
+    native_model: Mutex<&'static EmbeddingModel>,
+    /// Native model loaded as a re-embedding target, promoted on completion.
+    next_native: Mutex<Option<native::NativeEngine>>,
     ollama: ollama::OllamaEngine,
     active_backend: Mutex<AiBackend>,
     loading: Mutex<bool>,
     error: Mutex<Option<String>>,
     hardware: hardware::HardwareInfo,
+    query_cache: Mutex<cache::QueryCache>,
 }
 
 impl EmbeddingEngine {
@@ -70,11 +93,14 @@ impl EmbeddingEngine {
     pub fn new(hardware: hardware::HardwareInfo) -> Self {
         Self {
             native: Mutex::new(None),
+            native_model: Mutex::new(models::default_model()),
+            next_native: Mutex::new(None),
             ollama: ollama::OllamaEngine::new(),
             active_backend: Mutex::new(AiBackend::None),
             loading: Mutex::new(false),
             error: Mutex::new(None),
             hardware,
+            query_cache: Mutex::new(cache::QueryCache::new(cache::QUERY_CACHE_CAPACITY)),
         }
     }
 
@@ -83,6 +109,8 @@ impl EmbeddingEngine {
     pub fn none() -> Self {
         Self {
             native: Mutex::new(None),
+            native_model: Mutex::new(models::default_model()),
+            next_native: Mutex::new(None),
             ollama: ollama::OllamaEngine::new(),
             active_backend: Mutex::new(AiBackend::None),
             loading: Mutex::new(false),
@@ -95,9 +123,25 @@ impl EmbeddingEngine {
                 total_ram_mb: 0,
                 available_ram_mb: 0,
             },
+            query_cache: Mutex::new(cache::QueryCache::new(cache::QUERY_CACHE_CAPACITY)),
         }
     }
 
+    /// Run `model` natively instead of the default (the vault's saved model).
+    /// Ollama-served models leave the default native model in place.
+    pub fn with_model(self, model: &'static EmbeddingModel) -> Self {
+        if model.backend() == AiBackend::Native {
+            *self.native_model.lock().unwrap_or_else(|e| e.into_inner()) = model;
+        }
+        self
+    }
+
+    /// Use `ollama` as the Ollama backend (e.g. a server at another address).
+    pub fn with_ollama(mut self, ollama: ollama::OllamaEngine) -> Self {
+        self.ollama = ollama;
+        self
+    }
+
     /// Load the embedding engine in the background: try native first, fall back to Ollama.
     ///
     /// This is safe to call multiple times — it guards against concurrent loads.
@@ -130,9 +174,14 @@ impl EmbeddingEngine {
         crate::ensure_tls_provider();
 
         // Try native engine first
-        match native::NativeEngine::load(&self.hardware).await {
+        let model = *self.native_model.lock().unwrap_or_else(|e| e.into_inner());
+        match native::NativeEngine::load(&self.hardware, model).await {
             Ok(engine) => {
-                tracing::info!("Native embedding engine loaded (Candle, 384D)");
+                tracing::info!(
+                    "Native embedding engine loaded (Candle, {}, {}D)",
+                    model.name,
+                    model.dimensions
+                );
                 *self.native.lock().unwrap_or_else(|e| e.into_inner()) = Some(engine);
                 *self
                     .active_backend
@@ -183,23 +232,171 @@ impl EmbeddingEngine {
 
     /// Get the embedding dimensions for the active backend.
     pub fn dimensions(&self) -> usize {
-        let backend = self
+        self.dimensions_for(&self.backend())
+    }
+
+    /// Switch the active backend (after a re-embedding migration completes).
+    pub fn set_active_backend(&self, backend: AiBackend) {
+        tracing::info!("Embedding backend switched to {}", backend);
+        *self
             .active_backend
             .lock()
-            .unwrap_or_else(|e| e.into_inner());
-        match *backend {
+            .unwrap_or_else(|e| e.into_inner()) = backend;
+        self.clear_query_cache();
+    }
+
+    /// The registry model whose vectors the active backend produces.
+    pub fn active_model(&self) -> &'static EmbeddingModel {
+        match self.backend() {
+            AiBackend::Ollama => models::find_target("ollama").unwrap_or(models::default_model()),
+            _ => *self.native_model.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+
+    /// Identifies the vector space of the active backend in the embedding
+    /// cache (e.g. "native:384").
+    pub fn model_key(&self) -> String {
+        match self.backend() {
+            AiBackend::None => "none:0".to_string(),
+            _ => self.active_model().cache_key.to_string(),
+        }
+    }
+
+    /// Drop all cached query embeddings.
+    pub fn clear_query_cache(&self) {
+        self.query_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Query cache size and (hits, misses) since startup.
+    pub fn query_cache_stats(&self) -> (usize, u64, u64) {
+        let cache = self.query_cache.lock().unwrap_or_else(|e| e.into_inner());
+        let (hits, misses) = cache.counters();
+        (cache.len(), hits, misses)
+    }
+
+    /// Embedding dimensions a specific backend produces.
+    pub fn dimensions_for(&self, backend: &AiBackend) -> usize {
+        match backend {
             AiBackend::Native => self
                 .native
                 .lock()
                 .unwrap_or_else(|e| e.into_inner())
                 .as_ref()
                 .map(|n| n.dimensions())
-                .unwrap_or(384),
+                .unwrap_or_else(|| {
+                    self.native_model
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .dimensions
+                }),
             AiBackend::Ollama => self.ollama.dimensions(),
             AiBackend::None => 0,
         }
     }
 
+    /// Check if a specific backend can produce embeddings right now.
+    pub async fn is_backend_available(&self, backend: &AiBackend) -> bool {
+        match backend {
+            AiBackend::Native => self
+                .native
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .is_some(),
+            AiBackend::Ollama => self.ollama.health_check().await.unwrap_or(false),
+            AiBackend::None => false,
+        }
+    }
+
+    /// Make `model` ready to embed without switching to it: checks Ollama,
+    /// or downloads and loads a native model beside the active one.
+    pub async fn prepare_model(&self, model: &'static EmbeddingModel) -> Result<()> {
+        if model.backend() == AiBackend::Ollama {
+            return match self.ollama.health_check().await {
+                Ok(true) => Ok(()),
+                _ => Err(GhostError::Embedding(format!(
+                    "{} needs a running Ollama server",
+                    model.name
+                ))),
+            };
+        }
+        if self.has_native(model) {
+            return Ok(());
+        }
+        crate::ensure_tls_provider();
+        let engine = native::NativeEngine::load(&self.hardware, model).await?;
+        *self.next_native.lock().unwrap_or_else(|e| e.into_inner()) = Some(engine);
+        Ok(())
+    }
+
+    /// Switch to `model` (after a re-embedding migration completes).
+    pub fn activate_model(&self, model: &'static EmbeddingModel) {
+        if model.backend() == AiBackend::Native {
+            let mut next = self.next_native.lock().unwrap_or_else(|e| e.into_inner());
+            if next.as_ref().is_some_and(|n| n.profile().id == model.id) {
+                *self.native.lock().unwrap_or_else(|e| e.into_inner()) = next.take();
+            }
+            *self.native_model.lock().unwrap_or_else(|e| e.into_inner()) = model;
+        }
+        self.set_active_backend(model.backend());
+    }
+
+    /// Whether a native engine for `model` is loaded (active or as a target).
+    fn has_native(&self, model: &EmbeddingModel) -> bool {
+        [&self.native, &self.next_native].iter().any(|slot| {
+            slot.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()
+                .is_some_and(|n| n.profile().id == model.id)
+        })
+    }
+
+    /// Run `f` on the loaded native engine for `model`.
+    fn with_native<T>(
+        &self,
+        model: &EmbeddingModel,
+        f: impl FnOnce(&native::NativeEngine) -> Result<T>,
+    ) -> Result<T> {
+        for slot in [&self.native, &self.next_native] {
+            let guard = slot.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(engine) = guard.as_ref().filter(|n| n.profile().id == model.id) {
+                return f(engine);
+            }
+        }
+        Err(GhostError::Embedding(format!(
+            "{} is not loaded",
+            model.name
+        )))
+    }
+
+    /// Generate document embeddings with a specific model (no fallback).
+    ///
+    /// Used by the re-embedding migration, which must not mix vector spaces.
+    pub async fn embed_batch_for(
+        &self,
+        model: &'static EmbeddingModel,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>> {
+        match model.backend() {
+            AiBackend::Ollama => self.ollama.embed_batch(texts).await,
+            _ => self.with_native(model, |n| n.embed_batch(texts)),
+        }
+    }
+
+    /// Generate a query embedding with a specific model (no fallback, no cache).
+    pub async fn embed_query_for(
+        &self,
+        model: &'static EmbeddingModel,
+        text: &str,
+    ) -> Result<Vec<f32>> {
+        match model.backend() {
+            AiBackend::Ollama => self.ollama.embed(text).await,
+            _ => self.with_native(model, |n| n.embed(text)),
+        }
+    }
+
     /// Check if the engine is currently loading.
     pub fn is_loading(&self) -> bool {
         *self.loading.lock().unwrap_or_else(|e| e.into_inner())
@@ -216,9 +413,8 @@ impl EmbeddingEngine {
         AiStatus {
             backend: backend.clone(),
             model_name: match &backend {
-                AiBackend::Native => "all-MiniLM-L6-v2".to_string(),
-                AiBackend::Ollama => "nomic-embed-text".to_string(),
                 AiBackend::None => "none".to_string(),
+                _ => self.active_model().name.to_string(),
             },
             dimensions: self.dimensions(),
             loading,
@@ -244,11 +440,33 @@ impl EmbeddingEngine {
         }
     }
 
-    /// Generate an embedding for a single text.
-    /// Uses the active backend: Native → Ollama.
+    /// Generate an embedding for a single text (a search query).
+    /// Recent queries are answered from the LRU cache; otherwise uses the
+    /// active backend: Native → Ollama.
     pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        // Try native first
+        let key = format!("{}\n{}", self.model_key(), cache::normalize_query(text));
+        if let Some(embedding) = self
+            .query_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
         {
+            return Ok(embedding);
+        }
+        let embedding = self.embed_uncached(text).await?;
+        // A fallback backend may have answered; only cache the expected shape
+        if embedding.len() == self.dimensions() {
+            self.query_cache
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(key, embedding.clone());
+        }
+        Ok(embedding)
+    }
+
+    async fn embed_uncached(&self, text: &str) -> Result<Vec<f32>> {
+        // Try native first (unless the vault was migrated to Ollama)
+        if self.backend() != AiBackend::Ollama {
             let native_guard = self.native.lock().unwrap_or_else(|e| e.into_inner());
             if let Some(ref native) = *native_guard {
                 match native.embed(text) {
@@ -273,7 +491,7 @@ impl EmbeddingEngine {
     /// Generate embeddings for a batch of texts.
     pub async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         // Try native first (synchronous, no HTTP overhead)
-        {
+        if self.backend() != AiBackend::Ollama {
             let native_guard = self.native.lock().unwrap_or_else(|e| e.into_inner());
             if let Some(ref native) = *native_guard {
                 match native.embed_batch(texts) {
@@ -0,0 +1,188 @@
+//! Embedding model registry.
+//!
+//! Each entry is one vector space: switching between entries re-embeds the
+//! vault (see [`super::migration`]). Native entries are BERT-family models
+//! run in-process by Candle; the Ollama entry uses a local Ollama server.
+
+use serde::Serialize;
+
+use super::AiBackend;
+
+/// How token embeddings are pooled into one sentence vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pooling {
+    /// Average over tokens, ignoring padding.
+    Mean,
+    /// The `[CLS]` token (BGE models).
+    Cls,
+}
+
+/// A selectable embedding model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EmbeddingModel {
+    /// Unique identifier (e.g., "bge-small-en-v1.5").
+    pub id: &'static str,
+    /// Human-readable name.
+    pub name: &'static str,
+    /// Short description for the UI.
+    pub description: &'static str,
+    /// HuggingFace repo with safetensors weights (native models only).
+    pub repo_id: Option<&'static str>,
+    /// Output vector size.
+    pub dimensions: usize,
+    pub pooling: Pooling,
+    /// Prepended to search queries (instruction-tuned models).
+    pub query_prefix: &'static str,
+    /// Prepended to indexed chunks.
+    pub document_prefix: &'static str,
+    /// Trained on many languages, so queries match documents across languages.
+    pub multilingual: bool,
+    /// Approximate download size in MB (0 for Ollama-served models).
+    pub size_mb: u64,
+    /// Key of this vector space in the embedding cache. The two original
+    /// models keep their historical keys so existing caches stay valid.
+    pub cache_key: &'static str,
+}
+
+impl EmbeddingModel {
+    /// The backend that runs this model.
+    pub fn backend(&self) -> AiBackend {
+        if self.repo_id.is_some() {
+            AiBackend::Native
+        } else {
+            AiBackend::Ollama
+        }
+    }
+}
+
+/// Event emitted when settings select another model; the UI then offers to
+/// re-embed the vault (payload: the requested model ID).
+pub const REEMBED_REQUIRED_EVENT: &str = "embeddings://reembed-required";
+
+/// ID of the default model.
+pub const DEFAULT_MODEL_ID: &str = "all-minilm-l6-v2";
+
+/// All selectable embedding models. The first entry is the default.
+pub const EMBEDDING_MODELS: &[EmbeddingModel] = &[
+    EmbeddingModel {
+        id: DEFAULT_MODEL_ID,
+        name: "all-MiniLM-L6-v2",
+        description: "Fast and small. Good English search quality.",
+        repo_id: Some("sentence-transformers/all-MiniLM-L6-v2"),
+        dimensions: 384,
+        pooling: Pooling::Mean,
+        query_prefix: "",
+        document_prefix: "",
+        multilingual: false,
+        size_mb: 23,
+        cache_key: "native:384",
+    },
+    EmbeddingModel {
+        id: "bge-small-en-v1.5",
+        name: "BGE Small EN v1.5",
+        description: "Same size as MiniLM with better English retrieval.",
+        repo_id: Some("BAAI/bge-small-en-v1.5"),
+        dimensions: 384,
+        pooling: Pooling::Cls,
+        query_prefix: "Represent this sentence for searching relevant passages: ",
+        document_prefix: "",
+        multilingual: false,
+        size_mb: 133,
+        cache_key: "bge-small-en-v1.5:384",
+    },
+    EmbeddingModel {
+        id: "multilingual-e5-small",
+        name: "Multilingual E5 Small",
+        description: "100 languages; finds documents written in another language than the query.",
+        repo_id: Some("intfloat/multilingual-e5-small"),
+        dimensions: 384,
+        pooling: Pooling::Mean,
+        query_prefix: "query: ",
+        document_prefix: "passage: ",
+        multilingual: true,
+        size_mb: 470,
+        cache_key: "multilingual-e5-small:384",
+    },
+    EmbeddingModel {
+        id: "nomic-embed-text",
+        name: "nomic-embed-text (Ollama)",
+        description: "Higher quality 768D vectors. Requires a running Ollama server.",
+        repo_id: None,
+        dimensions: 768,
+        pooling: Pooling::Mean,
+        query_prefix: "",
+        document_prefix: "",
+        multilingual: false,
+        size_mb: 0,
+        cache_key: "ollama:768",
+    },
+];
+
+/// Find a model by ID.
+pub fn find_model(id: &str) -> Option<&'static EmbeddingModel> {
+    EMBEDDING_MODELS.iter().find(|m| m.id == id)
+}
+
+/// The default model.
+pub fn default_model() -> &'static EmbeddingModel {
+    &EMBEDDING_MODELS[0]
+}
+
+/// Resolve a re-embedding target: a model ID, or a backend name ("native",
+/// "ollama") as stored by migrations started before the registry existed.
+pub fn find_target(name: &str) -> Option<&'static EmbeddingModel> {
+    find_model(name).or_else(|| match AiBackend::from_name(name)? {
+        AiBackend::Native => Some(default_model()),
+        AiBackend::Ollama => find_model("nomic-embed-text"),
+        AiBackend::None => None,
+    })
+}
+
+/// A registry entry with its status for the settings UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingModelInfo {
+    #[serde(flatten)]
+    pub model: EmbeddingModel,
+    /// The vault's vectors were made with this model.
+    pub active: bool,
+}
+
+/// All models, marking the one the vault currently uses.
+pub fn list_models(active_id: &str) -> Vec<EmbeddingModelInfo> {
+    EMBEDDING_MODELS
+        .iter()
+        .map(|model| EmbeddingModelInfo {
+            model: model.clone(),
+            active: model.id == active_id,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_keys_are_unique() {
+        for (i, a) in EMBEDDING_MODELS.iter().enumerate() {
+            for b in &EMBEDDING_MODELS[i + 1..] {
+                assert_ne!(a.id, b.id);
+                assert_ne!(a.cache_key, b.cache_key);
+            }
+        }
+        assert_eq!(default_model().id, DEFAULT_MODEL_ID);
+    }
+
+    #[test]
+    fn test_find_target_accepts_legacy_backend_names() {
+        assert_eq!(find_target("native").unwrap().id, DEFAULT_MODEL_ID);
+        assert_eq!(find_target("ollama").unwrap().backend(), AiBackend::Ollama);
+        assert_eq!(
+            find_target("multilingual-e5-small").unwrap().dimensions,
+            384
+        );
+        assert!(find_target("none").is_none());
+        assert!(find_target("unknown").is_none());
+    }
+}
@@ -1,7 +1,8 @@
 //! Native embedding engine using Candle (HuggingFace's Rust ML framework).
 //!
 //! Runs embedding models directly in-process without external dependencies.
-//! Supports BERT-family models in safetensors format, downloaded from HuggingFace Hub.
+//! Supports BERT-family models in safetensors format, downloaded from HuggingFace Hub
+//! (see [`super::models`] for the registry).
 
 use std::path::PathBuf;
 
@@ -11,18 +12,15 @@ use candle_transformers::models::bert::{BertModel, Config as BertConfig, DTYPE};
 use tokenizers::Tokenizer;
 
 use super::hardware;
+use super::models::{EmbeddingModel, Pooling};
 use crate::error::{GhostError, Result};
 
-/// Default model: all-MiniLM-L6-v2 — fast, small (23MB), 384-dim, excellent quality.
-const DEFAULT_MODEL_REPO: &str = "sentence-transformers/all-MiniLM-L6-v2";
-const DEFAULT_DIMENSIONS: usize = 384;
-
 /// Native embedding engine that runs models directly via Candle.
 pub struct NativeEngine {
     model: BertModel,
     tokenizer: Tokenizer,
     device: Device,
-    dimensions: usize,
+    profile: &'static EmbeddingModel,
     normalize: bool,
 }
 
@@ -32,9 +30,16 @@ impl NativeEngine {
     /// Uses the already-detected hardware info to select compute device,
     /// avoiding redundant hardware detection. GPU vs CPU is selected via
     /// `HardwareInfo::select_device("auto")` — a single unified code path.
-    pub async fn load(hw: &hardware::HardwareInfo) -> Result<Self> {
+    pub async fn load(
+        hw: &hardware::HardwareInfo,
+        profile: &'static EmbeddingModel,
+    ) -> Result<Self> {
+        let repo_id = profile.repo_id.ok_or_else(|| {
+            GhostError::NativeModel(format!("{} is not a native model", profile.name))
+        })?;
         tracing::info!(
-            "Loading native embedding model ({} cores, SIMD={}, GPU={:?})",
+            "Loading native embedding model {} ({} cores, SIMD={}, GPU={:?})",
+            profile.name,
             hw.cpu_cores,
             hw.has_simd(),
             hw.gpu_backend
@@ -45,7 +50,7 @@ impl NativeEngine {
         tracing::info!("Embedding compute device: {:?}", device);
 
         // Download or use cached model files from HuggingFace Hub
-        let (model_path, tokenizer_path, config_path) = Self::ensure_model_files().await?;
+        let (model_path, tokenizer_path, config_path) = Self::ensure_model_files(repo_id).await?;
 
         // Load config
         let config_str = std::fs::read_to_string(&config_path)
@@ -69,8 +74,8 @@ impl NativeEngine {
 
         tracing::info!(
             "Native embedding model loaded: {} ({}D, device={:?})",
-            DEFAULT_MODEL_REPO,
-            DEFAULT_DIMENSIONS,
+            repo_id,
+            profile.dimensions,
             device
         );
 
@@ -78,18 +83,32 @@ impl NativeEngine {
             model,
             tokenizer,
             device,
-            dimensions: DEFAULT_DIMENSIONS,
+            profile,
             normalize: true,
         })
     }
 
+    /// The registry entry this engine runs.
+    pub fn profile(&self) -> &'static EmbeddingModel {
+        self.profile
+    }
+
     /// Get the embedding dimensions for database schema.
     pub fn dimensions(&self) -> usize {
-        self.dimensions
+        self.profile.dimensions
     }
 
-    /// Generate an embedding for a single text.
+    /// Generate an embedding for a search query (adds the model's query prefix).
     pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        if self.profile.query_prefix.is_empty() {
+            self.embed_text(text)
+        } else {
+            self.embed_text(&format!("{}{}", self.profile.query_prefix, text))
+        }
+    }
+
+    /// Embed one text as-is.
+    fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
         let encoding = self
             .tokenizer
             .encode(text, true)
@@ -120,28 +139,7 @@ impl NativeEngine {
             .forward(&tokens, &type_ids, Some(&attention))
             .map_err(|e| GhostError::Embedding(format!("Model forward pass failed: {}", e)))?;
 
-        // Mean pooling over token dimension (ignoring padding via attention mask)
-        let attention_f = attention
-            .to_dtype(candle_core::DType::F32)
-            .map_err(|e| GhostError::Embedding(format!("Dtype conversion failed: {}", e)))?
-            .unsqueeze(2)
-            .map_err(|e| GhostError::Embedding(format!("Unsqueeze failed: {}", e)))?;
-
-        let masked = output
-            .broadcast_mul(&attention_f)
-            .map_err(|e| GhostError::Embedding(format!("Broadcast mul failed: {}", e)))?;
-
-        let summed = masked
-            .sum(1)
-            .map_err(|e| GhostError::Embedding(format!("Sum failed: {}", e)))?;
-
-        let count = attention_f
-            .sum(1)
-            .map_err(|e| GhostError::Embedding(format!("Attention sum failed: {}", e)))?;
-
-        let pooled = summed
-            .broadcast_div(&count)
-            .map_err(|e| GhostError::Embedding(format!("Division failed: {}", e)))?;
+        let pooled = self.pool(&output, &attention)?;
 
         // L2 normalization
         let embedding = if self.normalize {
@@ -169,18 +167,28 @@ impl NativeEngine {
         Ok(vec)
     }
 
-    /// Generate embeddings for a batch of texts.
+    /// Generate embeddings for a batch of documents (adds the document prefix).
     /// Uses real tensor batching for 2-5x speedup over sequential embedding.
     /// Processes in sub-batches of BATCH_SIZE to control memory usage.
     pub fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         if texts.is_empty() {
             return Ok(vec![]);
         }
+        let prefixed: Vec<String>;
+        let texts = if self.profile.document_prefix.is_empty() {
+            texts
+        } else {
+            prefixed = texts
+                .iter()
+                .map(|t| format!("{}{}", self.profile.document_prefix, t))
+                .collect();
+            &prefixed
+        };
         // For very small batches, sequential is fine
         if texts.len() <= 2 {
             let mut embeddings = Vec::with_capacity(texts.len());
             for text in texts {
-                embeddings.push(self.embed(text)?);
+                embeddings.push(self.embed_text(text)?);
             }
             return Ok(embeddings);
         }
@@ -247,25 +255,7 @@ impl NativeEngine {
             .forward(&tokens, &type_ids, Some(&attention))
             .map_err(|e| GhostError::Embedding(format!("Batch forward pass failed: {}", e)))?;
 
-        // Mean pooling per sequence
-        let attention_f = attention
-            .to_dtype(candle_core::DType::F32)
-            .map_err(|e| GhostError::Embedding(format!("Dtype conversion failed: {}", e)))?
-            .unsqueeze(2)
-            .map_err(|e| GhostError::Embedding(format!("Unsqueeze failed: {}", e)))?;
-
-        let masked = output
-            .broadcast_mul(&attention_f)
-            .map_err(|e| GhostError::Embedding(format!("Broadcast mul failed: {}", e)))?;
-        let summed = masked
-            .sum(1)
-            .map_err(|e| GhostError::Embedding(format!("Sum failed: {}", e)))?;
-        let count = attention_f
-            .sum(1)
-            .map_err(|e| GhostError::Embedding(format!("Attention sum failed: {}", e)))?;
-        let pooled = summed
-            .broadcast_div(&count)
-            .map_err(|e| GhostError::Embedding(format!("Division failed: {}", e)))?;
+        let pooled = self.pool(&output, &attention)?;
 
         // L2 normalize
         let embedding = if self.normalize {
@@ -297,17 +287,56 @@ impl NativeEngine {
         Ok(results)
     }
 
+    /// Pool token embeddings `[batch, seq, hidden]` into `[batch, hidden]`.
+    fn pool(&self, output: &Tensor, attention: &Tensor) -> Result<Tensor> {
+        if self.profile.pooling == Pooling::Cls {
+            return output
+                .narrow(1, 0, 1)
+                .and_then(|cls| cls.squeeze(1))
+                .map_err(|e| GhostError::Embedding(format!("CLS pooling failed: {}", e)));
+        }
+
+        // Mean pooling over token dimension (ignoring padding via attention mask)
+        let attention_f = attention
+            .to_dtype(candle_core::DType::F32)
+            .map_err(|e| GhostError::Embedding(format!("Dtype conversion failed: {}", e)))?
+            .unsqueeze(2)
+            .map_err(|e| GhostError::Embedding(format!("Unsqueeze failed: {}", e)))?;
+        let masked = output
+            .broadcast_mul(&attention_f)
+            .map_err(|e| GhostError::Embedding(format!("Broadcast mul failed: {}", e)))?;
+        let summed = masked
+            .sum(1)
+            .map_err(|e| GhostError::Embedding(format!("Sum failed: {}", e)))?;
+        let count = attention_f
+            .sum(1)
+            .map_err(|e| GhostError::Embedding(format!("Attention sum failed: {}", e)))?;
+        summed
+            .broadcast_div(&count)
+            .map_err(|e| GhostError::Embedding(format!("Division failed: {}", e)))
+    }
+
     /// Download model files from HuggingFace Hub if not already cached.
-    async fn ensure_model_files() -> Result<(PathBuf, PathBuf, PathBuf)> {
+    async fn ensure_model_files(repo_id: &str) -> Result<(PathBuf, PathBuf, PathBuf)> {
         let _models_dir = hardware::models_dir()?;
 
         let api = hf_hub::api::sync::Api::new().map_err(|e| {
             GhostError::NativeModel(format!("Failed to init HuggingFace Hub API: {}", e))
         })?;
 
-        let repo = api.model(DEFAULT_MODEL_REPO.to_string());
+        let repo = api.model(repo_id.to_string());
+
+        tracing::info!("Ensuring model files for {}", repo_id);
 
-        tracing::info!("Ensuring model files for {}", DEFAULT_MODEL_REPO);
+        // Weights go through the resumable download manager; hf-hub then finds them cached.
+        if !crate::downloads::is_hf_cached(repo_id, "model.safetensors") {
+            crate::downloads::manager()
+                .ensure_allowed()
+                .map_err(GhostError::NativeModel)?;
+            if let Err(e) = crate::downloads::hf_download(repo_id, "model.safetensors").await {
+                tracing::warn!("Resumable download failed ({}), falling back to hf-hub", e);
+            }
+        }
 
         let model_path = repo.get("model.safetensors").map_err(|e| {
             GhostError::NativeModel(format!(
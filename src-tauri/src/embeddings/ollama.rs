@@ -34,6 +34,12 @@ impl OllamaEngine {
         }
     }
 
+    /// Use an Ollama server at `base_url` instead of the local default.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
     /// Check if Ollama is reachable and the model is available.
     pub async fn health_check(&self) -> Result<bool> {
         let resp = self
@@ -34,6 +34,38 @@ pub enum GhostError {
 
     #[error("Agent error: {0}")]
     Agent(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+
+    #[error("Plugin error: {0}")]
+    Plugin(String),
+
+    #[error("Vault error: {0}")]
+    Vault(String),
+
+    #[error("The vault is locked")]
+    VaultLocked,
+
+    #[error("Secret error: {0}")]
+    Secret(String),
+
+    #[error("MCP error: {0}")]
+    Mcp(String),
+
+    #[error("Window error: {0}")]
+    Window(String),
+
+    /// A poisoned lock or a panicked background task.
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    #[cfg(feature = "telemetry")]
+    #[error("Telemetry error: {0}")]
+    Telemetry(String),
 }
 
 impl serde::Serialize for GhostError {
@@ -46,3 +78,248 @@ impl serde::Serialize for GhostError {
 }
 
 pub type Result<T> = std::result::Result<T, GhostError>;
+
+/// Stable error codes the frontend can branch on. Never renumbered or
+/// renamed; new variants may be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Database,
+    /// The vault is busy with another write; retrying shortly works.
+    DatabaseBusy,
+    Io,
+    FileNotFound,
+    PermissionDenied,
+    Network,
+    InvalidData,
+    Indexer,
+    Embedding,
+    Search,
+    OllamaUnavailable,
+    ModelUnavailable,
+    Chat,
+    Agent,
+    InvalidQuery,
+    Snapshot,
+    Plugin,
+    Vault,
+    /// An encrypted vault waits for its passphrase.
+    VaultLocked,
+    Secret,
+    Mcp,
+    Window,
+    /// A bug in Ghost (poisoned lock, panicked task); restarting usually helps.
+    Internal,
+    #[cfg(feature = "telemetry")]
+    Telemetry,
+    /// A plain-text error with no more specific code.
+    Unknown,
+}
+
+impl GhostError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Database(e) if is_busy(e) => ErrorCode::DatabaseBusy,
+            Self::Database(_) => ErrorCode::Database,
+            Self::Io(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => ErrorCode::FileNotFound,
+                std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+                _ => ErrorCode::Io,
+            },
+            Self::Http(_) => ErrorCode::Network,
+            Self::Json(_) => ErrorCode::InvalidData,
+            Self::Indexer(_) => ErrorCode::Indexer,
+            Self::Embedding(_) => ErrorCode::Embedding,
+            Self::Search(_) => ErrorCode::Search,
+            Self::OllamaUnavailable(_) => ErrorCode::OllamaUnavailable,
+            Self::NativeModel(_) => ErrorCode::ModelUnavailable,
+            Self::Chat(_) => ErrorCode::Chat,
+            Self::Agent(_) => ErrorCode::Agent,
+            Self::InvalidQuery(_) => ErrorCode::InvalidQuery,
+            Self::Snapshot(_) => ErrorCode::Snapshot,
+            Self::Plugin(_) => ErrorCode::Plugin,
+            Self::Vault(_) => ErrorCode::Vault,
+            Self::VaultLocked => ErrorCode::VaultLocked,
+            Self::Secret(_) => ErrorCode::Secret,
+            Self::Mcp(_) => ErrorCode::Mcp,
+            Self::Window(_) => ErrorCode::Window,
+            Self::Internal(_) => ErrorCode::Internal,
+            #[cfg(feature = "telemetry")]
+            Self::Telemetry(_) => ErrorCode::Telemetry,
+        }
+    }
+
+    /// Whether the same request may succeed if tried again later.
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self.code(),
+            ErrorCode::DatabaseBusy | ErrorCode::Network | ErrorCode::OllamaUnavailable
+        ) || matches!(self, Self::Io(e) if matches!(
+            e.kind(),
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::Interrupted
+        ))
+    }
+
+    /// What the user can do about it, when there is something.
+    pub fn hint(&self) -> Option<&'static str> {
+        Some(match self.code() {
+            ErrorCode::DatabaseBusy => "Ghost is busy writing to the vault. Try again in a moment.",
+            ErrorCode::FileNotFound => "The file or folder was moved or deleted.",
+            ErrorCode::PermissionDenied => {
+                "Ghost can't read this location. Check the folder permissions."
+            }
+            ErrorCode::Network => "Check your internet connection and try again.",
+            ErrorCode::OllamaUnavailable => {
+                "Start Ollama (ollama serve) or switch to the built-in model in Settings."
+            }
+            ErrorCode::ModelUnavailable => {
+                "The model may still be downloading. Check the status bar, or pick another model in Settings."
+            }
+            ErrorCode::InvalidQuery => "Rephrase the search or remove special characters.",
+            ErrorCode::VaultLocked => "Unlock the vault with its passphrase.",
+            ErrorCode::Secret => "Make sure the system keychain is unlocked, then try again.",
+            ErrorCode::Internal => "Something went wrong inside Ghost. Restarting the app should fix it.",
+            _ => return None,
+        })
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for GhostError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for GhostError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Self::Internal(format!("Background task failed: {}", e))
+    }
+}
+
+impl From<tauri::Error> for GhostError {
+    fn from(e: tauri::Error) -> Self {
+        Self::Window(e.to_string())
+    }
+}
+
+impl From<tauri_plugin_opener::Error> for GhostError {
+    fn from(e: tauri_plugin_opener::Error) -> Self {
+        use tauri_plugin_opener::Error as OpenerError;
+
+        match e {
+            OpenerError::Io(e) => Self::Io(e),
+            OpenerError::Tauri(e) => e.into(),
+            e @ (OpenerError::ForbiddenPath { .. } | OpenerError::ForbiddenUrl { .. }) => Self::Io(
+                std::io::Error::new(std::io::ErrorKind::PermissionDenied, e.to_string()),
+            ),
+            e => Self::Io(std::io::Error::other(e.to_string())),
+        }
+    }
+}
+
+fn is_busy(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Error returned by Tauri commands: a stable code plus guidance, instead of
+/// a bare string.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+    pub hint: Option<String>,
+}
+
+impl From<GhostError> for CommandError {
+    fn from(e: GhostError) -> Self {
+        Self {
+            code: e.code(),
+            retryable: e.retryable(),
+            hint: e.hint().map(str::to_string),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for CommandError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        GhostError::from(e).into()
+    }
+}
+
+impl From<tokio::task::JoinError> for CommandError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        GhostError::from(e).into()
+    }
+}
+
+impl From<tauri::Error> for CommandError {
+    fn from(e: tauri::Error) -> Self {
+        GhostError::from(e).into()
+    }
+}
+
+impl From<tauri_plugin_opener::Error> for CommandError {
+    fn from(e: tauri_plugin_opener::Error) -> Self {
+        GhostError::from(e).into()
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self {
+            code: ErrorCode::Unknown,
+            message,
+            retryable: false,
+            hint: None,
+        }
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// Result of a Tauri command that reports [`CommandError`]s.
+pub type CommandResult<T> = std::result::Result<T, CommandError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let missing = GhostError::Io(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(missing.code(), ErrorCode::FileNotFound);
+        assert!(!missing.retryable());
+        assert!(missing.hint().is_some());
+
+        let ollama = CommandError::from(GhostError::OllamaUnavailable("refused".into()));
+        assert_eq!(ollama.code, ErrorCode::OllamaUnavailable);
+        assert!(ollama.retryable);
+        assert_eq!(
+            serde_json::to_value(&ollama).unwrap()["code"],
+            "ollama_unavailable"
+        );
+
+        let plain = CommandError::from("boom".to_string());
+        assert_eq!(plain.code, ErrorCode::Unknown);
+        assert_eq!(plain.message, "boom");
+        assert_eq!(CommandError::from("boom").message, "boom");
+
+        let lock = std::sync::Mutex::new(());
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = lock.lock().unwrap();
+            panic!("poison the lock");
+        });
+        let poisoned = CommandError::from(lock.lock().unwrap_err());
+        assert_eq!(poisoned.code, ErrorCode::Internal);
+        assert!(poisoned.hint.is_some());
+    }
+}
@@ -26,6 +26,13 @@ pub trait PlatformExtensions: Send + Sync {
     fn initialize(&self) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Called with the key of a newly encrypted vault. Pro escrows it for
+    /// recovery and syncs it between devices; the community edition keeps
+    /// nothing, so a forgotten passphrase can't be recovered.
+    fn escrow_vault_key(&self, _vault_id: &str, _key: &[u8; 32]) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
 /// Community (free) edition — default no-op implementation.
@@ -0,0 +1,155 @@
+//! System health and degraded-capability reporting.
+//!
+//! Ghost keeps working when an optional capability is missing: without
+//! sqlite-vec, search falls back to FTS5 keyword search; without an embedding
+//! model, nothing new gets embedded. Instead of leaving only a log line, each
+//! capability is reported with why it is degraded and what the user can do
+//! about it — through `get_system_health`, and as a `capability-status`
+//! event at startup and whenever a retry changes its state.
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::embeddings::{AiBackend, AiStatus};
+
+/// Tauri event carrying a [`CapabilityStatus`].
+pub const CAPABILITY_EVENT: &str = "capability-status";
+
+/// State of one optional capability.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityStatus {
+    /// Stable identifier: "vector_search" or "embeddings".
+    pub capability: &'static str,
+    pub available: bool,
+    /// Why the capability is unavailable.
+    pub reason: Option<String>,
+    /// What the user loses while it is unavailable.
+    pub impact: Option<String>,
+    /// What the user can do about it.
+    pub remediation: Option<String>,
+    /// Command that retries enabling the capability without a restart.
+    pub retry_command: Option<&'static str>,
+}
+
+/// Health report returned by `get_system_health`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemHealth {
+    /// Whether any capability is unavailable.
+    pub degraded: bool,
+    pub capabilities: Vec<CapabilityStatus>,
+}
+
+/// Current state of sqlite-vec vector search.
+pub fn vector_search_status(db: &Database) -> CapabilityStatus {
+    vector_search_from_error(db.vec_error())
+}
+
+fn vector_search_from_error(error: Option<String>) -> CapabilityStatus {
+    let Some(reason) = error else {
+        return CapabilityStatus {
+            capability: "vector_search",
+            available: true,
+            reason: None,
+            impact: None,
+            remediation: None,
+            retry_command: None,
+        };
+    };
+    CapabilityStatus {
+        capability: "vector_search",
+        available: false,
+        impact: Some(
+            "Search matches keywords only; results by meaning and related-document \
+             suggestions are unavailable."
+                .into(),
+        ),
+        remediation: Some(vec_remediation(&reason).into()),
+        reason: Some(reason),
+        retry_command: Some("retry_enable_vec"),
+    }
+}
+
+/// Remediation hint for a sqlite-vec load error.
+fn vec_remediation(error: &str) -> &'static str {
+    let error = error.to_lowercase();
+    if error.contains("locked") || error.contains("busy") {
+        "The vault was busy while the vector index was being created. Retry in a moment."
+    } else if error.contains("chunks_vec") {
+        "The vector index could not be created. Check that the disk has free space and \
+         the Ghost data folder is writable, then retry."
+    } else {
+        "The sqlite-vec extension did not load. This usually means the install is \
+         incomplete or security software blocked it. Retry, and if that fails, \
+         reinstall Ghost or allow it in your antivirus."
+    }
+}
+
+/// Current state of the embedding engine.
+pub fn embeddings_status(ai: &AiStatus) -> CapabilityStatus {
+    let (available, reason, remediation) = match (&ai.backend, ai.loading) {
+        (AiBackend::None, true) => (
+            false,
+            "The embedding model is still loading.",
+            "Wait for the model to finish loading; new files are embedded afterwards.",
+        ),
+        (AiBackend::None, false) => (
+            false,
+            "No embedding engine is available.",
+            "Ghost could not load its built-in model or reach Ollama. Check your network \
+             for the first model download, or start Ollama.",
+        ),
+        _ => (true, "", ""),
+    };
+    CapabilityStatus {
+        capability: "embeddings",
+        available,
+        reason: (!available).then(|| reason.into()),
+        impact: (!available)
+            .then(|| "New and changed files are indexed for keyword search only.".into()),
+        remediation: (!available).then(|| remediation.into()),
+        retry_command: None,
+    }
+}
+
+/// Full health report.
+pub fn system_health(db: &Database, ai: &AiStatus) -> SystemHealth {
+    let capabilities = vec![vector_search_status(db), embeddings_status(ai)];
+    SystemHealth {
+        degraded: capabilities.iter().any(|c| !c.available),
+        capabilities,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_search_status() {
+        let ok = vector_search_from_error(None);
+        assert!(ok.available);
+        assert!(ok.remediation.is_none());
+
+        let missing = vector_search_from_error(Some(
+            "sqlite-vec not available: no such function: vec_version".into(),
+        ));
+        assert!(!missing.available);
+        assert_eq!(missing.retry_command, Some("retry_enable_vec"));
+        assert!(missing.remediation.unwrap().contains("did not load"));
+
+        let table = vector_search_from_error(Some(
+            "Failed to create chunks_vec table: disk I/O error".into(),
+        ));
+        assert!(table.remediation.unwrap().contains("free space"));
+    }
+
+    #[test]
+    fn test_vector_search_status_matches_db() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(vector_search_status(&db).available, db.is_vec_enabled());
+        // Retrying on a working vault is a no-op
+        if db.is_vec_enabled() {
+            assert!(db.retry_enable_vec().unwrap());
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Built-in help — answers questions about Ghost itself.
+//!
+//! Ships a small knowledge pack (`knowledge.md`, embedded at compile time)
+//! split into topics by `##` headings. Questions are matched against topics
+//! with an in-memory IDF-weighted keyword index, and the best topics are
+//! handed to the local chat model to phrase an answer. When no model is
+//! available, the matching topics are returned verbatim.
+//!
+//! The pack is never written to the user's vault, so it doesn't pollute
+//! file search results.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use serde::Serialize;
+
+use crate::chat::{ChatEngine, ChatMessage};
+
+/// The embedded knowledge pack.
+const KNOWLEDGE_PACK: &str = include_str!("knowledge.md");
+
+/// Maximum number of topics used to answer a question.
+const MAX_TOPICS: usize = 3;
+
+/// Max tokens for a generated help answer.
+pub const HELP_MAX_TOKENS: usize = 384;
+
+/// Words too common to carry meaning in a help question.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "can", "do", "does", "for", "from", "how", "i", "in", "is", "it",
+    "my", "of", "on", "or", "the", "to", "what", "when", "where", "why", "with", "you", "ghost",
+];
+
+/// One help topic (a `##` section of the knowledge pack).
+#[derive(Debug, Clone, Serialize)]
+pub struct HelpTopic {
+    pub title: String,
+    pub body: String,
+}
+
+/// Answer to a help question.
+#[derive(Debug, Clone, Serialize)]
+pub struct HelpAnswer {
+    /// The answer text.
+    pub answer: String,
+    /// Titles of the topics the answer is based on.
+    pub sources: Vec<String>,
+    /// True if a model phrased the answer; false if topics were returned verbatim.
+    pub generated: bool,
+}
+
+/// In-memory keyword index over the knowledge pack.
+struct HelpIndex {
+    topics: Vec<HelpTopic>,
+    /// Distinct terms per topic (title terms are counted twice as heavy at query time).
+    terms: Vec<HashSet<String>>,
+    title_terms: Vec<HashSet<String>>,
+    /// Inverse document frequency per term.
+    idf: HashMap<String, f64>,
+}
+
+static INDEX: LazyLock<HelpIndex> = LazyLock::new(|| HelpIndex::build(KNOWLEDGE_PACK));
+
+impl HelpIndex {
+    fn build(pack: &str) -> Self {
+        let topics = parse_topics(pack);
+        let terms: Vec<HashSet<String>> = topics
+            .iter()
+            .map(|t| tokenize(&format!("{} {}", t.title, t.body)))
+            .collect();
+        let title_terms: Vec<HashSet<String>> = topics.iter().map(|t| tokenize(&t.title)).collect();
+
+        let mut df: HashMap<String, usize> = HashMap::new();
+        for set in &terms {
+            for term in set {
+                *df.entry(term.clone()).or_default() += 1;
+            }
+        }
+        let n = topics.len().max(1) as f64;
+        let idf = df
+            .into_iter()
+            .map(|(term, count)| (term, (1.0 + n / count as f64).ln()))
+            .collect();
+
+        Self {
+            topics,
+            terms,
+            title_terms,
+            idf,
+        }
+    }
+
+    /// Rank topics for a question, best first. Topics with no overlap are dropped.
+    fn search(&self, question: &str, limit: usize) -> Vec<&HelpTopic> {
+        let query = tokenize(question);
+        let mut scored: Vec<(f64, usize)> = self
+            .terms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, set)| {
+                let score: f64 = query
+                    .iter()
+                    .filter(|q| set.contains(*q))
+                    .map(|q| {
+                        let idf = self.idf.get(q).copied().unwrap_or(0.0);
+                        if self.title_terms[i].contains(q) {
+                            idf * 2.0
+                        } else {
+                            idf
+                        }
+                    })
+                    .sum();
+                (score > 0.0).then_some((score, i))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, i)| &self.topics[i])
+            .collect()
+    }
+}
+
+/// Split the knowledge pack into topics at `##` headings.
+fn parse_topics(pack: &str) -> Vec<HelpTopic> {
+    let mut topics = Vec::new();
+    let mut current: Option<HelpTopic> = None;
+
+    for line in pack.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            topics.extend(current.take());
+            current = Some(HelpTopic {
+                title: title.trim().to_string(),
+                body: String::new(),
+            });
+        } else if let Some(ref mut topic) = current {
+            if !topic.body.is_empty() || !line.trim().is_empty() {
+                topic.body.push_str(line);
+                topic.body.push('\n');
+            }
+        }
+    }
+    topics.extend(current);
+
+    for topic in &mut topics {
+        topic.body = topic.body.trim().to_string();
+    }
+    topics
+}
+
+/// Lowercase alphanumeric terms with stopwords and naive plural "s" removed.
+fn tokenize(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|w| w.to_lowercase())
+        .filter(|w| w.len() > 1 && !STOPWORDS.contains(&w.as_str()))
+        .map(|w| {
+            if w.len() > 3 && w.ends_with('s') && !w.ends_with("ss") {
+                w[..w.len() - 1].to_string()
+            } else {
+                w
+            }
+        })
+        .collect()
+}
+
+/// Find the help topics most relevant to a question.
+pub fn find_topics(question: &str) -> Vec<HelpTopic> {
+    INDEX
+        .search(question, MAX_TOPICS)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Messages asking a model to answer `question` from the given help topics.
+pub fn prompt(question: &str, topics: &str) -> Vec<ChatMessage> {
+    vec![
+        ChatMessage {
+            role: "system".into(),
+            content: format!(
+                "You are Ghost's built-in help. Answer the user's question about Ghost \
+                 in 2-4 sentences using ONLY the help topics below. If the topics don't \
+                 answer it, say so.\n\n<help>\n{}\n</help>",
+                topics
+            ),
+        },
+        ChatMessage {
+            role: "user".into(),
+            content: question.to_string(),
+        },
+    ]
+}
+
+/// Answer a question about Ghost using the knowledge pack and the local model.
+pub async fn answer(chat_engine: &ChatEngine, question: &str) -> HelpAnswer {
+    let matches = find_topics(question);
+    if matches.is_empty() {
+        return HelpAnswer {
+            answer: "I couldn't find anything about that in Ghost's help. \
+                     Try rephrasing, or check the documentation at \
+                     https://github.com/ghostapp-ai/ghost."
+                .to_string(),
+            sources: Vec::new(),
+            generated: false,
+        };
+    }
+
+    let sources: Vec<String> = matches.iter().map(|t| t.title.clone()).collect();
+    let context = matches
+        .iter()
+        .map(|t| format!("## {}\n{}", t.title, t.body))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let messages = prompt(question, &context);
+    match chat_engine.chat(&messages, HELP_MAX_TOKENS).await {
+        Ok(response) if !response.content.trim().is_empty() => HelpAnswer {
+            answer: response.content.trim().to_string(),
+            sources,
+            generated: true,
+        },
+        Ok(_) => HelpAnswer {
+            answer: context,
+            sources,
+            generated: false,
+        },
+        Err(e) => {
+            tracing::debug!("Help answer generation unavailable: {}", e);
+            HelpAnswer {
+                answer: context,
+                sources,
+                generated: false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_knowledge_pack_parses() {
+        let topics = &INDEX.topics;
+        assert!(topics.len() >= 10, "Expected a full knowledge pack");
+        assert!(topics
+            .iter()
+            .all(|t| !t.title.is_empty() && !t.body.is_empty()));
+    }
+
+    #[test]
+    fn test_parse_topics_ignores_preamble() {
+        let topics = parse_topics("# Title\nintro\n\n## One\nfirst\n\n## Two\nsecond\n");
+        assert_eq!(topics.len(), 2);
+        assert_eq!(topics[0].title, "One");
+        assert_eq!(topics[0].body, "first");
+        assert_eq!(topics[1].body, "second");
+    }
+
+    #[test]
+    fn test_tokenize_drops_stopwords_and_plurals() {
+        let terms = tokenize("How do I exclude folders?");
+        assert!(terms.contains("exclude"));
+        assert!(terms.contains("folder"));
+        assert!(!terms.contains("how"));
+        assert!(!terms.contains("i"));
+    }
+
+    #[test]
+    fn test_find_exclude_folder_topic() {
+        let found = find_topics("how do I exclude a folder?");
+        assert!(!found.is_empty());
+        assert_eq!(found[0].title, "Exclude or stop indexing a folder");
+    }
+
+    #[test]
+    fn test_find_shortcut_topic() {
+        let found = find_topics("what is the keyboard shortcut");
+        assert_eq!(found[0].title, "Open Ghost with the keyboard shortcut");
+    }
+
+    #[test]
+    fn test_no_match() {
+        assert!(find_topics("zzzz qqqq").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_answer_without_match_is_honest() {
+        let state = crate::test_app_state();
+        let result = answer(&state.chat_engine, "zzzz qqqq").await;
+        assert!(!result.generated);
+        assert!(result.sources.is_empty());
+        assert!(result.answer.contains("couldn't find"));
+    }
+}
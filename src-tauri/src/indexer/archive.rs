@@ -0,0 +1,438 @@
+//! Text of the files inside `.zip`, `.tar`, `.tar.gz`/`.tgz` and `.gz`
+//! archives, one level deep.
+//!
+//! An archive is indexed as one document: a listing of its members, then one
+//! section per member in a supported format, titled with the member's path
+//! inside the archive. Search results thus name the archive (the document
+//! path) and the file within it (the chunk section). Members are read into
+//! memory and extracted from there, so nothing is unpacked to disk. Nested
+//! archives are listed but not opened, members that can't be read (such as
+//! encrypted ZIP entries) are listed but skipped, and size caps guard
+//! against archive bombs.
+
+use std::io::Read;
+use std::path::Path;
+
+use super::extractor::{self, Section};
+use crate::error::{GhostError, Result};
+
+/// Members listed per archive. ZIP archives count the rest from their
+/// central directory; tar streams stop reading here.
+const MAX_MEMBERS: usize = 1000;
+
+/// Largest member (uncompressed) whose text is extracted.
+const MAX_MEMBER_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Uncompressed bytes extracted per archive, across members. Tar streams
+/// also stop once this many bytes were read past, extracted or not, since
+/// skipping a member of a `.tar.gz` still decompresses it.
+const MAX_EXTRACTED_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Whether an extension is an archive handled by [`extract_archive`].
+pub fn is_archive_extension(extension: &str) -> bool {
+    matches!(
+        extension.to_lowercase().as_str(),
+        "zip" | "tar" | "tgz" | "gz"
+    )
+}
+
+/// A file inside an archive.
+struct Member {
+    /// Path inside the archive.
+    name: String,
+    /// Uncompressed size, unless unknown (a `.gz` file not extracted).
+    size: Option<u64>,
+    /// Contents, if small enough and in a supported format.
+    data: Option<Vec<u8>>,
+}
+
+/// The members read from an archive.
+struct Contents {
+    members: Vec<Member>,
+    /// Files in the archive, or seen before reading stopped.
+    total: usize,
+    /// Reading stopped before the end of the archive, so `total` is a lower bound.
+    partial: bool,
+}
+
+/// List an archive and extract the text of its supported members.
+pub fn extract_archive(path: &Path) -> Result<Vec<Section>> {
+    let filename = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let file = std::fs::File::open(path)
+        .map_err(|e| GhostError::Indexer(format!("Failed to open {}: {}", path.display(), e)))?;
+    let reader = std::io::BufReader::new(file);
+
+    let Contents {
+        members,
+        total,
+        partial,
+    } = if filename.ends_with(".zip") {
+        read_zip(reader)
+    } else if filename.ends_with(".tar") {
+        read_tar(reader)
+    } else if filename.ends_with(".tar.gz") || filename.ends_with(".tgz") {
+        read_tar(flate2::read::GzDecoder::new(reader))
+    } else {
+        // A single compressed file: `notes.txt.gz` holds `notes.txt`
+        let name = filename.trim_end_matches(".gz").to_string();
+        read_gzip(reader, name)
+    }
+    .map_err(|e| {
+        GhostError::Indexer(format!("Failed to read archive {}: {}", path.display(), e))
+    })?;
+    if members.is_empty() {
+        return Err(GhostError::Indexer(format!(
+            "Empty archive: {}",
+            path.display()
+        )));
+    }
+
+    let mut listing = String::from("Files in this archive:\n");
+    for member in &members {
+        match member.size {
+            Some(size) => listing.push_str(&format!("{} ({})\n", member.name, format_size(size))),
+            None => listing.push_str(&format!("{}\n", member.name)),
+        }
+    }
+    if total > members.len() {
+        let bound = if partial { "at least " } else { "" };
+        listing.push_str(&format!(
+            "... and {}{} more\n",
+            bound,
+            total - members.len()
+        ));
+    }
+    let mut sections = vec![Section {
+        title: None,
+        text: listing,
+    }];
+
+    for member in members {
+        let Some(data) = member.data else {
+            continue;
+        };
+        match extractor::extract_bytes(&member.name, &data) {
+            Ok(text) if !text.trim().is_empty() => sections.push(Section {
+                title: Some(member.name),
+                text: text.trim().to_string(),
+            }),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("Skipping {} in {}: {}", member.name, path.display(), e),
+        }
+    }
+    Ok(sections)
+}
+
+/// Whether a member's contents should be read: a supported, non-archive
+/// file that isn't OS metadata.
+fn is_extractable(name: &str) -> bool {
+    if name.starts_with("__MACOSX/") || name.rsplit('/').next().is_some_and(|f| f.starts_with('.'))
+    {
+        return false;
+    }
+    member_extension(name)
+        .is_some_and(|ext| extractor::is_supported_extension(&ext) && !is_archive_extension(&ext))
+}
+
+fn member_extension(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+}
+
+/// Read up to `size` bytes of a member, if it fits the remaining budget.
+fn read_member(
+    reader: impl Read,
+    name: &str,
+    size: u64,
+    budget: &mut u64,
+) -> std::io::Result<Option<Vec<u8>>> {
+    if !is_extractable(name) || size > MAX_MEMBER_BYTES || size > *budget {
+        return Ok(None);
+    }
+    // Declared sizes can lie; never read past the cap or the budget
+    let limit = MAX_MEMBER_BYTES.min(*budget);
+    let mut data = Vec::with_capacity(size as usize);
+    reader.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Ok(None);
+    }
+    *budget -= data.len() as u64;
+    Ok(Some(data))
+}
+
+/// Members of a ZIP archive, and how many files it holds in total.
+fn read_zip(reader: impl Read + std::io::Seek) -> std::io::Result<Contents> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut members = Vec::new();
+    let mut total = 0;
+    let mut budget = MAX_EXTRACTED_BYTES;
+    for i in 0..archive.len() {
+        // Raw entries need no decryption, so every file can be listed
+        let (name, size) = match archive.by_index_raw(i) {
+            Ok(entry) if entry.is_dir() => continue,
+            Ok(entry) => (entry.name().to_string(), entry.size()),
+            Err(e) => {
+                tracing::warn!("Skipping unreadable ZIP entry {}: {}", i, e);
+                continue;
+            }
+        };
+        total += 1;
+        if members.len() >= MAX_MEMBERS {
+            continue;
+        }
+        let data = match archive.by_index(i) {
+            Ok(entry) => read_member(entry, &name, size, &mut budget).unwrap_or_else(|e| {
+                tracing::warn!("Skipping ZIP entry {}: {}", name, e);
+                None
+            }),
+            Err(e) => {
+                tracing::warn!("Skipping ZIP entry {}: {}", name, e);
+                None
+            }
+        };
+        members.push(Member {
+            name,
+            size: Some(size),
+            data,
+        });
+    }
+    Ok(Contents {
+        members,
+        total,
+        partial: false,
+    })
+}
+
+/// Members of a tar archive, and how many files it holds in total. Tar
+/// streams have no index, so reading stops at [`MAX_MEMBERS`] members or
+/// [`MAX_EXTRACTED_BYTES`] of member data.
+fn read_tar(reader: impl Read) -> std::io::Result<Contents> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    let mut total = 0;
+    let mut budget = MAX_EXTRACTED_BYTES;
+    let mut scanned = 0u64;
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        total += 1;
+        // Moving to the next entry reads past this one's data
+        scanned = scanned.saturating_add(entry.size());
+        if members.len() >= MAX_MEMBERS || scanned > MAX_EXTRACTED_BYTES {
+            return Ok(Contents {
+                members,
+                total,
+                partial: true,
+            });
+        }
+        let name = entry
+            .path()?
+            .to_string_lossy()
+            .trim_start_matches("./")
+            .to_string();
+        let size = entry.size();
+        let data = read_member(entry, &name, size, &mut budget)?;
+        members.push(Member {
+            name,
+            size: Some(size),
+            data,
+        });
+    }
+    Ok(Contents {
+        members,
+        total,
+        partial: false,
+    })
+}
+
+/// The single file of a gzip-compressed file.
+fn read_gzip(reader: impl Read, name: String) -> std::io::Result<Contents> {
+    let decoder = flate2::read::GzDecoder::new(reader);
+    let mut budget = MAX_EXTRACTED_BYTES;
+    // The uncompressed size is only known after reading, so it's left
+    // unknown rather than decompressing a file that isn't extracted
+    let data = read_member(decoder, &name, 0, &mut budget)?;
+    let size = data.as_ref().map(|data| data.len() as u64);
+    Ok(Contents {
+        members: vec![Member { name, size, data }],
+        total: 1,
+        partial: false,
+    })
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_extract_zip() {
+        let dir = std::env::temp_dir().join("ghost_test_archive_zip");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("project.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("docs/plan.md", options).unwrap();
+        zip.write_all(b"# Plan\n\nShip the quarterly budget report.")
+            .unwrap();
+        zip.start_file("logo.png", options).unwrap();
+        zip.write_all(&[0x89, b'P', b'N', b'G']).unwrap();
+        zip.start_file("inner.zip", options).unwrap();
+        zip.write_all(b"PK").unwrap();
+        zip.start_file("__MACOSX/docs/._plan.md", options).unwrap();
+        zip.write_all(b"junk").unwrap();
+        zip.finish().unwrap();
+
+        let sections = extract_archive(&path).unwrap();
+        assert!(sections[0].title.is_none());
+        assert!(sections[0].text.contains("docs/plan.md ("));
+        assert!(sections[0].text.contains("logo.png"));
+        assert!(sections[0].text.contains("inner.zip"));
+        // Only the Markdown file has text; the nested archive isn't opened
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1].title.as_deref(), Some("docs/plan.md"));
+        assert!(sections[1].text.contains("quarterly budget"));
+        assert!(extractor::extract_text(&path)
+            .unwrap()
+            .contains("quarterly budget"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_extract_tar_gz() {
+        let dir = std::env::temp_dir().join("ghost_test_archive_tgz");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("backup.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(encoder);
+        let text = b"Meeting notes: Dana approved the budget.";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(text.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "./notes/meeting.txt", &text[..])
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let sections = extract_archive(&path).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[1].title.as_deref(), Some("notes/meeting.txt"));
+        assert!(sections[1].text.contains("Dana approved"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_tar_stops_at_member_cap() {
+        let mut tar = tar::Builder::new(Vec::new());
+        for i in 0..MAX_MEMBERS + 5 {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(1);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, format!("f{}.txt", i), &b"x"[..])
+                .unwrap();
+        }
+        let data = tar.into_inner().unwrap();
+
+        let contents = read_tar(&data[..]).unwrap();
+        assert_eq!(contents.members.len(), MAX_MEMBERS);
+        assert_eq!(contents.total, MAX_MEMBERS + 1);
+        assert!(contents.partial);
+    }
+
+    #[test]
+    fn test_extract_gzip_file() {
+        let dir = std::env::temp_dir().join("ghost_test_archive_gz");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.log.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"ERROR disk full on /var").unwrap();
+        encoder.finish().unwrap();
+
+        let sections = extract_archive(&path).unwrap();
+        assert!(sections[0].text.contains("server.log (23 B)"));
+        assert_eq!(sections[1].title.as_deref(), Some("server.log"));
+        assert!(sections[1].text.contains("disk full"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_zip_skips_encrypted_member() {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("secret.txt", options).unwrap();
+        zip.write_all(b"hidden").unwrap();
+        zip.start_file("notes.txt", options).unwrap();
+        zip.write_all(b"visible").unwrap();
+        let mut data = zip.finish().unwrap().into_inner();
+        // Flag the first entry as encrypted in its local and central headers
+        let local = data.windows(4).position(|w| w == b"PK\x03\x04").unwrap();
+        data[local + 6] |= 1;
+        let central = data.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        data[central + 8] |= 1;
+
+        let contents = read_zip(std::io::Cursor::new(data)).unwrap();
+        assert_eq!(contents.total, 2);
+        assert_eq!(contents.members[0].name, "secret.txt");
+        assert_eq!(contents.members[0].data, None);
+        assert_eq!(contents.members[1].data.as_deref(), Some(&b"visible"[..]));
+    }
+
+    #[test]
+    fn test_gzip_size_unknown_when_not_extracted() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[0u8; 64]).unwrap();
+        let data = encoder.finish().unwrap();
+
+        let contents = read_gzip(&data[..], "photo.jpg".to_string()).unwrap();
+        assert_eq!(contents.members[0].size, None);
+        assert_eq!(contents.members[0].data, None);
+    }
+
+    #[test]
+    fn test_is_extractable() {
+        assert!(is_extractable("a/b/report.pdf"));
+        assert!(!is_extractable("a/.DS_Store"));
+        assert!(!is_extractable("__MACOSX/a/._report.pdf"));
+        assert!(!is_extractable("nested.tar"));
+        assert!(!is_extractable("photo.jpg"));
+    }
+
+    #[test]
+    fn test_read_member_budget_ignores_declared_size() {
+        let mut budget = 10;
+        // Declared as empty, but larger than the remaining budget
+        let data = read_member(&[b'x'; 20][..], "a.txt", 0, &mut budget).unwrap();
+        assert_eq!(data, None);
+        assert_eq!(budget, 10);
+        let data = read_member(&[b'x'; 6][..], "b.txt", 0, &mut budget).unwrap();
+        assert_eq!(data.map(|d| d.len()), Some(6));
+        assert_eq!(budget, 4);
+    }
+}
@@ -1,6 +1,15 @@
+use std::sync::{LazyLock, RwLock};
+
+use super::extractor::Section;
+
+/// Smallest configurable chunk size, in tokens.
+const MIN_CHUNK_SIZE: usize = 64;
+
+/// Largest configurable chunk size; longer chunks exceed embedding model context.
+const MAX_CHUNK_SIZE: usize = 2048;
+
 /// Approximate token count by splitting on whitespace.
 /// This is a rough estimate (~1.3 tokens per word for English).
-#[allow(dead_code)]
 pub fn estimate_tokens(text: &str) -> usize {
     text.split_whitespace().count()
 }
@@ -21,6 +30,8 @@ pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<ChunkInf
             index: 0,
             content: words.join(" "),
             token_count: words.len() as i32,
+            section: None,
+            symbol: None,
         }];
     }
 
@@ -38,6 +49,8 @@ pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<ChunkInf
             index,
             content,
             token_count: chunk_words.len() as i32,
+            section: None,
+            symbol: None,
         });
 
         start += step;
@@ -51,9 +64,66 @@ pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<ChunkInf
     chunks
 }
 
-/// Default chunking with 512 tokens and 64 overlap.
+/// Target chunk size and overlap, in estimated tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkConfig {
+    pub chunk_size: usize,
+    pub overlap: usize,
+}
+
+impl ChunkConfig {
+    /// Clamp user-provided values to a usable range: the size to
+    /// 64..=2048 tokens, the overlap to at most half the size.
+    pub fn new(chunk_size: usize, overlap: usize) -> Self {
+        let chunk_size = chunk_size.clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE);
+        Self {
+            chunk_size,
+            overlap: overlap.min(chunk_size / 2),
+        }
+    }
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 512,
+            overlap: 64,
+        }
+    }
+}
+
+static CONFIG: LazyLock<RwLock<ChunkConfig>> = LazyLock::new(Default::default);
+
+/// Set the chunk size and overlap used for newly indexed files (from Settings).
+pub fn configure(config: ChunkConfig) {
+    if let Ok(mut current) = CONFIG.write() {
+        *current = config;
+    }
+}
+
+/// The configured chunk size and overlap.
+pub fn current() -> ChunkConfig {
+    CONFIG.read().map(|c| *c).unwrap_or_default()
+}
+
+/// Default chunking with the configured size and overlap (512/64 unless changed).
 pub fn chunk_text_default(text: &str) -> Vec<ChunkInfo> {
-    chunk_text(text, 512, 64)
+    let config = current();
+    chunk_text(text, config.chunk_size, config.overlap)
+}
+
+/// Default chunking of each section separately, tagging chunks with the
+/// section title. Chunks never span two sections.
+pub fn chunk_sections(sections: &[Section]) -> Vec<ChunkInfo> {
+    let mut chunks = Vec::new();
+    for section in sections {
+        for mut chunk in chunk_text_default(&section.text) {
+            chunk.index = chunks.len() as i32;
+            chunk.section = section.title.clone();
+            chunks.push(chunk);
+        }
+    }
+    chunks
 }
 
 #[derive(Debug, Clone)]
@@ -61,6 +131,10 @@ pub struct ChunkInfo {
     pub index: i32,
     pub content: String,
     pub token_count: i32,
+    /// Title of the section (e.g. e-book chapter) this chunk belongs to.
+    pub section: Option<String>,
+    /// Code symbol defined in this chunk (e.g. "Database::open").
+    pub symbol: Option<String>,
 }
 
 #[cfg(test)]
@@ -99,4 +173,41 @@ mod tests {
         assert_eq!(chunks[0].index, 0);
         assert_eq!(chunks[1].index, 1);
     }
+
+    #[test]
+    fn test_chunk_config_clamped() {
+        assert_eq!(ChunkConfig::new(512, 64), ChunkConfig::default());
+        assert_eq!(
+            ChunkConfig::new(10, 64),
+            ChunkConfig {
+                chunk_size: 64,
+                overlap: 32
+            }
+        );
+        assert_eq!(ChunkConfig::new(100_000, 0).chunk_size, 2048);
+    }
+
+    #[test]
+    fn test_chunk_sections() {
+        let long: Vec<String> = (0..600).map(|i| format!("w{}", i)).collect();
+        let sections = vec![
+            Section {
+                title: Some("Chapter 1: Start".into()),
+                text: long.join(" "),
+            },
+            Section {
+                title: Some("Chapter 2: End".into()),
+                text: "the end".into(),
+            },
+        ];
+        let chunks = chunk_sections(&sections);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks.iter().map(|c| c.index).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(chunks[1].section.as_deref(), Some("Chapter 1: Start"));
+        assert_eq!(chunks[2].section.as_deref(), Some("Chapter 2: End"));
+        assert_eq!(chunks[2].content, "the end");
+    }
 }
@@ -0,0 +1,412 @@
+//! Code-aware chunking with tree-sitter.
+//!
+//! Source files are split on definition boundaries (functions, methods,
+//! classes, impls, traits, interfaces) instead of arbitrary word windows, so
+//! each chunk holds one whole definition and records its symbol name
+//! (`Database::open`, `Parser.parse`). Search uses the symbol to rank a
+//! definition above chunks that merely mention it.
+//!
+//! Definitions keep their leading doc comments and attributes. Code between
+//! definitions (imports, constants, statements) is grouped into chunks without
+//! a symbol. Containers too large for one chunk are split into their members,
+//! which are qualified with the container's name. Unsupported languages and
+//! files that fail to parse fall back to plain word chunking.
+
+use tree_sitter::{Language, Node, Parser};
+
+use super::chunker::{self, chunk_text, estimate_tokens, ChunkInfo};
+
+/// Languages with a tree-sitter grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lang {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+}
+
+impl Lang {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "mjs" | "cjs" | "jsx" => Some(Self::JavaScript),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            _ => None,
+        }
+    }
+
+    fn language(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        }
+    }
+
+    /// Separator between a container and member name.
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            _ => ".",
+        }
+    }
+}
+
+/// Whether files with this extension get code-aware chunking.
+pub fn is_code_extension(extension: &str) -> bool {
+    Lang::from_extension(extension).is_some()
+}
+
+/// A named definition found in the syntax tree.
+struct Definition<'a> {
+    name: String,
+    /// Member list of a container (impl, class, module), if any.
+    body: Option<Node<'a>>,
+}
+
+/// A byte range of the source that becomes one or more chunks.
+struct Unit {
+    start: usize,
+    end: usize,
+    symbol: Option<String>,
+}
+
+/// Comments and attributes that belong to the definition that follows them.
+fn is_leading_trivia(kind: &str) -> bool {
+    matches!(
+        kind,
+        "line_comment" | "block_comment" | "comment" | "attribute_item" | "decorator"
+    )
+}
+
+fn field_text(node: Node, field: &str, source: &str) -> Option<String> {
+    let text = node
+        .child_by_field_name(field)?
+        .utf8_text(source.as_bytes())
+        .ok()?;
+    // `Foo<T>` → `Foo`
+    let name = text.split('<').next().unwrap_or(text).trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The definition `node` introduces, if it is one.
+fn definition<'a>(lang: Lang, node: Node<'a>, source: &str) -> Option<Definition<'a>> {
+    let named = |node: Node<'a>| {
+        Some(Definition {
+            name: field_text(node, "name", source)?,
+            body: None,
+        })
+    };
+    let container = |node: Node<'a>, name_field: &str| {
+        Some(Definition {
+            name: field_text(node, name_field, source)?,
+            body: node.child_by_field_name("body"),
+        })
+    };
+
+    match (lang, node.kind()) {
+        (
+            Lang::Rust,
+            "function_item"
+            | "function_signature_item"
+            | "struct_item"
+            | "enum_item"
+            | "union_item"
+            | "type_item"
+            | "const_item"
+            | "static_item"
+            | "macro_definition",
+        ) => named(node),
+        (Lang::Rust, "trait_item" | "mod_item") => container(node, "name"),
+        (Lang::Rust, "impl_item") => container(node, "type"),
+
+        (Lang::Python, "function_definition") => named(node),
+        (Lang::Python, "class_definition") => container(node, "name"),
+        (Lang::Python, "decorated_definition") => {
+            definition(lang, node.child_by_field_name("definition")?, source)
+        }
+
+        (
+            Lang::JavaScript | Lang::TypeScript | Lang::Tsx,
+            "function_declaration"
+            | "generator_function_declaration"
+            | "function_signature"
+            | "method_definition"
+            | "method_signature"
+            | "abstract_method_signature"
+            | "interface_declaration"
+            | "type_alias_declaration"
+            | "enum_declaration",
+        ) => named(node),
+        (
+            Lang::JavaScript | Lang::TypeScript | Lang::Tsx,
+            "class_declaration" | "abstract_class_declaration" | "internal_module" | "module",
+        ) => container(node, "name"),
+        (Lang::JavaScript | Lang::TypeScript | Lang::Tsx, "export_statement") => {
+            definition(lang, node.child_by_field_name("declaration")?, source)
+        }
+        (Lang::JavaScript | Lang::TypeScript | Lang::Tsx, "expression_statement") => {
+            definition(lang, node.named_child(0)?, source)
+        }
+        // `const handler = () => {}` and `const Foo = class {}`
+        (
+            Lang::JavaScript | Lang::TypeScript | Lang::Tsx,
+            "lexical_declaration" | "variable_declaration",
+        ) => {
+            let mut cursor = node.walk();
+            let declarator = node.named_children(&mut cursor).find(|d| {
+                d.kind() == "variable_declarator"
+                    && d.child_by_field_name("value").is_some_and(|v| {
+                        matches!(
+                            v.kind(),
+                            "arrow_function"
+                                | "function_expression"
+                                | "function"
+                                | "generator_function"
+                                | "class"
+                        )
+                    })
+            })?;
+            named(declarator)
+        }
+        _ => None,
+    }
+}
+
+/// Split the children of `parent` into definition and glue units.
+fn collect_units(
+    lang: Lang,
+    parent: Node,
+    source: &str,
+    prefix: Option<&str>,
+    chunk_size: usize,
+    units: &mut Vec<Unit>,
+) {
+    let glue_symbol = prefix.map(str::to_string);
+    // Pending glue and trailing comments, as (start, end) byte ranges
+    let mut glue: Option<(usize, usize)> = None;
+    let mut trivia: Option<(usize, usize)> = None;
+
+    let mut cursor = parent.walk();
+    for child in parent.named_children(&mut cursor) {
+        if is_leading_trivia(child.kind()) {
+            let start = trivia.map_or(child.start_byte(), |(start, _)| start);
+            trivia = Some((start, child.end_byte()));
+            continue;
+        }
+        let Some(def) = definition(lang, child, source) else {
+            let start = glue
+                .or(trivia)
+                .map_or(child.start_byte(), |(start, _)| start);
+            glue = Some((start, child.end_byte()));
+            trivia = None;
+            continue;
+        };
+
+        if let Some((start, end)) = glue.take() {
+            units.push(Unit {
+                start,
+                end,
+                symbol: glue_symbol.clone(),
+            });
+        }
+        let start = trivia.take().map_or(child.start_byte(), |(start, _)| start);
+        let name = match prefix {
+            Some(prefix) => format!("{}{}{}", prefix, lang.separator(), def.name),
+            None => def.name,
+        };
+
+        match def.body {
+            Some(body) if estimate_tokens(&source[start..child.end_byte()]) > chunk_size => {
+                // Too big for one chunk: the header, then each member
+                units.push(Unit {
+                    start,
+                    end: body.start_byte(),
+                    symbol: Some(name.clone()),
+                });
+                collect_units(lang, body, source, Some(&name), chunk_size, units);
+            }
+            _ => units.push(Unit {
+                start,
+                end: child.end_byte(),
+                symbol: Some(name),
+            }),
+        }
+    }
+
+    let rest = match (glue, trivia) {
+        (Some((start, _)), Some((_, end))) => Some((start, end)),
+        (glue, trivia) => glue.or(trivia),
+    };
+    if let Some((start, end)) = rest {
+        units.push(Unit {
+            start,
+            end,
+            symbol: glue_symbol,
+        });
+    }
+}
+
+/// Merge adjacent units with the same symbol while they fit in one chunk,
+/// so runs of imports or small container headers don't become tiny chunks.
+fn merge_units(units: Vec<Unit>, source: &str, chunk_size: usize) -> Vec<Unit> {
+    let mut merged: Vec<Unit> = Vec::with_capacity(units.len());
+    for unit in units {
+        if let Some(last) = merged.last_mut() {
+            if last.symbol == unit.symbol
+                && estimate_tokens(&source[last.start..unit.end]) <= chunk_size
+            {
+                last.end = unit.end;
+                continue;
+            }
+        }
+        merged.push(unit);
+    }
+    merged
+}
+
+/// Chunk source code on definition boundaries.
+///
+/// Returns `None` when the extension has no grammar or the file doesn't
+/// parse, so the caller can fall back to plain chunking.
+pub fn chunk_code(text: &str, extension: &str) -> Option<Vec<ChunkInfo>> {
+    let lang = Lang::from_extension(extension)?;
+    let mut parser = Parser::new();
+    parser.set_language(&lang.language()).ok()?;
+    let tree = parser.parse(text, None)?;
+    let root = tree.root_node();
+    if root.has_error() && root.named_child_count() == 0 {
+        return None;
+    }
+
+    let config = chunker::current();
+    let mut units = Vec::new();
+    collect_units(lang, root, text, None, config.chunk_size, &mut units);
+
+    let mut chunks = Vec::new();
+    for unit in merge_units(units, text, config.chunk_size) {
+        let content = text[unit.start..unit.end].trim();
+        if content.is_empty() {
+            continue;
+        }
+        let tokens = estimate_tokens(content);
+        let pieces = if tokens > config.chunk_size {
+            chunk_text(content, config.chunk_size, config.overlap)
+        } else {
+            vec![ChunkInfo {
+                index: 0,
+                content: content.to_string(),
+                token_count: tokens as i32,
+                section: None,
+                symbol: None,
+            }]
+        };
+        for mut chunk in pieces {
+            chunk.index = chunks.len() as i32;
+            chunk.symbol = unit.symbol.clone();
+            chunks.push(chunk);
+        }
+    }
+    Some(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(chunks: &[ChunkInfo]) -> Vec<Option<&str>> {
+        chunks.iter().map(|c| c.symbol.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_chunk_rust() {
+        let source = r#"use std::path::Path;
+
+/// Open the vault.
+#[inline]
+pub fn open(path: &Path) -> bool {
+    path.exists()
+}
+
+struct Database;
+
+impl Database {
+    fn close(&self) {}
+}
+"#;
+        let chunks = chunk_code(source, "rs").unwrap();
+        // The struct and its small impl merge into one chunk
+        assert_eq!(symbols(&chunks), vec![None, Some("open"), Some("Database")]);
+        // Doc comments and attributes stay with their function
+        assert!(chunks[1].content.starts_with("/// Open the vault."));
+        assert!(chunks[1].content.contains("#[inline]"));
+        assert_eq!(chunks[0].content, "use std::path::Path;");
+        let indices: Vec<i32> = chunks.iter().map(|c| c.index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_large_impl_split_into_methods() {
+        let body = "let x = 1; ".repeat(200);
+        let source = format!(
+            "impl Engine {{\n    fn start(&self) {{ {} }}\n    fn stop(&self) {{ {} }}\n}}\n",
+            body, body
+        );
+        let chunks = chunk_code(&source, "rs").unwrap();
+        let symbols = symbols(&chunks);
+        assert!(symbols.contains(&Some("Engine::start")));
+        assert!(symbols.contains(&Some("Engine::stop")));
+    }
+
+    #[test]
+    fn test_chunk_python() {
+        let source = r#"import os
+
+@cache
+def load(path):
+    return os.path.exists(path)
+
+class Parser:
+    def parse(self, text):
+        return text.split()
+"#;
+        let chunks = chunk_code(source, "py").unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("load"), Some("Parser")]);
+        assert!(chunks[1].content.starts_with("@cache"));
+    }
+
+    #[test]
+    fn test_chunk_typescript() {
+        let source = r#"import { invoke } from "@tauri-apps/api/core";
+
+// Search the vault.
+export async function search(query: string) {
+  return invoke("search_query", { query });
+}
+
+export const format = (n: number) => n.toFixed(2);
+
+interface Result {
+  path: string;
+}
+"#;
+        let chunks = chunk_code(source, "ts").unwrap();
+        assert_eq!(
+            symbols(&chunks),
+            vec![None, Some("search"), Some("format"), Some("Result")]
+        );
+        assert!(chunks[1].content.starts_with("// Search the vault."));
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        assert!(chunk_code("package main", "go").is_none());
+        assert!(is_code_extension("TSX"));
+        assert!(!is_code_extension("md"));
+    }
+}
@@ -1,9 +1,70 @@
+use std::borrow::Cow;
+use std::io::{Read, Seek};
 use std::path::Path;
 
 use calamine::Reader;
 
 use crate::error::{GhostError, Result};
 
+/// Where a document's contents come from: a file, or bytes already in memory
+/// (a member of an archive, see [`extract_bytes`]).
+enum Source<'a> {
+    File(&'a Path),
+    Memory { name: &'a str, data: &'a [u8] },
+}
+
+trait ReadSeek: Read + Seek {}
+impl<T: Read + Seek> ReadSeek for T {}
+
+impl Source<'_> {
+    /// The file path, or the name of the in-memory document.
+    fn display(&self) -> Cow<'_, str> {
+        match self {
+            Self::File(path) => path.to_string_lossy(),
+            Self::Memory { name, .. } => Cow::Borrowed(*name),
+        }
+    }
+
+    fn name(&self) -> &Path {
+        match self {
+            Self::File(path) => path,
+            Self::Memory { name, .. } => Path::new(name),
+        }
+    }
+
+    /// Lowercase extension.
+    fn extension(&self) -> String {
+        self.name()
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+    }
+
+    /// The whole contents.
+    fn read(&self) -> Result<Cow<'_, [u8]>> {
+        match self {
+            Self::File(path) => std::fs::read(path).map(Cow::Owned).map_err(|e| {
+                GhostError::Indexer(format!("Failed to read {}: {}", path.display(), e))
+            }),
+            Self::Memory { data, .. } => Ok(Cow::Borrowed(data)),
+        }
+    }
+
+    /// A seekable reader over the contents, for ZIP-based formats.
+    fn reader(&self) -> Result<Box<dyn ReadSeek + '_>> {
+        match self {
+            Self::File(path) => {
+                let file = std::fs::File::open(path).map_err(|e| {
+                    GhostError::Indexer(format!("Failed to open {}: {}", path.display(), e))
+                })?;
+                Ok(Box::new(std::io::BufReader::new(file)))
+            }
+            Self::Memory { data, .. } => Ok(Box::new(std::io::Cursor::new(*data))),
+        }
+    }
+}
+
 /// Extract text content from a file based on its extension.
 pub fn extract_text(path: &Path) -> Result<String> {
     let extension = path
@@ -12,19 +73,45 @@ pub fn extract_text(path: &Path) -> Result<String> {
         .unwrap_or("")
         .to_lowercase();
 
+    // User-registered plugins take precedence over built-in extractors
+    if let Some(plugin) = super::plugins::plugin_for(&extension) {
+        return super::plugins::run(&plugin, path);
+    }
+    if super::archive::is_archive_extension(&extension) {
+        return super::archive::extract_archive(path).map(|sections| join_sections(&sections));
+    }
+    extract_source(&Source::File(path))
+}
+
+/// Extract text from a document held in memory, such as an archive member,
+/// without writing it to disk. `name` gives the file type and appears in
+/// errors. Extractor plugins run on files, so only built-in extractors are
+/// used, and nested archives are not opened.
+pub fn extract_bytes(name: &str, data: &[u8]) -> Result<String> {
+    extract_source(&Source::Memory { name, data })
+}
+
+fn extract_source(source: &Source) -> Result<String> {
+    let extension = source.extension();
     match extension.as_str() {
-        "txt" | "md" | "markdown" | "rst" | "csv" | "json" | "yaml" | "yml" | "toml" | "xml"
-        | "html" | "htm" | "log" | "ini" | "cfg" | "conf" => extract_plain_text(path),
+        "txt" | "md" | "markdown" | "rst" | "json" | "yaml" | "yml" | "toml" | "xml" | "html"
+        | "htm" | "log" | "ini" | "cfg" | "conf" => extract_plain_text(source),
         // Source code files (also plain text)
         "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "java" | "c" | "cpp" | "h" | "hpp" | "go"
         | "rb" | "php" | "swift" | "kt" | "scala" | "sh" | "bash" | "zsh" | "fish" | "ps1"
         | "bat" | "cmd" | "lua" | "r" | "sql" | "css" | "scss" | "sass" | "less" | "vue"
         | "svelte" | "astro" | "zig" | "nim" | "dart" | "ex" | "exs" | "erl" | "hrl" | "clj"
         | "cljs" | "lisp" | "el" | "vim" | "tex" | "bib" | "org" | "makefile" | "dockerfile"
-        | "gitignore" | "env" | "editorconfig" => extract_plain_text(path),
-        "pdf" => extract_pdf(path),
-        "docx" => extract_docx(path),
-        "xlsx" | "xls" | "ods" => extract_spreadsheet(path),
+        | "gitignore" | "env" | "editorconfig" => extract_plain_text(source),
+        "pdf" => extract_pdf(source),
+        "docx" => extract_docx(source),
+        "xlsx" | "xls" | "ods" | "csv" => {
+            extract_table_sections(source).map(|sections| join_sections(&sections))
+        }
+        "eml" | "mbox" => email_text(source).map(|(text, _)| text),
+        "ics" | "ical" => calendar_text(source).map(|(text, _)| text),
+        "vcf" | "vcard" => contacts_text(source),
+        "epub" | "mobi" | "azw" => ebook_sections(source).map(|sections| join_sections(&sections)),
         _ => Err(GhostError::Indexer(format!(
             "Unsupported file type: {}",
             extension
@@ -32,11 +119,13 @@ pub fn extract_text(path: &Path) -> Result<String> {
     }
 }
 
-/// Check if a file extension is supported for indexing.
+/// Check if a file extension is supported for indexing
+/// (built in, or handled by an extractor plugin).
 pub fn is_supported_extension(extension: &str) -> bool {
-    matches!(
-        extension.to_lowercase().as_str(),
-        "txt"
+    super::plugins::handles(extension)
+        || matches!(
+            extension.to_lowercase().as_str(),
+            "txt"
             | "md"
             | "markdown"
             | "rst"
@@ -57,6 +146,19 @@ pub fn is_supported_extension(extension: &str) -> bool {
             | "xlsx"
             | "xls"
             | "ods"
+            | "eml"
+            | "mbox"
+            | "ics"
+            | "ical"
+            | "vcf"
+            | "vcard"
+            | "epub"
+            | "mobi"
+            | "azw"
+            | "zip"
+            | "tar"
+            | "tgz"
+            | "gz"
             // Source code
             | "rs"
             | "py"
@@ -112,19 +214,20 @@ pub fn is_supported_extension(extension: &str) -> bool {
             | "gitignore"
             | "env"
             | "editorconfig"
-    )
+        )
 }
 
-fn extract_plain_text(path: &Path) -> Result<String> {
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| GhostError::Indexer(format!("Failed to read {}: {}", path.display(), e)))?;
-    Ok(content)
+fn extract_plain_text(source: &Source) -> Result<String> {
+    String::from_utf8(source.read()?.into_owned())
+        .map_err(|e| GhostError::Indexer(format!("Failed to read {}: {}", source.display(), e)))
 }
 
-fn extract_pdf(path: &Path) -> Result<String> {
-    let doc = lopdf::Document::load(path).map_err(|e| {
-        GhostError::Indexer(format!("Failed to parse PDF {}: {}", path.display(), e))
-    })?;
+fn extract_pdf(source: &Source) -> Result<String> {
+    let doc = match source {
+        Source::File(path) => lopdf::Document::load(path),
+        Source::Memory { data, .. } => lopdf::Document::load_mem(data),
+    }
+    .map_err(|e| GhostError::Indexer(format!("Failed to parse PDF {}: {}", source.display(), e)))?;
 
     let mut text = String::new();
     let pages = doc.get_pages();
@@ -139,7 +242,7 @@ fn extract_pdf(path: &Path) -> Result<String> {
     if text.trim().is_empty() {
         return Err(GhostError::Indexer(format!(
             "No extractable text in PDF: {}",
-            path.display()
+            source.display()
         )));
     }
 
@@ -147,13 +250,13 @@ fn extract_pdf(path: &Path) -> Result<String> {
 }
 
 /// Extract text from a DOCX file (ZIP archive containing XML).
-fn extract_docx(path: &Path) -> Result<String> {
-    let file = std::fs::File::open(path).map_err(|e| {
-        GhostError::Indexer(format!("Failed to open DOCX {}: {}", path.display(), e))
-    })?;
-
-    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file)).map_err(|e| {
-        GhostError::Indexer(format!("Failed to read DOCX ZIP {}: {}", path.display(), e))
+fn extract_docx(source: &Source) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(source.reader()?).map_err(|e| {
+        GhostError::Indexer(format!(
+            "Failed to read DOCX ZIP {}: {}",
+            source.display(),
+            e
+        ))
     })?;
 
     let mut text = String::new();
@@ -161,7 +264,7 @@ fn extract_docx(path: &Path) -> Result<String> {
     // DOCX stores text content in word/document.xml
     if let Ok(mut document) = archive.by_name("word/document.xml") {
         let mut xml_content = String::new();
-        std::io::Read::read_to_string(&mut document, &mut xml_content)
+        Read::read_to_string(&mut document, &mut xml_content)
             .map_err(|e| GhostError::Indexer(format!("Failed to read document.xml: {}", e)))?;
 
         // Extract text between <w:t> and <w:t xml:space="preserve"> tags
@@ -210,43 +313,1595 @@ fn extract_docx(path: &Path) -> Result<String> {
     if text.trim().is_empty() {
         return Err(GhostError::Indexer(format!(
             "No extractable text in DOCX: {}",
-            path.display()
+            source.display()
         )));
     }
 
     Ok(text)
 }
 
-fn extract_spreadsheet(path: &Path) -> Result<String> {
-    let mut workbook: calamine::Sheets<std::io::BufReader<std::fs::File>> =
-        calamine::open_workbook_auto(path).map_err(|e| {
-            GhostError::Indexer(format!(
-                "Failed to open spreadsheet {}: {}",
-                path.display(),
-                e
-            ))
-        })?;
+/// Data rows per spreadsheet chunk group; every row carries its column headers.
+const ROWS_PER_GROUP: usize = 25;
 
-    let mut text = String::new();
+/// Whether an extension is extracted as titled sections (see [`extract_sections`]).
+pub fn is_sectioned_extension(extension: &str) -> bool {
+    is_ebook_extension(extension)
+        || is_table_extension(extension)
+        || super::archive::is_archive_extension(extension)
+}
+
+/// Whether an extension is a spreadsheet or CSV table.
+pub fn is_table_extension(extension: &str) -> bool {
+    matches!(
+        extension.to_lowercase().as_str(),
+        "xlsx" | "xls" | "ods" | "csv"
+    )
+}
+
+/// Extract a document as titled sections: e-book chapters, spreadsheet
+/// row groups titled with their sheet name, or the files of an archive.
+pub fn extract_sections(path: &Path) -> Result<Vec<Section>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if is_table_extension(&extension) {
+        extract_table_sections(&Source::File(path))
+    } else if super::archive::is_archive_extension(&extension) {
+        super::archive::extract_archive(path)
+    } else {
+        extract_ebook(path)
+    }
+}
+
+/// Extract each sheet of a spreadsheet (or a CSV file) as groups of rows.
+///
+/// The first non-empty row of a sheet is taken as the column headers, and
+/// each data row is written as `Header: value` pairs so a chunk stays
+/// readable on its own. Sections are titled "Sheet (rows 2–26)".
+fn extract_table_sections(source: &Source) -> Result<Vec<Section>> {
+    let open_error = |e: calamine::Error| {
+        GhostError::Indexer(format!(
+            "Failed to open spreadsheet {}: {}",
+            source.display(),
+            e
+        ))
+    };
+    let sheets: Vec<(String, Vec<Vec<String>>)> = if source.extension() == "csv" {
+        let bytes = source.read()?;
+        let text = match std::str::from_utf8(&bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => decode_charset(&bytes, "windows-1252"),
+        };
+        let name = source
+            .name()
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        vec![(name, parse_csv(&text))]
+    } else {
+        match source {
+            Source::File(path) => {
+                workbook_sheets(calamine::open_workbook_auto(path).map_err(open_error)?)
+            }
+            Source::Memory { data, .. } => workbook_sheets(
+                calamine::open_workbook_auto_from_rs(std::io::Cursor::new(*data))
+                    .map_err(open_error)?,
+            ),
+        }
+    };
+
+    let sections: Vec<Section> = sheets
+        .iter()
+        .flat_map(|(name, rows)| table_sections(name, rows))
+        .collect();
+    if sections.is_empty() {
+        return Err(GhostError::Indexer(format!(
+            "No data in spreadsheet: {}",
+            source.display()
+        )));
+    }
+    Ok(sections)
+}
+
+/// Every sheet of a workbook as rows of cell text.
+fn workbook_sheets<RS: Read + Seek>(
+    mut workbook: calamine::Sheets<RS>,
+) -> Vec<(String, Vec<Vec<String>>)> {
     let sheet_names: Vec<String> = workbook.sheet_names().to_vec();
+    sheet_names
+        .into_iter()
+        .filter_map(|name| {
+            let range = workbook.worksheet_range(&name).ok()?;
+            let rows = range
+                .rows()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell: &calamine::Data| cell.to_string())
+                        .collect()
+                })
+                .collect();
+            Some((name, rows))
+        })
+        .collect()
+}
+
+/// Split one sheet into row groups with headers attached to every value.
+fn table_sections(sheet: &str, rows: &[Vec<String>]) -> Vec<Section> {
+    let is_blank = |row: &Vec<String>| row.iter().all(|c| c.trim().is_empty());
+    let Some(header_index) = rows.iter().position(|row| !is_blank(row)) else {
+        return Vec::new();
+    };
+    let headers: Vec<String> = rows[header_index]
+        .iter()
+        .enumerate()
+        .map(|(i, h)| match h.trim() {
+            "" => format!("Column {}", i + 1),
+            h => h.to_string(),
+        })
+        .collect();
+
+    // (1-based row number, row) for every non-empty data row
+    let data: Vec<(usize, &Vec<String>)> = rows
+        .iter()
+        .enumerate()
+        .skip(header_index + 1)
+        .filter(|(_, row)| !is_blank(row))
+        .map(|(i, row)| (i + 1, row))
+        .collect();
+    if data.is_empty() {
+        return vec![Section {
+            title: Some(sheet.to_string()),
+            text: headers.join(", "),
+        }];
+    }
+
+    data.chunks(ROWS_PER_GROUP)
+        .map(|group| {
+            let first = group[0].0;
+            let last = group[group.len() - 1].0;
+            let text = group
+                .iter()
+                .map(|(_, row)| {
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_, value)| !value.trim().is_empty())
+                        .map(|(i, value)| match headers.get(i) {
+                            Some(header) => format!("{}: {}", header, value.trim()),
+                            None => value.trim().to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Section {
+                title: Some(format!("{} (rows {}–{})", sheet, first, last)),
+                text,
+            }
+        })
+        .collect()
+}
+
+/// Parse CSV text (RFC 4180 quoting). Semicolon- and tab-separated files,
+/// common in European locales and exports, are detected from the first line.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let text = text.trim_start_matches('\u{feff}');
+    let first_line = text.lines().next().unwrap_or("");
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| first_line.matches(*d).count())
+        .filter(|d| first_line.contains(*d))
+        .unwrap_or(',');
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter && !in_quotes => row.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Sender and date of an email file, stored as document metadata.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmailMetadata {
+    /// `From` header of the message (every distinct sender for mbox files).
+    pub sender: Option<String>,
+    /// Send date as ISO 8601 UTC (the earliest message for mbox files).
+    pub sent_at: Option<String>,
+}
+
+/// A single parsed email message.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct EmailMessage {
+    subject: String,
+    from: String,
+    date: Option<String>,
+    /// `Message-ID` header without the angle brackets.
+    message_id: Option<String>,
+    body: String,
+}
+
+impl EmailMessage {
+    /// Headers and body as indexed text.
+    fn to_text(&self) -> String {
+        let mut text = format!("Subject: {}\nFrom: {}\n", self.subject, self.from);
+        if let Some(date) = &self.date {
+            text.push_str(&format!("Date: {}\n", date));
+        }
+        text.push('\n');
+        text.push_str(self.body.trim());
+        text
+    }
+}
+
+/// One message of a mail store, read by a mail importer.
+#[cfg(feature = "mail-import")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailMessage {
+    pub subject: String,
+    /// Headers and body, as [`extract_email`] writes them.
+    pub text: String,
+    pub metadata: EmailMetadata,
+    /// `Message-ID` header without the angle brackets.
+    pub message_id: Option<String>,
+}
+
+/// Parse one RFC 5322 message.
+#[cfg(feature = "mail-import")]
+pub fn parse_message(raw: &[u8]) -> MailMessage {
+    let message = parse_email(&String::from_utf8_lossy(raw));
+    MailMessage {
+        text: message.to_text(),
+        metadata: EmailMetadata {
+            sender: (!message.from.is_empty()).then(|| message.from.clone()),
+            sent_at: message.date.clone(),
+        },
+        subject: message.subject,
+        message_id: message.message_id,
+    }
+}
+
+/// Whether an extension is an email format handled by [`extract_email`].
+pub fn is_email_extension(extension: &str) -> bool {
+    matches!(extension.to_lowercase().as_str(), "eml" | "mbox")
+}
+
+/// Extract the text and sender/date metadata of an `.eml` or `.mbox` file.
+pub fn extract_email(path: &Path) -> Result<(String, EmailMetadata)> {
+    email_text(&Source::File(path))
+}
+
+fn email_text(source: &Source) -> Result<(String, EmailMetadata)> {
+    let bytes = source.read()?;
+    let raw = String::from_utf8_lossy(&bytes);
+    let is_mbox = source.extension() == "mbox";
+
+    let mut messages: Vec<EmailMessage> = if is_mbox {
+        split_mbox(&raw).iter().map(|m| parse_email(m)).collect()
+    } else {
+        vec![parse_email(&raw)]
+    };
+    // Mailbox exports often hold the same message twice
+    let mut seen = std::collections::HashSet::new();
+    messages.retain(|m| match &m.message_id {
+        Some(id) => seen.insert(id.clone()),
+        None => true,
+    });
+    if messages.is_empty() {
+        return Err(GhostError::Indexer(format!(
+            "No messages in mailbox: {}",
+            source.display()
+        )));
+    }
+
+    let mut text = String::new();
+    for message in &messages {
+        text.push_str(&message.to_text());
+        text.push_str("\n\n");
+    }
+
+    let mut senders: Vec<&str> = Vec::new();
+    for message in &messages {
+        if !message.from.is_empty() && !senders.contains(&message.from.as_str()) {
+            senders.push(&message.from);
+        }
+    }
+    let metadata = EmailMetadata {
+        sender: (!senders.is_empty()).then(|| senders.join(", ")),
+        sent_at: messages.iter().filter_map(|m| m.date.clone()).min(),
+    };
+    Ok((text, metadata))
+}
+
+/// Split an mbox file into raw messages on `From ` separator lines.
+fn split_mbox(raw: &str) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current: Option<String> = None;
+    for line in raw.lines() {
+        if line.starts_with("From ") {
+            if let Some(message) = current.take() {
+                messages.push(message);
+            }
+            current = Some(String::new());
+            continue;
+        }
+        if let Some(message) = current.as_mut() {
+            // mboxrd escapes body lines starting with "From " as ">From "
+            let line = match line.strip_prefix('>') {
+                Some(rest) if rest.trim_start_matches('>').starts_with("From ") => rest,
+                _ => line,
+            };
+            message.push_str(line);
+            message.push('\n');
+        }
+    }
+    messages.extend(current);
+    messages
+}
+
+/// Split a message (or MIME part) into unfolded headers and the raw body.
+fn split_headers(raw: &str) -> (Vec<(String, String)>, &str) {
+    if raw.starts_with('\n') || raw.starts_with("\r\n") {
+        // No headers (e.g. a bare MIME part)
+        return (Vec::new(), raw.trim_start_matches(['\r', '\n']));
+    }
+    let end = [("\r\n\r\n", 4), ("\n\n", 2)]
+        .iter()
+        .filter_map(|(sep, len)| raw.find(sep).map(|i| (i, i + len)))
+        .min();
+    let (header_block, body) = match end {
+        Some((end, body_start)) => (&raw[..end], &raw[body_start..]),
+        None => (raw, ""),
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in header_block.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            // Folded continuation of the previous header
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Value of a `key=value` parameter in a header like `Content-Type`.
+fn header_param(value: &str, param: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|p| {
+        let (key, val) = p.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(param)
+            .then(|| val.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Parse an RFC 5322 message into subject, sender, date, and plain-text body.
+fn parse_email(raw: &str) -> EmailMessage {
+    let (headers, _) = split_headers(raw);
+    let date = header(&headers, "date").and_then(|d| {
+        chrono::DateTime::parse_from_rfc2822(d).ok().map(|dt| {
+            dt.with_timezone(&chrono::Utc)
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string()
+        })
+    });
+    EmailMessage {
+        subject: decode_encoded_words(header(&headers, "subject").unwrap_or("")),
+        from: decode_encoded_words(header(&headers, "from").unwrap_or("")),
+        date,
+        message_id: header(&headers, "message-id")
+            .map(|id| {
+                id.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+            .filter(|id| !id.is_empty()),
+        body: mime_text(raw).unwrap_or_default(),
+    }
+}
+
+/// Best text content of a MIME entity: text/plain preferred, HTML stripped as fallback.
+fn mime_text(raw: &str) -> Option<String> {
+    let (headers, body) = split_headers(raw);
+    let content_type = header(&headers, "content-type").unwrap_or("text/plain");
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+
+    if mime.starts_with("multipart/") {
+        let boundary = header_param(content_type, "boundary")?;
+        let delimiter = format!("--{}", boundary);
+        let parts: Vec<&str> = body
+            .split(delimiter.as_str())
+            .skip(1)
+            .take_while(|p| !p.starts_with("--"))
+            .map(|p| {
+                p.strip_prefix("\r\n")
+                    .or_else(|| p.strip_prefix('\n'))
+                    .unwrap_or(p)
+            })
+            .collect();
+        let texts: Vec<(bool, String)> = parts
+            .iter()
+            .filter_map(|part| {
+                let (part_headers, _) = split_headers(part);
+                let is_html = header(&part_headers, "content-type")
+                    .is_some_and(|ct| ct.to_lowercase().starts_with("text/html"));
+                mime_text(part).map(|text| (is_html, text))
+            })
+            .collect();
+        if mime == "multipart/alternative" {
+            return texts
+                .iter()
+                .find(|(is_html, _)| !is_html)
+                .or_else(|| texts.first())
+                .map(|(_, text)| text.clone());
+        }
+        let joined: Vec<String> = texts.into_iter().map(|(_, text)| text).collect();
+        return (!joined.is_empty()).then(|| joined.join("\n\n"));
+    }
+
+    if !mime.starts_with("text/") {
+        // Attachments (images, PDFs, ...) are not part of the message text
+        return None;
+    }
+    let disposition = header(&headers, "content-disposition").unwrap_or("");
+    if disposition.to_lowercase().starts_with("attachment") {
+        return None;
+    }
+
+    let encoding = header(&headers, "content-transfer-encoding")
+        .unwrap_or("")
+        .to_lowercase();
+    let bytes = match encoding.as_str() {
+        "base64" => decode_base64(body),
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.as_bytes().to_vec(),
+    };
+    let charset = header_param(content_type, "charset").unwrap_or_else(|| "utf-8".into());
+    let text = decode_charset(&bytes, &charset);
+
+    if mime == "text/html" {
+        Some(strip_html_tags(&text))
+    } else {
+        Some(text)
+    }
+}
+
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    let encoding =
+        encoding_rs::Encoding::for_label(charset.as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// Decode RFC 2047 encoded words (`=?UTF-8?B?...?=`) in a header value.
+fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..]
+            .split_once("?=")
+            .and_then(|(word, after)| {
+                let mut fields = word.splitn(3, '?');
+                let (charset, encoding, text) = (fields.next()?, fields.next()?, fields.next()?);
+                let bytes = match encoding.to_ascii_uppercase().as_str() {
+                    "B" => decode_base64(text),
+                    "Q" => decode_quoted_printable(&text.replace('_', " ")),
+                    _ => return None,
+                };
+                Some((decode_charset(&bytes, charset), after))
+            });
+        match decoded {
+            Some((text, after)) => {
+                let between = &rest[..start];
+                // Whitespace between adjacent encoded words is not displayed
+                if out.is_empty() || !between.trim().is_empty() {
+                    out.push_str(between);
+                }
+                out.push_str(&text);
+                rest = after;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_base64(input: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => continue,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    out
+}
+
+fn decode_quoted_printable(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // Soft line break
+            if bytes[i + 1..].starts_with(b"\r\n") {
+                i += 3;
+                continue;
+            }
+            if bytes[i + 1..].starts_with(b"\n") {
+                i += 2;
+                continue;
+            }
+            if let Some(hex) = input.get(i + 1..i + 3) {
+                if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Drop HTML tags, keeping text content (line breaks at block elements).
+///
+/// The contents of `<head>`, `<script>`, and `<style>` are skipped.
+fn strip_html_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut tag = String::new();
+    let mut skip_until: Option<&str> = None;
+    for ch in html.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                let lower = tag.to_lowercase();
+                let closing = lower.starts_with('/');
+                let name = lower
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or("")
+                    .to_string();
+                if let Some(until) = skip_until {
+                    if closing && name == until {
+                        skip_until = None;
+                    }
+                    continue;
+                }
+                if !closing {
+                    skip_until = ["head", "script", "style"].into_iter().find(|t| name == *t);
+                }
+                if [
+                    "br", "p", "div", "tr", "li", "h1", "h2", "h3", "h4", "h5", "h6",
+                ]
+                .contains(&name.as_str())
+                {
+                    text.push('\n');
+                }
+            }
+            _ if in_tag => tag.push(ch),
+            _ if skip_until.is_some() => {}
+            _ => text.push(ch),
+        }
+    }
+    decode_html_entities(&text)
+}
+
+/// Decode the common named entities and numeric character references.
+fn decode_html_entities(text: &str) -> String {
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'");
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("&#") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let code = &rest[2..end];
+            let value = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => code.parse().ok(),
+            };
+            value.and_then(char::from_u32).map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push_str("&#");
+                rest = &rest[2..];
+            }
+        }
+    }
+    out.push_str(rest);
+    // Last, so "&amp;lt;" stays "&lt;"
+    out.replace("&amp;", "&")
+}
+
+/// When a calendar event takes place, stored as document metadata for
+/// `on:` filters. Local wall-clock times, `YYYY-MM-DDTHH:MM:SS`; the end is
+/// the event's last second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventTime {
+    pub starts_at: String,
+    pub ends_at: String,
+}
+
+/// A property line of an iCalendar or vCard file.
+#[derive(Debug, Clone, PartialEq)]
+struct ContentLine {
+    /// Uppercased, without a vCard group prefix (`item1.EMAIL` is `EMAIL`).
+    name: String,
+    /// Uppercased parameter names with unquoted values.
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn text(&self) -> String {
+        unescape_value(&self.value)
+    }
+}
+
+/// Decode the `\n`, `\,` and `\;` escapes of a property value.
+fn unescape_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out.trim().to_string()
+}
+
+/// Whether an extension is a calendar handled by [`extract_calendar`].
+pub fn is_calendar_extension(extension: &str) -> bool {
+    matches!(extension.to_lowercase().as_str(), "ics" | "ical")
+}
+
+/// Split an iCalendar/vCard file into unfolded property lines. vCard 2.1
+/// quoted-printable values are decoded.
+fn content_lines(raw: &str) -> Vec<ContentLine> {
+    let mut unfolded: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !unfolded.is_empty() => unfolded.last_mut().unwrap().push_str(rest),
+            _ => unfolded.push(line.to_string()),
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut iter = unfolded.into_iter();
+    while let Some(mut line) = iter.next() {
+        // The name and parameters end at the first colon outside quotes
+        let mut in_quotes = false;
+        let Some(colon) = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(i),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let mut parts = line[..colon].split(';');
+        let name = parts.next().unwrap_or("");
+        let name = name
+            .rsplit('.')
+            .next()
+            .unwrap_or(name)
+            .trim()
+            .to_uppercase();
+        let params: Vec<(String, String)> = parts
+            .map(|p| match p.split_once('=') {
+                Some((k, v)) => (k.trim().to_uppercase(), v.trim_matches('"').to_string()),
+                // vCard 2.1 bare parameters (`TEL;CELL:...`) are types
+                None => ("TYPE".to_string(), p.to_string()),
+            })
+            .collect();
+        let quoted_printable = params
+            .iter()
+            .any(|(k, v)| k == "ENCODING" && v.eq_ignore_ascii_case("quoted-printable"));
+        // Quoted-printable soft line breaks end a line with '='
+        while quoted_printable && line.ends_with('=') {
+            let Some(next) = iter.next() else {
+                break;
+            };
+            line.push('\n');
+            line.push_str(&next);
+        }
+        let mut value = line[colon + 1..].to_string();
+        if quoted_printable {
+            let charset = params
+                .iter()
+                .find(|(k, _)| k == "CHARSET")
+                .map_or("utf-8", |(_, v)| v.as_str());
+            value = decode_charset(&decode_quoted_printable(&value), charset);
+        }
+        lines.push(ContentLine {
+            name,
+            params,
+            value,
+        });
+    }
+    lines
+}
+
+/// A date or date-time value (`20250312`, `20250312T100000`,
+/// `20250312T090000Z`) as local time. UTC times are converted; times with a
+/// `TZID` are kept as written. The flag is true for whole-day dates.
+fn parse_ical_time(line: &ContentLine) -> Option<(chrono::NaiveDateTime, bool)> {
+    let value = line.value.trim();
+    if value.len() == 8 || line.param("VALUE") == Some("DATE") {
+        let date = chrono::NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0)?, true));
+    }
+    match value.strip_suffix(['Z', 'z']) {
+        Some(utc) => {
+            let time = chrono::NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            let local = time.and_utc().with_timezone(&chrono::Local).naive_local();
+            Some((local, false))
+        }
+        None => chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|time| (time, false)),
+    }
+}
+
+/// An RFC 5545 duration (`PT1H30M`, `P1D`, `P2W`).
+fn parse_ical_duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix('P')?;
+    let mut seconds = 0i64;
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = after;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let number: i64 = rest[..digits].parse().ok()?;
+        let unit = match (rest[digits..].chars().next()?, in_time) {
+            ('W', false) => 7 * 86_400,
+            ('D', false) => 86_400,
+            ('H', true) => 3_600,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return None,
+        };
+        seconds += number * unit;
+        rest = &rest[digits + 1..];
+    }
+    Some(chrono::Duration::seconds(if negative {
+        -seconds
+    } else {
+        seconds
+    }))
+}
+
+/// Display name of an `ORGANIZER`/`ATTENDEE`: its `CN`, else the address.
+fn ical_person(line: &ContentLine) -> String {
+    match line.param("CN") {
+        Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+        _ => {
+            let value = line.value.trim();
+            let address = value
+                .get(..7)
+                .filter(|p| p.eq_ignore_ascii_case("mailto:"))
+                .map_or(value, |_| &value[7..]);
+            address.to_string()
+        }
+    }
+}
+
+/// Describe one `VEVENT` as text and compute its times.
+fn describe_event(lines: &[ContentLine]) -> (String, Option<EventTime>) {
+    let find = |name: &str| lines.iter().find(|l| l.name == name);
+    let mut text = format!(
+        "Event: {}\n",
+        find("SUMMARY").map_or_else(|| "(untitled)".to_string(), |l| l.text())
+    );
 
-    for name in sheet_names {
-        if let Ok(range) = workbook.worksheet_range(&name) {
-            text.push_str(&format!("--- Sheet: {} ---\n", name));
-            for row in range.rows() {
-                let row_text: Vec<String> = row
-                    .iter()
-                    .map(|cell: &calamine::Data| format!("{}", cell))
-                    .collect();
-                text.push_str(&row_text.join("\t"));
-                text.push('\n');
+    let start = find("DTSTART").and_then(parse_ical_time);
+    let time = start.map(|(start, all_day)| {
+        let end = find("DTEND")
+            .and_then(parse_ical_time)
+            .map(|(end, _)| end)
+            .or_else(|| {
+                find("DURATION")
+                    .and_then(|l| parse_ical_duration(&l.value))
+                    .map(|d| start + d)
+            })
+            // Without an end, a date lasts the day and a date-time is an instant
+            .unwrap_or(if all_day {
+                start + chrono::Duration::days(1)
+            } else {
+                start
+            });
+        // The stored end is inclusive
+        let last = if end > start {
+            end - chrono::Duration::seconds(1)
+        } else {
+            start
+        };
+        let when = if all_day {
+            let (first, last) = (start.date(), last.date());
+            if first == last {
+                format!("{} (all day)", first.format("%A, %B %-d, %Y"))
+            } else {
+                format!(
+                    "{} to {}",
+                    first.format("%A, %B %-d, %Y"),
+                    last.format("%A, %B %-d, %Y")
+                )
             }
+        } else if end.date() == start.date() {
+            format!(
+                "{} to {}",
+                start.format("%A, %B %-d, %Y %H:%M"),
+                end.format("%H:%M")
+            )
+        } else {
+            format!(
+                "{} to {}",
+                start.format("%A, %B %-d, %Y %H:%M"),
+                end.format("%A, %B %-d, %Y %H:%M")
+            )
+        };
+        text.push_str(&format!("When: {}\n", when));
+        EventTime {
+            starts_at: start.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            ends_at: last.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }
+    });
+
+    if let Some(rule) = find("RRULE") {
+        let freq = rule
+            .value
+            .split(';')
+            .find_map(|p| p.strip_prefix("FREQ="))
+            .unwrap_or("")
+            .to_lowercase();
+        if !freq.is_empty() {
+            text.push_str(&format!("Repeats: {}\n", freq));
+        }
+    }
+    if let Some(location) = find("LOCATION").map(|l| l.text()) {
+        if !location.is_empty() {
+            text.push_str(&format!("Where: {}\n", location));
+        }
+    }
+    if let Some(organizer) = find("ORGANIZER") {
+        text.push_str(&format!("Organizer: {}\n", ical_person(organizer)));
+    }
+    let attendees: Vec<String> = lines
+        .iter()
+        .filter(|l| l.name == "ATTENDEE")
+        .map(ical_person)
+        .collect();
+    if !attendees.is_empty() {
+        text.push_str(&format!("Attendees: {}\n", attendees.join(", ")));
+    }
+    if let Some(description) = find("DESCRIPTION").map(|l| l.text()) {
+        if !description.is_empty() {
+            text.push('\n');
+            text.push_str(&description);
+            text.push('\n');
         }
     }
+    (text, time)
+}
+
+/// Extract the events of an `.ics` calendar: title, time, place, people and
+/// description as text, plus each event's times.
+pub fn extract_calendar(path: &Path) -> Result<(String, Vec<EventTime>)> {
+    calendar_text(&Source::File(path))
+}
+
+fn calendar_text(source: &Source) -> Result<(String, Vec<EventTime>)> {
+    let bytes = source.read()?;
+    let raw = String::from_utf8_lossy(&bytes);
+
+    let mut text = String::new();
+    let mut times = Vec::new();
+    // Properties of the current event; nested components (alarms) are skipped
+    let mut event: Option<Vec<ContentLine>> = None;
+    let mut nested = 0;
+    for line in content_lines(&raw) {
+        let component = line.value.trim().to_uppercase();
+        match line.name.as_str() {
+            "BEGIN" if component == "VEVENT" => event = Some(Vec::new()),
+            "BEGIN" if event.is_some() => nested += 1,
+            "END" if nested > 0 => nested -= 1,
+            "END" if component == "VEVENT" => {
+                if let Some(lines) = event.take() {
+                    let (described, time) = describe_event(&lines);
+                    text.push_str(&described);
+                    text.push('\n');
+                    times.extend(time);
+                }
+            }
+            _ => {
+                if let (Some(lines), 0) = (event.as_mut(), nested) {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    if text.is_empty() {
+        return Err(GhostError::Indexer(format!(
+            "No events in calendar: {}",
+            source.display()
+        )));
+    }
+    Ok((text, times))
+}
 
+/// Extract the contacts of a `.vcf` file: names, organizations, addresses,
+/// phone numbers, emails, birthdays and notes.
+pub fn extract_contacts(path: &Path) -> Result<String> {
+    contacts_text(&Source::File(path))
+}
+
+fn contacts_text(source: &Source) -> Result<String> {
+    let bytes = source.read()?;
+    let raw = String::from_utf8_lossy(&bytes);
+
+    let mut text = String::new();
+    let mut card: Option<Vec<ContentLine>> = None;
+    for line in content_lines(&raw) {
+        let is_vcard = line.value.trim().eq_ignore_ascii_case("VCARD");
+        match line.name.as_str() {
+            "BEGIN" if is_vcard => card = Some(Vec::new()),
+            "END" if is_vcard => {
+                if let Some(lines) = card.take() {
+                    text.push_str(&describe_contact(&lines));
+                    text.push('\n');
+                }
+            }
+            _ => {
+                if let Some(lines) = card.as_mut() {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    if text.is_empty() {
+        return Err(GhostError::Indexer(format!(
+            "No contacts in address book: {}",
+            source.display()
+        )));
+    }
     Ok(text)
 }
 
+/// Describe one vCard as text.
+fn describe_contact(lines: &[ContentLine]) -> String {
+    // Structured values (`N`, `ADR`, `ORG`) have `;`-separated components
+    let components = |line: &ContentLine| -> Vec<String> {
+        line.value
+            .split(';')
+            .map(unescape_value)
+            .filter(|part| !part.is_empty())
+            .collect()
+    };
+    let name = lines
+        .iter()
+        .find(|l| l.name == "FN")
+        .map(|l| l.text())
+        .filter(|n| !n.is_empty())
+        .or_else(|| {
+            // `N` is family;given;additional;prefix;suffix
+            let line = lines.iter().find(|l| l.name == "N")?;
+            let mut parts: Vec<String> = line.value.split(';').map(str::to_string).collect();
+            parts.resize(5, String::new());
+            let ordered = [&parts[3], &parts[1], &parts[2], &parts[0], &parts[4]];
+            let name = ordered
+                .iter()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            (!name.is_empty()).then_some(name)
+        })
+        .unwrap_or_else(|| "(unnamed)".to_string());
+
+    let mut text = format!("Contact: {}\n", name);
+    for line in lines {
+        let (label, value) = match line.name.as_str() {
+            "NICKNAME" => ("Nickname", line.text()),
+            "ORG" => ("Organization", components(line).join(", ")),
+            "TITLE" => ("Title", line.text()),
+            "ROLE" => ("Role", line.text()),
+            "EMAIL" => ("Email", line.text()),
+            "TEL" => ("Phone", line.text()),
+            "ADR" => ("Address", components(line).join(", ")),
+            "URL" => ("Website", line.text()),
+            "BDAY" => ("Birthday", line.text()),
+            "NOTE" => ("Note", line.text()),
+            _ => continue,
+        };
+        if value.is_empty() {
+            continue;
+        }
+        match line.param("TYPE") {
+            Some(kind) if matches!(line.name.as_str(), "EMAIL" | "TEL" | "ADR") => {
+                text.push_str(&format!("{} ({}): {}\n", label, kind.to_lowercase(), value))
+            }
+            _ => text.push_str(&format!("{}: {}\n", label, value)),
+        }
+    }
+    text
+}
+
+/// A titled part of a document (an e-book chapter), chunked separately so
+/// every chunk carries its section title.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// Whether an extension is an e-book format handled by [`extract_ebook`].
+pub fn is_ebook_extension(extension: &str) -> bool {
+    matches!(extension.to_lowercase().as_str(), "epub" | "mobi" | "azw")
+}
+
+/// Extract the chapters of an `.epub` or `.mobi` e-book.
+pub fn extract_ebook(path: &Path) -> Result<Vec<Section>> {
+    ebook_sections(&Source::File(path))
+}
+
+fn ebook_sections(source: &Source) -> Result<Vec<Section>> {
+    let sections = if source.extension() == "epub" {
+        extract_epub(source)?
+    } else {
+        extract_mobi(source)?
+    };
+    if sections.is_empty() {
+        return Err(GhostError::Indexer(format!(
+            "No extractable text in e-book: {}",
+            source.display()
+        )));
+    }
+    Ok(sections)
+}
+
+fn join_sections(sections: &[Section]) -> String {
+    sections
+        .iter()
+        .map(|s| match &s.title {
+            Some(title) => format!("{}\n\n{}", title, s.text),
+            None => s.text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Number untitled chapters and drop empty ones (cover pages, blank spine items).
+fn finish_sections(chapters: Vec<(Option<String>, String)>) -> Vec<Section> {
+    chapters
+        .into_iter()
+        .map(|(title, text)| (title, text.trim().to_string()))
+        .filter(|(_, text)| !text.is_empty())
+        .enumerate()
+        .map(|(i, (title, text))| Section {
+            title: Some(title.unwrap_or_else(|| format!("Chapter {}", i + 1))),
+            text,
+        })
+        .collect()
+}
+
+/// Extract an EPUB (ZIP of XHTML files) chapter by chapter, in reading order.
+///
+/// Chapter titles come from the table of contents (EPUB 3 `nav` or EPUB 2
+/// `toc.ncx`), falling back to the chapter's first heading.
+fn extract_epub(source: &Source) -> Result<Vec<Section>> {
+    let mut archive = zip::ZipArchive::new(source.reader()?).map_err(|e| {
+        GhostError::Indexer(format!(
+            "Failed to read EPUB ZIP {}: {}",
+            source.display(),
+            e
+        ))
+    })?;
+    let mut read = |name: &str| -> Option<String> {
+        let mut entry = archive.by_name(name).ok()?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content).ok()?;
+        Some(content)
+    };
+
+    let container = read("META-INF/container.xml")
+        .ok_or_else(|| GhostError::Indexer("EPUB has no META-INF/container.xml".into()))?;
+    let opf_path = xml_tags(&container, "rootfile")
+        .iter()
+        .find_map(|tag| xml_attr(tag, "full-path"))
+        .ok_or_else(|| GhostError::Indexer("EPUB container has no rootfile".into()))?;
+    let opf = read(&opf_path)
+        .ok_or_else(|| GhostError::Indexer(format!("EPUB package {} is missing", opf_path)))?;
+    let base = match opf_path.rfind('/') {
+        Some(i) => &opf_path[..=i],
+        None => "",
+    };
+
+    // Manifest: id -> (href, media type, properties)
+    let manifest: Vec<(String, String, String, String)> = xml_tags(&opf, "item")
+        .iter()
+        .filter_map(|tag| {
+            Some((
+                xml_attr(tag, "id")?,
+                xml_attr(tag, "href")?,
+                xml_attr(tag, "media-type").unwrap_or_default(),
+                xml_attr(tag, "properties").unwrap_or_default(),
+            ))
+        })
+        .collect();
+    let href_of = |id: &str| {
+        manifest
+            .iter()
+            .find(|(item_id, ..)| item_id == id)
+            .map(|(_, href, ..)| resolve_href(base, href))
+    };
+
+    // Table of contents: chapter file -> title
+    let mut toc: Vec<(String, String)> = Vec::new();
+    let nav = manifest
+        .iter()
+        .find(|(.., props)| props.split_whitespace().any(|p| p == "nav"));
+    let ncx = manifest
+        .iter()
+        .find(|(_, _, media, _)| media == "application/x-dtbncx+xml");
+    if let Some((_, href, ..)) = nav {
+        let nav_path = resolve_href(base, href);
+        if let Some(nav_doc) = read(&nav_path) {
+            toc = nav_links(&nav_doc, &dir_of(&nav_path));
+        }
+    }
+    if toc.is_empty() {
+        if let Some((_, href, ..)) = ncx {
+            let ncx_path = resolve_href(base, href);
+            if let Some(ncx_doc) = read(&ncx_path) {
+                toc = ncx_links(&ncx_doc, &dir_of(&ncx_path));
+            }
+        }
+    }
+
+    let mut chapters = Vec::new();
+    for itemref in xml_tags(&opf, "itemref") {
+        let Some(chapter_path) = xml_attr(itemref, "idref").and_then(|id| href_of(&id)) else {
+            continue;
+        };
+        let Some(xhtml) = read(&chapter_path) else {
+            continue;
+        };
+        let title = toc
+            .iter()
+            .find(|(href, _)| *href == chapter_path)
+            .map(|(_, title)| title.clone())
+            .or_else(|| first_heading(&xhtml));
+        chapters.push((title, strip_html_tags(&xhtml)));
+    }
+    Ok(finish_sections(chapters))
+}
+
+fn dir_of(path: &str) -> String {
+    match path.rfind('/') {
+        Some(i) => path[..=i].to_string(),
+        None => String::new(),
+    }
+}
+
+/// Resolve a relative, possibly percent-encoded href against `base` (a directory
+/// ending in `/`), dropping any `#fragment`.
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or("");
+    let href = percent_decode(href);
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// `(chapter path, title)` pairs from an EPUB 3 navigation document.
+fn nav_links(nav: &str, base: &str) -> Vec<(String, String)> {
+    // Only the table of contents, not landmarks or page lists
+    let toc = nav
+        .find("epub:type=\"toc\"")
+        .map(|start| {
+            let rest = &nav[start..];
+            &rest[..rest.find("</nav>").unwrap_or(rest.len())]
+        })
+        .unwrap_or(nav);
+    let mut links = Vec::new();
+    let mut rest = toc;
+    while let Some(start) = rest.find("<a ") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[2..tag_end];
+        let Some(close) = rest.find("</a>") else {
+            break;
+        };
+        let title = collapse_whitespace(&strip_html_tags(&rest[tag_end + 1..close]));
+        if let Some(href) = xml_attr(tag, "href") {
+            if !title.is_empty() {
+                links.push((resolve_href(base, &href), title));
+            }
+        }
+        rest = &rest[close..];
+    }
+    links
+}
+
+/// `(chapter path, title)` pairs from an EPUB 2 `toc.ncx`.
+fn ncx_links(ncx: &str, base: &str) -> Vec<(String, String)> {
+    ncx.split("<navPoint")
+        .skip(1)
+        .filter_map(|point| {
+            let label_start = point.find("<text>")? + "<text>".len();
+            let label_end = point[label_start..].find("</text>")? + label_start;
+            let title = collapse_whitespace(&strip_html_tags(&point[label_start..label_end]));
+            let src = xml_tags(point, "content")
+                .iter()
+                .find_map(|tag| xml_attr(tag, "src"))?;
+            (!title.is_empty()).then(|| (resolve_href(base, &src), title))
+        })
+        .collect()
+}
+
+/// Text of the first `<h1>`–`<h3>` (or `<title>`) in an XHTML/HTML fragment.
+fn first_heading(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets valid in `html`
+    let lower = html.to_ascii_lowercase();
+    ["<h1", "<h2", "<h3", "<title"].iter().find_map(|open| {
+        let start = lower.find(open)?;
+        let content_start = lower[start..].find('>')? + start + 1;
+        let close = format!("</{}", &open[1..]);
+        let content_end = lower[content_start..].find(&close)? + content_start;
+        let title = collapse_whitespace(&strip_html_tags(&html[content_start..content_end]));
+        (!title.is_empty()).then_some(title)
+    })
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Attribute strings of every `<name ...>` tag (namespace prefixes allowed).
+fn xml_tags<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut tags = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..end];
+        let tag_name = tag
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .trim_end_matches('/');
+        let local = tag_name.rsplit(':').next().unwrap_or(tag_name);
+        if local == name {
+            tags.push(tag);
+        }
+        rest = &rest[end + 1..];
+    }
+    tags
+}
+
+/// Value of `name="..."` (or single-quoted) in a tag's attribute string.
+fn xml_attr(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(name) {
+        let before = rest[..pos].chars().last();
+        let after = &rest[pos + name.len()..];
+        rest = after;
+        if !matches!(before, Some(c) if c.is_whitespace()) {
+            continue;
+        }
+        let Some(value) = after.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            continue;
+        }
+        let end = value[1..].find(quote)?;
+        return Some(value[1..=end].to_string());
+    }
+    None
+}
+
+/// Extract a MOBI (Mobipocket / PalmDOC) e-book, split into chapters at page breaks.
+///
+/// Supports uncompressed and PalmDOC-compressed books. HUFF/CDIC-compressed
+/// and DRM-protected books are rejected.
+fn extract_mobi(source: &Source) -> Result<Vec<Section>> {
+    let data = source.read()?;
+    let html = mobi_html(&data).map_err(|e| {
+        GhostError::Indexer(format!("Failed to parse MOBI {}: {}", source.display(), e))
+    })?;
+
+    let chapters = html
+        .split("<mbp:pagebreak")
+        .enumerate()
+        .map(|(i, part)| {
+            // Drop the rest of the page-break tag itself
+            let part = match part.find('>') {
+                Some(end) if i > 0 => &part[end + 1..],
+                _ => part,
+            };
+            (first_heading(part), strip_html_tags(part))
+        })
+        .collect();
+    Ok(finish_sections(chapters))
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Decode the HTML text of a MOBI file from its PalmDB records.
+fn mobi_html(data: &[u8]) -> std::result::Result<String, String> {
+    const TRUNCATED: &str = "truncated file";
+    let record_count = be_u16(data, 76).ok_or(TRUNCATED)?;
+    let record_offsets: Vec<usize> = (0..record_count)
+        .map(|i| be_u32(data, 78 + i * 8).ok_or(TRUNCATED))
+        .collect::<std::result::Result<_, _>>()?;
+    let record = |i: usize| -> Option<&[u8]> {
+        let start = *record_offsets.get(i)?;
+        let end = record_offsets.get(i + 1).copied().unwrap_or(data.len());
+        data.get(start..end.max(start))
+    };
+
+    let header = record(0).ok_or(TRUNCATED)?;
+    let compression = be_u16(header, 0).ok_or(TRUNCATED)?;
+    let text_records = be_u16(header, 8).ok_or(TRUNCATED)?;
+    if be_u16(header, 12).ok_or(TRUNCATED)? != 0 {
+        return Err("book is DRM-protected".into());
+    }
+    if compression != 1 && compression != 2 {
+        return Err(format!("unsupported compression type {}", compression));
+    }
+
+    // MOBI header: text encoding and trailing-entry flags
+    let (utf8, extra_flags) = if header.get(16..20) == Some(b"MOBI".as_slice()) {
+        let header_len = be_u32(header, 20).unwrap_or(0);
+        let utf8 = be_u32(header, 28) == Some(65001);
+        let flags = if header_len >= 0xE4 {
+            be_u16(header, 0xF2).unwrap_or(0)
+        } else {
+            0
+        };
+        (utf8, flags)
+    } else {
+        (false, 0)
+    };
+
+    let mut bytes = Vec::new();
+    for i in 1..=text_records {
+        let Some(raw) = record(i) else {
+            break;
+        };
+        let raw = &raw[..raw.len() - trailing_entries_size(raw, extra_flags).min(raw.len())];
+        if compression == 2 {
+            bytes.extend(palmdoc_decompress(raw));
+        } else {
+            bytes.extend_from_slice(raw);
+        }
+    }
+
+    Ok(if utf8 {
+        String::from_utf8_lossy(&bytes).into_owned()
+    } else {
+        decode_charset(&bytes, "windows-1252")
+    })
+}
+
+/// Size of the trailing entries appended to a MOBI text record.
+fn trailing_entries_size(record: &[u8], flags: usize) -> usize {
+    let mut size = 0;
+    for bit in 1..16 {
+        if flags & (1 << bit) == 0 {
+            continue;
+        }
+        // Backward-encoded variable-length size at the current end
+        let end = record.len().saturating_sub(size);
+        let mut value = 0usize;
+        let mut shift = 0;
+        for &byte in record[..end].iter().rev().take(4) {
+            value |= ((byte & 0x7F) as usize) << shift;
+            shift += 7;
+            if byte & 0x80 != 0 {
+                break;
+            }
+        }
+        size += value;
+    }
+    if flags & 1 != 0 {
+        // Multibyte character overlap
+        if let Some(&byte) = record
+            .len()
+            .checked_sub(size + 1)
+            .and_then(|i| record.get(i))
+        {
+            size += (byte & 0x3) as usize + 1;
+        }
+    }
+    size
+}
+
+/// Decompress a PalmDOC (LZ77 variant) record.
+fn palmdoc_decompress(input: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::with_capacity(input.len() * 2);
+    let mut i = 0;
+    while i < input.len() {
+        let byte = input[i];
+        i += 1;
+        match byte {
+            // Literal run of the next 1-8 bytes
+            0x01..=0x08 => {
+                let end = (i + byte as usize).min(input.len());
+                out.extend_from_slice(&input[i..end]);
+                i = end;
+            }
+            // Back-reference: 11-bit distance, 3-bit length
+            0x80..=0xBF => {
+                let Some(&next) = input.get(i) else {
+                    break;
+                };
+                i += 1;
+                let pair = ((byte as usize) << 8 | next as usize) & 0x3FFF;
+                let distance = pair >> 3;
+                let length = (pair & 0x7) + 3;
+                if distance == 0 || distance > out.len() {
+                    continue;
+                }
+                for _ in 0..length {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+            // Space followed by a character
+            0xC0..=0xFF => {
+                out.push(b' ');
+                out.push(byte ^ 0x80);
+            }
+            _ => out.push(byte),
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,6 +1928,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_extract_bytes() {
+        let text = extract_bytes("notes/todo.md", b"# Todo\n\nRenew the lease").unwrap();
+        assert!(text.contains("Renew the lease"));
+        let text = extract_bytes("q3/budget.CSV", b"item,cost\nrent,1200\n").unwrap();
+        assert!(text.contains("rent"));
+        // Archives in memory are nested archives, which aren't opened
+        assert!(extract_bytes("inner.zip", b"PK").is_err());
+    }
+
     #[test]
     fn test_supported_extensions() {
         assert!(is_supported_extension("txt"));
@@ -280,7 +1945,371 @@ mod tests {
         assert!(is_supported_extension("md"));
         assert!(is_supported_extension("xlsx"));
         assert!(is_supported_extension("docx"));
+        assert!(is_supported_extension("eml"));
+        assert!(is_supported_extension("MBOX"));
+        assert!(is_supported_extension("epub"));
+        assert!(is_supported_extension("mobi"));
+        assert!(is_supported_extension("ics"));
+        assert!(is_supported_extension("VCF"));
+        assert!(is_supported_extension("zip"));
+        assert!(is_supported_extension("tgz"));
         assert!(!is_supported_extension("exe"));
         assert!(!is_supported_extension("dll"));
     }
+
+    #[test]
+    fn test_extract_eml() {
+        let dir = std::env::temp_dir().join("ghost_test_extract_eml");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("invoice.eml");
+        std::fs::write(
+            &path,
+            "From: =?UTF-8?B?Sm9zw6k=?= <jose@example.com>\r\n\
+             Subject: =?UTF-8?Q?Factura_de_marzo?=\r\n\
+             Date: Tue, 5 Mar 2024 18:30:00 +0100\r\n\
+             Content-Type: multipart/alternative; boundary=\"b1\"\r\n\
+             \r\n\
+             --b1\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: quoted-printable\r\n\
+             \r\n\
+             Total: 120 =E2=82=AC, due=\r\n in April\r\n\
+             --b1\r\n\
+             Content-Type: text/html\r\n\
+             \r\n\
+             <p>Total: 120 &euro;</p>\r\n\
+             --b1--\r\n",
+        )
+        .unwrap();
+
+        let (text, metadata) = extract_email(&path).unwrap();
+        assert!(text.contains("Subject: Factura de marzo"));
+        assert!(text.contains("Total: 120 €, due in April"));
+        assert!(!text.contains("<p>"));
+        assert_eq!(metadata.sender.as_deref(), Some("José <jose@example.com>"));
+        assert_eq!(metadata.sent_at.as_deref(), Some("2024-03-05T17:30:00Z"));
+        assert!(extract_text(&path).unwrap().contains("Factura"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_extract_mbox() {
+        let dir = std::env::temp_dir().join("ghost_test_extract_mbox");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("archive.mbox");
+        std::fs::write(
+            &path,
+            "From alice@example.com Mon Jan  1 00:00:00 2024\n\
+             From: Alice <alice@example.com>\n\
+             Subject: Lunch\n\
+             Date: Mon, 1 Jan 2024 12:00:00 +0000\n\
+             Message-ID: <lunch-1@example.com>\n\
+             \n\
+             Pizza on Friday?\n\
+             >From here on, no salad.\n\
+             \n\
+             From alice@example.com Mon Jan  1 00:00:00 2024\n\
+             From: Alice <alice@example.com>\n\
+             Subject: Lunch\n\
+             Message-ID: <lunch-1@example.com>\n\
+             \n\
+             Pizza on Friday?\n\
+             \n\
+             From bob@example.com Mon Dec  4 00:00:00 2023\n\
+             From: Bob <bob@example.com>\n\
+             Subject: Re: Lunch\n\
+             Date: Mon, 4 Dec 2023 09:15:00 +0000\n\
+             Content-Type: text/plain\n\
+             Content-Transfer-Encoding: base64\n\
+             \n\
+             U291bmRzIGdvb2Qu\n",
+        )
+        .unwrap();
+
+        let (text, metadata) = extract_email(&path).unwrap();
+        // The second copy of Alice's message is dropped
+        assert_eq!(text.matches("Pizza on Friday?").count(), 1);
+        assert!(text.contains("\nFrom here on, no salad."));
+        assert!(text.contains("Sounds good."));
+        assert_eq!(
+            metadata.sender.as_deref(),
+            Some("Alice <alice@example.com>, Bob <bob@example.com>")
+        );
+        // Earliest message date, so `before:` matches any message in the box
+        assert_eq!(metadata.sent_at.as_deref(), Some("2023-12-04T09:15:00Z"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_extract_calendar() {
+        let dir = std::env::temp_dir().join("ghost_test_extract_ics");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("work.ics");
+        std::fs::write(
+            &path,
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Budget review\r\n\
+             DTSTART;TZID=Europe/Madrid:20250312T100000\r\n\
+             DURATION:PT1H30M\r\n\
+             LOCATION:Room 4\\, second floor\r\n\
+             ORGANIZER;CN=\"Dana Smith\":mailto:dana@example.com\r\n\
+             ATTENDEE;CN=Bob;ROLE=REQ-PARTICIPANT:mailto:bob@example.com\r\n\
+             ATTENDEE:mailto:carol@example.com\r\n\
+             DESCRIPTION:Q2 numbers\\nBring the forecast and the hiring\r\n  plan\r\n\
+             BEGIN:VALARM\r\n\
+             DESCRIPTION:Reminder\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Offsite\r\n\
+             DTSTART;VALUE=DATE:20250320\r\n\
+             DTEND;VALUE=DATE:20250322\r\n\
+             RRULE:FREQ=YEARLY\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        let (text, times) = extract_calendar(&path).unwrap();
+        assert!(text.contains("Event: Budget review"));
+        assert!(text.contains("When: Wednesday, March 12, 2025 10:00 to 11:30"));
+        assert!(text.contains("Where: Room 4, second floor"));
+        assert!(text.contains("Organizer: Dana Smith"));
+        assert!(text.contains("Attendees: Bob, carol@example.com"));
+        assert!(text.contains("Bring the forecast and the hiring plan"));
+        assert!(!text.contains("Reminder"));
+        assert!(text.contains("When: Thursday, March 20, 2025 to Friday, March 21, 2025"));
+        assert!(text.contains("Repeats: yearly"));
+        assert_eq!(
+            times,
+            vec![
+                EventTime {
+                    starts_at: "2025-03-12T10:00:00".into(),
+                    ends_at: "2025-03-12T11:29:59".into(),
+                },
+                EventTime {
+                    starts_at: "2025-03-20T00:00:00".into(),
+                    ends_at: "2025-03-21T23:59:59".into(),
+                },
+            ]
+        );
+        assert!(extract_text(&path).unwrap().contains("Budget review"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_ical_duration() {
+        assert_eq!(
+            parse_ical_duration("PT1H30M"),
+            Some(chrono::Duration::minutes(90))
+        );
+        assert_eq!(parse_ical_duration("P1W"), Some(chrono::Duration::days(7)));
+        assert_eq!(
+            parse_ical_duration("-P1DT2H"),
+            Some(-chrono::Duration::hours(26))
+        );
+        assert_eq!(parse_ical_duration("1H"), None);
+    }
+
+    #[test]
+    fn test_extract_contacts() {
+        let dir = std::env::temp_dir().join("ghost_test_extract_vcf");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("contacts.vcf");
+        std::fs::write(
+            &path,
+            "BEGIN:VCARD\r\n\
+             VERSION:3.0\r\n\
+             FN:Dana Smith\r\n\
+             N:Smith;Dana;;;\r\n\
+             ORG:Acme;Finance\r\n\
+             TITLE:Budget lead\r\n\
+             item1.EMAIL;TYPE=WORK:dana@example.com\r\n\
+             TEL;TYPE=CELL:+1 555 0100\r\n\
+             ADR;TYPE=HOME:;;12 Main St;Springfield;;12345;USA\r\n\
+             END:VCARD\r\n\
+             BEGIN:VCARD\r\n\
+             VERSION:2.1\r\n\
+             N;ENCODING=QUOTED-PRINTABLE;CHARSET=UTF-8:Garc=C3=ADa;Jos=C3=A9\r\n\
+             NOTE;ENCODING=QUOTED-PRINTABLE;CHARSET=UTF-8:Met at the =\r\n\
+             conference\r\n\
+             END:VCARD\r\n",
+        )
+        .unwrap();
+
+        let text = extract_contacts(&path).unwrap();
+        assert!(text.contains("Contact: Dana Smith"));
+        assert!(text.contains("Organization: Acme, Finance"));
+        assert!(text.contains("Email (work): dana@example.com"));
+        assert!(text.contains("Phone (cell): +1 555 0100"));
+        assert!(text.contains("Address (home): 12 Main St, Springfield, 12345, USA"));
+        // vCard 2.1 without FN: the name comes from N, quoted-printable decoded
+        assert!(text.contains("Contact: José García"));
+        assert!(text.contains("Note: Met at the conference"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_first_heading_after_non_ascii() {
+        // 'İ' grows from 2 to 3 bytes when Unicode-lowercased
+        assert_eq!(
+            first_heading("<p>İİ</p><H1>Bölüm</h1>"),
+            Some("Bölüm".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_epub() {
+        use std::io::Write as _;
+
+        let dir = std::env::temp_dir().join("ghost_test_extract_epub");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("novel.epub");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        let files = [
+            (
+                "META-INF/container.xml",
+                r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<package><manifest>
+                    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+                    <item id="c1" href="text/ch%201.xhtml" media-type="application/xhtml+xml"/>
+                    <item id="c2" href="text/ch2.xhtml" media-type="application/xhtml+xml"/>
+                    <item id="cover" href="text/cover.xhtml" media-type="application/xhtml+xml"/>
+                </manifest><spine>
+                    <itemref idref="cover"/><itemref idref="c1"/><itemref idref="c2"/>
+                </spine></package>"#,
+            ),
+            (
+                "OEBPS/nav.xhtml",
+                r#"<nav epub:type="toc"><ol>
+                    <li><a href="text/ch%201.xhtml#start">Chapter 4: The Storm</a></li>
+                </ol></nav>"#,
+            ),
+            (
+                "OEBPS/text/ch 1.xhtml",
+                "<html><head><title>x</title><style>p{}</style></head>\
+                 <body><p>Rain hit the windows &#8212; hard.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/ch2.xhtml",
+                "<html><body><h2>Aftermath</h2><p>Morning came.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/cover.xhtml",
+                "<html><body><img src=\"c.jpg\"/></body></html>",
+            ),
+        ];
+        for (name, content) in files {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let sections = extract_ebook(&path).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title.as_deref(), Some("Chapter 4: The Storm"));
+        assert_eq!(sections[0].text, "Rain hit the windows \u{2014} hard.");
+        assert_eq!(sections[1].title.as_deref(), Some("Aftermath"));
+        assert!(sections[1].text.contains("Morning came."));
+        assert!(extract_text(&path)
+            .unwrap()
+            .contains("Chapter 4: The Storm"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_palmdoc_decompress() {
+        // Literal "ab", space+'c', then copy 3 bytes from distance 4
+        let input = [0x02, b'a', b'b', 0x80 ^ b'c', 0x80, 0x20];
+        assert_eq!(palmdoc_decompress(&input), b"ab cab ".to_vec());
+    }
+
+    #[test]
+    fn test_extract_mobi() {
+        let html = "<html><body><h1>Prologue</h1><p>It began.</p>\
+                    <mbp:pagebreak/><h2>Chapter 1: Arrival</h2><p>We landed.</p></body></html>";
+        let mut record0 = vec![0u8; 16];
+        record0[1] = 1; // no compression
+        record0[4..8].copy_from_slice(&(html.len() as u32).to_be_bytes());
+        record0[9] = 1; // one text record
+        record0.extend_from_slice(b"MOBI");
+        record0.extend_from_slice(&24u32.to_be_bytes());
+        record0.extend_from_slice(&[0u8; 4]);
+        record0.extend_from_slice(&65001u32.to_be_bytes());
+
+        let header_len = 78 + 2 * 8 + 2;
+        let mut data = vec![0u8; 76];
+        data.extend_from_slice(&2u16.to_be_bytes());
+        for offset in [header_len, header_len + record0.len()] {
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&[0u8; 4]);
+        }
+        data.extend_from_slice(&[0u8; 2]);
+        data.extend_from_slice(&record0);
+        data.extend_from_slice(html.as_bytes());
+
+        let dir = std::env::temp_dir().join("ghost_test_extract_mobi");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("book.mobi");
+        std::fs::write(&path, &data).unwrap();
+
+        let sections = extract_ebook(&path).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title.as_deref(), Some("Prologue"));
+        assert_eq!(sections[1].title.as_deref(), Some("Chapter 1: Arrival"));
+        assert!(sections[1].text.contains("We landed."));
+        assert!(!sections[1].text.contains("/>"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows =
+            parse_csv("Item,Note\r\n\"Desk, oak\",\"Says \"\"hi\"\"\"\r\nLamp,\"two\nlines\"\n");
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], vec!["Desk, oak", "Says \"hi\""]);
+        assert_eq!(rows[2], vec!["Lamp", "two\nlines"]);
+
+        let semicolons = parse_csv("Name;Amount\nRent;1200,50");
+        assert_eq!(semicolons[1], vec!["Rent", "1200,50"]);
+    }
+
+    #[test]
+    fn test_extract_csv_sections() {
+        let dir = std::env::temp_dir().join("ghost_test_extract_csv");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("budget.csv");
+        let mut csv = String::from("Category,Amount,\n");
+        for i in 0..30 {
+            csv.push_str(&format!("Item {},{},\n", i, i * 10));
+        }
+        csv.push_str(",,\nGroceries,450,weekly\n");
+        std::fs::write(&path, csv).unwrap();
+
+        let sections = extract_sections(&path).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title.as_deref(), Some("budget (rows 2–26)"));
+        assert!(sections[0]
+            .text
+            .starts_with("Category: Item 0; Amount: 0\n"));
+        assert_eq!(sections[1].title.as_deref(), Some("budget (rows 27–33)"));
+        assert!(sections[1]
+            .text
+            .ends_with("Category: Groceries; Amount: 450; Column 3: weekly"));
+        assert!(extract_text(&path).unwrap().contains("Amount: 450"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
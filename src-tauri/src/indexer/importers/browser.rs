@@ -0,0 +1,540 @@
+//! Browser history and bookmarks.
+//!
+//! Reads Chrome, Edge, and Firefox profiles from their usual locations:
+//! Chromium-based browsers keep history in a `History` SQLite database and
+//! bookmarks in a `Bookmarks` JSON file; Firefox keeps both in
+//! `places.sqlite`. Databases are read from temporary copies (see
+//! [`super::DatabaseCopy`]), so a running browser neither blocks the import
+//! nor sees its files touched.
+//!
+//! Every http(s) page becomes one document whose path is its URL: its title,
+//! URL, bookmark folder and — with `page_text` on — the page description the
+//! browser cached (Firefox only; Chromium browsers don't keep page text).
+//! Pages are tagged `browser`, plus `bookmark` / `history` and the browser's
+//! name, so `tag:browser` narrows a search to the web.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{DatabaseCopy, ImportStats, ImportedDocument};
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::Result;
+
+/// Importer name, and the tag every page gets.
+pub const SOURCE: &str = "browser";
+
+/// Seconds between 1601-01-01 (Chromium's epoch) and 1970-01-01.
+const CHROMIUM_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// Longest page description kept.
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+/// A supported browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+}
+
+impl Browser {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Chrome => "chrome",
+            Self::Edge => "edge",
+            Self::Firefox => "firefox",
+        }
+    }
+
+    /// Directory holding the browser's profiles on this OS.
+    fn profiles_root(self) -> Option<PathBuf> {
+        let relative: &[&str] = match self {
+            #[cfg(target_os = "linux")]
+            Self::Chrome => &["google-chrome"],
+            #[cfg(target_os = "linux")]
+            Self::Edge => &["microsoft-edge"],
+            #[cfg(target_os = "linux")]
+            Self::Firefox => return dirs::home_dir().map(|h| h.join(".mozilla/firefox")),
+            #[cfg(target_os = "macos")]
+            Self::Chrome => &["Google", "Chrome"],
+            #[cfg(target_os = "macos")]
+            Self::Edge => &["Microsoft Edge"],
+            #[cfg(target_os = "macos")]
+            Self::Firefox => &["Firefox", "Profiles"],
+            #[cfg(target_os = "windows")]
+            Self::Chrome => &["Google", "Chrome", "User Data"],
+            #[cfg(target_os = "windows")]
+            Self::Edge => &["Microsoft", "Edge", "User Data"],
+            #[cfg(target_os = "windows")]
+            Self::Firefox => &["Mozilla", "Firefox", "Profiles"],
+            #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+            _ => return None,
+        };
+        // Linux: ~/.config; macOS: ~/Library/Application Support;
+        // Windows: %LOCALAPPDATA% for Chromium, %APPDATA% for Firefox
+        let base = if cfg!(target_os = "windows") && self != Self::Firefox {
+            dirs::data_local_dir()
+        } else if cfg!(target_os = "linux") {
+            dirs::config_dir()
+        } else {
+            dirs::data_dir()
+        }?;
+        Some(relative.iter().fold(base, |path, part| path.join(part)))
+    }
+}
+
+/// Browser import preferences. Off until the user turns it on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserImportSettings {
+    /// Re-import every hour along with the periodic re-index.
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_browsers")]
+    pub browsers: Vec<Browser>,
+    #[serde(default = "default_true")]
+    pub history: bool,
+    #[serde(default = "default_true")]
+    pub bookmarks: bool,
+    /// Also index page descriptions the browser cached.
+    #[serde(default)]
+    pub page_text: bool,
+    /// Most recently visited pages imported per profile.
+    #[serde(default = "default_max_history_entries")]
+    pub max_history_entries: usize,
+}
+
+fn default_browsers() -> Vec<Browser> {
+    vec![Browser::Chrome, Browser::Edge, Browser::Firefox]
+}
+fn default_true() -> bool {
+    true
+}
+fn default_max_history_entries() -> usize {
+    5000
+}
+
+impl Default for BrowserImportSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            browsers: default_browsers(),
+            history: true,
+            bookmarks: true,
+            page_text: false,
+            max_history_entries: default_max_history_entries(),
+        }
+    }
+}
+
+/// A browser profile found on this computer.
+#[derive(Debug, Clone, Serialize)]
+pub struct BrowserProfile {
+    pub browser: Browser,
+    /// Profile directory name, e.g. "Default" or "abcd1234.default-release".
+    pub name: String,
+    pub path: String,
+}
+
+/// A page read from a profile, before pages are merged.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PageEntry {
+    url: String,
+    title: Option<String>,
+    /// Unix seconds of the last visit or of bookmarking.
+    time: Option<i64>,
+    bookmarked: bool,
+    visited: bool,
+    folder: Option<String>,
+    description: Option<String>,
+}
+
+/// Profiles of the given browsers that have history or bookmarks.
+pub fn find_profiles(browsers: &[Browser]) -> Vec<BrowserProfile> {
+    let mut profiles = Vec::new();
+    for &browser in browsers {
+        let Some(root) = browser.profiles_root() else {
+            continue;
+        };
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let has_data = match browser {
+                Browser::Firefox => path.join("places.sqlite").is_file(),
+                _ => path.join("History").is_file() || path.join("Bookmarks").is_file(),
+            };
+            if has_data {
+                profiles.push(BrowserProfile {
+                    browser,
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    path: path.to_string_lossy().into_owned(),
+                });
+            }
+        }
+    }
+    profiles.sort_by(|a, b| (a.browser, &a.name).cmp(&(b.browser, &b.name)));
+    profiles
+}
+
+/// Import every profile of the configured browsers.
+pub async fn import(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    settings: &BrowserImportSettings,
+) -> Result<ImportStats> {
+    let mut pages: BTreeMap<String, (PageEntry, Vec<Browser>)> = BTreeMap::new();
+    let mut complete = true;
+    for profile in find_profiles(&settings.browsers) {
+        let entries = match read_profile(&profile, settings) {
+            Ok(entries) => entries,
+            Err(e) => {
+                // Keep its pages from earlier imports
+                complete = false;
+                tracing::warn!(
+                    "Cannot read {} profile {}: {}",
+                    profile.browser.as_str(),
+                    profile.name,
+                    e
+                );
+                continue;
+            }
+        };
+        for entry in entries {
+            let (page, browsers) = pages
+                .entry(entry.url.clone())
+                .or_insert_with(|| (PageEntry::default(), Vec::new()));
+            merge(page, entry);
+            if !browsers.contains(&profile.browser) {
+                browsers.push(profile.browser);
+            }
+        }
+    }
+    let documents: Vec<ImportedDocument> = pages
+        .into_values()
+        .map(|(page, browsers)| to_document(page, &browsers))
+        .collect();
+    super::import_all(db, embedding_engine, SOURCE, &documents, complete).await
+}
+
+fn read_profile(
+    profile: &BrowserProfile,
+    settings: &BrowserImportSettings,
+) -> Result<Vec<PageEntry>> {
+    let dir = Path::new(&profile.path);
+    let mut entries = Vec::new();
+    match profile.browser {
+        Browser::Firefox => {
+            let places = DatabaseCopy::open(&dir.join("places.sqlite"))?;
+            if settings.history {
+                entries.extend(read_firefox_history(
+                    &places.conn,
+                    settings.max_history_entries,
+                    settings.page_text,
+                )?);
+            }
+            if settings.bookmarks {
+                entries.extend(read_firefox_bookmarks(&places.conn)?);
+            }
+        }
+        Browser::Chrome | Browser::Edge => {
+            let history = dir.join("History");
+            if settings.history && history.is_file() {
+                let history = DatabaseCopy::open(&history)?;
+                entries.extend(read_chromium_history(
+                    &history.conn,
+                    settings.max_history_entries,
+                )?);
+            }
+            let bookmarks = dir.join("Bookmarks");
+            if settings.bookmarks && bookmarks.is_file() {
+                let json: serde_json::Value =
+                    serde_json::from_str(&std::fs::read_to_string(&bookmarks)?)?;
+                entries.extend(read_chromium_bookmarks(&json));
+            }
+        }
+    }
+    entries.retain(|e| super::is_web_url(&e.url));
+    Ok(entries)
+}
+
+/// Unix seconds from a Chromium timestamp (microseconds since 1601).
+fn chromium_time(micros: i64) -> Option<i64> {
+    (micros > 0).then(|| micros / 1_000_000 - CHROMIUM_EPOCH_OFFSET_SECS)
+}
+
+fn read_chromium_history(conn: &rusqlite::Connection, limit: usize) -> Result<Vec<PageEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT url, title, last_visit_time FROM urls
+         WHERE hidden = 0 ORDER BY last_visit_time DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(PageEntry {
+            url: row.get(0)?,
+            title: row.get(1)?,
+            time: chromium_time(row.get::<_, Option<i64>>(2)?.unwrap_or(0)),
+            visited: true,
+            ..Default::default()
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Bookmarks of a Chromium `Bookmarks` file, with their folder paths.
+fn read_chromium_bookmarks(json: &serde_json::Value) -> Vec<PageEntry> {
+    fn walk(node: &serde_json::Value, folder: &str, out: &mut Vec<PageEntry>) {
+        let name = node["name"].as_str().unwrap_or_default();
+        match node["type"].as_str() {
+            Some("url") => out.push(PageEntry {
+                url: node["url"].as_str().unwrap_or_default().to_string(),
+                title: Some(name.to_string()).filter(|n| !n.is_empty()),
+                time: node["date_added"]
+                    .as_str()
+                    .and_then(|t| t.parse().ok())
+                    .and_then(chromium_time),
+                bookmarked: true,
+                folder: Some(folder.to_string()).filter(|f| !f.is_empty()),
+                ..Default::default()
+            }),
+            Some("folder") => {
+                let folder = if folder.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{} / {}", folder, name)
+                };
+                for child in node["children"].as_array().into_iter().flatten() {
+                    walk(child, &folder, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut entries = Vec::new();
+    if let Some(roots) = json["roots"].as_object() {
+        for root in roots.values() {
+            walk(root, "", &mut entries);
+        }
+    }
+    entries
+}
+
+fn read_firefox_history(
+    conn: &rusqlite::Connection,
+    limit: usize,
+    page_text: bool,
+) -> Result<Vec<PageEntry>> {
+    // Older profiles have no description column
+    let has_description: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('moz_places') WHERE name = 'description'",
+        [],
+        |row| row.get(0),
+    )?;
+    let description = if page_text && has_description {
+        "description"
+    } else {
+        "NULL"
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT url, title, last_visit_date, {} FROM moz_places
+         WHERE last_visit_date IS NOT NULL AND hidden = 0
+         ORDER BY last_visit_date DESC LIMIT ?1",
+        description
+    ))?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(PageEntry {
+            url: row.get(0)?,
+            title: row.get(1)?,
+            time: row.get::<_, Option<i64>>(2)?.map(|t| t / 1_000_000),
+            visited: true,
+            description: row.get(3)?,
+            ..Default::default()
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+fn read_firefox_bookmarks(conn: &rusqlite::Connection) -> Result<Vec<PageEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.url, b.title, b.dateAdded, parent.title
+         FROM moz_bookmarks b
+         JOIN moz_places p ON p.id = b.fk
+         LEFT JOIN moz_bookmarks parent ON parent.id = b.parent
+         WHERE b.type = 1",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(PageEntry {
+            url: row.get(0)?,
+            title: row.get(1)?,
+            time: row.get::<_, Option<i64>>(2)?.map(|t| t / 1_000_000),
+            bookmarked: true,
+            folder: row.get::<_, Option<String>>(3)?.filter(|f| !f.is_empty()),
+            ..Default::default()
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Fold another entry for the same URL into `page`.
+fn merge(page: &mut PageEntry, entry: PageEntry) {
+    if page.url.is_empty() {
+        page.url = entry.url;
+    }
+    // Bookmark titles are chosen by the user; prefer them
+    if entry.bookmarked || page.title.as_deref().unwrap_or("").is_empty() {
+        if let Some(title) = entry.title.filter(|t| !t.trim().is_empty()) {
+            page.title = Some(title);
+        }
+    }
+    page.time = page.time.max(entry.time);
+    page.bookmarked |= entry.bookmarked;
+    page.visited |= entry.visited;
+    page.folder = page.folder.take().or(entry.folder);
+    page.description = page.description.take().or(entry.description);
+}
+
+fn to_document(page: PageEntry, browsers: &[Browser]) -> ImportedDocument {
+    let title = page
+        .title
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| page.url.clone());
+    let mut text = format!("{}\n{}", title, page.url);
+    if let Some(folder) = &page.folder {
+        text.push_str(&format!("\nBookmark folder: {}", folder));
+    }
+    if let Some(description) = page.description.as_deref().map(str::trim) {
+        if !description.is_empty() {
+            text.push_str("\n\n");
+            text.extend(description.chars().take(MAX_DESCRIPTION_CHARS));
+        }
+    }
+
+    let mut tags = vec![SOURCE.to_string()];
+    if page.bookmarked {
+        tags.push("bookmark".into());
+    }
+    if page.visited {
+        tags.push("history".into());
+    }
+    let mut browsers = browsers.to_vec();
+    browsers.sort();
+    tags.extend(browsers.iter().map(|b| b.as_str().to_string()));
+
+    ImportedDocument {
+        modified_at: page
+            .time
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string()),
+        path: page.url,
+        title,
+        text,
+        tags,
+        email: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_chromium_bookmarks() {
+        let json = serde_json::json!({
+            "roots": {
+                "bookmark_bar": {
+                    "type": "folder",
+                    "name": "Bookmarks bar",
+                    "children": [
+                        { "type": "url", "name": "Rust", "url": "https://www.rust-lang.org/",
+                          "date_added": "13350000000000000" },
+                        { "type": "folder", "name": "Recipes", "children": [
+                            { "type": "url", "name": "Bread", "url": "https://bread.example/" }
+                        ]}
+                    ]
+                },
+                "other": { "type": "folder", "name": "Other", "children": [] }
+            }
+        });
+        let entries = read_chromium_bookmarks(&json);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title.as_deref(), Some("Rust"));
+        // 2024-01-17, converted from microseconds since 1601
+        assert_eq!(entries[0].time, Some(1_705_526_400));
+        assert_eq!(
+            entries[1].folder.as_deref(),
+            Some("Bookmarks bar / Recipes")
+        );
+    }
+
+    #[test]
+    fn test_read_firefox_places() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT,
+                 last_visit_date INTEGER, hidden INTEGER DEFAULT 0, description TEXT);
+             CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER,
+                 parent INTEGER, title TEXT, dateAdded INTEGER);
+             INSERT INTO moz_places VALUES
+                 (1, 'https://docs.example/guide', 'Guide', 1700000000000000, 0, 'How to start'),
+                 (2, 'https://old.example/', 'Old', NULL, 0, NULL);
+             INSERT INTO moz_bookmarks VALUES
+                 (10, 2, NULL, 0, 'toolbar', 0),
+                 (11, 1, 2, 10, 'Old site', 1600000000000000);",
+        )
+        .unwrap();
+
+        let history = read_firefox_history(&conn, 10, true).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].time, Some(1_700_000_000));
+        assert_eq!(history[0].description.as_deref(), Some("How to start"));
+        assert!(read_firefox_history(&conn, 10, false).unwrap()[0]
+            .description
+            .is_none());
+
+        let bookmarks = read_firefox_bookmarks(&conn).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].url, "https://old.example/");
+        assert_eq!(bookmarks[0].folder.as_deref(), Some("toolbar"));
+    }
+
+    #[test]
+    fn test_merge_and_to_document() {
+        let mut page = PageEntry::default();
+        merge(
+            &mut page,
+            PageEntry {
+                url: "https://docs.example/".into(),
+                title: Some("Docs — Home".into()),
+                time: Some(1_700_000_000),
+                visited: true,
+                ..Default::default()
+            },
+        );
+        merge(
+            &mut page,
+            PageEntry {
+                url: "https://docs.example/".into(),
+                title: Some("Team docs".into()),
+                time: Some(1_600_000_000),
+                bookmarked: true,
+                folder: Some("Work".into()),
+                ..Default::default()
+            },
+        );
+        let doc = to_document(page, &[Browser::Firefox, Browser::Chrome]);
+        assert_eq!(doc.path, "https://docs.example/");
+        assert_eq!(doc.title, "Team docs");
+        assert_eq!(doc.modified_at, "2023-11-14T22:13:20Z");
+        assert!(doc.text.contains("Bookmark folder: Work"));
+        assert_eq!(
+            doc.tags,
+            ["browser", "bookmark", "history", "chrome", "firefox"]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Outlook and Apple Mail stores (`mail-import` feature).
+//!
+//! Apple Mail keeps every message as an `.emlx` file under `~/Library/Mail`:
+//! the message's length on the first line, the RFC 5322 message, then a
+//! property list of flags. Outlook `.pst`/`.ost` files are unpacked into
+//! `.eml` messages by `readpst` (libpst) in a temporary directory that is
+//! deleted after the import.
+//!
+//! Every message becomes one document with the sender and send date of an
+//! indexed `.eml` file, so `from:` / `before:` filters apply. Its path is the
+//! `mid:` URL of its `Message-ID` (see [`message_path`]), so a message filed
+//! in several folders or stores is stored once. Messages are tagged `email`
+//! plus `apple-mail` or `outlook`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::{ImportStats, ImportedDocument, TempDir};
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::{GhostError, Result};
+use crate::indexer::extractor;
+use crate::settings::MailImportSettings;
+
+/// Importer name.
+pub const SOURCE: &str = "mail";
+
+/// Tag of every imported message.
+const EMAIL_TAG: &str = "email";
+
+/// Outcome of a mail import.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MailImport {
+    #[serde(flatten)]
+    pub stats: ImportStats,
+    /// Copies of messages already read from another folder or store.
+    pub duplicates: usize,
+}
+
+/// Path of an imported email message: an RFC 2392 `mid:` URL of its
+/// `Message-ID`.
+pub fn message_path(message_id: &str) -> String {
+    let mut path = String::from("mid:");
+    for byte in message_id.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&byte) {
+            path.push(byte as char);
+        } else {
+            path.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    path
+}
+
+/// Import the configured mail stores.
+pub async fn import(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    settings: &MailImportSettings,
+) -> Result<MailImport> {
+    if !settings.apple_mail && settings.outlook_files.is_empty() {
+        return Err(GhostError::Indexer(
+            "No mail stores to import: turn on Apple Mail or add an Outlook file".into(),
+        ));
+    }
+    let mut run = Run {
+        db,
+        embedding_engine,
+        source: super::tag_source(SOURCE),
+        seen: HashSet::new(),
+        result: MailImport::default(),
+    };
+    // Stores that can't be read keep their messages from earlier imports
+    let mut complete = true;
+
+    if settings.apple_mail {
+        if let Err(e) = import_apple_mail(&mut run).await {
+            tracing::warn!("Apple Mail import failed: {}", e);
+            crate::push_log("warn", format!("Apple Mail import failed: {}", e));
+            complete = false;
+        }
+    }
+    let readpst = settings.readpst_path.as_deref().unwrap_or("readpst");
+    for file in &settings.outlook_files {
+        if let Err(e) = import_outlook(&mut run, Path::new(file), readpst).await {
+            tracing::warn!("Outlook import of {} failed: {}", file, e);
+            crate::push_log("warn", format!("Outlook import of {} failed: {}", file, e));
+            complete = false;
+        }
+    }
+
+    if complete {
+        let keep: HashSet<&str> = run.seen.iter().map(String::as_str).collect();
+        run.result.stats.removed = super::prune(db, &run.source, &keep)?;
+    }
+    super::log_stats(SOURCE, &run.result.stats);
+    Ok(run.result)
+}
+
+/// State of an import across stores.
+struct Run<'a> {
+    db: &'a Database,
+    embedding_engine: &'a EmbeddingEngine,
+    source: String,
+    /// Paths of the messages read so far.
+    seen: HashSet<String>,
+    result: MailImport,
+}
+
+impl Run<'_> {
+    /// Store one raw message from the store tagged `store`.
+    async fn add(&mut self, raw: &[u8], store: &str) {
+        let message = extractor::parse_message(raw);
+        let path = match &message.message_id {
+            Some(id) => message_path(id),
+            // No Message-ID: key the message by its content
+            None => {
+                let hash = hex::encode(Sha256::digest(raw));
+                message_path(&format!("{}@ghost.invalid", &hash[..32]))
+            }
+        };
+        if !self.seen.insert(path.clone()) {
+            self.result.duplicates += 1;
+            return;
+        }
+        let title = match message.subject.trim() {
+            "" => "(no subject)".to_string(),
+            subject => subject.to_string(),
+        };
+        let doc = ImportedDocument {
+            path,
+            title,
+            text: message.text,
+            modified_at: message
+                .metadata
+                .sent_at
+                .clone()
+                .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string()),
+            tags: vec![EMAIL_TAG.to_string(), store.to_string()],
+            email: Some(message.metadata),
+        };
+        let stats = &mut self.result.stats;
+        match super::store(self.db, self.embedding_engine, &self.source, &doc).await {
+            Ok(true) => stats.imported += 1,
+            Ok(false) => stats.unchanged += 1,
+            Err(e) => {
+                tracing::warn!("Failed to import message {}: {}", doc.path, e);
+                stats.failed += 1;
+            }
+        }
+    }
+}
+
+/// Read every `.emlx` message under `~/Library/Mail`.
+async fn import_apple_mail(run: &mut Run<'_>) -> Result<()> {
+    if !cfg!(target_os = "macos") {
+        return Err(GhostError::Indexer("Apple Mail is only on macOS".into()));
+    }
+    let root = dirs::home_dir()
+        .map(|home| home.join("Library").join("Mail"))
+        .ok_or_else(|| GhostError::Indexer("No home directory".into()))?;
+    // Mail's folder is protected: reading it needs Full Disk Access
+    std::fs::read_dir(&root).map_err(|e| {
+        GhostError::Indexer(format!(
+            "Cannot read {} ({}); grant Ghost Full Disk Access in System Settings",
+            root.display(),
+            e
+        ))
+    })?;
+    for file in files_with_extension(&root, "emlx") {
+        match std::fs::read(&file) {
+            Ok(data) => {
+                if let Some(message) = emlx_message(&data) {
+                    run.add(message, "apple-mail").await;
+                }
+            }
+            Err(e) => tracing::debug!("Cannot read {}: {}", file.display(), e),
+        }
+    }
+    Ok(())
+}
+
+/// The message inside an `.emlx` file.
+fn emlx_message(data: &[u8]) -> Option<&[u8]> {
+    let newline = data.iter().position(|&b| b == b'\n')?;
+    let length: usize = std::str::from_utf8(&data[..newline])
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let message = &data[newline + 1..];
+    // A cut-off file still holds the start of the message
+    Some(message.get(..length).unwrap_or(message))
+}
+
+/// Unpack an Outlook file with `readpst` and read its messages.
+async fn import_outlook(run: &mut Run<'_>, file: &Path, readpst: &str) -> Result<()> {
+    if !file.is_file() {
+        return Err(GhostError::Indexer(format!("{} not found", file.display())));
+    }
+    let dir = TempDir::new()?;
+    // -e: one .eml file per message; -t e: emails only; -b: skip RTF attachments
+    let output = tokio::process::Command::new(readpst)
+        .args(["-e", "-q", "-b", "-t", "e", "-o"])
+        .arg(&dir.0)
+        .arg(file)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            GhostError::Indexer(format!(
+                "Cannot run {} ({}); install libpst or set mail_import.readpst_path",
+                readpst, e
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(GhostError::Indexer(format!(
+            "readpst failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    for message in files_with_extension(&dir.0, "eml") {
+        match std::fs::read(&message) {
+            Ok(data) => run.add(&data, "outlook").await,
+            Err(e) => tracing::debug!("Cannot read {}: {}", message.display(), e),
+        }
+    }
+    Ok(())
+}
+
+/// Files under `root` with the given extension, in path order.
+fn files_with_extension(root: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_path() {
+        assert_eq!(
+            message_path("a1b2/c3@mail.example.com"),
+            "mid:a1b2%2Fc3@mail.example.com"
+        );
+        assert!(super::super::is_message_path(&message_path("x@y")));
+    }
+
+    #[test]
+    fn test_emlx_message() {
+        let message = b"Subject: Hi\n\nHello";
+        let mut data = format!("{}\n", message.len()).into_bytes();
+        data.extend_from_slice(message);
+        data.extend_from_slice(b"<?xml version=\"1.0\"?><plist></plist>");
+        assert_eq!(emlx_message(&data), Some(&message[..]));
+        assert_eq!(emlx_message(b"not an emlx"), None);
+    }
+
+    #[tokio::test]
+    async fn test_add_dedups_by_message_id() {
+        let db = Database::open_in_memory().unwrap();
+        let engine = EmbeddingEngine::none();
+        let mut run = Run {
+            db: &db,
+            embedding_engine: &engine,
+            source: crate::indexer::importers::tag_source(SOURCE),
+            seen: HashSet::new(),
+            result: MailImport::default(),
+        };
+        let raw = b"From: Dana <dana@example.com>\r\n\
+                    Subject: Budget\r\n\
+                    Date: Wed, 12 Mar 2025 10:00:00 +0000\r\n\
+                    Message-ID: <budget-1@example.com>\r\n\
+                    \r\n\
+                    Numbers attached.\r\n";
+        run.add(raw, "outlook").await;
+        run.add(raw, "apple-mail").await;
+        assert_eq!(run.result.stats.imported, 1);
+        assert_eq!(run.result.duplicates, 1);
+
+        let (id, _) = db
+            .get_document_by_path("mid:budget-1@example.com")
+            .unwrap()
+            .unwrap();
+        assert_eq!(db.get_document_tags(id).unwrap(), vec!["email", "outlook"]);
+        assert_eq!(
+            db.filter_documents_by_email(Some("dana"), None).unwrap(),
+            vec![id]
+        );
+    }
+}
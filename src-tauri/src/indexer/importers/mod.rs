@@ -0,0 +1,297 @@
+//! Importers for content that doesn't sit in watched folders as files.
+//!
+//! An importer reads an application's own storage (e.g. a browser's history
+//! database) and turns it into [`ImportedDocument`]s. (Note vaults are
+//! folders of files and are indexed as such; [`notes`] adds their links and
+//! tags on top.) Each is stored like an
+//! indexed file — chunked, embedded, keyword-searchable — under a stable
+//! path (a URL for web pages), and tagged so `tag:` filters can find it.
+//! Tags are recorded with the importer's source (`importer:<name>`), which
+//! also marks the documents the importer owns: re-imports skip unchanged
+//! documents by content hash and remove those the source no longer has.
+
+pub mod browser;
+#[cfg(feature = "mail-import")]
+pub mod mail;
+pub mod notes;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::chunker;
+use super::extractor::EmailMetadata;
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::{GhostError, Result};
+
+/// Longest title kept as a document's filename.
+const MAX_TITLE_CHARS: usize = 200;
+
+/// A document produced by an importer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedDocument {
+    /// Unique key of the document: a URL or a path inside the source.
+    pub path: String,
+    /// Shown as the document's filename in search results.
+    pub title: String,
+    pub text: String,
+    /// `YYYY-MM-DDTHH:MM:SSZ`, like indexed files.
+    pub modified_at: String,
+    pub tags: Vec<String>,
+    /// Sender and date of an email message, for `from:` / `before:` filters.
+    pub email: Option<EmailMetadata>,
+}
+
+/// Outcome of an import run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportStats {
+    pub imported: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    pub failed: usize,
+}
+
+/// Tag source marking the documents of importer `name`.
+pub fn tag_source(name: &str) -> String {
+    format!("importer:{}", name)
+}
+
+/// Whether an indexed path is a web page rather than a file.
+pub fn is_web_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Whether an indexed path is an imported email message (see
+/// `mail::message_path`).
+pub fn is_message_path(path: &str) -> bool {
+    path.starts_with("mid:")
+}
+
+/// URL opening an imported message in the system mail app, where it has one
+/// (Apple Mail's `message:` URLs on macOS).
+pub fn message_url(path: &str) -> Option<String> {
+    let id = path.strip_prefix("mid:")?;
+    cfg!(target_os = "macos").then(|| format!("message://%3C{}%3E", id))
+}
+
+/// Store every document. When `complete` (every part of the source was
+/// read), also remove the importer's documents that are no longer among them.
+pub async fn import_all(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    name: &str,
+    documents: &[ImportedDocument],
+    complete: bool,
+) -> Result<ImportStats> {
+    let source = tag_source(name);
+    let mut stats = ImportStats::default();
+    for doc in documents {
+        match store(db, embedding_engine, &source, doc).await {
+            Ok(true) => stats.imported += 1,
+            Ok(false) => stats.unchanged += 1,
+            Err(e) => {
+                tracing::warn!("Failed to import {}: {}", doc.path, e);
+                stats.failed += 1;
+            }
+        }
+    }
+    if complete {
+        let keep: HashSet<&str> = documents.iter().map(|d| d.path.as_str()).collect();
+        stats.removed = prune(db, &source, &keep)?;
+    }
+    log_stats(name, &stats);
+    Ok(stats)
+}
+
+fn log_stats(name: &str, stats: &ImportStats) {
+    tracing::info!(
+        "{} import: {} imported, {} unchanged, {} removed, {} failed",
+        name,
+        stats.imported,
+        stats.unchanged,
+        stats.removed,
+        stats.failed
+    );
+}
+
+/// Store one document. Returns false if it was already stored unchanged.
+async fn store(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    source: &str,
+    doc: &ImportedDocument,
+) -> Result<bool> {
+    let mut hasher = Sha256::new();
+    hasher.update(doc.title.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.text.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.tags.join(",").as_bytes());
+    let hash = hex::encode(hasher.finalize());
+    if let Some((_, existing_hash)) = db.get_document_by_path(&doc.path)? {
+        if existing_hash == hash {
+            return Ok(false);
+        }
+    }
+
+    let title: String = doc.title.chars().take(MAX_TITLE_CHARS).collect();
+    let doc_id = db.upsert_document(
+        &doc.path,
+        &title,
+        None,
+        doc.text.len() as i64,
+        &hash,
+        &doc.modified_at,
+    )?;
+    db.delete_embeddings_for_document(doc_id)?;
+    db.delete_chunks_for_document(doc_id)?;
+    let chunks = chunker::chunk_text_default(&doc.text);
+    super::store_chunks(db, doc_id, &chunks)?;
+
+    db.clear_document_tags(doc_id, source)?;
+    for tag in &doc.tags {
+        db.add_document_tag(doc_id, tag, source)?;
+    }
+    if let Some(email) = &doc.email {
+        db.set_email_metadata(doc_id, email.sender.as_deref(), email.sent_at.as_deref())?;
+    }
+
+    super::embed_new_chunks(db, embedding_engine, doc_id, None, chunks.len(), &title).await?;
+    Ok(true)
+}
+
+/// Delete the documents tagged by `source` whose paths aren't in `keep`.
+fn prune(db: &Database, source: &str, keep: &HashSet<&str>) -> Result<usize> {
+    let owned: Vec<(i64, String)> = db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT d.id, d.path FROM documents d
+             JOIN document_tags t ON t.document_id = d.id
+             WHERE t.source = ?1",
+        )?;
+        let rows = stmt.query_map([source], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })?;
+    let mut removed = 0;
+    for (id, path) in owned {
+        if !keep.contains(path.as_str()) {
+            db.delete_document(id)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// A temporary copy of another application's SQLite database, deleted on
+/// drop. Browsers keep their databases locked while running, and a copy
+/// guarantees theirs is never written to.
+pub struct DatabaseCopy {
+    pub conn: rusqlite::Connection,
+    // Dropped after `conn`, so the files are closed when they are deleted
+    _dir: TempDir,
+}
+
+/// A private temporary directory, deleted on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Result<Self> {
+        static DIRS: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "ghost-import-{}-{}",
+            std::process::id(),
+            DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+impl DatabaseCopy {
+    /// Copy `path` (and its write-ahead log, which holds recent changes) and
+    /// open the copy.
+    pub fn open(path: &Path) -> Result<Self> {
+        let dir = TempDir::new()?;
+        let copy = dir.0.join("copy.sqlite");
+        std::fs::copy(path, &copy)
+            .and_then(|_| {
+                let wal = PathBuf::from(format!("{}-wal", path.display()));
+                if wal.is_file() {
+                    std::fs::copy(&wal, dir.0.join("copy.sqlite-wal"))?;
+                }
+                Ok(())
+            })
+            .map_err(|e| GhostError::Indexer(format!("Cannot copy {}: {}", path.display(), e)))?;
+        Ok(Self {
+            conn: rusqlite::Connection::open(&copy)?,
+            _dir: dir,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(path: &str, text: &str) -> ImportedDocument {
+        ImportedDocument {
+            path: path.into(),
+            title: "Page".into(),
+            text: text.into(),
+            modified_at: "2026-01-01T00:00:00Z".into(),
+            tags: vec!["browser".into()],
+            email: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_all() {
+        let db = Database::open_in_memory().unwrap();
+        let engine = EmbeddingEngine::none();
+        let docs = vec![
+            page("https://example.com/a", "Rust async book"),
+            page("https://example.com/b", "Sourdough starter guide"),
+        ];
+        let stats = import_all(&db, &engine, "test", &docs, true).await.unwrap();
+        assert_eq!(stats.imported, 2);
+
+        // A user's file is never pruned by an importer
+        db.upsert_document("/notes/a.md", "a.md", Some("md"), 1, "h", "2026-01-01")
+            .unwrap();
+        let docs = vec![page("https://example.com/a", "Rust async book")];
+        // Nothing is removed after a partial read
+        let stats = import_all(&db, &engine, "test", &docs, false)
+            .await
+            .unwrap();
+        assert_eq!(stats.removed, 0);
+        let stats = import_all(&db, &engine, "test", &docs, true).await.unwrap();
+        assert_eq!((stats.imported, stats.unchanged, stats.removed), (0, 1, 1));
+        assert!(db
+            .get_document_by_path("https://example.com/b")
+            .unwrap()
+            .is_none());
+        assert!(db.get_document_by_path("/notes/a.md").unwrap().is_some());
+
+        let (id, _) = db
+            .get_document_by_path("https://example.com/a")
+            .unwrap()
+            .unwrap();
+        assert_eq!(db.get_document_tags(id).unwrap(), vec!["browser"]);
+        assert_eq!(db.get_document_chunks(id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_is_web_url() {
+        assert!(is_web_url("https://example.com"));
+        assert!(!is_web_url("/home/me/https.txt"));
+    }
+}
@@ -0,0 +1,774 @@
+//! Obsidian, Logseq, and Notion note vaults.
+//!
+//! Importing a vault indexes its files like any folder and registers it in
+//! `note_vaults`; from then on every Markdown note indexed inside it is also
+//! read for the conventions of its app:
+//! - Wikilinks (`[[Note]]`, `[[Note#Heading|alias]]`, Logseq `#[[Page]]`)
+//!   and, in Notion exports, links to other exported `.md` pages
+//! - Tags from frontmatter (`tags: [a, b]`), Logseq properties (`tags:: a`)
+//!   and inline `#tags`
+//! - Daily notes (`2024-01-15.md`, Logseq `journals/2024_01_15.md`), tagged
+//!   `daily`; links to dates such as `[[Jan 15th, 2024]]` point to them
+//!
+//! Links are stored by normalized note name in `note_links` and each note's
+//! own name in `note_pages`, so backlinks ("notes linking to X") are a join
+//! and the `links:` search filter can use them. Tags are ordinary document
+//! tags recorded with the `importer:notes` source, so `tag:` filters work.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::{GhostError, Result};
+use crate::indexer::progress::RunControl;
+
+/// Importer name; tags are recorded as `importer:notes`.
+pub const SOURCE: &str = "notes";
+
+/// Tag of daily notes.
+const DAILY_TAG: &str = "daily";
+
+static WIKILINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[\[([^\[\]\n]+)\]\]").unwrap());
+static MARKDOWN_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[[^\]\n]*\]\(([^)\s]+\.md)\)").unwrap());
+static INLINE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)#([\p{L}\p{N}_/-]+)").unwrap());
+static INLINE_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`[^`\n]*`").unwrap());
+/// Notion appends a 32-hex-digit page ID to exported file names.
+static NOTION_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+[0-9a-f]{32}$").unwrap());
+static ORDINAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\d)(?:st|nd|rd|th)\b").unwrap());
+
+/// The app a vault comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultKind {
+    Obsidian,
+    Logseq,
+    Notion,
+}
+
+impl VaultKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Obsidian => "obsidian",
+            Self::Logseq => "logseq",
+            Self::Notion => "notion",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "obsidian" => Some(Self::Obsidian),
+            "logseq" => Some(Self::Logseq),
+            "notion" => Some(Self::Notion),
+            _ => None,
+        }
+    }
+}
+
+/// What a note says about itself and other notes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedNote {
+    /// Normalized name other notes link to it by.
+    pub name: String,
+    pub title: String,
+    pub daily: Option<NaiveDate>,
+    pub tags: BTreeSet<String>,
+    /// Normalized names of the notes it links to.
+    pub links: BTreeSet<String>,
+}
+
+/// A note at one end of a link.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteRef {
+    /// Normalized note name.
+    pub name: String,
+    /// The note's document; `None` for links to notes that don't exist yet.
+    pub document_id: Option<i64>,
+    pub path: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Links of one note in both directions.
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteLinks {
+    pub name: String,
+    pub outgoing: Vec<NoteRef>,
+    pub backlinks: Vec<NoteRef>,
+    pub tags: Vec<String>,
+}
+
+/// Outcome of a vault import.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultImport {
+    pub kind: VaultKind,
+    pub notes: usize,
+    pub links: usize,
+}
+
+/// Initialize the note vault tables in the database.
+pub fn initialize_notes_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS note_vaults (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                root TEXT NOT NULL UNIQUE,
+                kind TEXT NOT NULL,
+                imported_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            -- One row per note in a vault: the name links resolve to
+            CREATE TABLE IF NOT EXISTS note_pages (
+                document_id INTEGER PRIMARY KEY
+                    REFERENCES documents(id) ON DELETE CASCADE,
+                vault_id INTEGER NOT NULL REFERENCES note_vaults(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                title TEXT NOT NULL,
+                daily TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS note_links (
+                document_id INTEGER NOT NULL
+                    REFERENCES documents(id) ON DELETE CASCADE,
+                target TEXT NOT NULL,
+                PRIMARY KEY (document_id, target)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_note_pages_name ON note_pages(name);
+            CREATE INDEX IF NOT EXISTS idx_note_links_target ON note_links(target);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Which app a folder is a vault of.
+pub fn detect_kind(root: &Path) -> Option<VaultKind> {
+    if root.join(".obsidian").is_dir() {
+        return Some(VaultKind::Obsidian);
+    }
+    if root.join("logseq").join("config.edn").is_file()
+        || (root.join("pages").is_dir() && root.join("journals").is_dir())
+    {
+        return Some(VaultKind::Logseq);
+    }
+    note_files(root)
+        .iter()
+        .any(|path| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|stem| NOTION_ID.is_match(stem))
+        })
+        .then_some(VaultKind::Notion)
+}
+
+/// Normalize a note name or link target: decoded, without extension,
+/// heading or alias, Notion page ID, lowercase; dates become `YYYY-MM-DD`.
+pub fn normalize_name(raw: &str) -> String {
+    let raw = raw.split(['|', '#', '^']).next().unwrap_or_default();
+    let decoded = percent_decode(raw.trim());
+    let mut name = decoded.trim();
+    if name.len() > 3
+        && name
+            .get(name.len() - 3..)
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(".md"))
+    {
+        name = &name[..name.len() - 3];
+    }
+    let name = NOTION_ID.replace(name, "");
+    if let Some(date) = parse_daily(&name) {
+        return date.format("%Y-%m-%d").to_string();
+    }
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// `%XX` escapes, as Notion writes them in links.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = if bytes[i] == b'%' {
+            s.get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+        } else {
+            None
+        };
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The date a daily note's name stands for: `2024-01-15`, `2024_01_15`
+/// (Logseq files) or `Jan 15th, 2024` (Logseq titles).
+fn parse_daily(name: &str) -> Option<NaiveDate> {
+    let name = name.trim();
+    ["%Y-%m-%d", "%Y_%m_%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(name, format).ok())
+        .or_else(|| {
+            let plain = ORDINAL.replace_all(name, "$1");
+            ["%b %d, %Y", "%B %d, %Y"]
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(&plain, format).ok())
+        })
+}
+
+/// Read a note. `relative` is its path inside the vault.
+pub fn parse_note(kind: VaultKind, relative: &Path, text: &str) -> ParsedNote {
+    let stem = relative
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    // Logseq stores namespaced pages (`a/b`) as `a___b.md`
+    let stem = match kind {
+        VaultKind::Logseq => stem.replace("___", "/"),
+        _ => stem.to_string(),
+    };
+    let mut note = ParsedNote {
+        name: normalize_name(&stem),
+        title: NOTION_ID.replace(&percent_decode(&stem), "").into_owned(),
+        ..Default::default()
+    };
+    let in_journals = relative
+        .components()
+        .next()
+        .is_some_and(|c| c.as_os_str() == "journals");
+    if kind != VaultKind::Notion || in_journals {
+        note.daily = parse_daily(&stem);
+    }
+
+    let body = read_properties(text, &mut note);
+    let mut in_code = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let line = INLINE_CODE.replace_all(line, "");
+        for capture in WIKILINK.captures_iter(&line) {
+            let target = &capture[1];
+            // Embedded attachments (`![[photo.png]]`) aren't notes
+            let path_part = target.split(['|', '#']).next().unwrap_or_default();
+            if is_attachment(path_part) {
+                continue;
+            }
+            let target = match kind {
+                // Obsidian links may carry a folder path; the name is the note
+                VaultKind::Obsidian => path_part.rsplit('/').next().unwrap_or(path_part),
+                _ => target,
+            };
+            add_link(&mut note, target);
+            // Logseq `#[[Page]]` is a tag too
+            let start = capture.get(0).map_or(0, |m| m.start());
+            if kind == VaultKind::Logseq && line[..start].ends_with('#') {
+                add_tag(&mut note, target);
+            }
+        }
+        if kind == VaultKind::Notion {
+            for capture in MARKDOWN_LINK.captures_iter(&line) {
+                let target = &capture[1];
+                if !crate::indexer::importers::is_web_url(target) {
+                    add_link(&mut note, target.rsplit('/').next().unwrap_or(target));
+                }
+            }
+        }
+        // A heading's `#`s aren't a tag, but its text may carry some
+        let trimmed = line.trim_start();
+        let heading_text = trimmed.trim_start_matches('#');
+        if trimmed.starts_with('#') && heading_text.starts_with(' ') {
+            scan_inline_tags(kind, heading_text, &mut note);
+        } else {
+            scan_inline_tags(kind, &line, &mut note);
+        }
+    }
+    if note.daily.is_some() {
+        note.tags.insert(DAILY_TAG.into());
+    }
+    note.links.remove(&note.name);
+    note
+}
+
+fn scan_inline_tags(kind: VaultKind, line: &str, note: &mut ParsedNote) {
+    for capture in INLINE_TAG.captures_iter(line) {
+        let tag = capture[1].trim_end_matches('/');
+        // `#1` is an issue number or a list position, not a tag
+        if tag.is_empty() || tag.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        add_tag(note, tag);
+        // In Logseq every tag is a page
+        if kind == VaultKind::Logseq {
+            add_link(note, tag);
+        }
+    }
+}
+
+/// Whether a link target names a file other than a note, e.g. `photo.png`.
+fn is_attachment(target: &str) -> bool {
+    Path::new(target)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            !ext.eq_ignore_ascii_case("md")
+                && (1..=5).contains(&ext.len())
+                && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+fn add_link(note: &mut ParsedNote, target: &str) {
+    let name = normalize_name(target);
+    if !name.is_empty() {
+        note.links.insert(name);
+    }
+}
+
+fn add_tag(note: &mut ParsedNote, tag: &str) {
+    if let Some(tag) = crate::db::normalize_tag(tag.trim_start_matches('#')) {
+        note.tags.insert(tag);
+    }
+}
+
+/// Take tags and title from YAML frontmatter or leading Logseq `key:: value`
+/// properties. Returns the rest of the text.
+fn read_properties<'a>(text: &'a str, note: &mut ParsedNote) -> &'a str {
+    let text = text.trim_start_matches('\u{feff}');
+    if let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    {
+        let Some(end) = rest.find("\n---") else {
+            return text;
+        };
+        let mut in_tags = false;
+        for line in rest[..end].lines() {
+            let trimmed = line.trim();
+            if in_tags {
+                if let Some(item) = trimmed.strip_prefix("- ") {
+                    add_tag(note, item.trim().trim_matches(['"', '\'']));
+                    continue;
+                }
+                in_tags = false;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            match key.trim().to_lowercase().as_str() {
+                "tags" | "tag" => {
+                    let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+                    if value.is_empty() {
+                        in_tags = true;
+                    }
+                    for tag in value.split(',') {
+                        add_tag(note, tag.trim().trim_matches(['"', '\'']));
+                    }
+                }
+                "title" if !value.trim().is_empty() => {
+                    note.title = value.trim().trim_matches(['"', '\'']).to_string();
+                }
+                _ => {}
+            }
+        }
+        let body = &rest[end + 4..];
+        return body.split_once('\n').map(|(_, b)| b).unwrap_or("");
+    }
+
+    // Logseq page properties: the first block's `key:: value` lines
+    let mut consumed = 0;
+    for line in text.lines() {
+        let trimmed = line.trim().trim_start_matches("- ");
+        let Some((key, value)) = trimmed.split_once(":: ") else {
+            break;
+        };
+        match key.trim().to_lowercase().as_str() {
+            "tags" | "tag" => {
+                for tag in value.split(',') {
+                    let tag = tag.trim();
+                    let tag = tag
+                        .strip_prefix("[[")
+                        .and_then(|t| t.strip_suffix("]]"))
+                        .unwrap_or(tag);
+                    add_tag(note, tag);
+                    add_link(note, tag);
+                }
+            }
+            "title" => note.title = value.trim().to_string(),
+            _ => {}
+        }
+        consumed += line.len() + 1;
+    }
+    &text[consumed.min(text.len())..]
+}
+
+/// Record a vault. Returns its ID.
+fn register_vault(db: &Database, root: &str, kind: VaultKind) -> Result<i64> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO note_vaults (root, kind) VALUES (?1, ?2)
+             ON CONFLICT(root) DO UPDATE SET kind = excluded.kind,
+                imported_at = datetime('now')",
+            rusqlite::params![root, kind.as_str()],
+        )?;
+        Ok(conn.query_row(
+            "SELECT id FROM note_vaults WHERE root = ?1",
+            [root],
+            |row| row.get(0),
+        )?)
+    })
+}
+
+/// The registered vault containing `path`, with its root.
+fn vault_of(db: &Database, path: &Path) -> Result<Option<(i64, PathBuf, VaultKind)>> {
+    let vaults: Vec<(i64, String, String)> = db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare("SELECT id, root, kind FROM note_vaults")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })?;
+    Ok(vaults
+        .into_iter()
+        .filter(|(_, root, _)| path.starts_with(root))
+        .max_by_key(|(_, root, _)| root.len())
+        .and_then(|(id, root, kind)| Some((id, PathBuf::from(root), VaultKind::from_name(&kind)?))))
+}
+
+/// Store what a note says about itself and its links.
+fn store_note(db: &Database, document_id: i64, vault_id: i64, note: &ParsedNote) -> Result<()> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO note_pages (document_id, vault_id, name, title, daily)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                document_id,
+                vault_id,
+                note.name,
+                note.title,
+                note.daily.map(|d| d.to_string())
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM note_links WHERE document_id = ?1",
+            [document_id],
+        )?;
+        for target in &note.links {
+            conn.execute(
+                "INSERT OR IGNORE INTO note_links (document_id, target) VALUES (?1, ?2)",
+                rusqlite::params![document_id, target],
+            )?;
+        }
+        Ok(())
+    })?;
+    let source = super::tag_source(SOURCE);
+    db.clear_document_tags(document_id, &source)?;
+    for tag in &note.tags {
+        db.add_document_tag(document_id, tag, &source)?;
+    }
+    Ok(())
+}
+
+/// Read links and tags of a note that was just indexed, if it lies in an
+/// imported vault.
+pub fn on_note_indexed(db: &Database, document_id: i64, path: &Path, text: &str) -> Result<()> {
+    let Some((vault_id, root, kind)) = vault_of(db, path)? else {
+        return Ok(());
+    };
+    let relative = path.strip_prefix(&root).unwrap_or(path);
+    store_note(db, document_id, vault_id, &parse_note(kind, relative, text))
+}
+
+/// Whether notes are read from files with this extension.
+pub fn is_note_extension(extension: &str) -> bool {
+    extension.eq_ignore_ascii_case("md")
+}
+
+/// Markdown notes of a vault, skipping hidden folders and Logseq's own.
+fn note_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                if !(dir == root && name == "logseq") {
+                    stack.push(path);
+                }
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(is_note_extension)
+            {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Import a vault: index its files, then read every note's links and tags.
+pub async fn import_vault(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    root: &Path,
+    control: std::sync::Arc<RunControl>,
+) -> Result<VaultImport> {
+    let kind = detect_kind(root).ok_or_else(|| {
+        GhostError::Indexer(format!(
+            "{} is not an Obsidian or Logseq vault or a Notion export",
+            root.display()
+        ))
+    })?;
+    let vault_id = register_vault(db, &root.to_string_lossy(), kind)?;
+    let stats = crate::indexer::index_directory_with(db, embedding_engine, root, control).await?;
+
+    // Unchanged files were skipped by the indexer; read all notes here
+    let mut result = VaultImport {
+        kind,
+        notes: 0,
+        links: 0,
+    };
+    if stats.cancelled || stats.paused {
+        return Ok(result);
+    }
+    for path in note_files(root) {
+        let Some((document_id, _)) = db.get_document_by_path(&path.to_string_lossy())? else {
+            continue;
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::warn!("Cannot read note {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let note = parse_note(kind, relative, &text);
+        result.notes += 1;
+        result.links += note.links.len();
+        store_note(db, document_id, vault_id, &note)?;
+    }
+    tracing::info!(
+        "Imported {} vault {}: {} notes, {} links",
+        kind.as_str(),
+        root.display(),
+        result.notes,
+        result.links
+    );
+    Ok(result)
+}
+
+/// IDs of notes linking to every one of `names` (normalized), most recently
+/// indexed first.
+pub fn documents_linking_to(db: &Database, names: &[String]) -> Result<Vec<i64>> {
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+    db.with_read_conn(|conn| {
+        let placeholders = vec!["?"; names.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT d.id FROM documents d
+             JOIN note_links l ON l.document_id = d.id
+             WHERE l.target IN ({})
+             GROUP BY d.id HAVING COUNT(DISTINCT l.target) = {}
+             ORDER BY d.indexed_at DESC, d.id DESC",
+            placeholders,
+            names.len()
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(names), |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// Outgoing links, backlinks and tags of the note at `path`.
+pub fn note_links(db: &Database, path: &str) -> Result<NoteLinks> {
+    let (document_id, _) = db
+        .get_document_by_path(path)?
+        .ok_or_else(|| GhostError::InvalidQuery(format!("{} is not indexed", path)))?;
+    let tags = db.get_document_tags(document_id)?;
+    db.with_read_conn(|conn| {
+        let (name, vault_id): (String, i64) = match conn.query_row(
+            "SELECT name, vault_id FROM note_pages WHERE document_id = ?1",
+            [document_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(page) => page,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(GhostError::InvalidQuery(format!(
+                    "{} is not a note in an imported vault",
+                    path
+                )))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let note_ref = |row: &rusqlite::Row| {
+            Ok(NoteRef {
+                name: row.get(0)?,
+                document_id: row.get(1)?,
+                path: row.get(2)?,
+                title: row.get(3)?,
+            })
+        };
+        // Links resolve to a note of the same vault
+        let mut stmt = conn.prepare(
+            "SELECT l.target, p.document_id, d.path, p.title FROM note_links l
+             LEFT JOIN note_pages p ON p.name = l.target AND p.vault_id = ?2
+             LEFT JOIN documents d ON d.id = p.document_id
+             WHERE l.document_id = ?1
+             GROUP BY l.target ORDER BY l.target",
+        )?;
+        let outgoing = stmt
+            .query_map(rusqlite::params![document_id, vault_id], note_ref)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut stmt = conn.prepare(
+            "SELECT p.name, p.document_id, d.path, p.title FROM note_links l
+             JOIN note_pages p ON p.document_id = l.document_id AND p.vault_id = ?2
+             JOIN documents d ON d.id = p.document_id
+             WHERE l.target = ?1 AND l.document_id != ?3
+             ORDER BY p.title",
+        )?;
+        let backlinks = stmt
+            .query_map(rusqlite::params![name, vault_id, document_id], note_ref)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(NoteLinks {
+            name,
+            outgoing,
+            backlinks,
+            tags,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Project Alpha"), "project alpha");
+        assert_eq!(
+            normalize_name("Project Alpha#Goals|the project"),
+            "project alpha"
+        );
+        assert_eq!(
+            normalize_name("Meeting%20notes%20a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6.md"),
+            "meeting notes"
+        );
+        assert_eq!(normalize_name("Jan 15th, 2024"), "2024-01-15");
+        assert_eq!(normalize_name("2024_01_15"), "2024-01-15");
+        // Non-ASCII names don't end on a byte boundary three bytes from the end
+        assert_eq!(normalize_name("Заметки"), "заметки");
+        assert_eq!(normalize_name("Заметки.md"), "заметки");
+    }
+
+    #[test]
+    fn test_parse_obsidian_note() {
+        let text = "---\ntitle: Alpha plan\ntags:\n  - project\n  - \"Q3\"\n---\n\
+            # Plan #draft\n\
+            See [[People/Ana|Ana]] and [[Budget#Totals]] before [[2024-01-15]].\n\
+            ![[diagram.png]] issue #42 `[[not a link]]`\n\
+            ```\n[[in code]] #nope\n```\n";
+        let note = parse_note(VaultKind::Obsidian, Path::new("Projects/Alpha.md"), text);
+        assert_eq!(note.name, "alpha");
+        assert_eq!(note.title, "Alpha plan");
+        assert_eq!(note.daily, None);
+        assert_eq!(
+            note.tags.iter().collect::<Vec<_>>(),
+            ["draft", "project", "q3"]
+        );
+        assert_eq!(
+            note.links.iter().collect::<Vec<_>>(),
+            ["2024-01-15", "ana", "budget"]
+        );
+    }
+
+    #[test]
+    fn test_parse_logseq_journal() {
+        let text = "tags:: [[standup]], work\n\n- Talked to [[Ana]] about #[[Big Launch]]\n\
+            - Review #design\n";
+        let note = parse_note(VaultKind::Logseq, Path::new("journals/2024_01_15.md"), text);
+        assert_eq!(note.name, "2024-01-15");
+        assert_eq!(note.daily, NaiveDate::from_ymd_opt(2024, 1, 15));
+        assert!(note.tags.contains("daily"));
+        assert!(note.tags.contains("big launch"));
+        assert!(note.tags.contains("standup"));
+        // Tags are pages in Logseq
+        assert!(note.links.contains("design"));
+        assert!(note.links.contains("ana"));
+    }
+
+    #[test]
+    fn test_parse_notion_page() {
+        let text = "# Roadmap\n\nSee [Q3 goals](Roadmap%20a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6/Q3%20goals%20ffffffffffffffffffffffffffffffff.md) \
+            and [site](https://example.com/page.md).";
+        let note = parse_note(
+            VaultKind::Notion,
+            Path::new("Roadmap a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6.md"),
+            text,
+        );
+        assert_eq!(note.name, "roadmap");
+        assert_eq!(note.title, "Roadmap");
+        assert_eq!(note.links.iter().collect::<Vec<_>>(), ["q3 goals"]);
+    }
+
+    #[test]
+    fn test_backlinks() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_notes_schema(&db).unwrap();
+        register_vault(&db, "/vault", VaultKind::Obsidian).unwrap();
+        let add = |path: &str, text: &str| {
+            let id = db
+                .upsert_document(path, path, Some("md"), 1, path, "2026-01-01")
+                .unwrap();
+            on_note_indexed(&db, id, Path::new(path), text).unwrap();
+            id
+        };
+        let plan = add("/vault/Plan.md", "Owner: [[Ana]] #project");
+        let ana = add("/vault/Ana.md", "Works on [[Plan]]");
+        add("/vault/Log.md", "Called [[ana]]");
+        // Outside the vault: indexed, but not read as a note
+        add("/other/Ana.md", "[[Plan]]");
+
+        assert_eq!(documents_linking_to(&db, &["plan".into()]).unwrap(), [ana]);
+        let links = note_links(&db, "/vault/Ana.md").unwrap();
+        assert_eq!(links.name, "ana");
+        let backlinks: Vec<_> = links
+            .backlinks
+            .iter()
+            .map(|b| b.title.clone().unwrap())
+            .collect();
+        assert_eq!(backlinks, ["Log", "Plan"]);
+        assert_eq!(links.outgoing[0].document_id, Some(plan));
+
+        let links = note_links(&db, "/vault/Plan.md").unwrap();
+        assert_eq!(links.tags, ["project"]);
+        assert!(note_links(&db, "/other/Ana.md").is_err());
+    }
+}
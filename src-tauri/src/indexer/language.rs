@@ -0,0 +1,234 @@
+//! Lightweight language detection for chunks.
+//!
+//! Non-Latin scripts are recognized from their Unicode blocks; Latin-script
+//! languages are told apart by counting common function words. Good enough
+//! to label chunks of a few sentences, and free of any model download.
+
+use serde::Serialize;
+
+use crate::db::LanguageCount;
+
+/// Languages recognized by stopwords, as ISO 639-1 codes.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "that", "it", "for", "with", "was", "on", "be",
+            "this", "have", "not", "by", "from", "at", "or", "you", "we", "they", "which", "an",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "y", "en", "que", "es", "por", "con", "para", "una", "del", "se",
+            "como", "más", "pero", "sus", "al", "está", "son", "también", "fue", "este", "esta",
+            "muy",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "as", "em", "que", "é", "um", "uma", "não", "com", "para", "do", "da",
+            "dos", "das", "na", "ao", "mais", "mas", "foi", "também", "são", "está", "isso",
+            "você",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "les", "et", "est", "un", "une", "des", "du", "qui", "dans", "pour", "pas",
+            "sur", "au", "avec", "ce", "il", "elle", "sont", "mais", "ou", "nous", "vous", "je",
+            "cette",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "den", "mit", "von",
+            "sich", "auf", "für", "im", "dem", "auch", "sind", "wir", "ich", "aber", "oder",
+            "wird", "werden",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "di", "che", "è", "non", "per", "della", "sono", "gli", "lo", "ma", "anche",
+            "più", "nel", "questo", "questa", "delle", "alla", "come", "ha", "hanno", "essere",
+            "perché", "molto", "ci",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "is", "dat", "niet", "op", "te", "zijn", "met",
+            "voor", "ook", "er", "maar", "om", "hij", "wij", "ze", "aan", "als", "bij", "wordt",
+            "naar",
+        ],
+    ),
+];
+
+/// Share of chunks a second language needs before cross-language search matters.
+const SECONDARY_LANGUAGE_SHARE: f64 = 0.1;
+
+/// Languages in the vault, for the settings UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultLanguages {
+    /// Chunk counts per language, most common first.
+    pub languages: Vec<LanguageCount>,
+    /// The vault mixes languages but the embedding model is English-only.
+    pub multilingual_recommended: bool,
+}
+
+impl VaultLanguages {
+    pub fn new(languages: Vec<LanguageCount>, model_is_multilingual: bool) -> Self {
+        let multilingual_recommended = !model_is_multilingual && is_mixed(&languages);
+        Self {
+            languages,
+            multilingual_recommended,
+        }
+    }
+}
+
+/// Whether at least two languages each hold a meaningful share of chunks.
+fn is_mixed(languages: &[LanguageCount]) -> bool {
+    let total: i64 = languages.iter().map(|l| l.chunk_count).sum();
+    languages
+        .iter()
+        .filter(|l| l.chunk_count as f64 >= total as f64 * SECONDARY_LANGUAGE_SHARE)
+        .count()
+        >= 2
+}
+
+/// Stopword hits needed before a Latin-script guess is trusted.
+const MIN_STOPWORD_HITS: usize = 3;
+/// Letters needed before a script-based guess is trusted.
+const MIN_SCRIPT_LETTERS: usize = 8;
+
+/// Detect the language of `text` as an ISO 639-1 code ("en", "es", "ja").
+///
+/// Returns `None` for short or ambiguous text (code, tables, file names).
+pub fn detect(text: &str) -> Option<&'static str> {
+    detect_script(text).or_else(|| detect_latin(text))
+}
+
+/// Languages identified by a dominant non-Latin script.
+fn detect_script(text: &str) -> Option<&'static str> {
+    let mut letters = 0usize;
+    let mut counts = [0usize; 9];
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        let slot = match c as u32 {
+            0x3040..=0x30FF => 0,                   // Hiragana, Katakana
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => 1, // CJK ideographs
+            0xAC00..=0xD7AF | 0x1100..=0x11FF => 2, // Hangul
+            0x0400..=0x04FF => 3,                   // Cyrillic
+            0x0600..=0x06FF => 4,                   // Arabic
+            0x0370..=0x03FF => 5,                   // Greek
+            0x0590..=0x05FF => 6,                   // Hebrew
+            0x0900..=0x097F => 7,                   // Devanagari
+            0x0E00..=0x0E7F => 8,                   // Thai
+            _ => continue,
+        };
+        counts[slot] += 1;
+    }
+    let non_latin: usize = counts.iter().sum();
+    if non_latin < MIN_SCRIPT_LETTERS || non_latin * 2 < letters {
+        return None;
+    }
+    // Japanese mixes kana with kanji; kanji alone is Chinese
+    if counts[0] > 0 && counts[0] * 10 >= counts[1] {
+        return Some("ja");
+    }
+    let (slot, _) = counts.iter().enumerate().skip(1).max_by_key(|(_, n)| **n)?;
+    Some(["ja", "zh", "ko", "ru", "ar", "el", "he", "hi", "th"][slot])
+}
+
+/// Latin-script languages identified by their function words.
+fn detect_latin(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    let mut hits = [0usize; STOPWORDS.len()];
+    for word in lower.split(|c: char| !c.is_alphabetic()) {
+        if word.is_empty() {
+            continue;
+        }
+        for (i, (_, words)) in STOPWORDS.iter().enumerate() {
+            if words.contains(&word) {
+                hits[i] += 1;
+            }
+        }
+    }
+    let (best, &best_hits) = hits.iter().enumerate().max_by_key(|(_, n)| **n)?;
+    let runner_up = hits
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != best)
+        .map(|(_, n)| *n)
+        .max()
+        .unwrap_or(0);
+    (best_hits >= MIN_STOPWORD_HITS && best_hits > runner_up).then_some(STOPWORDS[best].0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_latin_languages() {
+        assert_eq!(
+            detect("The meeting notes for the project are in the shared folder."),
+            Some("en")
+        );
+        assert_eq!(
+            detect("Las notas de la reunión están en la carpeta compartida para el equipo."),
+            Some("es")
+        );
+        assert_eq!(
+            detect("Les notes de la réunion sont dans le dossier partagé avec vous."),
+            Some("fr")
+        );
+        assert_eq!(
+            detect("Die Notizen der Besprechung sind im Ordner und nicht auf dem Desktop."),
+            Some("de")
+        );
+        assert_eq!(
+            detect("As notas da reunião estão na pasta e não no computador do escritório."),
+            Some("pt")
+        );
+    }
+
+    #[test]
+    fn test_detect_scripts() {
+        assert_eq!(detect("会議のメモは共有フォルダにあります。"), Some("ja"));
+        assert_eq!(detect("会议记录在共享文件夹中请查看"), Some("zh"));
+        assert_eq!(
+            detect("Заметки о встрече находятся в общей папке."),
+            Some("ru")
+        );
+        assert_eq!(detect("회의 메모는 공유 폴더에 있습니다."), Some("ko"));
+    }
+
+    #[test]
+    fn test_multilingual_recommended_for_mixed_vaults() {
+        let counts = |pairs: &[(&str, i64)]| -> Vec<LanguageCount> {
+            pairs
+                .iter()
+                .map(|(language, chunk_count)| LanguageCount {
+                    language: language.to_string(),
+                    chunk_count: *chunk_count,
+                })
+                .collect()
+        };
+        let mixed = counts(&[("en", 600), ("es", 400)]);
+        assert!(VaultLanguages::new(mixed.clone(), false).multilingual_recommended);
+        assert!(!VaultLanguages::new(mixed, true).multilingual_recommended);
+        let mostly_english = counts(&[("en", 990), ("fr", 10)]);
+        assert!(!VaultLanguages::new(mostly_english, false).multilingual_recommended);
+    }
+
+    #[test]
+    fn test_detect_rejects_short_or_ambiguous_text() {
+        assert_eq!(detect(""), None);
+        assert_eq!(detect("fn main() { let x = 1; }"), None);
+        assert_eq!(detect("invoice_2024.pdf"), None);
+    }
+}
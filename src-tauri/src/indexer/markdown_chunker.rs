@@ -0,0 +1,343 @@
+//! Heading-aware chunking for Markdown and Org files.
+//!
+//! Documents are split on heading boundaries so a chunk never mixes two
+//! sections, and each chunk starts with a breadcrumb of the headings above it
+//! (`# Project > ## Design > ### Auth`). The breadcrumb is part of the chunk
+//! text, so both the embedding and keyword search see where in the document
+//! the passage sits. Headings inside fenced code or source blocks are ignored.
+
+use super::chunker::{chunk_text, estimate_tokens, ChunkConfig, ChunkInfo};
+
+/// Fewest body tokens per chunk, however long the breadcrumb is.
+const MIN_BODY_TOKENS: usize = 64;
+
+/// Markup flavours with headings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Markdown,
+    Org,
+}
+
+impl Format {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "md" | "markdown" | "mdx" => Some(Self::Markdown),
+            "org" => Some(Self::Org),
+            _ => None,
+        }
+    }
+
+    /// Heading marker for a level, as written in the source (`##`, `**`).
+    fn marker(self, level: usize) -> String {
+        let c = match self {
+            Self::Markdown => "#",
+            Self::Org => "*",
+        };
+        c.repeat(level)
+    }
+}
+
+/// The text under one heading, up to the next heading of any level.
+#[derive(Debug, PartialEq)]
+struct Block {
+    /// Headings from the top of the document down to this block's own.
+    path: Vec<(usize, String)>,
+    body: String,
+}
+
+/// ATX heading (`## Title ##`) as (level, title).
+fn markdown_heading(line: &str) -> Option<(usize, String)> {
+    // Up to three spaces of indentation; more is a code block
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let line = line.trim_start();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    // Optional closing sequence: `## Title ##`
+    let title = rest.trim();
+    let title = match title.trim_end_matches('#') {
+        stripped if stripped.is_empty() || stripped.ends_with([' ', '\t']) => stripped.trim(),
+        _ => title,
+    };
+    Some((level, title.to_string()))
+}
+
+/// Setext underline (`===` or `---`) as the level it gives the line above.
+fn setext_level(line: &str) -> Option<usize> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    if line.chars().all(|c| c == '=') {
+        Some(1)
+    } else if line.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Org heading (`** TODO Title :tag:`) as (level, title).
+fn org_heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|&c| c == '*').count();
+    if level == 0 || !line[level..].starts_with(' ') {
+        return None;
+    }
+    let mut title = line[level..].trim();
+    // Trailing tags: `Title   :work:urgent:`
+    if let Some((head, tags)) = title.rsplit_once(char::is_whitespace) {
+        let is_tags = tags.len() > 2
+            && tags.starts_with(':')
+            && tags.ends_with(':')
+            && tags
+                .chars()
+                .all(|c| c == ':' || c == '_' || c == '@' || c.is_alphanumeric());
+        if is_tags {
+            title = head.trim_end();
+        }
+    }
+    Some((level, title.to_string()))
+}
+
+/// Split a document into blocks, one per heading (plus any preamble).
+fn split_blocks(text: &str, format: Format) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut path: Vec<(usize, String)> = Vec::new();
+    let mut body: Vec<&str> = Vec::new();
+    // Opening fence of the code block we're inside, if any
+    let mut fence: Option<String> = None;
+
+    let mut lines = text.lines().peekable();
+    // YAML front matter is metadata, not a heading or an underline
+    if format == Format::Markdown && lines.peek().is_some_and(|l| l.trim_end() == "---") {
+        body.push(lines.next().unwrap_or_default());
+        for line in lines.by_ref() {
+            body.push(line);
+            if matches!(line.trim_end(), "---" | "...") {
+                break;
+            }
+        }
+    }
+
+    for line in lines {
+        let trimmed = line.trim_start();
+        if let Some(open) = &fence {
+            let closes = match format {
+                Format::Markdown => trimmed.starts_with(open.as_str()),
+                Format::Org => trimmed.to_lowercase().starts_with("#+end_"),
+            };
+            if closes {
+                fence = None;
+            }
+            body.push(line);
+            continue;
+        }
+
+        let heading = match format {
+            Format::Markdown => {
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    let marker = trimmed.chars().next().unwrap_or('`');
+                    let len = trimmed.chars().take_while(|&c| c == marker).count();
+                    fence = Some(marker.to_string().repeat(len));
+                    body.push(line);
+                    continue;
+                }
+                match setext_level(line) {
+                    // The underline turns the previous paragraph line into a heading
+                    Some(level) if body.last().is_some_and(|l| !l.trim().is_empty()) => {
+                        let title = body.pop().unwrap_or_default().trim().to_string();
+                        Some((level, title))
+                    }
+                    _ => markdown_heading(line),
+                }
+            }
+            Format::Org => {
+                if trimmed.to_lowercase().starts_with("#+begin_") {
+                    fence = Some(String::new());
+                    body.push(line);
+                    continue;
+                }
+                org_heading(line)
+            }
+        };
+
+        match heading {
+            Some((level, title)) => {
+                blocks.push(Block {
+                    path: path.clone(),
+                    body: body.join("\n"),
+                });
+                body.clear();
+                while path.last().is_some_and(|(l, _)| *l >= level) {
+                    path.pop();
+                }
+                path.push((level, title));
+            }
+            None => body.push(line),
+        }
+    }
+    blocks.push(Block {
+        path,
+        body: body.join("\n"),
+    });
+
+    blocks.retain(|b| !b.body.trim().is_empty());
+    blocks
+}
+
+/// `# Project > ## Design > ### Auth`
+fn breadcrumb(path: &[(usize, String)], format: Format) -> String {
+    path.iter()
+        .map(|(level, title)| format!("{} {}", format.marker(*level), title))
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+/// Chunk a Markdown or Org document along its headings.
+///
+/// Returns `None` for other extensions and for documents without headings,
+/// so the caller can fall back to plain chunking.
+pub fn chunk_markdown(text: &str, extension: &str, config: &ChunkConfig) -> Option<Vec<ChunkInfo>> {
+    let format = Format::from_extension(extension)?;
+    let blocks = split_blocks(text, format);
+    if blocks.iter().all(|b| b.path.is_empty()) {
+        return None;
+    }
+
+    let mut chunks = Vec::new();
+    for block in blocks {
+        let body = block.body.trim();
+        let (crumb, section) = if block.path.is_empty() {
+            (None, None)
+        } else {
+            let titles: Vec<&str> = block.path.iter().map(|(_, t)| t.as_str()).collect();
+            (
+                Some(breadcrumb(&block.path, format)),
+                Some(titles.join(" > ")),
+            )
+        };
+        // The breadcrumb counts against the chunk size
+        let crumb_tokens = crumb.as_deref().map_or(0, estimate_tokens);
+        let body_size = config
+            .chunk_size
+            .saturating_sub(crumb_tokens)
+            .max(MIN_BODY_TOKENS);
+
+        let pieces = if estimate_tokens(body) > body_size {
+            chunk_text(body, body_size, config.overlap.min(body_size / 2))
+                .into_iter()
+                .map(|c| c.content)
+                .collect()
+        } else {
+            // Small enough: keep the original line breaks and list structure
+            vec![body.to_string()]
+        };
+        for piece in pieces {
+            let content = match &crumb {
+                Some(crumb) => format!("{}\n\n{}", crumb, piece),
+                None => piece,
+            };
+            chunks.push(ChunkInfo {
+                index: chunks.len() as i32,
+                token_count: estimate_tokens(&content) as i32,
+                content,
+                section: section.clone(),
+                symbol: None,
+            });
+        }
+    }
+    Some(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_breadcrumbs() {
+        let text = r#"---
+title: Notes
+---
+Intro paragraph.
+
+# Project
+
+Overview.
+
+## Design
+
+```bash
+# not a heading
+```
+
+### Auth ###
+
+Tokens expire after an hour.
+
+## Rollout
+Phase one.
+"#;
+        let chunks = chunk_markdown(text, "md", &ChunkConfig::default()).unwrap();
+        let sections: Vec<Option<&str>> = chunks.iter().map(|c| c.section.as_deref()).collect();
+        assert_eq!(
+            sections,
+            vec![
+                None,
+                Some("Project"),
+                Some("Project > Design"),
+                Some("Project > Design > Auth"),
+                Some("Project > Rollout"),
+            ]
+        );
+        assert!(chunks[0].content.starts_with("---\ntitle: Notes"));
+        assert_eq!(
+            chunks[3].content,
+            "# Project > ## Design > ### Auth\n\nTokens expire after an hour."
+        );
+        assert!(chunks[2].content.contains("# not a heading"));
+    }
+
+    #[test]
+    fn test_setext_and_long_sections() {
+        let long = "word ".repeat(300);
+        let text = format!("Guide\n=====\n\nSetup\n-----\n{}", long);
+        let config = ChunkConfig {
+            chunk_size: 100,
+            overlap: 10,
+        };
+        let chunks = chunk_markdown(&text, "markdown", &config).unwrap();
+        assert!(chunks.len() > 1);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i as i32);
+            assert!(chunk.content.starts_with("# Guide > ## Setup\n\n"));
+            assert!(chunk.token_count as usize <= config.chunk_size);
+        }
+    }
+
+    #[test]
+    fn test_org_headings() {
+        let text = "#+TITLE: Plan\n* TODO Launch   :work:\nShip it.\n** Risks\n#+BEGIN_SRC sh\n* not a heading\n#+END_SRC\n";
+        let chunks = chunk_markdown(text, "org", &ChunkConfig::default()).unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].content, "* TODO Launch\n\nShip it.");
+        assert!(chunks[2]
+            .content
+            .starts_with("* TODO Launch > ** Risks\n\n"));
+        assert!(chunks[2].content.contains("* not a heading"));
+    }
+
+    #[test]
+    fn test_no_headings_falls_back() {
+        let config = ChunkConfig::default();
+        assert!(chunk_markdown("Just a paragraph.", "md", &config).is_none());
+        assert!(chunk_markdown("# Title\ntext", "txt", &config).is_none());
+    }
+}
@@ -1,5 +1,13 @@
+pub mod archive;
 pub mod chunker;
+pub mod code_chunker;
 pub mod extractor;
+pub mod importers;
+pub mod language;
+pub mod markdown_chunker;
+pub mod plugins;
+pub mod progress;
+pub mod throttle;
 
 /// File watcher — desktop only (requires `notify` crate with OS-level file events).
 #[cfg(desktop)]
@@ -20,6 +28,24 @@ pub async fn index_file(
     db: &Database,
     embedding_engine: &EmbeddingEngine,
     path: &Path,
+) -> Result<()> {
+    index_file_inner(db, embedding_engine, path, false).await
+}
+
+/// Index a file even if it is unchanged, e.g. after the chunk settings change.
+pub async fn reindex_file(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    path: &Path,
+) -> Result<()> {
+    index_file_inner(db, embedding_engine, path, true).await
+}
+
+async fn index_file_inner(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    path: &Path,
+    force: bool,
 ) -> Result<()> {
     let path_str = path.to_string_lossy().to_string();
 
@@ -58,14 +84,43 @@ pub async fn index_file(
 
     // Check if file already indexed with same hash
     if let Some((_, existing_hash)) = db.get_document_by_path(&path_str)? {
-        if existing_hash == hash {
+        if existing_hash == hash && !force {
             tracing::debug!("File unchanged, skipping: {}", path.display());
             return Ok(());
         }
     }
 
-    // Extract text
-    let text = extractor::extract_text(path)?;
+    // Extract text (emails also yield sender/date metadata, calendars event
+    // times; e-books and spreadsheets come as titled chapters/sheet row groups)
+    let extension = path.extension().and_then(|e| e.to_str());
+    let mut email_metadata = None;
+    let mut event_times = None;
+    let mut sections = None;
+    let text = match extension {
+        // Extractor plugins override the email/e-book extractors too
+        Some(ext) if plugins::handles(ext) => extractor::extract_text(path)?,
+        Some(ext) if extractor::is_email_extension(ext) => {
+            let (text, metadata) = extractor::extract_email(path)?;
+            email_metadata = Some(metadata);
+            text
+        }
+        Some(ext) if extractor::is_calendar_extension(ext) => {
+            let (text, times) = extractor::extract_calendar(path)?;
+            event_times = Some(times);
+            text
+        }
+        Some(ext) if extractor::is_sectioned_extension(ext) => {
+            let extracted = extractor::extract_sections(path)?;
+            let text = extracted
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            sections = Some(extracted);
+            text
+        }
+        _ => extractor::extract_text(path)?,
+    };
     if text.trim().is_empty() {
         tracing::warn!("No text extracted from: {}", path.display());
         return Ok(());
@@ -75,7 +130,6 @@ pub async fn index_file(
         .file_name()
         .and_then(|f| f.to_str())
         .unwrap_or("unknown");
-    let extension = path.extension().and_then(|e| e.to_str());
 
     // Upsert document
     let doc_id = db.upsert_document(
@@ -86,12 +140,31 @@ pub async fn index_file(
         &hash,
         &modified_at,
     )?;
+    if let Some(metadata) = &email_metadata {
+        db.set_email_metadata(
+            doc_id,
+            metadata.sender.as_deref(),
+            metadata.sent_at.as_deref(),
+        )?;
+    }
+    if let Some(times) = &event_times {
+        db.set_document_events(doc_id, times)?;
+    }
 
     // Delete old chunks and embeddings, then re-chunk
     db.delete_embeddings_for_document(doc_id)?;
     db.delete_chunks_for_document(doc_id)?;
 
-    let chunks = chunker::chunk_text_default(&text);
+    let chunks = match &sections {
+        Some(sections) => chunker::chunk_sections(sections),
+        // Source code splits on function/class boundaries, Markdown/Org on headings
+        None => extension
+            .and_then(|ext| {
+                code_chunker::chunk_code(&text, ext)
+                    .or_else(|| markdown_chunker::chunk_markdown(&text, ext, &chunker::current()))
+            })
+            .unwrap_or_else(|| chunker::chunk_text_default(&text)),
+    };
     tracing::info!(
         "Indexing {} ({} chunks): {}",
         filename,
@@ -99,25 +172,84 @@ pub async fn index_file(
         path.display()
     );
 
-    // Insert chunks in a single transaction (10-50x faster than individual inserts)
+    store_chunks(db, doc_id, &chunks)?;
+
+    // Let WASM plugins tag the new chunks (no-op without document hooks)
+    crate::wasm_plugins::on_document_indexed(db, doc_id);
+
+    // Reminders and TODOs in notes (source code TODOs aren't personal reminders)
+    if !extension.is_some_and(code_chunker::is_code_extension) {
+        let written = crate::reminders::local_time(&modified_at)
+            .unwrap_or_else(|| chrono::Local::now().naive_local());
+        if let Err(e) = crate::reminders::scan_document(db, doc_id, &text, written) {
+            tracing::warn!("Failed to scan reminders in {}: {}", path.display(), e);
+        }
+    }
+
+    // Links and tags of notes in imported Obsidian/Logseq/Notion vaults
+    if extension.is_some_and(importers::notes::is_note_extension) {
+        if let Err(e) = importers::notes::on_note_indexed(db, doc_id, path, &text) {
+            tracing::warn!("Failed to read note links in {}: {}", path.display(), e);
+        }
+    }
+
+    embed_new_chunks(
+        db,
+        embedding_engine,
+        doc_id,
+        extension,
+        chunks.len(),
+        filename,
+    )
+    .await
+}
+
+/// Insert a document's chunks in a single transaction (10-50x faster than
+/// individual inserts).
+pub(crate) fn store_chunks(
+    db: &Database,
+    doc_id: i64,
+    chunks: &[chunker::ChunkInfo],
+) -> Result<()> {
     db.with_transaction(|conn| {
-        for chunk in &chunks {
+        for chunk in chunks {
             conn.execute(
-                "INSERT OR REPLACE INTO chunks (document_id, chunk_index, content, token_count)
-                 VALUES (?1, ?2, ?3, ?4)",
-                rusqlite::params![doc_id, chunk.index, chunk.content, chunk.token_count],
+                "INSERT OR REPLACE INTO chunks
+                    (document_id, chunk_index, content, token_count, section, symbol, language)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![
+                    doc_id,
+                    chunk.index,
+                    chunk.content,
+                    chunk.token_count,
+                    chunk.section,
+                    chunk.symbol,
+                    language::detect(&chunk.content)
+                ],
             )?;
         }
         Ok(())
-    })?;
+    })
+}
 
-    // Try to generate embeddings in batches (graceful degradation if engine is down)
+/// Embed up to `count` chunks still missing embeddings in batches, storing
+/// them under `doc_id` (graceful degradation if the engine is down; `label`
+/// names the document in logs).
+pub(crate) async fn embed_new_chunks(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    doc_id: i64,
+    extension: Option<&str>,
+    count: usize,
+    label: &str,
+) -> Result<()> {
     if embedding_engine.health_check().await.unwrap_or(false) {
-        let unembedded = db.get_unembedded_chunks(chunks.len())?;
+        let unembedded = db.get_unembedded_chunks(count)?;
         if !unembedded.is_empty() {
-            // Batch embed all chunks at once (2-5x faster with tensor batching)
+            // Batch embed all chunks at once (2-5x faster with tensor batching),
+            // reusing stored embeddings for unchanged chunk text
             let texts: Vec<String> = unembedded.iter().map(|(_, c)| c.clone()).collect();
-            match embedding_engine.embed_batch(&texts).await {
+            match crate::embeddings::cache::embed_chunks(db, embedding_engine, &texts).await {
                 Ok(embeddings) => {
                     // Store all embeddings in a single transaction
                     // Includes document_id (partition key) and extension (metadata)
@@ -148,21 +280,16 @@ pub async fn index_file(
                         }
                         Ok(())
                     })?;
-                    tracing::debug!(
-                        "Batch embedded {} chunks for {}",
-                        embeddings.len(),
-                        filename
-                    );
+                    tracing::debug!("Batch embedded {} chunks for {}", embeddings.len(), label);
                 }
                 Err(e) => {
-                    tracing::warn!("Batch embedding failed for {}: {}", filename, e);
+                    tracing::warn!("Batch embedding failed for {}: {}", label, e);
                 }
             }
         }
     } else {
         tracing::info!("No embedding engine available — skipping embeddings, FTS5 index created");
     }
-
     Ok(())
 }
 
@@ -171,6 +298,18 @@ pub async fn index_directory(
     db: &Database,
     embedding_engine: &EmbeddingEngine,
     dir: &Path,
+) -> Result<IndexStats> {
+    index_directory_with(db, embedding_engine, dir, Default::default()).await
+}
+
+/// [`index_directory`], stopping before the next file once `control` asks
+/// the run to cancel or pause. Background runs wait between files while
+/// the throttle holds indexing.
+pub async fn index_directory_with(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    dir: &Path,
+    control: std::sync::Arc<progress::RunControl>,
 ) -> Result<IndexStats> {
     let mut stats = IndexStats::default();
 
@@ -181,21 +320,56 @@ pub async fn index_directory(
         )));
     }
 
+    let background = control.is_background();
+    let run = progress::start(dir, control);
     let entries = walk_directory(dir)?;
+    run.scanned(entries.len());
 
     for path in entries {
+        if background
+            && throttle::wait_while_throttled(
+                |_| run.throttled(),
+                || run.stop_requested().is_some(),
+            )
+            .await
+        {
+            run.resumed();
+        }
+        match run.stop_requested() {
+            Some(progress::StopRequest::Cancel) => {
+                stats.cancelled = true;
+                break;
+            }
+            Some(progress::StopRequest::Pause) => {
+                stats.paused = true;
+                break;
+            }
+            None => {}
+        }
+        run.file_started(&path);
         match index_file(db, embedding_engine, &path).await {
-            Ok(()) => stats.indexed += 1,
+            Ok(()) => {
+                stats.indexed += 1;
+                run.file_finished(true);
+            }
             Err(e) => {
                 tracing::warn!("Failed to index {}: {}", path.display(), e);
                 stats.failed += 1;
+                run.file_finished(false);
             }
         }
     }
 
     stats.total = stats.indexed + stats.failed;
     tracing::info!(
-        "Indexing complete: {} indexed, {} failed, {} total",
+        "Indexing {}: {} indexed, {} failed, {} total",
+        if stats.cancelled {
+            "cancelled"
+        } else if stats.paused {
+            "paused"
+        } else {
+            "complete"
+        },
         stats.indexed,
         stats.failed,
         stats.total
@@ -204,6 +378,34 @@ pub async fn index_directory(
     Ok(stats)
 }
 
+/// Re-chunk and re-embed every indexed document with the current chunk
+/// settings. Documents whose files are gone are left for the watcher.
+pub async fn rechunk_all(db: &Database, embedding_engine: &EmbeddingEngine) -> Result<IndexStats> {
+    const PAGE: usize = 500;
+    let mut paths = Vec::new();
+    loop {
+        let page = db.list_documents(paths.len(), PAGE)?;
+        let done = page.len() < PAGE;
+        paths.extend(page.into_iter().map(|d| d.path));
+        if done {
+            break;
+        }
+    }
+
+    let mut stats = IndexStats::default();
+    for path in paths.iter().map(Path::new).filter(|p| p.is_file()) {
+        match reindex_file(db, embedding_engine, path).await {
+            Ok(()) => stats.indexed += 1,
+            Err(e) => {
+                tracing::warn!("Failed to re-chunk {}: {}", path.display(), e);
+                stats.failed += 1;
+            }
+        }
+    }
+    stats.total = stats.indexed + stats.failed;
+    Ok(stats)
+}
+
 /// Walk a directory iteratively and collect all supported files.
 /// Uses an explicit stack instead of recursion to avoid stack overflow on deep trees.
 fn walk_directory(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
@@ -343,6 +545,10 @@ pub struct IndexStats {
     pub total: usize,
     pub indexed: usize,
     pub failed: usize,
+    /// The run was cancelled before every file was processed.
+    pub cancelled: bool,
+    /// The run was paused; its job indexes the remaining files on resume.
+    pub paused: bool,
 }
 
 #[cfg(test)]
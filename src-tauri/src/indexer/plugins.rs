@@ -0,0 +1,304 @@
+//! User-registered extractor plugins.
+//!
+//! A plugin maps a file extension to an external command (e.g. `pandoc` for
+//! `.rtf`, or a script for a proprietary format) whose stdout is used as the
+//! document text. Plugins take precedence over the built-in extractors for
+//! their extension and make otherwise unsupported extensions indexable.
+//!
+//! Plugins are sandboxed as far as a plain child process allows:
+//! - No shell: the file path is passed as an argument, never interpolated
+//! - Cleared environment (only PATH and locale/system variables pass through),
+//!   with HOME, TMPDIR, and the working directory set to a scratch directory
+//! - stdin closed, stdout capped at [`MAX_OUTPUT_BYTES`]
+//! - Killed when the timeout expires
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GhostError, Result};
+
+/// Most text kept from a plugin's stdout.
+const MAX_OUTPUT_BYTES: usize = 20 * 1024 * 1024;
+
+/// Stderr kept for error messages.
+const MAX_STDERR_BYTES: usize = 4096;
+
+/// Longest a plugin may be configured to run.
+const MAX_TIMEOUT_SECS: u64 = 600;
+
+/// Environment variables passed through to plugins.
+const PASSTHROUGH_ENV: &[&str] = &["PATH", "LANG", "LC_ALL", "SYSTEMROOT", "WINDIR"];
+
+/// An external command registered for one file extension.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExtractorPlugin {
+    /// Extension handled, without the dot (e.g. "rtf").
+    pub extension: String,
+    /// Program to run: an absolute path or a name on PATH.
+    pub command: String,
+    /// Arguments; `{path}` is replaced with the file path.
+    /// When no argument contains `{path}`, the path is appended.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds before the command is killed.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+fn default_enabled() -> bool {
+    true
+}
+
+static PLUGINS: LazyLock<RwLock<Vec<ExtractorPlugin>>> = LazyLock::new(Default::default);
+
+/// Normalize an extension for lookup: lowercase, no leading dot.
+fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// Why a plugin can't be registered, if it is invalid.
+pub fn validate(plugin: &ExtractorPlugin) -> std::result::Result<(), String> {
+    let extension = normalize_extension(&plugin.extension);
+    if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid extension '{}'", plugin.extension));
+    }
+    if plugin.command.trim().is_empty() {
+        return Err(format!("No command set for .{}", extension));
+    }
+    if plugin.timeout_secs == 0 || plugin.timeout_secs > MAX_TIMEOUT_SECS {
+        return Err(format!(
+            "Timeout for .{} must be between 1 and {} seconds",
+            extension, MAX_TIMEOUT_SECS
+        ));
+    }
+    Ok(())
+}
+
+/// Replace the registered plugins (from Settings). Invalid entries are skipped.
+pub fn configure(plugins: &[ExtractorPlugin]) {
+    let valid: Vec<ExtractorPlugin> = plugins
+        .iter()
+        .filter(|p| p.enabled)
+        .filter(|p| match validate(p) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Ignoring extractor plugin: {}", e);
+                false
+            }
+        })
+        .map(|p| ExtractorPlugin {
+            extension: normalize_extension(&p.extension),
+            ..p.clone()
+        })
+        .collect();
+    if let Ok(mut registered) = PLUGINS.write() {
+        *registered = valid;
+    }
+}
+
+/// The enabled plugin for an extension, if any.
+pub fn plugin_for(extension: &str) -> Option<ExtractorPlugin> {
+    let extension = normalize_extension(extension);
+    PLUGINS
+        .read()
+        .ok()?
+        .iter()
+        .find(|p| p.extension == extension)
+        .cloned()
+}
+
+/// Whether a plugin is registered for an extension.
+pub fn handles(extension: &str) -> bool {
+    plugin_for(extension).is_some()
+}
+
+/// Command-line arguments for running `plugin` on `path`.
+fn plugin_args(plugin: &ExtractorPlugin, path: &Path) -> Vec<String> {
+    let path = path.to_string_lossy();
+    if plugin.args.iter().any(|a| a.contains("{path}")) {
+        plugin
+            .args
+            .iter()
+            .map(|a| a.replace("{path}", &path))
+            .collect()
+    } else {
+        let mut args = plugin.args.clone();
+        args.push(path.into_owned());
+        args
+    }
+}
+
+/// Scratch directory used as HOME/TMPDIR/working directory for plugins.
+fn scratch_dir() -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("ghost_extractor_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Run `plugin` on `path` and return its stdout as text.
+pub fn run(plugin: &ExtractorPlugin, path: &Path) -> Result<String> {
+    let scratch = scratch_dir()?;
+    let mut command = Command::new(&plugin.command);
+    command
+        .args(plugin_args(plugin, path))
+        .env_clear()
+        .env("HOME", &scratch)
+        .env("TMPDIR", &scratch)
+        .env("TEMP", &scratch)
+        .current_dir(&scratch)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for name in PASSTHROUGH_ENV {
+        if let Some(value) = std::env::var_os(name) {
+            command.env(name, value);
+        }
+    }
+
+    let mut child = command.spawn().map_err(|e| {
+        GhostError::Indexer(format!(
+            "Failed to start extractor '{}' for .{}: {}",
+            plugin.command, plugin.extension, e
+        ))
+    })?;
+
+    // Read both pipes on threads so a chatty plugin can't block on a full pipe
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| read_capped(out, MAX_OUTPUT_BYTES));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| read_capped(err, MAX_STDERR_BYTES));
+
+    let deadline = Instant::now() + Duration::from_secs(plugin.timeout_secs);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(GhostError::Indexer(format!(
+                "Extractor '{}' timed out after {}s on {}",
+                plugin.command,
+                plugin.timeout_secs,
+                path.display()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(25));
+    };
+
+    let output = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+    if !status.success() {
+        let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+        let message = String::from_utf8_lossy(&stderr);
+        return Err(GhostError::Indexer(format!(
+            "Extractor '{}' failed on {} ({}): {}",
+            plugin.command,
+            path.display(),
+            status,
+            message.trim()
+        )));
+    }
+    if output.len() >= MAX_OUTPUT_BYTES {
+        tracing::warn!(
+            "Extractor '{}' output truncated to {} bytes for {}",
+            plugin.command,
+            MAX_OUTPUT_BYTES,
+            path.display()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Read up to `limit` bytes on a background thread, draining the rest.
+fn read_capped(
+    mut reader: impl Read + Send + 'static,
+    limit: usize,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = (&mut reader).take(limit as u64).read_to_end(&mut buf);
+        let _ = std::io::copy(&mut reader, &mut std::io::sink());
+        buf
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(command: &str, args: &[&str], timeout_secs: u64) -> ExtractorPlugin {
+        ExtractorPlugin {
+            extension: "xyz".into(),
+            command: command.into(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            timeout_secs,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_validate_and_args() {
+        assert!(validate(&plugin("pandoc", &[], 30)).is_ok());
+        assert!(validate(&plugin("", &[], 30)).is_err());
+        assert!(validate(&plugin("pandoc", &[], 0)).is_err());
+        let mut bad = plugin("pandoc", &[], 30);
+        bad.extension = "r/tf".into();
+        assert!(validate(&bad).is_err());
+
+        let path = Path::new("/docs/a b.rtf");
+        assert_eq!(
+            plugin_args(&plugin("pandoc", &["-t", "plain"], 30), path),
+            vec!["-t", "plain", "/docs/a b.rtf"]
+        );
+        assert_eq!(
+            plugin_args(&plugin("conv", &["--in={path}", "-"], 30), path),
+            vec!["--in=/docs/a b.rtf", "-"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_plugin() {
+        let dir = std::env::temp_dir().join("ghost_test_extractor_plugin");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("note.xyz");
+        std::fs::write(&path, "plugin text").unwrap();
+
+        assert_eq!(run(&plugin("cat", &[], 5), &path).unwrap(), "plugin text");
+
+        // The environment is cleared
+        std::env::set_var("GHOST_TEST_PLUGIN_SECRET", "leaked");
+        let env = plugin("sh", &["-c", "echo ${GHOST_TEST_PLUGIN_SECRET:-clean}"], 5);
+        assert_eq!(run(&env, &path).unwrap().trim(), "clean");
+
+        let failing = plugin("sh", &["-c", "echo bad input >&2; exit 3"], 5);
+        assert!(run(&failing, &path)
+            .unwrap_err()
+            .to_string()
+            .contains("bad input"));
+
+        let slow = plugin("sh", &["-c", "sleep 10"], 1);
+        let started = Instant::now();
+        assert!(run(&slow, &path)
+            .unwrap_err()
+            .to_string()
+            .contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,348 @@
+//! Live progress of directory indexing runs.
+//!
+//! Every [`super::index_directory`] call registers a run here. Progress is
+//! broadcast to subscribers (forwarded to the frontend as
+//! [`INDEX_PROGRESS_EVENT`]) at most every [`EMIT_INTERVAL`], and the list of
+//! runs in flight is available to `get_active_index_jobs`. A run can be
+//! cancelled or paused through its [`RunControl`]; the indexer stops before
+//! its next file. Pausing is driven by the job scheduler, which keeps the job
+//! and runs it again on resume (unchanged files are skipped by hash).
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Tauri event carrying an [`IndexProgress`].
+pub const INDEX_PROGRESS_EVENT: &str = "indexer://progress";
+
+/// Minimum time between two progress events of one run.
+const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Files processed before an ETA is estimated.
+const MIN_FILES_FOR_ETA: usize = 3;
+
+/// Phase of an indexing run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunState {
+    /// Walking the directory tree.
+    Scanning,
+    Indexing,
+    /// Held on battery or by CPU temperature (see [`super::throttle`]).
+    Throttled,
+    Completed,
+    Cancelled,
+    /// Stopped early; its job runs again when resumed.
+    Paused,
+}
+
+/// Why a run was asked to stop before its next file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopRequest {
+    Cancel,
+    Pause,
+}
+
+/// Stop flag shared between a run and whoever started it.
+#[derive(Debug, Default)]
+pub struct RunControl {
+    /// Scheduler job that owns the run, if any.
+    job_id: Option<i64>,
+    stop: AtomicU8,
+}
+
+impl RunControl {
+    /// Control for a scheduler job. Job runs are background work and
+    /// follow the battery/thermal throttle.
+    pub fn for_job(job_id: i64) -> Self {
+        Self {
+            job_id: Some(job_id),
+            stop: AtomicU8::new(0),
+        }
+    }
+
+    pub fn is_background(&self) -> bool {
+        self.job_id.is_some()
+    }
+
+    pub fn request(&self, request: StopRequest) {
+        let value = match request {
+            StopRequest::Cancel => 1,
+            StopRequest::Pause => 2,
+        };
+        self.stop.store(value, Ordering::SeqCst);
+    }
+
+    pub fn requested(&self) -> Option<StopRequest> {
+        match self.stop.load(Ordering::SeqCst) {
+            1 => Some(StopRequest::Cancel),
+            2 => Some(StopRequest::Pause),
+            _ => None,
+        }
+    }
+}
+
+/// Snapshot of an indexing run for the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexProgress {
+    /// Run ID, used to cancel it.
+    pub id: u64,
+    /// Scheduler job running this directory, used to pause it.
+    pub job_id: Option<i64>,
+    pub directory: String,
+    pub state: RunState,
+    /// Supported files found in the directory.
+    pub scanned: usize,
+    pub indexed: usize,
+    pub failed: usize,
+    /// File being indexed right now.
+    pub current_file: Option<String>,
+    pub elapsed_seconds: u64,
+    /// Estimated time to finish, once a few files are done.
+    pub eta_seconds: Option<u64>,
+}
+
+/// An indexing run in flight.
+pub struct IndexRun {
+    started: Instant,
+    control: Arc<RunControl>,
+    progress: Mutex<IndexProgress>,
+    last_emit: Mutex<Option<Instant>>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static ACTIVE: LazyLock<Mutex<HashMap<u64, Arc<IndexRun>>>> = LazyLock::new(Default::default);
+static EVENTS: LazyLock<broadcast::Sender<IndexProgress>> =
+    LazyLock::new(|| broadcast::channel(64).0);
+
+/// Receive progress events of all runs.
+pub fn subscribe() -> broadcast::Receiver<IndexProgress> {
+    EVENTS.subscribe()
+}
+
+/// Runs in flight, oldest first.
+pub fn active() -> Vec<IndexProgress> {
+    let mut runs: Vec<IndexProgress> = ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .map(|run| run.snapshot())
+        .collect();
+    runs.sort_by_key(|p| p.id);
+    runs
+}
+
+/// Ask a run to stop before its next file. Returns false for unknown runs.
+pub fn cancel(id: u64) -> bool {
+    match ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).get(&id) {
+        Some(run) => {
+            run.control.request(StopRequest::Cancel);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Register a run for `directory`. It is unregistered when the handle drops.
+pub fn start(directory: &Path, control: Arc<RunControl>) -> IndexRunHandle {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let run = Arc::new(IndexRun {
+        started: Instant::now(),
+        progress: Mutex::new(IndexProgress {
+            id,
+            job_id: control.job_id,
+            directory: directory.display().to_string(),
+            state: RunState::Scanning,
+            scanned: 0,
+            indexed: 0,
+            failed: 0,
+            current_file: None,
+            elapsed_seconds: 0,
+            eta_seconds: None,
+        }),
+        last_emit: Mutex::new(None),
+        control,
+    });
+    ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, run.clone());
+    run.emit(true);
+    IndexRunHandle(run)
+}
+
+impl IndexRun {
+    pub fn stop_requested(&self) -> Option<StopRequest> {
+        self.control.requested()
+    }
+
+    /// The directory walk found `files` files to index.
+    pub fn scanned(&self, files: usize) {
+        self.update(true, |p| {
+            p.scanned = files;
+            p.state = RunState::Indexing;
+        });
+    }
+
+    pub fn throttled(&self) {
+        self.update(true, |p| {
+            p.state = RunState::Throttled;
+            p.eta_seconds = None;
+        });
+    }
+
+    pub fn resumed(&self) {
+        self.update(true, |p| p.state = RunState::Indexing);
+    }
+
+    pub fn file_started(&self, path: &Path) {
+        let path = path.display().to_string();
+        self.update(false, |p| p.current_file = Some(path));
+    }
+
+    pub fn file_finished(&self, ok: bool) {
+        self.update(false, |p| {
+            if ok {
+                p.indexed += 1;
+            } else {
+                p.failed += 1;
+            }
+        });
+    }
+
+    fn finish(&self) {
+        let state = match self.stop_requested() {
+            Some(StopRequest::Cancel) => RunState::Cancelled,
+            Some(StopRequest::Pause) => RunState::Paused,
+            None => RunState::Completed,
+        };
+        self.update(true, |p| {
+            p.state = state;
+            p.current_file = None;
+            p.eta_seconds = None;
+        });
+    }
+
+    fn snapshot(&self) -> IndexProgress {
+        self.progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn update(&self, force_emit: bool, f: impl FnOnce(&mut IndexProgress)) {
+        {
+            let mut progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+            f(&mut progress);
+            let elapsed = self.started.elapsed();
+            progress.elapsed_seconds = elapsed.as_secs();
+            if progress.state == RunState::Indexing {
+                progress.eta_seconds = eta(
+                    elapsed,
+                    progress.indexed + progress.failed,
+                    progress.scanned,
+                );
+            }
+        }
+        self.emit(force_emit);
+    }
+
+    fn emit(&self, force: bool) {
+        {
+            let mut last = self.last_emit.lock().unwrap_or_else(|e| e.into_inner());
+            if !force && last.is_some_and(|t| t.elapsed() < EMIT_INTERVAL) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+        // No subscribers (tests, headless) is fine
+        let _ = EVENTS.send(self.snapshot());
+    }
+}
+
+/// Owner of a registered run; reports the final state and unregisters on drop,
+/// including when the indexing task is aborted.
+pub struct IndexRunHandle(Arc<IndexRun>);
+
+impl std::ops::Deref for IndexRunHandle {
+    type Target = IndexRun;
+
+    fn deref(&self) -> &IndexRun {
+        &self.0
+    }
+}
+
+impl Drop for IndexRunHandle {
+    fn drop(&mut self) {
+        let id = self.0.snapshot().id;
+        ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        self.0.finish();
+    }
+}
+
+/// Remaining time at the average pace so far.
+fn eta(elapsed: Duration, done: usize, total: usize) -> Option<u64> {
+    if done < MIN_FILES_FOR_ETA || done > total {
+        return None;
+    }
+    let per_file = elapsed.as_secs_f64() / done as f64;
+    Some((per_file * (total - done) as f64).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta() {
+        assert_eq!(eta(Duration::from_secs(10), 1, 100), None);
+        assert_eq!(eta(Duration::from_secs(10), 10, 100), Some(90));
+        assert_eq!(eta(Duration::from_secs(10), 100, 100), Some(0));
+    }
+
+    #[test]
+    fn test_run_lifecycle() {
+        let mut events = subscribe();
+        let run = start(Path::new("/vault/notes"), Default::default());
+        let id = run.snapshot().id;
+        run.scanned(2);
+        run.file_started(Path::new("/vault/notes/a.md"));
+        run.file_finished(true);
+        assert!(active().iter().any(|p| p.id == id && p.indexed == 1));
+
+        assert!(cancel(id));
+        assert_eq!(run.stop_requested(), Some(StopRequest::Cancel));
+        drop(run);
+        assert!(active().iter().all(|p| p.id != id));
+        assert!(!cancel(id));
+
+        let last = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|p| p.id == id)
+            .last()
+            .unwrap();
+        assert_eq!(last.state, RunState::Cancelled);
+        assert_eq!(last.scanned, 2);
+    }
+
+    #[test]
+    fn test_paused_run_reports_job() {
+        let mut events = subscribe();
+        let control = Arc::new(RunControl::for_job(42));
+        let run = start(Path::new("/vault/archive"), control.clone());
+        let id = run.snapshot().id;
+        assert_eq!(run.snapshot().job_id, Some(42));
+
+        control.request(StopRequest::Pause);
+        drop(run);
+        let last = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|p| p.id == id)
+            .last()
+            .unwrap();
+        assert_eq!(last.state, RunState::Paused);
+    }
+}
@@ -0,0 +1,207 @@
+//! Battery and thermal throttle for background indexing.
+//!
+//! Directory indexing and re-embedding wait between files (or batches) while
+//! the laptop runs on battery or the CPU is too hot, and continue by
+//! themselves once it is plugged in or has cooled down. Power and temperature
+//! are read at most every [`CHECK_INTERVAL`], so the check is cheap enough to
+//! run before every file.
+
+use std::sync::{LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::embeddings::hardware::{self, PowerSource};
+
+/// How long a power/temperature reading is reused.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Pause between checks while indexing is held.
+const WAIT_STEP: Duration = Duration::from_secs(5);
+
+/// Degrees below the limit the CPU must cool to before work resumes.
+const RESUME_MARGIN_C: f32 = 10.0;
+
+/// User-configurable throttling policy, persisted in Settings.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ThrottleSettings {
+    /// Hold background indexing while on battery.
+    #[serde(default = "default_pause_on_battery")]
+    pub pause_on_battery: bool,
+    /// Hold background indexing at or above this CPU temperature in °C (0 = off).
+    #[serde(default = "default_max_cpu_temp_c")]
+    pub max_cpu_temp_c: u32,
+}
+
+fn default_pause_on_battery() -> bool {
+    true
+}
+
+fn default_max_cpu_temp_c() -> u32 {
+    90
+}
+
+impl Default for ThrottleSettings {
+    fn default() -> Self {
+        Self {
+            pause_on_battery: default_pause_on_battery(),
+            max_cpu_temp_c: default_max_cpu_temp_c(),
+        }
+    }
+}
+
+/// Why indexing is being held.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(tag = "reason", rename_all = "lowercase")]
+pub enum ThrottleReason {
+    Battery,
+    Temperature { celsius: f32 },
+}
+
+/// Current power state and throttle decision, for the settings UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThrottleStatus {
+    pub power_source: PowerSource,
+    pub cpu_temperature_c: Option<f32>,
+    /// Set while background indexing is held.
+    pub throttled: Option<ThrottleReason>,
+}
+
+struct Reading {
+    at: Instant,
+    power_source: PowerSource,
+    cpu_temperature_c: Option<f32>,
+}
+
+static SETTINGS: LazyLock<RwLock<ThrottleSettings>> = LazyLock::new(Default::default);
+static LAST_READING: Mutex<Option<Reading>> = Mutex::new(None);
+/// Whether the last check held indexing, for the temperature hysteresis.
+static THROTTLED: Mutex<bool> = Mutex::new(false);
+
+/// Apply the user's throttle settings.
+pub fn configure(settings: ThrottleSettings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// Power source and CPU temperature, refreshed at most every [`CHECK_INTERVAL`].
+fn reading() -> (PowerSource, Option<f32>) {
+    let mut last = LAST_READING.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(r) = last.as_ref().filter(|r| r.at.elapsed() < CHECK_INTERVAL) {
+        return (r.power_source, r.cpu_temperature_c);
+    }
+    let reading = Reading {
+        at: Instant::now(),
+        power_source: hardware::power_source(),
+        cpu_temperature_c: hardware::cpu_temperature_c(),
+    };
+    let values = (reading.power_source, reading.cpu_temperature_c);
+    *last = Some(reading);
+    values
+}
+
+/// The throttle decision for a reading. Once held for heat, work resumes only
+/// after the CPU is [`RESUME_MARGIN_C`] below the limit.
+fn decide(
+    settings: &ThrottleSettings,
+    power_source: PowerSource,
+    cpu_temperature_c: Option<f32>,
+    throttled: bool,
+) -> Option<ThrottleReason> {
+    if settings.pause_on_battery && power_source == PowerSource::Battery {
+        return Some(ThrottleReason::Battery);
+    }
+    let celsius = cpu_temperature_c?;
+    let limit = settings.max_cpu_temp_c as f32;
+    let threshold = if throttled {
+        limit - RESUME_MARGIN_C
+    } else {
+        limit
+    };
+    (settings.max_cpu_temp_c > 0 && celsius >= threshold)
+        .then_some(ThrottleReason::Temperature { celsius })
+}
+
+/// Current throttle status. Blocking: may spawn a process to read the power source.
+pub fn status() -> ThrottleStatus {
+    let settings = *SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+    let (power_source, cpu_temperature_c) = reading();
+    let mut throttled = THROTTLED.lock().unwrap_or_else(|e| e.into_inner());
+    let reason = decide(&settings, power_source, cpu_temperature_c, *throttled);
+    *throttled = reason.is_some();
+    ThrottleStatus {
+        power_source,
+        cpu_temperature_c,
+        throttled: reason,
+    }
+}
+
+/// Why background indexing should wait right now, if it should.
+pub async fn check() -> Option<ThrottleReason> {
+    tokio::task::spawn_blocking(status)
+        .await
+        .ok()
+        .and_then(|s| s.throttled)
+}
+
+/// Wait until indexing may continue, or until `stop` returns true.
+/// `on_throttled` is called on every check that holds indexing. Returns
+/// whether any time was spent waiting.
+pub async fn wait_while_throttled(
+    mut on_throttled: impl FnMut(ThrottleReason),
+    stop: impl Fn() -> bool,
+) -> bool {
+    let mut waited = false;
+    while let Some(reason) = check().await {
+        if stop() {
+            break;
+        }
+        if !waited {
+            tracing::info!("Background indexing held: {:?}", reason);
+        }
+        on_throttled(reason);
+        waited = true;
+        tokio::time::sleep(WAIT_STEP).await;
+    }
+    if waited {
+        tracing::info!("Background indexing resumed");
+    }
+    waited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide_battery() {
+        let settings = ThrottleSettings::default();
+        assert_eq!(
+            decide(&settings, PowerSource::Battery, None, false),
+            Some(ThrottleReason::Battery)
+        );
+        assert_eq!(decide(&settings, PowerSource::Ac, None, false), None);
+        assert_eq!(decide(&settings, PowerSource::Unknown, None, false), None);
+        let off = ThrottleSettings {
+            pause_on_battery: false,
+            ..settings
+        };
+        assert_eq!(decide(&off, PowerSource::Battery, None, false), None);
+    }
+
+    #[test]
+    fn test_decide_temperature_hysteresis() {
+        let settings = ThrottleSettings::default();
+        let hot = decide(&settings, PowerSource::Ac, Some(92.0), false);
+        assert_eq!(hot, Some(ThrottleReason::Temperature { celsius: 92.0 }));
+        // Below the limit but not cooled down enough while held
+        assert!(decide(&settings, PowerSource::Ac, Some(85.0), true).is_some());
+        assert!(decide(&settings, PowerSource::Ac, Some(85.0), false).is_none());
+        assert!(decide(&settings, PowerSource::Ac, Some(79.0), true).is_none());
+
+        let off = ThrottleSettings {
+            max_cpu_temp_c: 0,
+            ..settings
+        };
+        assert!(decide(&off, PowerSource::Ac, Some(99.0), false).is_none());
+    }
+}
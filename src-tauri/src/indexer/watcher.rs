@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::Duration;
 
@@ -17,6 +17,14 @@ pub enum FileEvent {
     Removed(PathBuf),
 }
 
+impl FileEvent {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Changed(path) | Self::Removed(path) => path,
+        }
+    }
+}
+
 /// Start watching a directory for file changes.
 /// Returns a receiver that emits FileEvents.
 pub fn start_watching(directories: Vec<PathBuf>) -> Result<mpsc::Receiver<Vec<FileEvent>>> {
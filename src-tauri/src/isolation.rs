@@ -0,0 +1,246 @@
+//! Per-user vault isolation checks.
+//!
+//! The vault and settings hold the user's indexed documents, conversations,
+//! and API keys, so they must only be readable by the OS account that owns
+//! them. At startup Ghost checks that:
+//! - The app data dir lives inside the current user's profile (portable
+//!   installs on shared drives and network/roaming paths are flagged)
+//! - On Unix, the data dir is owned by the current user
+//!
+//! and tightens permissions: 0700 on the data dir and each vault's directory
+//! under `vaults/`, 0600 on the vault and settings files. On Windows, files
+//! under the user profile already inherit a per-user ACL, so only the
+//! location checks apply.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Files in the data dir, and in each vault's directory, that must be
+/// private to the owning user.
+const PRIVATE_FILES: &[&str] = &[
+    "ghost_vault.db",
+    "ghost_vault.db-wal",
+    "ghost_vault.db-shm",
+    "settings.json",
+];
+
+/// Result of the isolation checks.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IsolationReport {
+    pub data_dir: String,
+    /// Problems the user should know about (shared or foreign-owned data dir).
+    pub warnings: Vec<String>,
+    /// Paths whose permissions were tightened.
+    pub hardened: Vec<String>,
+}
+
+/// Warnings about where the data dir lives relative to the user's home.
+fn location_warnings(data_dir: &Path, home: Option<&Path>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let display = data_dir.to_string_lossy();
+    if display.starts_with("\\\\") || display.starts_with("//") {
+        warnings.push(format!(
+            "The Ghost data folder is on a network path ({}). It may roam between \
+             machines or be readable by other accounts.",
+            display
+        ));
+    } else if let Some(home) = home {
+        if !data_dir.starts_with(home) {
+            warnings.push(format!(
+                "The Ghost data folder ({}) is outside your user profile. If this is a \
+                 shared or portable install, other accounts on this computer may be able \
+                 to read your vault.",
+                display
+            ));
+        }
+    }
+    warnings
+}
+
+/// Warn when the data dir belongs to a different OS user than the home dir.
+#[cfg(unix)]
+fn owner_warnings(data_dir: &Path, home: Option<&Path>) -> Vec<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let (Ok(data_meta), Some(Ok(home_meta))) =
+        (std::fs::metadata(data_dir), home.map(std::fs::metadata))
+    else {
+        return Vec::new();
+    };
+    if data_meta.uid() != home_meta.uid() {
+        vec![format!(
+            "The Ghost data folder ({}) is owned by another user account (uid {}). \
+             Each OS user should have their own vault.",
+            data_dir.display(),
+            data_meta.uid()
+        )]
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(not(unix))]
+fn owner_warnings(_data_dir: &Path, _home: Option<&Path>) -> Vec<String> {
+    Vec::new()
+}
+
+/// Restrict `path` to `mode` if group/other have any access. Returns true if changed.
+#[cfg(unix)]
+fn restrict_permissions(path: &Path, mode: u32) -> std::io::Result<bool> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let current = std::fs::metadata(path)?.permissions().mode();
+    if current & 0o077 == 0 {
+        return Ok(false);
+    }
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path, _mode: u32) -> std::io::Result<bool> {
+    Ok(false)
+}
+
+/// Make a newly written file private to the current user (no-op on Windows).
+pub fn make_private(path: &Path) {
+    if let Err(e) = restrict_permissions(path, 0o600) {
+        tracing::debug!("Could not restrict {}: {}", path.display(), e);
+    }
+}
+
+/// Tighten permissions on a vault's directory and its private files. Called
+/// after a vault's database is created, so it doesn't wait for the next launch.
+pub fn harden_vault_dir(dir: &Path) {
+    let mut report = IsolationReport::default();
+    harden_dir(dir, &mut report);
+    for warning in report.warnings {
+        tracing::warn!("{}", warning);
+    }
+}
+
+/// Restrict `dir` to 0700 and its [`PRIVATE_FILES`] to 0600.
+fn harden_dir(dir: &Path, report: &mut IsolationReport) {
+    harden_path(dir, 0o700, report);
+    for path in PRIVATE_FILES.iter().map(|name| dir.join(name)) {
+        if path.exists() {
+            harden_path(&path, 0o600, report);
+        }
+    }
+}
+
+fn harden_path(path: &Path, mode: u32, report: &mut IsolationReport) {
+    match restrict_permissions(path, mode) {
+        Ok(true) => report.hardened.push(path.to_string_lossy().to_string()),
+        Ok(false) => {}
+        Err(e) => report.warnings.push(format!(
+            "Could not restrict permissions on {}: {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+/// Run the isolation checks and tighten permissions in `data_dir`.
+pub fn check_and_harden(data_dir: &Path) -> IsolationReport {
+    let home = dirs::home_dir();
+    let mut report = IsolationReport {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    report
+        .warnings
+        .extend(location_warnings(data_dir, home.as_deref()));
+    report
+        .warnings
+        .extend(owner_warnings(data_dir, home.as_deref()));
+
+    harden_dir(data_dir, &mut report);
+    let vaults_dir = data_dir.join("vaults");
+    if vaults_dir.is_dir() {
+        harden_path(&vaults_dir, 0o700, &mut report);
+        if let Ok(entries) = std::fs::read_dir(&vaults_dir) {
+            for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+                harden_dir(&entry.path(), &mut report);
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_warnings() {
+        let home = Path::new("/home/alice");
+        assert!(location_warnings(
+            Path::new("/home/alice/.local/share/com.ghost.app"),
+            Some(home)
+        )
+        .is_empty());
+        assert_eq!(
+            location_warnings(Path::new("/media/usb/ghost/data"), Some(home)).len(),
+            1
+        );
+        assert!(
+            location_warnings(Path::new("//server/share/ghost"), Some(home))[0]
+                .contains("network path")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hardens_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("ghost_test_isolation_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let settings = dir.join("settings.json");
+        std::fs::write(&settings, "{}").unwrap();
+        std::fs::set_permissions(&settings, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let report = check_and_harden(&dir);
+        assert_eq!(report.hardened.len(), 2);
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(&settings), 0o600);
+
+        // Second run has nothing to do
+        assert!(check_and_harden(&dir).hardened.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hardens_other_vaults() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!(
+            "ghost_test_isolation_vaults_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let vault = crate::vaults::create(&dir, "Work").unwrap();
+        let vault_dir = crate::vaults::vault_dir(&dir, &vault.id);
+        std::fs::set_permissions(&vault_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let db = crate::vaults::db_path(&dir, &vault.id);
+        let wal = vault_dir.join("ghost_vault.db-wal");
+        for path in [&db, &wal] {
+            std::fs::write(path, "").unwrap();
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        check_and_harden(&dir);
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&vault_dir), 0o700);
+        assert_eq!(mode(&db), 0o600);
+        assert_eq!(mode(&wal), 0o600);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
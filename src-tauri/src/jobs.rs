@@ -0,0 +1,904 @@
+//! Background job scheduler.
+//!
+//! Long-running background work — directory indexing, periodic re-indexing,
+//! the re-embedding migration, MCP registry refresh, npm precache, vault
+//! maintenance, duplicate detection, knowledge graph extraction, digests, and
+//! browser, note vault and mail imports — is submitted here instead of being
+//! spawned ad hoc:
+//! - Jobs run in priority order (user-initiated work first), at most
+//!   [`MAX_CONCURRENT_JOBS`] at a time
+//! - Submitting a job identical to one already queued or running returns the
+//!   existing job instead of doing the work twice
+//! - Every job is recorded in the vault's `jobs` table; jobs interrupted by a
+//!   shutdown are queued again on the next launch
+//! - Indexing jobs can be paused; they stay paused across restarts until
+//!   resumed, and then skip the files already indexed
+//! - `list_jobs` / `cancel_job` / `pause_job` / `resume_job` expose the queue
+//!   to the frontend
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::indexer::progress::{RunControl, StopRequest};
+use crate::AppState;
+
+/// Jobs allowed to run at the same time.
+pub const MAX_CONCURRENT_JOBS: usize = 2;
+
+/// Scheduling priority. Higher priorities are started first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobPriority {
+    /// Maintenance and periodic safety nets.
+    Low,
+    /// Automatic work the user is waiting on (first-launch indexing).
+    Normal,
+    /// Work the user explicitly asked for.
+    High,
+}
+
+impl JobPriority {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+
+    fn from_name(name: &str) -> Self {
+        match name {
+            "high" => Self::High,
+            "normal" => Self::Normal,
+            _ => Self::Low,
+        }
+    }
+}
+
+/// What a job does. Persisted as JSON so interrupted jobs can be re-run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobSpec {
+    /// Index (or re-index) one directory.
+    IndexDirectory { path: String },
+    /// Re-scan every watched directory.
+    Reindex,
+    /// Continue the embedding-model migration (see [`crate::embeddings::migration`]).
+    Reembed,
+    /// Re-chunk and re-embed every document after the chunk settings change.
+    Rechunk,
+    /// Refresh the cached MCP Registry.
+    RegistrySync,
+    /// Pre-download npm packages for the default MCP tools.
+    Precache,
+    /// WAL checkpoint and query-planner statistics refresh.
+    Maintenance,
+    /// Periodic point-in-time snapshot (see [`crate::vault_snapshots`]).
+    VaultSnapshot,
+    /// Near-duplicate file detection (see [`crate::duplicates`]).
+    DetectDuplicates,
+    /// Entity and relation extraction (see [`crate::agent::knowledge`]).
+    BuildKnowledgeGraph,
+    /// "What's new in my files" digest (see [`crate::digest`]).
+    Digest,
+    /// Browser history and bookmarks import (see [`crate::indexer::importers::browser`]).
+    ImportBrowser,
+    /// Obsidian/Logseq vault or Notion export import (see
+    /// [`crate::indexer::importers::notes`]).
+    ImportNotes { path: String },
+    /// Outlook and Apple Mail import (`mail-import` feature). Known to every
+    /// build, so a persisted job still loads, and fails, without the feature.
+    ImportMail,
+}
+
+impl JobSpec {
+    /// Short human-readable description for the jobs list.
+    pub fn label(&self) -> String {
+        match self {
+            Self::IndexDirectory { path } => format!("Index {}", path),
+            Self::Reindex => "Re-index watched folders".into(),
+            Self::Reembed => "Re-embed vault".into(),
+            Self::Rechunk => "Re-chunk vault".into(),
+            Self::RegistrySync => "Sync MCP Registry".into(),
+            Self::Precache => "Pre-cache MCP tools".into(),
+            Self::Maintenance => "Vault maintenance".into(),
+            Self::VaultSnapshot => "Snapshot vault".into(),
+            Self::DetectDuplicates => "Find duplicate files".into(),
+            Self::BuildKnowledgeGraph => "Build knowledge graph".into(),
+            Self::Digest => "Write digest".into(),
+            Self::ImportBrowser => "Import browser history".into(),
+            Self::ImportNotes { path } => format!("Import notes from {}", path),
+            Self::ImportMail => "Import mail".into(),
+        }
+    }
+
+    /// Indexing jobs stop between files, so they can be paused and cancelled
+    /// without losing work.
+    fn is_indexing(&self) -> bool {
+        matches!(
+            self,
+            Self::IndexDirectory { .. } | Self::Reindex | Self::ImportNotes { .. }
+        )
+    }
+}
+
+/// A job as shown to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: i64,
+    pub label: String,
+    pub spec: JobSpec,
+    pub priority: JobPriority,
+    /// "queued", "running", "paused", "completed", "failed", or "cancelled".
+    pub status: String,
+    /// Result summary, or the error for failed jobs.
+    pub message: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+struct QueuedJob {
+    id: i64,
+    spec: JobSpec,
+    priority: JobPriority,
+}
+
+struct RunningJob {
+    spec: JobSpec,
+    handle: Option<tauri::async_runtime::JoinHandle<()>>,
+    control: Arc<RunControl>,
+    cancel_requested: bool,
+    pause_requested: bool,
+}
+
+#[derive(Default)]
+struct Queue {
+    queued: Vec<QueuedJob>,
+    running: HashMap<i64, RunningJob>,
+}
+
+/// In-memory run state of the scheduler (the job history lives in the vault).
+pub struct JobScheduler {
+    queue: Mutex<Queue>,
+    max_concurrent: usize,
+}
+
+impl JobScheduler {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            queue: Mutex::new(Queue::default()),
+            max_concurrent,
+        }
+    }
+
+    /// Jobs waiting and jobs running.
+    pub fn depth(&self) -> (usize, usize) {
+        let queue = self.lock();
+        (queue.queued.len(), queue.running.len())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for JobScheduler {
+    fn default() -> Self {
+        Self::new(MAX_CONCURRENT_JOBS)
+    }
+}
+
+/// Initialize the jobs table in the database.
+pub fn initialize_jobs_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                spec TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'queued',
+                message TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                started_at TEXT,
+                finished_at TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+fn insert_job(db: &Database, spec: &JobSpec, priority: JobPriority) -> Result<i64> {
+    let spec_json = serde_json::to_string(spec)?;
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO jobs (spec, priority) VALUES (?1, ?2)",
+            rusqlite::params![spec_json, priority.as_str()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+}
+
+fn set_status(db: &Database, id: i64, status: &str, message: Option<&str>) {
+    let result = db.with_conn(|conn| {
+        let timestamp = match status {
+            "queued" => "started_at = NULL",
+            "paused" => "finished_at = NULL",
+            "running" => "started_at = datetime('now')",
+            _ => "finished_at = datetime('now')",
+        };
+        let sql = format!(
+            "UPDATE jobs SET status = ?1, message = ?2, {} WHERE id = ?3",
+            timestamp
+        );
+        conn.execute(&sql, rusqlite::params![status, message, id])?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        tracing::warn!("Failed to update job {}: {}", id, e);
+    }
+}
+
+/// Most recent jobs, newest first.
+pub fn list_jobs(db: &Database, limit: usize) -> Result<Vec<JobInfo>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, spec, priority, status, message, created_at, started_at, finished_at
+             FROM jobs ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![limit as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })?;
+        let mut jobs = Vec::new();
+        for row in rows {
+            let (id, spec, priority, status, message, created_at, started_at, finished_at) = row?;
+            // Skip rows written by a newer version with job types we don't know
+            let Ok(spec) = serde_json::from_str::<JobSpec>(&spec) else {
+                continue;
+            };
+            jobs.push(JobInfo {
+                id,
+                label: spec.label(),
+                spec,
+                priority: JobPriority::from_name(&priority),
+                status,
+                message,
+                created_at,
+                started_at,
+                finished_at,
+            });
+        }
+        Ok(jobs)
+    })
+}
+
+fn get_job(db: &Database, id: i64) -> Result<Option<JobInfo>> {
+    // The history is short; paused jobs are looked up rarely
+    Ok(list_jobs(db, 1000)?.into_iter().find(|job| job.id == id))
+}
+
+/// Queue a job and start it if a slot is free. Returns the job ID.
+///
+/// If an identical job is already queued, running or paused, its ID is
+/// returned instead (and a queued one is bumped to the higher priority).
+/// A paused job stays paused until [`resume`] is called.
+pub fn submit(state: &Arc<AppState>, spec: JobSpec, priority: JobPriority) -> Result<i64> {
+    let id = {
+        let mut queue = state.jobs.lock();
+        if let Some((&id, _)) = queue.running.iter().find(|(_, job)| job.spec == spec) {
+            return Ok(id);
+        }
+        if let Some(job) = queue.queued.iter_mut().find(|job| job.spec == spec) {
+            job.priority = job.priority.max(priority);
+            return Ok(job.id);
+        }
+        if spec.is_indexing() {
+            let paused = list_jobs(&state.db, 1000)?
+                .into_iter()
+                .find(|job| job.status == "paused" && job.spec == spec);
+            if let Some(job) = paused {
+                return Ok(job.id);
+            }
+        }
+        let id = insert_job(&state.db, &spec, priority)?;
+        queue.queued.push(QueuedJob { id, spec, priority });
+        id
+    };
+    pump(state);
+    Ok(id)
+}
+
+/// Index of the next job to start: highest priority, oldest first.
+fn next_job(queued: &[QueuedJob]) -> Option<usize> {
+    queued
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(b.id.cmp(&a.id)))
+        .map(|(i, _)| i)
+}
+
+/// Start queued jobs while there are free slots.
+fn pump(state: &Arc<AppState>) {
+    let mut queue = state.jobs.lock();
+    while queue.running.len() < state.jobs.max_concurrent {
+        let Some(index) = next_job(&queue.queued) else {
+            break;
+        };
+        let QueuedJob { id, spec, .. } = queue.queued.remove(index);
+        set_status(&state.db, id, "running", None);
+        tracing::info!("Job {} started: {}", id, spec.label());
+
+        let control = Arc::new(RunControl::for_job(id));
+        queue.running.insert(
+            id,
+            RunningJob {
+                spec: spec.clone(),
+                handle: None,
+                control: control.clone(),
+                cancel_requested: false,
+                pause_requested: false,
+            },
+        );
+        let task_state = state.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            let result = execute(&task_state, &spec, &control).await;
+            finish(&task_state, id, result);
+        });
+        if let Some(running) = queue.running.get_mut(&id) {
+            running.handle = Some(handle);
+        }
+    }
+}
+
+/// Record the outcome of a job and start the next one.
+fn finish(state: &Arc<AppState>, id: i64, result: std::result::Result<String, String>) {
+    let (cancelled, paused) = {
+        let mut queue = state.jobs.lock();
+        match queue.running.remove(&id) {
+            Some(job) => (job.cancel_requested, job.pause_requested),
+            // Already recorded as cancelled by `cancel`
+            None => return,
+        }
+    };
+    match (cancelled, paused, result) {
+        (true, _, _) => set_status(&state.db, id, "cancelled", None),
+        (false, true, Ok(message)) => {
+            tracing::info!("Job {} paused: {}", id, message);
+            set_status(&state.db, id, "paused", Some(&message));
+        }
+        (false, _, Ok(message)) => {
+            tracing::info!("Job {} completed: {}", id, message);
+            set_status(&state.db, id, "completed", Some(&message));
+        }
+        (false, _, Err(e)) => {
+            tracing::warn!("Job {} failed: {}", id, e);
+            set_status(&state.db, id, "failed", Some(&e));
+        }
+    }
+    pump(state);
+}
+
+/// Cancel a queued, running or paused job.
+///
+/// Running jobs are aborted at their next await point, except indexing,
+/// which stops before its next file, and re-embedding, which is asked to
+/// pause after its current batch so progress is kept.
+pub fn cancel(state: &Arc<AppState>, id: i64) -> std::result::Result<(), String> {
+    let mut queue = state.jobs.lock();
+    if let Some(index) = queue.queued.iter().position(|job| job.id == id) {
+        queue.queued.remove(index);
+        drop(queue);
+        set_status(&state.db, id, "cancelled", None);
+        return Ok(());
+    }
+
+    let Some(job) = queue.running.get_mut(&id) else {
+        drop(queue);
+        return match get_job(&state.db, id).map_err(|e| e.to_string())? {
+            Some(job) if job.status == "paused" => {
+                set_status(&state.db, id, "cancelled", None);
+                Ok(())
+            }
+            _ => Err(format!("Job {} is not queued, running or paused", id)),
+        };
+    };
+    job.cancel_requested = true;
+    if job.spec.is_indexing() {
+        job.control.request(StopRequest::Cancel);
+        return Ok(());
+    }
+    if job.spec == JobSpec::Reembed {
+        state.reembed_job.request_pause();
+        return Ok(());
+    }
+    if let Some(handle) = queue.running.remove(&id).and_then(|job| job.handle) {
+        handle.abort();
+    }
+    drop(queue);
+    set_status(&state.db, id, "cancelled", None);
+    pump(state);
+    Ok(())
+}
+
+/// Pause a queued or running indexing job. A running job stops before its
+/// next file; the job stays paused, across restarts too, until [`resume`].
+pub fn pause(state: &Arc<AppState>, id: i64) -> std::result::Result<(), String> {
+    let mut queue = state.jobs.lock();
+    if let Some(index) = queue.queued.iter().position(|job| job.id == id) {
+        if !queue.queued[index].spec.is_indexing() {
+            return Err("Only indexing jobs can be paused".into());
+        }
+        queue.queued.remove(index);
+        drop(queue);
+        set_status(&state.db, id, "paused", None);
+        return Ok(());
+    }
+
+    let Some(job) = queue.running.get_mut(&id) else {
+        return Err(format!("Job {} is not queued or running", id));
+    };
+    if !job.spec.is_indexing() {
+        return Err("Only indexing jobs can be paused".into());
+    }
+    job.pause_requested = true;
+    job.control.request(StopRequest::Pause);
+    Ok(())
+}
+
+/// Queue a paused job again.
+pub fn resume(state: &Arc<AppState>, id: i64) -> std::result::Result<(), String> {
+    let job = get_job(&state.db, id)
+        .map_err(|e| e.to_string())?
+        .filter(|job| job.status == "paused")
+        .ok_or_else(|| format!("Job {} is not paused", id))?;
+    set_status(&state.db, id, "queued", None);
+    state.jobs.lock().queued.push(QueuedJob {
+        id,
+        spec: job.spec,
+        priority: job.priority,
+    });
+    pump(state);
+    Ok(())
+}
+
+/// Queue again the jobs that were queued or running when Ghost last exited.
+/// Paused jobs stay paused.
+pub fn resume_interrupted(state: &Arc<AppState>) -> Result<usize> {
+    let interrupted: Vec<JobInfo> = list_jobs(&state.db, 1000)?
+        .into_iter()
+        .filter(|job| job.status == "queued" || job.status == "running")
+        .collect();
+    let count = interrupted.len();
+    {
+        let mut queue = state.jobs.lock();
+        for job in interrupted.into_iter().rev() {
+            set_status(&state.db, job.id, "queued", None);
+            queue.queued.push(QueuedJob {
+                id: job.id,
+                spec: job.spec,
+                priority: job.priority,
+            });
+        }
+    }
+    pump(state);
+    Ok(count)
+}
+
+/// Run a job to completion. Returns a short summary for the jobs list.
+async fn execute(
+    state: &Arc<AppState>,
+    spec: &JobSpec,
+    control: &Arc<RunControl>,
+) -> std::result::Result<String, String> {
+    match spec {
+        JobSpec::IndexDirectory { path } => {
+            crate::push_log("info", format!("Indexing directory: {}", path));
+            let stats = crate::indexer::index_directory_with(
+                &state.db,
+                &state.embedding_engine,
+                &PathBuf::from(path),
+                control.clone(),
+            )
+            .await
+            .map_err(|e| {
+                crate::push_log("warn", format!("Failed to index {}: {}", path, e));
+                e.to_string()
+            })?;
+            let summary = format!(
+                "Indexed {}: {} files ({} ok, {} failed){}",
+                path,
+                stats.total,
+                stats.indexed,
+                stats.failed,
+                if stats.cancelled {
+                    ", cancelled"
+                } else if stats.paused {
+                    ", paused"
+                } else {
+                    ""
+                }
+            );
+            crate::push_log("info", summary.clone());
+            Ok(summary)
+        }
+
+        JobSpec::Reindex => {
+            let dirs = state
+                .settings
+                .lock()
+                .map(|s| s.watched_directories.clone())
+                .unwrap_or_default();
+            if dirs.is_empty() {
+                return Ok("No watched directories".into());
+            }
+            crate::push_log(
+                "info",
+                format!("Periodic re-index: scanning {} directories", dirs.len()),
+            );
+            for dir_path in &dirs {
+                if control.requested().is_some() {
+                    return Ok("Re-index stopped".into());
+                }
+                if let Err(e) = crate::indexer::index_directory_with(
+                    &state.db,
+                    &state.embedding_engine,
+                    &PathBuf::from(dir_path),
+                    control.clone(),
+                )
+                .await
+                {
+                    tracing::warn!("Periodic re-index failed for {}: {}", dir_path, e);
+                }
+            }
+            let stats = state.db.get_stats().map_err(|e| e.to_string())?;
+            let summary = format!(
+                "Re-index complete: {} docs, {} chunks",
+                stats.document_count, stats.chunk_count
+            );
+            crate::push_log("info", summary.clone());
+            Ok(summary)
+        }
+
+        JobSpec::Reembed => {
+            use crate::embeddings::migration::ReembedOutcome;
+
+            match state
+                .reembed_job
+                .run(&state.db, &state.embedding_engine)
+                .await
+            {
+                Ok(ReembedOutcome::Completed(model)) => {
+                    let summary = format!("Re-embedding complete — now using {}", model.name);
+                    crate::push_log("info", summary.clone());
+                    let mut settings = state.settings.lock().unwrap_or_else(|e| e.into_inner());
+                    settings.embedding_backend = model.backend().to_string();
+                    settings.embedding_model = model.id.to_string();
+                    if let Err(e) = settings.save(&crate::get_settings_path()) {
+                        tracing::warn!("Failed to save embedding backend: {}", e);
+                    }
+                    Ok(summary)
+                }
+                Ok(ReembedOutcome::Paused) => {
+                    crate::push_log("info", "Re-embedding paused".to_string());
+                    Ok("Re-embedding paused".into())
+                }
+                Err(e) => {
+                    crate::push_log("warn", format!("Re-embedding failed: {}", e));
+                    Err(e.to_string())
+                }
+            }
+        }
+
+        JobSpec::Rechunk => {
+            let config = crate::indexer::chunker::current();
+            crate::push_log(
+                "info",
+                format!(
+                    "Re-chunking vault ({} tokens, {} overlap)",
+                    config.chunk_size, config.overlap
+                ),
+            );
+            let stats = crate::indexer::rechunk_all(&state.db, &state.embedding_engine)
+                .await
+                .map_err(|e| e.to_string())?;
+            let summary = format!(
+                "Re-chunked {} documents ({} failed)",
+                stats.indexed, stats.failed
+            );
+            crate::push_log("info", summary.clone());
+            Ok(summary)
+        }
+
+        JobSpec::RegistrySync => {
+            let cache_dir = crate::get_app_data_dir();
+            let result = crate::protocols::mcp_catalog::sync_registry(&cache_dir).await;
+            match result.error {
+                Some(e) if !result.success => Err(e),
+                _ => Ok(format!(
+                    "{} servers ({} installable)",
+                    result.total_servers, result.installable_count
+                )),
+            }
+        }
+
+        JobSpec::Precache => {
+            crate::protocols::mcp_catalog::precache_npm_packages().await;
+            Ok("npm packages cached".into())
+        }
+
+        JobSpec::Maintenance => {
+            state.db.checkpoint().map_err(|e| e.to_string())?;
+            state
+                .db
+                .with_conn(|conn| {
+                    conn.execute_batch("PRAGMA optimize")?;
+                    Ok(())
+                })
+                .map_err(|e| e.to_string())?;
+            Ok("WAL checkpointed, statistics refreshed".into())
+        }
+
+        JobSpec::VaultSnapshot => {
+            let keep = state
+                .settings
+                .lock()
+                .map_err(|e| e.to_string())?
+                .vault_snapshot_keep;
+            let snapshot = crate::vault_snapshots::create_snapshot(&state.db, None, keep)
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "Snapshot {} ({} documents)",
+                snapshot.id, snapshot.document_count
+            ))
+        }
+
+        JobSpec::DetectDuplicates => {
+            let summary = crate::duplicates::scan(&state.db).map_err(|e| e.to_string())?;
+            Ok(format!(
+                "{} duplicate groups in {} documents",
+                summary.group_count, summary.documents_scanned
+            ))
+        }
+
+        JobSpec::BuildKnowledgeGraph => {
+            let stats = crate::agent::knowledge::build(state)
+                .await
+                .map_err(|e| e.to_string())?;
+            let summary = format!(
+                "Knowledge graph: {} entities, {} relations",
+                stats.entity_count, stats.edge_count
+            );
+            crate::push_log("info", summary.clone());
+            Ok(summary)
+        }
+
+        JobSpec::Digest => {
+            let digest = crate::digest::generate(state)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "{} ({} new files)",
+                digest.title, digest.document_count
+            ))
+        }
+
+        JobSpec::ImportBrowser => {
+            let settings = state
+                .settings
+                .lock()
+                .map_err(|e| e.to_string())?
+                .browser_import
+                .clone();
+            let stats = crate::indexer::importers::browser::import(
+                &state.db,
+                &state.embedding_engine,
+                &settings,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "Browser import: {} pages updated, {} unchanged, {} removed",
+                stats.imported, stats.unchanged, stats.removed
+            ))
+        }
+
+        JobSpec::ImportNotes { path } => {
+            let import = crate::indexer::importers::notes::import_vault(
+                &state.db,
+                &state.embedding_engine,
+                &PathBuf::from(path),
+                control.clone(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            let summary = format!(
+                "Notes import: {} notes, {} links",
+                import.notes, import.links
+            );
+            crate::push_log("info", summary.clone());
+            Ok(summary)
+        }
+
+        #[cfg(feature = "mail-import")]
+        JobSpec::ImportMail => {
+            let settings = state
+                .settings
+                .lock()
+                .map_err(|e| e.to_string())?
+                .mail_import
+                .clone();
+            let import = crate::indexer::importers::mail::import(
+                &state.db,
+                &state.embedding_engine,
+                &settings,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            let summary = format!(
+                "Mail import: {} messages updated, {} unchanged, {} duplicates, {} removed",
+                import.stats.imported,
+                import.stats.unchanged,
+                import.duplicates,
+                import.stats.removed
+            );
+            crate::push_log("info", summary.clone());
+            Ok(summary)
+        }
+
+        #[cfg(not(feature = "mail-import"))]
+        JobSpec::ImportMail => Err("This build of Ghost can't import mail".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// App state whose scheduler never starts jobs, so the queue can be inspected.
+    fn paused_state() -> Arc<AppState> {
+        let state = crate::test_app_state();
+        initialize_jobs_schema(&state.db).unwrap();
+        // Replace the scheduler's slots: zero means nothing is ever started
+        let state = Arc::try_unwrap(state).unwrap_or_else(|_| unreachable!());
+        Arc::new(AppState {
+            jobs: JobScheduler::new(0),
+            ..state
+        })
+    }
+
+    #[test]
+    fn test_next_job_priority_then_fifo() {
+        let queued = vec![
+            QueuedJob {
+                id: 1,
+                spec: JobSpec::Maintenance,
+                priority: JobPriority::Low,
+            },
+            QueuedJob {
+                id: 2,
+                spec: JobSpec::Reindex,
+                priority: JobPriority::Normal,
+            },
+            QueuedJob {
+                id: 3,
+                spec: JobSpec::Precache,
+                priority: JobPriority::Normal,
+            },
+        ];
+        assert_eq!(next_job(&queued), Some(1));
+        assert_eq!(next_job(&queued[..1]), Some(0));
+        assert_eq!(next_job(&[]), None);
+    }
+
+    #[test]
+    fn test_submit_dedupes_and_cancels() {
+        let state = paused_state();
+        let index = JobSpec::IndexDirectory {
+            path: "/home/user/docs".into(),
+        };
+        let first = submit(&state, index.clone(), JobPriority::Normal).unwrap();
+        let maintenance = submit(&state, JobSpec::Maintenance, JobPriority::Low).unwrap();
+        assert_ne!(first, maintenance);
+        // Same spec again: same job, bumped priority
+        assert_eq!(submit(&state, index, JobPriority::High).unwrap(), first);
+
+        let jobs = list_jobs(&state.db, 10).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.iter().all(|j| j.status == "queued"));
+        assert_eq!(state.jobs.lock().queued[0].priority, JobPriority::High);
+
+        cancel(&state, maintenance).unwrap();
+        let jobs = list_jobs(&state.db, 10).unwrap();
+        assert_eq!(jobs[0].status, "cancelled");
+        assert!(jobs[0].finished_at.is_some());
+        assert!(cancel(&state, maintenance).is_err());
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let state = paused_state();
+        let index = JobSpec::IndexDirectory {
+            path: "/home/user/archive".into(),
+        };
+        let id = submit(&state, index.clone(), JobPriority::Normal).unwrap();
+        let maintenance = submit(&state, JobSpec::Maintenance, JobPriority::Low).unwrap();
+        assert!(pause(&state, maintenance).is_err());
+
+        pause(&state, id).unwrap();
+        assert_eq!(list_jobs(&state.db, 10).unwrap()[1].status, "paused");
+        // Resubmitting (e.g. the watcher) doesn't restart a paused job
+        assert_eq!(submit(&state, index, JobPriority::High).unwrap(), id);
+        assert_eq!(state.jobs.lock().queued.len(), 1);
+        // After a restart only the maintenance job is queued again
+        state.jobs.lock().queued.clear();
+        assert_eq!(resume_interrupted(&state).unwrap(), 1);
+        assert_eq!(state.jobs.lock().queued[0].id, maintenance);
+
+        resume(&state, id).unwrap();
+        assert!(resume(&state, id).is_err());
+        let queue = state.jobs.lock();
+        assert!(queue.queued.iter().any(|job| job.id == id));
+    }
+
+    #[test]
+    fn test_resume_interrupted() {
+        let state = paused_state();
+        let id = insert_job(&state.db, &JobSpec::Reindex, JobPriority::Low).unwrap();
+        set_status(&state.db, id, "running", None);
+        let done = insert_job(&state.db, &JobSpec::Precache, JobPriority::Low).unwrap();
+        set_status(&state.db, done, "completed", Some("ok"));
+
+        assert_eq!(resume_interrupted(&state).unwrap(), 1);
+        let queue = state.jobs.lock();
+        assert_eq!(queue.queued.len(), 1);
+        assert_eq!(queue.queued[0].spec, JobSpec::Reindex);
+    }
+
+    #[test]
+    fn test_mail_import_job_loads_in_every_build() {
+        let state = paused_state();
+        let id = insert_job(&state.db, &JobSpec::ImportMail, JobPriority::Low).unwrap();
+        let job = get_job(&state.db, id).unwrap().unwrap();
+        assert_eq!(job.spec, JobSpec::ImportMail);
+        assert_eq!(
+            serde_json::to_value(&job.spec).unwrap()["type"],
+            "import_mail"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_job_runs_to_completion() {
+        let state = crate::test_app_state();
+        initialize_jobs_schema(&state.db).unwrap();
+        let id = submit(&state, JobSpec::Maintenance, JobPriority::Low).unwrap();
+
+        for _ in 0..100 {
+            let job = list_jobs(&state.db, 1).unwrap().remove(0);
+            if job.status == "completed" {
+                assert_eq!(job.id, id);
+                assert!(job.message.is_some());
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("Maintenance job did not complete");
+    }
+}
@@ -1,13 +1,34 @@
 mod agent;
+mod analytics;
 mod chat;
+mod crash;
 mod db;
+mod digest;
+mod downloads;
+mod duplicates;
 mod embeddings;
 mod error;
 mod extensions;
+mod health;
+mod help;
 mod indexer;
+mod isolation;
+mod jobs;
+/// Benchmark workloads, public for `benches/search.rs`.
+pub mod perf;
 mod protocols;
+mod reminders;
+mod resources;
 mod search;
+mod secrets;
+mod session;
 mod settings;
+mod snapshot;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+mod vault_snapshots;
+mod vaults;
+mod wasm_plugins;
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -29,6 +50,7 @@ pub(crate) fn ensure_tls_provider() {
 use db::Database;
 use embeddings::hardware::HardwareInfo;
 use embeddings::{AiStatus, EmbeddingEngine};
+use error::CommandResult;
 use search::SearchResult;
 use settings::Settings;
 
@@ -46,6 +68,8 @@ use tauri::Manager;
 
 /// Application state shared across commands.
 pub struct AppState {
+    /// The active vault's database. Switching vaults swaps its connections
+    /// in place (see [`vaults`]).
     pub db: Database,
     pub embedding_engine: EmbeddingEngine,
     pub chat_engine: chat::ChatEngine,
@@ -53,6 +77,40 @@ pub struct AppState {
     pub settings: std::sync::Mutex<Settings>,
     pub mcp_client: protocols::mcp_client::McpClientManager,
     pub agui_event_bus: protocols::agui::AgUiEventBus,
+    pub tool_approvals: agent::approval::ApprovalGate,
+    pub a2a_tasks: protocols::a2a::TaskStore,
+    pub reembed_job: embeddings::migration::ReembedJob,
+    pub jobs: jobs::JobScheduler,
+}
+
+/// Build a minimal in-memory AppState for unit tests.
+#[cfg(test)]
+pub(crate) fn test_app_state() -> Arc<AppState> {
+    ensure_tls_provider();
+    let db = Database::open_in_memory().unwrap();
+    let hardware = HardwareInfo {
+        cpu_cores: 4,
+        has_avx2: false,
+        has_neon: false,
+        gpu_backend: None,
+        total_ram_mb: 8192,
+        available_ram_mb: 4096,
+    };
+    let chat_engine = chat::ChatEngine::new(hardware.clone(), "qwen2.5-0.5b".into());
+
+    Arc::new(AppState {
+        db,
+        embedding_engine: EmbeddingEngine::none(),
+        chat_engine,
+        hardware,
+        settings: std::sync::Mutex::new(Settings::default()),
+        mcp_client: protocols::mcp_client::McpClientManager::new(),
+        agui_event_bus: protocols::agui::AgUiEventBus::new(32),
+        tool_approvals: agent::approval::ApprovalGate::new(),
+        a2a_tasks: protocols::a2a::TaskStore::new(),
+        reembed_job: embeddings::migration::ReembedJob::new(),
+        jobs: jobs::JobScheduler::default(),
+    })
 }
 
 /// A structured log entry for the debug panel.
@@ -85,6 +143,18 @@ fn push_log(level: &str, message: String) {
     }
 }
 
+/// The last `count` log entries as lines, for crash reports. Never blocks:
+/// returns nothing if the buffer is locked (e.g. by the panicking thread).
+pub(crate) fn recent_log_lines(count: usize) -> Vec<String> {
+    let Ok(logs) = LOG_BUFFER.try_lock() else {
+        return Vec::new();
+    };
+    logs.iter()
+        .skip(logs.len().saturating_sub(count))
+        .map(|entry| format!("{} [{}] {}", entry.timestamp, entry.level, entry.message))
+        .collect()
+}
+
 /// Get the app data directory.
 pub(crate) fn get_app_data_dir() -> PathBuf {
     dirs::data_dir()
@@ -92,9 +162,171 @@ pub(crate) fn get_app_data_dir() -> PathBuf {
         .join("com.ghost.app")
 }
 
-/// Get the default vault database path.
+/// Get the active vault's database path.
 fn get_db_path() -> PathBuf {
-    get_app_data_dir().join("ghost_vault.db")
+    vaults::db_path(&get_app_data_dir(), &vaults::active_id())
+}
+
+/// Get the active vault's settings path.
+pub(crate) fn get_settings_path() -> PathBuf {
+    vaults::settings_path(&get_app_data_dir(), &vaults::active_id())
+}
+
+/// Where the UI session (last conversation, window geometry) is saved.
+fn get_session_path() -> PathBuf {
+    vaults::session_path(&get_app_data_dir(), &vaults::active_id())
+}
+
+/// Push settings into the modules that keep their own copy.
+fn apply_settings(settings: &Settings) {
+    downloads::manager().configure(&settings.downloads);
+    indexer::throttle::configure(settings.indexing_throttle);
+    indexer::plugins::configure(&settings.extractor_plugins);
+    indexer::chunker::configure(settings.chunk_config());
+    search::ranking::configure(settings.ranking);
+    chat::remote::configure(&settings.remote_llm);
+    chat::openai_compat::configure(&settings.local_llm_server);
+    chat::models::register_custom_models(&settings.custom_models);
+    protocols::mcp_server::configure_auth(&settings.mcp_server);
+    agent::skills::configure(&settings.agent_config.skills_dir);
+}
+
+/// Share the watched directories with connected MCP servers as roots.
+async fn sync_mcp_roots(state: &AppState) {
+    let dirs = match state.settings.lock() {
+        Ok(settings) => settings.watched_directories.clone(),
+        Err(_) => return,
+    };
+    if protocols::mcp_roots::configure(&dirs) {
+        state.mcp_client.notify_roots_changed().await;
+    }
+}
+
+/// Create the tables kept next to the core schema (memory, jobs, history,
+/// caches...) in a freshly opened vault database. Failures are logged; the
+/// features depending on a table degrade on their own.
+fn initialize_vault_schemas(db: &Database) {
+    // Initialize conversation memory tables
+    if let Err(e) = agent::memory::initialize_memory_schema(db) {
+        tracing::warn!("Failed to initialize conversation memory schema: {}", e);
+        push_log("warn", format!("Memory schema init failed: {}", e));
+    } else {
+        push_log("info", "Conversation memory schema initialized".to_string());
+    }
+
+    // Initialize the agent command audit log
+    if let Err(e) = agent::audit::initialize_audit_schema(db) {
+        tracing::warn!("Failed to initialize command audit schema: {}", e);
+        push_log("warn", format!("Audit schema init failed: {}", e));
+    }
+
+    // Initialize the MCP tool call history
+    if let Err(e) = protocols::mcp_trace::initialize_trace_schema(db) {
+        tracing::warn!("Failed to initialize tool call history schema: {}", e);
+        push_log("warn", format!("Tool call history init failed: {}", e));
+    }
+
+    // Initialize the background job history
+    if let Err(e) = jobs::initialize_jobs_schema(db) {
+        tracing::warn!("Failed to initialize jobs schema: {}", e);
+        push_log("warn", format!("Jobs schema init failed: {}", e));
+    }
+
+    // Initialize the local search history
+    if let Err(e) = search::history::initialize_history_schema(db) {
+        tracing::warn!("Failed to initialize query history schema: {}", e);
+        push_log("warn", format!("Query history schema init failed: {}", e));
+    }
+
+    // Initialize the filename index for instant search
+    if let Err(e) = search::filenames::initialize_filename_schema(db) {
+        tracing::warn!("Failed to initialize filename index: {}", e);
+        push_log("warn", format!("Filename index init failed: {}", e));
+    }
+
+    // Initialize the chunk embedding cache
+    if let Err(e) = embeddings::cache::initialize_embedding_cache_schema(db) {
+        tracing::warn!("Failed to initialize embedding cache schema: {}", e);
+        push_log("warn", format!("Embedding cache schema init failed: {}", e));
+    }
+
+    // Initialize local usage analytics tables
+    if let Err(e) = analytics::initialize_usage_schema(db) {
+        tracing::warn!("Failed to initialize usage analytics schema: {}", e);
+        push_log("warn", format!("Usage analytics schema init failed: {}", e));
+    }
+
+    // Initialize the opt-in telemetry report log
+    #[cfg(feature = "telemetry")]
+    if let Err(e) = telemetry::initialize_telemetry_schema(db) {
+        tracing::warn!("Failed to initialize telemetry schema: {}", e);
+        push_log("warn", format!("Telemetry schema init failed: {}", e));
+    }
+
+    // Initialize reminders extracted from notes and chats
+    if let Err(e) = reminders::initialize_reminders_schema(db) {
+        tracing::warn!("Failed to initialize reminders schema: {}", e);
+        push_log("warn", format!("Reminders schema init failed: {}", e));
+    }
+
+    // Initialize point-in-time vault snapshots
+    if let Err(e) = vault_snapshots::initialize_vault_snapshots_schema(db) {
+        tracing::warn!("Failed to initialize vault snapshots schema: {}", e);
+        push_log("warn", format!("Vault snapshots schema init failed: {}", e));
+    }
+
+    // Initialize the knowledge graph
+    if let Err(e) = agent::knowledge::initialize_knowledge_schema(db) {
+        tracing::warn!("Failed to initialize knowledge graph schema: {}", e);
+        push_log("warn", format!("Knowledge graph schema init failed: {}", e));
+    }
+
+    // Initialize the document summary cache
+    if let Err(e) = agent::summarize::initialize_summaries_schema(db) {
+        tracing::warn!("Failed to initialize summaries schema: {}", e);
+        push_log("warn", format!("Summaries schema init failed: {}", e));
+    }
+
+    // Initialize digest history
+    if let Err(e) = digest::initialize_digest_schema(db) {
+        tracing::warn!("Failed to initialize digest schema: {}", e);
+        push_log("warn", format!("Digest schema init failed: {}", e));
+    }
+
+    // Initialize near-duplicate detection
+    if let Err(e) = duplicates::initialize_duplicates_schema(db) {
+        tracing::warn!("Failed to initialize duplicates schema: {}", e);
+        push_log("warn", format!("Duplicates schema init failed: {}", e));
+    }
+
+    // Initialize note vault links
+    if let Err(e) = indexer::importers::notes::initialize_notes_schema(db) {
+        tracing::warn!("Failed to initialize notes schema: {}", e);
+        push_log("warn", format!("Notes schema init failed: {}", e));
+    }
+}
+
+/// Open a vault database and create its extra tables. Encrypted vaults need
+/// their passphrase. Blocking: key derivation and schema setup take a while.
+fn open_vault_db(path: &PathBuf, passphrase: Option<&str>) -> error::Result<Database> {
+    let key = if db::encryption::is_encrypted(path) {
+        let passphrase = passphrase.ok_or(error::GhostError::VaultLocked)?;
+        Some(db::encryption::unlock_key(path, passphrase)?)
+    } else {
+        None
+    };
+    let db = Database::open_with_key(path, key.as_ref())?;
+    initialize_vault_schemas(&db);
+    Ok(db)
+}
+
+/// Re-queue background jobs interrupted by the active vault's last session.
+fn resume_vault_jobs(state: &Arc<AppState>) {
+    match jobs::resume_interrupted(state) {
+        Ok(0) => {}
+        Ok(n) => push_log("info", format!("Resumed {} interrupted background jobs", n)),
+        Err(e) => tracing::warn!("Failed to resume background jobs: {}", e),
+    }
 }
 
 // --- Window Management ---
@@ -113,10 +345,10 @@ fn toggle_window(app: &tauri::AppHandle) {
 }
 
 #[tauri::command]
-async fn hide_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn hide_window(app: tauri::AppHandle) -> CommandResult<()> {
     #[cfg(desktop)]
     if let Some(window) = app.get_webview_window("main") {
-        window.hide().map_err(|e| e.to_string())?;
+        window.hide()?;
     }
     #[cfg(not(desktop))]
     let _ = &app; // suppress unused warning
@@ -124,11 +356,11 @@ async fn hide_window(app: tauri::AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn show_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn show_window(app: tauri::AppHandle) -> CommandResult<()> {
     #[cfg(desktop)]
     if let Some(window) = app.get_webview_window("main") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+        window.show()?;
+        window.set_focus()?;
     }
     #[cfg(not(desktop))]
     let _ = &app; // suppress unused warning
@@ -138,10 +370,10 @@ async fn show_window(app: tauri::AppHandle) -> Result<(), String> {
 /// Programmatic window drag — fallback for data-tauri-drag-region issues on Linux.
 /// No-op on mobile.
 #[tauri::command]
-async fn start_dragging(app: tauri::AppHandle) -> Result<(), String> {
+async fn start_dragging(app: tauri::AppHandle) -> CommandResult<()> {
     #[cfg(desktop)]
     if let Some(window) = app.get_webview_window("main") {
-        window.start_dragging().map_err(|e| e.to_string())?;
+        window.start_dragging()?;
     }
     #[cfg(not(desktop))]
     let _ = &app; // suppress unused warning
@@ -150,10 +382,10 @@ async fn start_dragging(app: tauri::AppHandle) -> Result<(), String> {
 
 /// Minimize the main window.
 #[tauri::command]
-async fn minimize_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn minimize_window(app: tauri::AppHandle) -> CommandResult<()> {
     #[cfg(desktop)]
     if let Some(window) = app.get_webview_window("main") {
-        window.minimize().map_err(|e| e.to_string())?;
+        window.minimize()?;
     }
     #[cfg(not(desktop))]
     let _ = &app;
@@ -162,13 +394,13 @@ async fn minimize_window(app: tauri::AppHandle) -> Result<(), String> {
 
 /// Toggle maximize / restore the main window.
 #[tauri::command]
-async fn toggle_maximize_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn toggle_maximize_window(app: tauri::AppHandle) -> CommandResult<()> {
     #[cfg(desktop)]
     if let Some(window) = app.get_webview_window("main") {
         if window.is_maximized().unwrap_or(false) {
-            window.unmaximize().map_err(|e| e.to_string())?;
+            window.unmaximize()?;
         } else {
-            window.maximize().map_err(|e| e.to_string())?;
+            window.maximize()?;
         }
     }
     #[cfg(not(desktop))]
@@ -178,22 +410,67 @@ async fn toggle_maximize_window(app: tauri::AppHandle) -> Result<(), String> {
 
 /// Close the main window (exit the app).
 #[tauri::command]
-async fn close_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn close_window(app: tauri::AppHandle) -> CommandResult<()> {
     #[cfg(desktop)]
     if let Some(window) = app.get_webview_window("main") {
-        window.close().map_err(|e| e.to_string())?;
+        window.close()?;
     }
     #[cfg(not(desktop))]
     let _ = &app;
     Ok(())
 }
 
+// --- Session Restore ---
+
+/// The saved session (last conversation, last search, window geometry) and
+/// the background jobs still queued or running.
+#[tauri::command]
+async fn get_session_state(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<session::RestoredSession> {
+    let mut saved = session::SessionState::load(&get_session_path());
+    // Forget a conversation deleted since
+    if let Some(id) = saved.last_conversation_id {
+        let exists = agent::memory::get_conversation(&state.db, id)?.is_some();
+        if !exists {
+            saved.last_conversation_id = None;
+        }
+    }
+    let active_jobs = jobs::list_jobs(&state.db, 100)?
+        .into_iter()
+        .filter(|job| job.status == "queued" || job.status == "running")
+        .collect();
+    Ok(session::RestoredSession {
+        state: saved,
+        active_jobs,
+    })
+}
+
+/// Save the active conversation and last search query; the main window's
+/// geometry is captured alongside.
+#[tauri::command]
+async fn save_session_state(
+    session: session::SessionState,
+    app: tauri::AppHandle,
+) -> CommandResult<()> {
+    let path = get_session_path();
+    let mut saved = session::SessionState::load(&path);
+    saved.merge(session);
+    #[cfg(desktop)]
+    if let Some(window) = session::capture_window(&app) {
+        saved.window = Some(window);
+    }
+    #[cfg(not(desktop))]
+    let _ = &app;
+    Ok(saved.save(&path)?)
+}
+
 // --- Default Directories ---
 
 /// Get default user directories for auto-indexing (zero-config).
 /// Follows how Spotlight/Alfred/Everything auto-detect user content directories.
 #[tauri::command]
-async fn get_default_directories() -> Result<Vec<String>, String> {
+async fn get_default_directories() -> CommandResult<Vec<String>> {
     let mut found_dirs = Vec::new();
 
     let mut try_add = |path: std::path::PathBuf| {
@@ -249,74 +526,362 @@ async fn search_query(
     query: String,
     limit: Option<usize>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<SearchResult>, String> {
+) -> CommandResult<Vec<SearchResult>> {
     let limit = limit.unwrap_or(20);
-    search::hybrid_search(&state.db, &state.embedding_engine, &query, limit)
-        .await
-        .map_err(|e| e.to_string())
+    analytics::track(&state.db, analytics::UsageFeature::Search, None);
+    let results = search::hybrid_search(&state.db, &state.embedding_engine, &query, limit).await?;
+    let record_history = state
+        .settings
+        .lock()
+        .map(|s| s.search_history_enabled)
+        .unwrap_or(false);
+    if record_history {
+        search::history::track(&state.db, &query, results.len());
+    }
+    Ok(results)
+}
+
+/// Hybrid search that streams results: keyword hits are emitted on
+/// `search://results` as soon as FTS5 finishes, followed by the final hybrid
+/// ranking (also returned). `search_id` is echoed so stale updates can be dropped.
+#[tauri::command]
+async fn search_query_stream(
+    query: String,
+    limit: Option<usize>,
+    search_id: u64,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<Vec<SearchResult>> {
+    let limit = limit.unwrap_or(20);
+    analytics::track(&state.db, analytics::UsageFeature::Search, None);
+    let emit = |stage, results| {
+        let update = search::SearchUpdate {
+            search_id,
+            stage,
+            results,
+        };
+        let _ = app.emit(search::SEARCH_RESULTS_EVENT, &update);
+    };
+    let results = search::hybrid_search_progressive(
+        &state.db,
+        &state.embedding_engine,
+        &query,
+        limit,
+        |keyword| emit(search::SearchStage::Keyword, keyword),
+    )
+    .await?;
+    emit(search::SearchStage::Final, results.clone());
+    let record_history = state
+        .settings
+        .lock()
+        .map(|s| s.search_history_enabled)
+        .unwrap_or(false);
+    if record_history {
+        search::history::track(&state.db, &query, results.len());
+    }
+    Ok(results)
+}
+
+/// Instant filename search for the quick launcher. Uses only the filename
+/// index (no chunks, no embeddings), so it answers before hybrid search does.
+#[tauri::command]
+async fn search_filenames(
+    query: String,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<search::filenames::FilenameMatch>> {
+    Ok(search::filenames::search_filenames(
+        &state.db,
+        &query,
+        limit.unwrap_or(search::filenames::DEFAULT_LIMIT),
+    )?)
+}
+
+/// Most recent distinct searches, newest first.
+#[tauri::command]
+async fn get_recent_queries(
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<search::history::QueryStats>> {
+    Ok(search::history::recent(&state.db, limit.unwrap_or(10))?)
+}
+
+/// Most frequent searches over the last `days` days (all time by default).
+#[tauri::command]
+async fn get_frequent_queries(
+    limit: Option<usize>,
+    days: Option<u32>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<search::history::QueryStats>> {
+    Ok(search::history::frequent(
+        &state.db,
+        limit.unwrap_or(10),
+        days,
+    )?)
+}
+
+/// Earlier searches starting with `prefix`, most used first.
+#[tauri::command]
+async fn get_query_suggestions(
+    prefix: String,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<String>> {
+    Ok(search::history::suggest(
+        &state.db,
+        &prefix,
+        limit.unwrap_or(5),
+    )?)
+}
+
+/// Delete the recorded search history.
+#[tauri::command]
+async fn clear_query_history(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    push_log("info", "Search history cleared".to_string());
+    Ok(search::history::clear(&state.db)?)
+}
+
+/// Scope all searches — the search bar and the agent — to some folders, tags,
+/// or dates until cleared or `duration_minutes` pass.
+#[tauri::command]
+async fn set_focus_context(
+    request: search::focus::FocusRequest,
+) -> CommandResult<search::focus::FocusContext> {
+    let focus = search::focus::set(request)?;
+    push_log("info", format!("Focus set: {}", focus.describe()));
+    Ok(focus)
+}
+
+/// The focus in effect, if any.
+#[tauri::command]
+async fn get_focus_context() -> CommandResult<Option<search::focus::FocusContext>> {
+    Ok(search::focus::active())
+}
+
+/// End the focus; searches cover everything again.
+#[tauri::command]
+async fn clear_focus_context() -> CommandResult<Option<search::focus::FocusContext>> {
+    let cleared = search::focus::clear();
+    if cleared.is_some() {
+        push_log("info", "Focus cleared".to_string());
+    }
+    Ok(cleared)
+}
+
+/// Largest file scanned for the line of an opened result.
+const MAX_LOCATE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Open the file of a search result with its default app (web pages from
+/// importers in the browser) and record the click-through for ranking.
+/// Returns the line the result starts on, when the file is text.
+#[tauri::command]
+async fn open_search_result(
+    chunk_id: i64,
+    query: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<search::feedback::OpenedResult> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let chunk = state
+        .db
+        .get_chunk_with_document(chunk_id)?
+        .ok_or("This result is no longer in the index")?;
+    // Web pages, and imported mail where the mail app has URLs, open by URL
+    let url = if indexer::importers::is_web_url(&chunk.path) {
+        Some(chunk.path.clone())
+    } else if indexer::importers::is_message_path(&chunk.path) {
+        Some(
+            indexer::importers::message_url(&chunk.path)
+                .ok_or("This email was imported from a mail store and can't be opened here")?,
+        )
+    } else {
+        None
+    };
+    let web = url.is_some();
+    let small = web
+        || tokio::fs::metadata(&chunk.path)
+            .await
+            .map(|m| m.len() <= MAX_LOCATE_BYTES)
+            .map_err(|e| format!("Cannot open {}: {}", chunk.path, e))?;
+    let line = if web {
+        None
+    } else if small {
+        tokio::fs::read_to_string(&chunk.path)
+            .await
+            .ok()
+            .and_then(|text| search::feedback::locate_line(&text, &chunk.content))
+    } else {
+        None
+    };
+
+    if let Some(url) = &url {
+        app.opener().open_url(url, None::<&str>)
+    } else {
+        app.opener().open_path(&chunk.path, None::<&str>)
+    }?;
+    if let Err(e) = search::feedback::record_open(
+        &state.db,
+        chunk.document_id,
+        Some(chunk_id),
+        query.as_deref(),
+    ) {
+        tracing::debug!("Result open not recorded: {}", e);
+    }
+    Ok(search::feedback::OpenedResult {
+        document_id: chunk.document_id,
+        path: chunk.path,
+        line,
+    })
+}
+
+/// Show an indexed file (or a file in a watched folder) in the system file
+/// manager. Indexed files count as opened for ranking.
+#[tauri::command]
+async fn reveal_in_file_manager(
+    path: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let document = state.db.get_document_by_path(&path)?;
+    if document.is_none() {
+        let roots = state.settings.lock()?.watched_directories.clone();
+        agent::safety::resolve_sandboxed_path(&path, &roots)?;
+    }
+    app.opener().reveal_item_in_dir(&path)?;
+    if let Some((document_id, _)) = document {
+        if let Err(e) = search::feedback::record_open(&state.db, document_id, None, None) {
+            tracing::debug!("Result open not recorded: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Every document matching a topic and/or type and year, grouped by year,
+/// type, or folder — for enumeration questions answered as tables.
+#[tauri::command]
+async fn collect_documents(
+    request: search::aggregate::CollectionRequest,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<search::aggregate::Collection> {
+    Ok(search::aggregate::collect(&state.db, &state.embedding_engine, &request).await?)
 }
 
 #[tauri::command]
 async fn index_directory(
     path: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<indexer::IndexStats, String> {
+) -> CommandResult<indexer::IndexStats> {
     let dir = PathBuf::from(&path);
-    indexer::index_directory(&state.db, &state.embedding_engine, &dir)
-        .await
-        .map_err(|e| e.to_string())
+    analytics::track(&state.db, analytics::UsageFeature::IndexDirectory, None);
+    Ok(indexer::index_directory(&state.db, &state.embedding_engine, &dir).await?)
 }
 
 #[tauri::command]
-async fn index_file(path: String, state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+async fn index_file(path: String, state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
     let file_path = PathBuf::from(&path);
-    indexer::index_file(&state.db, &state.embedding_engine, &file_path)
-        .await
-        .map_err(|e| e.to_string())
+    analytics::track(&state.db, analytics::UsageFeature::IndexFile, None);
+    Ok(indexer::index_file(&state.db, &state.embedding_engine, &file_path).await?)
 }
 
+/// Run an extractor plugin on a sample file before saving it, returning a
+/// preview of the extracted text.
 #[tauri::command]
-async fn get_stats(state: tauri::State<'_, Arc<AppState>>) -> Result<db::DbStats, String> {
-    state.db.get_stats().map_err(|e| e.to_string())
+async fn test_extractor_plugin(
+    plugin: indexer::plugins::ExtractorPlugin,
+    path: String,
+) -> CommandResult<String> {
+    indexer::plugins::validate(&plugin)?;
+    let text = tokio::task::spawn_blocking(move || {
+        indexer::plugins::run(&plugin, std::path::Path::new(&path))
+    })
+    .await??;
+    Ok(text.chars().take(2000).collect())
 }
 
+/// List discovered WASM plugins, including ones that failed to load.
 #[tauri::command]
-async fn check_ollama(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, String> {
-    state
-        .embedding_engine
-        .health_check()
-        .await
-        .map_err(|e| e.to_string())
+async fn list_wasm_plugins() -> CommandResult<Vec<wasm_plugins::PluginInfo>> {
+    Ok(wasm_plugins::list())
+}
+
+/// Rescan the plugins folder and recompile every plugin.
+#[tauri::command]
+async fn reload_wasm_plugins() -> CommandResult<Vec<wasm_plugins::PluginInfo>> {
+    tokio::task::spawn_blocking(|| {
+        wasm_plugins::load_all(&wasm_plugins::plugins_dir());
+        wasm_plugins::list()
+    })
+    .await
+    .map_err(|e| e.to_string().into())
 }
 
 #[tauri::command]
-async fn check_ai_status(state: tauri::State<'_, Arc<AppState>>) -> Result<AiStatus, String> {
+async fn get_stats(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<db::DbStats> {
+    Ok(state.db.get_stats()?)
+}
+
+#[tauri::command]
+async fn check_ollama(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<bool> {
+    Ok(state.embedding_engine.health_check().await?)
+}
+
+#[tauri::command]
+async fn check_ai_status(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<AiStatus> {
     Ok(state.embedding_engine.status())
 }
 
-/// Start file watcher on directories. Desktop only — notify crate requires OS file events.
+/// Whether `path` is inside one of the active vault's watched directories.
+/// Watchers started for another vault stop at their next event after a
+/// switch; until then their events are dropped here.
 #[cfg(desktop)]
-#[tauri::command]
-async fn start_watcher(
-    directories: Vec<String>,
-    state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    let dirs: Vec<PathBuf> = directories.iter().map(PathBuf::from).collect();
+fn in_active_vault(state: &AppState, path: &std::path::Path) -> bool {
+    state
+        .settings
+        .lock()
+        .map(|s| {
+            s.watched_directories
+                .iter()
+                .any(|dir| path.starts_with(dir))
+        })
+        .unwrap_or(false)
+}
+
+/// Bumped by `switch_vault` so the previous vault's watchers stop.
+#[cfg(desktop)]
+static WATCHER_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
-    let app_state = state.inner().clone();
+/// Watch `dirs` and keep the index in sync with their files until the next
+/// vault switch.
+#[cfg(desktop)]
+fn spawn_watcher(state: Arc<AppState>, dirs: Vec<PathBuf>) -> error::Result<()> {
+    use std::sync::atomic::Ordering;
 
-    // Start watcher in a background thread
-    let rx = indexer::watcher::start_watching(dirs).map_err(|e| e.to_string())?;
+    let generation = WATCHER_GENERATION.load(Ordering::SeqCst);
+    let rx = indexer::watcher::start_watching(dirs)?;
 
     tauri::async_runtime::spawn(async move {
         while let Ok(events) = rx.recv() {
+            // Dropping `rx` ends the watcher thread
+            if WATCHER_GENERATION.load(Ordering::SeqCst) != generation {
+                break;
+            }
             for event in events {
+                if !in_active_vault(&state, event.path()) {
+                    continue;
+                }
                 match event {
                     indexer::watcher::FileEvent::Changed(path) => {
                         tracing::info!("File changed, re-indexing: {}", path.display());
+                        analytics::track(
+                            &state.db,
+                            analytics::UsageFeature::IndexFile,
+                            Some("watcher"),
+                        );
                         if let Err(e) =
-                            indexer::index_file(&app_state.db, &app_state.embedding_engine, &path)
-                                .await
+                            indexer::index_file(&state.db, &state.embedding_engine, &path).await
                         {
                             tracing::warn!("Failed to re-index {}: {}", path.display(), e);
                         }
@@ -324,9 +889,8 @@ async fn start_watcher(
                     indexer::watcher::FileEvent::Removed(path) => {
                         tracing::info!("File removed: {}", path.display());
                         let path_str = path.to_string_lossy().to_string();
-                        if let Ok(Some((doc_id, _))) = app_state.db.get_document_by_path(&path_str)
-                        {
-                            if let Err(e) = app_state.db.delete_document(doc_id) {
+                        if let Ok(Some((doc_id, _))) = state.db.get_document_by_path(&path_str) {
+                            if let Err(e) = state.db.delete_document(doc_id) {
                                 tracing::warn!("Failed to delete document {}: {}", path_str, e);
                             }
                         }
@@ -339,30 +903,77 @@ async fn start_watcher(
     Ok(())
 }
 
+/// Start file watcher on directories. Desktop only — notify crate requires OS file events.
+#[cfg(desktop)]
+#[tauri::command]
+async fn start_watcher(
+    directories: Vec<String>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    let dirs: Vec<PathBuf> = directories.iter().map(PathBuf::from).collect();
+    Ok(spawn_watcher(state.inner().clone(), dirs)?)
+}
+
 /// Mobile stub — file watching not available on mobile platforms.
 #[cfg(mobile)]
 #[tauri::command]
 async fn start_watcher(
     _directories: Vec<String>,
     _state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     tracing::info!("File watcher not available on this platform");
     Ok(())
 }
 
 #[tauri::command]
-async fn get_vec_status(state: tauri::State<'_, Arc<AppState>>) -> Result<bool, String> {
+async fn get_vec_status(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<bool> {
     Ok(state.db.is_vec_enabled())
 }
 
+/// Capability report: what is degraded, why, and how to fix it.
+#[tauri::command]
+async fn get_system_health(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<health::SystemHealth> {
+    Ok(health::system_health(
+        &state.db,
+        &state.embedding_engine.status(),
+    ))
+}
+
+/// Retry loading sqlite-vec and creating the vector table without restarting.
+#[tauri::command]
+async fn retry_enable_vec(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<health::CapabilityStatus> {
+    let enabled = state.db.retry_enable_vec()?;
+    let status = health::vector_search_status(&state.db);
+    if enabled {
+        push_log("info", "Vector search enabled".to_string());
+    } else {
+        push_log(
+            "warn",
+            format!(
+                "Vector search still unavailable: {}",
+                status.reason.as_deref().unwrap_or("unknown error")
+            ),
+        );
+    }
+    let _ = app.emit(health::CAPABILITY_EVENT, &status);
+    Ok(status)
+}
+
 // --- Chat Commands ---
 
 #[tauri::command]
 async fn chat_send(
     messages: Vec<chat::ChatMessage>,
     max_tokens: Option<usize>,
+    conversation_id: Option<i64>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<chat::ChatResponse, String> {
+    app: tauri::AppHandle,
+) -> CommandResult<chat::ChatResponse> {
     let max_tokens = max_tokens.unwrap_or_else(|| {
         state
             .settings
@@ -378,27 +989,39 @@ async fn chat_send(
             max_tokens
         ),
     );
-    state
+    analytics::track(&state.db, analytics::UsageFeature::Chat, None);
+    // Only conversations switched to "allow cloud" may reach a remote provider
+    let allow_cloud = match conversation_id {
+        Some(id) => agent::memory::get_conversation(&state.db, id)?.is_some_and(|c| c.allow_cloud),
+        None => false,
+    };
+    let response = state
         .chat_engine
-        .chat(&messages, max_tokens)
+        .chat_with_cloud(&messages, max_tokens, allow_cloud)
         .await
-        .map_err(|e| {
-            push_log("error", format!("Chat error: {}", e));
-            e.to_string()
-        })
+        .inspect_err(|e| push_log("error", format!("Chat error: {}", e)))?;
+    if let Some(ref context) = response.context {
+        push_log(
+            "warn",
+            format!(
+                "Chat context truncated: dropped {} oldest messages",
+                context.dropped_messages
+            ),
+        );
+        let _ = app.emit(chat::tokens::CONTEXT_TRUNCATED_EVENT, context);
+    }
+    Ok(response)
 }
 
-/// AG-UI streaming chat — emits events through Tauri event system.
-///
-/// Returns the run_id immediately. The frontend listens to
-/// `agui://event` Tauri events for the streaming response.
+/// Chat with the reply constrained to `json_schema`: the native model can
+/// only produce JSON the schema allows. Returns the parsed value.
 #[tauri::command]
-async fn chat_send_streaming(
+async fn chat_send_structured(
     messages: Vec<chat::ChatMessage>,
+    json_schema: serde_json::Value,
     max_tokens: Option<usize>,
     state: tauri::State<'_, Arc<AppState>>,
-    app: tauri::AppHandle,
-) -> Result<String, String> {
+) -> CommandResult<chat::structured::StructuredResponse> {
     let max_tokens = max_tokens.unwrap_or_else(|| {
         state
             .settings
@@ -406,58 +1029,108 @@ async fn chat_send_streaming(
             .map(|s| s.chat_max_tokens)
             .unwrap_or(512)
     });
-
-    let run_id = format!(
-        "run-{}",
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis()
-    );
-
     push_log(
         "info",
         format!(
-            "AG-UI streaming chat: run_id={}, {} messages, max_tokens={}",
-            run_id,
+            "Structured chat: {} messages, max_tokens={}",
             messages.len(),
             max_tokens
         ),
     );
+    analytics::track(&state.db, analytics::UsageFeature::Chat, None);
+    let response = state
+        .chat_engine
+        .chat_structured(&messages, &json_schema, max_tokens)
+        .await
+        .inspect_err(|e| push_log("error", format!("Structured chat error: {}", e)))?;
+    Ok(response)
+}
 
-    let state_inner = state.inner().clone();
-    let run_id_clone = run_id.clone();
-
-    // Subscribe to AG-UI events and forward to Tauri event system
-    let mut rx = state_inner.agui_event_bus.subscribe();
-    let app_clone = app.clone();
-    let run_id_for_listener = run_id.clone();
-
-    tokio::spawn(async move {
-        loop {
-            match rx.recv().await {
-                Ok(event) => {
-                    // Only forward events for this run
-                    if event.run_id == run_id_for_listener {
-                        let is_terminal = matches!(
-                            event.event_type,
-                            protocols::agui::EventType::RunFinished
-                                | protocols::agui::EventType::RunError
-                        );
-                        let _ = app_clone.emit("agui://event", &event);
-                        if is_terminal {
-                            break;
-                        }
-                    }
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!("AG-UI event listener lagged by {} events", n);
-                }
+/// Forward one run's AG-UI events to the frontend until it finishes.
+/// Events skipped while the listener lagged are replayed from the run buffer.
+async fn forward_run_events(
+    app: tauri::AppHandle,
+    state: Arc<AppState>,
+    mut rx: tokio::sync::broadcast::Receiver<protocols::agui::AgUiEvent>,
+    run_id: String,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut cursor = protocols::agui::RunCursor::new(&run_id);
+    loop {
+        let events = match rx.recv().await {
+            // Only forward events for this run
+            Ok(event) if cursor.accept(&event) => vec![event],
+            Ok(_) => continue,
+            Err(RecvError::Closed) => break,
+            Err(RecvError::Lagged(n)) => {
+                tracing::warn!("AG-UI event listener lagged by {} events, replaying", n);
+                cursor.recover(&state.agui_event_bus, n)
+            }
+        };
+        for event in events {
+            let is_terminal = matches!(
+                event.event_type,
+                protocols::agui::EventType::RunFinished | protocols::agui::EventType::RunError
+            );
+            let _ = app.emit("agui://event", &event);
+            if is_terminal {
+                return;
             }
         }
+    }
+}
+
+/// AG-UI streaming chat — emits events through Tauri event system.
+///
+/// Returns the run_id immediately. The frontend listens to
+/// `agui://event` Tauri events for the streaming response.
+#[tauri::command]
+async fn chat_send_streaming(
+    messages: Vec<chat::ChatMessage>,
+    max_tokens: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<String> {
+    let max_tokens = max_tokens.unwrap_or_else(|| {
+        state
+            .settings
+            .lock()
+            .map(|s| s.chat_max_tokens)
+            .unwrap_or(512)
     });
 
+    let run_id = format!(
+        "run-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    );
+
+    push_log(
+        "info",
+        format!(
+            "AG-UI streaming chat: run_id={}, {} messages, max_tokens={}",
+            run_id,
+            messages.len(),
+            max_tokens
+        ),
+    );
+    analytics::track(&state.db, analytics::UsageFeature::Chat, None);
+
+    let state_inner = state.inner().clone();
+    let run_id_clone = run_id.clone();
+
+    // Subscribe to AG-UI events and forward to Tauri event system
+    let rx = state_inner.agui_event_bus.subscribe();
+    tokio::spawn(forward_run_events(
+        app.clone(),
+        state_inner.clone(),
+        rx,
+        run_id.clone(),
+    ));
+
     // Spawn the agent runner in a background task
     tokio::spawn(async move {
         let executor = agent::executor::AgentExecutor::new(state_inner.clone());
@@ -477,13 +1150,24 @@ async fn chat_send_streaming(
     Ok(run_id)
 }
 
+/// Count tokens as a chat model sees them (defaults to the active model).
+#[tauri::command]
+async fn count_tokens(
+    text: String,
+    model_id: Option<String>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<usize> {
+    let model_id = model_id.unwrap_or_else(|| state.chat_engine.status().model_id);
+    Ok(chat::tokens::count_tokens(&text, &model_id))
+}
+
 #[tauri::command]
-async fn chat_status(state: tauri::State<'_, Arc<AppState>>) -> Result<chat::ChatStatus, String> {
+async fn chat_status(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<chat::ChatStatus> {
     Ok(state.chat_engine.status())
 }
 
 #[tauri::command]
-async fn chat_load_model(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
+async fn chat_load_model(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
     let state = state.inner().clone();
     tauri::async_runtime::spawn(async move {
         state.chat_engine.load_model().await;
@@ -495,38 +1179,142 @@ async fn chat_load_model(state: tauri::State<'_, Arc<AppState>>) -> Result<(), S
 async fn chat_switch_model(
     model_id: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     // Update settings
     {
-        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let mut settings = state.settings.lock()?;
         settings.chat_model = model_id.clone();
-        let _ = settings.save(&get_app_data_dir().join("settings.json"));
+        let _ = settings.save(&get_settings_path());
     }
     push_log("info", format!("Switching to model: {}", model_id));
 
-    state
-        .chat_engine
-        .switch_model(&model_id)
-        .await
-        .map_err(|e| e.to_string())
+    Ok(state.chat_engine.switch_model(&model_id).await?)
+}
+
+/// Models offered by the configured local OpenAI-compatible server
+/// (Ollama, LM Studio, llama-server, vLLM).
+#[tauri::command]
+async fn list_local_server_models() -> CommandResult<Vec<String>> {
+    let server = chat::openai_compat::current();
+    Ok(chat::openai_compat::list_models(&server).await?)
 }
 
 // --- Hardware & Model Commands ---
 
 #[tauri::command]
-async fn get_hardware_info(state: tauri::State<'_, Arc<AppState>>) -> Result<HardwareInfo, String> {
+async fn get_hardware_info(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<HardwareInfo> {
     Ok(state.hardware.clone())
 }
 
 #[tauri::command]
 async fn get_available_models(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<chat::models::ModelInfo>, String> {
+) -> CommandResult<Vec<chat::models::ModelInfo>> {
     Ok(state.chat_engine.available_models())
 }
 
+/// Import a local GGUF as a chat or agent model and persist it in settings.
+#[tauri::command]
+async fn import_custom_model(
+    path: String,
+    name: String,
+    context_window: Option<u32>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<chat::models::CustomModel> {
+    let custom = tokio::task::spawn_blocking(move || {
+        chat::models::import_custom_model(std::path::Path::new(&path), &name, context_window)
+    })
+    .await??;
+    register_imported_model(&state, &custom)?;
+    Ok(custom)
+}
+
+/// Add an imported model to the registry and persist it in settings.
+fn register_imported_model(
+    state: &AppState,
+    custom: &chat::models::CustomModel,
+) -> error::Result<()> {
+    let mut settings = state.settings.lock()?;
+    settings.custom_models.push(custom.clone());
+    chat::models::register_custom_models(&settings.custom_models);
+    settings.save(&get_settings_path())?;
+    push_log(
+        "info",
+        format!(
+            "Imported model {} ({}, {}, ctx={})",
+            custom.id, custom.parameters, custom.quantization, custom.context_window
+        ),
+    );
+    Ok(())
+}
+
+/// Search the Hugging Face Hub for GGUF models that fit this machine.
+#[tauri::command]
+async fn search_hub_models(
+    query: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<chat::models::hub::HubModel>> {
+    Ok(chat::models::hub::search_hub_models(&query, &state.hardware).await?)
+}
+
+/// Download a GGUF from the Hub and register it as a custom model.
+#[tauri::command]
+async fn add_hub_model(
+    repo_id: String,
+    filename: String,
+    name: Option<String>,
+    context_window: Option<u32>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<chat::models::CustomModel> {
+    downloads::manager().ensure_allowed()?;
+    let path = downloads::hf_download(&repo_id, &filename).await?;
+    let name = name.unwrap_or_else(|| filename.trim_end_matches(".gguf").to_string());
+    let custom = tokio::task::spawn_blocking(move || {
+        chat::models::import_custom_model(&path, &name, context_window)
+    })
+    .await??;
+    register_imported_model(&state, &custom)?;
+    Ok(custom)
+}
+
+/// Models that must stay on disk: loaded by the chat engine or used by the agent.
+fn models_in_use(state: &AppState) -> Vec<String> {
+    let mut ids = state.chat_engine.loaded_model_ids();
+    if let Ok(settings) = state.settings.lock() {
+        ids.push(settings.chat_model.clone());
+        ids.push(agent::config::resolve_agent_model(&settings.agent_config, &state.hardware).0);
+    }
+    ids
+}
+
+/// Downloaded chat model GGUFs with their disk usage.
+#[tauri::command]
+async fn list_downloaded_models(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<chat::models::DownloadedModel>> {
+    Ok(chat::models::list_downloaded_models(&models_in_use(&state)))
+}
+
+/// Delete a downloaded model that is not in use. Returns bytes freed.
+#[tauri::command]
+async fn delete_downloaded_model(
+    model_id: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<u64> {
+    let freed = chat::models::delete_downloaded_model(&model_id, &models_in_use(&state))?;
+    push_log(
+        "info",
+        format!(
+            "Deleted model {} ({} MB freed)",
+            model_id,
+            freed / 1_048_576
+        ),
+    );
+    Ok(freed)
+}
+
 #[tauri::command]
-async fn get_recommended_model(state: tauri::State<'_, Arc<AppState>>) -> Result<String, String> {
+async fn get_recommended_model(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<String> {
     Ok(state.chat_engine.recommended_model_id())
 }
 
@@ -535,7 +1323,7 @@ async fn get_recommended_model(state: tauri::State<'_, Arc<AppState>>) -> Result
 /// Get the current platform information for the frontend.
 /// Allows the UI to adapt layout and features based on platform.
 #[tauri::command]
-async fn get_platform_info() -> Result<serde_json::Value, String> {
+async fn get_platform_info() -> CommandResult<serde_json::Value> {
     let platform = if cfg!(target_os = "android") {
         "android"
     } else if cfg!(target_os = "ios") {
@@ -567,15 +1355,26 @@ async fn get_platform_info() -> Result<serde_json::Value, String> {
 // --- Debug Commands ---
 
 #[tauri::command]
-async fn get_logs(since_index: Option<usize>) -> Result<Vec<LogEntry>, String> {
-    let logs = LOG_BUFFER.lock().map_err(|e| e.to_string())?;
+async fn get_logs(since_index: Option<usize>) -> CommandResult<Vec<LogEntry>> {
+    let logs = LOG_BUFFER.lock()?;
     let since = since_index.unwrap_or(0);
     Ok(logs.iter().skip(since).cloned().collect())
 }
 
+/// Report of the last session that crashed or was killed (`null` if none).
+#[tauri::command]
+async fn get_last_crash_report() -> CommandResult<Option<crash::CrashReport>> {
+    Ok(crash::last_report(&get_app_data_dir()))
+}
+
 #[tauri::command]
-async fn clear_logs() -> Result<(), String> {
-    let mut logs = LOG_BUFFER.lock().map_err(|e| e.to_string())?;
+async fn dismiss_crash_report() -> CommandResult<()> {
+    Ok(crash::dismiss_report(&get_app_data_dir())?)
+}
+
+#[tauri::command]
+async fn clear_logs() -> CommandResult<()> {
+    let mut logs = LOG_BUFFER.lock()?;
     logs.clear();
     Ok(())
 }
@@ -606,7 +1405,7 @@ async fn is_pro() -> bool {
 #[tauri::command]
 async fn get_mcp_server_status(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     let config = state
         .settings
         .lock()
@@ -618,14 +1417,101 @@ async fn get_mcp_server_status(
         "host": config.host,
         "port": config.port,
         "url": format!("http://{}:{}/mcp", config.host, config.port),
+        "require_auth": config.require_auth,
+        "auth_token": config.auth_token,
+    }))
+}
+
+/// Replace the MCP server's bearer token and apply it right away; clients
+/// holding the old one are rejected while auth is required. Returns the new token.
+#[tauri::command]
+async fn rotate_mcp_token(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<String> {
+    let mut settings = state.settings.lock()?;
+    let token = protocols::mcp_server::generate_token();
+    settings.mcp_server.auth_token = Some(token.clone());
+    settings.save(&get_settings_path())?;
+    protocols::mcp_server::configure_auth(&settings.mcp_server);
+    push_log("info", "MCP server token rotated".to_string());
+    Ok(token)
+}
+
+/// Get AG-UI WebSocket server status (configuration and endpoint URL).
+#[tauri::command]
+async fn get_agui_ws_status(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<serde_json::Value> {
+    let config = state
+        .settings
+        .lock()
+        .map(|s| s.agui_ws.clone())
+        .unwrap_or_default();
+
+    Ok(serde_json::json!({
+        "enabled": config.enabled,
+        "host": config.host,
+        "port": config.port,
+        "url": format!("ws://{}:{}/agui/ws", config.host, config.port),
     }))
 }
 
+/// Get OpenAI-compatible API server status (configuration and base URL).
+#[tauri::command]
+async fn get_openai_server_status(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<serde_json::Value> {
+    let config = state
+        .settings
+        .lock()
+        .map(|s| s.openai_server.clone())
+        .unwrap_or_default();
+
+    Ok(serde_json::json!({
+        "enabled": config.enabled,
+        "host": config.host,
+        "port": config.port,
+        "requires_api_key": config.api_key.as_deref().is_some_and(|k| !k.is_empty()),
+        "url": config.base_url(),
+    }))
+}
+
+/// Get WebMCP bridge status (configuration, endpoint URL and origin permissions).
+#[tauri::command]
+async fn get_webmcp_status(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<serde_json::Value> {
+    let config = state
+        .settings
+        .lock()
+        .map(|s| s.webmcp.clone())
+        .unwrap_or_default();
+
+    Ok(serde_json::json!({
+        "enabled": config.enabled,
+        "host": config.host,
+        "port": config.port,
+        "url": config.url(),
+        "origins": config.origins,
+    }))
+}
+
+/// Allow or deny a browser origin on the WebMCP bridge; `null` forgets the
+/// decision so the origin is asked again.
+#[tauri::command]
+async fn set_webmcp_origin_permission(
+    state: tauri::State<'_, Arc<AppState>>,
+    origin: String,
+    permission: Option<protocols::webmcp::OriginPermission>,
+) -> CommandResult<()> {
+    Ok(protocols::webmcp::set_permission(
+        &state, &origin, permission,
+    )?)
+}
+
 /// List all configured external MCP servers and their connection status.
 #[tauri::command]
 async fn list_mcp_servers(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<protocols::mcp_client::ConnectedServer>, String> {
+) -> CommandResult<Vec<protocols::mcp_client::ConnectedServer>> {
     Ok(state.mcp_client.list_servers().await)
 }
 
@@ -634,9 +1520,9 @@ async fn list_mcp_servers(
 async fn connect_mcp_server(
     name: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<protocols::mcp_client::ConnectedServer, String> {
+) -> CommandResult<protocols::mcp_client::ConnectedServer> {
     let entry = {
-        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let settings = state.settings.lock()?;
         settings
             .mcp_servers
             .iter()
@@ -647,17 +1533,62 @@ async fn connect_mcp_server(
     Ok(state.mcp_client.connect(&entry).await)
 }
 
+/// Sign in to a remote MCP server that requires OAuth: opens the system
+/// browser, waits for the redirect, then connects the server.
+#[tauri::command]
+async fn sign_in_mcp_server(
+    name: String,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<protocols::mcp_client::ConnectedServer> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let entry = {
+        let settings = state.settings.lock()?;
+        settings
+            .mcp_servers
+            .iter()
+            .find(|s| s.name == name)
+            .cloned()
+            .ok_or_else(|| format!("MCP server '{}' not found in settings", name))?
+    };
+    let url = entry
+        .url
+        .clone()
+        .ok_or_else(|| format!("MCP server '{}' has no URL", name))?;
+    protocols::mcp_oauth::sign_in(&entry.name, &url, |authorize_url| {
+        app.opener()
+            .open_url(authorize_url, None::<&str>)
+            .map_err(anyhow::Error::from)
+    })
+    .await
+    .map_err(|e| error::GhostError::Mcp(e.to_string()))?;
+    let _ = state.mcp_client.disconnect(&name).await;
+    Ok(state.mcp_client.connect(&entry).await)
+}
+
+/// Forget a remote MCP server's OAuth tokens and disconnect it.
+#[tauri::command]
+async fn sign_out_mcp_server(
+    name: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    let _ = state.mcp_client.disconnect(&name).await;
+    protocols::mcp_oauth::forget(&name);
+    Ok(())
+}
+
 /// Disconnect from an external MCP server.
 #[tauri::command]
 async fn disconnect_mcp_server(
     name: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     state
         .mcp_client
         .disconnect(&name)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string().into())
 }
 
 /// Call a tool on a connected external MCP server.
@@ -667,19 +1598,49 @@ async fn call_mcp_tool(
     tool_name: String,
     arguments: Option<serde_json::Value>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<String, String> {
-    state
-        .mcp_client
-        .call_tool(&server_name, &tool_name, arguments)
+) -> CommandResult<String> {
+    protocols::mcp_trace::call_traced(&state, None, &server_name, &tool_name, arguments)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+/// Recorded MCP tool calls, newest first, optionally filtered.
+#[tauri::command]
+async fn get_tool_call_history(
+    server: Option<String>,
+    tool: Option<String>,
+    run_id: Option<String>,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<protocols::mcp_trace::ToolCallRecord>> {
+    let filter = protocols::mcp_trace::HistoryFilter {
+        server,
+        tool,
+        run_id,
+    };
+    Ok(protocols::mcp_trace::get_history(
+        &state.db,
+        &filter,
+        limit.unwrap_or(100).min(1000),
+    )?)
+}
+
+/// Call a recorded MCP tool again with the same arguments.
+#[tauri::command]
+async fn replay_tool_call(
+    id: i64,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<protocols::mcp_trace::ReplayResult> {
+    protocols::mcp_trace::replay(&state, id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string().into())
 }
 
 /// Get all available tools from all connected MCP servers.
 #[tauri::command]
 async fn list_mcp_tools(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> CommandResult<Vec<serde_json::Value>> {
     let tools = state.mcp_client.all_tools().await;
     let result: Vec<serde_json::Value> = tools
         .iter()
@@ -697,16 +1658,15 @@ async fn list_mcp_tools(
 /// Add a new MCP server entry to settings.
 #[tauri::command]
 async fn add_mcp_server_entry(
-    entry: protocols::McpServerEntry,
+    mut entry: protocols::McpServerEntry,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+) -> CommandResult<()> {
+    secrets::store_mcp_env(&mut entry);
+    let mut settings = state.settings.lock()?;
     // Avoid duplicates
     settings.mcp_servers.retain(|s| s.name != entry.name);
     settings.mcp_servers.push(entry);
-    settings
-        .save(&get_app_data_dir().join("settings.json"))
-        .map_err(|e| e.to_string())
+    Ok(settings.save(&get_settings_path())?)
 }
 
 /// Remove an MCP server entry from settings.
@@ -714,21 +1674,42 @@ async fn add_mcp_server_entry(
 async fn remove_mcp_server_entry(
     name: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     // Disconnect first
     let _ = state.mcp_client.disconnect(&name).await;
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    let mut settings = state.settings.lock()?;
+    for entry in settings.mcp_servers.iter().filter(|s| s.name == name) {
+        secrets::forget_mcp_env(entry);
+    }
     settings.mcp_servers.retain(|s| s.name != name);
-    settings
-        .save(&get_app_data_dir().join("settings.json"))
-        .map_err(|e| e.to_string())
+    Ok(settings.save(&get_settings_path())?)
+}
+
+/// Store a secret in the OS keychain. Refer to it in settings (e.g. an MCP
+/// server env value) as `${secret:<name>}`.
+#[tauri::command]
+async fn set_secret(name: String, value: String) -> CommandResult<()> {
+    secrets::set(&name, &value)?;
+    Ok(())
+}
+
+/// Read a secret from the OS keychain (`None` if not set).
+#[tauri::command]
+async fn get_secret(name: String) -> CommandResult<Option<String>> {
+    Ok(secrets::get(&name)?)
+}
+
+/// Delete a secret from the OS keychain. Returns false if it wasn't set.
+#[tauri::command]
+async fn delete_secret(name: String) -> CommandResult<bool> {
+    Ok(secrets::delete(&name)?)
 }
 
 // --- MCP Catalog Commands ---
 
 /// Get the curated MCP tool catalog.
 #[tauri::command]
-async fn get_mcp_catalog() -> Result<serde_json::Value, String> {
+async fn get_mcp_catalog() -> CommandResult<serde_json::Value> {
     let catalog = protocols::mcp_catalog::get_catalog();
     let categories = protocols::mcp_catalog::get_categories();
     Ok(serde_json::json!({
@@ -739,19 +1720,19 @@ async fn get_mcp_catalog() -> Result<serde_json::Value, String> {
 
 /// Detect available runtimes on the system (npx, node, python, uv, etc.).
 #[tauri::command]
-async fn detect_runtimes() -> Result<protocols::mcp_catalog::RuntimeInfo, String> {
+async fn detect_runtimes() -> CommandResult<protocols::mcp_catalog::RuntimeInfo> {
     Ok(protocols::mcp_catalog::detect_runtimes().await)
 }
 
 /// Get zero-config MCP tools that work without any API keys.
 #[tauri::command]
-async fn get_zero_config_tools() -> Result<Vec<protocols::mcp_catalog::CatalogEntry>, String> {
+async fn get_zero_config_tools() -> CommandResult<Vec<protocols::mcp_catalog::CatalogEntry>> {
     Ok(protocols::mcp_catalog::get_zero_config_tools())
 }
 
 /// Get the recommended default tools that Ghost auto-installs.
 #[tauri::command]
-async fn get_default_tools() -> Result<Vec<protocols::mcp_catalog::CatalogEntry>, String> {
+async fn get_default_tools() -> CommandResult<Vec<protocols::mcp_catalog::CatalogEntry>> {
     Ok(protocols::mcp_catalog::get_default_tools())
 }
 
@@ -760,7 +1741,7 @@ async fn get_default_tools() -> Result<Vec<protocols::mcp_catalog::CatalogEntry>
 #[tauri::command]
 async fn verify_mcp_package(
     catalog_id: String,
-) -> Result<protocols::mcp_catalog::PackageVerification, String> {
+) -> CommandResult<protocols::mcp_catalog::PackageVerification> {
     let catalog = protocols::mcp_catalog::get_catalog();
     let entry = catalog
         .iter()
@@ -774,20 +1755,18 @@ async fn verify_mcp_package(
 #[tauri::command]
 async fn auto_provision_mcp_defaults(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<usize, String> {
+) -> CommandResult<usize> {
     let runtimes = protocols::mcp_catalog::detect_runtimes().await;
     let entries = protocols::mcp_catalog::auto_provision_defaults(&runtimes).await;
     let count = entries.len();
 
     if !entries.is_empty() {
-        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let mut settings = state.settings.lock()?;
         for entry in entries {
             settings.mcp_servers.retain(|s| s.name != entry.name);
             settings.mcp_servers.push(entry);
         }
-        settings
-            .save(&get_app_data_dir().join("settings.json"))
-            .map_err(|e| e.to_string())?;
+        settings.save(&get_settings_path())?;
     }
 
     Ok(count)
@@ -801,7 +1780,7 @@ async fn install_mcp_from_catalog(
     catalog_id: String,
     env_vars: std::collections::HashMap<String, String>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<protocols::mcp_client::ConnectedServer, String> {
+) -> CommandResult<protocols::mcp_client::ConnectedServer> {
     // Find catalog entry
     let catalog = protocols::mcp_catalog::get_catalog();
     let entry = catalog
@@ -820,30 +1799,27 @@ async fn install_mcp_from_catalog(
         return Err(format!(
             "{} is required to install '{}'. Please install it and try again.",
             runtime_name, entry.name
-        ));
+        )
+        .into());
     }
 
     // Check required env vars
     for env_spec in &entry.required_env {
         if env_spec.required && !env_vars.contains_key(&env_spec.name) {
-            return Err(format!(
-                "Required configuration '{}' is missing",
-                env_spec.label
-            ));
+            return Err(format!("Required configuration '{}' is missing", env_spec.label).into());
         }
     }
 
-    // Build the server entry from catalog
-    let server_entry = protocols::mcp_catalog::build_server_entry(entry, env_vars);
+    // Build the server entry from catalog, tokens go to the keychain
+    let mut server_entry = protocols::mcp_catalog::build_server_entry(entry, env_vars);
+    secrets::store_mcp_env(&mut server_entry);
 
     // Save to settings (avoid duplicates)
     {
-        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let mut settings = state.settings.lock()?;
         settings.mcp_servers.retain(|s| s.name != server_entry.name);
         settings.mcp_servers.push(server_entry.clone());
-        settings
-            .save(&get_app_data_dir().join("settings.json"))
-            .map_err(|e| e.to_string())?;
+        settings.save(&get_settings_path())?;
     }
 
     // Auto-connect
@@ -867,9 +1843,9 @@ async fn install_mcp_entry(
     entry: protocols::mcp_catalog::CatalogEntry,
     env_vars: std::collections::HashMap<String, String>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<protocols::mcp_client::ConnectedServer, String> {
+) -> CommandResult<protocols::mcp_client::ConnectedServer> {
     // Check runtime availability (skip for remote/http servers)
-    if entry.transport != "http" {
+    if entry.transport != "http" && entry.transport != "sse" {
         let runtimes = protocols::mcp_catalog::detect_runtimes().await;
         if !protocols::mcp_catalog::can_install(&entry, &runtimes) {
             let runtime_name = match entry.runtime.as_str() {
@@ -881,31 +1857,28 @@ async fn install_mcp_entry(
             return Err(format!(
                 "{} is required to install '{}'. Please install it and try again.",
                 runtime_name, entry.name
-            ));
+            )
+            .into());
         }
     }
 
     // Check required env vars
     for env_spec in &entry.required_env {
         if env_spec.required && !env_vars.contains_key(&env_spec.name) {
-            return Err(format!(
-                "Required configuration '{}' is missing",
-                env_spec.label
-            ));
+            return Err(format!("Required configuration '{}' is missing", env_spec.label).into());
         }
     }
 
-    // Build the server entry from catalog
-    let server_entry = protocols::mcp_catalog::build_server_entry(&entry, env_vars);
+    // Build the server entry from catalog, tokens go to the keychain
+    let mut server_entry = protocols::mcp_catalog::build_server_entry(&entry, env_vars);
+    secrets::store_mcp_env(&mut server_entry);
 
     // Save to settings (avoid duplicates)
     {
-        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let mut settings = state.settings.lock()?;
         settings.mcp_servers.retain(|s| s.name != server_entry.name);
         settings.mcp_servers.push(server_entry.clone());
-        settings
-            .save(&get_app_data_dir().join("settings.json"))
-            .map_err(|e| e.to_string())?;
+        settings.save(&get_settings_path())?;
     }
 
     // Auto-connect
@@ -921,18 +1894,48 @@ async fn install_mcp_entry(
     Ok(result)
 }
 
+/// Install wizard: check a catalog or registry entry before saving it. Validates
+/// the env vars, connects, lists the tools and tries one read-only call; the
+/// server is saved (tokens to the keychain) only if every check passes. The
+/// report tells the UI which step failed and how to fix it.
+#[tauri::command]
+async fn install_catalog_entry(
+    entry: protocols::mcp_catalog::CatalogEntry,
+    env_vars: std::collections::HashMap<String, String>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<protocols::mcp_catalog::InstallReport> {
+    let mut server_entry = protocols::mcp_catalog::build_server_entry(&entry, env_vars);
+    let report =
+        protocols::mcp_catalog::verify_install(&state.mcp_client, &entry, &server_entry).await;
+    if report.installed {
+        secrets::store_mcp_env(&mut server_entry);
+        let mut settings = state.settings.lock()?;
+        settings.mcp_servers.retain(|s| s.name != server_entry.name);
+        settings.mcp_servers.push(server_entry);
+        settings.save(&get_settings_path())?;
+    }
+    tracing::info!(
+        "MCP Catalog: install check for '{}' ({}): installed={}",
+        entry.name,
+        entry.id,
+        report.installed
+    );
+    Ok(report)
+}
+
 /// Uninstall an MCP server (disconnect + remove from settings).
 #[tauri::command]
 async fn uninstall_mcp_server(
     name: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let _ = state.mcp_client.disconnect(&name).await;
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    let mut settings = state.settings.lock()?;
+    for entry in settings.mcp_servers.iter().filter(|s| s.name == name) {
+        secrets::forget_mcp_env(entry);
+    }
     settings.mcp_servers.retain(|s| s.name != name);
-    settings
-        .save(&get_app_data_dir().join("settings.json"))
-        .map_err(|e| e.to_string())
+    Ok(settings.save(&get_settings_path())?)
 }
 
 // --- MCP Registry Commands ---
@@ -941,7 +1944,7 @@ async fn uninstall_mcp_server(
 /// Fetches all servers from registry.modelcontextprotocol.io and caches locally.
 /// This is an opt-in action — respects Ghost's privacy-first design.
 #[tauri::command]
-async fn sync_mcp_registry() -> Result<protocols::mcp_catalog::RegistrySyncResult, String> {
+async fn sync_mcp_registry() -> CommandResult<protocols::mcp_catalog::RegistrySyncResult> {
     let cache_dir = get_app_data_dir();
     std::fs::create_dir_all(&cache_dir).map_err(|e| format!("Cannot create cache dir: {}", e))?;
     Ok(protocols::mcp_catalog::sync_registry(&cache_dir).await)
@@ -954,7 +1957,7 @@ async fn sync_mcp_registry() -> Result<protocols::mcp_catalog::RegistrySyncResul
 async fn search_mcp_registry(
     query: String,
     limit: Option<usize>,
-) -> Result<Vec<protocols::mcp_catalog::CatalogEntry>, String> {
+) -> CommandResult<Vec<protocols::mcp_catalog::CatalogEntry>> {
     let cache_dir = get_app_data_dir();
     let limit = limit.unwrap_or(50);
     Ok(protocols::mcp_catalog::search_registry(
@@ -964,7 +1967,7 @@ async fn search_mcp_registry(
 
 /// Get the registry cache status (last sync time, server count, freshness).
 #[tauri::command]
-async fn get_registry_status() -> Result<serde_json::Value, String> {
+async fn get_registry_status() -> CommandResult<serde_json::Value> {
     let cache_dir = get_app_data_dir();
     let meta = protocols::mcp_catalog::get_cache_meta(&cache_dir);
     let fresh = protocols::mcp_catalog::is_cache_fresh(&cache_dir);
@@ -981,7 +1984,7 @@ async fn get_registry_status() -> Result<serde_json::Value, String> {
 /// Reports whether each runtime is installed, managed by Ghost, and its version.
 #[tauri::command]
 async fn get_runtime_bootstrap_status(
-) -> Result<protocols::runtime_bootstrap::BootstrapStatus, String> {
+) -> CommandResult<protocols::runtime_bootstrap::BootstrapStatus> {
     let app_data = get_app_data_dir();
     let bootstrapper = protocols::runtime_bootstrap::RuntimeBootstrapper::new(&app_data);
     Ok(bootstrapper.get_status().await)
@@ -993,7 +1996,7 @@ async fn get_runtime_bootstrap_status(
 async fn install_runtime(
     kind: protocols::runtime_bootstrap::RuntimeKind,
     app: tauri::AppHandle,
-) -> Result<protocols::runtime_bootstrap::InstallResult, String> {
+) -> CommandResult<protocols::runtime_bootstrap::InstallResult> {
     let app_data = get_app_data_dir();
     let bootstrapper = protocols::runtime_bootstrap::RuntimeBootstrapper::new(&app_data);
 
@@ -1012,7 +2015,7 @@ async fn install_runtime(
 #[tauri::command]
 async fn bootstrap_all_runtimes(
     app: tauri::AppHandle,
-) -> Result<Vec<protocols::runtime_bootstrap::InstallResult>, String> {
+) -> CommandResult<Vec<protocols::runtime_bootstrap::InstallResult>> {
     let app_data = get_app_data_dir();
     let bootstrapper = protocols::runtime_bootstrap::RuntimeBootstrapper::new(&app_data);
 
@@ -1031,7 +2034,7 @@ async fn bootstrap_all_runtimes(
 #[tauri::command]
 async fn recommend_mcp_tools(
     query: String,
-) -> Result<Vec<protocols::runtime_bootstrap::ToolRecommendation>, String> {
+) -> CommandResult<Vec<protocols::runtime_bootstrap::ToolRecommendation>> {
     let catalog = protocols::mcp_catalog::get_catalog();
     Ok(protocols::runtime_bootstrap::recommend_tools(
         &query, &catalog,
@@ -1041,7 +2044,7 @@ async fn recommend_mcp_tools(
 /// Check what a tool needs before it can be installed.
 /// Returns the runtime requirements and whether they're met.
 #[tauri::command]
-async fn check_tool_requirements(catalog_id: String) -> Result<serde_json::Value, String> {
+async fn check_tool_requirements(catalog_id: String) -> CommandResult<serde_json::Value> {
     let catalog = protocols::mcp_catalog::get_catalog();
     let entry = catalog
         .iter()
@@ -1106,13 +2109,13 @@ pub struct FsEntry {
 /// List contents of a directory for the file browser.
 /// Returns sorted entries: directories first, then files.
 #[tauri::command]
-async fn list_directory(path: String) -> Result<Vec<FsEntry>, String> {
+async fn list_directory(path: String) -> CommandResult<Vec<FsEntry>> {
     let dir = PathBuf::from(&path);
     if !dir.exists() {
-        return Err(format!("Directory does not exist: {}", path));
+        return Err(format!("Directory does not exist: {}", path).into());
     }
     if !dir.is_dir() {
-        return Err(format!("Not a directory: {}", path));
+        return Err(format!("Not a directory: {}", path).into());
     }
 
     let mut entries = Vec::new();
@@ -1207,15 +2210,15 @@ fn detect_cloud_status(_metadata: &std::fs::Metadata) -> (bool, bool) {
 
 /// Get the user's home directory.
 #[tauri::command]
-async fn get_home_directory() -> Result<String, String> {
-    dirs::home_dir()
+async fn get_home_directory() -> CommandResult<String> {
+    Ok(dirs::home_dir()
         .map(|p| p.to_string_lossy().to_string())
-        .ok_or_else(|| "Could not determine home directory".to_string())
+        .ok_or_else(|| "Could not determine home directory".to_string())?)
 }
 
 /// Get common root directories for filesystem browsing.
 #[tauri::command]
-async fn get_root_directories() -> Result<Vec<FsEntry>, String> {
+async fn get_root_directories() -> CommandResult<Vec<FsEntry>> {
     let mut roots = Vec::new();
 
     // Add home directory
@@ -1339,40 +2342,28 @@ async fn get_root_directories() -> Result<Vec<FsEntry>, String> {
 async fn add_watch_directory(
     path: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     let dir = PathBuf::from(&path);
     if !dir.exists() || !dir.is_dir() {
-        return Err(format!("Invalid directory: {}", path));
+        return Err(format!("Invalid directory: {}", path).into());
     }
 
     // Add to settings
     {
-        let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let mut settings = state.settings.lock()?;
         if !settings.watched_directories.contains(&path) {
             settings.watched_directories.push(path.clone());
-            let _ = settings.save(&get_app_data_dir().join("settings.json"));
+            let _ = settings.save(&get_settings_path());
         }
     }
+    sync_mcp_roots(&state).await;
 
     // Start indexing in background
-    let app_state = state.inner().clone();
-    tauri::async_runtime::spawn(async move {
-        push_log("info", format!("Indexing new directory: {}", path));
-        match indexer::index_directory(&app_state.db, &app_state.embedding_engine, &dir).await {
-            Ok(stats) => {
-                push_log(
-                    "info",
-                    format!(
-                        "Indexed {}: {} files ({} ok, {} failed)",
-                        path, stats.total, stats.indexed, stats.failed
-                    ),
-                );
-            }
-            Err(e) => {
-                push_log("warn", format!("Failed to index {}: {}", path, e));
-            }
-        }
-    });
+    jobs::submit(
+        state.inner(),
+        jobs::JobSpec::IndexDirectory { path },
+        jobs::JobPriority::High,
+    )?;
 
     Ok(())
 }
@@ -1382,43 +2373,245 @@ async fn add_watch_directory(
 async fn remove_watch_directory(
     path: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    settings.watched_directories.retain(|d| d != &path);
-    let _ = settings.save(&get_app_data_dir().join("settings.json"));
+) -> CommandResult<()> {
+    {
+        let mut settings = state.settings.lock()?;
+        settings.watched_directories.retain(|d| d != &path);
+        let _ = settings.save(&get_settings_path());
+    }
+    sync_mcp_roots(&state).await;
     Ok(())
 }
 
 // --- Settings Commands ---
 
 #[tauri::command]
-async fn get_settings(state: tauri::State<'_, Arc<AppState>>) -> Result<Settings, String> {
-    let settings = state.settings.lock().map_err(|e| e.to_string())?;
+async fn get_settings(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<Settings> {
+    let settings = state.settings.lock()?;
     Ok(settings.clone())
 }
 
+/// Save settings. When the chunk size or overlap changed and `rechunk` is
+/// set, the vault is re-chunked and re-embedded in a background job;
+/// otherwise the new values only apply to files indexed from now on.
 #[tauri::command]
 async fn save_settings(
-    new_settings: Settings,
+    mut new_settings: Settings,
+    rechunk: Option<bool>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    *settings = new_settings;
-    settings
-        .save(&get_app_data_dir().join("settings.json"))
-        .map_err(|e| e.to_string())
+    app: tauri::AppHandle,
+) -> CommandResult<()> {
+    new_settings.validate();
+    new_settings.mcp_server.ensure_token();
+    secrets::store_all_mcp_env(&mut new_settings.mcp_servers);
+    let (chunking_changed, requested_model) = {
+        let mut settings = state.settings.lock()?;
+        // The embedding model only changes once the vault is re-embedded; keep
+        // the current one and let the UI offer the migration.
+        let requested_model =
+            (new_settings.embedding_model != settings.embedding_model).then(|| {
+                std::mem::replace(
+                    &mut new_settings.embedding_model,
+                    settings.embedding_model.clone(),
+                )
+            });
+        let chunking_changed = settings.chunk_config() != new_settings.chunk_config();
+        apply_settings(&new_settings);
+        *settings = new_settings;
+        settings.save(&get_settings_path())?;
+        (chunking_changed, requested_model)
+    };
+    sync_mcp_roots(&state).await;
+
+    if let Some(model) = requested_model.and_then(|id| embeddings::models::find_model(&id)) {
+        let _ = app.emit(embeddings::models::REEMBED_REQUIRED_EVENT, model.id);
+    }
+
+    if chunking_changed && rechunk.unwrap_or(false) {
+        jobs::submit(
+            state.inner(),
+            jobs::JobSpec::Rechunk,
+            jobs::JobPriority::Normal,
+        )?;
+    }
+    Ok(())
 }
 
-/// Mark initial setup/onboarding as complete.
+/// Reset one settings section (e.g. "ranking") to its defaults and save.
+/// Returns the updated settings.
 #[tauri::command]
-async fn complete_setup(state: tauri::State<'_, Arc<AppState>>) -> Result<(), String> {
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    settings.setup_complete = true;
-    settings
-        .save(&get_app_data_dir().join("settings.json"))
-        .map_err(|e| e.to_string())?;
-    tracing::info!("Initial setup marked as complete");
-    Ok(())
+async fn reset_settings_section(
+    section: String,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<Settings> {
+    let mut settings = state.settings.lock()?.clone();
+    settings.reset_section(&section)?;
+    save_settings(settings, None, state.clone(), app).await?;
+    let settings = state.settings.lock()?;
+    Ok(settings.clone())
+}
+
+// --- Vaults ---
+
+/// All vaults, the default one first; `active` marks the open one.
+#[tauri::command]
+async fn list_vaults() -> CommandResult<Vec<vaults::VaultInfo>> {
+    Ok(vaults::list(&get_app_data_dir()))
+}
+
+/// Create an empty vault. It starts with a copy of the active vault's
+/// settings, minus the watched directories. With a passphrase its database
+/// is encrypted; the passphrase can't be recovered or added later.
+#[tauri::command]
+async fn create_vault(
+    name: String,
+    passphrase: Option<String>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<vaults::VaultInfo> {
+    if let Some(passphrase) = &passphrase {
+        db::encryption::check_passphrase(passphrase)?;
+        if !db::encryption::is_supported() {
+            return Err(error::GhostError::Vault(
+                "This build of Ghost has no encryption support".into(),
+            )
+            .into());
+        }
+    }
+    let data_dir = get_app_data_dir();
+    let mut vault = vaults::create(&data_dir, &name)?;
+    let mut settings = state.settings.lock()?.clone();
+    settings.watched_directories.clear();
+    settings.save(&vaults::settings_path(&data_dir, &vault.id))?;
+
+    if let Some(passphrase) = passphrase {
+        let db_path = vaults::db_path(&data_dir, &vault.id);
+        let vault_id = vault.id.clone();
+        tokio::task::spawn_blocking(move || -> error::Result<()> {
+            let key = db::encryption::create_key(&db_path, &passphrase)?;
+            Database::open_with_key(&db_path, Some(&key))?;
+            if let Err(e) = extensions::extensions().escrow_vault_key(&vault_id, &key) {
+                tracing::warn!("Vault key escrow failed: {}", e);
+            }
+            Ok(())
+        })
+        .await??;
+        vault.encrypted = true;
+    }
+    push_log("info", format!("Created vault {}", vault.name));
+    Ok(vault)
+}
+
+/// Make another vault active without restarting: its database, settings and
+/// session replace the current ones, its embedding model is loaded and its
+/// watched folders replace the file watchers. Loaded chat models stay. Encrypted vaults need their passphrase. Refused
+/// while background jobs are queued or running.
+#[tauri::command]
+async fn switch_vault(
+    id: String,
+    passphrase: Option<String>,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<vaults::VaultInfo> {
+    let data_dir = get_app_data_dir();
+    let vault = vaults::get(&data_dir, &id)
+        .ok_or_else(|| error::GhostError::Vault(format!("No vault with ID {}", id)))?;
+    if vault.active {
+        return Ok(vault);
+    }
+    let (queued, running) = state.jobs.depth();
+    if queued + running > 0 || !indexer::progress::active().is_empty() {
+        return Err(error::GhostError::Vault(
+            "Finish or cancel background jobs before switching vaults".into(),
+        )
+        .into());
+    }
+
+    let settings_path = vaults::settings_path(&data_dir, &id);
+    let mut settings = Settings::load(&settings_path);
+    let token_added = settings.mcp_server.ensure_token();
+    let secrets_moved = secrets::store_all_mcp_env(&mut settings.mcp_servers);
+    if token_added || secrets_moved > 0 {
+        settings.save(&settings_path)?;
+    }
+    let db_path = vaults::db_path(&data_dir, &id);
+    let db = tokio::task::spawn_blocking(move || open_vault_db(&db_path, passphrase.as_deref()))
+        .await??;
+    isolation::harden_vault_dir(&vaults::vault_dir(&data_dir, &id));
+
+    // Stored vectors only match the model that produced them
+    if let Some(model) = embeddings::models::find_model(&settings.embedding_model) {
+        if model.id != state.embedding_engine.active_model().id {
+            state.embedding_engine.prepare_model(model).await?;
+            state.embedding_engine.activate_model(model);
+        }
+    }
+    state.embedding_engine.clear_query_cache();
+
+    let _ = state.db.checkpoint();
+    state.db.replace_with(db)?;
+    vaults::set_active(&data_dir, &id)?;
+    apply_settings(&settings);
+    #[cfg(desktop)]
+    let watch_dirs: Vec<PathBuf> = settings
+        .watched_directories
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    *state.settings.lock()? = settings;
+    sync_mcp_roots(&state).await;
+
+    // Stop the previous vault's watchers and watch this vault's folders
+    #[cfg(desktop)]
+    {
+        WATCHER_GENERATION.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if let Err(e) = spawn_watcher(state.inner().clone(), watch_dirs) {
+            push_log("warn", format!("Failed to start watcher: {}", e));
+        }
+    }
+
+    let vault = vaults::VaultInfo {
+        active: true,
+        ..vault
+    };
+    push_log("info", format!("Switched to vault {}", vault.name));
+    resume_vault_jobs(state.inner());
+    let _ = app.emit(vaults::VAULT_SWITCHED_EVENT, &vault);
+    Ok(vault)
+}
+
+/// Whether the active vault is encrypted and waits for `unlock_vault`.
+#[tauri::command]
+async fn is_vault_locked(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<bool> {
+    Ok(state.db.is_locked())
+}
+
+/// Unlock the active vault with its passphrase (encrypted vaults start locked).
+#[tauri::command]
+async fn unlock_vault(
+    passphrase: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    if !state.db.is_locked() {
+        return Ok(());
+    }
+    let db_path = state.db.path().unwrap_or_else(get_db_path);
+    let db =
+        tokio::task::spawn_blocking(move || open_vault_db(&db_path, Some(&passphrase))).await??;
+    state.db.replace_with(db)?;
+    push_log("info", "Vault unlocked".to_string());
+    resume_vault_jobs(state.inner());
+    Ok(())
+}
+
+/// Mark initial setup/onboarding as complete.
+#[tauri::command]
+async fn complete_setup(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    let mut settings = state.settings.lock()?;
+    settings.setup_complete = true;
+    settings.save(&get_settings_path())?;
+    tracing::info!("Initial setup marked as complete");
+    Ok(())
 }
 
 // --- Agent Commands ---
@@ -1432,7 +2625,7 @@ async fn agent_chat(
     conversation_id: Option<i64>,
     state: tauri::State<'_, Arc<AppState>>,
     app: tauri::AppHandle,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     let run_id = format!(
         "run-{}",
         std::time::SystemTime::now()
@@ -1450,48 +2643,112 @@ async fn agent_chat(
             conversation_id
         ),
     );
+    analytics::track(&state.db, analytics::UsageFeature::AgentRun, None);
 
     let state_inner = state.inner().clone();
     let run_id_clone = run_id.clone();
 
     // Subscribe to AG-UI events and forward to Tauri event system
-    let mut rx = state_inner.agui_event_bus.subscribe();
-    let app_clone = app.clone();
-    let run_id_for_listener = run_id.clone();
+    let rx = state_inner.agui_event_bus.subscribe();
+    tokio::spawn(forward_run_events(
+        app.clone(),
+        state_inner.clone(),
+        rx,
+        run_id.clone(),
+    ));
 
+    // Spawn the agent executor in a background task
     tokio::spawn(async move {
-        loop {
-            match rx.recv().await {
-                Ok(event) => {
-                    if event.run_id == run_id_for_listener {
-                        let is_terminal = matches!(
-                            event.event_type,
-                            protocols::agui::EventType::RunFinished
-                                | protocols::agui::EventType::RunError
-                        );
-                        let _ = app_clone.emit("agui://event", &event);
-                        if is_terminal {
-                            break;
-                        }
-                    }
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!("AG-UI event listener lagged by {} events", n);
-                }
-            }
+        let executor = agent::executor::AgentExecutor::new(state_inner.clone());
+        if let Err(e) = executor
+            .run(
+                &run_id_clone,
+                &messages,
+                conversation_id,
+                &state_inner.agui_event_bus,
+            )
+            .await
+        {
+            tracing::error!("Agent run failed: {}", e);
+            push_log("error", format!("Agent run failed: {}", e));
         }
     });
 
-    // Spawn the agent executor in a background task
+    Ok(run_id)
+}
+
+/// Plan a request without carrying it out: only read-only tools run, and the
+/// resulting plan arrives as a `plan_ready` CUSTOM event for the user to
+/// approve with `execute_plan` or drop with `discard_plan`.
+/// Returns the run_id immediately.
+#[tauri::command]
+async fn agent_plan(
+    messages: Vec<chat::ChatMessage>,
+    conversation_id: Option<i64>,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<String> {
+    let state_inner = state.inner().clone();
+    let executor = agent::executor::AgentExecutor::planner(state_inner.clone());
+    let run_id = spawn_agent_run(state_inner, app, executor, messages, conversation_id);
+    push_log("info", format!("Agent plan: run_id={}", run_id));
+    analytics::track(&state.db, analytics::UsageFeature::AgentRun, None);
+    Ok(run_id)
+}
+
+/// Carry out an approved plan as a real agent run. Returns the new run_id.
+#[tauri::command]
+async fn execute_plan(
+    plan_id: String,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<String> {
+    let (messages, conversation_id) = agent::plan::approve(&plan_id)?;
+    let state_inner = state.inner().clone();
+    let executor = agent::executor::AgentExecutor::new(state_inner.clone());
+    let run_id = spawn_agent_run(state_inner, app, executor, messages, conversation_id);
+    push_log(
+        "info",
+        format!("Executing plan {}: run_id={}", plan_id, run_id),
+    );
+    analytics::track(&state.db, analytics::UsageFeature::AgentRun, None);
+    Ok(run_id)
+}
+
+/// Drop a plan the user won't run.
+#[tauri::command]
+async fn discard_plan(plan_id: String) -> CommandResult<()> {
+    Ok(agent::plan::discard(&plan_id)?)
+}
+
+/// Start `executor` on `messages` in the background, forwarding its AG-UI
+/// events to the frontend. Returns the run_id.
+fn spawn_agent_run(
+    state: Arc<AppState>,
+    app: tauri::AppHandle,
+    executor: agent::executor::AgentExecutor,
+    messages: Vec<chat::ChatMessage>,
+    conversation_id: Option<i64>,
+) -> String {
+    let run_id = format!(
+        "run-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    );
+
+    let rx = state.agui_event_bus.subscribe();
+    tokio::spawn(forward_run_events(app, state.clone(), rx, run_id.clone()));
+
+    let run_id_clone = run_id.clone();
     tokio::spawn(async move {
-        let executor = agent::executor::AgentExecutor::new(state_inner.clone());
         if let Err(e) = executor
             .run(
                 &run_id_clone,
                 &messages,
                 conversation_id,
-                &state_inner.agui_event_bus,
+                &state.agui_event_bus,
             )
             .await
         {
@@ -1500,135 +2757,1232 @@ async fn agent_chat(
         }
     });
 
-    Ok(run_id)
+    run_id
+}
+
+/// Buffered AG-UI events of a run after `since_seq` (default 0), so a
+/// reconnecting frontend can recover deltas it missed.
+#[tauri::command]
+async fn get_run_events(
+    run_id: String,
+    since_seq: Option<u64>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<protocols::agui::RunEvents> {
+    Ok(state
+        .agui_event_bus
+        .events_since(&run_id, since_seq.unwrap_or(0))
+        .ok_or_else(|| format!("No buffered events for run {}", run_id))?)
+}
+
+/// Ghost's own CPU, memory, model memory, vault size, and job queue depth.
+#[tauri::command]
+async fn get_resource_usage(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<resources::ResourceUsage> {
+    let state = state.inner().clone();
+    tokio::task::spawn_blocking(move || resources::sample(&state))
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+/// Stream resource usage every `interval_ms` (default 2000) as AG-UI
+/// `STATE_SNAPSHOT` events on `resources://usage`, until stopped.
+#[tauri::command]
+async fn start_resource_monitor(
+    interval_ms: Option<u64>,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<()> {
+    resources::start_monitor(
+        state.inner().clone(),
+        std::time::Duration::from_millis(interval_ms.unwrap_or(2000)),
+        move |event| {
+            let _ = app.emit(resources::RESOURCE_USAGE_EVENT, &event);
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_resource_monitor() -> CommandResult<bool> {
+    Ok(resources::stop_monitor())
+}
+
+/// Backpressure counters for the AG-UI event bus (lag, replay, buffer use).
+#[tauri::command]
+async fn get_agui_bus_metrics(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<protocols::agui::BusMetrics> {
+    Ok(state.agui_event_bus.metrics())
+}
+
+/// Answer a pending tool approval prompt from a running agent.
+/// `decision` is "approve", "always_allow", or "deny".
+#[tauri::command]
+async fn respond_tool_approval(
+    approval_id: String,
+    decision: agent::approval::ApprovalDecision,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    push_log(
+        "info",
+        format!("Tool approval {}: {:?}", approval_id, decision),
+    );
+    if state.tool_approvals.respond(&approval_id, decision) {
+        Ok(())
+    } else {
+        Err(format!(
+            "No pending approval '{}' (already answered or timed out)",
+            approval_id
+        )
+        .into())
+    }
+}
+
+/// Answer an input request (elicitation) from an external MCP server.
+/// `action` is "accept" (with `content`), "decline", or "cancel".
+#[tauri::command]
+async fn respond_elicitation(
+    elicitation_id: String,
+    action: rmcp::model::ElicitationAction,
+    content: Option<serde_json::Value>,
+) -> CommandResult<()> {
+    push_log(
+        "info",
+        format!("Elicitation {}: {:?}", elicitation_id, action),
+    );
+    Ok(protocols::mcp_elicitation::respond(
+        &elicitation_id,
+        action,
+        content,
+    )?)
+}
+
+/// List tool approvals still waiting for an answer (e.g. after a UI reload).
+#[tauri::command]
+async fn list_pending_approvals(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<String>> {
+    Ok(state.tool_approvals.pending_ids())
+}
+
+/// Create a new conversation.
+#[tauri::command]
+async fn create_conversation(
+    title: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<i64> {
+    Ok(agent::memory::create_conversation(&state.db, &title)?)
+}
+
+/// List all conversations.
+#[tauri::command]
+async fn list_conversations(
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::memory::Conversation>> {
+    let limit = limit.unwrap_or(50);
+    Ok(agent::memory::list_conversations(&state.db, limit)?)
+}
+
+/// Get messages for a conversation.
+#[tauri::command]
+async fn get_conversation_messages(
+    conversation_id: i64,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::memory::Message>> {
+    Ok(agent::memory::get_messages(
+        &state.db,
+        conversation_id,
+        limit,
+    )?)
+}
+
+/// Delete a conversation.
+#[tauri::command]
+async fn delete_conversation(
+    conversation_id: i64,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    Ok(agent::memory::delete_conversation(
+        &state.db,
+        conversation_id,
+    )?)
+}
+
+/// Update conversation title.
+#[tauri::command]
+async fn update_conversation_title(
+    conversation_id: i64,
+    title: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    Ok(agent::memory::update_conversation_title(
+        &state.db,
+        conversation_id,
+        &title,
+    )?)
+}
+
+/// Switch a conversation between local only and allowing the remote provider.
+#[tauri::command]
+async fn set_conversation_cloud(
+    conversation_id: i64,
+    allow_cloud: bool,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    agent::memory::set_conversation_cloud(&state.db, conversation_id, allow_cloud)?;
+    push_log(
+        "info",
+        format!(
+            "Conversation {} is now {}",
+            conversation_id,
+            if allow_cloud {
+                "cloud-allowed"
+            } else {
+                "local only"
+            }
+        ),
+    );
+    Ok(())
+}
+
+/// Pin an indexed document or folder to a conversation so its searches
+/// only cover the attached sources.
+#[tauri::command]
+async fn attach_to_conversation(
+    conversation_id: i64,
+    path: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::memory::Attachment> {
+    Ok(agent::memory::attach_to_conversation(
+        &state.db,
+        conversation_id,
+        &path,
+    )?)
+}
+
+/// Unpin a document or folder from a conversation.
+#[tauri::command]
+async fn detach_from_conversation(
+    conversation_id: i64,
+    path: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<bool> {
+    Ok(agent::memory::detach_from_conversation(
+        &state.db,
+        conversation_id,
+        &path,
+    )?)
+}
+
+/// Documents and folders pinned to a conversation.
+#[tauri::command]
+async fn list_conversation_attachments(
+    conversation_id: i64,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::memory::Attachment>> {
+    Ok(agent::memory::list_attachments(&state.db, conversation_id)?)
+}
+
+/// Summarize an indexed document with the local model (map-reduce over its
+/// chunks). Cached by content hash until the file changes.
+#[tauri::command]
+async fn summarize_document(
+    document_id: i64,
+    style: agent::summarize::SummaryStyle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::summarize::Summary> {
+    Ok(agent::summarize::summarize_document(&state, document_id, style).await?)
+}
+
+/// Distill a conversation into a Markdown note saved in a watched folder.
+#[tauri::command]
+async fn distill_conversation(
+    conversation_id: i64,
+    destination: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::distill::DistilledNote> {
+    Ok(agent::distill::distill_conversation(&state, conversation_id, &destination).await?)
+}
+
+/// Suggest a descriptive name for a file based on its content.
+#[tauri::command]
+async fn suggest_filename(
+    path: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::rename::FilenameSuggestion> {
+    Ok(agent::rename::suggest_filename(&state, &path).await?)
+}
+
+/// Suggest names for the generically named files in a folder
+/// (every file with `include_all`).
+#[tauri::command]
+async fn suggest_folder_filenames(
+    folder: String,
+    include_all: Option<bool>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::rename::FilenameSuggestion>> {
+    Ok(
+        agent::rename::suggest_folder_filenames(&state, &folder, include_all.unwrap_or(false))
+            .await?,
+    )
+}
+
+/// Rename a file (e.g. to an accepted suggestion), keeping the index in sync.
+/// Returns the new path.
+#[tauri::command]
+async fn rename_file(
+    path: String,
+    new_name: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<String> {
+    Ok(agent::rename::rename_file(&state, &path, &new_name).await?)
+}
+
+/// Translate text into a language (e.g. "Spanish") with the local model.
+#[tauri::command]
+async fn translate_text(
+    text: String,
+    target_language: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::language::Translation> {
+    Ok(agent::language::translate(&state, &text, &target_language).await?)
+}
+
+/// Search across conversation memory.
+#[tauri::command]
+async fn search_memory(
+    query: String,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::memory::Message>> {
+    let limit = limit.unwrap_or(20);
+    Ok(agent::memory::search_conversations(
+        &state.db, &query, limit,
+    )?)
+}
+
+/// Get the agent's shell command audit log (newest first).
+#[tauri::command]
+async fn get_agent_audit_log(
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::audit::CommandAuditEntry>> {
+    let limit = limit.unwrap_or(100).min(1000);
+    Ok(agent::audit::get_audit_log(&state.db, limit)?)
+}
+
+/// Get agent configuration.
+#[tauri::command]
+async fn get_agent_config(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::config::AgentConfig> {
+    let settings = state.settings.lock()?;
+    Ok(settings.agent_config.clone())
+}
+
+/// Save agent configuration.
+#[tauri::command]
+async fn save_agent_config(
+    config: agent::config::AgentConfig,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    let mut settings = state.settings.lock()?;
+    settings.agent_config = config;
+    Ok(settings.save(&get_settings_path())?)
+}
+
+/// List available agent model tiers and which one is recommended.
+#[tauri::command]
+async fn get_agent_model_tiers(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<serde_json::Value> {
+    let (recommended, ctx) = agent::config::recommend_agent_model(&state.hardware);
+    let tiers: Vec<serde_json::Value> = agent::config::AGENT_MODEL_TIERS
+        .iter()
+        .map(|t| {
+            serde_json::json!({
+                "model_id": t.model_id,
+                "name": t.name,
+                "min_ram_mb": t.min_ram_mb,
+                "recommended_ctx": t.recommended_ctx,
+                "tool_calling_reliable": t.tool_calling_reliable,
+                "quality": t.quality,
+                "approx_usage_mb": t.approx_usage_mb,
+                "is_recommended": t.model_id == recommended.model_id,
+            })
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "tiers": tiers,
+        "recommended_model": recommended.model_id,
+        "recommended_ctx": ctx,
+        "available_ram_mb": state.hardware.available_ram_mb,
+    }))
+}
+
+/// List loaded skills.
+#[tauri::command]
+async fn list_skills() -> CommandResult<Vec<agent::skills::Skill>> {
+    let registry = agent::skills::loaded();
+    Ok(registry.all_skills().into_iter().cloned().collect())
+}
+
+/// List the community skills of the configured marketplace.
+#[tauri::command]
+async fn list_marketplace_skills(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<agent::skills::marketplace::MarketplaceSkill>> {
+    let (config, skills_dir) = marketplace_settings(&state)?;
+    Ok(agent::skills::marketplace::list(&config, std::path::Path::new(&skills_dir)).await?)
+}
+
+/// Install (or update) a marketplace skill into the skills directory.
+#[tauri::command]
+async fn install_marketplace_skill(
+    name: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::skills::Skill> {
+    let (config, skills_dir) = marketplace_settings(&state)?;
+    let skill =
+        agent::skills::marketplace::install(&config, std::path::Path::new(&skills_dir), &name)
+            .await?;
+    push_log("info", format!("Installed skill '{}'", skill.name));
+    Ok(skill)
+}
+
+fn marketplace_settings(
+    state: &AppState,
+) -> error::Result<(agent::skills::marketplace::MarketplaceConfig, String)> {
+    let settings = state.settings.lock()?;
+    if settings.agent_config.skills_dir.is_empty() {
+        return Err(error::GhostError::Agent(
+            "Set a skills directory first".into(),
+        ));
+    }
+    Ok((
+        settings.skill_marketplace.clone(),
+        settings.agent_config.skills_dir.clone(),
+    ))
+}
+
+/// Run a skill with the agent, filling its template with `params`.
+/// Streams AG-UI events like `agent_chat` and returns the run_id immediately.
+#[tauri::command]
+async fn run_skill(
+    name: String,
+    params: Option<serde_json::Map<String, serde_json::Value>>,
+    state: tauri::State<'_, Arc<AppState>>,
+    app: tauri::AppHandle,
+) -> CommandResult<String> {
+    let registry = agent::skills::loaded();
+    let skill = registry
+        .get(&name)
+        .filter(|s| s.enabled)
+        .cloned()
+        .ok_or_else(|| format!("No enabled skill named '{}'", name))?;
+    let args = agent::skills::string_args(params.unwrap_or_default());
+    // Fail fast on missing parameters instead of in the background run
+    skill.render_prompt(&args)?;
+
+    let run_id = format!(
+        "run-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    );
+    push_log(
+        "info",
+        format!("Skill run: run_id={}, skill={}", run_id, name),
+    );
+    analytics::track(&state.db, analytics::UsageFeature::AgentRun, None);
+
+    let state_inner = state.inner().clone();
+    let rx = state_inner.agui_event_bus.subscribe();
+    tokio::spawn(forward_run_events(
+        app.clone(),
+        state_inner.clone(),
+        rx,
+        run_id.clone(),
+    ));
+
+    let run_id_clone = run_id.clone();
+    tokio::spawn(async move {
+        let executor = agent::executor::AgentExecutor::new(state_inner.clone());
+        if let Err(e) = executor
+            .run_skill(&run_id_clone, &skill, &args, &state_inner.agui_event_bus)
+            .await
+        {
+            tracing::error!("Skill run failed: {}", e);
+            push_log("error", format!("Skill '{}' failed: {}", skill.name, e));
+        }
+    });
+
+    Ok(run_id)
+}
+
+// --- A2A (Agent-to-Agent) ---
+
+/// List configured remote A2A agents.
+#[tauri::command]
+async fn list_a2a_agents(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<protocols::A2aAgentEntry>> {
+    let settings = state.settings.lock()?;
+    Ok(settings.a2a_agents.clone())
+}
+
+/// Fetch a remote agent's Agent Card (used to preview before adding it).
+#[tauri::command]
+async fn discover_a2a_agent(url: String) -> CommandResult<protocols::a2a::AgentCard> {
+    protocols::a2a::fetch_agent_card(&url)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+/// Add (or replace) a remote A2A agent entry in settings.
+#[tauri::command]
+async fn add_a2a_agent(
+    entry: protocols::A2aAgentEntry,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    let mut settings = state.settings.lock()?;
+    settings.a2a_agents.retain(|a| a.name != entry.name);
+    push_log(
+        "info",
+        format!("A2A agent added: {} ({})", entry.name, entry.url),
+    );
+    settings.a2a_agents.push(entry);
+    Ok(settings.save(&get_settings_path())?)
+}
+
+/// Remove a remote A2A agent entry from settings.
+#[tauri::command]
+async fn remove_a2a_agent(
+    name: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    let mut settings = state.settings.lock()?;
+    settings.a2a_agents.retain(|a| a.name != name);
+    Ok(settings.save(&get_settings_path())?)
+}
+
+/// Delegate a task to a configured A2A agent and wait for its answer.
+#[tauri::command]
+async fn delegate_a2a_task(
+    agent: String,
+    task: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<String> {
+    let url = {
+        let settings = state.settings.lock()?;
+        settings
+            .a2a_agents
+            .iter()
+            .find(|a| a.name == agent)
+            .map(|a| a.url.clone())
+            .ok_or_else(|| format!("Unknown A2A agent: {}", agent))?
+    };
+    protocols::a2a::delegate_task(&url, &task)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+/// List tasks received from remote agents via Ghost's A2A endpoint.
+#[tauri::command]
+async fn list_a2a_tasks(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<protocols::a2a::Task>> {
+    Ok(state.a2a_tasks.list(&Default::default()))
+}
+
+// --- Usage Analytics (local-only) ---
+
+/// Get the local usage dashboard for the last `days` days (default: 30).
+#[tauri::command]
+async fn get_usage_dashboard(
+    days: Option<u32>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<analytics::UsageDashboard> {
+    Ok(analytics::get_dashboard(&state.db, days.unwrap_or(30))?)
+}
+
+/// Export the usage dashboard as pretty JSON for the user to attach to a bug report.
+/// Nothing is sent anywhere — the frontend decides what to do with the text.
+#[tauri::command]
+async fn export_usage_report(
+    days: Option<u32>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<String> {
+    let dashboard = analytics::get_dashboard(&state.db, days.unwrap_or(30))?;
+    let report = serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "usage": dashboard,
+    });
+    serde_json::to_string_pretty(&report).map_err(|e| e.to_string().into())
+}
+
+/// Delete all locally recorded usage data.
+#[tauri::command]
+async fn clear_usage_data(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    push_log("info", "Usage data cleared".to_string());
+    Ok(analytics::clear(&state.db)?)
+}
+
+// --- Telemetry (opt-in, `telemetry` feature only) ---
+
+/// Prepare (or return the pending) anonymized telemetry report for review.
+#[cfg(feature = "telemetry")]
+#[tauri::command]
+async fn preview_telemetry_report(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<telemetry::TelemetryReport> {
+    let settings = state.settings.lock()?.telemetry.clone();
+    telemetry::preview_report(&state.db, &settings)
+}
+
+/// Send a previewed report exactly as shown (kept locally in local-only mode).
+#[cfg(feature = "telemetry")]
+#[tauri::command]
+async fn send_telemetry_report(
+    id: i64,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<String> {
+    let settings = state.settings.lock()?.telemetry.clone();
+    telemetry::send_report(&state.db, &settings, id).await
+}
+
+/// Drop a pending telemetry report without sending it.
+#[cfg(feature = "telemetry")]
+#[tauri::command]
+async fn discard_telemetry_report(
+    id: i64,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    telemetry::discard_report(&state.db, id)
+}
+
+/// Past telemetry reports and their status, newest first.
+#[cfg(feature = "telemetry")]
+#[tauri::command]
+async fn list_telemetry_reports(
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<telemetry::TelemetryReport>> {
+    telemetry::list_reports(&state.db, limit.unwrap_or(20))
+}
+
+// --- IDE Integration ---
+
+/// Rank vault context (notes, docs, related code) for an editor workspace.
+#[tauri::command]
+async fn get_workspace_context(
+    request: search::workspace::WorkspaceContextRequest,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<search::workspace::WorkspaceContextItem>> {
+    Ok(search::workspace::workspace_context(&state.db, &state.embedding_engine, &request).await?)
+}
+
+// --- SQL Console (read-only) ---
+
+/// Run a read-only SELECT over the vault database (row and time limited).
+#[tauri::command]
+async fn run_readonly_sql(
+    query: String,
+    max_rows: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<db::query::SqlQueryResult> {
+    let state = state.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        db::query::run_readonly_sql(&state.db, &query, max_rows)
+    })
+    .await??)
+}
+
+// --- Vault Maintenance ---
+
+/// Rebuild the vault database to return free space to the OS.
+/// Blocks writes (indexing) while it runs.
+#[tauri::command]
+async fn vacuum_database(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<db::maintenance::MaintenanceReport> {
+    let state = state.inner().clone();
+    push_log("info", "Vacuuming vault database".into());
+    Ok(tokio::task::spawn_blocking(move || db::maintenance::vacuum(&state.db)).await??)
+}
+
+/// Check the vault database and full-text index for corruption.
+/// `quick` (default false) skips the slower index consistency checks.
+#[tauri::command]
+async fn run_integrity_check(
+    quick: Option<bool>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<db::maintenance::IntegrityReport> {
+    let state = state.inner().clone();
+    let quick = quick.unwrap_or(false);
+    Ok(
+        tokio::task::spawn_blocking(move || db::maintenance::integrity_check(&state.db, quick))
+            .await??,
+    )
+}
+
+/// Merge the full-text index and refresh query-planner statistics.
+#[tauri::command]
+async fn optimize_fts(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<db::maintenance::MaintenanceReport> {
+    let state = state.inner().clone();
+    Ok(tokio::task::spawn_blocking(move || db::maintenance::optimize_fts(&state.db)).await??)
+}
+
+// --- Performance Self-Test ---
+
+/// Time chunking, keyword search, vector search and RRF merging on a
+/// synthetic in-memory vault and compare each against its budget.
+#[tauri::command]
+async fn run_perf_selftest() -> CommandResult<perf::PerfReport> {
+    push_log("info", "Running performance self-test".into());
+    Ok(tokio::task::spawn_blocking(perf::run_selftest).await??)
+}
+
+// --- Built-in Help ---
+
+/// Answer a question about Ghost itself from the embedded knowledge pack.
+#[tauri::command]
+async fn ghost_help(
+    question: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<help::HelpAnswer> {
+    let question = question.trim();
+    if question.is_empty() {
+        return Err("Question cannot be empty".into());
+    }
+    Ok(help::answer(&state.chat_engine, question).await)
+}
+
+// --- Re-embedding ---
+
+/// Get the progress of the vault re-embedding migration.
+#[tauri::command]
+async fn get_reembed_status(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<embeddings::migration::ReembedStatus> {
+    Ok(state.reembed_job.status(&state.db)?)
+}
+
+/// List the embedding models that can be selected, marking the active one.
+#[tauri::command]
+async fn list_embedding_models(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<embeddings::models::EmbeddingModelInfo>> {
+    Ok(embeddings::models::list_models(
+        state.embedding_engine.active_model().id,
+    ))
+}
+
+/// Re-embed the whole vault with another embedding model (a registry ID, or
+/// a backend name: "native" or "ollama"). The model is downloaded first if
+/// needed. Search keeps working on the old index until the migration completes.
+#[tauri::command]
+async fn start_reembedding(
+    backend: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    let target = embeddings::models::find_target(&backend)
+        .ok_or_else(|| format!("Unknown embedding model: {}", backend))?;
+    if state.embedding_engine.backend() != embeddings::AiBackend::None
+        && state.embedding_engine.active_model().id == target.id
+    {
+        return Err(format!("The vault already uses {}", target.name).into());
+    }
+    state
+        .reembed_job
+        .start(&state.db, &state.embedding_engine, target)
+        .await?;
+    push_log("info", format!("Re-embedding vault with {}", target.name));
+    jobs::submit(
+        state.inner(),
+        jobs::JobSpec::Reembed,
+        jobs::JobPriority::Normal,
+    )?;
+    Ok(())
+}
+
+/// Pause the re-embedding migration after the current batch.
+#[tauri::command]
+async fn pause_reembedding(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    if state.reembed_job.is_running() {
+        state.reembed_job.request_pause();
+        Ok(())
+    } else {
+        Ok(state.db.set_vec_migration_paused(true)?)
+    }
+}
+
+/// Resume a paused re-embedding migration from where it stopped.
+#[tauri::command]
+async fn resume_reembedding(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    if state.reembed_job.is_running() {
+        return Ok(());
+    }
+    if state.db.get_vec_migration()?.is_none() {
+        return Err("No re-embedding migration in progress".into());
+    }
+    jobs::submit(
+        state.inner(),
+        jobs::JobSpec::Reembed,
+        jobs::JobPriority::Normal,
+    )?;
+    Ok(())
+}
+
+/// Abandon the re-embedding migration and keep the current index.
+#[tauri::command]
+async fn cancel_reembedding(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.reembed_job.request_pause();
+    for _ in 0..100 {
+        if !state.reembed_job.is_running() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    if state.reembed_job.is_running() {
+        return Err("Re-embedding is still finishing a batch, try again".into());
+    }
+    push_log("info", "Re-embedding cancelled".to_string());
+    Ok(state.db.cancel_vec_migration()?)
+}
+
+// --- Embedding Cache ---
+
+/// Query and chunk embedding cache statistics.
+#[tauri::command]
+async fn get_embedding_cache_stats(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<embeddings::cache::EmbeddingCacheStats> {
+    let (query_entries, query_hits, query_misses) = state.embedding_engine.query_cache_stats();
+    Ok(embeddings::cache::EmbeddingCacheStats {
+        query_entries,
+        query_hits,
+        query_misses,
+        stored_embeddings: embeddings::cache::stored_count(&state.db)?,
+    })
+}
+
+/// Drop cached query embeddings and stored chunk embeddings.
+#[tauri::command]
+async fn clear_embedding_cache(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    state.embedding_engine.clear_query_cache();
+    push_log("info", "Embedding cache cleared".to_string());
+    Ok(embeddings::cache::clear(&state.db)?)
+}
+
+// --- Downloads ---
+
+/// Directory indexing runs in flight, with progress and ETA.
+#[tauri::command]
+async fn get_active_index_jobs() -> CommandResult<Vec<indexer::progress::IndexProgress>> {
+    Ok(indexer::progress::active())
+}
+
+/// Power source, CPU temperature, and whether background indexing is held.
+#[tauri::command]
+async fn get_throttle_status() -> CommandResult<indexer::throttle::ThrottleStatus> {
+    tokio::task::spawn_blocking(indexer::throttle::status)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+/// Stop an indexing run before its next file. Files already indexed stay.
+#[tauri::command]
+async fn cancel_index_job(id: u64) -> CommandResult<()> {
+    if indexer::progress::cancel(id) {
+        Ok(())
+    } else {
+        Err(format!("Indexing run {} is not active", id).into())
+    }
+}
+
+/// Model and runtime downloads currently in progress.
+#[tauri::command]
+async fn list_active_downloads() -> CommandResult<Vec<downloads::ActiveDownload>> {
+    Ok(downloads::manager().list_active())
+}
+
+/// Whether the current network connection is metered (`null` = unknown).
+#[tauri::command]
+async fn is_metered_connection() -> CommandResult<Option<bool>> {
+    tokio::task::spawn_blocking(downloads::is_metered_connection)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+// --- Vault Isolation ---
+
+/// Re-run the per-user isolation checks on the app data dir.
+#[tauri::command]
+async fn get_vault_isolation_report() -> CommandResult<isolation::IsolationReport> {
+    Ok(isolation::check_and_harden(&get_app_data_dir()))
+}
+
+// --- Support Snapshots ---
+
+/// Bundle settings, vault info, models, MCP config (redacted), and job states
+/// into a zip for bug reports. Returns the archive path.
+#[tauri::command]
+async fn create_state_snapshot(
+    path: Option<String>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<String> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => get_app_data_dir().join("snapshots").join(format!(
+            "ghost-snapshot-{}.zip",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        )),
+    };
+    snapshot::create_state_snapshot(&state, &path).await?;
+    push_log(
+        "info",
+        format!("State snapshot written to {}", path.display()),
+    );
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Restore the settings from a state snapshot (development builds only).
+#[tauri::command]
+async fn load_state_snapshot(
+    path: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<snapshot::LoadedSnapshot> {
+    let settings_path = get_settings_path();
+    let (settings, loaded) =
+        snapshot::load_state_snapshot(std::path::Path::new(&path), &settings_path)?;
+    *state.settings.lock()? = settings;
+    push_log("info", format!("State snapshot loaded from {}", path));
+    Ok(loaded)
+}
+
+// --- Jobs ---
+
+/// Recent background jobs (queued, running, and finished), newest first.
+#[tauri::command]
+async fn list_jobs(
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<jobs::JobInfo>> {
+    Ok(jobs::list_jobs(&state.db, limit.unwrap_or(50))?)
+}
+
+/// Cancel a queued or running background job.
+#[tauri::command]
+async fn cancel_job(id: i64, state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    Ok(jobs::cancel(state.inner(), id)?)
+}
+
+/// Pause a queued or running indexing job until `resume_job`, even across restarts.
+#[tauri::command]
+async fn pause_job(id: i64, state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    Ok(jobs::pause(state.inner(), id)?)
+}
+
+/// Queue a paused job again; it skips files indexed before the pause.
+#[tauri::command]
+async fn resume_job(id: i64, state: tauri::State<'_, Arc<AppState>>) -> CommandResult<()> {
+    Ok(jobs::resume(state.inner(), id)?)
+}
+
+// --- Reminders ---
+
+/// Open reminders due within `days` (default 7, overdue included), soonest
+/// first, then undated TODOs when `include_undated` is set.
+#[tauri::command]
+async fn list_upcoming_reminders(
+    days: Option<u32>,
+    include_undated: Option<bool>,
+    limit: Option<usize>,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<reminders::Reminder>> {
+    Ok(reminders::list_upcoming(
+        &state.db,
+        chrono::Local::now().naive_local(),
+        days.unwrap_or(7),
+        include_undated.unwrap_or(false),
+        limit.unwrap_or(100),
+    )?)
+}
+
+/// Mark a reminder done, or open it again.
+#[tauri::command]
+async fn set_reminder_done(
+    id: i64,
+    done: bool,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<()> {
+    Ok(reminders::set_done(&state.db, id, done)?)
+}
+
+// --- Tags ---
+
+/// Tag a document. Returns the document's tags afterwards.
+#[tauri::command]
+async fn add_document_tag(
+    document_id: i64,
+    tag: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<String>> {
+    Ok(state
+        .db
+        .add_document_tag(document_id, &tag, "user")
+        .and_then(|_| state.db.get_document_tags(document_id))?)
+}
+
+/// Remove a tag from a document, whoever added it. Returns the document's
+/// tags afterwards.
+#[tauri::command]
+async fn remove_document_tag(
+    document_id: i64,
+    tag: String,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<String>> {
+    Ok(state
+        .db
+        .remove_document_tag(document_id, &tag)
+        .and_then(|_| state.db.get_document_tags(document_id))?)
+}
+
+/// Tags of one document, alphabetically.
+#[tauri::command]
+async fn get_document_tags(
+    document_id: i64,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<Vec<String>> {
+    Ok(state.db.get_document_tags(document_id)?)
+}
+
+/// Languages detected in the vault, and whether a multilingual embedding
+/// model would help cross-language search.
+#[tauri::command]
+async fn get_vault_languages(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<indexer::language::VaultLanguages> {
+    let languages = state.db.language_counts()?;
+    Ok(indexer::language::VaultLanguages::new(
+        languages,
+        state.embedding_engine.active_model().multilingual,
+    ))
+}
+
+/// Every tag in use, with how many documents carry it.
+#[tauri::command]
+async fn list_tags(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<Vec<db::TagCount>> {
+    Ok(state.db.list_tags()?)
 }
 
-/// Create a new conversation.
+// --- Vault Snapshots ---
+
+/// Snapshot the vault now (metadata and chunk hashes only).
 #[tauri::command]
-async fn create_conversation(
-    title: String,
+async fn create_vault_snapshot(
+    label: Option<String>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<i64, String> {
-    agent::memory::create_conversation(&state.db, &title).map_err(|e| e.to_string())
+) -> CommandResult<vault_snapshots::VaultSnapshot> {
+    let keep = state.settings.lock()?.vault_snapshot_keep;
+    Ok(vault_snapshots::create_snapshot(
+        &state.db,
+        label.as_deref(),
+        keep,
+    )?)
 }
 
-/// List all conversations.
+/// List vault snapshots, newest first.
 #[tauri::command]
-async fn list_conversations(
-    limit: Option<usize>,
+async fn list_vault_snapshots(
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<agent::memory::Conversation>, String> {
-    let limit = limit.unwrap_or(50);
-    agent::memory::list_conversations(&state.db, limit).map_err(|e| e.to_string())
+) -> CommandResult<Vec<vault_snapshots::VaultSnapshot>> {
+    Ok(vault_snapshots::list_snapshots(&state.db)?)
 }
 
-/// Get messages for a conversation.
+/// Delete a vault snapshot.
 #[tauri::command]
-async fn get_conversation_messages(
-    conversation_id: i64,
-    limit: Option<usize>,
+async fn delete_vault_snapshot(
+    id: i64,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<agent::memory::Message>, String> {
-    agent::memory::get_messages(&state.db, conversation_id, limit).map_err(|e| e.to_string())
+) -> CommandResult<()> {
+    Ok(vault_snapshots::delete_snapshot(&state.db, id)?)
 }
 
-/// Delete a conversation.
+/// Files removed, modified, and added since a snapshot.
 #[tauri::command]
-async fn delete_conversation(
-    conversation_id: i64,
+async fn diff_vault_snapshot(
+    id: i64,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    agent::memory::delete_conversation(&state.db, conversation_id).map_err(|e| e.to_string())
+) -> CommandResult<vault_snapshots::SnapshotDiff> {
+    Ok(vault_snapshots::diff_snapshot(&state.db, id)?)
 }
 
-/// Update conversation title.
+/// Search the vault as it was when a snapshot was taken.
 #[tauri::command]
-async fn update_conversation_title(
-    conversation_id: i64,
-    title: String,
+async fn search_vault_snapshot(
+    id: i64,
+    query: String,
+    limit: Option<usize>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    agent::memory::update_conversation_title(&state.db, conversation_id, &title)
-        .map_err(|e| e.to_string())
+) -> CommandResult<vault_snapshots::PointInTimeResults> {
+    Ok(vault_snapshots::search_snapshot(
+        &state.db,
+        &state.embedding_engine,
+        id,
+        &query,
+        limit.unwrap_or(20),
+    )
+    .await?)
 }
 
-/// Search across conversation memory.
+// --- Duplicates ---
+
+/// Queue a near-duplicate scan now. Returns the job ID.
 #[tauri::command]
-async fn search_memory(
-    query: String,
+async fn scan_duplicates(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<i64> {
+    Ok(jobs::submit(
+        state.inner(),
+        jobs::JobSpec::DetectDuplicates,
+        jobs::JobPriority::High,
+    )?)
+}
+
+/// Duplicate groups from the last scan, with wasted space and the copies
+/// that could be cleaned up.
+#[tauri::command]
+async fn get_duplicate_report(
     limit: Option<usize>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<agent::memory::Message>, String> {
-    let limit = limit.unwrap_or(20);
-    agent::memory::search_conversations(&state.db, &query, limit).map_err(|e| e.to_string())
+) -> CommandResult<duplicates::DuplicateReport> {
+    Ok(duplicates::report(&state.db, limit.unwrap_or(100))?)
 }
 
-/// Get agent configuration.
+// --- Importers ---
+
+/// Queue a browser history and bookmarks import now. Returns the job ID.
 #[tauri::command]
-async fn get_agent_config(
+async fn import_browser_data(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<i64> {
+    Ok(jobs::submit(
+        state.inner(),
+        jobs::JobSpec::ImportBrowser,
+        jobs::JobPriority::High,
+    )?)
+}
+
+/// Browser profiles found on this computer, for the import settings.
+#[tauri::command]
+fn list_browser_profiles() -> Vec<indexer::importers::browser::BrowserProfile> {
+    use indexer::importers::browser::{find_profiles, Browser};
+    find_profiles(&[Browser::Chrome, Browser::Edge, Browser::Firefox])
+}
+
+/// Queue an import of the Obsidian or Logseq vault or Notion export at
+/// `path`. Returns the job ID.
+#[tauri::command]
+async fn import_notes_vault(
+    path: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<agent::config::AgentConfig, String> {
-    let settings = state.settings.lock().map_err(|e| e.to_string())?;
-    Ok(settings.agent_config.clone())
+) -> CommandResult<i64> {
+    if !std::path::Path::new(&path).is_dir() {
+        return Err(format!("{} is not a folder", path).into());
+    }
+    Ok(jobs::submit(
+        state.inner(),
+        jobs::JobSpec::ImportNotes { path },
+        jobs::JobPriority::High,
+    )?)
 }
 
-/// Save agent configuration.
+/// Queue an Outlook and Apple Mail import now (`mail-import` feature).
+/// Returns the job ID.
+#[cfg(feature = "mail-import")]
 #[tauri::command]
-async fn save_agent_config(
-    config: agent::config::AgentConfig,
+async fn import_mail(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<i64> {
+    jobs::submit(
+        state.inner(),
+        jobs::JobSpec::ImportMail,
+        jobs::JobPriority::High,
+    )
+}
+
+/// Outgoing links, backlinks and tags of an imported note.
+#[tauri::command]
+async fn get_note_links(
+    path: String,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), String> {
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
-    settings.agent_config = config;
-    settings
-        .save(&get_app_data_dir().join("settings.json"))
-        .map_err(|e| e.to_string())
+) -> CommandResult<indexer::importers::notes::NoteLinks> {
+    Ok(indexer::importers::notes::note_links(&state.db, &path)?)
 }
 
-/// List available agent model tiers and which one is recommended.
+// --- Digests ---
+
+/// Queue a "what's new in my files" digest now. Returns the job ID.
 #[tauri::command]
-async fn get_agent_model_tiers(
+async fn run_digest(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<i64> {
+    Ok(jobs::submit(
+        state.inner(),
+        jobs::JobSpec::Digest,
+        jobs::JobPriority::High,
+    )?)
+}
+
+/// Recent digests, newest first. Each links to the conversation holding it.
+#[tauri::command]
+async fn list_digests(
+    limit: Option<usize>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<serde_json::Value, String> {
-    let (recommended, ctx) = agent::config::recommend_agent_model(&state.hardware);
-    let tiers: Vec<serde_json::Value> = agent::config::AGENT_MODEL_TIERS
-        .iter()
-        .map(|t| {
-            serde_json::json!({
-                "model_id": t.model_id,
-                "name": t.name,
-                "min_ram_mb": t.min_ram_mb,
-                "recommended_ctx": t.recommended_ctx,
-                "tool_calling_reliable": t.tool_calling_reliable,
-                "quality": t.quality,
-                "approx_usage_mb": t.approx_usage_mb,
-                "is_recommended": t.model_id == recommended.model_id,
-            })
-        })
-        .collect();
+) -> CommandResult<Vec<digest::Digest>> {
+    Ok(digest::list_digests(&state.db, limit.unwrap_or(20))?)
+}
 
-    Ok(serde_json::json!({
-        "tiers": tiers,
-        "recommended_model": recommended.model_id,
-        "recommended_ctx": ctx,
-        "available_ram_mb": state.hardware.available_ram_mb,
-    }))
+// --- Knowledge Graph ---
+
+/// Queue entity and relation extraction over chunks not read yet. Returns
+/// the job ID.
+#[tauri::command]
+async fn build_knowledge_graph(state: tauri::State<'_, Arc<AppState>>) -> CommandResult<i64> {
+    push_log("info", "Building knowledge graph".to_string());
+    Ok(jobs::submit(
+        state.inner(),
+        jobs::JobSpec::BuildKnowledgeGraph,
+        jobs::JobPriority::High,
+    )?)
 }
 
-/// List loaded skills.
+/// Entities matching `query` and the relations within `depth` hops.
 #[tauri::command]
-async fn list_skills(
+async fn query_knowledge_graph(
+    query: String,
+    depth: Option<usize>,
+    limit: Option<usize>,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<Vec<agent::skills::Skill>, String> {
-    let skills_dir = state
-        .settings
-        .lock()
-        .map(|s| s.agent_config.skills_dir.clone())
-        .unwrap_or_default();
+) -> CommandResult<agent::knowledge::KnowledgeGraph> {
+    Ok(agent::knowledge::query(
+        &state.db,
+        &query,
+        depth.unwrap_or(1),
+        limit.unwrap_or(10),
+    )?)
+}
 
-    let mut registry = agent::skills::SkillRegistry::new();
-    registry.load_from_directory(std::path::Path::new(&skills_dir));
-    Ok(registry.all_skills().into_iter().cloned().collect())
+/// Graph size and extraction progress.
+#[tauri::command]
+async fn get_knowledge_graph_stats(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> CommandResult<agent::knowledge::KnowledgeStats> {
+    Ok(agent::knowledge::stats(&state.db)?)
 }
 
 // --- App Setup ---
@@ -1652,6 +4006,19 @@ pub fn run() {
         format!("Starting Ghost v{}", env!("CARGO_PKG_VERSION")),
     );
 
+    // Crash detection: report an unclean previous exit, capture panics from now on
+    if let Some(report) = crash::install(&get_app_data_dir()) {
+        let cause = report
+            .panic
+            .as_ref()
+            .map_or("no panic captured".to_string(), |p| p.message.clone());
+        tracing::warn!("Previous session crashed ({})", cause);
+        push_log(
+            "warn",
+            format!("Previous session crashed ({}); see the crash report", cause),
+        );
+    }
+
     // Log edition info
     let ext = extensions::extensions();
     if ext.is_licensed() {
@@ -1675,9 +4042,36 @@ pub fn run() {
         ),
     );
 
-    // --- Step 2: Load settings ---
-    let settings_path = get_app_data_dir().join("settings.json");
-    let settings = Settings::load(&settings_path);
+    // --- Step 2: Load the active vault's settings ---
+    let vault_id = vaults::init(&get_app_data_dir());
+    if vault_id != vaults::DEFAULT_VAULT_ID {
+        push_log("info", format!("Active vault: {}", vault_id));
+    }
+    let settings_path = get_settings_path();
+    let mut settings = Settings::load(&settings_path);
+    let token_added = settings.mcp_server.ensure_token();
+    let secrets_moved = secrets::store_all_mcp_env(&mut settings.mcp_servers);
+    if secrets_moved > 0 {
+        push_log(
+            "info",
+            format!("Moved {} MCP server secrets to the keychain", secrets_moved),
+        );
+    }
+    if token_added || secrets_moved > 0 {
+        if let Err(e) = settings.save(&settings_path) {
+            tracing::warn!("Failed to save settings: {}", e);
+        }
+    }
+    apply_settings(&settings);
+    // Servers connected later list these; changes are pushed by sync_mcp_roots
+    protocols::mcp_roots::configure(&settings.watched_directories);
+    // WASM plugins compile in the background; their hooks and tools apply once loaded
+    tauri::async_runtime::spawn_blocking(|| {
+        let loaded = wasm_plugins::load_all(&wasm_plugins::plugins_dir());
+        if loaded > 0 {
+            push_log("info", format!("Loaded {} WASM plugin(s)", loaded));
+        }
+    });
     push_log(
         "info",
         format!(
@@ -1690,24 +4084,40 @@ pub fn run() {
     // --- Step 3: Initialize database ---
     let db_path = get_db_path();
     tracing::info!("Database path: {}", db_path.display());
-    let db = Database::open(&db_path).expect("Failed to open database");
-    push_log(
-        "info",
-        format!("Database opened (vec_enabled={})", db.is_vec_enabled()),
-    );
-
-    // Initialize conversation memory tables
-    if let Err(e) = agent::memory::initialize_memory_schema(&db) {
-        tracing::warn!("Failed to initialize conversation memory schema: {}", e);
-        push_log("warn", format!("Memory schema init failed: {}", e));
+    let db = if db::encryption::is_encrypted(&db_path) {
+        // Queries fail with VaultLocked until the frontend calls unlock_vault
+        push_log(
+            "info",
+            "Vault is encrypted — waiting for its passphrase".to_string(),
+        );
+        Database::locked(&db_path).expect("Failed to open database")
     } else {
-        push_log("info", "Conversation memory schema initialized".to_string());
+        let db = Database::open(&db_path).expect("Failed to open database");
+        push_log(
+            "info",
+            format!("Database opened (vec_enabled={})", db.is_vec_enabled()),
+        );
+        initialize_vault_schemas(&db);
+        db
+    };
+
+    // Per-user isolation: warn about shared data dirs, tighten file permissions
+    let isolation = isolation::check_and_harden(&get_app_data_dir());
+    for path in &isolation.hardened {
+        push_log("info", format!("Restricted permissions on {}", path));
+    }
+    for warning in &isolation.warnings {
+        tracing::warn!("{}", warning);
+        push_log("warn", warning.clone());
     }
 
     // --- Step 4: Create embedding engine (deferred loading) ---
     // Like ChatEngine: start immediately with FTS5-only, load native model in background.
     // This prevents blocking the UI during model download (~23MB) or loading (~200ms).
-    let embedding_engine = EmbeddingEngine::new(hardware.clone());
+    let embedding_engine = EmbeddingEngine::new(hardware.clone()).with_model(
+        embeddings::models::find_model(&settings.embedding_model)
+            .unwrap_or_else(embeddings::models::default_model),
+    );
     push_log(
         "info",
         "Embedding engine created (deferred loading)".to_string(),
@@ -1734,7 +4144,9 @@ pub fn run() {
 
     // --- Step 6: Create chat engine (deferred loading) ---
     // llama.cpp auto-detects GPU at runtime — no device preference needed.
-    let chat_engine = chat::ChatEngine::new(hardware.clone(), model_id.clone());
+    let chat_engine = chat::ChatEngine::new(hardware.clone(), model_id.clone())
+        .with_fast_model(&settings.fast_chat_model)
+        .with_speculative_decoding(settings.speculative_decoding);
     push_log(
         "info",
         format!(
@@ -1751,10 +4163,17 @@ pub fn run() {
         settings: std::sync::Mutex::new(settings),
         mcp_client: protocols::mcp_client::McpClientManager::new(),
         agui_event_bus: protocols::agui::AgUiEventBus::new(256),
+        tool_approvals: agent::approval::ApprovalGate::new(),
+        a2a_tasks: protocols::a2a::TaskStore::new(),
+        reembed_job: embeddings::migration::ReembedJob::new(),
+        jobs: jobs::JobScheduler::default(),
     });
+    protocols::mcp_client::attach(&app_state);
 
     #[allow(unused_mut)]
-    let mut builder = tauri::Builder::default().plugin(tauri_plugin_opener::init());
+    let mut builder = tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init());
 
     // Desktop-only plugins
     #[cfg(desktop)]
@@ -1769,13 +4188,30 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Search & indexing
             search_query,
+            search_query_stream,
+            search_filenames,
+            collect_documents,
+            open_search_result,
+            reveal_in_file_manager,
+            get_recent_queries,
+            get_frequent_queries,
+            get_query_suggestions,
+            clear_query_history,
+            set_focus_context,
+            get_focus_context,
+            clear_focus_context,
             index_directory,
             index_file,
+            test_extractor_plugin,
+            list_wasm_plugins,
+            reload_wasm_plugins,
             get_stats,
             check_ollama,
             check_ai_status,
             start_watcher,
             get_vec_status,
+            get_system_health,
+            retry_enable_vec,
             // Window
             hide_window,
             show_window,
@@ -1783,27 +4219,46 @@ pub fn run() {
             minimize_window,
             toggle_maximize_window,
             close_window,
+            // Session restore
+            get_session_state,
+            save_session_state,
             // Auto-indexing
             get_default_directories,
             // Chat
             chat_send,
+            chat_send_structured,
             chat_send_streaming,
             chat_status,
+            count_tokens,
             chat_load_model,
             chat_switch_model,
+            list_local_server_models,
             // Hardware & models
             get_hardware_info,
             get_available_models,
+            list_downloaded_models,
+            delete_downloaded_model,
+            import_custom_model,
+            search_hub_models,
+            add_hub_model,
             get_recommended_model,
             // Platform
             get_platform_info,
             // Debug
             get_logs,
+            get_last_crash_report,
+            dismiss_crash_report,
             clear_logs,
             log_from_frontend,
             // Settings
             get_settings,
             save_settings,
+            reset_settings_section,
+            list_vaults,
+            create_vault,
+            switch_vault,
+            is_vault_locked,
+            unlock_vault,
             complete_setup,
             // Pro
             is_pro,
@@ -1815,13 +4270,25 @@ pub fn run() {
             remove_watch_directory,
             // MCP Protocol
             get_mcp_server_status,
+            rotate_mcp_token,
+            get_agui_ws_status,
+            get_openai_server_status,
+            get_webmcp_status,
+            set_webmcp_origin_permission,
             list_mcp_servers,
             connect_mcp_server,
             disconnect_mcp_server,
+            sign_in_mcp_server,
+            sign_out_mcp_server,
             call_mcp_tool,
+            get_tool_call_history,
+            replay_tool_call,
             list_mcp_tools,
             add_mcp_server_entry,
             remove_mcp_server_entry,
+            set_secret,
+            get_secret,
+            delete_secret,
             // MCP Catalog (App Store)
             get_mcp_catalog,
             detect_runtimes,
@@ -1831,6 +4298,7 @@ pub fn run() {
             auto_provision_mcp_defaults,
             install_mcp_from_catalog,
             install_mcp_entry,
+            install_catalog_entry,
             uninstall_mcp_server,
             // MCP Registry
             sync_mcp_registry,
@@ -1844,21 +4312,141 @@ pub fn run() {
             check_tool_requirements,
             // Agent
             agent_chat,
+            agent_plan,
+            execute_plan,
+            discard_plan,
+            get_run_events,
+            get_agui_bus_metrics,
+            get_resource_usage,
+            start_resource_monitor,
+            stop_resource_monitor,
+            respond_tool_approval,
+            respond_elicitation,
+            list_pending_approvals,
             create_conversation,
             list_conversations,
             get_conversation_messages,
             delete_conversation,
             update_conversation_title,
+            set_conversation_cloud,
+            attach_to_conversation,
+            detach_from_conversation,
+            list_conversation_attachments,
+            distill_conversation,
+            summarize_document,
+            suggest_filename,
+            suggest_folder_filenames,
+            rename_file,
+            translate_text,
             search_memory,
+            get_agent_audit_log,
             get_agent_config,
             save_agent_config,
             get_agent_model_tiers,
             list_skills,
+            run_skill,
+            list_marketplace_skills,
+            install_marketplace_skill,
+            // A2A
+            list_a2a_agents,
+            discover_a2a_agent,
+            add_a2a_agent,
+            remove_a2a_agent,
+            delegate_a2a_task,
+            list_a2a_tasks,
+            // Usage analytics
+            get_usage_dashboard,
+            export_usage_report,
+            clear_usage_data,
+            // Telemetry (opt-in feature)
+            #[cfg(feature = "telemetry")]
+            preview_telemetry_report,
+            #[cfg(feature = "telemetry")]
+            send_telemetry_report,
+            #[cfg(feature = "telemetry")]
+            discard_telemetry_report,
+            #[cfg(feature = "telemetry")]
+            list_telemetry_reports,
+            // IDE integration
+            get_workspace_context,
+            // SQL console
+            run_readonly_sql,
+            // Vault maintenance
+            vacuum_database,
+            run_integrity_check,
+            optimize_fts,
+            // Performance self-test
+            run_perf_selftest,
+            // Help
+            ghost_help,
+            // Re-embedding
+            get_reembed_status,
+            list_embedding_models,
+            start_reembedding,
+            pause_reembedding,
+            resume_reembedding,
+            cancel_reembedding,
+            // Embedding cache
+            get_embedding_cache_stats,
+            clear_embedding_cache,
+            // Downloads
+            list_active_downloads,
+            get_active_index_jobs,
+            cancel_index_job,
+            get_throttle_status,
+            is_metered_connection,
+            // Vault isolation
+            get_vault_isolation_report,
+            // Support snapshots
+            create_state_snapshot,
+            load_state_snapshot,
+            // Jobs
+            list_jobs,
+            cancel_job,
+            pause_job,
+            resume_job,
+            // Reminders
+            list_upcoming_reminders,
+            set_reminder_done,
+            // Tags
+            add_document_tag,
+            remove_document_tag,
+            get_document_tags,
+            list_tags,
+            get_vault_languages,
+            // Vault snapshots
+            create_vault_snapshot,
+            list_vault_snapshots,
+            delete_vault_snapshot,
+            diff_vault_snapshot,
+            search_vault_snapshot,
+            // Duplicates
+            scan_duplicates,
+            get_duplicate_report,
+            // Importers
+            import_browser_data,
+            list_browser_profiles,
+            import_notes_vault,
+            get_note_links,
+            #[cfg(feature = "mail-import")]
+            import_mail,
+            // Digests
+            run_digest,
+            list_digests,
+            // Knowledge graph
+            build_knowledge_graph,
+            query_knowledge_graph,
+            get_knowledge_graph_stats,
         ])
         .setup(move |app| {
             // --- Desktop-only setup: System Tray + Global Shortcuts ---
             #[cfg(desktop)]
             {
+                // Put the window back where it was last time
+                if let Some(window) = session::SessionState::load(&get_session_path()).window {
+                    session::restore_window(app.handle(), &window);
+                }
+
                 let show_item = MenuItem::with_id(app, "show", "Show Ghost", true, None::<&str>)?;
                 let quit_item = MenuItem::with_id(app, "quit", "Quit Ghost", true, None::<&str>)?;
                 let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
@@ -1880,6 +4468,15 @@ pub fn run() {
                         "quit" => {
                             // Graceful shutdown: checkpoint WAL before exiting
                             tracing::info!("Quit requested — performing graceful shutdown...");
+                            // Remember where the window was for the next launch
+                            if let Some(window) = session::capture_window(_app) {
+                                let path = get_session_path();
+                                let mut saved = session::SessionState::load(&path);
+                                saved.window = Some(window);
+                                if let Err(e) = saved.save(&path) {
+                                    tracing::warn!("Failed to save session: {}", e);
+                                }
+                            }
                             if let Err(e) = quit_state.db.checkpoint() {
                                 tracing::warn!("WAL checkpoint failed during shutdown: {}", e);
                             }
@@ -1927,6 +4524,126 @@ pub fn run() {
             #[cfg(not(desktop))]
             let _ = &app;
 
+            // --- Report degraded capabilities ---
+            let vec_status = health::vector_search_status(&app_state.db);
+            if !vec_status.available {
+                push_log(
+                    "warn",
+                    format!(
+                        "Vector search disabled: {}",
+                        vec_status.reason.as_deref().unwrap_or("unknown error")
+                    ),
+                );
+                let _ = app.handle().emit(health::CAPABILITY_EVENT, &vec_status);
+            }
+
+            // --- Forward indexing progress to the frontend ---
+            let progress_handle = app.handle().clone();
+            let mut progress_rx = indexer::progress::subscribe();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+                loop {
+                    match progress_rx.recv().await {
+                        Ok(progress) => {
+                            let _ = progress_handle
+                                .emit(indexer::progress::INDEX_PROGRESS_EVENT, &progress);
+                        }
+                        // Only the latest snapshot matters; skip what was missed
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            // --- Start AG-UI WebSocket Server ---
+            let ws_state = app_state.clone();
+            let ws_config = ws_state
+                .settings
+                .lock()
+                .map(|s| s.agui_ws.clone())
+                .unwrap_or_default();
+
+            tauri::async_runtime::spawn(async move {
+                match protocols::agui::start_ws_server(ws_state, &ws_config).await {
+                    Ok(addr) => {
+                        push_log("info", format!("AG-UI WebSocket server: {}", addr));
+                    }
+                    Err(e) => {
+                        push_log(
+                            "warn",
+                            format!("AG-UI WebSocket server failed to start: {}", e),
+                        );
+                        tracing::warn!("AG-UI WebSocket server failed to start: {}", e);
+                    }
+                }
+            });
+
+            // --- Start OpenAI-compatible API server ---
+            let openai_state = app_state.clone();
+            let openai_config = openai_state
+                .settings
+                .lock()
+                .map(|s| s.openai_server.clone())
+                .unwrap_or_default();
+
+            tauri::async_runtime::spawn(async move {
+                match protocols::openai_server::start_server(openai_state, &openai_config).await {
+                    Ok(addr) => {
+                        push_log("info", format!("OpenAI-compatible API: {}", addr));
+                    }
+                    Err(e) => {
+                        push_log(
+                            "warn",
+                            format!("OpenAI-compatible API failed to start: {}", e),
+                        );
+                        tracing::warn!("OpenAI-compatible API failed to start: {}", e);
+                    }
+                }
+            });
+
+            // --- Start WebMCP browser bridge ---
+            let webmcp_state = app_state.clone();
+            let webmcp_config = webmcp_state
+                .settings
+                .lock()
+                .map(|s| s.webmcp.clone())
+                .unwrap_or_default();
+
+            tauri::async_runtime::spawn(async move {
+                match protocols::webmcp::start_server(webmcp_state, &webmcp_config).await {
+                    Ok(addr) => {
+                        push_log("info", format!("WebMCP bridge: {}", addr));
+                    }
+                    Err(e) => {
+                        push_log("warn", format!("WebMCP bridge failed to start: {}", e));
+                        tracing::warn!("WebMCP bridge failed to start: {}", e);
+                    }
+                }
+            });
+
+            // --- Requests external MCP servers make of the user ---
+            let prompt_handle = app.handle().clone();
+            protocols::mcp_client::set_prompt_listener(move |name, value| {
+                let _ = prompt_handle.emit(
+                    "mcp://prompt",
+                    serde_json::json!({ "name": name, "value": value }),
+                );
+            });
+
+            // --- Supervise external MCP servers ---
+            // Pings connected servers, restarts crashed stdio processes, and
+            // reports health changes to the frontend.
+            let supervisor_state = app_state.clone();
+            let supervisor_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                supervisor_state
+                    .mcp_client
+                    .run_supervisor(move |status| {
+                        let _ = supervisor_handle.emit("mcp-server-status", status);
+                    })
+                    .await;
+            });
+
             // --- Start MCP Server ---
             let mcp_state = app_state.clone();
             let mcp_config = mcp_state
@@ -1992,7 +4709,7 @@ pub fn run() {
                             for entry in &default_entries {
                                 settings.mcp_servers.push(entry.clone());
                             }
-                            let _ = settings.save(&get_app_data_dir().join("settings.json"));
+                            let _ = settings.save(&get_settings_path());
                         }
 
                         push_log(
@@ -2002,9 +4719,11 @@ pub fn run() {
                         tracing::info!("MCP auto-provision: installed {} default tools", count);
 
                         // Pre-cache npm packages in background for faster first use
-                        tokio::spawn(async {
-                            protocols::mcp_catalog::precache_npm_packages().await;
-                        });
+                        let _ = jobs::submit(
+                            &mcp_state,
+                            jobs::JobSpec::Precache,
+                            jobs::JobPriority::Low,
+                        );
                     } else {
                         push_log(
                             "warn",
@@ -2070,6 +4789,49 @@ pub fn run() {
                     status.backend,
                     status.dimensions
                 );
+
+                // Honor the backend the vault was migrated to, and resume an
+                // interrupted re-embedding migration.
+                let preferred = state_for_embeddings
+                    .settings
+                    .lock()
+                    .map(|s| s.embedding_backend.clone())
+                    .unwrap_or_default();
+                if let Some(backend) = embeddings::AiBackend::from_name(&preferred) {
+                    if backend != status.backend
+                        && state_for_embeddings
+                            .embedding_engine
+                            .is_backend_available(&backend)
+                            .await
+                    {
+                        state_for_embeddings
+                            .embedding_engine
+                            .set_active_backend(backend);
+                    }
+                }
+                if let Ok(Some(migration)) = state_for_embeddings.db.get_vec_migration() {
+                    if !migration.paused {
+                        push_log(
+                            "info",
+                            format!(
+                                "Resuming re-embedding with {} ({} chunks done)",
+                                migration.target_id(),
+                                migration.migrated
+                            ),
+                        );
+                        let _ = jobs::submit(
+                            &state_for_embeddings,
+                            jobs::JobSpec::Reembed,
+                            jobs::JobPriority::Low,
+                        );
+                    }
+                }
+
+                // Re-queue background jobs interrupted by the last shutdown
+                // (an encrypted vault does this once unlocked)
+                if !state_for_embeddings.db.is_locked() {
+                    resume_vault_jobs(&state_for_embeddings);
+                }
             });
 
             // --- Background chat model loading ---
@@ -2100,7 +4862,11 @@ pub fn run() {
             tauri::async_runtime::spawn(async move {
                 let needs_auto_setup = {
                     match state_for_autoindex.settings.lock() {
-                        Ok(settings) => settings.watched_directories.is_empty(),
+                        // Vaults created later start empty on purpose
+                        Ok(settings) => {
+                            settings.watched_directories.is_empty()
+                                && vaults::active_id() == vaults::DEFAULT_VAULT_ID
+                        }
                         Err(e) => {
                             tracing::error!("Failed to lock settings for auto-setup check: {}", e);
                             false
@@ -2163,8 +4929,7 @@ pub fn run() {
                             match state_for_autoindex.settings.lock() {
                                 Ok(mut settings) => {
                                     settings.watched_directories = auto_dirs.clone();
-                                    let _ =
-                                        settings.save(&get_app_data_dir().join("settings.json"));
+                                    let _ = settings.save(&get_settings_path());
                                 }
                                 Err(e) => {
                                     tracing::error!(
@@ -2175,33 +4940,19 @@ pub fn run() {
                             }
                         }
 
-                        // Start indexing each directory
+                        sync_mcp_roots(&state_for_autoindex).await;
+
+                        // Queue indexing for each directory
                         for dir_path in &auto_dirs {
-                            let path = std::path::PathBuf::from(dir_path);
                             push_log("info", format!("Auto-indexing: {}", dir_path));
-                            match crate::indexer::index_directory(
-                                &state_for_autoindex.db,
-                                &state_for_autoindex.embedding_engine,
-                                &path,
-                            )
-                            .await
-                            {
-                                Ok(stats) => {
-                                    push_log(
-                                        "info",
-                                        format!(
-                                            "Indexed {}: {} files ({} ok, {} failed)",
-                                            dir_path, stats.total, stats.indexed, stats.failed
-                                        ),
-                                    );
-                                }
-                                Err(e) => {
-                                    push_log(
-                                        "warn",
-                                        format!("Failed to index {}: {}", dir_path, e),
-                                    );
-                                    tracing::warn!("Auto-index failed for {}: {}", dir_path, e);
-                                }
+                            if let Err(e) = jobs::submit(
+                                &state_for_autoindex,
+                                jobs::JobSpec::IndexDirectory {
+                                    path: dir_path.clone(),
+                                },
+                                jobs::JobPriority::Normal,
+                            ) {
+                                tracing::warn!("Auto-index failed for {}: {}", dir_path, e);
                             }
                         }
 
@@ -2210,56 +4961,8 @@ pub fn run() {
                         {
                             let watch_dirs: Vec<std::path::PathBuf> =
                                 auto_dirs.iter().map(std::path::PathBuf::from).collect();
-                            match crate::indexer::watcher::start_watching(watch_dirs) {
-                                Ok(rx) => {
-                                    let watcher_state = state_for_autoindex.clone();
-                                    tauri::async_runtime::spawn(async move {
-                                        while let Ok(events) = rx.recv() {
-                                            for event in events {
-                                                match event {
-                                                    crate::indexer::watcher::FileEvent::Changed(
-                                                        path,
-                                                    ) => {
-                                                        tracing::info!(
-                                                            "File changed, re-indexing: {}",
-                                                            path.display()
-                                                        );
-                                                        if let Err(e) = crate::indexer::index_file(
-                                                            &watcher_state.db,
-                                                            &watcher_state.embedding_engine,
-                                                            &path,
-                                                        )
-                                                        .await
-                                                        {
-                                                            tracing::warn!(
-                                                                "Failed to re-index {}: {}",
-                                                                path.display(),
-                                                                e
-                                                            );
-                                                        }
-                                                    }
-                                                    crate::indexer::watcher::FileEvent::Removed(
-                                                        path,
-                                                    ) => {
-                                                        tracing::info!(
-                                                            "File removed: {}",
-                                                            path.display()
-                                                        );
-                                                        let path_str =
-                                                            path.to_string_lossy().to_string();
-                                                        if let Ok(Some((doc_id, _))) = watcher_state
-                                                            .db
-                                                            .get_document_by_path(&path_str)
-                                                        {
-                                                            if let Err(e) = watcher_state.db.delete_document(doc_id) {
-                                                                tracing::warn!("Failed to delete document {}: {}", path_str, e);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    });
+                            match spawn_watcher(state_for_autoindex.clone(), watch_dirs) {
+                                Ok(()) => {
                                     push_log(
                                         "info",
                                         "File watcher started on auto-discovered directories"
@@ -2272,8 +4975,8 @@ pub fn run() {
                             }
                         }
 
-                        push_log("info", "Auto-indexing complete!".into());
-                        tracing::info!("Auto-indexing complete");
+                        push_log("info", "Auto-indexing queued".into());
+                        tracing::info!("Auto-indexing queued");
                     } else {
                         push_log("warn", "No user directories found for auto-indexing".into());
                     }
@@ -2286,6 +4989,7 @@ pub fn run() {
             // reconnected, or any events the watcher might have missed.
             // Runs infrequently (60min) to minimize CPU usage since the
             // watcher + hash-based dedup already covers 99% of changes.
+            // Queued at low priority alongside vault maintenance.
             let state_for_reindex = app_state.clone();
             tauri::async_runtime::spawn(async move {
                 // Wait 120 seconds before first re-index to let initial indexing finish
@@ -2293,46 +4997,136 @@ pub fn run() {
                 let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
                 loop {
                     interval.tick().await;
-                    let dirs = {
-                        state_for_reindex
-                            .settings
-                            .lock()
-                            .map(|s| s.watched_directories.clone())
-                            .unwrap_or_default()
-                    };
-                    if dirs.is_empty() {
-                        continue;
-                    }
-                    push_log(
-                        "info",
-                        format!("Periodic re-index: scanning {} directories", dirs.len()),
-                    );
-                    for dir_path in &dirs {
-                        let path = std::path::PathBuf::from(dir_path);
-                        if let Err(e) = crate::indexer::index_directory(
-                            &state_for_reindex.db,
-                            &state_for_reindex.embedding_engine,
-                            &path,
-                        )
-                        .await
-                        {
-                            tracing::warn!("Periodic re-index failed for {}: {}", dir_path, e);
+                    for spec in [jobs::JobSpec::Reindex, jobs::JobSpec::Maintenance] {
+                        let queued = jobs::submit(&state_for_reindex, spec, jobs::JobPriority::Low);
+                        if let Err(e) = queued {
+                            tracing::warn!("Failed to queue periodic job: {}", e);
                         }
                     }
-                    if let Ok(stats) = state_for_reindex.db.get_stats() {
-                        push_log(
-                            "info",
-                            format!(
-                                "Re-index complete: {} docs, {} chunks",
-                                stats.document_count, stats.chunk_count
-                            ),
+                    // Snapshot the vault when the last snapshot is old enough
+                    let interval_days = state_for_reindex
+                        .settings
+                        .lock()
+                        .map(|s| s.vault_snapshot_interval_days)
+                        .unwrap_or(0);
+                    if vault_snapshots::is_due(&state_for_reindex.db, interval_days)
+                        .unwrap_or(false)
+                    {
+                        let _ = jobs::submit(
+                            &state_for_reindex,
+                            jobs::JobSpec::VaultSnapshot,
+                            jobs::JobPriority::Low,
+                        );
+                    }
+                    // Look for duplicate files once a day
+                    if duplicates::is_due(&state_for_reindex.db).unwrap_or(false) {
+                        let _ = jobs::submit(
+                            &state_for_reindex,
+                            jobs::JobSpec::DetectDuplicates,
+                            jobs::JobPriority::Low,
+                        );
+                    }
+                    // Re-import browser history when the user turned it on
+                    let browser_import = state_for_reindex
+                        .settings
+                        .lock()
+                        .map(|s| s.browser_import.enabled)
+                        .unwrap_or(false);
+                    if browser_import {
+                        let _ = jobs::submit(
+                            &state_for_reindex,
+                            jobs::JobSpec::ImportBrowser,
+                            jobs::JobPriority::Low,
+                        );
+                    }
+                    // Write the "what's new" digest on its schedule
+                    let digest_settings = state_for_reindex
+                        .settings
+                        .lock()
+                        .map(|s| s.digest.clone())
+                        .unwrap_or_default();
+                    if digest::is_due(&state_for_reindex.db, &digest_settings).unwrap_or(false) {
+                        let _ = jobs::submit(
+                            &state_for_reindex,
+                            jobs::JobSpec::Digest,
+                            jobs::JobPriority::Low,
+                        );
+                    }
+                    // Keep an opted-in registry cache fresh (never syncs unless the user did once)
+                    let cache_dir = get_app_data_dir();
+                    if protocols::mcp_catalog::get_cache_meta(&cache_dir).is_some()
+                        && !protocols::mcp_catalog::is_cache_fresh(&cache_dir)
+                    {
+                        let _ = jobs::submit(
+                            &state_for_reindex,
+                            jobs::JobSpec::RegistrySync,
+                            jobs::JobPriority::Low,
                         );
                     }
                 }
             });
 
+            // --- Due reminders and new digests → OS notification center (checked every minute) ---
+            let state_for_reminders = app_state.clone();
+            let reminder_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tauri_plugin_notification::NotificationExt;
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                loop {
+                    interval.tick().await;
+                    let now = chrono::Local::now().naive_local();
+                    let due = match reminders::take_due(&state_for_reminders.db, now) {
+                        Ok(due) => due,
+                        Err(e) => {
+                            tracing::warn!("Failed to check reminders: {}", e);
+                            continue;
+                        }
+                    };
+                    for reminder in due {
+                        let shown = reminder_handle
+                            .notification()
+                            .builder()
+                            .title("Ghost reminder")
+                            .body(&reminder.text)
+                            .show();
+                        if let Err(e) = shown {
+                            tracing::warn!("Failed to show reminder notification: {}", e);
+                        }
+                        let _ = reminder_handle.emit("reminder-due", &reminder);
+                    }
+                    // New digests, announced once
+                    let notify = state_for_reminders
+                        .settings
+                        .lock()
+                        .map(|s| s.digest.notify)
+                        .unwrap_or(false);
+                    for digest in
+                        digest::take_unnotified(&state_for_reminders.db).unwrap_or_default()
+                    {
+                        if notify {
+                            let body = format!("{} new or changed files", digest.document_count);
+                            let shown = reminder_handle
+                                .notification()
+                                .builder()
+                                .title(&digest.title)
+                                .body(&body)
+                                .show();
+                            if let Err(e) = shown {
+                                tracing::warn!("Failed to show digest notification: {}", e);
+                            }
+                        }
+                        let _ = reminder_handle.emit("digest-ready", &digest);
+                    }
+                }
+            });
+
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running Ghost");
+        .build(tauri::generate_context!())
+        .expect("error while building Ghost")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                crash::mark_clean_exit();
+            }
+        });
 }
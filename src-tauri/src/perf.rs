@@ -0,0 +1,385 @@
+//! Performance workloads shared by the benchmarks and the in-app self-test.
+//!
+//! The criterion benches in `benches/search.rs` time chunking, FTS5 queries,
+//! sqlite-vec KNN search at 10k/100k/1M chunks, and RRF merging. Compare
+//! against a saved baseline to catch regressions before a release:
+//!
+//! ```text
+//! cargo bench --bench search -- --save-baseline main   # on main
+//! cargo bench --bench search -- --baseline main        # on the branch
+//! ```
+//!
+//! [`run_selftest`] runs the same workloads on a small synthetic vault on the
+//! user's hardware and checks each against a fixed p95 budget, so slow
+//! machines and regressions show up in the Debug panel. The synthetic vault
+//! lives in memory; the user's vault is never touched.
+
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::indexer::chunker;
+use crate::search::ranking;
+
+/// Embedding width of the synthetic vault (all-MiniLM-L6-v2).
+pub const EMBEDDING_DIM: usize = 384;
+
+/// Chunks in the self-test vault.
+const SELFTEST_CHUNKS: usize = 10_000;
+
+/// Timed runs per self-test check (after one warm-up run).
+const SELFTEST_ITERATIONS: usize = 20;
+
+/// Chunks per synthetic document.
+const CHUNKS_PER_DOCUMENT: usize = 100;
+
+/// Words per synthetic chunk.
+const WORDS_PER_CHUNK: usize = 120;
+
+/// p95 budgets for the self-test, in microseconds, on a release build.
+const BUDGET_CHUNKING_US: u64 = 50_000;
+const BUDGET_FTS_US: u64 = 25_000;
+const BUDGET_VEC_US: u64 = 100_000;
+const BUDGET_RRF_US: u64 = 1_000;
+
+/// Queries used against the synthetic vault (terms from [`VOCABULARY`]).
+pub const SAMPLE_QUERIES: &[&str] = &[
+    "invoice",
+    "quarterly budget",
+    "meeting notes project",
+    "contract renewal deadline",
+];
+
+const VOCABULARY: &[&str] = &[
+    "invoice",
+    "quarterly",
+    "budget",
+    "meeting",
+    "notes",
+    "project",
+    "contract",
+    "renewal",
+    "deadline",
+    "report",
+    "design",
+    "review",
+    "customer",
+    "support",
+    "release",
+    "roadmap",
+    "database",
+    "search",
+    "vector",
+    "index",
+    "summary",
+    "draft",
+    "policy",
+    "travel",
+    "expense",
+    "payment",
+    "schedule",
+    "research",
+    "paper",
+    "analysis",
+    "team",
+    "hiring",
+    "the",
+    "of",
+    "and",
+    "to",
+    "in",
+    "for",
+    "with",
+    "on",
+];
+
+/// Small deterministic generator so every run builds the same vault.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() % 20_001) as f32 / 10_000.0 - 1.0
+    }
+}
+
+/// Deterministic text of `words` words drawn from a fixed vocabulary.
+pub fn sample_text(words: usize, seed: u64) -> String {
+    let mut rng = Lcg(seed);
+    let mut text = String::with_capacity(words * 8);
+    for i in 0..words {
+        if i > 0 {
+            text.push(' ');
+        }
+        text.push_str(VOCABULARY[rng.next_u64() as usize % VOCABULARY.len()]);
+    }
+    text
+}
+
+/// Deterministic unit-length embedding.
+pub fn sample_embedding(seed: u64) -> Vec<f32> {
+    let mut rng = Lcg(seed);
+    let mut v: Vec<f32> = (0..EMBEDDING_DIM).map(|_| rng.next_f32()).collect();
+    let norm = v
+        .iter()
+        .map(|x| x * x)
+        .sum::<f32>()
+        .sqrt()
+        .max(f32::EPSILON);
+    v.iter_mut().for_each(|x| *x /= norm);
+    v
+}
+
+/// A ranked list of `len` results with chunk IDs spaced `stride` apart, so
+/// rankings with different strides partly overlap like FTS and vector hits do.
+pub fn sample_ranking(len: usize, stride: i64) -> Vec<(i64, f64)> {
+    (0..len)
+        .map(|i| (i as i64 * stride, 1.0 / (i + 1) as f64))
+        .collect()
+}
+
+/// Chunk `text` with the default settings; returns the number of chunks.
+pub fn chunk(text: &str) -> usize {
+    chunker::chunk_text(text, 512, 64).len()
+}
+
+/// Merge two rankings with RRF; returns the number of merged results.
+pub fn rrf(fts: &[(i64, f64)], vec: &[(i64, f64)]) -> usize {
+    ranking::reciprocal_rank_fusion(fts, vec).len()
+}
+
+/// An in-memory vault filled with synthetic chunks and embeddings.
+pub struct BenchVault {
+    db: Database,
+    chunks: usize,
+}
+
+impl BenchVault {
+    /// Build a vault of `chunks` chunks. Embeddings are only stored when
+    /// sqlite-vec is available.
+    pub fn build(chunks: usize) -> Result<Self> {
+        let db = Database::open_in_memory()?;
+        let with_vectors = db.is_vec_enabled();
+        db.with_transaction(|conn| {
+            let mut insert_doc = conn.prepare(
+                "INSERT INTO documents (path, filename, extension, size_bytes, hash, modified_at)
+                 VALUES (?1, ?2, 'txt', 0, ?3, '2026-01-01T00:00:00Z')",
+            )?;
+            let mut insert_chunk = conn.prepare(
+                "INSERT INTO chunks (document_id, chunk_index, content, token_count)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut insert_vec = if with_vectors {
+                Some(conn.prepare(
+                    "INSERT INTO chunks_vec(chunk_id, document_id, extension, embedding)
+                     VALUES (?1, ?2, 'txt', ?3)",
+                )?)
+            } else {
+                None
+            };
+
+            let mut document_id = 0;
+            for i in 0..chunks {
+                let index = i % CHUNKS_PER_DOCUMENT;
+                if index == 0 {
+                    let doc = i / CHUNKS_PER_DOCUMENT;
+                    insert_doc.execute(rusqlite::params![
+                        format!("/bench/doc{}.txt", doc),
+                        format!("doc{}.txt", doc),
+                        format!("h{}", doc),
+                    ])?;
+                    document_id = conn.last_insert_rowid();
+                }
+                let content = sample_text(WORDS_PER_CHUNK, i as u64);
+                insert_chunk.execute(rusqlite::params![
+                    document_id,
+                    index as i32,
+                    content,
+                    WORDS_PER_CHUNK as i32
+                ])?;
+                if let Some(stmt) = insert_vec.as_mut() {
+                    let chunk_id = conn.last_insert_rowid();
+                    let blob: Vec<u8> = sample_embedding(i as u64)
+                        .iter()
+                        .flat_map(|f| f.to_le_bytes())
+                        .collect();
+                    stmt.execute(rusqlite::params![chunk_id, document_id, blob])?;
+                }
+            }
+            Ok(())
+        })?;
+        Ok(Self { db, chunks })
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks
+    }
+
+    pub fn vec_enabled(&self) -> bool {
+        self.db.is_vec_enabled()
+    }
+
+    /// Run one FTS5 query; returns the number of hits.
+    pub fn fts_query(&self, query: &str, limit: usize) -> Result<usize> {
+        Ok(self.db.fts_search(query, limit)?.len())
+    }
+
+    /// Run one KNN query with a synthetic embedding; returns the number of hits.
+    pub fn vec_query(&self, seed: u64, limit: usize) -> Result<usize> {
+        Ok(self.db.vec_search(&sample_embedding(seed), limit)?.len())
+    }
+}
+
+/// Timing of one self-test workload.
+#[derive(Debug, Clone, Serialize)]
+pub struct PerfCheck {
+    pub name: String,
+    pub iterations: usize,
+    pub median_us: u64,
+    pub p95_us: u64,
+    pub budget_us: u64,
+    /// Whether the p95 stayed within the budget.
+    pub passed: bool,
+}
+
+/// Result of [`run_selftest`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PerfReport {
+    pub checks: Vec<PerfCheck>,
+    /// Whether every check passed.
+    pub passed: bool,
+    /// Chunks in the synthetic vault.
+    pub vault_chunks: usize,
+    /// False when sqlite-vec is unavailable; the vector check is then skipped.
+    pub vec_enabled: bool,
+    /// Time spent building the synthetic vault.
+    pub setup_ms: u64,
+    pub duration_ms: u64,
+}
+
+/// Time `iterations` runs of `f` after one warm-up run.
+fn measure<F>(name: &str, iterations: usize, budget_us: u64, mut f: F) -> Result<PerfCheck>
+where
+    F: FnMut(usize) -> Result<()>,
+{
+    f(0)?;
+    let mut samples = Vec::with_capacity(iterations);
+    for i in 0..iterations {
+        let started = Instant::now();
+        f(i)?;
+        samples.push(started.elapsed().as_micros() as u64);
+    }
+    samples.sort_unstable();
+    let median_us = samples[samples.len() / 2];
+    let p95_us = samples[(samples.len() * 95).div_ceil(100) - 1];
+    Ok(PerfCheck {
+        name: name.to_string(),
+        iterations,
+        median_us,
+        p95_us,
+        budget_us,
+        passed: p95_us <= budget_us,
+    })
+}
+
+/// Run every workload on a synthetic vault and check it against its budget.
+///
+/// Takes a few seconds; run it off the async runtime.
+pub fn run_selftest() -> Result<PerfReport> {
+    run_with(SELFTEST_CHUNKS, SELFTEST_ITERATIONS)
+}
+
+fn run_with(chunks: usize, iterations: usize) -> Result<PerfReport> {
+    let started = Instant::now();
+    let vault = BenchVault::build(chunks)?;
+    let setup_ms = started.elapsed().as_millis() as u64;
+
+    let text = sample_text(50_000, 7);
+    let fts = sample_ranking(100, 2);
+    let vec = sample_ranking(100, 3);
+
+    let mut checks = vec![
+        measure("Chunk 50k words", iterations, BUDGET_CHUNKING_US, |_| {
+            chunk(&text);
+            Ok(())
+        })?,
+        measure("Keyword search", iterations, BUDGET_FTS_US, |i| {
+            vault.fts_query(SAMPLE_QUERIES[i % SAMPLE_QUERIES.len()], 50)?;
+            Ok(())
+        })?,
+    ];
+    if vault.vec_enabled() {
+        checks.push(measure("Vector search", iterations, BUDGET_VEC_US, |i| {
+            vault.vec_query(i as u64, 50)?;
+            Ok(())
+        })?);
+    }
+    checks.push(measure("RRF merge", iterations, BUDGET_RRF_US, |_| {
+        rrf(&fts, &vec);
+        Ok(())
+    })?);
+
+    let report = PerfReport {
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+        vault_chunks: vault.chunk_count(),
+        vec_enabled: vault.vec_enabled(),
+        setup_ms,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    tracing::info!(
+        "Performance self-test {} in {} ms",
+        if report.passed { "passed" } else { "failed" },
+        report.duration_ms
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workloads() {
+        assert_eq!(sample_text(50, 1), sample_text(50, 1));
+        assert_eq!(sample_text(50, 1).split_whitespace().count(), 50);
+        let norm: f32 = sample_embedding(3).iter().map(|x| x * x).sum();
+        assert!((norm - 1.0).abs() < 1e-4);
+        assert_eq!(chunk(&sample_text(1000, 2)), 3);
+        // IDs 0, 6, 12 and 18 appear in both rankings
+        assert_eq!(rrf(&sample_ranking(10, 2), &sample_ranking(10, 3)), 16);
+
+        let vault = BenchVault::build(250).unwrap();
+        assert!(vault.fts_query("invoice", 10).unwrap() > 0);
+        if vault.vec_enabled() {
+            assert_eq!(vault.vec_query(1, 10).unwrap(), 10);
+        }
+
+        let report = run_with(250, 3).unwrap();
+        assert_eq!(report.vault_chunks, 250);
+        assert_eq!(report.checks.len(), if report.vec_enabled { 4 } else { 3 });
+    }
+
+    /// Regression gate: `cargo test --release perf -- --ignored`.
+    #[test]
+    #[ignore = "timing-sensitive; run on a release build before tagging"]
+    fn test_selftest_within_budgets() {
+        let report = run_selftest().unwrap();
+        for check in &report.checks {
+            assert!(
+                check.passed,
+                "{}: p95 {} µs exceeds budget {} µs",
+                check.name, check.p95_us, check.budget_us
+            );
+        }
+    }
+}
@@ -34,7 +34,14 @@
 #![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::chat::ChatMessage;
+use crate::protocols::agui::{EventPayload, EventType};
+use crate::AppState;
 
 // ---------------------------------------------------------------------------
 // Agent Card (/.well-known/agent.json)
@@ -416,29 +423,400 @@ pub struct SendMessageConfig {
     pub accepted_output_modes: Vec<String>,
 }
 
+/// Parameters for `tasks/get` and `tasks/cancel`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskQueryParams {
+    /// Task ID.
+    pub id: String,
+    /// Max messages to include in history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_length: Option<usize>,
+}
+
+/// Parameters for `tasks/list`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTasksParams {
+    /// Only return tasks in this context.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
+    /// Only return tasks in this state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<TaskState>,
+}
+
+impl TaskState {
+    /// Whether the task has reached a final state.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::Failed | Self::Canceled | Self::Rejected
+        )
+    }
+}
+
+impl A2aMessage {
+    /// Build a single-part text message.
+    pub fn text(role: &str, text: &str) -> Self {
+        Self {
+            message_id: new_id("msg"),
+            role: role.to_string(),
+            parts: vec![Part::Text {
+                text: text.to_string(),
+            }],
+            context_id: None,
+            task_id: None,
+        }
+    }
+
+    /// Concatenate all text parts (data parts are rendered as JSON).
+    pub fn text_content(&self) -> String {
+        parts_to_text(&self.parts)
+    }
+}
+
+/// Render message/artifact parts as plain text for the local model.
+fn parts_to_text(parts: &[Part]) -> String {
+    parts
+        .iter()
+        .map(|p| match p {
+            Part::Text { text } => text.clone(),
+            Part::Data { data } => data.to_string(),
+            Part::File { file } => format!("[file: {}]", file.name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// Generate a process-unique ID like `task-1718000000000-3`.
+fn new_id(prefix: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!(
+        "{}-{}-{}",
+        prefix,
+        millis,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Task Store (server side)
+// ---------------------------------------------------------------------------
+
+/// Maximum number of tasks kept in memory. Oldest finished tasks are evicted
+/// first; when none has finished, the oldest running one is aborted and evicted.
+const MAX_STORED_TASKS: usize = 200;
+
+/// In-memory store of A2A tasks received from remote agents.
+pub struct TaskStore {
+    /// Tasks in creation order.
+    tasks: Mutex<Vec<Task>>,
+    /// Abort handles for tasks still running.
+    handles: Mutex<HashMap<String, tokio::task::AbortHandle>>,
+}
+
+impl TaskStore {
+    /// Create an empty task store.
+    pub fn new() -> Self {
+        Self {
+            tasks: Mutex::new(Vec::new()),
+            handles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a new WORKING task for an incoming message.
+    pub fn create(&self, message: &A2aMessage) -> Task {
+        let id = new_id("task");
+        let context_id = message.context_id.clone().unwrap_or_else(|| new_id("ctx"));
+        let mut incoming = message.clone();
+        incoming.task_id = Some(id.clone());
+        incoming.context_id = Some(context_id.clone());
+
+        let task = Task {
+            id,
+            context_id,
+            status: TaskStatus {
+                state: TaskState::Working,
+                message: None,
+                timestamp: now_rfc3339(),
+            },
+            history: vec![incoming],
+            artifacts: Vec::new(),
+            metadata: HashMap::new(),
+        };
+
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if tasks.len() >= MAX_STORED_TASKS {
+            let pos = tasks
+                .iter()
+                .position(|t| t.status.state.is_terminal())
+                .unwrap_or(0);
+            let evicted = tasks.remove(pos);
+            if let Some(handle) = self
+                .handles
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&evicted.id)
+            {
+                tracing::warn!("A2A task store full; aborting task {}", evicted.id);
+                handle.abort();
+            }
+        }
+        tasks.push(task.clone());
+        task
+    }
+
+    /// Get a task by ID.
+    pub fn get(&self, id: &str) -> Option<Task> {
+        self.tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|t| t.id == id)
+            .cloned()
+    }
+
+    /// List tasks, newest first, optionally filtered.
+    pub fn list(&self, params: &ListTasksParams) -> Vec<Task> {
+        self.tasks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .rev()
+            .filter(|t| {
+                params
+                    .context_id
+                    .as_ref()
+                    .map_or(true, |c| &t.context_id == c)
+                    && params
+                        .status
+                        .as_ref()
+                        .map_or(true, |s| &t.status.state == s)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Move a task to a new state. Terminal tasks are never updated again.
+    pub fn update(&self, id: &str, state: TaskState, reply: Option<&str>) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(task) = tasks.iter_mut().find(|t| t.id == id) else {
+            return;
+        };
+        if task.status.state.is_terminal() {
+            return;
+        }
+
+        let message = reply.map(|text| {
+            let mut msg = A2aMessage::text("agent", text);
+            msg.task_id = Some(task.id.clone());
+            msg.context_id = Some(task.context_id.clone());
+            msg
+        });
+        if let (TaskState::Completed, Some(text)) = (&state, reply) {
+            task.artifacts.push(Artifact {
+                artifact_id: new_id("artifact"),
+                name: Some("response".to_string()),
+                parts: vec![Part::Text {
+                    text: text.to_string(),
+                }],
+            });
+        }
+        if let Some(ref msg) = message {
+            task.history.push(msg.clone());
+        }
+        task.status = TaskStatus {
+            state,
+            message,
+            timestamp: now_rfc3339(),
+        };
+        drop(tasks);
+
+        self.handles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+    }
+
+    /// Remember the background job running a task so it can be canceled.
+    /// A task that was canceled or evicted in the meantime is aborted instead.
+    fn set_handle(&self, id: &str, handle: tokio::task::AbortHandle) {
+        // Lock order matches `create`: tasks, then handles
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let running = tasks
+            .iter()
+            .any(|t| t.id == id && !t.status.state.is_terminal());
+        if running {
+            self.handles
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(id.to_string(), handle);
+        } else {
+            handle.abort();
+        }
+    }
+
+    /// Cancel a running task. Returns the error code if it cannot be canceled.
+    pub fn cancel(&self, id: &str) -> Result<Task, i32> {
+        let task = self.get(id).ok_or(error_codes::TASK_NOT_FOUND)?;
+        if task.status.state.is_terminal() {
+            return Err(error_codes::TASK_NOT_CANCELABLE);
+        }
+        if let Some(handle) = self
+            .handles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id)
+        {
+            handle.abort();
+        }
+        self.update(id, TaskState::Canceled, None);
+        self.get(id).ok_or(error_codes::TASK_NOT_FOUND)
+    }
+}
+
+impl Default for TaskStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Trim a task's history to the last `history_length` messages.
+fn with_history_length(mut task: Task, history_length: Option<usize>) -> Task {
+    if let Some(n) = history_length {
+        let skip = task.history.len().saturating_sub(n);
+        task.history.drain(..skip);
+    }
+    task
+}
+
+/// Start the local agent on a new task in the background.
+///
+/// The task ID doubles as the AG-UI run ID, so the run can be observed
+/// (and streamed) through the event bus.
+fn spawn_task(state: &Arc<AppState>, message: &A2aMessage) -> (Task, tokio::task::JoinHandle<()>) {
+    let task = state.a2a_tasks.create(message);
+    let task_id = task.id.clone();
+    let messages = vec![ChatMessage {
+        role: "user".into(),
+        content: message.text_content(),
+    }];
+
+    tracing::info!(
+        "A2A task {} started ({} chars)",
+        task_id,
+        messages[0].content.len()
+    );
+
+    // The task waits until its abort handle is stored, so a task that ends
+    // right away can't leave the handle behind
+    let (registered_tx, registered_rx) = tokio::sync::oneshot::channel::<()>();
+    let state_inner = state.clone();
+    let handle = tokio::spawn(async move {
+        let _ = registered_rx.await;
+        let executor = crate::agent::executor::AgentExecutor::new(state_inner.clone());
+        match executor
+            .run(&task_id, &messages, None, &state_inner.agui_event_bus)
+            .await
+        {
+            Ok(result) => {
+                state_inner
+                    .a2a_tasks
+                    .update(&task_id, TaskState::Completed, Some(&result.content));
+            }
+            Err(e) => {
+                tracing::warn!("A2A task {} failed: {}", task_id, e);
+                state_inner
+                    .a2a_tasks
+                    .update(&task_id, TaskState::Failed, Some(&e.to_string()));
+            }
+        }
+    });
+    state.a2a_tasks.set_handle(&task.id, handle.abort_handle());
+    let _ = registered_tx.send(());
+    (task, handle)
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(
+    params: Option<serde_json::Value>,
+) -> Result<T, String> {
+    serde_json::from_value(params.unwrap_or(json!({}))).map_err(|e| e.to_string())
+}
+
 // ---------------------------------------------------------------------------
 // A2A Method Dispatcher (server side)
 // ---------------------------------------------------------------------------
 
 /// Dispatch an incoming A2A JSON-RPC request to the appropriate handler.
 ///
-/// Currently returns "Method Not Implemented" — full implementation is Phase 2.
-pub async fn dispatch_request(request: JsonRpcRequest) -> JsonRpcResponse {
+/// `message/stream` is served as SSE by [`stream_message`]; when it reaches
+/// this dispatcher it behaves like a non-blocking `message/send`.
+pub async fn dispatch_request(state: &Arc<AppState>, request: JsonRpcRequest) -> JsonRpcResponse {
     let id = request.id.clone();
     match request.method.as_str() {
         "message/send" | "message/stream" => {
-            // Phase 2: wire up to the agent executor
-            JsonRpcResponse::error(
-                id,
-                error_codes::UNSUPPORTED_OPERATION,
-                "A2A message handling is planned for Phase 2",
-            )
+            let params: SendMessageParams = match parse_params(request.params) {
+                Ok(p) => p,
+                Err(e) => return JsonRpcResponse::error(id, error_codes::INVALID_PARAMS, &e),
+            };
+            let blocking = params.configuration.as_ref().is_some_and(|c| c.blocking);
+            let history_length = params.configuration.as_ref().and_then(|c| c.history_length);
+
+            let (task, handle) = spawn_task(state, &params.message);
+            let task = if blocking {
+                let _ = handle.await;
+                state.a2a_tasks.get(&task.id).unwrap_or(task)
+            } else {
+                task
+            };
+            JsonRpcResponse::ok(id, json!(with_history_length(task, history_length)))
+        }
+        "tasks/get" => {
+            let params: TaskQueryParams = match parse_params(request.params) {
+                Ok(p) => p,
+                Err(e) => return JsonRpcResponse::error(id, error_codes::INVALID_PARAMS, &e),
+            };
+            match state.a2a_tasks.get(&params.id) {
+                Some(task) => {
+                    JsonRpcResponse::ok(id, json!(with_history_length(task, params.history_length)))
+                }
+                None => JsonRpcResponse::error(
+                    id,
+                    error_codes::TASK_NOT_FOUND,
+                    &format!("Task not found: {}", params.id),
+                ),
+            }
+        }
+        "tasks/list" => {
+            let params: ListTasksParams = match parse_params(request.params) {
+                Ok(p) => p,
+                Err(e) => return JsonRpcResponse::error(id, error_codes::INVALID_PARAMS, &e),
+            };
+            JsonRpcResponse::ok(id, json!({ "tasks": state.a2a_tasks.list(&params) }))
+        }
+        "tasks/cancel" => {
+            let params: TaskQueryParams = match parse_params(request.params) {
+                Ok(p) => p,
+                Err(e) => return JsonRpcResponse::error(id, error_codes::INVALID_PARAMS, &e),
+            };
+            match state.a2a_tasks.cancel(&params.id) {
+                Ok(task) => JsonRpcResponse::ok(id, json!(task)),
+                Err(code) => JsonRpcResponse::error(
+                    id,
+                    code,
+                    &format!("Task cannot be canceled: {}", params.id),
+                ),
+            }
         }
-        "tasks/get" | "tasks/list" | "tasks/cancel" => JsonRpcResponse::error(
-            id,
-            error_codes::TASK_NOT_FOUND,
-            "Task management is planned for Phase 2",
-        ),
         "tasks/pushNotificationConfig/set"
         | "tasks/pushNotificationConfig/get"
         | "tasks/pushNotificationConfig/list"
@@ -455,6 +833,240 @@ pub async fn dispatch_request(request: JsonRpcRequest) -> JsonRpcResponse {
     }
 }
 
+/// Serve `message/stream` as Server-Sent Events.
+///
+/// Emits the initial task, `artifact-update` events for each streamed text
+/// delta, and a final `status-update` once the task reaches a terminal state.
+/// Every SSE payload is a JSON-RPC response carrying the same request ID.
+pub fn stream_message(
+    state: &Arc<AppState>,
+    request: JsonRpcRequest,
+) -> Result<
+    impl futures::Stream<Item = Result<axum::response::sse::Event, std::convert::Infallible>>,
+    Box<JsonRpcResponse>,
+> {
+    let id = request.id.clone();
+    let params: SendMessageParams = parse_params(request.params).map_err(|e| {
+        Box::new(JsonRpcResponse::error(
+            id.clone(),
+            error_codes::INVALID_PARAMS,
+            &e,
+        ))
+    })?;
+
+    // Subscribe before spawning so no early deltas are missed.
+    let mut rx = state.agui_event_bus.subscribe();
+    let (task, handle) = spawn_task(state, &params.message);
+    let store_state = state.clone();
+
+    let sse = |resp: JsonRpcResponse| {
+        Ok::<_, std::convert::Infallible>(
+            axum::response::sse::Event::default()
+                .data(serde_json::to_string(&resp).unwrap_or_default()),
+        )
+    };
+
+    Ok(async_stream::stream! {
+        let task_id = task.id.clone();
+        let context_id = task.context_id.clone();
+        let artifact_id = new_id("artifact");
+        yield sse(JsonRpcResponse::ok(id.clone(), json!(task)));
+
+        let mut handle = handle;
+        loop {
+            // Stop as soon as the agent job ends; yield outside `select!`.
+            let event = tokio::select! {
+                event = rx.recv() => event,
+                _ = &mut handle => break,
+            };
+            match event {
+                Ok(event)
+                    if event.run_id == task_id
+                        && event.event_type == EventType::TextMessageContent =>
+                {
+                    if let EventPayload::TextMessageContent { delta, .. } = event.payload {
+                        let artifact = Artifact {
+                            artifact_id: artifact_id.clone(),
+                            name: Some("response".to_string()),
+                            parts: vec![Part::Text { text: delta }],
+                        };
+                        yield sse(JsonRpcResponse::ok(id.clone(), json!({
+                            "kind": "artifact-update",
+                            "taskId": task_id,
+                            "contextId": context_id,
+                            "artifact": artifact,
+                            "append": true,
+                            "lastChunk": false,
+                        })));
+                    }
+                }
+                Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    let _ = (&mut handle).await;
+                    break;
+                }
+            }
+        }
+
+        if let Some(task) = store_state.a2a_tasks.get(&task_id) {
+            yield sse(JsonRpcResponse::ok(id.clone(), json!({
+                "kind": "status-update",
+                "taskId": task.id,
+                "contextId": task.context_id,
+                "status": task.status,
+                "final": true,
+            })));
+        }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// A2A Client (delegating to remote agents)
+// ---------------------------------------------------------------------------
+
+/// How long to wait for a delegated task before giving up.
+const DELEGATE_TIMEOUT_SECS: u64 = 300;
+/// Poll interval while waiting for a delegated task.
+const DELEGATE_POLL_MS: u64 = 1000;
+
+/// Fetch a remote agent's Agent Card from `<base_url>/.well-known/agent.json`.
+pub async fn fetch_agent_card(base_url: &str) -> anyhow::Result<AgentCard> {
+    let url = format!("{}/.well-known/agent.json", base_url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!("Agent card request returned {}", response.status());
+    }
+    Ok(response.json().await?)
+}
+
+/// Resolve the JSON-RPC endpoint for a remote agent.
+///
+/// Uses the card's "a2a" interface when available, otherwise `<base_url>/a2a`.
+pub async fn resolve_endpoint(base_url: &str) -> String {
+    match fetch_agent_card(base_url).await {
+        Ok(card) => card
+            .interfaces
+            .iter()
+            .find(|i| i.protocol == "a2a")
+            .map(|i| i.url.clone())
+            .unwrap_or_else(|| format!("{}/a2a", base_url.trim_end_matches('/'))),
+        Err(_) => format!("{}/a2a", base_url.trim_end_matches('/')),
+    }
+}
+
+/// Make a JSON-RPC call to a remote A2A endpoint and return its `result`.
+async fn rpc_call(
+    endpoint: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> anyhow::Result<serde_json::Value> {
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(new_id("req"))),
+        method: method.to_string(),
+        params: Some(params),
+    };
+    let response: JsonRpcResponse = reqwest::Client::new()
+        .post(endpoint)
+        .json(&request)
+        .timeout(std::time::Duration::from_secs(DELEGATE_TIMEOUT_SECS))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(err) = response.error {
+        anyhow::bail!("A2A error {}: {}", err.code, err.message);
+    }
+    response
+        .result
+        .ok_or_else(|| anyhow::anyhow!("A2A response has neither result nor error"))
+}
+
+/// Send a text message to a remote agent (`message/send`).
+pub async fn send_message(endpoint: &str, text: &str, blocking: bool) -> anyhow::Result<Task> {
+    let params = SendMessageParams {
+        message: A2aMessage::text("user", text),
+        configuration: Some(SendMessageConfig {
+            blocking,
+            history_length: None,
+            accepted_output_modes: vec!["text/plain".to_string()],
+        }),
+    };
+    let result = rpc_call(endpoint, "message/send", serde_json::to_value(params)?).await?;
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Get a task from a remote agent (`tasks/get`).
+pub async fn get_task(endpoint: &str, task_id: &str) -> anyhow::Result<Task> {
+    let result = rpc_call(endpoint, "tasks/get", json!({ "id": task_id })).await?;
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Cancel a task on a remote agent (`tasks/cancel`).
+pub async fn cancel_task(endpoint: &str, task_id: &str) -> anyhow::Result<Task> {
+    let result = rpc_call(endpoint, "tasks/cancel", json!({ "id": task_id })).await?;
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Delegate a subtask to a remote agent and wait for the outcome.
+///
+/// Returns the text of the task's artifacts (or final status message).
+pub async fn delegate_task(base_url: &str, text: &str) -> anyhow::Result<String> {
+    let endpoint = resolve_endpoint(base_url).await;
+    let mut task = send_message(&endpoint, text, true).await?;
+
+    let deadline =
+        std::time::Instant::now() + std::time::Duration::from_secs(DELEGATE_TIMEOUT_SECS);
+    while !task.status.state.is_terminal() {
+        if task.status.state == TaskState::InputRequired
+            || task.status.state == TaskState::AuthRequired
+        {
+            anyhow::bail!(
+                "Remote agent needs more input ({:?}): {}",
+                task.status.state,
+                task.status
+                    .message
+                    .as_ref()
+                    .map(|m| m.text_content())
+                    .unwrap_or_default()
+            );
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = cancel_task(&endpoint, &task.id).await;
+            anyhow::bail!(
+                "Remote agent did not finish within {}s",
+                DELEGATE_TIMEOUT_SECS
+            );
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(DELEGATE_POLL_MS)).await;
+        task = get_task(&endpoint, &task.id).await?;
+    }
+
+    let output = if task.artifacts.is_empty() {
+        task.status
+            .message
+            .as_ref()
+            .map(|m| m.text_content())
+            .unwrap_or_default()
+    } else {
+        task.artifacts
+            .iter()
+            .map(|a| parts_to_text(&a.parts))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    match task.status.state {
+        TaskState::Completed => Ok(output),
+        state => anyhow::bail!("Remote task ended as {:?}: {}", state, output),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -522,6 +1134,19 @@ mod tests {
         assert!(!json.contains("\"result\""));
     }
 
+    fn test_state() -> Arc<AppState> {
+        crate::test_app_state()
+    }
+
+    fn rpc(method: &str, params: serde_json::Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(1)),
+            method: method.to_string(),
+            params: Some(params),
+        }
+    }
+
     #[tokio::test]
     async fn test_dispatch_unknown_method() {
         let req = JsonRpcRequest {
@@ -530,8 +1155,123 @@ mod tests {
             method: "unknown/method".to_string(),
             params: None,
         };
-        let resp = dispatch_request(req).await;
+        let resp = dispatch_request(&test_state(), req).await;
         assert!(resp.error.is_some());
         assert_eq!(resp.error.unwrap().code, error_codes::UNSUPPORTED_OPERATION);
     }
+
+    #[tokio::test]
+    async fn test_dispatch_get_unknown_task() {
+        let resp = dispatch_request(&test_state(), rpc("tasks/get", json!({"id": "nope"}))).await;
+        assert_eq!(resp.error.unwrap().code, error_codes::TASK_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_send_invalid_params() {
+        let resp = dispatch_request(&test_state(), rpc("message/send", json!({"bogus": 1}))).await;
+        assert_eq!(resp.error.unwrap().code, error_codes::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_task_store_lifecycle() {
+        let store = TaskStore::new();
+        let task = store.create(&A2aMessage::text("user", "hello"));
+        assert_eq!(task.status.state, TaskState::Working);
+        assert_eq!(task.history.len(), 1);
+        assert_eq!(task.history[0].task_id.as_deref(), Some(task.id.as_str()));
+
+        store.update(&task.id, TaskState::Completed, Some("done"));
+        let done = store.get(&task.id).unwrap();
+        assert_eq!(done.status.state, TaskState::Completed);
+        assert_eq!(done.artifacts.len(), 1);
+        assert_eq!(parts_to_text(&done.artifacts[0].parts), "done");
+        assert_eq!(done.history.len(), 2);
+
+        // Terminal tasks are immutable and not cancelable
+        store.update(&task.id, TaskState::Failed, Some("late"));
+        assert_eq!(
+            store.get(&task.id).unwrap().status.state,
+            TaskState::Completed
+        );
+        assert_eq!(
+            store.cancel(&task.id).unwrap_err(),
+            error_codes::TASK_NOT_CANCELABLE
+        );
+    }
+
+    #[test]
+    fn test_task_store_cancel_and_list() {
+        let store = TaskStore::new();
+        let a = store.create(&A2aMessage::text("user", "a"));
+        let mut msg = A2aMessage::text("user", "b");
+        msg.context_id = Some("ctx-shared".into());
+        let b = store.create(&msg);
+
+        let canceled = store.cancel(&a.id).unwrap();
+        assert_eq!(canceled.status.state, TaskState::Canceled);
+        assert_eq!(
+            store.cancel("missing").unwrap_err(),
+            error_codes::TASK_NOT_FOUND
+        );
+
+        let all = store.list(&ListTasksParams::default());
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, b.id, "newest first");
+
+        let by_ctx = store.list(&ListTasksParams {
+            context_id: Some("ctx-shared".into()),
+            status: None,
+        });
+        assert_eq!(by_ctx.len(), 1);
+        let working = store.list(&ListTasksParams {
+            context_id: None,
+            status: Some(TaskState::Working),
+        });
+        assert_eq!(working.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_task_store_is_capped() {
+        let store = TaskStore::new();
+        let first = store.create(&A2aMessage::text("user", "first"));
+        let job = tokio::spawn(std::future::pending::<()>());
+        store.set_handle(&first.id, job.abort_handle());
+        for i in 0..MAX_STORED_TASKS {
+            store.create(&A2aMessage::text("user", &i.to_string()));
+        }
+
+        // Nothing finished, so the oldest running task was aborted and dropped
+        assert_eq!(
+            store.list(&ListTasksParams::default()).len(),
+            MAX_STORED_TASKS
+        );
+        assert!(store.get(&first.id).is_none());
+        assert!(job.await.unwrap_err().is_cancelled());
+        assert!(store.handles.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_length_trims_oldest() {
+        let store = TaskStore::new();
+        let task = store.create(&A2aMessage::text("user", "q"));
+        store.update(&task.id, TaskState::Completed, Some("a"));
+        let trimmed = with_history_length(store.get(&task.id).unwrap(), Some(1));
+        assert_eq!(trimmed.history.len(), 1);
+        assert_eq!(trimmed.history[0].role, "agent");
+    }
+
+    #[test]
+    fn test_send_params_deserialize() {
+        let params: SendMessageParams = serde_json::from_value(json!({
+            "message": {
+                "messageId": "m1",
+                "role": "user",
+                "parts": [{"text": "find my tax documents"}]
+            },
+            "configuration": {"blocking": true}
+        }))
+        .unwrap();
+        assert_eq!(params.message.text_content(), "find my tax documents");
+        assert!(params.configuration.unwrap().blocking);
+    }
 }
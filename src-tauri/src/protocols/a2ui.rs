@@ -6,6 +6,10 @@
 //!
 //! A2UI messages are transported via AG-UI CUSTOM events over Tauri IPC.
 //!
+//! Agent tools don't build surfaces by hand: a JSON tool result may declare
+//! a [`UiPayload`] (table, form or chart) under its `ui` key, which is
+//! validated and turned into a surface (see [`emit_tool_ui`]).
+//!
 //! ## Protocol Messages (Server → Client)
 //! - `createSurface` — Initialize a new UI surface with a catalog and theme.
 //! - `updateComponents` — Provide/update component definitions for a surface.
@@ -20,11 +24,12 @@
 //! Spec version: v0.9
 
 // A2UI types are the public API for agents to build generative UI.
-// Not yet called from main app code — usage begins when agents produce surfaces.
+// Not every message type and builder is used by Ghost's own tools yet.
 #![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use super::agui::{AgUiEvent, AgUiEventBus};
 
@@ -617,6 +622,358 @@ impl Default for A2uiComponent {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Tool Result UI Payloads
+// ---------------------------------------------------------------------------
+
+/// Key of a JSON tool result that declares UI to show.
+pub const TOOL_UI_KEY: &str = "ui";
+
+/// Most payloads one tool result may declare.
+const MAX_UI_PAYLOADS: usize = 4;
+const MAX_TABLE_COLUMNS: usize = 20;
+const MAX_TABLE_ROWS: usize = 200;
+const MAX_FORM_FIELDS: usize = 20;
+const MAX_CHART_SERIES: usize = 8;
+const MAX_CHART_POINTS: usize = 100;
+
+/// UI a tool result can declare, rendered as a card in the chat.
+///
+/// Tables and charts are Ghost catalog extensions (`Table`, `Chart`) bound to
+/// the surface's data model; forms use standard input components.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UiPayload {
+    Table {
+        #[serde(default)]
+        title: Option<String>,
+        columns: Vec<String>,
+        /// One array of scalar cells per row, in column order.
+        rows: Vec<Vec<serde_json::Value>>,
+    },
+    Form {
+        #[serde(default)]
+        title: Option<String>,
+        fields: Vec<FormField>,
+        /// Event sent when the form is submitted.
+        action: String,
+        #[serde(default)]
+        submit_label: Option<String>,
+    },
+    Chart {
+        #[serde(default)]
+        title: Option<String>,
+        kind: ChartKind,
+        /// X-axis labels (or slice labels for pie charts).
+        labels: Vec<String>,
+        series: Vec<ChartSeries>,
+    },
+}
+
+/// One input of a form payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormField {
+    pub name: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub kind: FieldKind,
+    /// Choices, for `choice` fields.
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Initial value.
+    #[serde(default)]
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    #[default]
+    Text,
+    LongText,
+    Number,
+    Checkbox,
+    Choice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartKind {
+    Bar,
+    Line,
+    Pie,
+}
+
+/// A named series of chart values, one per label.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSeries {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+impl UiPayload {
+    /// Check the payload's shape and size limits.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            UiPayload::Table { columns, rows, .. } => {
+                if columns.is_empty() || columns.len() > MAX_TABLE_COLUMNS {
+                    return Err(format!("a table needs 1 to {} columns", MAX_TABLE_COLUMNS));
+                }
+                if rows.len() > MAX_TABLE_ROWS {
+                    return Err(format!("a table has at most {} rows", MAX_TABLE_ROWS));
+                }
+                for (i, row) in rows.iter().enumerate() {
+                    if row.len() != columns.len() {
+                        return Err(format!(
+                            "row {} has {} cells, expected {}",
+                            i,
+                            row.len(),
+                            columns.len()
+                        ));
+                    }
+                    if row.iter().any(|c| c.is_object() || c.is_array()) {
+                        return Err(format!("row {} has a non-scalar cell", i));
+                    }
+                }
+            }
+            UiPayload::Form { fields, action, .. } => {
+                if action.trim().is_empty() {
+                    return Err("a form needs an action".into());
+                }
+                if fields.is_empty() || fields.len() > MAX_FORM_FIELDS {
+                    return Err(format!("a form needs 1 to {} fields", MAX_FORM_FIELDS));
+                }
+                let mut names = HashSet::new();
+                for field in fields {
+                    if field.name.is_empty() || field.name.contains('/') {
+                        return Err(format!("invalid field name '{}'", field.name));
+                    }
+                    if !names.insert(field.name.as_str()) {
+                        return Err(format!("duplicate field '{}'", field.name));
+                    }
+                    if field.kind == FieldKind::Choice && field.options.is_empty() {
+                        return Err(format!("choice field '{}' has no options", field.name));
+                    }
+                }
+            }
+            UiPayload::Chart {
+                kind,
+                labels,
+                series,
+                ..
+            } => {
+                if labels.is_empty() || labels.len() > MAX_CHART_POINTS {
+                    return Err(format!("a chart needs 1 to {} labels", MAX_CHART_POINTS));
+                }
+                if series.is_empty() || series.len() > MAX_CHART_SERIES {
+                    return Err(format!("a chart needs 1 to {} series", MAX_CHART_SERIES));
+                }
+                if *kind == ChartKind::Pie && series.len() != 1 {
+                    return Err("a pie chart has exactly one series".into());
+                }
+                for s in series {
+                    if s.values.len() != labels.len() {
+                        return Err(format!(
+                            "series '{}' has {} values, expected {}",
+                            s.name,
+                            s.values.len(),
+                            labels.len()
+                        ));
+                    }
+                    if s.values.iter().any(|v| !v.is_finite()) {
+                        return Err(format!("series '{}' has a non-finite value", s.name));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the surface: components (rooted at a Card) and the data model.
+    pub fn to_surface(&self) -> (Vec<A2uiComponent>, serde_json::Value) {
+        let (title, mut body, data) = match self {
+            UiPayload::Table {
+                title,
+                columns,
+                rows,
+            } => {
+                let table = A2uiComponent {
+                    id: "table".into(),
+                    component: "Table".into(),
+                    extra: HashMap::from([
+                        ("columns".into(), serde_json::json!(columns)),
+                        ("rows".into(), serde_json::json!({ "path": "/rows" })),
+                    ]),
+                    ..Default::default()
+                };
+                (title, vec![table], serde_json::json!({ "rows": rows }))
+            }
+            UiPayload::Chart {
+                title,
+                kind,
+                labels,
+                series,
+            } => {
+                let chart = A2uiComponent {
+                    id: "chart".into(),
+                    component: "Chart".into(),
+                    variant: serde_json::to_value(kind)
+                        .ok()
+                        .and_then(|v| v.as_str().map(str::to_string)),
+                    extra: HashMap::from([
+                        ("labels".into(), serde_json::json!({ "path": "/labels" })),
+                        ("series".into(), serde_json::json!({ "path": "/series" })),
+                    ]),
+                    ..Default::default()
+                };
+                (
+                    title,
+                    vec![chart],
+                    serde_json::json!({ "labels": labels, "series": series }),
+                )
+            }
+            UiPayload::Form {
+                title,
+                fields,
+                action,
+                submit_label,
+            } => {
+                let mut components = Vec::with_capacity(fields.len() + 2);
+                let mut values = serde_json::Map::new();
+                for field in fields {
+                    let id = format!("field-{}", field.name);
+                    let path = format!("/form/{}", field.name);
+                    let mut label = field.label.clone().unwrap_or_else(|| field.name.clone());
+                    if field.required {
+                        label.push_str(" *");
+                    }
+                    let component = match field.kind {
+                        FieldKind::Checkbox => Components::checkbox(&id, &label, &path),
+                        FieldKind::Choice => {
+                            let options = field.options.iter().map(|o| (o.as_str(), o.as_str()));
+                            let mut c = Components::choice_picker(
+                                &id,
+                                options.collect(),
+                                &path,
+                                "dropdown",
+                            );
+                            c.label = Some(DynamicString::Literal(label));
+                            c
+                        }
+                        FieldKind::Text | FieldKind::LongText | FieldKind::Number => {
+                            let mut c = Components::text_field(&id, &label, &path);
+                            c.variant = Some(
+                                match field.kind {
+                                    FieldKind::LongText => "longText",
+                                    FieldKind::Number => "number",
+                                    _ => "shortText",
+                                }
+                                .into(),
+                            );
+                            c
+                        }
+                    };
+                    components.push(component);
+                    let initial = field.value.clone().unwrap_or(match field.kind {
+                        FieldKind::Checkbox => serde_json::Value::Bool(false),
+                        _ => serde_json::Value::String(String::new()),
+                    });
+                    values.insert(field.name.clone(), initial);
+                }
+                let submit_text = submit_label.as_deref().unwrap_or("Submit");
+                components.push(Components::text("submit-label", submit_text));
+                let mut submit = Components::button("submit", "submit-label", action);
+                if let Some(event) = submit.action.as_mut().and_then(|a| a.event.as_mut()) {
+                    event.context = Some(serde_json::json!({ "values": { "path": "/form" } }));
+                }
+                components.push(submit);
+                (title, components, serde_json::json!({ "form": values }))
+            }
+        };
+
+        let mut children: Vec<String> = Vec::new();
+        let mut components = Vec::with_capacity(body.len() + 3);
+        if let Some(title) = title {
+            children.push("title".into());
+            components.push(Components::text_variant("title", title, "h3"));
+        }
+        // Only top-level body components go in the column; the submit label
+        // is the button's child
+        children.extend(
+            body.iter()
+                .filter(|c| c.id != "submit-label")
+                .map(|c| c.id.clone()),
+        );
+        components.append(&mut body);
+        let child_refs: Vec<&str> = children.iter().map(String::as_str).collect();
+        components.push(Components::column("content", child_refs));
+        components.push(Components::card("root", "content"));
+        (components, data)
+    }
+}
+
+/// Parse the `ui` value of a tool result: one payload or a list of them.
+pub fn parse_ui_payloads(value: &serde_json::Value) -> Result<Vec<UiPayload>, String> {
+    let items = match value {
+        serde_json::Value::Array(items) => items.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+    if items.is_empty() || items.len() > MAX_UI_PAYLOADS {
+        return Err(format!("declare 1 to {} UI payloads", MAX_UI_PAYLOADS));
+    }
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let payload: UiPayload = serde_json::from_value(item.clone())
+                .map_err(|e| format!("UI payload {}: {}", i, e))?;
+            payload
+                .validate()
+                .map_err(|e| format!("UI payload {}: {}", i, e))?;
+            Ok(payload)
+        })
+        .collect()
+}
+
+static NEXT_SURFACE: AtomicU64 = AtomicU64::new(1);
+
+/// Show the UI a tool result declares, returning the text for the model.
+///
+/// Results that aren't JSON objects with a [`TOOL_UI_KEY`] pass through
+/// untouched. Valid payloads are emitted as surfaces on the run and the key
+/// is replaced by a note that the user saw them; invalid ones show nothing
+/// and the key becomes a `ui_error` the model can act on.
+pub fn emit_tool_ui(run_id: &str, result: String, event_bus: &AgUiEventBus) -> String {
+    let Ok(serde_json::Value::Object(mut object)) = serde_json::from_str(&result) else {
+        return result;
+    };
+    let Some(ui) = object.remove(TOOL_UI_KEY) else {
+        return result;
+    };
+    match parse_ui_payloads(&ui) {
+        Ok(payloads) => {
+            for payload in &payloads {
+                let surface_id =
+                    format!("tool-ui-{}", NEXT_SURFACE.fetch_add(1, Ordering::Relaxed));
+                let (components, data) = payload.to_surface();
+                emit_surface(run_id, &surface_id, components, Some(data), None, event_bus);
+            }
+            object.insert(
+                TOOL_UI_KEY.into(),
+                serde_json::json!(format!("Shown to the user as {} card(s)", payloads.len())),
+            );
+        }
+        Err(e) => {
+            tracing::warn!("Tool UI payload rejected: {}", e);
+            object.insert("ui_error".into(), serde_json::json!(e));
+        }
+    }
+    serde_json::Value::Object(object).to_string()
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -755,4 +1112,106 @@ mod tests {
             let _roundtrip: A2uiMessage = serde_json::from_str(&json).unwrap();
         }
     }
+
+    #[test]
+    fn test_ui_payload_validation() {
+        let table = serde_json::json!({
+            "type": "table",
+            "columns": ["File", "Size"],
+            "rows": [["a.md", 120], ["b.md", null]],
+        });
+        assert!(parse_ui_payloads(&table).is_ok());
+
+        let ragged = serde_json::json!({
+            "type": "table", "columns": ["File", "Size"], "rows": [["a.md"]],
+        });
+        let err = parse_ui_payloads(&ragged).unwrap_err();
+        assert!(err.contains("row 0"), "{err}");
+
+        let pie = serde_json::json!({
+            "type": "chart",
+            "kind": "pie",
+            "labels": ["pdf", "md"],
+            "series": [{ "name": "a", "values": [1, 2] }, { "name": "b", "values": [3, 4] }],
+        });
+        assert!(parse_ui_payloads(&pie).is_err());
+
+        let form = serde_json::json!([{
+            "type": "form",
+            "action": "create_issue",
+            "fields": [{ "name": "title" }, { "name": "title", "kind": "long_text" }],
+        }]);
+        assert!(parse_ui_payloads(&form).unwrap_err().contains("duplicate"));
+        assert!(parse_ui_payloads(&serde_json::json!({ "type": "video" })).is_err());
+    }
+
+    #[test]
+    fn test_form_surface() {
+        let payload: UiPayload = serde_json::from_value(serde_json::json!({
+            "type": "form",
+            "title": "New issue",
+            "action": "create_issue",
+            "fields": [
+                { "name": "title", "required": true },
+                { "name": "urgent", "kind": "checkbox" },
+                { "name": "repo", "kind": "choice", "options": ["ghost", "site"] },
+            ],
+        }))
+        .unwrap();
+        payload.validate().unwrap();
+        let (components, data) = payload.to_surface();
+        let ids: Vec<&str> = components.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "title",
+                "field-title",
+                "field-urgent",
+                "field-repo",
+                "submit-label",
+                "submit",
+                "content",
+                "root"
+            ]
+        );
+        assert_eq!(
+            data,
+            serde_json::json!({ "form": { "title": "", "urgent": false, "repo": "" } })
+        );
+        let content = components.iter().find(|c| c.id == "content").unwrap();
+        assert!(
+            matches!(&content.children, Some(ChildList::Array(ids)) if !ids.contains(&"submit-label".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_emit_tool_ui() {
+        let bus = AgUiEventBus::new(16);
+        let mut rx = bus.subscribe();
+
+        let plain = "[1, 2, 3]".to_string();
+        assert_eq!(emit_tool_ui("run-1", plain.clone(), &bus), plain);
+
+        let result = serde_json::json!({
+            "count": 1,
+            "ui": { "type": "table", "columns": ["Name"], "rows": [["Ana"]] },
+        })
+        .to_string();
+        let text: serde_json::Value =
+            serde_json::from_str(&emit_tool_ui("run-1", result, &bus)).unwrap();
+        assert_eq!(text["count"], 1);
+        assert!(text["ui"].as_str().unwrap().contains("1 card"));
+        // createSurface, updateComponents, updateDataModel
+        for _ in 0..3 {
+            assert_eq!(rx.try_recv().unwrap().run_id, "run-1");
+        }
+
+        let invalid =
+            serde_json::json!({ "ui": { "type": "table", "columns": [], "rows": [] } }).to_string();
+        let text: serde_json::Value =
+            serde_json::from_str(&emit_tool_ui("run-1", invalid, &bus)).unwrap();
+        assert!(text.get("ui").is_none());
+        assert!(text["ui_error"].is_string());
+        assert!(rx.try_recv().is_err());
+    }
 }
@@ -14,11 +14,15 @@
 //! - Special:        RAW, CUSTOM
 //!
 //! Transport: Tauri events (frontend↔backend IPC) for desktop use.
-//! The protocol is also exposed via SSE on the MCP HTTP server for external clients.
+//! The protocol is also exposed via SSE on the MCP HTTP server for external clients,
+//! and over a WebSocket server (`/agui/ws`) that lets dashboards and browser
+//! clients both observe and drive agent runs.
 //!
 //! Reference: https://docs.ag-ui.com/concepts/events
 
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
@@ -89,11 +93,52 @@ pub struct AgUiEvent {
     pub thread_id: Option<String>,
     /// Unix timestamp in milliseconds.
     pub timestamp: u64,
+    /// 1-based position in the run's event stream, assigned by
+    /// [`AgUiEventBus::emit`]. Clients pass the last one they saw to
+    /// [`AgUiEventBus::events_since`] to recover missed events.
+    #[serde(default)]
+    pub seq: u64,
+    /// Screen-reader summary, filled in by [`AgUiEventBus::emit`] for events
+    /// worth announcing (streaming deltas have none).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessibility: Option<Accessibility>,
     /// Event-specific payload.
     #[serde(flatten)]
     pub payload: EventPayload,
 }
 
+/// Accessibility metadata so the frontend can build screen-reader
+/// announcements (Narrator, VoiceOver) without re-deriving them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Accessibility {
+    /// Plain-language description of what happened.
+    pub label: String,
+    /// 1-based position in a sequence (step number), when meaningful.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    /// Length of the sequence (number of citations), when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+}
+
+impl Accessibility {
+    fn label(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            position: None,
+            total: None,
+        }
+    }
+}
+
+/// "ghost_search" -> "search", "read_file" -> "read file".
+fn humanize_tool_name(name: &str) -> String {
+    name.strip_prefix("ghost_")
+        .unwrap_or(name)
+        .replace(['_', '-'], " ")
+}
+
 /// Event-specific payload variants.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -102,6 +147,9 @@ pub enum EventPayload {
     RunStarted {
         #[serde(rename = "threadId", skip_serializing_if = "Option::is_none")]
         thread_id: Option<String>,
+        /// The run that spawned this one (sub-agent runs).
+        #[serde(rename = "parentRunId", skip_serializing_if = "Option::is_none")]
+        parent_run_id: Option<String>,
     },
     /// RUN_FINISHED event.
     RunFinished {},
@@ -277,18 +325,35 @@ impl AgUiEvent {
             event_type: EventType::RunStarted,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
-            payload: EventPayload::RunStarted { thread_id: None },
+            seq: 0,
+            payload: EventPayload::RunStarted {
+                thread_id: None,
+                parent_run_id: None,
+            },
         }
     }
 
+    /// Create a RUN_STARTED event for a sub-agent run spawned by `parent_run_id`.
+    pub fn child_run_started(run_id: &str, parent_run_id: &str) -> Self {
+        let mut event = Self::run_started(run_id);
+        event.payload = EventPayload::RunStarted {
+            thread_id: None,
+            parent_run_id: Some(parent_run_id.to_string()),
+        };
+        event
+    }
+
     /// Create a RUN_FINISHED event.
     pub fn run_finished(run_id: &str) -> Self {
         Self {
             event_type: EventType::RunFinished,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::RunFinished {},
         }
     }
@@ -299,7 +364,9 @@ impl AgUiEvent {
             event_type: EventType::RunError,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::RunError {
                 message: message.to_string(),
                 code: None,
@@ -313,7 +380,9 @@ impl AgUiEvent {
             event_type: EventType::StepStarted,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::StepStarted {
                 step_name: step_name.to_string(),
                 step_index,
@@ -327,7 +396,9 @@ impl AgUiEvent {
             event_type: EventType::StepFinished,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::StepFinished {
                 step_name: step_name.to_string(),
             },
@@ -340,7 +411,9 @@ impl AgUiEvent {
             event_type: EventType::TextMessageStart,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::TextMessageStart {
                 message_id: message_id.to_string(),
                 role: role.to_string(),
@@ -354,7 +427,9 @@ impl AgUiEvent {
             event_type: EventType::TextMessageContent,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::TextMessageContent {
                 message_id: message_id.to_string(),
                 delta: delta.to_string(),
@@ -368,7 +443,9 @@ impl AgUiEvent {
             event_type: EventType::TextMessageEnd,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::TextMessageEnd {
                 message_id: message_id.to_string(),
             },
@@ -386,7 +463,9 @@ impl AgUiEvent {
             event_type: EventType::ToolCallStart,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ToolCallStart {
                 tool_call_id: tool_call_id.to_string(),
                 tool_call_name: tool_name.to_string(),
@@ -401,7 +480,9 @@ impl AgUiEvent {
             event_type: EventType::ToolCallArgs,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ToolCallArgs {
                 tool_call_id: tool_call_id.to_string(),
                 delta: delta.to_string(),
@@ -415,7 +496,9 @@ impl AgUiEvent {
             event_type: EventType::ToolCallEnd,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ToolCallEnd {
                 tool_call_id: tool_call_id.to_string(),
                 result: result.map(|s| s.to_string()),
@@ -429,7 +512,9 @@ impl AgUiEvent {
             event_type: EventType::StateSnapshot,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::StateSnapshot { snapshot },
         }
     }
@@ -440,7 +525,9 @@ impl AgUiEvent {
             event_type: EventType::StateDelta,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::StateDelta { delta },
         }
     }
@@ -451,7 +538,9 @@ impl AgUiEvent {
             event_type: EventType::MessagesSnapshot,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::MessagesSnapshot { messages },
         }
     }
@@ -467,7 +556,9 @@ impl AgUiEvent {
             event_type: EventType::ToolCallResult,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ToolCallResult {
                 message_id: message_id.to_string(),
                 tool_call_id: tool_call_id.to_string(),
@@ -488,7 +579,9 @@ impl AgUiEvent {
             event_type: EventType::ActivitySnapshot,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ActivitySnapshot {
                 message_id: message_id.to_string(),
                 activity_type: activity_type.to_string(),
@@ -504,7 +597,9 @@ impl AgUiEvent {
             event_type: EventType::ReasoningStart,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ReasoningStart {
                 message_id: message_id.to_string(),
             },
@@ -517,7 +612,9 @@ impl AgUiEvent {
             event_type: EventType::ReasoningMessageContent,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ReasoningMessageContent {
                 message_id: message_id.to_string(),
                 delta: delta.to_string(),
@@ -531,20 +628,66 @@ impl AgUiEvent {
             event_type: EventType::ReasoningEnd,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::ReasoningEnd {
                 message_id: message_id.to_string(),
             },
         }
     }
 
+    /// Plain-language description of this event, if it is worth announcing.
+    pub fn describe(&self) -> Option<Accessibility> {
+        if self.event_type == EventType::RunFinished {
+            return Some(Accessibility::label("Ghost finished responding"));
+        }
+        match &self.payload {
+            EventPayload::RunStarted { .. } => {
+                Some(Accessibility::label("Ghost is working on your request"))
+            }
+            EventPayload::RunError { message, .. } => {
+                Some(Accessibility::label(format!("Error: {}", message)))
+            }
+            EventPayload::StepStarted {
+                step_name,
+                step_index,
+            } => Some(Accessibility {
+                label: match step_index {
+                    Some(i) => format!("Step {}: {}", i + 1, step_name),
+                    None => format!("Started {}", step_name),
+                },
+                position: step_index.map(|i| i + 1),
+                total: None,
+            }),
+            EventPayload::ToolCallStart { tool_call_name, .. } => Some(Accessibility::label(
+                format!("Using {}", humanize_tool_name(tool_call_name)),
+            )),
+            EventPayload::ReasoningStart { .. } => Some(Accessibility::label("Ghost is thinking")),
+            EventPayload::CustomEvent { name, value } if name == "citations" => {
+                let total = value["citations"].as_array().map_or(0, |c| c.len());
+                Some(Accessibility {
+                    label: match total {
+                        1 => "1 source cited".to_string(),
+                        n => format!("{} sources cited", n),
+                    },
+                    position: None,
+                    total: Some(total),
+                })
+            }
+            _ => None,
+        }
+    }
+
     /// Create a CUSTOM event.
     pub fn custom(run_id: &str, name: &str, value: serde_json::Value) -> Self {
         Self {
             event_type: EventType::Custom,
             run_id: run_id.to_string(),
             thread_id: None,
+            accessibility: None,
             timestamp: Self::now_ms(),
+            seq: 0,
             payload: EventPayload::CustomEvent {
                 name: name.to_string(),
                 value,
@@ -557,25 +700,117 @@ impl AgUiEvent {
 // AG-UI Event Bus (broadcast channel for real-time streaming)
 // ---------------------------------------------------------------------------
 
+/// Events kept per run for replay; older ones are dropped first.
+const RUN_BUFFER_CAPACITY: usize = 4096;
+
+/// Runs kept for replay; finished runs are evicted before running ones.
+const MAX_BUFFERED_RUNS: usize = 16;
+
+/// Recent events of one run.
+#[derive(Default)]
+struct RunBuffer {
+    events: VecDeque<AgUiEvent>,
+    last_seq: u64,
+    finished: bool,
+}
+
+/// Per-run buffers in creation order.
+#[derive(Default)]
+struct RunBuffers {
+    runs: HashMap<String, RunBuffer>,
+    order: VecDeque<String>,
+}
+
+impl RunBuffers {
+    /// Drop one run to make room: the oldest finished one, else the oldest.
+    fn evict(&mut self) -> usize {
+        let index = self
+            .order
+            .iter()
+            .position(|id| self.runs.get(id).is_some_and(|r| r.finished))
+            .unwrap_or(0);
+        match self.order.remove(index) {
+            Some(id) => self.runs.remove(&id).map_or(0, |r| r.events.len()),
+            None => 0,
+        }
+    }
+}
+
+/// Events of one run after a given sequence number.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvents {
+    pub run_id: String,
+    pub events: Vec<AgUiEvent>,
+    /// Sequence number of the run's latest event.
+    pub last_seq: u64,
+    /// Whether events after `since_seq` were already dropped from the buffer.
+    pub truncated: bool,
+    /// Whether the run has finished (RUN_FINISHED or RUN_ERROR).
+    pub finished: bool,
+}
+
+/// Backpressure counters for the event bus.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMetrics {
+    /// Broadcast channel capacity per subscriber.
+    pub capacity: usize,
+    pub subscribers: usize,
+    pub emitted: u64,
+    /// Events subscribers skipped because they fell behind.
+    pub lagged: u64,
+    /// Events recovered from run buffers after a lag.
+    pub replayed: u64,
+    pub buffered_runs: usize,
+    pub buffered_events: usize,
+    /// Events dropped from full or evicted run buffers.
+    pub evicted: u64,
+}
+
 /// The AG-UI event bus — distributes events to all subscribers.
 ///
 /// Uses tokio broadcast channel for fan-out to multiple consumers:
 /// - Tauri IPC events (frontend)
 /// - SSE endpoint (external clients)
 /// - Internal logging
+///
+/// A broadcast receiver that falls behind skips the oldest events, so every
+/// event is also numbered and kept in a bounded per-run buffer. Receivers
+/// that lag (or frontends that reconnect) replay what they missed with
+/// [`events_since`](Self::events_since).
 pub struct AgUiEventBus {
     sender: broadcast::Sender<AgUiEvent>,
+    capacity: usize,
+    runs: Mutex<RunBuffers>,
+    emitted: AtomicU64,
+    lagged: AtomicU64,
+    replayed: AtomicU64,
+    evicted: AtomicU64,
 }
 
 impl AgUiEventBus {
     /// Create a new event bus with the given channel capacity.
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            capacity,
+            runs: Mutex::new(RunBuffers::default()),
+            emitted: AtomicU64::new(0),
+            lagged: AtomicU64::new(0),
+            replayed: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+        }
     }
 
     /// Emit an event to all subscribers.
-    pub fn emit(&self, event: AgUiEvent) {
+    pub fn emit(&self, mut event: AgUiEvent) {
+        if event.accessibility.is_none() {
+            event.accessibility = event.describe();
+        }
+        self.buffer(&mut event);
+        self.emitted.fetch_add(1, Ordering::Relaxed);
         let event_type = format!("{:?}", event.event_type);
         match self.sender.send(event) {
             Ok(n) => {
@@ -597,6 +832,390 @@ impl AgUiEventBus {
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
     }
+
+    /// Number the event within its run and keep a copy for replay.
+    fn buffer(&self, event: &mut AgUiEvent) {
+        let Ok(mut buffers) = self.runs.lock() else {
+            return;
+        };
+        if !buffers.runs.contains_key(&event.run_id) {
+            while buffers.runs.len() >= MAX_BUFFERED_RUNS {
+                let dropped = buffers.evict();
+                self.evicted.fetch_add(dropped as u64, Ordering::Relaxed);
+            }
+            buffers.order.push_back(event.run_id.clone());
+        }
+        let run = buffers.runs.entry(event.run_id.clone()).or_default();
+        run.last_seq += 1;
+        event.seq = run.last_seq;
+        if matches!(
+            event.event_type,
+            EventType::RunFinished | EventType::RunError
+        ) {
+            run.finished = true;
+        }
+        if run.events.len() >= RUN_BUFFER_CAPACITY {
+            run.events.pop_front();
+            self.evicted.fetch_add(1, Ordering::Relaxed);
+        }
+        run.events.push_back(event.clone());
+    }
+
+    /// Buffered events of a run with a sequence number above `since_seq`
+    /// (`None` when the run is unknown or was evicted).
+    pub fn events_since(&self, run_id: &str, since_seq: u64) -> Option<RunEvents> {
+        let buffers = self.runs.lock().ok()?;
+        let run = buffers.runs.get(run_id)?;
+        let first_seq = run.events.front().map_or(run.last_seq + 1, |e| e.seq);
+        Some(RunEvents {
+            run_id: run_id.to_string(),
+            events: run
+                .events
+                .iter()
+                .filter(|e| e.seq > since_seq)
+                .cloned()
+                .collect(),
+            last_seq: run.last_seq,
+            truncated: since_seq + 1 < first_seq,
+            finished: run.finished,
+        })
+    }
+
+    /// Note that a subscriber skipped `missed` events.
+    pub fn record_lag(&self, missed: u64) {
+        self.lagged.fetch_add(missed, Ordering::Relaxed);
+    }
+
+    /// Current backpressure counters.
+    pub fn metrics(&self) -> BusMetrics {
+        let (buffered_runs, buffered_events) = self
+            .runs
+            .lock()
+            .map(|b| (b.runs.len(), b.runs.values().map(|r| r.events.len()).sum()))
+            .unwrap_or((0, 0));
+        BusMetrics {
+            capacity: self.capacity,
+            subscribers: self.subscriber_count(),
+            emitted: self.emitted.load(Ordering::Relaxed),
+            lagged: self.lagged.load(Ordering::Relaxed),
+            replayed: self.replayed.load(Ordering::Relaxed),
+            buffered_runs,
+            buffered_events,
+            evicted: self.evicted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Follows one run on a broadcast receiver: filters out other runs and
+/// duplicates, and recovers events skipped while the receiver lagged.
+pub struct RunCursor {
+    run_id: String,
+    last_seq: u64,
+}
+
+impl RunCursor {
+    pub fn new(run_id: &str) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            last_seq: 0,
+        }
+    }
+
+    /// Whether `event` is the next unseen event of this run; advances the cursor.
+    pub fn accept(&mut self, event: &AgUiEvent) -> bool {
+        if event.run_id != self.run_id || event.seq <= self.last_seq {
+            return false;
+        }
+        self.last_seq = event.seq;
+        true
+    }
+
+    /// After a lag of `missed` events, the buffered events this cursor has
+    /// not seen yet, in order. Advances the cursor past them.
+    pub fn recover(&mut self, bus: &AgUiEventBus, missed: u64) -> Vec<AgUiEvent> {
+        bus.record_lag(missed);
+        let Some(replay) = bus.events_since(&self.run_id, self.last_seq) else {
+            return Vec::new();
+        };
+        if replay.truncated {
+            tracing::warn!(
+                "AG-UI run {} lost events after seq {} (buffer full)",
+                self.run_id,
+                self.last_seq
+            );
+        }
+        bus.replayed
+            .fetch_add(replay.events.len() as u64, Ordering::Relaxed);
+        if let Some(last) = replay.events.last() {
+            self.last_seq = last.seq;
+        }
+        replay.events
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AG-UI WebSocket transport
+// ---------------------------------------------------------------------------
+
+/// WebSocket transport configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgUiWsConfig {
+    /// Whether the WebSocket server is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Port for the WebSocket server (default: 6775).
+    #[serde(default = "default_ws_port")]
+    pub port: u16,
+    /// Hostname to bind (default: 127.0.0.1 — localhost only).
+    #[serde(default = "default_ws_host")]
+    pub host: String,
+    /// Extra browser origins allowed to connect (localhost origins are always allowed).
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+}
+
+fn default_ws_port() -> u16 {
+    6775
+}
+fn default_ws_host() -> String {
+    "127.0.0.1".into()
+}
+
+impl Default for AgUiWsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_ws_port(),
+            host: default_ws_host(),
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+/// Messages a WebSocket client can send to Ghost.
+///
+/// Tool approvals are answered only in the app: approval ids are broadcast
+/// in events, so any client could otherwise approve its own tool calls.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsClientMessage {
+    /// Only forward events for this run (`null` = all runs).
+    Subscribe {
+        #[serde(default, rename = "runId")]
+        run_id: Option<String>,
+    },
+    /// Start a new agent run. Events for it are streamed back on the socket.
+    Run {
+        messages: Vec<crate::chat::ChatMessage>,
+        #[serde(default, rename = "conversationId")]
+        conversation_id: Option<i64>,
+    },
+    /// Resend a run's buffered events after `sinceSeq` (after a lag or reconnect).
+    Replay {
+        #[serde(rename = "runId")]
+        run_id: String,
+        #[serde(default, rename = "sinceSeq")]
+        since_seq: u64,
+    },
+}
+
+/// Query parameters accepted on `/agui/ws`.
+#[derive(Debug, Default, Deserialize)]
+struct WsQuery {
+    run_id: Option<String>,
+}
+
+/// Whether a browser `Origin` header may open a WebSocket.
+///
+/// Browsers don't apply CORS to WebSockets, so without this check any web
+/// page could drive the local agent. Connections without an origin are
+/// refused too; other clients must send one of the allowed origins.
+fn is_allowed_origin(origin: Option<&str>, allowed: &[String]) -> bool {
+    let Some(origin) = origin else {
+        return false;
+    };
+    if allowed.iter().any(|o| o == origin) {
+        return true;
+    }
+    let rest = origin.split("://").nth(1).unwrap_or(origin);
+    let host = if rest.starts_with('[') {
+        rest.split(']').next().map(|h| format!("{}]", h))
+    } else {
+        rest.split([':', '/']).next().map(str::to_string)
+    };
+    matches!(
+        host.as_deref(),
+        Some("localhost" | "127.0.0.1" | "[::1]" | "tauri.localhost")
+    )
+}
+
+/// Numbers runs started over WebSockets, so sockets starting runs in the same
+/// millisecond get distinct run IDs.
+static NEXT_WS_RUN: AtomicU64 = AtomicU64::new(1);
+
+/// Handle one client message, returning the JSON reply sent back on the socket.
+fn handle_client_message(
+    state: &Arc<AppState>,
+    text: &str,
+    run_filter: &mut Option<String>,
+) -> serde_json::Value {
+    let message: WsClientMessage = match serde_json::from_str(text) {
+        Ok(m) => m,
+        Err(e) => {
+            return serde_json::json!({ "type": "ERROR", "message": format!("Invalid message: {}", e) })
+        }
+    };
+
+    match message {
+        WsClientMessage::Subscribe { run_id } => {
+            *run_filter = run_id.clone();
+            serde_json::json!({ "type": "SUBSCRIBED", "runId": run_id })
+        }
+        WsClientMessage::Run {
+            messages,
+            conversation_id,
+        } => {
+            if messages.is_empty() {
+                return serde_json::json!({ "type": "ERROR", "message": "messages cannot be empty" });
+            }
+            let run_id = format!(
+                "ws-run-{}-{}",
+                AgUiEvent::now_ms(),
+                NEXT_WS_RUN.fetch_add(1, Ordering::Relaxed)
+            );
+            crate::analytics::track(&state.db, crate::analytics::UsageFeature::AgentRun, None);
+
+            let state_inner = state.clone();
+            let run_id_inner = run_id.clone();
+            tokio::spawn(async move {
+                let executor = crate::agent::executor::AgentExecutor::new(state_inner.clone());
+                if let Err(e) = executor
+                    .run(
+                        &run_id_inner,
+                        &messages,
+                        conversation_id,
+                        &state_inner.agui_event_bus,
+                    )
+                    .await
+                {
+                    tracing::warn!("AG-UI WebSocket run {} failed: {}", run_id_inner, e);
+                }
+            });
+            serde_json::json!({ "type": "RUN_ACCEPTED", "runId": run_id })
+        }
+        WsClientMessage::Replay { run_id, since_seq } => {
+            match state.agui_event_bus.events_since(&run_id, since_seq) {
+                Some(replay) => {
+                    let mut reply = serde_json::to_value(replay).unwrap_or_default();
+                    reply["type"] = "REPLAY".into();
+                    reply
+                }
+                None => serde_json::json!({
+                    "type": "ERROR",
+                    "message": format!("No buffered events for run {}", run_id),
+                }),
+            }
+        }
+    }
+}
+
+/// Serve one WebSocket connection: forward bus events, handle client messages.
+async fn serve_socket(
+    mut socket: axum::extract::ws::WebSocket,
+    state: Arc<AppState>,
+    mut run_filter: Option<String>,
+) {
+    use axum::extract::ws::Message;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut rx = state.agui_event_bus.subscribe();
+    tracing::info!("AG-UI WebSocket client connected");
+
+    loop {
+        let outgoing = tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if run_filter.as_ref().is_some_and(|id| *id != event.run_id) {
+                        continue;
+                    }
+                    match serde_json::to_string(&event) {
+                        Ok(json) => json,
+                        Err(_) => continue,
+                    }
+                }
+                Err(RecvError::Closed) => break,
+                Err(RecvError::Lagged(n)) => {
+                    // Clients recover with a `replay` message per run
+                    tracing::warn!("AG-UI WebSocket client lagged by {} events", n);
+                    state.agui_event_bus.record_lag(n);
+                    format!("{{\"lagged\":{}}}", n)
+                }
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    handle_client_message(&state, text.as_str(), &mut run_filter).to_string()
+                }
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                // Ping/pong is answered by axum; binary frames are ignored.
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        if socket.send(Message::Text(outgoing.into())).await.is_err() {
+            break;
+        }
+    }
+
+    tracing::info!("AG-UI WebSocket client disconnected");
+}
+
+/// Start the AG-UI WebSocket server in the background.
+/// Returns the address it's listening on.
+pub async fn start_ws_server(
+    state: Arc<AppState>,
+    config: &AgUiWsConfig,
+) -> anyhow::Result<String> {
+    if !config.enabled {
+        tracing::info!("AG-UI WebSocket server disabled in settings");
+        return Ok("disabled".to_string());
+    }
+
+    let allowed_origins = Arc::new(config.allowed_origins.clone());
+    let ws_handler = axum::routing::get(
+        move |ws: axum::extract::ws::WebSocketUpgrade,
+              headers: axum::http::HeaderMap,
+              axum::extract::Query(query): axum::extract::Query<WsQuery>| {
+            let state = state.clone();
+            let allowed_origins = allowed_origins.clone();
+            async move {
+                use axum::response::IntoResponse;
+                let origin = headers
+                    .get(axum::http::header::ORIGIN)
+                    .and_then(|v| v.to_str().ok());
+                if !is_allowed_origin(origin, &allowed_origins) {
+                    tracing::warn!("AG-UI WebSocket rejected origin {:?}", origin);
+                    return axum::http::StatusCode::FORBIDDEN.into_response();
+                }
+                ws.on_upgrade(move |socket| serve_socket(socket, state, query.run_id))
+                    .into_response()
+            }
+        },
+    );
+
+    let router = axum::Router::new().route("/agui/ws", ws_handler);
+
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let addr_str = listener.local_addr()?.to_string();
+    tracing::info!("AG-UI WebSocket endpoint on ws://{}/agui/ws", addr_str);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!("AG-UI WebSocket server error: {}", e);
+        }
+    });
+
+    Ok(addr_str)
 }
 
 // ---------------------------------------------------------------------------
@@ -730,11 +1349,14 @@ impl AgentRunner {
         }
 
         // Execute the tool call
-        match self
-            .state
-            .mcp_client
-            .call_tool(server_name, tool_name, arguments)
-            .await
+        match super::mcp_trace::call_traced(
+            &self.state,
+            Some(run_id),
+            server_name,
+            tool_name,
+            arguments,
+        )
+        .await
         {
             Ok(result) => {
                 event_bus.emit(AgUiEvent::tool_call_end(
@@ -775,6 +1397,16 @@ mod tests {
         assert!(json.contains("\"runId\":\"run-123\""));
     }
 
+    #[test]
+    fn test_child_run_started_links_parent() {
+        let json =
+            serde_json::to_value(AgUiEvent::child_run_started("run-1-sub1", "run-1")).unwrap();
+        assert_eq!(json["type"], "RUN_STARTED");
+        assert_eq!(json["parentRunId"], "run-1");
+        let top = serde_json::to_value(AgUiEvent::run_started("run-1")).unwrap();
+        assert!(top.get("parentRunId").is_none());
+    }
+
     #[test]
     fn test_text_message_content_event() {
         let event = AgUiEvent::text_message_content("run-456", "msg-1", "Hello world");
@@ -824,6 +1456,40 @@ mod tests {
         assert_eq!(event.run_id, "run-sub");
     }
 
+    #[test]
+    fn test_accessibility_metadata() {
+        let bus = AgUiEventBus::new(32);
+        let mut rx = bus.subscribe();
+
+        bus.emit(AgUiEvent::step_started("run-a11y", "searching", Some(1)));
+        let step = rx.try_recv().unwrap().accessibility.unwrap();
+        assert_eq!(step.label, "Step 2: searching");
+        assert_eq!(step.position, Some(2));
+
+        bus.emit(AgUiEvent::tool_call_start(
+            "run-a11y",
+            "tc-1",
+            "ghost_read_file",
+            None,
+        ));
+        let json = serde_json::to_string(&rx.try_recv().unwrap()).unwrap();
+        assert!(json.contains("\"accessibility\":{\"label\":\"Using read file\"}"));
+
+        bus.emit(AgUiEvent::custom(
+            "run-a11y",
+            "citations",
+            serde_json::json!({"citations": [{}, {}]}),
+        ));
+        let citations = rx.try_recv().unwrap().accessibility.unwrap();
+        assert_eq!(citations.label, "2 sources cited");
+        assert_eq!(citations.total, Some(2));
+
+        // Streaming deltas are not announced
+        bus.emit(AgUiEvent::text_message_content("run-a11y", "m", "hi"));
+        let json = serde_json::to_string(&rx.try_recv().unwrap()).unwrap();
+        assert!(!json.contains("accessibility"));
+    }
+
     #[test]
     fn test_event_type_enum_serialization() {
         assert_eq!(
@@ -914,4 +1580,146 @@ mod tests {
         assert!(json.contains("\"messages\""));
         assert!(json.contains("\"hello\""));
     }
+    #[test]
+    fn test_ws_config_defaults_to_localhost() {
+        let config = AgUiWsConfig::default();
+        assert!(!config.enabled);
+        assert_eq!(config.host, "127.0.0.1");
+        assert_eq!(config.port, 6775);
+    }
+
+    #[test]
+    fn test_ws_origin_check() {
+        assert!(!is_allowed_origin(None, &[]));
+        assert!(is_allowed_origin(Some("http://localhost:5173"), &[]));
+        assert!(is_allowed_origin(Some("http://127.0.0.1"), &[]));
+        assert!(is_allowed_origin(Some("http://[::1]:3000"), &[]));
+        assert!(is_allowed_origin(Some("tauri://localhost"), &[]));
+        assert!(!is_allowed_origin(Some("https://evil.example"), &[]));
+        assert!(!is_allowed_origin(
+            Some("http://localhost.evil.example"),
+            &[]
+        ));
+        assert!(is_allowed_origin(
+            Some("https://dash.example"),
+            &["https://dash.example".to_string()]
+        ));
+    }
+
+    #[test]
+    fn test_lagged_receiver_recovers_from_run_buffer() {
+        use tokio::sync::broadcast::error::TryRecvError;
+
+        let bus = AgUiEventBus::new(2);
+        let mut rx = bus.subscribe();
+        bus.emit(AgUiEvent::run_started("run-a"));
+        for i in 0..4 {
+            bus.emit(AgUiEvent::text_message_content(
+                "run-a",
+                "m",
+                &i.to_string(),
+            ));
+        }
+        bus.emit(AgUiEvent::run_started("run-b"));
+
+        let mut cursor = RunCursor::new("run-a");
+        let missed = match rx.try_recv() {
+            Err(TryRecvError::Lagged(n)) => n,
+            other => panic!("expected lag, got {:?}", other),
+        };
+        let seqs: Vec<u64> = cursor.recover(&bus, missed).iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+        // What the receiver still holds is a duplicate or another run
+        while let Ok(event) = rx.try_recv() {
+            assert!(!cursor.accept(&event));
+        }
+
+        let replay = bus.events_since("run-a", 3).unwrap();
+        assert_eq!(replay.events.len(), 2);
+        assert_eq!(replay.last_seq, 5);
+        assert!(!replay.truncated && !replay.finished);
+        assert!(bus.events_since("run-missing", 0).is_none());
+
+        let metrics = bus.metrics();
+        assert_eq!(metrics.emitted, 6);
+        assert_eq!(metrics.lagged, 4);
+        assert_eq!(metrics.replayed, 5);
+        assert_eq!((metrics.buffered_runs, metrics.buffered_events), (2, 6));
+    }
+
+    #[test]
+    fn test_run_buffer_eviction() {
+        let bus = AgUiEventBus::new(8);
+        for i in 0..MAX_BUFFERED_RUNS {
+            bus.emit(AgUiEvent::run_started(&format!("run-{}", i)));
+        }
+        bus.emit(AgUiEvent::run_finished("run-5"));
+        assert!(bus.events_since("run-5", 0).unwrap().finished);
+
+        // Finished runs go first, even when newer than running ones
+        bus.emit(AgUiEvent::run_started("run-new"));
+        assert!(bus.events_since("run-5", 0).is_none());
+        assert!(bus.events_since("run-0", 0).is_some());
+        assert_eq!(bus.metrics().evicted, 2);
+    }
+
+    #[test]
+    fn test_ws_client_message_parsing() {
+        let msg: WsClientMessage =
+            serde_json::from_str(r#"{"type":"subscribe","runId":"run-1"}"#).unwrap();
+        assert!(
+            matches!(msg, WsClientMessage::Subscribe { run_id: Some(ref id) } if id == "run-1")
+        );
+
+        let msg: WsClientMessage =
+            serde_json::from_str(r#"{"type":"run","messages":[{"role":"user","content":"hi"}]}"#)
+                .unwrap();
+        assert!(
+            matches!(msg, WsClientMessage::Run { ref messages, conversation_id: None } if messages.len() == 1)
+        );
+
+        // Approvals can't be sent over the socket
+        assert!(serde_json::from_str::<WsClientMessage>(
+            r#"{"type":"approve","approvalId":"approval-1","decision":"deny"}"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ws_handle_subscribe_and_errors() {
+        let state = crate::test_app_state();
+        let mut filter = None;
+
+        let reply = handle_client_message(
+            &state,
+            r#"{"type":"subscribe","runId":"run-9"}"#,
+            &mut filter,
+        );
+        assert_eq!(reply["type"], "SUBSCRIBED");
+        assert_eq!(filter.as_deref(), Some("run-9"));
+
+        let reply = handle_client_message(&state, "not json", &mut filter);
+        assert_eq!(reply["type"], "ERROR");
+
+        let reply = handle_client_message(&state, r#"{"type":"run","messages":[]}"#, &mut filter);
+        assert_eq!(reply["type"], "ERROR");
+
+        let reply = handle_client_message(
+            &state,
+            r#"{"type":"approve","approvalId":"approval-404","decision":"approve"}"#,
+            &mut filter,
+        );
+        assert_eq!(reply["type"], "ERROR");
+
+        state.agui_event_bus.emit(AgUiEvent::run_started("run-9"));
+        state.agui_event_bus.emit(AgUiEvent::run_finished("run-9"));
+        let reply = handle_client_message(
+            &state,
+            r#"{"type":"replay","runId":"run-9","sinceSeq":1}"#,
+            &mut filter,
+        );
+        assert_eq!(reply["type"], "REPLAY");
+        assert_eq!(reply["events"][0]["seq"], 2);
+        assert_eq!(reply["finished"], true);
+    }
 }
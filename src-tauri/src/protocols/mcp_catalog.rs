@@ -1229,7 +1229,7 @@ pub fn build_server_entry(
         transport: entry.transport.clone(),
         command: Some(entry.command.clone()),
         args: resolved_args,
-        url: if entry.transport == "http" {
+        url: if entry.transport == "http" || entry.transport == "sse" {
             // For remote servers, the URL is in args[0] or env
             entry.args.first().cloned()
         } else {
@@ -1237,6 +1237,359 @@ pub fn build_server_entry(
         },
         enabled: true,
         env: env_vars,
+        limits: Default::default(),
+        sampling: Default::default(),
+    }
+}
+
+// ─── Install Wizard ─────────────────────────────
+
+/// How long the wizard's test call may take.
+const TEST_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Tool name prefixes the wizard treats as read-only.
+const READ_ONLY_PREFIXES: &[&str] = &[
+    "list", "get", "read", "describe", "show", "whoami", "status", "ping", "current",
+];
+
+/// Tool name words that rule a tool out as a test call.
+const SIDE_EFFECT_WORDS: &[&str] = &[
+    "create", "delete", "remove", "write", "update", "send", "post", "push", "move", "exec", "run",
+    "set", "put", "kill", "drop",
+];
+
+/// Checks of an install, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStep {
+    Runtime,
+    Env,
+    Connect,
+    ListTools,
+    TestCall,
+}
+
+const INSTALL_STEPS: [InstallStep; 5] = [
+    InstallStep::Runtime,
+    InstallStep::Env,
+    InstallStep::Connect,
+    InstallStep::ListTools,
+    InstallStep::TestCall,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    /// Not needed, or not reached because an earlier step failed.
+    Skipped,
+}
+
+/// Outcome of one install check.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub step: InstallStep,
+    pub status: StepStatus,
+    pub message: String,
+    /// What the user can do about a failure.
+    pub hint: Option<String>,
+}
+
+/// What is wrong with a provided env var.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvProblem {
+    /// Required but absent or blank.
+    Missing,
+    /// Still the example value.
+    Placeholder,
+    /// Leading or trailing whitespace (usually a copy-paste slip).
+    Whitespace,
+}
+
+/// A problem with one env var, for the wizard to highlight its field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvIssue {
+    pub name: String,
+    pub label: String,
+    pub problem: EnvProblem,
+}
+
+/// Result of [`verify_install`]: every step in order, with the ones after a
+/// failure marked skipped.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallReport {
+    /// All checks passed and the server stays connected.
+    pub installed: bool,
+    pub steps: Vec<StepResult>,
+    pub env_issues: Vec<EnvIssue>,
+    /// Connection status, once the connect step ran.
+    pub server: Option<super::mcp_client::ConnectedServer>,
+    /// The tool the test call used.
+    pub test_tool: Option<String>,
+}
+
+impl InstallReport {
+    fn new() -> Self {
+        Self {
+            installed: false,
+            steps: Vec::new(),
+            env_issues: Vec::new(),
+            server: None,
+            test_tool: None,
+        }
+    }
+
+    fn record(&mut self, step: InstallStep, status: StepStatus, message: String) {
+        self.steps.push(StepResult {
+            step,
+            status,
+            message,
+            hint: None,
+        });
+    }
+
+    fn fail(mut self, step: InstallStep, message: String, hint: Option<String>) -> Self {
+        self.steps.push(StepResult {
+            step,
+            status: StepStatus::Failed,
+            message,
+            hint,
+        });
+        self.finish()
+    }
+
+    /// Mark the steps not reached as skipped.
+    fn finish(mut self) -> Self {
+        for step in INSTALL_STEPS.into_iter().skip(self.steps.len()) {
+            self.record(step, StepStatus::Skipped, "Not run".into());
+        }
+        self.installed = self.steps.iter().all(|s| s.status != StepStatus::Failed);
+        self
+    }
+}
+
+/// Check the env vars provided for `entry` against its specs.
+pub fn validate_env(entry: &CatalogEntry, env_vars: &HashMap<String, String>) -> Vec<EnvIssue> {
+    entry
+        .required_env
+        .iter()
+        .filter_map(|spec| {
+            let value = env_vars.get(&spec.name).map(String::as_str).unwrap_or("");
+            let problem = if value.trim().is_empty() {
+                spec.required.then_some(EnvProblem::Missing)?
+            } else if spec.placeholder.as_deref() == Some(value) {
+                EnvProblem::Placeholder
+            } else if value.trim() != value {
+                EnvProblem::Whitespace
+            } else {
+                return None;
+            };
+            Some(EnvIssue {
+                name: spec.name.clone(),
+                label: spec.label.clone(),
+                problem,
+            })
+        })
+        .collect()
+}
+
+/// A tool that is safe to call with no arguments: read-only by its name and
+/// without required parameters. `list_*` tools are preferred.
+pub fn pick_test_tool(
+    tools: &[super::mcp_client::ToolInfo],
+) -> Option<&super::mcp_client::ToolInfo> {
+    let words = |name: &str| -> Vec<String> {
+        name.split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect()
+    };
+    let candidates = tools.iter().filter(|tool| {
+        let words = words(&tool.name);
+        let no_required = tool
+            .input_schema
+            .as_ref()
+            .and_then(|schema| schema.get("required"))
+            .and_then(|required| required.as_array())
+            .map_or(true, |required| required.is_empty());
+        no_required
+            && words
+                .first()
+                .is_some_and(|first| READ_ONLY_PREFIXES.contains(&first.as_str()))
+            && !words
+                .iter()
+                .any(|w| SIDE_EFFECT_WORDS.contains(&w.as_str()))
+    });
+    candidates.min_by_key(|tool| !words(&tool.name)[0].starts_with("list"))
+}
+
+/// Labels of the secret env vars of `entry` (tokens, API keys).
+fn credential_labels(entry: &CatalogEntry) -> Option<String> {
+    let labels: Vec<&str> = entry
+        .required_env
+        .iter()
+        .filter(|spec| spec.sensitive)
+        .map(|spec| spec.label.as_str())
+        .collect();
+    (!labels.is_empty()).then(|| labels.join(", "))
+}
+
+/// Hint for a failure that looks like rejected credentials.
+fn credential_hint(entry: &CatalogEntry, error: &str) -> Option<String> {
+    let error = error.to_lowercase();
+    let rejected = [
+        "401",
+        "403",
+        "unauthorized",
+        "forbidden",
+        "bad credentials",
+        "invalid token",
+        "invalid api key",
+        "authentication",
+    ]
+    .iter()
+    .any(|needle| error.contains(needle));
+    if !rejected {
+        return None;
+    }
+    credential_labels(entry)
+        .map(|labels| format!("The server rejected the credentials. Check {}.", labels))
+}
+
+/// Run the install checks for `server` (built from `entry`): runtime, env
+/// vars, connection, tool listing, and one read-only test call. The server is
+/// left connected if every check passes and disconnected otherwise.
+pub async fn verify_install(
+    client: &super::mcp_client::McpClientManager,
+    entry: &CatalogEntry,
+    server: &super::McpServerEntry,
+) -> InstallReport {
+    let mut report = InstallReport::new();
+
+    let remote = matches!(entry.transport.as_str(), "http" | "sse");
+    if remote {
+        report.record(
+            InstallStep::Runtime,
+            StepStatus::Skipped,
+            "Remote server".into(),
+        );
+    } else if can_install(entry, &detect_runtimes().await) {
+        report.record(
+            InstallStep::Runtime,
+            StepStatus::Passed,
+            format!("{} is available", entry.command),
+        );
+    } else {
+        return report.fail(
+            InstallStep::Runtime,
+            format!("'{}' needs {}", entry.name, entry.command),
+            Some(format!(
+                "Install the {} runtime from the Runtimes section, then try again.",
+                entry.runtime
+            )),
+        );
+    }
+
+    report.env_issues = validate_env(entry, &server.env);
+    if !report.env_issues.is_empty() {
+        let labels: Vec<String> = report
+            .env_issues
+            .iter()
+            .map(|issue| issue.label.clone())
+            .collect();
+        let message = format!("Check {}", labels.join(", "));
+        return report.fail(InstallStep::Env, message, None);
+    }
+    report.record(
+        InstallStep::Env,
+        StepStatus::Passed,
+        format!("{} setting(s) provided", server.env.len()),
+    );
+
+    let connected = client.connect(server).await;
+    report.server = Some(connected.clone());
+    if !connected.connected {
+        let error = connected.error.unwrap_or_default();
+        let hint = if connected.needs_sign_in {
+            Some("Sign in to the server, then try again.".to_string())
+        } else {
+            credential_hint(entry, &error).or_else(|| {
+                (!remote).then(|| {
+                    format!(
+                        "Check that `{} {}` starts on this machine.",
+                        server.command.as_deref().unwrap_or_default(),
+                        server.args.join(" ")
+                    )
+                })
+            })
+        };
+        return report.fail(InstallStep::Connect, error, hint);
+    }
+    report.record(
+        InstallStep::Connect,
+        StepStatus::Passed,
+        format!("Connected via {}", connected.transport),
+    );
+
+    if connected.tools.is_empty() {
+        let _ = client.disconnect(&server.name).await;
+        let hint = credential_labels(entry).map(|labels| {
+            format!(
+                "Some servers only list tools for valid credentials. Check {}.",
+                labels
+            )
+        });
+        return report.fail(
+            InstallStep::ListTools,
+            "The server offers no tools".into(),
+            hint,
+        );
+    }
+    report.record(
+        InstallStep::ListTools,
+        StepStatus::Passed,
+        format!("{} tool(s) available", connected.tools.len()),
+    );
+
+    let Some(tool) = pick_test_tool(&connected.tools) else {
+        report.record(
+            InstallStep::TestCall,
+            StepStatus::Skipped,
+            "No read-only tool without arguments to try".into(),
+        );
+        return report.finish();
+    };
+    report.test_tool = Some(tool.name.clone());
+    let call = client.call_tool_checked(&server.name, &tool.name, Some(serde_json::json!({})));
+    match tokio::time::timeout(TEST_CALL_TIMEOUT, call).await {
+        Ok(Ok(_)) => {
+            report.record(
+                InstallStep::TestCall,
+                StepStatus::Passed,
+                format!("{} answered", tool.name),
+            );
+            report.finish()
+        }
+        Ok(Err(e)) => {
+            let _ = client.disconnect(&server.name).await;
+            let error = e.to_string();
+            let hint = credential_hint(entry, &error);
+            report.fail(
+                InstallStep::TestCall,
+                format!("{} failed: {}", tool.name, error),
+                hint,
+            )
+        }
+        Err(_) => {
+            let _ = client.disconnect(&server.name).await;
+            report.fail(
+                InstallStep::TestCall,
+                format!("{} didn't answer within {:?}", tool.name, TEST_CALL_TIMEOUT),
+                None,
+            )
+        }
     }
 }
 
@@ -1676,11 +2029,21 @@ pub fn registry_to_catalog_entry(server: &RegistryServer) -> Option<CatalogEntry
             ),
             _ => return None, // nuget, mcpb — not yet supported
         }
-    } else if let Some(remote) = server.remotes.first() {
-        // Remote-only server — no local install needed
+    } else if let Some(remote) = server
+        .remotes
+        .iter()
+        .find(|r| r.remote_type == "streamable-http")
+        .or_else(|| server.remotes.first())
+    {
+        // Remote-only server — no local install needed.
+        // Prefer streamable HTTP; fall back to the legacy SSE transport.
         (
             "remote".to_string(),
-            "http".to_string(),
+            if remote.remote_type == "sse" {
+                "sse".to_string()
+            } else {
+                "http".to_string()
+            },
             String::new(),
             vec![remote.url.clone()],
             Some(remote.url.clone()),
@@ -2451,6 +2814,82 @@ mod tests {
         assert!(server.command.is_some());
     }
 
+    #[test]
+    fn test_validate_env() {
+        let entry = get_catalog()
+            .into_iter()
+            .find(|e| e.required_env.iter().any(|s| s.required))
+            .unwrap();
+        let spec = entry.required_env.iter().find(|s| s.required).unwrap();
+        let issues = |value: &str| {
+            let env = HashMap::from([(spec.name.clone(), value.to_string())]);
+            validate_env(&entry, &env)
+                .into_iter()
+                .find(|i| i.name == spec.name)
+                .map(|i| i.problem)
+        };
+        assert_eq!(
+            validate_env(&entry, &HashMap::new())[0].problem,
+            EnvProblem::Missing
+        );
+        assert_eq!(issues("   "), Some(EnvProblem::Missing));
+        assert_eq!(issues(" abc123 "), Some(EnvProblem::Whitespace));
+        assert_eq!(issues("abc123"), None);
+        if let Some(placeholder) = &spec.placeholder {
+            assert_eq!(issues(placeholder), Some(EnvProblem::Placeholder));
+        }
+    }
+
+    #[test]
+    fn test_pick_test_tool() {
+        let tool = |name: &str, required: &[&str]| crate::protocols::mcp_client::ToolInfo {
+            name: name.into(),
+            description: None,
+            input_schema: Some(serde_json::json!({ "type": "object", "required": required })),
+        };
+        let tools = vec![
+            tool("delete_file", &[]),
+            tool("get_me", &[]),
+            tool("search_code", &["query"]),
+            tool("list_repos", &[]),
+            tool("get_and_update_issue", &[]),
+        ];
+        assert_eq!(pick_test_tool(&tools).unwrap().name, "list_repos");
+        assert_eq!(pick_test_tool(&tools[..3]).unwrap().name, "get_me");
+        assert!(pick_test_tool(&[tool("read_file", &["path"])]).is_none());
+    }
+
+    #[test]
+    fn test_install_report_skips_steps_after_failure() {
+        let mut report = InstallReport::new();
+        report.record(InstallStep::Runtime, StepStatus::Passed, "ok".into());
+        let report = report.fail(InstallStep::Env, "Check Token".into(), None);
+        assert!(!report.installed);
+        let statuses: Vec<StepStatus> = report.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                StepStatus::Passed,
+                StepStatus::Failed,
+                StepStatus::Skipped,
+                StepStatus::Skipped,
+                StepStatus::Skipped
+            ]
+        );
+        assert_eq!(report.steps[4].step, InstallStep::TestCall);
+    }
+
+    #[test]
+    fn test_credential_hint() {
+        let github = get_catalog()
+            .into_iter()
+            .find(|e| e.required_env.iter().any(|s| s.sensitive))
+            .unwrap();
+        assert!(credential_hint(&github, "HTTP 401 Bad credentials").is_some());
+        assert!(credential_hint(&github, "connection refused").is_none());
+        assert!(credential_hint(&get_catalog()[0], "401").is_none());
+    }
+
     #[test]
     fn test_categories() {
         let cats = get_categories();
@@ -4,17 +4,71 @@
 //! - filesystem servers, GitHub servers, database servers, etc.
 //! - Any of the 10,000+ MCP servers in the ecosystem.
 //!
-//! Supports both stdio (child process) and HTTP (streamable) transports.
+//! Supports stdio (child process), streamable HTTP, and legacy HTTP+SSE transports.
+//! Remote (HTTP/SSE) connections are retried with exponential backoff, and a
+//! remote server that drops mid-session is reconnected on the next tool call.
+//! Streamable HTTP resumes its session via `Mcp-Session-Id`/`Last-Event-ID`
+//! (handled by rmcp); SSE resumes via `Last-Event-ID` (see [`super::mcp_sse`]).
+//!
+//! A supervisor ([`McpClientManager::run_supervisor`]) pings every connected
+//! server periodically. Servers that stop answering are marked unhealthy;
+//! crashed stdio processes are restarted with backoff, up to [`MAX_RESTARTS`]
+//! consecutive attempts, after which the server is marked failed.
+//!
+//! Tool calls go through each server's call policy ([`super::mcp_limits`]):
+//! a timeout, a concurrency cap and per-minute rate limits.
+//!
+//! Remote servers that require OAuth are connected with the access token from
+//! [`super::mcp_oauth`]; until the user signs in they fail with
+//! `needs_sign_in` set.
+//!
+//! Servers can ask Ghost's local model for completions (MCP sampling, see
+//! [`super::mcp_sampling`]), ask the user for input ([`super::mcp_elicitation`]),
+//! and are told the watched directories as roots ([`super::mcp_roots`]).
 
 use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 
 use rmcp::{
-    model::{CallToolRequestParams, ListToolsResult, RawContent},
-    ServiceExt,
+    model::{
+        CallToolRequestParams, CallToolResult, ClientCapabilities, ClientInfo,
+        CreateElicitationRequestParams, CreateElicitationResult, CreateMessageRequestParams,
+        CreateMessageResult, ElicitationCapability, FormElicitationCapability, Implementation,
+        ListRootsResult, ListToolsResult, RawContent, UrlElicitationCapability,
+    },
+    service::RequestContext,
+    ClientHandler, RoleClient, ServiceExt,
 };
 use tokio::sync::RwLock;
 
+use super::mcp_limits::{CallLimitError, CallLimiter};
+use super::mcp_oauth::SignInRequired;
+use super::mcp_sse::backoff_delay;
 use super::McpServerEntry;
+use crate::AppState;
+
+/// Connection attempts for remote (HTTP/SSE) servers before reporting failure.
+const REMOTE_CONNECT_ATTEMPTS: u32 = 4;
+
+/// Interval between supervisor health checks.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a server has to answer a health ping.
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Consecutive restart attempts before a stdio server is marked failed.
+pub const MAX_RESTARTS: u32 = 5;
+
+/// Backoff before the next restart attempt, or `None` once the cap is reached.
+fn restart_delay(restarts: u32) -> Option<Duration> {
+    (restarts < MAX_RESTARTS).then(|| backoff_delay(restarts))
+}
+
+/// Whether a transport talks to a remote server over the network.
+fn is_remote_transport(transport: &str) -> bool {
+    matches!(transport, "http" | "streamable-http" | "sse")
+}
 
 /// Represents a connected MCP server with its available tools.
 #[derive(Debug, Clone, serde::Serialize)]
@@ -29,6 +83,26 @@ pub struct ConnectedServer {
     pub transport: String,
     /// Error message if connection failed.
     pub error: Option<String>,
+    /// Health as last seen by the supervisor.
+    pub health: ServerHealth,
+    /// Consecutive supervisor restarts (reset once the server answers a ping).
+    pub restarts: u32,
+    /// The server requires OAuth sign-in before it can be connected.
+    pub needs_sign_in: bool,
+}
+
+/// Server health as tracked by the supervisor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerHealth {
+    /// Answering pings.
+    Healthy,
+    /// Missed a ping (remote servers reconnect on the next tool call).
+    Unhealthy,
+    /// A stdio process is being restarted.
+    Restarting,
+    /// Not connected and no longer retried.
+    Failed,
 }
 
 /// Information about an MCP tool.
@@ -42,8 +116,120 @@ pub struct ToolInfo {
     pub input_schema: Option<serde_json::Value>,
 }
 
+/// AG-UI run ID of requests servers make of the user (they belong to no agent run).
+pub const PROMPT_RUN_ID: &str = "mcp-client";
+
+/// Receives requests servers make of the user, for the desktop UI.
+type PromptListener = Box<dyn Fn(&str, &serde_json::Value) + Send + Sync>;
+
+/// App state used to answer server requests; set once at startup.
+static APP_STATE: std::sync::RwLock<Weak<AppState>> = std::sync::RwLock::new(Weak::new());
+
+static PROMPT_LISTENER: std::sync::RwLock<Option<PromptListener>> = std::sync::RwLock::new(None);
+
+/// Make the app state available to server requests (sampling, elicitation).
+pub fn attach(state: &Arc<AppState>) {
+    *APP_STATE.write().unwrap_or_else(|e| e.into_inner()) = Arc::downgrade(state);
+}
+
+pub(super) fn app_state() -> Option<Arc<AppState>> {
+    APP_STATE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .upgrade()
+}
+
+/// Forward requests servers make of the user (`name`, payload) to the desktop UI.
+pub fn set_prompt_listener(listener: impl Fn(&str, &serde_json::Value) + Send + Sync + 'static) {
+    *PROMPT_LISTENER.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(listener));
+}
+
+/// Whether anyone can answer a request for the user.
+pub(super) fn can_prompt(state: &AppState) -> bool {
+    PROMPT_LISTENER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some()
+        || state.agui_event_bus.subscriber_count() > 0
+}
+
+/// Show a request for the user as an AG-UI custom event and in the desktop UI.
+pub(super) fn emit_prompt(state: &AppState, name: &str, value: serde_json::Value) {
+    if let Some(listener) = PROMPT_LISTENER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        listener(name, &value);
+    }
+    state
+        .agui_event_bus
+        .emit(super::agui::AgUiEvent::custom(PROMPT_RUN_ID, name, value));
+}
+
+/// Client side of one external server connection: answers the server's
+/// sampling, elicitation and roots requests.
+#[derive(Debug, Clone)]
+pub struct GhostClient {
+    server: String,
+}
+
+impl GhostClient {
+    pub fn new(server: &str) -> Self {
+        Self {
+            server: server.to_string(),
+        }
+    }
+}
+
+impl ClientHandler for GhostClient {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder()
+                .enable_roots()
+                .enable_roots_list_changed()
+                .enable_sampling()
+                .enable_elicitation_with(ElicitationCapability {
+                    form: Some(FormElicitationCapability::default()),
+                    url: Some(UrlElicitationCapability::default()),
+                })
+                .build(),
+            client_info: Implementation {
+                name: "ghost".into(),
+                title: Some("Ghost".into()),
+                version: env!("CARGO_PKG_VERSION").into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::ErrorData> {
+        super::mcp_sampling::create_message(&self.server, params).await
+    }
+
+    async fn create_elicitation(
+        &self,
+        params: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, rmcp::ErrorData> {
+        super::mcp_elicitation::create_elicitation(&self.server, params).await
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, rmcp::ErrorData> {
+        Ok(super::mcp_roots::list_roots())
+    }
+}
+
 /// A running MCP client service handle.
-type McpClientService = rmcp::service::RunningService<rmcp::service::RoleClient, ()>;
+type McpClientService = rmcp::service::RunningService<RoleClient, GhostClient>;
 
 /// Manager for all external MCP server connections.
 pub struct McpClientManager {
@@ -51,6 +237,10 @@ pub struct McpClientManager {
     services: RwLock<HashMap<String, McpClientService>>,
     /// Cached server info (for quick frontend queries).
     server_info: RwLock<HashMap<String, ConnectedServer>>,
+    /// Config entries of connected servers (used to reconnect remote servers).
+    entries: RwLock<HashMap<String, McpServerEntry>>,
+    /// Call policy of each connected server.
+    limiters: RwLock<HashMap<String, Arc<CallLimiter>>>,
 }
 
 impl McpClientManager {
@@ -58,6 +248,8 @@ impl McpClientManager {
         Self {
             services: RwLock::new(HashMap::new()),
             server_info: RwLock::new(HashMap::new()),
+            entries: RwLock::new(HashMap::new()),
+            limiters: RwLock::new(HashMap::new()),
         }
     }
 
@@ -93,7 +285,8 @@ impl McpClientManager {
         }
         // Inject managed runtimes into PATH
         cmd.env("PATH", bootstrapper.build_env_path());
-        for (key, value) in &entry.env {
+        // Secret references are looked up in the OS keychain
+        for (key, value) in crate::secrets::resolve_env(&entry.env)? {
             cmd.env(key, value);
         }
 
@@ -105,7 +298,7 @@ impl McpClientManager {
         }
 
         let transport = rmcp::transport::TokioChildProcess::new(cmd)?;
-        let service = ().serve(transport).await?;
+        let service = GhostClient::new(&entry.name).serve(transport).await?;
 
         // Discover tools
         let tools_result = service.list_tools(Default::default()).await?;
@@ -117,6 +310,9 @@ impl McpClientManager {
             tools: tools.clone(),
             transport: "stdio".to_string(),
             error: None,
+            health: ServerHealth::Healthy,
+            restarts: 0,
+            needs_sign_in: false,
         };
 
         // Store handles
@@ -151,8 +347,18 @@ impl McpClientManager {
             url
         );
 
-        let transport = rmcp::transport::StreamableHttpClientTransport::from_uri(url.as_str());
-        let service = ().serve(transport).await?;
+        let mut config =
+            rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig::with_uri(
+                url.as_str(),
+            );
+        if let Some(token) = super::mcp_oauth::access_token(&entry.name).await? {
+            config = config.auth_header(token);
+        }
+        let transport = rmcp::transport::StreamableHttpClientTransport::from_config(config);
+        let service = match GhostClient::new(&entry.name).serve(transport).await {
+            Ok(service) => service,
+            Err(e) => return Err(explain_connect_error(entry, url, e.into()).await),
+        };
 
         // Discover tools
         let tools_result = service.list_tools(Default::default()).await?;
@@ -164,6 +370,9 @@ impl McpClientManager {
             tools: tools.clone(),
             transport: "http".to_string(),
             error: None,
+            health: ServerHealth::Healthy,
+            restarts: 0,
+            needs_sign_in: false,
         };
 
         self.services
@@ -184,6 +393,106 @@ impl McpClientManager {
         Ok(info)
     }
 
+    /// Connect to an MCP server via the legacy HTTP+SSE transport.
+    pub async fn connect_sse(&self, entry: &McpServerEntry) -> anyhow::Result<ConnectedServer> {
+        let url = entry
+            .url
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No URL specified for SSE transport"))?;
+
+        tracing::info!(
+            "MCP Client: connecting to '{}' via SSE ({})",
+            entry.name,
+            url
+        );
+
+        let token = super::mcp_oauth::access_token(&entry.name).await?;
+        let transport = match super::mcp_sse::connect(url, token.as_deref()).await {
+            Ok(transport) => transport,
+            Err(e) => return Err(explain_connect_error(entry, url, e).await),
+        };
+        let service = GhostClient::new(&entry.name).serve(transport).await?;
+
+        // Discover tools
+        let tools_result = service.list_tools(Default::default()).await?;
+        let tools = extract_tools(&tools_result);
+
+        let info = ConnectedServer {
+            name: entry.name.clone(),
+            connected: true,
+            tools: tools.clone(),
+            transport: "sse".to_string(),
+            error: None,
+            health: ServerHealth::Healthy,
+            restarts: 0,
+            needs_sign_in: false,
+        };
+
+        self.services
+            .write()
+            .await
+            .insert(entry.name.clone(), service);
+        self.server_info
+            .write()
+            .await
+            .insert(entry.name.clone(), info.clone());
+
+        tracing::info!(
+            "MCP Client: connected to '{}' — {} tools available",
+            entry.name,
+            tools.len()
+        );
+
+        Ok(info)
+    }
+
+    /// Connect to a remote server, retrying with exponential backoff.
+    async fn connect_remote(&self, entry: &McpServerEntry) -> anyhow::Result<ConnectedServer> {
+        let mut attempt = 0;
+        loop {
+            let result = if entry.transport == "sse" {
+                self.connect_sse(entry).await
+            } else {
+                self.connect_http(entry).await
+            };
+            match result {
+                Ok(info) => return Ok(info),
+                // Retrying won't help until the user signs in
+                Err(e) if e.is::<SignInRequired>() => return Err(e),
+                Err(e) if attempt + 1 < REMOTE_CONNECT_ATTEMPTS => {
+                    let delay = backoff_delay(attempt);
+                    tracing::info!(
+                        "MCP Client: '{}' connect attempt {} failed ({}), retrying in {:?}",
+                        entry.name,
+                        attempt + 1,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Re-establish a dropped remote connection using its stored config entry.
+    async fn reconnect(&self, server_name: &str) -> anyhow::Result<()> {
+        let entry = self
+            .entries
+            .read()
+            .await
+            .get(server_name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Server '{}' not connected", server_name))?;
+
+        tracing::info!("MCP Client: reconnecting to '{}'", server_name);
+        if let Some(service) = self.services.write().await.remove(server_name) {
+            let _ = service.cancel().await;
+        }
+        self.connect_remote(&entry).await.map(|_| ())
+    }
+
     /// Connect to an MCP server based on its configuration entry.
     pub async fn connect(&self, entry: &McpServerEntry) -> ConnectedServer {
         if !entry.enabled {
@@ -193,6 +502,9 @@ impl McpClientManager {
                 tools: vec![],
                 transport: entry.transport.clone(),
                 error: Some("Server disabled".to_string()),
+                health: ServerHealth::Failed,
+                restarts: 0,
+                needs_sign_in: false,
             };
         }
 
@@ -203,12 +515,22 @@ impl McpClientManager {
             "stdio" => Err(anyhow::anyhow!(
                 "Stdio transport not available on this platform. Use HTTP transport instead."
             )),
-            "http" | "streamable-http" => self.connect_http(entry).await,
+            "http" | "streamable-http" | "sse" => self.connect_remote(entry).await,
             other => Err(anyhow::anyhow!("Unknown transport: {}", other)),
         };
 
         match result {
-            Ok(info) => info,
+            Ok(info) => {
+                self.limiters.write().await.insert(
+                    entry.name.clone(),
+                    Arc::new(CallLimiter::new(entry.limits.clone())),
+                );
+                self.entries
+                    .write()
+                    .await
+                    .insert(entry.name.clone(), entry.clone());
+                info
+            }
             Err(e) => {
                 let error_msg = format!("{}", e);
                 tracing::warn!(
@@ -222,18 +544,117 @@ impl McpClientManager {
                     tools: vec![],
                     transport: entry.transport.clone(),
                     error: Some(error_msg),
+                    health: ServerHealth::Failed,
+                    restarts: 0,
+                    needs_sign_in: e.is::<SignInRequired>(),
                 }
             }
         }
     }
 
     /// Call a tool on a connected MCP server.
+    ///
+    /// Calls over the server's limits fail with a [`CallLimitError`], as do
+    /// calls that run past their timeout. If a remote server's session
+    /// dropped before the call, it is reconnected (with backoff) first; a
+    /// call that fails once sent is never retried.
     pub async fn call_tool(
         &self,
         server_name: &str,
         tool_name: &str,
         arguments: Option<serde_json::Value>,
     ) -> anyhow::Result<String> {
+        let limiter = self.limiters.read().await.get(server_name).cloned();
+        let Some(limiter) = limiter else {
+            return self
+                .call_tool_reconnecting(server_name, tool_name, arguments)
+                .await;
+        };
+        let _permit = limiter.admit(server_name, tool_name, Instant::now())?;
+        let call = self.call_tool_reconnecting(server_name, tool_name, arguments);
+        match limiter.timeout(tool_name) {
+            Some(timeout) => tokio::time::timeout(timeout, call)
+                .await
+                .map_err(|_| CallLimitError::timed_out(server_name, tool_name))?,
+            None => call.await,
+        }
+    }
+
+    async fn call_tool_reconnecting(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> anyhow::Result<String> {
+        // Only a session found closed before the call is reconnected: once a
+        // request went out, the tool may have run, and calling it again could
+        // repeat its side effects.
+        if self.remote_session_closed(server_name).await {
+            tracing::warn!(
+                "MCP Client: session with '{}' closed, reconnecting before calling '{}'",
+                server_name,
+                tool_name
+            );
+            self.reconnect(server_name).await?;
+        }
+        self.call_tool_once(server_name, tool_name, arguments).await
+    }
+
+    /// Whether a remote server's session has dropped.
+    async fn remote_session_closed(&self, server_name: &str) -> bool {
+        let remote = self
+            .entries
+            .read()
+            .await
+            .get(server_name)
+            .is_some_and(|entry| is_remote_transport(&entry.transport));
+        remote
+            && self
+                .services
+                .read()
+                .await
+                .get(server_name)
+                .map_or(true, |service| {
+                    service.is_closed() || service.is_transport_closed()
+                })
+    }
+
+    async fn call_tool_once(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> anyhow::Result<String> {
+        let result = self
+            .call_tool_result(server_name, tool_name, arguments)
+            .await?;
+        Ok(result_text(&result))
+    }
+
+    /// Call a tool once, failing if the server reports a tool error (which
+    /// [`Self::call_tool`] passes on as text). Used to test a fresh install.
+    pub async fn call_tool_checked(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> anyhow::Result<String> {
+        let result = self
+            .call_tool_result(server_name, tool_name, arguments)
+            .await?;
+        let text = result_text(&result);
+        if result.is_error == Some(true) {
+            anyhow::bail!("{}", text);
+        }
+        Ok(text)
+    }
+
+    async fn call_tool_result(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> anyhow::Result<CallToolResult> {
         let services = self.services.read().await;
         let service = services
             .get(server_name)
@@ -245,34 +666,20 @@ impl McpClientManager {
             server_name
         );
 
-        let result = service
+        Ok(service
             .call_tool(CallToolRequestParams {
                 meta: None,
                 name: tool_name.to_string().into(),
                 arguments: arguments.and_then(|v| v.as_object().cloned()),
                 task: None,
             })
-            .await?;
-
-        // Extract text content from the result
-        let text: String = result
-            .content
-            .iter()
-            .filter_map(|c| {
-                if let RawContent::Text(tc) = &c.raw {
-                    Some(tc.text.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        Ok(text)
+            .await?)
     }
 
     /// Disconnect from a specific MCP server.
     pub async fn disconnect(&self, server_name: &str) -> anyhow::Result<()> {
+        self.entries.write().await.remove(server_name);
+        self.limiters.write().await.remove(server_name);
         if let Some(service) = self.services.write().await.remove(server_name) {
             let _ = service.cancel().await;
             tracing::info!("MCP Client: disconnected from '{}'", server_name);
@@ -283,6 +690,16 @@ impl McpClientManager {
         Ok(())
     }
 
+    /// Tell every connected server that the roots changed.
+    pub async fn notify_roots_changed(&self) {
+        let services = self.services.read().await;
+        for (name, service) in services.iter() {
+            if let Err(e) = service.notify_roots_list_changed().await {
+                tracing::debug!("MCP Client: roots update to '{}' failed: {}", name, e);
+            }
+        }
+    }
+
     /// Disconnect all connected servers.
     pub async fn disconnect_all(&self) {
         let mut services = self.services.write().await;
@@ -301,6 +718,165 @@ impl McpClientManager {
         self.server_info.read().await.values().cloned().collect()
     }
 
+    /// Update a server's health. Returns the new status if it changed.
+    async fn set_health(
+        &self,
+        server_name: &str,
+        health: ServerHealth,
+        error: Option<String>,
+    ) -> Option<ConnectedServer> {
+        let running = self.services.read().await.contains_key(server_name);
+        let mut info = self.server_info.write().await;
+        let server = info.get_mut(server_name)?;
+        let changed = server.health != health || server.error != error;
+        server.health = health;
+        server.connected =
+            running && matches!(health, ServerHealth::Healthy | ServerHealth::Unhealthy);
+        server.error = error;
+        changed.then(|| server.clone())
+    }
+
+    /// Ping a server. A `tools/list` request doubles as the ping and refreshes the tool list.
+    async fn ping(&self, server_name: &str) -> anyhow::Result<Vec<ToolInfo>> {
+        let services = self.services.read().await;
+        let service = services
+            .get(server_name)
+            .ok_or_else(|| anyhow::anyhow!("Server '{}' not running", server_name))?;
+        let result = tokio::time::timeout(PING_TIMEOUT, service.list_tools(Default::default()))
+            .await
+            .map_err(|_| anyhow::anyhow!("No response within {:?}", PING_TIMEOUT))??;
+        Ok(extract_tools(&result))
+    }
+
+    /// Restart a stdio server, honoring the capped retry policy.
+    async fn restart_stdio(
+        &self,
+        entry: &McpServerEntry,
+        on_status: &(dyn Fn(&ConnectedServer) + Send + Sync),
+    ) {
+        let restarts = self
+            .server_info
+            .read()
+            .await
+            .get(&entry.name)
+            .map_or(0, |s| s.restarts);
+
+        let Some(delay) = restart_delay(restarts) else {
+            tracing::warn!(
+                "MCP Client: '{}' failed after {} restarts, giving up",
+                entry.name,
+                restarts
+            );
+            if let Some(service) = self.services.write().await.remove(&entry.name) {
+                let _ = service.cancel().await;
+            }
+            let error = format!("Stopped responding; gave up after {} restarts", restarts);
+            if let Some(status) = self
+                .set_health(&entry.name, ServerHealth::Failed, Some(error))
+                .await
+            {
+                on_status(&status);
+            }
+            return;
+        };
+
+        if let Some(status) = self
+            .set_health(&entry.name, ServerHealth::Restarting, None)
+            .await
+        {
+            on_status(&status);
+        }
+        if let Some(service) = self.services.write().await.remove(&entry.name) {
+            let _ = service.cancel().await;
+        }
+        tokio::time::sleep(delay).await;
+
+        tracing::info!(
+            "MCP Client: restarting '{}' (attempt {}/{})",
+            entry.name,
+            restarts + 1,
+            MAX_RESTARTS
+        );
+        let result = self.connect(entry).await;
+        let status = {
+            let mut info = self.server_info.write().await;
+            let status = info
+                .entry(entry.name.clone())
+                .or_insert_with(|| result.clone());
+            status.restarts = restarts + 1;
+            if result.connected {
+                status.health = ServerHealth::Healthy;
+            } else {
+                status.connected = false;
+                status.health = ServerHealth::Unhealthy;
+                status.error = result.error;
+            }
+            status.clone()
+        };
+        on_status(&status);
+    }
+
+    /// Run one supervisor pass over all connected servers.
+    ///
+    /// `on_status` is called for every server whose health changed.
+    pub async fn check_health(&self, on_status: &(dyn Fn(&ConnectedServer) + Send + Sync)) {
+        let entries: Vec<McpServerEntry> = self.entries.read().await.values().cloned().collect();
+
+        for entry in entries {
+            let failed = self
+                .server_info
+                .read()
+                .await
+                .get(&entry.name)
+                .is_some_and(|s| s.health == ServerHealth::Failed);
+            if failed {
+                continue;
+            }
+
+            match self.ping(&entry.name).await {
+                Ok(tools) => {
+                    let changed = self
+                        .set_health(&entry.name, ServerHealth::Healthy, None)
+                        .await;
+                    if let Some(server) = self.server_info.write().await.get_mut(&entry.name) {
+                        server.tools = tools;
+                        server.restarts = 0;
+                    }
+                    if let Some(status) = changed {
+                        tracing::info!("MCP Client: '{}' is healthy again", entry.name);
+                        on_status(&status);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "MCP Client: health check for '{}' failed: {}",
+                        entry.name,
+                        e
+                    );
+                    if entry.transport == "stdio" {
+                        self.restart_stdio(&entry, on_status).await;
+                    } else if let Some(status) = self
+                        .set_health(&entry.name, ServerHealth::Unhealthy, Some(e.to_string()))
+                        .await
+                    {
+                        on_status(&status);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Supervise connected servers forever, checking health every [`HEALTH_CHECK_INTERVAL`].
+    pub async fn run_supervisor(&self, on_status: impl Fn(&ConnectedServer) + Send + Sync) {
+        let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval.tick().await; // first tick completes immediately
+        loop {
+            interval.tick().await;
+            self.check_health(&on_status).await;
+        }
+    }
+
     /// Get all available tools from all connected servers.
     pub async fn all_tools(&self) -> Vec<(String, ToolInfo)> {
         let info = self.server_info.read().await;
@@ -316,6 +892,39 @@ impl McpClientManager {
     }
 }
 
+/// Report a remote connect failure as [`SignInRequired`] when the server
+/// refuses unauthenticated requests (no token yet, or a revoked one).
+async fn explain_connect_error(
+    entry: &McpServerEntry,
+    url: &str,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    if super::mcp_oauth::requires_sign_in(url).await {
+        SignInRequired {
+            server: entry.name.clone(),
+        }
+        .into()
+    } else {
+        error
+    }
+}
+
+/// Text content of a tool result.
+fn result_text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| {
+            if let RawContent::Text(tc) = &c.raw {
+                Some(tc.text.clone())
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Extract tool information from an MCP ListToolsResult.
 fn extract_tools(result: &ListToolsResult) -> Vec<ToolInfo> {
     result
@@ -328,3 +937,100 @@ fn extract_tools(result: &ListToolsResult) -> Vec<ToolInfo> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_remote_transport() {
+        assert!(is_remote_transport("http"));
+        assert!(is_remote_transport("streamable-http"));
+        assert!(is_remote_transport("sse"));
+        assert!(!is_remote_transport("stdio"));
+    }
+
+    #[tokio::test]
+    async fn test_connect_disabled_server() {
+        let manager = McpClientManager::new();
+        let entry = McpServerEntry {
+            name: "remote".into(),
+            transport: "sse".into(),
+            command: None,
+            args: vec![],
+            url: Some("http://127.0.0.1:9/sse".into()),
+            enabled: false,
+            env: HashMap::new(),
+            limits: Default::default(),
+            sampling: Default::default(),
+        };
+        let info = manager.connect(&entry).await;
+        assert!(!info.connected);
+        assert_eq!(info.error.as_deref(), Some("Server disabled"));
+    }
+
+    #[test]
+    fn test_restart_delay_is_capped() {
+        assert_eq!(restart_delay(0), Some(Duration::from_millis(500)));
+        assert_eq!(
+            restart_delay(MAX_RESTARTS - 1),
+            Some(backoff_delay(MAX_RESTARTS - 1))
+        );
+        assert_eq!(restart_delay(MAX_RESTARTS), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_health_gives_up_after_max_restarts() {
+        let manager = McpClientManager::new();
+        let entry = McpServerEntry {
+            name: "crashy".into(),
+            transport: "stdio".into(),
+            command: Some("ghost-test-missing-command".into()),
+            args: vec![],
+            url: None,
+            enabled: true,
+            env: HashMap::new(),
+            limits: Default::default(),
+            sampling: Default::default(),
+        };
+        manager
+            .entries
+            .write()
+            .await
+            .insert(entry.name.clone(), entry.clone());
+        manager.server_info.write().await.insert(
+            entry.name.clone(),
+            ConnectedServer {
+                name: entry.name.clone(),
+                connected: true,
+                tools: vec![],
+                transport: "stdio".into(),
+                error: None,
+                health: ServerHealth::Healthy,
+                restarts: MAX_RESTARTS,
+                needs_sign_in: false,
+            },
+        );
+
+        let events = std::sync::Mutex::new(Vec::new());
+        let record = |s: &ConnectedServer| events.lock().unwrap().push(s.health);
+        manager.check_health(&record).await;
+        assert_eq!(*events.lock().unwrap(), vec![ServerHealth::Failed]);
+
+        // Failed servers are no longer checked
+        manager.check_health(&record).await;
+        assert_eq!(events.lock().unwrap().len(), 1);
+        let servers = manager.list_servers().await;
+        assert!(!servers[0].connected);
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_unknown_server() {
+        let manager = McpClientManager::new();
+        let err = manager
+            .call_tool("missing", "tool", None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not connected"));
+    }
+}
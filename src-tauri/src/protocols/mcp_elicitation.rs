@@ -0,0 +1,240 @@
+//! MCP elicitation — external servers asking the user for input.
+//!
+//! A server that needs a missing parameter mid-call sends
+//! `elicitation/create`, either with a form schema (flat object of
+//! primitive fields) or with a URL for the user to visit. The request is
+//! shown as an `elicitation_required` prompt (see
+//! [`super::mcp_client::emit_prompt`]) and answered with the
+//! `respond_elicitation` command. Requests nobody answers within
+//! [`ELICITATION_TIMEOUT`] are cancelled, and so are requests made while no
+//! UI is listening.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use rmcp::model::{CreateElicitationRequestParams, CreateElicitationResult, ElicitationAction};
+use rmcp::ErrorData as McpError;
+use serde_json::json;
+use tokio::sync::oneshot;
+
+use super::mcp_client::{app_state, can_prompt, emit_prompt};
+
+/// How long the user has to answer.
+pub const ELICITATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// An elicitation waiting for the user.
+struct Pending {
+    /// Fields a form answer must fill.
+    required: Vec<String>,
+    reply: oneshot::Sender<CreateElicitationResult>,
+}
+
+static PENDING: LazyLock<Mutex<HashMap<String, Pending>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn cancelled() -> CreateElicitationResult {
+    CreateElicitationResult {
+        action: ElicitationAction::Cancel,
+        content: None,
+    }
+}
+
+/// Ask the user for the input the server `server` requests.
+pub async fn create_elicitation(
+    server: &str,
+    params: CreateElicitationRequestParams,
+) -> Result<CreateElicitationResult, McpError> {
+    let Some(state) = app_state().filter(|s| can_prompt(s)) else {
+        tracing::info!("MCP elicitation from '{}' cancelled: no UI", server);
+        return Ok(cancelled());
+    };
+
+    let id = format!("elicitation-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let timeout_ms = ELICITATION_TIMEOUT.as_millis() as u64;
+    let (required, prompt) = match &params {
+        CreateElicitationRequestParams::FormElicitationParams {
+            message,
+            requested_schema,
+            ..
+        } => (
+            requested_schema.required.clone().unwrap_or_default(),
+            json!({
+                "elicitation_id": id,
+                "server": server,
+                "mode": "form",
+                "message": message,
+                "requested_schema": requested_schema,
+                "timeout_ms": timeout_ms,
+            }),
+        ),
+        CreateElicitationRequestParams::UrlElicitationParams { message, url, .. } => (
+            Vec::new(),
+            json!({
+                "elicitation_id": id,
+                "server": server,
+                "mode": "url",
+                "message": message,
+                "url": url,
+                "timeout_ms": timeout_ms,
+            }),
+        ),
+    };
+    let (tx, rx) = oneshot::channel();
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        id.clone(),
+        Pending {
+            required,
+            reply: tx,
+        },
+    );
+
+    emit_prompt(&state, "elicitation_required", prompt);
+    tracing::info!(
+        "Waiting for the user to answer elicitation {} from '{}'",
+        id,
+        server
+    );
+
+    let result = match tokio::time::timeout(ELICITATION_TIMEOUT, rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => cancelled(),
+        Err(_) => {
+            PENDING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&id);
+            tracing::info!("Elicitation {} from '{}' timed out", id, server);
+            cancelled()
+        }
+    };
+    emit_prompt(
+        &state,
+        "elicitation_resolved",
+        json!({
+            "elicitation_id": id,
+            "server": server,
+            "action": result.action,
+        }),
+    );
+    Ok(result)
+}
+
+/// Deliver the user's answer to a pending elicitation. Accepted form answers
+/// must be an object filling every required field; otherwise the request
+/// stays pending so the user can correct it.
+pub fn respond(
+    elicitation_id: &str,
+    action: ElicitationAction,
+    content: Option<serde_json::Value>,
+) -> Result<(), String> {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = pending.get(elicitation_id).ok_or_else(|| {
+        format!(
+            "No pending elicitation '{}' (already answered or timed out)",
+            elicitation_id
+        )
+    })?;
+    let content = match action {
+        ElicitationAction::Accept => {
+            check_content(&entry.required, content.as_ref())?;
+            content
+        }
+        ElicitationAction::Decline | ElicitationAction::Cancel => None,
+    };
+    if let Some(entry) = pending.remove(elicitation_id) {
+        let _ = entry
+            .reply
+            .send(CreateElicitationResult { action, content });
+    }
+    Ok(())
+}
+
+/// Check an accepted answer against the required fields.
+fn check_content(required: &[String], content: Option<&serde_json::Value>) -> Result<(), String> {
+    let fields = match content {
+        Some(serde_json::Value::Object(fields)) => Some(fields),
+        Some(_) => return Err("The answer must be an object of field values".into()),
+        None => None,
+    };
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|name| {
+            fields
+                .and_then(|f| f.get(name.as_str()))
+                .map_or(true, |v| v.is_null())
+        })
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Missing required fields: {}", missing.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register(required: &[&str]) -> (String, oneshot::Receiver<CreateElicitationResult>) {
+        let id = format!(
+            "elicitation-test-{}",
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let (tx, rx) = oneshot::channel();
+        PENDING.lock().unwrap().insert(
+            id.clone(),
+            Pending {
+                required: required.iter().map(|s| s.to_string()).collect(),
+                reply: tx,
+            },
+        );
+        (id, rx)
+    }
+
+    #[tokio::test]
+    async fn test_respond_accept() {
+        let (id, rx) = register(&["repo"]);
+        let err = respond(&id, ElicitationAction::Accept, Some(json!({}))).unwrap_err();
+        assert!(err.contains("repo"));
+        // Still pending after a rejected answer
+        respond(
+            &id,
+            ElicitationAction::Accept,
+            Some(json!({ "repo": "ghost" })),
+        )
+        .unwrap();
+        let result = rx.await.unwrap();
+        assert_eq!(result.action, ElicitationAction::Accept);
+        assert_eq!(result.content, Some(json!({ "repo": "ghost" })));
+        assert!(respond(&id, ElicitationAction::Cancel, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_respond_decline_drops_content() {
+        let (id, rx) = register(&["repo"]);
+        respond(
+            &id,
+            ElicitationAction::Decline,
+            Some(json!({ "repo": "x" })),
+        )
+        .unwrap();
+        let result = rx.await.unwrap();
+        assert_eq!(result.action, ElicitationAction::Decline);
+        assert!(result.content.is_none());
+    }
+
+    #[test]
+    fn test_check_content() {
+        let required = vec!["owner".to_string()];
+        assert!(check_content(&[], None).is_ok());
+        assert!(check_content(&required, None).is_err());
+        assert!(check_content(&required, Some(&json!("owner"))).is_err());
+        assert!(check_content(&required, Some(&json!({ "owner": null }))).is_err());
+        assert!(check_content(&required, Some(&json!({ "owner": "ana" }))).is_ok());
+    }
+}
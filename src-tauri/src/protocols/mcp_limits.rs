@@ -0,0 +1,254 @@
+//! Call policy for external MCP servers: timeouts, concurrency caps and
+//! per-minute rate limits, configured per server with per-tool overrides
+//! (see [`super::CallLimits`]).
+//!
+//! Calls over a limit are refused right away with a [`CallLimitError`]
+//! instead of queueing, so an agent run never hangs on a slow or flooded
+//! server; the agent gets the error as JSON and can wait or pick another tool.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::CallLimits;
+
+/// Window of the per-minute rate limits.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Why a call was refused or abandoned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitReason {
+    /// Too many calls in the last minute.
+    RateLimited,
+    /// Too many calls running at once.
+    TooManyConcurrent,
+    /// The call took longer than its timeout.
+    TimedOut,
+}
+
+/// A call refused or abandoned by the policy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallLimitError {
+    pub server: String,
+    pub tool: String,
+    pub reason: LimitReason,
+    /// When a retry can succeed, for rate limits.
+    pub retry_after_secs: Option<u64>,
+}
+
+impl std::fmt::Display for CallLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.reason {
+            LimitReason::RateLimited => write!(
+                f,
+                "'{}' on '{}' is rate limited; retry in {}s",
+                self.tool,
+                self.server,
+                self.retry_after_secs.unwrap_or(1)
+            ),
+            LimitReason::TooManyConcurrent => write!(
+                f,
+                "'{}' is busy with other calls; retry when they finish",
+                self.server
+            ),
+            LimitReason::TimedOut => {
+                write!(f, "'{}' on '{}' timed out", self.tool, self.server)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CallLimitError {}
+
+impl CallLimitError {
+    pub fn timed_out(server: &str, tool: &str) -> Self {
+        Self {
+            server: server.to_string(),
+            tool: tool.to_string(),
+            reason: LimitReason::TimedOut,
+            retry_after_secs: None,
+        }
+    }
+
+    /// Tool result handed to the agent: `{"error": "throttled", ...}`.
+    pub fn to_tool_result(&self) -> String {
+        serde_json::json!({
+            "error": "throttled",
+            "reason": self.reason,
+            "server": self.server,
+            "tool": self.tool,
+            "retry_after_secs": self.retry_after_secs,
+            "message": self.to_string(),
+        })
+        .to_string()
+    }
+}
+
+/// Held while an admitted call runs.
+pub struct CallPermit {
+    _running: Option<OwnedSemaphorePermit>,
+}
+
+/// Enforces the [`CallLimits`] of one server.
+pub struct CallLimiter {
+    limits: CallLimits,
+    running: Option<Arc<Semaphore>>,
+    /// Start times within the rate window: `None` for the server, `Some(tool)` per tool.
+    started: Mutex<HashMap<Option<String>, VecDeque<Instant>>>,
+}
+
+impl CallLimiter {
+    pub fn new(limits: CallLimits) -> Self {
+        let running =
+            (limits.max_concurrent > 0).then(|| Arc::new(Semaphore::new(limits.max_concurrent)));
+        Self {
+            limits,
+            running,
+            started: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Timeout of a call to `tool`, if any.
+    pub fn timeout(&self, tool: &str) -> Option<Duration> {
+        let secs = self
+            .limits
+            .tools
+            .get(tool)
+            .and_then(|t| t.timeout_secs)
+            .unwrap_or(self.limits.timeout_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    /// Admit a call to `tool` or refuse it.
+    pub fn admit(
+        &self,
+        server: &str,
+        tool: &str,
+        now: Instant,
+    ) -> Result<CallPermit, CallLimitError> {
+        let refuse = |reason, retry_after: Option<Duration>| CallLimitError {
+            server: server.to_string(),
+            tool: tool.to_string(),
+            reason,
+            retry_after_secs: retry_after.map(|d| d.as_secs().max(1)),
+        };
+        let tool_limit = self.limits.tools.get(tool).and_then(|t| t.calls_per_minute);
+        let mut started = self.started.lock().unwrap_or_else(|e| e.into_inner());
+        let windows = [
+            (None, self.limits.calls_per_minute),
+            (Some(tool.to_string()), tool_limit.unwrap_or(0)),
+        ];
+        for (key, limit) in &windows {
+            if *limit == 0 {
+                continue;
+            }
+            let window = started.entry(key.clone()).or_default();
+            while window
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+            {
+                window.pop_front();
+            }
+            if window.len() >= *limit as usize {
+                let oldest = window.front().copied().unwrap_or(now);
+                let retry_after = RATE_WINDOW.saturating_sub(now.duration_since(oldest));
+                return Err(refuse(LimitReason::RateLimited, Some(retry_after)));
+            }
+        }
+
+        let permit = match &self.running {
+            Some(running) => Some(
+                running
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| refuse(LimitReason::TooManyConcurrent, None))?,
+            ),
+            None => None,
+        };
+        for (key, limit) in windows {
+            if limit > 0 {
+                started.entry(key).or_default().push_back(now);
+            }
+        }
+        Ok(CallPermit { _running: permit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::ToolLimits;
+
+    fn limits(per_minute: u32, concurrent: usize) -> CallLimits {
+        CallLimits {
+            timeout_secs: 30,
+            max_concurrent: concurrent,
+            calls_per_minute: per_minute,
+            tools: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_window() {
+        let limiter = CallLimiter::new(limits(2, 0));
+        let start = Instant::now();
+        assert!(limiter.admit("s", "a", start).is_ok());
+        assert!(limiter
+            .admit("s", "b", start + Duration::from_secs(10))
+            .is_ok());
+        let err = limiter
+            .admit("s", "a", start + Duration::from_secs(20))
+            .err()
+            .unwrap();
+        assert_eq!(err.reason, LimitReason::RateLimited);
+        assert_eq!(err.retry_after_secs, Some(40));
+        // The first call leaves the window after a minute
+        assert!(limiter.admit("s", "a", start + RATE_WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let limiter = CallLimiter::new(limits(0, 1));
+        let now = Instant::now();
+        let permit = limiter.admit("s", "a", now).unwrap();
+        let err = limiter.admit("s", "b", now).err().unwrap();
+        assert_eq!(err.reason, LimitReason::TooManyConcurrent);
+        drop(permit);
+        assert!(limiter.admit("s", "b", now).is_ok());
+    }
+
+    #[test]
+    fn test_tool_overrides() {
+        let mut limits = limits(0, 0);
+        limits.tools.insert(
+            "slow_report".into(),
+            ToolLimits {
+                timeout_secs: Some(300),
+                calls_per_minute: Some(1),
+            },
+        );
+        let limiter = CallLimiter::new(limits);
+        assert_eq!(
+            limiter.timeout("slow_report"),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(limiter.timeout("other"), Some(Duration::from_secs(30)));
+
+        let now = Instant::now();
+        assert!(limiter.admit("s", "slow_report", now).is_ok());
+        assert!(limiter.admit("s", "slow_report", now).is_err());
+        assert!(limiter.admit("s", "other", now).is_ok());
+    }
+
+    #[test]
+    fn test_tool_result_is_json() {
+        let err = CallLimitError::timed_out("github", "list_repos");
+        let json: serde_json::Value = serde_json::from_str(&err.to_tool_result()).unwrap();
+        assert_eq!(json["error"], "throttled");
+        assert_eq!(json["reason"], "timed_out");
+    }
+}
@@ -0,0 +1,569 @@
+//! OAuth 2.1 sign-in for remote MCP servers.
+//!
+//! Follows the MCP authorization spec: a server that answers 401 points to
+//! its protected resource metadata (RFC 9728), which names the authorization
+//! server, whose metadata (RFC 8414) gives the endpoints. Ghost registers as
+//! a public client (RFC 7591), opens the system browser for the authorization
+//! code flow with PKCE, and receives the code on a loopback redirect. Tokens
+//! are kept in the OS keychain (see [`crate::secrets`]) and refreshed shortly
+//! before they expire.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::header::{ACCEPT, WWW_AUTHENTICATE};
+use reqwest::{StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// How long the user has to finish signing in in the browser.
+const SIGN_IN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Timeout of each metadata, registration and token request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Tokens expiring within this many seconds are refreshed before use.
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// Path of the loopback redirect URI.
+const CALLBACK_PATH: &str = "/callback";
+
+/// A server that can't be connected until the user signs in.
+#[derive(Debug)]
+pub struct SignInRequired {
+    pub server: String,
+}
+
+impl std::fmt::Display for SignInRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' requires sign-in", self.server)
+    }
+}
+
+impl std::error::Error for SignInRequired {}
+
+/// Tokens and client registration of one server, kept in the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredTokens {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    /// Unix seconds; `None` if the server didn't say.
+    #[serde(default)]
+    expires_at: Option<i64>,
+    token_endpoint: String,
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+    /// Canonical server URI, sent as the RFC 8707 `resource`.
+    resource: String,
+}
+
+impl StoredTokens {
+    fn needs_refresh(&self, now: i64) -> bool {
+        self.expires_at
+            .is_some_and(|at| at - REFRESH_MARGIN_SECS <= now)
+    }
+
+    /// Take the tokens of a token response. A missing refresh token keeps
+    /// the previous one.
+    fn update(&mut self, response: TokenResponse, now: i64) {
+        self.access_token = response.access_token;
+        self.expires_at = response.expires_in.map(|secs| now + secs);
+        if response.refresh_token.is_some() {
+            self.refresh_token = response.refresh_token;
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+    #[serde(default)]
+    code_challenge_methods_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+fn secret_name(server: &str) -> String {
+    crate::secrets::mcp_secret_name(server, "oauth")
+}
+
+fn load(server: &str) -> Option<StoredTokens> {
+    match crate::secrets::get(&secret_name(server)) {
+        Ok(json) => json.and_then(|json| serde_json::from_str(&json).ok()),
+        Err(e) => {
+            tracing::warn!("Failed to read the OAuth tokens of '{}': {}", server, e);
+            None
+        }
+    }
+}
+
+fn store(server: &str, tokens: &StoredTokens) -> anyhow::Result<()> {
+    crate::secrets::set(&secret_name(server), &serde_json::to_string(tokens)?)?;
+    Ok(())
+}
+
+/// Delete the stored tokens of `server` (sign out).
+pub fn forget(server: &str) {
+    if let Err(e) = crate::secrets::delete(&secret_name(server)) {
+        tracing::warn!("Failed to delete the OAuth tokens of '{}': {}", server, e);
+    }
+}
+
+fn http_client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()
+}
+
+/// Access token for `server`, refreshed first if it is about to expire.
+/// `None` if the user never signed in.
+pub async fn access_token(server: &str) -> anyhow::Result<Option<String>> {
+    let Some(mut tokens) = load(server) else {
+        return Ok(None);
+    };
+    let now = chrono::Utc::now().timestamp();
+    if tokens.needs_refresh(now) {
+        let signed_out = || SignInRequired {
+            server: server.to_string(),
+        };
+        let refresh_token = tokens.refresh_token.clone().ok_or_else(signed_out)?;
+        let grant = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ];
+        let response = request_token(&http_client()?, &tokens, &grant)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Refreshing the OAuth token of '{}' failed: {}", server, e);
+                signed_out()
+            })?;
+        tokens.update(response, now);
+        store(server, &tokens)?;
+    }
+    Ok(Some(tokens.access_token))
+}
+
+/// Whether `url` refuses unauthenticated requests with 401.
+pub async fn requires_sign_in(url: &str) -> bool {
+    let (Ok(url), Ok(client)) = (Url::parse(url), http_client()) else {
+        return false;
+    };
+    matches!(challenge(&client, &url).await, Ok(Some(_)))
+}
+
+/// Send an unauthenticated request. `Some(WWW-Authenticate)` if refused with 401.
+async fn challenge(client: &reqwest::Client, url: &Url) -> anyhow::Result<Option<String>> {
+    let response = client
+        .get(url.clone())
+        .header(ACCEPT, "application/json, text/event-stream")
+        .send()
+        .await?;
+    if response.status() != StatusCode::UNAUTHORIZED {
+        return Ok(None);
+    }
+    let header = response
+        .headers()
+        .get(WWW_AUTHENTICATE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    Ok(Some(header.to_string()))
+}
+
+/// The `resource_metadata` parameter of a `WWW-Authenticate` header.
+fn resource_metadata_url(www_authenticate: &str) -> Option<String> {
+    const PARAM: &str = "resource_metadata=";
+    let start = www_authenticate.find(PARAM)? + PARAM.len();
+    let rest = &www_authenticate[start..];
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split([',', ' ']).next()?,
+    };
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Well-known URL with the suffix inserted between the host and the path,
+/// as RFC 8414 and RFC 9728 specify.
+fn well_known(url: &Url, suffix: &str) -> Url {
+    let mut well_known = url.clone();
+    well_known.set_path(&format!(
+        "/.well-known/{}{}",
+        suffix,
+        url.path().trim_end_matches('/')
+    ));
+    well_known.set_query(None);
+    well_known.set_fragment(None);
+    well_known
+}
+
+/// Where an authorization server's metadata may be, most specific first.
+fn auth_server_metadata_urls(issuer: &Url) -> Vec<Url> {
+    let mut openid_appended = issuer.clone();
+    openid_appended.set_path(&format!(
+        "{}/.well-known/openid-configuration",
+        issuer.path().trim_end_matches('/')
+    ));
+    let mut urls = vec![
+        well_known(issuer, "oauth-authorization-server"),
+        well_known(issuer, "openid-configuration"),
+        openid_appended,
+    ];
+    urls.dedup();
+    urls
+}
+
+/// The server URI identifying it as an OAuth resource.
+fn canonical_resource(url: &Url) -> String {
+    let mut resource = url.clone();
+    resource.set_query(None);
+    resource.set_fragment(None);
+    resource.as_str().trim_end_matches('/').to_string()
+}
+
+async fn fetch_json<T: DeserializeOwned>(client: &reqwest::Client, url: Url) -> Option<T> {
+    let response = client
+        .get(url)
+        .header(ACCEPT, "application/json")
+        .send()
+        .await;
+    match response.and_then(|r| r.error_for_status()) {
+        Ok(response) => response.json().await.ok(),
+        Err(_) => None,
+    }
+}
+
+/// Find the authorization server of the MCP server at `url`.
+async fn discover(client: &reqwest::Client, url: &Url) -> anyhow::Result<AuthServerMetadata> {
+    let header = challenge(client, url).await?;
+    let mut origin = url.clone();
+    origin.set_path("/");
+    let mut candidates: Vec<Url> = header
+        .as_deref()
+        .and_then(resource_metadata_url)
+        .and_then(|u| Url::parse(&u).ok())
+        .into_iter()
+        .collect();
+    candidates.push(well_known(url, "oauth-protected-resource"));
+    candidates.push(well_known(&origin, "oauth-protected-resource"));
+
+    let mut issuer = None;
+    for candidate in candidates {
+        if let Some(metadata) = fetch_json::<ResourceMetadata>(client, candidate).await {
+            issuer = metadata.authorization_servers.into_iter().next();
+            if issuer.is_some() {
+                break;
+            }
+        }
+    }
+    // Servers predating RFC 9728 are their own authorization server
+    let issuer = match issuer {
+        Some(issuer) => Url::parse(&issuer)?,
+        None => origin,
+    };
+    for candidate in auth_server_metadata_urls(&issuer) {
+        if let Some(metadata) = fetch_json(client, candidate).await {
+            return Ok(metadata);
+        }
+    }
+    anyhow::bail!("No OAuth metadata found for {}", issuer)
+}
+
+/// Register Ghost as a public client for `redirect_uri`.
+async fn register(
+    client: &reqwest::Client,
+    metadata: &AuthServerMetadata,
+    redirect_uri: &str,
+) -> anyhow::Result<ClientRegistration> {
+    let endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+        anyhow::anyhow!("The authorization server doesn't support client registration")
+    })?;
+    let body = serde_json::json!({
+        "client_name": "Ghost",
+        "redirect_uris": [redirect_uri],
+        "grant_types": ["authorization_code", "refresh_token"],
+        "response_types": ["code"],
+        "token_endpoint_auth_method": "none",
+    });
+    Ok(client
+        .post(endpoint)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// POST a grant to the token endpoint.
+async fn request_token(
+    client: &reqwest::Client,
+    tokens: &StoredTokens,
+    grant: &[(&str, &str)],
+) -> anyhow::Result<TokenResponse> {
+    let mut form = grant.to_vec();
+    form.push(("client_id", tokens.client_id.as_str()));
+    form.push(("resource", tokens.resource.as_str()));
+    if let Some(secret) = &tokens.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let response = client
+        .post(&tokens.token_endpoint)
+        .header(ACCEPT, "application/json")
+        .form(&form)
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("Token request failed ({}): {}", status, body.trim());
+    }
+    Ok(response.json().await?)
+}
+
+/// 256 random bits, base64url-encoded (PKCE verifier, `state`).
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// PKCE S256 challenge of a verifier.
+fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Query parameters of a `GET /callback?...` request.
+fn parse_callback(request: &str) -> Option<HashMap<String, String>> {
+    let target = request
+        .lines()
+        .next()?
+        .strip_prefix("GET ")?
+        .split(' ')
+        .next()?;
+    let url = Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+    (url.path() == CALLBACK_PATH).then(|| url.query_pairs().into_owned().collect())
+}
+
+/// Answer browser requests until one arrives on the callback path.
+async fn receive_callback(listener: &TcpListener) -> anyhow::Result<HashMap<String, String>> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buf = vec![0u8; 8192];
+        let n = stream.read(&mut buf).await?;
+        let Some(params) = parse_callback(&String::from_utf8_lossy(&buf[..n])) else {
+            let _ = stream
+                .write_all(
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .await;
+            continue;
+        };
+        let body = "<!doctype html><title>Ghost</title><p>Sign-in finished. You can close this tab and return to Ghost.</p>";
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return Ok(params);
+    }
+}
+
+/// Sign in to `server` at `url`: `open_browser` is called with the
+/// authorization URL, then the redirect is awaited and the tokens stored.
+pub async fn sign_in(
+    server: &str,
+    url: &str,
+    open_browser: impl FnOnce(&str) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let server_url = Url::parse(url)?;
+    let client = http_client()?;
+    let metadata = discover(&client, &server_url).await?;
+    if !metadata.code_challenge_methods_supported.is_empty()
+        && !metadata
+            .code_challenge_methods_supported
+            .iter()
+            .any(|m| m == "S256")
+    {
+        anyhow::bail!("The authorization server doesn't support PKCE (S256)");
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let redirect_uri = format!(
+        "http://127.0.0.1:{}{}",
+        listener.local_addr()?.port(),
+        CALLBACK_PATH
+    );
+    let registration = register(&client, &metadata, &redirect_uri).await?;
+    let mut tokens = StoredTokens {
+        access_token: String::new(),
+        refresh_token: None,
+        expires_at: None,
+        token_endpoint: metadata.token_endpoint,
+        client_id: registration.client_id,
+        client_secret: registration.client_secret,
+        resource: canonical_resource(&server_url),
+    };
+
+    let verifier = random_string();
+    let state = random_string();
+    let mut authorize_url = Url::parse(&metadata.authorization_endpoint)?;
+    authorize_url
+        .query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &tokens.client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("code_challenge", &code_challenge(&verifier))
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("state", &state)
+        .append_pair("resource", &tokens.resource);
+    open_browser(authorize_url.as_str())?;
+
+    let params = tokio::time::timeout(SIGN_IN_TIMEOUT, receive_callback(&listener))
+        .await
+        .map_err(|_| anyhow::anyhow!("Sign-in timed out"))??;
+    if let Some(error) = params.get("error") {
+        let description = params.get("error_description").unwrap_or(error);
+        anyhow::bail!("Sign-in failed: {}", description);
+    }
+    if params.get("state") != Some(&state) {
+        anyhow::bail!("Sign-in failed: the redirect didn't match this request");
+    }
+    let code = params
+        .get("code")
+        .ok_or_else(|| anyhow::anyhow!("Sign-in failed: no authorization code"))?;
+
+    let grant = [
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("code_verifier", verifier.as_str()),
+    ];
+    let response = request_token(&client, &tokens, &grant).await?;
+    tokens.update(response, chrono::Utc::now().timestamp());
+    store(server, &tokens)?;
+    tracing::info!("MCP Client: signed in to '{}'", server);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_challenge_matches_rfc7636() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(random_string().len(), 43);
+    }
+
+    #[test]
+    fn test_resource_metadata_url() {
+        assert_eq!(
+            resource_metadata_url(
+                r#"Bearer error="invalid_token", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource""#
+            )
+            .as_deref(),
+            Some("https://mcp.example.com/.well-known/oauth-protected-resource")
+        );
+        assert_eq!(
+            resource_metadata_url("Bearer resource_metadata=https://a.example/m, scope=x")
+                .as_deref(),
+            Some("https://a.example/m")
+        );
+        assert_eq!(resource_metadata_url("Bearer realm=\"x\""), None);
+    }
+
+    #[test]
+    fn test_well_known_urls() {
+        let url = Url::parse("https://mcp.example.com/v1/mcp?x=1").unwrap();
+        assert_eq!(
+            well_known(&url, "oauth-protected-resource").as_str(),
+            "https://mcp.example.com/.well-known/oauth-protected-resource/v1/mcp"
+        );
+        assert_eq!(canonical_resource(&url), "https://mcp.example.com/v1/mcp");
+
+        let issuer = Url::parse("https://auth.example.com/tenant1").unwrap();
+        let urls: Vec<String> = auth_server_metadata_urls(&issuer)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://auth.example.com/.well-known/oauth-authorization-server/tenant1",
+                "https://auth.example.com/.well-known/openid-configuration/tenant1",
+                "https://auth.example.com/tenant1/.well-known/openid-configuration",
+            ]
+        );
+        let root = Url::parse("https://auth.example.com").unwrap();
+        assert_eq!(auth_server_metadata_urls(&root).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_callback() {
+        let params =
+            parse_callback("GET /callback?code=abc&state=xyz HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n")
+                .unwrap();
+        assert_eq!(params["code"], "abc");
+        assert_eq!(params["state"], "xyz");
+        assert!(parse_callback("GET /favicon.ico HTTP/1.1\r\n\r\n").is_none());
+        assert!(parse_callback("POST /callback HTTP/1.1\r\n\r\n").is_none());
+    }
+
+    #[test]
+    fn test_token_refresh_window() {
+        let mut tokens = StoredTokens {
+            access_token: "old".into(),
+            refresh_token: Some("refresh".into()),
+            expires_at: Some(1_000),
+            token_endpoint: "https://auth.example.com/token".into(),
+            client_id: "ghost".into(),
+            client_secret: None,
+            resource: "https://mcp.example.com".into(),
+        };
+        assert!(!tokens.needs_refresh(900));
+        assert!(tokens.needs_refresh(950));
+        tokens.update(
+            TokenResponse {
+                access_token: "new".into(),
+                expires_in: Some(3600),
+                refresh_token: None,
+            },
+            1_000,
+        );
+        assert_eq!(tokens.access_token, "new");
+        assert_eq!(tokens.expires_at, Some(4_600));
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+    }
+}
@@ -0,0 +1,63 @@
+//! MCP roots — the directories external servers may work in.
+//!
+//! Ghost's MCP client advertises the `roots` capability and answers
+//! `roots/list` with the active vault's watched directories as `file://`
+//! URIs, so servers like `server-filesystem` scope their operations to them.
+//! When the watched directories change, connected servers get a
+//! `notifications/roots/list_changed` (see
+//! [`super::mcp_client::McpClientManager::notify_roots_changed`]).
+
+use std::path::Path;
+use std::sync::RwLock;
+
+use rmcp::model::{ListRootsResult, Root};
+
+/// Current roots, as directory paths.
+static ROOTS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Set the roots to the watched directories. Returns whether they changed.
+pub fn configure(watched_directories: &[String]) -> bool {
+    let mut roots = ROOTS.write().unwrap_or_else(|e| e.into_inner());
+    if roots.as_slice() == watched_directories {
+        return false;
+    }
+    *roots = watched_directories.to_vec();
+    true
+}
+
+/// The `roots/list` answer. Directories that aren't absolute paths are left out.
+pub fn list_roots() -> ListRootsResult {
+    let roots = ROOTS.read().unwrap_or_else(|e| e.into_inner());
+    ListRootsResult {
+        roots: roots.iter().filter_map(|dir| to_root(dir)).collect(),
+    }
+}
+
+fn to_root(dir: &str) -> Option<Root> {
+    let path = Path::new(dir);
+    let uri = reqwest::Url::from_directory_path(path).ok()?;
+    Some(Root {
+        uri: uri.to_string(),
+        name: path.file_name().map(|n| n.to_string_lossy().into_owned()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_root() {
+        #[cfg(unix)]
+        let (dir, uri) = ("/home/ana/My Notes", "file:///home/ana/My%20Notes/");
+        #[cfg(windows)]
+        let (dir, uri) = (
+            "C:\\Users\\ana\\My Notes",
+            "file:///C:/Users/ana/My%20Notes/",
+        );
+        let root = to_root(dir).unwrap();
+        assert_eq!(root.uri, uri);
+        assert_eq!(root.name.as_deref(), Some("My Notes"));
+        assert!(to_root("relative/dir").is_none());
+    }
+}
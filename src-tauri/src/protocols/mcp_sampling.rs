@@ -0,0 +1,249 @@
+//! MCP sampling — external servers asking Ghost's local model for completions.
+//!
+//! Ghost's MCP client advertises the `sampling` capability and answers
+//! `sampling/createMessage` with the local [`crate::chat::ChatEngine`] (see
+//! [`create_message`]). Each server's [`SamplingPolicy`] decides whether the
+//! user is asked first: the request is shown as a `sampling_approval_required`
+//! prompt (see [`super::mcp_client::emit_prompt`]) and answered with
+//! `respond_tool_approval` ("always allow" stores `allow` for the server).
+//! Requests are refused when no UI is listening. Only text is supported.
+//!
+//! The other direction, Ghost's built-in server asking a connected client's
+//! model, lives in [`super::mcp_server`].
+
+use rmcp::model::{CreateMessageRequestParams, CreateMessageResult, Role, SamplingMessage};
+use rmcp::ErrorData as McpError;
+use serde_json::json;
+
+use super::mcp_client::{app_state, can_prompt, emit_prompt};
+use super::SamplingPolicy;
+use crate::agent::approval::ApprovalDecision;
+use crate::chat::ChatMessage;
+use crate::AppState;
+
+/// Most tokens a server may ask for in one request.
+const MAX_SAMPLING_TOKENS: u32 = 2048;
+
+/// Answer a `sampling/createMessage` request from the server `server`.
+pub async fn create_message(
+    server: &str,
+    params: CreateMessageRequestParams,
+) -> Result<CreateMessageResult, McpError> {
+    let state =
+        app_state().ok_or_else(|| McpError::internal_error("Sampling is not available", None))?;
+    let messages = to_chat_messages(&params).map_err(|e| McpError::invalid_params(e, None))?;
+    let max_tokens = params.max_tokens.clamp(1, MAX_SAMPLING_TOKENS);
+
+    let allowed = match policy(&state, server) {
+        SamplingPolicy::Allow => true,
+        SamplingPolicy::Deny => false,
+        SamplingPolicy::Ask => ask_user(&state, server, &messages, max_tokens).await,
+    };
+    if !allowed {
+        tracing::info!("MCP sampling request from '{}' refused", server);
+        return Err(McpError::invalid_request(
+            "The user declined the sampling request",
+            None,
+        ));
+    }
+
+    tracing::info!(
+        "MCP sampling: answering '{}' ({} messages, max {} tokens)",
+        server,
+        messages.len(),
+        max_tokens
+    );
+    let response = state
+        .chat_engine
+        .chat(&messages, max_tokens as usize)
+        .await
+        .map_err(|e| McpError::internal_error(format!("Local model error: {}", e), None))?;
+    let stop_reason = if response.tokens_generated >= max_tokens as usize {
+        CreateMessageResult::STOP_REASON_END_MAX_TOKEN
+    } else {
+        CreateMessageResult::STOP_REASON_END_TURN
+    };
+    Ok(CreateMessageResult {
+        model: response.model_id,
+        stop_reason: Some(stop_reason.into()),
+        message: SamplingMessage::assistant_text(response.content),
+    })
+}
+
+/// Convert a sampling request to chat messages. Fails on non-text content.
+fn to_chat_messages(params: &CreateMessageRequestParams) -> Result<Vec<ChatMessage>, String> {
+    let mut messages = Vec::with_capacity(params.messages.len() + 1);
+    if let Some(system) = params.system_prompt.as_ref().filter(|s| !s.is_empty()) {
+        messages.push(ChatMessage {
+            role: "system".into(),
+            content: system.clone(),
+        });
+    }
+    for message in &params.messages {
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        let content = message
+            .content
+            .clone()
+            .into_vec()
+            .iter()
+            .map(|c| {
+                c.as_text()
+                    .map(|t| t.text.clone())
+                    .ok_or("Only text sampling messages are supported")
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+        messages.push(ChatMessage {
+            role: role.into(),
+            content,
+        });
+    }
+    if !messages.iter().any(|m| m.role != "system") {
+        return Err("The sampling request has no messages".into());
+    }
+    Ok(messages)
+}
+
+fn policy(state: &AppState, server: &str) -> SamplingPolicy {
+    state
+        .settings
+        .lock()
+        .ok()
+        .and_then(|s| {
+            s.mcp_servers
+                .iter()
+                .find(|e| e.name == server)
+                .map(|e| e.sampling)
+        })
+        .unwrap_or_default()
+}
+
+/// Ask the user to approve a request. Unanswered requests are refused after
+/// the agent's approval timeout.
+async fn ask_user(
+    state: &AppState,
+    server: &str,
+    messages: &[ChatMessage],
+    max_tokens: u32,
+) -> bool {
+    if !can_prompt(state) {
+        return false;
+    }
+    let timeout_ms = state
+        .settings
+        .lock()
+        .map(|s| s.agent_config.approval_timeout_ms)
+        .unwrap_or(120_000);
+
+    let (approval_id, rx) = state.tool_approvals.register();
+    emit_prompt(
+        state,
+        "sampling_approval_required",
+        json!({
+            "approval_id": approval_id,
+            "server": server,
+            "messages": messages,
+            "max_tokens": max_tokens,
+            "timeout_ms": timeout_ms,
+        }),
+    );
+    let timeout = std::time::Duration::from_millis(timeout_ms);
+    let decision = match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(decision)) => Some(decision),
+        Ok(Err(_)) => None,
+        Err(_) => {
+            state.tool_approvals.cancel(&approval_id);
+            None
+        }
+    };
+    emit_prompt(
+        state,
+        "sampling_approval_resolved",
+        json!({
+            "approval_id": approval_id,
+            "server": server,
+            "decision": decision,
+        }),
+    );
+
+    match decision {
+        Some(ApprovalDecision::AlwaysAllow) => {
+            persist_allow(state, server);
+            true
+        }
+        Some(ApprovalDecision::Approve) => true,
+        Some(ApprovalDecision::Deny) | None => false,
+    }
+}
+
+fn persist_allow(state: &AppState, server: &str) {
+    let Ok(mut settings) = state.settings.lock() else {
+        return;
+    };
+    let Some(entry) = settings.mcp_servers.iter_mut().find(|e| e.name == server) else {
+        return;
+    };
+    entry.sampling = SamplingPolicy::Allow;
+    if let Err(e) = settings.save(&crate::get_settings_path()) {
+        tracing::warn!(
+            "Failed to persist sampling permission for '{}': {}",
+            server,
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(messages: Vec<SamplingMessage>) -> CreateMessageRequestParams {
+        CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages,
+            model_preferences: None,
+            system_prompt: Some("Be brief.".into()),
+            include_context: None,
+            temperature: None,
+            max_tokens: 100,
+            stop_sequences: None,
+            metadata: None,
+            tools: None,
+            tool_choice: None,
+        }
+    }
+
+    #[test]
+    fn test_to_chat_messages() {
+        let params = request(vec![
+            SamplingMessage::user_text("Summarize this"),
+            SamplingMessage::assistant_text("Sure"),
+        ]);
+        let messages = to_chat_messages(&params).unwrap();
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant"]);
+        assert_eq!(messages[1].content, "Summarize this");
+    }
+
+    #[test]
+    fn test_to_chat_messages_rejects_empty_and_non_text() {
+        assert!(to_chat_messages(&request(Vec::new())).is_err());
+        let tool_use = SamplingMessage::assistant_tool_use("1", "search", Default::default());
+        assert!(to_chat_messages(&request(vec![tool_use])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requests_refused_without_listener() {
+        let state = crate::test_app_state();
+        let messages = vec![ChatMessage {
+            role: "user".into(),
+            content: "hi".into(),
+        }];
+        assert!(!ask_user(&state, "github", &messages, 100).await);
+        assert!(state.tool_approvals.pending_ids().is_empty());
+    }
+}
@@ -3,19 +3,38 @@
 //! When running, Claude Desktop, Cursor, VS Code Copilot, and any MCP-compatible
 //! client can connect to Ghost and use its local search, indexing, and file tools.
 //!
+//! Indexed documents are also exposed as MCP resources: `ghost://doc/{id}`
+//! returns a document's full text and `ghost://doc/{id}/chunk/{index}` a single chunk.
+//! Enabled agent skills (SKILL.md) are exposed as MCP prompts.
+//!
 //! Transport: Streamable HTTP on localhost (configurable port).
 //! Protocol: MCP v2025-11-25 via `rmcp` crate.
+//!
+//! Any local process can reach the port, so the server can require a bearer
+//! token (`require_auth` in [`McpServerConfig`]). The token is generated on
+//! first run and applies to `/mcp`, `/agui` and `/a2a`; the public A2A agent
+//! card stays open.
+//!
+//! Clients that support MCP sampling lend their model to Ghost: `ghost_help`
+//! asks the client to phrase its answer when no local model is loaded.
+
+use std::sync::{Arc, RwLock};
 
-use std::sync::Arc;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
 
 use rmcp::{
     handler::server::{tool::ToolRouter, wrapper::Parameters},
     model::*,
-    tool, tool_handler, tool_router, ServerHandler,
+    service::{Peer, RequestContext},
+    tool, tool_handler, tool_router, RoleServer, ServerHandler,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::McpServerConfig;
+use crate::chat::ChatMessage;
 use crate::AppState;
 
 // ---------------------------------------------------------------------------
@@ -47,7 +66,7 @@ pub struct SearchResultItem {
     pub snippet: String,
     /// Relevance score (higher is better).
     pub score: f64,
-    /// Result source: "fts", "vector", or "hybrid".
+    /// Result source: "fts", "vector", "hybrid", or "fuzzy".
     pub source: String,
 }
 
@@ -63,6 +82,29 @@ fn default_recent_limit() -> usize {
     20
 }
 
+/// Parameters for the `ghost_workspace_context` tool.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct WorkspaceContextParams {
+    /// Absolute path of the project/workspace root.
+    pub project_root: String,
+    /// Recently opened or edited files (absolute paths, most recent first).
+    #[serde(default)]
+    pub recent_files: Vec<String>,
+    /// What the user is working on (selection, task, or question).
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Maximum number of context items (default: 10).
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+/// Parameters for the `ghost_help` tool.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct HelpParams {
+    /// A question about Ghost (e.g. "how do I exclude a folder?").
+    pub question: String,
+}
+
 /// A recently indexed file entry.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[allow(dead_code)]
@@ -94,6 +136,42 @@ pub struct IndexStatusResult {
     pub watched_directories: Vec<String>,
 }
 
+// ---------------------------------------------------------------------------
+// Resource URIs
+// ---------------------------------------------------------------------------
+
+/// URI scheme prefix for document resources.
+const DOC_URI_PREFIX: &str = "ghost://doc/";
+
+/// Number of documents returned per `resources/list` page.
+const RESOURCE_PAGE_SIZE: usize = 100;
+
+/// A parsed `ghost://doc/...` resource URI.
+#[derive(Debug, PartialEq, Eq)]
+enum ResourceRef {
+    /// `ghost://doc/{id}` — the whole document.
+    Document(i64),
+    /// `ghost://doc/{id}/chunk/{index}` — a single chunk.
+    Chunk(i64, i32),
+}
+
+fn document_uri(document_id: i64) -> String {
+    format!("{}{}", DOC_URI_PREFIX, document_id)
+}
+
+fn parse_resource_uri(uri: &str) -> Option<ResourceRef> {
+    let rest = uri.strip_prefix(DOC_URI_PREFIX)?;
+    let mut parts = rest.split('/');
+    let document_id = parts.next()?.parse().ok()?;
+    match (parts.next(), parts.next(), parts.next()) {
+        (None, _, _) => Some(ResourceRef::Document(document_id)),
+        (Some("chunk"), Some(index), None) => {
+            Some(ResourceRef::Chunk(document_id, index.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Ghost MCP Server Handler
 // ---------------------------------------------------------------------------
@@ -216,6 +294,137 @@ impl GhostMcpServer {
         let json = serde_json::to_string_pretty(&recent).unwrap_or_default();
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
+
+    /// Rank vault context relevant to the caller's editor workspace.
+    #[tool(
+        name = "ghost_workspace_context",
+        description = "Get local context for a coding workspace: given the project root, recently edited files, and an optional task, returns ranked notes, docs, and related code from the user's indexed files. Items inside the project are marked in_workspace."
+    )]
+    async fn ghost_workspace_context(
+        &self,
+        params: Parameters<WorkspaceContextParams>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        let params = params.0;
+        tracing::info!(
+            "MCP ghost_workspace_context: root={}, {} recent files",
+            params.project_root,
+            params.recent_files.len()
+        );
+
+        let request = crate::search::workspace::WorkspaceContextRequest {
+            project_root: params.project_root,
+            recent_files: params.recent_files,
+            query: params.query,
+            limit: Some(params.limit),
+        };
+        let items = crate::search::workspace::workspace_context(
+            &self.state.db,
+            &self.state.embedding_engine,
+            &request,
+        )
+        .await
+        .map_err(|e| rmcp::ErrorData::internal_error(format!("Context error: {}", e), None))?;
+
+        let json = serde_json::to_string_pretty(&items).unwrap_or_default();
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    /// Answer a question about Ghost's own features and settings.
+    #[tool(
+        name = "ghost_help",
+        description = "Answer questions about how to use Ghost itself (indexing folders, excluding folders, models, agent tools, MCP, settings) from Ghost's built-in help."
+    )]
+    async fn ghost_help(
+        &self,
+        params: Parameters<HelpParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::ErrorData> {
+        let question = &params.0.question;
+        tracing::info!("MCP ghost_help: question=\"{}\"", question);
+
+        let mut answer = crate::help::answer(&self.state.chat_engine, question).await;
+        // Without a local model, let the client's model phrase the topics
+        if !answer.generated && !answer.sources.is_empty() {
+            let messages = crate::help::prompt(question, &answer.answer);
+            if let Some(text) =
+                sample_client(&peer, &messages, crate::help::HELP_MAX_TOKENS as u32).await
+            {
+                answer.answer = text;
+                answer.generated = true;
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&answer).unwrap_or_default();
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+}
+
+/// Sampling request for `messages`; system messages become the system prompt.
+fn sampling_request(messages: &[ChatMessage], max_tokens: u32) -> CreateMessageRequestParams {
+    let system_prompt = messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let messages = messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| match m.role.as_str() {
+            "assistant" => SamplingMessage::assistant_text(m.content.clone()),
+            _ => SamplingMessage::user_text(m.content.clone()),
+        })
+        .collect();
+    CreateMessageRequestParams {
+        meta: None,
+        task: None,
+        messages,
+        model_preferences: None,
+        system_prompt: (!system_prompt.is_empty()).then_some(system_prompt),
+        include_context: None,
+        temperature: None,
+        max_tokens,
+        stop_sequences: None,
+        metadata: None,
+        tools: None,
+        tool_choice: None,
+    }
+}
+
+/// Ask the connected client's model for a completion (MCP sampling).
+/// `None` if the client doesn't support sampling, or declines or fails.
+async fn sample_client(
+    peer: &Peer<RoleServer>,
+    messages: &[ChatMessage],
+    max_tokens: u32,
+) -> Option<String> {
+    let supported = peer
+        .peer_info()
+        .is_some_and(|info| info.capabilities.sampling.is_some());
+    if !supported {
+        return None;
+    }
+    match peer
+        .create_message(sampling_request(messages, max_tokens))
+        .await
+    {
+        Ok(result) => {
+            let text = result
+                .message
+                .content
+                .into_vec()
+                .iter()
+                .filter_map(|c| c.as_text().map(|t| t.text.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        }
+        Err(e) => {
+            tracing::debug!("MCP sampling request to the client failed: {}", e);
+            None
+        }
+    }
 }
 
 /// Implement the MCP ServerHandler trait for Ghost.
@@ -224,7 +433,11 @@ impl ServerHandler for GhostMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
             server_info: Implementation {
                 name: "ghost".into(),
                 title: Some("Ghost — Agent OS".into()),
@@ -241,11 +454,220 @@ impl ServerHandler for GhostMcpServer {
                 "Ghost is a private, local-first Agent OS for desktop. \
                  It indexes local files and provides hybrid semantic + keyword search. \
                  Use ghost_search to find documents, ghost_index_status to check indexing progress, \
-                 and ghost_recent_files to see recently indexed files."
+                 ghost_recent_files to see recently indexed files, \
+                 ghost_workspace_context to pull notes and docs relevant to a coding project, \
+                 and ghost_help for questions about using Ghost itself. \
+                 Indexed documents are browsable as resources (ghost://doc/{id}), \
+                 and Ghost skills are available as prompts."
                     .into(),
             ),
         }
     }
+
+    /// List indexed documents as `ghost://doc/{id}` resources (paginated by offset cursor).
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, rmcp::ErrorData> {
+        let offset = request
+            .and_then(|r| r.cursor)
+            .and_then(|c| c.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        let docs = self
+            .state
+            .db
+            .list_documents(offset, RESOURCE_PAGE_SIZE)
+            .map_err(|e| rmcp::ErrorData::internal_error(format!("List error: {}", e), None))?;
+
+        let next_cursor =
+            (docs.len() == RESOURCE_PAGE_SIZE).then(|| (offset + RESOURCE_PAGE_SIZE).to_string());
+
+        let resources = docs
+            .into_iter()
+            .map(|doc| {
+                let mut raw = RawResource::new(document_uri(doc.id), doc.filename);
+                raw.description = Some(format!("{} ({} chunks)", doc.path, doc.chunk_count));
+                raw.mime_type = Some("text/plain".into());
+                raw.size = u32::try_from(doc.size_bytes).ok();
+                raw.no_annotation()
+            })
+            .collect();
+
+        let mut result = ListResourcesResult::with_all_items(resources);
+        result.next_cursor = next_cursor;
+        Ok(result)
+    }
+
+    /// Read a document (all chunks joined) or a single chunk by URI.
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::ErrorData> {
+        let uri = request.uri;
+        tracing::info!("MCP resources/read: {}", uri);
+
+        let not_found = || {
+            rmcp::ErrorData::resource_not_found(
+                format!("Resource not found: {}", uri),
+                Some(serde_json::json!({ "uri": uri })),
+            )
+        };
+        let db_error =
+            |e: crate::error::GhostError| rmcp::ErrorData::internal_error(e.to_string(), None);
+
+        let text = match parse_resource_uri(&uri).ok_or_else(not_found)? {
+            ResourceRef::Document(id) => {
+                if self.state.db.get_document(id).map_err(db_error)?.is_none() {
+                    return Err(not_found());
+                }
+                self.state
+                    .db
+                    .get_document_chunks(id)
+                    .map_err(db_error)?
+                    .into_iter()
+                    .map(|(_, content)| content)
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            }
+            ResourceRef::Chunk(id, index) => self
+                .state
+                .db
+                .get_document_chunks(id)
+                .map_err(db_error)?
+                .into_iter()
+                .find(|(i, _)| *i == index)
+                .map(|(_, content)| content)
+                .ok_or_else(not_found)?,
+        };
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(text, uri.clone())],
+        })
+    }
+
+    /// List enabled skills as prompt templates.
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, rmcp::ErrorData> {
+        let registry = crate::agent::skills::loaded();
+        let mut skills = registry.enabled_skills();
+        skills.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let prompts = skills
+            .into_iter()
+            .map(|skill| {
+                let mut arguments: Vec<PromptArgument> = skill
+                    .template_parameters()
+                    .into_iter()
+                    .map(|p| PromptArgument {
+                        required: Some(p.required && p.default.is_none()),
+                        description: Some(p.description).filter(|d| !d.is_empty()),
+                        name: p.name,
+                        title: None,
+                    })
+                    .collect();
+                if !arguments
+                    .iter()
+                    .any(|a| a.name == crate::agent::skills::PROMPT_INPUT_ARG)
+                {
+                    arguments.push(PromptArgument {
+                        name: crate::agent::skills::PROMPT_INPUT_ARG.into(),
+                        title: None,
+                        description: Some("The task or content to apply the skill to".into()),
+                        required: Some(false),
+                    });
+                }
+                Prompt::new(&skill.name, Some(&skill.description), Some(arguments))
+            })
+            .collect();
+
+        Ok(ListPromptsResult::with_all_items(prompts))
+    }
+
+    /// Render a skill as a prompt, substituting its arguments.
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, rmcp::ErrorData> {
+        tracing::info!("MCP prompts/get: {}", request.name);
+
+        let registry = crate::agent::skills::loaded();
+        let skill = registry
+            .get(&request.name)
+            .filter(|s| s.enabled)
+            .ok_or_else(|| {
+                rmcp::ErrorData::invalid_params(format!("Unknown prompt: {}", request.name), None)
+            })?;
+
+        let args = crate::agent::skills::string_args(request.arguments.unwrap_or_default());
+
+        let text = skill
+            .render_prompt(&args)
+            .map_err(|e| rmcp::ErrorData::invalid_params(e, None))?;
+
+        Ok(GetPromptResult {
+            description: Some(skill.description.clone()),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Authentication
+// ---------------------------------------------------------------------------
+
+/// Token requests must carry, or `None` while auth is off. Set by
+/// [`configure_auth`], so a rotated token applies without a restart.
+static AUTH_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// A new random bearer token (256 bits, hex).
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Apply the auth settings to the running server.
+pub fn configure_auth(config: &McpServerConfig) {
+    let token = config
+        .require_auth
+        .then(|| config.auth_token.clone().unwrap_or_default());
+    *AUTH_TOKEN.write().unwrap_or_else(|e| e.into_inner()) = token;
+}
+
+/// Whether a request may pass. Auth without a token rejects everything.
+fn is_authorized(headers: &axum::http::HeaderMap, token: Option<&str>) -> bool {
+    match token {
+        None => true,
+        Some("") => false,
+        Some(token) => super::openai_server::is_authorized(headers, Some(token)),
+    }
+}
+
+/// Middleware rejecting requests without the configured bearer token.
+async fn require_token(
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let authorized = {
+        let token = AUTH_TOKEN.read().unwrap_or_else(|e| e.into_inner());
+        is_authorized(request.headers(), token.as_deref())
+    };
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(axum::http::header::WWW_AUTHENTICATE, "Bearer")],
+            "Missing or invalid bearer token",
+        )
+            .into_response();
+    }
+    next.run(request).await
 }
 
 // ---------------------------------------------------------------------------
@@ -313,19 +735,33 @@ pub async fn start_server(state: Arc<AppState>, addr: &str) -> anyhow::Result<St
         }
     });
 
-    // A2A JSON-RPC endpoint: receives tasks from remote agents
+    // A2A JSON-RPC endpoint: receives tasks from remote agents.
+    // `message/stream` is answered with SSE, everything else with a JSON body.
+    let a2a_state = state.clone();
     let a2a_jsonrpc_handler = axum::routing::post(
-        |axum::Json(req): axum::Json<super::a2a::JsonRpcRequest>| async move {
-            let resp = super::a2a::dispatch_request(req).await;
-            axum::Json(resp)
+        move |axum::Json(req): axum::Json<super::a2a::JsonRpcRequest>| {
+            let state = a2a_state.clone();
+            async move {
+                if req.method == "message/stream" {
+                    match super::a2a::stream_message(&state, req) {
+                        Ok(stream) => axum::response::sse::Sse::new(stream)
+                            .keep_alive(axum::response::sse::KeepAlive::default())
+                            .into_response(),
+                        Err(resp) => axum::Json(*resp).into_response(),
+                    }
+                } else {
+                    axum::Json(super::a2a::dispatch_request(&state, req).await).into_response()
+                }
+            }
         },
     );
 
     let router = axum::Router::new()
         .route("/mcp", axum::routing::any_service(service))
         .route("/agui", agui_sse_handler)
-        .route("/.well-known/agent.json", a2a_card_handler)
-        .route("/a2a", a2a_jsonrpc_handler);
+        .route("/a2a", a2a_jsonrpc_handler)
+        .route_layer(axum::middleware::from_fn(require_token))
+        .route("/.well-known/agent.json", a2a_card_handler);
 
     let listener = tokio::net::TcpListener::bind(&addr_owned).await?;
     let actual_addr = listener.local_addr()?;
@@ -337,6 +773,7 @@ pub async fn start_server(state: Arc<AppState>, addr: &str) -> anyhow::Result<St
         "A2A Agent Card at http://{}/.well-known/agent.json",
         addr_str
     );
+    tracing::info!("A2A JSON-RPC endpoint on http://{}/a2a", addr_str);
 
     tokio::spawn(async move {
         if let Err(e) = axum::serve(listener, router).await {
@@ -346,3 +783,67 @@ pub async fn start_server(state: Arc<AppState>, addr: &str) -> anyhow::Result<St
 
     Ok(addr_str)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_uri() {
+        assert_eq!(
+            parse_resource_uri("ghost://doc/42"),
+            Some(ResourceRef::Document(42))
+        );
+        assert_eq!(
+            parse_resource_uri("ghost://doc/42/chunk/3"),
+            Some(ResourceRef::Chunk(42, 3))
+        );
+        assert_eq!(
+            parse_resource_uri(&document_uri(7)),
+            Some(ResourceRef::Document(7))
+        );
+    }
+
+    #[test]
+    fn test_parse_resource_uri_rejects_invalid() {
+        assert_eq!(parse_resource_uri("ghost://doc/"), None);
+        assert_eq!(parse_resource_uri("ghost://doc/abc"), None);
+        assert_eq!(parse_resource_uri("ghost://doc/1/chunk"), None);
+        assert_eq!(parse_resource_uri("ghost://doc/1/chunk/2/extra"), None);
+        assert_eq!(parse_resource_uri("file:///etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert!(is_authorized(&headers, None));
+        assert!(!is_authorized(&headers, Some("")));
+        headers.insert("authorization", "Bearer abc".parse().unwrap());
+        assert!(is_authorized(&headers, Some("abc")));
+        assert!(!is_authorized(&headers, Some("abd")));
+        // Auth enabled without a token fails closed
+        headers.insert("authorization", "Bearer ".parse().unwrap());
+        assert!(!is_authorized(&headers, Some("")));
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn test_sampling_request() {
+        let messages = crate::help::prompt("How do I exclude a folder?", "## Excluding\n...");
+        let request = sampling_request(&messages, 384);
+        assert!(request
+            .system_prompt
+            .as_deref()
+            .is_some_and(|p| p.contains("## Excluding")));
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(request.messages[0].role, Role::User);
+        assert_eq!(request.max_tokens, 384);
+        assert!(request.validate().is_ok());
+    }
+}
@@ -0,0 +1,282 @@
+//! Legacy HTTP+SSE transport for the MCP client.
+//!
+//! Older remote MCP servers (protocol 2024-11-05) speak two endpoints:
+//! - `GET <url>` opens an SSE stream; its first `endpoint` event carries the
+//!   URL to POST client messages to, later `message` events carry server messages
+//! - `POST <endpoint>` delivers one JSON-RPC message from the client
+//!
+//! The stream is re-opened with exponential backoff when it drops, sending
+//! `Last-Event-ID` so servers that support it can resume the session without
+//! losing messages. The transport is handed to rmcp as a (sink, stream) pair.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use tokio::sync::watch;
+
+/// How long to wait for the server's `endpoint` event on first connect.
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Stream re-open attempts before the transport gives up.
+const MAX_RECONNECTS: u32 = 8;
+
+/// POST attempts per outgoing message.
+const MAX_POST_ATTEMPTS: u32 = 3;
+
+/// Exponential backoff: 500ms, 1s, 2s, ... capped at 30s.
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let ms = 500u64.saturating_mul(1u64 << attempt.min(16));
+    Duration::from_millis(ms.min(30_000))
+}
+
+/// One parsed server-sent event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    pub event: String,
+    pub data: String,
+    pub id: Option<String>,
+}
+
+/// Incremental `text/event-stream` parser (handles events split across chunks).
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buf: Vec<u8>,
+    event: String,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk of bytes, returning every event completed by it.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        event: if self.event.is_empty() {
+                            "message".into()
+                        } else {
+                            std::mem::take(&mut self.event)
+                        },
+                        data: self.data.join("\n"),
+                        id: self.id.clone(),
+                    });
+                }
+                self.data.clear();
+                self.event.clear();
+                continue;
+            }
+            if line.starts_with(':') {
+                continue; // comment / keep-alive
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => self.event = value.to_string(),
+                "data" => self.data.push(value.to_string()),
+                "id" => self.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// Connect to a legacy SSE MCP server, sending `bearer_token` (from OAuth
+/// sign-in) with every request.
+///
+/// Returns a (sink, stream) pair usable as an rmcp client transport.
+pub async fn connect(
+    url: &str,
+    bearer_token: Option<&str>,
+) -> anyhow::Result<(
+    mpsc::UnboundedSender<ClientJsonRpcMessage>,
+    mpsc::UnboundedReceiver<ServerJsonRpcMessage>,
+)> {
+    let base = reqwest::Url::parse(url)?;
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(token) = bearer_token {
+        let mut value = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))?;
+        value.set_sensitive(true);
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+    }
+    let client = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?;
+
+    let (incoming_tx, incoming_rx) = mpsc::unbounded::<ServerJsonRpcMessage>();
+    let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded::<ClientJsonRpcMessage>();
+    let (endpoint_tx, mut endpoint_rx) = watch::channel::<Option<reqwest::Url>>(None);
+
+    // Reader: keeps the SSE stream open, re-opening it with backoff when it drops.
+    let reader_client = client.clone();
+    tokio::spawn(async move {
+        let mut last_event_id: Option<String> = None;
+        let mut failures = 0u32;
+
+        while failures <= MAX_RECONNECTS && !incoming_tx.is_closed() {
+            let mut request = reader_client
+                .get(base.clone())
+                .header(reqwest::header::ACCEPT, "text/event-stream");
+            if let Some(ref id) = last_event_id {
+                request = request.header("Last-Event-ID", id);
+            }
+
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(mut response) => {
+                    let mut parser = SseParser::default();
+                    loop {
+                        match response.chunk().await {
+                            Ok(Some(bytes)) => {
+                                for event in parser.feed(&bytes) {
+                                    // A healthy stream resets the backoff.
+                                    failures = 0;
+                                    if event.id.is_some() {
+                                        last_event_id = event.id.clone();
+                                    }
+                                    match event.event.as_str() {
+                                        "endpoint" => match base.join(event.data.trim()) {
+                                            Ok(endpoint) => {
+                                                let _ = endpoint_tx.send(Some(endpoint));
+                                            }
+                                            Err(e) => {
+                                                tracing::warn!("MCP SSE: bad endpoint: {}", e)
+                                            }
+                                        },
+                                        "message" => {
+                                            match serde_json::from_str::<ServerJsonRpcMessage>(
+                                                &event.data,
+                                            ) {
+                                                Ok(msg) => {
+                                                    if incoming_tx.unbounded_send(msg).is_err() {
+                                                        return; // client shut down
+                                                    }
+                                                }
+                                                Err(e) => tracing::debug!(
+                                                    "MCP SSE: ignoring malformed message: {}",
+                                                    e
+                                                ),
+                                            }
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            }
+                            Ok(None) => break,
+                            Err(e) => {
+                                tracing::debug!("MCP SSE: stream error: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                    tracing::info!("MCP SSE: stream closed by {}, reconnecting", base);
+                }
+                Err(e) => {
+                    tracing::warn!("MCP SSE: connect to {} failed: {}", base, e);
+                }
+            }
+
+            let delay = backoff_delay(failures);
+            failures += 1;
+            tokio::time::sleep(delay).await;
+        }
+        tracing::warn!("MCP SSE: giving up on {}", base);
+    });
+
+    // Wait for the first endpoint so connection errors surface to the caller.
+    tokio::time::timeout(ENDPOINT_TIMEOUT, endpoint_rx.wait_for(|e| e.is_some()))
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out waiting for SSE endpoint event from {}", url))?
+        .map_err(|_| anyhow::anyhow!("SSE stream to {} closed before sending an endpoint", url))?;
+
+    // Writer: POSTs each client message to the latest endpoint.
+    tokio::spawn(async move {
+        while let Some(message) = outgoing_rx.next().await {
+            let mut attempt = 0u32;
+            loop {
+                let Some(endpoint) = endpoint_rx.borrow().clone() else {
+                    break;
+                };
+                let result = client
+                    .post(endpoint)
+                    .json(&message)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status());
+                match result {
+                    Ok(_) => break,
+                    Err(e) if attempt + 1 < MAX_POST_ATTEMPTS => {
+                        tracing::debug!("MCP SSE: POST failed ({}), retrying", e);
+                        tokio::time::sleep(backoff_delay(attempt)).await;
+                        attempt += 1;
+                    }
+                    Err(e) => {
+                        tracing::warn!("MCP SSE: dropping message after retries: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok((outgoing_tx, incoming_rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(1), Duration::from_secs(1));
+        assert_eq!(backoff_delay(3), Duration::from_secs(4));
+        assert_eq!(backoff_delay(10), Duration::from_secs(30));
+        assert_eq!(backoff_delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_sse_parser_basic() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"event: endpoint\ndata: /messages?session=1\n\n");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: "endpoint".into(),
+                data: "/messages?session=1".into(),
+                id: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sse_parser_split_chunks_and_ids() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"id: 7\r\ndata: {\"a\":").is_empty());
+        assert!(parser.feed(b"1}\r\n").is_empty());
+        let events = parser.feed(b"\r\n: keep-alive\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "message");
+        assert_eq!(events[0].data, "{\"a\":1}");
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn test_sse_parser_multiline_data() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"data: line1\ndata: line2\n\n");
+        assert_eq!(events[0].data, "line1\nline2");
+    }
+}
@@ -0,0 +1,352 @@
+//! MCP tool call tracing and replay.
+//!
+//! Every call to an external MCP tool made through [`call_traced`] (by the
+//! agent, AG-UI runs, or the frontend) is recorded in the vault's
+//! `tool_calls` table: server, tool, arguments, a SHA-256 of the result,
+//! latency, outcome and the agent run it belongs to. A recorded call can be
+//! replayed with the same arguments; comparing result hashes shows whether a
+//! flaky server answers consistently. Only the newest [`MAX_RECORDS`] calls
+//! are kept.
+
+use std::time::Instant;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::mcp_limits::CallLimitError;
+use crate::db::Database;
+use crate::error::Result;
+use crate::AppState;
+
+/// Calls kept in the history.
+const MAX_RECORDS: i64 = 5000;
+
+/// Longest error message stored per call, in characters.
+const MAX_ERROR_CHARS: usize = 1000;
+
+/// One recorded tool call.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCallRecord {
+    pub id: i64,
+    pub server: String,
+    pub tool: String,
+    /// Arguments as sent (JSON).
+    pub arguments: Option<serde_json::Value>,
+    /// "ok", "error", or "throttled".
+    pub status: String,
+    /// SHA-256 (hex) of the result text, for successful calls.
+    pub result_hash: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    /// Agent run that made the call; `None` for calls from the UI.
+    pub run_id: Option<String>,
+    /// The call this one replayed.
+    pub replay_of: Option<i64>,
+    pub created_at: String,
+}
+
+/// Filter for [`get_history`]; unset fields match everything.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct HistoryFilter {
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+}
+
+/// Outcome of [`replay`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayResult {
+    /// The new call's record.
+    pub record: ToolCallRecord,
+    /// Result text, if the call succeeded.
+    pub result: Option<String>,
+    /// Whether the result hash equals the original call's.
+    pub same_result: bool,
+}
+
+/// Initialize the tool call history table.
+pub fn initialize_trace_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS tool_calls (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                server TEXT NOT NULL,
+                tool TEXT NOT NULL,
+                arguments TEXT,
+                status TEXT NOT NULL,
+                result_hash TEXT,
+                error TEXT,
+                latency_ms INTEGER NOT NULL DEFAULT 0,
+                run_id TEXT,
+                replay_of INTEGER,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_tool_calls_server_tool
+                ON tool_calls(server, tool);
+            CREATE INDEX IF NOT EXISTS idx_tool_calls_run
+                ON tool_calls(run_id);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+fn hash_result(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+/// A call to record.
+struct NewCall<'a> {
+    server: &'a str,
+    tool: &'a str,
+    arguments: Option<&'a serde_json::Value>,
+    run_id: Option<&'a str>,
+    replay_of: Option<i64>,
+}
+
+/// Record a finished call and drop the oldest ones past [`MAX_RECORDS`].
+fn record(
+    db: &Database,
+    call: &NewCall<'_>,
+    outcome: &anyhow::Result<String>,
+    latency_ms: i64,
+) -> Result<i64> {
+    let (status, result_hash, error) = match outcome {
+        Ok(text) => ("ok", Some(hash_result(text)), None),
+        Err(e) => {
+            let status = if e.is::<CallLimitError>() {
+                "throttled"
+            } else {
+                "error"
+            };
+            let error: String = e.to_string().chars().take(MAX_ERROR_CHARS).collect();
+            (status, None, Some(error))
+        }
+    };
+    let arguments = call.arguments.map(|a| a.to_string());
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO tool_calls
+                (server, tool, arguments, status, result_hash, error, latency_ms, run_id, replay_of)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                call.server,
+                call.tool,
+                arguments,
+                status,
+                result_hash,
+                error,
+                latency_ms,
+                call.run_id,
+                call.replay_of
+            ],
+        )?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM tool_calls WHERE id <= ?1",
+            rusqlite::params![id - MAX_RECORDS],
+        )?;
+        Ok(id)
+    })
+}
+
+async fn call_and_record(
+    state: &AppState,
+    call: NewCall<'_>,
+) -> (Option<i64>, anyhow::Result<String>) {
+    let start = Instant::now();
+    let outcome = state
+        .mcp_client
+        .call_tool(call.server, call.tool, call.arguments.cloned())
+        .await;
+    let latency_ms = start.elapsed().as_millis() as i64;
+    let id = match record(&state.db, &call, &outcome, latency_ms) {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::debug!("Tool call not recorded: {}", e);
+            None
+        }
+    };
+    (id, outcome)
+}
+
+/// Call an MCP tool and record the call in the history.
+pub async fn call_traced(
+    state: &AppState,
+    run_id: Option<&str>,
+    server: &str,
+    tool: &str,
+    arguments: Option<serde_json::Value>,
+) -> anyhow::Result<String> {
+    let call = NewCall {
+        server,
+        tool,
+        arguments: arguments.as_ref(),
+        run_id,
+        replay_of: None,
+    };
+    call_and_record(state, call).await.1
+}
+
+const SELECT_RECORD: &str = "SELECT id, server, tool, arguments, status, result_hash, error,
+        latency_ms, run_id, replay_of, created_at
+     FROM tool_calls";
+
+fn read_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<ToolCallRecord> {
+    let arguments: Option<String> = row.get(3)?;
+    Ok(ToolCallRecord {
+        id: row.get(0)?,
+        server: row.get(1)?,
+        tool: row.get(2)?,
+        arguments: arguments.and_then(|a| serde_json::from_str(&a).ok()),
+        status: row.get(4)?,
+        result_hash: row.get(5)?,
+        error: row.get(6)?,
+        latency_ms: row.get(7)?,
+        run_id: row.get(8)?,
+        replay_of: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+/// Recorded calls matching `filter`, newest first.
+pub fn get_history(
+    db: &Database,
+    filter: &HistoryFilter,
+    limit: usize,
+) -> Result<Vec<ToolCallRecord>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE (?1 IS NULL OR server = ?1)
+               AND (?2 IS NULL OR tool = ?2)
+               AND (?3 IS NULL OR run_id = ?3)
+             ORDER BY id DESC
+             LIMIT ?4",
+            SELECT_RECORD
+        ))?;
+        let rows = stmt.query_map(
+            rusqlite::params![filter.server, filter.tool, filter.run_id, limit as i64],
+            read_record,
+        )?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// A recorded call by ID.
+pub fn get_call(db: &Database, id: i64) -> Result<Option<ToolCallRecord>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(&format!("{} WHERE id = ?1", SELECT_RECORD))?;
+        let mut rows = stmt.query_map(rusqlite::params![id], read_record)?;
+        Ok(rows.next().transpose()?)
+    })
+}
+
+/// Call a recorded tool again with the same arguments. The replay is
+/// recorded too, linked to the original.
+pub async fn replay(state: &AppState, id: i64) -> anyhow::Result<ReplayResult> {
+    let original =
+        get_call(&state.db, id)?.ok_or_else(|| anyhow::anyhow!("No tool call with ID {}", id))?;
+    let call = NewCall {
+        server: &original.server,
+        tool: &original.tool,
+        arguments: original.arguments.as_ref(),
+        run_id: None,
+        replay_of: Some(original.id),
+    };
+    let (new_id, outcome) = call_and_record(state, call).await;
+    let record = match new_id {
+        Some(new_id) => get_call(&state.db, new_id)?,
+        None => None,
+    }
+    .ok_or_else(|| anyhow::anyhow!("The replay could not be recorded"))?;
+    let same_result = record.result_hash.is_some() && record.result_hash == original.result_hash;
+    Ok(ReplayResult {
+        record,
+        result: outcome.ok(),
+        same_result,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_test_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        initialize_trace_schema(&db).unwrap();
+        db
+    }
+
+    fn call<'a>(tool: &'a str, run_id: Option<&'a str>) -> NewCall<'a> {
+        NewCall {
+            server: "github",
+            tool,
+            arguments: None,
+            run_id,
+            replay_of: None,
+        }
+    }
+
+    #[test]
+    fn test_record_and_filter() {
+        let db = setup_test_db();
+        let args = serde_json::json!({ "owner": "ghostapp-ai" });
+        let listed = NewCall {
+            arguments: Some(&args),
+            ..call("list_repos", Some("run-1"))
+        };
+        record(&db, &listed, &Ok("[]".into()), 120).unwrap();
+        record(
+            &db,
+            &call("get_issue", Some("run-1")),
+            &Err(anyhow::anyhow!("404")),
+            30,
+        )
+        .unwrap();
+        let throttled = CallLimitError::timed_out("github", "search");
+        record(&db, &call("search", None), &Err(throttled.into()), 5).unwrap();
+
+        let all = get_history(&db, &HistoryFilter::default(), 10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].status, "throttled");
+        assert_eq!(all[1].error.as_deref(), Some("404"));
+        assert_eq!(all[2].arguments, Some(args));
+        assert_eq!(all[2].result_hash, Some(hash_result("[]")));
+
+        let run = HistoryFilter {
+            run_id: Some("run-1".into()),
+            ..Default::default()
+        };
+        assert_eq!(get_history(&db, &run, 10).unwrap().len(), 2);
+        let tool = HistoryFilter {
+            tool: Some("search".into()),
+            ..Default::default()
+        };
+        assert_eq!(get_history(&db, &tool, 10).unwrap().len(), 1);
+        assert_eq!(get_call(&db, all[1].id).unwrap().unwrap().tool, "get_issue");
+        assert!(get_call(&db, 999).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let db = setup_test_db();
+        let id = record(&db, &call("a", None), &Ok(String::new()), 1).unwrap();
+        db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE sqlite_sequence SET seq = ?1 WHERE name = 'tool_calls'",
+                rusqlite::params![id + MAX_RECORDS],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        record(&db, &call("b", None), &Ok(String::new()), 1).unwrap();
+        let all = get_history(&db, &HistoryFilter::default(), 10).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].tool, "b");
+    }
+}
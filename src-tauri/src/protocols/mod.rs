@@ -5,16 +5,26 @@
 //! - **MCP Client**: Connect to external MCP servers (filesystem, GitHub, databases, etc.)
 //! - **AG-UI**: Agent↔User interaction streaming (Phase 1.5+)
 //! - **A2UI**: Generative UI from JSON schemas (Phase 1.5+)
-//! - **A2A**: Agent-to-Agent coordination (Phase 2)
-//! - **WebMCP**: Browser tool contracts (Phase 2.5)
+//! - **A2A**: Agent-to-Agent coordination (serve tasks + delegate to remote agents)
+//! - **WebMCP**: Bridge exposing Ghost tools to browser extensions (per-origin permissions)
+//! - **OpenAI-compatible API**: `/v1/chat/completions` and `/v1/embeddings` for local tools
 
 pub mod a2a;
 pub mod a2ui;
 pub mod agui;
 pub mod mcp_catalog;
 pub mod mcp_client;
+pub mod mcp_elicitation;
+pub mod mcp_limits;
+pub mod mcp_oauth;
+pub mod mcp_roots;
+pub mod mcp_sampling;
 pub mod mcp_server;
+pub mod mcp_sse;
+pub mod mcp_trace;
+pub mod openai_server;
 pub mod runtime_bootstrap;
+pub mod webmcp;
 
 use std::sync::Arc;
 
@@ -32,6 +42,12 @@ pub struct McpServerConfig {
     /// Hostname to bind (default: 127.0.0.1 for security).
     #[serde(default = "default_mcp_host")]
     pub host: String,
+    /// Reject requests without `Authorization: Bearer <auth_token>`.
+    #[serde(default)]
+    pub require_auth: bool,
+    /// Bearer token for clients, generated on first run.
+    #[serde(default)]
+    pub auth_token: Option<String>,
 }
 
 fn default_mcp_enabled() -> bool {
@@ -50,7 +66,21 @@ impl Default for McpServerConfig {
             enabled: default_mcp_enabled(),
             port: default_mcp_port(),
             host: default_mcp_host(),
+            require_auth: false,
+            auth_token: None,
+        }
+    }
+}
+
+impl McpServerConfig {
+    /// Generate the bearer token if there is none yet.
+    /// Returns true if one was generated (and settings need saving).
+    pub fn ensure_token(&mut self) -> bool {
+        if self.auth_token.as_deref().is_some_and(|t| !t.is_empty()) {
+            return false;
         }
+        self.auth_token = Some(mcp_server::generate_token());
+        true
     }
 }
 
@@ -59,7 +89,7 @@ impl Default for McpServerConfig {
 pub struct McpServerEntry {
     /// Display name for this server.
     pub name: String,
-    /// Transport type: "stdio" or "http".
+    /// Transport type: "stdio", "http" (streamable HTTP), or "sse" (legacy HTTP+SSE).
     pub transport: String,
     /// For stdio: the command to execute (e.g., "npx", "uvx", "python").
     pub command: Option<String>,
@@ -71,9 +101,80 @@ pub struct McpServerEntry {
     /// Whether this server connection is enabled.
     #[serde(default = "default_true")]
     pub enabled: bool,
-    /// Environment variables to pass to stdio processes.
+    /// Environment variables to pass to stdio processes. A value of
+    /// `${secret:<name>}` is read from the OS keychain (see [`crate::secrets`]).
     #[serde(default)]
     pub env: std::collections::HashMap<String, String>,
+    /// Timeouts and rate limits for calls to this server's tools.
+    #[serde(default)]
+    pub limits: CallLimits,
+    /// Whether the server may ask Ghost's local model for completions.
+    #[serde(default)]
+    pub sampling: SamplingPolicy,
+}
+
+/// How sampling requests from an external MCP server are handled (see
+/// [`mcp_sampling`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingPolicy {
+    /// Ask the user for every request.
+    #[default]
+    Ask,
+    /// Answer without asking.
+    Allow,
+    /// Refuse every request.
+    Deny,
+}
+
+/// Limits on tool calls to one MCP server (see [`mcp_limits`]). Zero
+/// disables a limit.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CallLimits {
+    /// Seconds a call may run before it is abandoned.
+    pub timeout_secs: u64,
+    /// Calls running at once; more are refused until one finishes.
+    pub max_concurrent: usize,
+    /// Calls started per rolling minute.
+    pub calls_per_minute: u32,
+    /// Overrides for single tools, by tool name.
+    pub tools: std::collections::HashMap<String, ToolLimits>,
+}
+
+impl Default for CallLimits {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 120,
+            max_concurrent: 4,
+            calls_per_minute: 60,
+            tools: Default::default(),
+        }
+    }
+}
+
+/// Per-tool overrides of [`CallLimits`]; `None` keeps the server's value.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToolLimits {
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub calls_per_minute: Option<u32>,
+}
+
+/// A remote A2A agent Ghost's agent can delegate subtasks to.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct A2aAgentEntry {
+    /// Display name (used by the agent to pick a delegate).
+    pub name: String,
+    /// Base URL serving `/.well-known/agent.json` (e.g., "http://localhost:9000").
+    pub url: String,
+    /// Short description of what the agent is good at (shown to the local model).
+    #[serde(default)]
+    pub description: String,
+    /// Whether delegation to this agent is enabled.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
 }
 
 fn default_true() -> bool {
@@ -0,0 +1,456 @@
+//! OpenAI-compatible HTTP API on localhost.
+//!
+//! Serves `/v1/chat/completions`, `/v1/embeddings` and `/v1/models` backed by
+//! Ghost's ChatEngine and EmbeddingEngine, so local tools (Continue, Raycast,
+//! scripts using an OpenAI SDK) can use Ghost as their model provider.
+//! Off by default; an optional API key is checked as a bearer token.
+
+use std::sync::Arc;
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::chat::ChatMessage;
+use crate::AppState;
+
+/// Largest reply a client may request, in tokens.
+const MAX_COMPLETION_TOKENS: usize = 4096;
+/// Most inputs accepted in one `/v1/embeddings` request.
+const MAX_EMBEDDING_INPUTS: usize = 256;
+
+/// OpenAI-compatible server configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiServerConfig {
+    /// Whether the server is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Port for the HTTP server (default: 6776).
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Hostname to bind (default: 127.0.0.1 — localhost only).
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Required as `Authorization: Bearer <key>` when set.
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+fn default_port() -> u16 {
+    6776
+}
+fn default_host() -> String {
+    "127.0.0.1".into()
+}
+
+impl Default for OpenAiServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_port(),
+            host: default_host(),
+            api_key: None,
+        }
+    }
+}
+
+impl OpenAiServerConfig {
+    /// Base URL clients point their OpenAI SDK at.
+    pub fn base_url(&self) -> String {
+        format!("http://{}:{}/v1", self.host, self.port)
+    }
+}
+
+/// Message content: a plain string or a list of typed parts.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentPart {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+impl MessageContent {
+    /// Text of the message; non-text parts (images, audio) are skipped.
+    fn into_text(self) -> String {
+        match self {
+            Self::Text(text) => text,
+            Self::Parts(parts) => parts
+                .into_iter()
+                .filter(|p| p.kind == "text")
+                .filter_map(|p| p.text)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<MessageContent>,
+}
+
+/// Body of `POST /v1/chat/completions`. Unknown fields are ignored.
+#[derive(Debug, Deserialize)]
+struct ChatCompletionRequest {
+    #[serde(default)]
+    model: Option<String>,
+    messages: Vec<RequestMessage>,
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    max_completion_tokens: Option<usize>,
+    #[serde(default)]
+    stream: bool,
+}
+
+/// Embedding input: one string or a list of strings.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EmbeddingInput {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Body of `POST /v1/embeddings`.
+#[derive(Debug, Deserialize)]
+struct EmbeddingRequest {
+    input: EmbeddingInput,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+/// OpenAI-style error response.
+fn error(status: StatusCode, kind: &str, message: impl Into<String>) -> Response {
+    let body = serde_json::json!({
+        "error": { "message": message.into(), "type": kind, "code": null }
+    });
+    (status, axum::Json(body)).into_response()
+}
+
+/// Compare without leaking the length of the matching prefix through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether the request carries the configured API key (always true without one).
+pub(super) fn is_authorized(headers: &HeaderMap, api_key: Option<&str>) -> bool {
+    let Some(expected) = api_key.filter(|k| !k.is_empty()) else {
+        return true;
+    };
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), expected.as_bytes()))
+}
+
+/// Convert request messages to Ghost chat messages.
+fn to_chat_messages(messages: Vec<RequestMessage>) -> Vec<ChatMessage> {
+    messages
+        .into_iter()
+        .map(|m| ChatMessage {
+            // Tool and function results are plain context for the local model
+            role: match m.role.as_str() {
+                "system" | "developer" => "system".into(),
+                "assistant" => "assistant".into(),
+                _ => "user".into(),
+            },
+            content: m.content.map(MessageContent::into_text).unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Rough token count for the `usage` field.
+fn approx_tokens(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+async fn chat_completions(state: Arc<AppState>, request: ChatCompletionRequest) -> Response {
+    if request.messages.is_empty() {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request_error",
+            "messages cannot be empty",
+        );
+    }
+    let max_tokens = request
+        .max_completion_tokens
+        .or(request.max_tokens)
+        .unwrap_or_else(|| {
+            state
+                .settings
+                .lock()
+                .map(|s| s.chat_max_tokens)
+                .unwrap_or(512)
+        })
+        .clamp(1, MAX_COMPLETION_TOKENS);
+    let messages = to_chat_messages(request.messages);
+    let prompt_tokens: usize = messages.iter().map(|m| approx_tokens(&m.content)).sum();
+
+    crate::analytics::track(&state.db, crate::analytics::UsageFeature::Chat, None);
+    let response = match state.chat_engine.chat(&messages, max_tokens).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("OpenAI API chat failed: {}", e);
+            return error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "server_error",
+                e.to_string(),
+            );
+        }
+    };
+
+    let id = format!(
+        "chatcmpl-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    );
+    let created = unix_now();
+    let model = request.model.unwrap_or(response.model_id);
+
+    if request.stream {
+        // Ghost generates the whole reply at once; send it as a single delta
+        let chunks = [
+            serde_json::json!({
+                "id": id, "object": "chat.completion.chunk", "created": created, "model": model,
+                "choices": [{ "index": 0, "delta": { "role": "assistant", "content": response.content }, "finish_reason": null }],
+            }),
+            serde_json::json!({
+                "id": id, "object": "chat.completion.chunk", "created": created, "model": model,
+                "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }],
+            }),
+        ];
+        let events = chunks
+            .into_iter()
+            .map(|chunk| axum::response::sse::Event::default().data(chunk.to_string()))
+            .chain(std::iter::once(
+                axum::response::sse::Event::default().data("[DONE]"),
+            ))
+            .map(Ok::<_, std::convert::Infallible>);
+        return axum::response::sse::Sse::new(futures::stream::iter(events)).into_response();
+    }
+
+    axum::Json(serde_json::json!({
+        "id": id,
+        "object": "chat.completion",
+        "created": created,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": response.content },
+            "finish_reason": "stop",
+        }],
+        "usage": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": response.tokens_generated,
+            "total_tokens": prompt_tokens + response.tokens_generated,
+        },
+    }))
+    .into_response()
+}
+
+async fn embeddings(state: Arc<AppState>, request: EmbeddingRequest) -> Response {
+    let inputs = match request.input {
+        EmbeddingInput::One(text) => vec![text],
+        EmbeddingInput::Many(texts) => texts,
+    };
+    if inputs.is_empty() || inputs.len() > MAX_EMBEDDING_INPUTS {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request_error",
+            format!("input must have 1-{} items", MAX_EMBEDDING_INPUTS),
+        );
+    }
+    let vectors = match state.embedding_engine.embed_batch(&inputs).await {
+        Ok(vectors) => vectors,
+        Err(e) => {
+            tracing::warn!("OpenAI API embeddings failed: {}", e);
+            return error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "server_error",
+                e.to_string(),
+            );
+        }
+    };
+    let tokens: usize = inputs.iter().map(|t| approx_tokens(t)).sum();
+    let data: Vec<serde_json::Value> = vectors
+        .into_iter()
+        .enumerate()
+        .map(|(index, embedding)| {
+            serde_json::json!({ "object": "embedding", "index": index, "embedding": embedding })
+        })
+        .collect();
+
+    axum::Json(serde_json::json!({
+        "object": "list",
+        "data": data,
+        "model": request.model.unwrap_or_else(|| state.embedding_engine.model_key()),
+        "usage": { "prompt_tokens": tokens, "total_tokens": tokens },
+    }))
+    .into_response()
+}
+
+fn models(state: &AppState) -> Response {
+    let created = unix_now();
+    let chat = state.chat_engine.status().model_id;
+    let embedding = state.embedding_engine.model_key();
+    let data: Vec<serde_json::Value> = [chat, embedding]
+        .into_iter()
+        .map(|id| serde_json::json!({ "id": id, "object": "model", "created": created, "owned_by": "ghost" }))
+        .collect();
+    axum::Json(serde_json::json!({ "object": "list", "data": data })).into_response()
+}
+
+/// Start the OpenAI-compatible server in the background.
+/// Returns the address it's listening on.
+pub async fn start_server(
+    state: Arc<AppState>,
+    config: &OpenAiServerConfig,
+) -> anyhow::Result<String> {
+    if !config.enabled {
+        tracing::info!("OpenAI-compatible API disabled in settings");
+        return Ok("disabled".to_string());
+    }
+
+    let api_key = Arc::new(config.api_key.clone());
+
+    let chat_state = state.clone();
+    let chat_key = api_key.clone();
+    let chat_handler = axum::routing::post(
+        move |headers: HeaderMap, axum::Json(request): axum::Json<ChatCompletionRequest>| {
+            let state = chat_state.clone();
+            let api_key = chat_key.clone();
+            async move {
+                if !is_authorized(&headers, api_key.as_deref()) {
+                    return error(
+                        StatusCode::UNAUTHORIZED,
+                        "invalid_api_key",
+                        "Invalid API key",
+                    );
+                }
+                chat_completions(state, request).await
+            }
+        },
+    );
+
+    let embed_state = state.clone();
+    let embed_key = api_key.clone();
+    let embeddings_handler = axum::routing::post(
+        move |headers: HeaderMap, axum::Json(request): axum::Json<EmbeddingRequest>| {
+            let state = embed_state.clone();
+            let api_key = embed_key.clone();
+            async move {
+                if !is_authorized(&headers, api_key.as_deref()) {
+                    return error(
+                        StatusCode::UNAUTHORIZED,
+                        "invalid_api_key",
+                        "Invalid API key",
+                    );
+                }
+                embeddings(state, request).await
+            }
+        },
+    );
+
+    let models_handler = axum::routing::get(move |headers: HeaderMap| {
+        let state = state.clone();
+        let api_key = api_key.clone();
+        async move {
+            if !is_authorized(&headers, api_key.as_deref()) {
+                return error(
+                    StatusCode::UNAUTHORIZED,
+                    "invalid_api_key",
+                    "Invalid API key",
+                );
+            }
+            models(&state)
+        }
+    });
+
+    let router = axum::Router::new()
+        .route("/v1/chat/completions", chat_handler)
+        .route("/v1/embeddings", embeddings_handler)
+        .route("/v1/models", models_handler);
+
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let addr_str = listener.local_addr()?.to_string();
+    tracing::info!("OpenAI-compatible API on http://{}/v1", addr_str);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!("OpenAI-compatible API server error: {}", e);
+        }
+    });
+
+    Ok(addr_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_authorized() {
+        let mut headers = HeaderMap::new();
+        assert!(is_authorized(&headers, None));
+        assert!(!is_authorized(&headers, Some("secret")));
+        headers.insert("authorization", "Bearer wrong".parse().unwrap());
+        assert!(!is_authorized(&headers, Some("secret")));
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        assert!(is_authorized(&headers, Some("secret")));
+    }
+
+    #[test]
+    fn test_parse_chat_request() {
+        let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4o",
+            "temperature": 0.2,
+            "messages": [
+                { "role": "developer", "content": "Be brief." },
+                { "role": "user", "content": [
+                    { "type": "text", "text": "What is" },
+                    { "type": "image_url", "image_url": { "url": "data:..." } },
+                    { "type": "text", "text": "Rust?" }
+                ]},
+                { "role": "tool", "content": "42" }
+            ]
+        }))
+        .unwrap();
+        assert!(!request.stream);
+        let messages = to_chat_messages(request.messages);
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "user"]);
+        assert_eq!(messages[1].content, "What is\nRust?");
+    }
+
+    #[test]
+    fn test_parse_embedding_input() {
+        let one: EmbeddingRequest =
+            serde_json::from_value(serde_json::json!({ "input": "hello" })).unwrap();
+        assert!(matches!(one.input, EmbeddingInput::One(_)));
+        let many: EmbeddingRequest =
+            serde_json::from_value(serde_json::json!({ "input": ["a", "b"], "model": "x" }))
+                .unwrap();
+        assert!(matches!(many.input, EmbeddingInput::Many(ref v) if v.len() == 2));
+    }
+}
@@ -955,32 +955,16 @@ async fn get_binary_version(bin: &Path, args: &[&str]) -> Option<(String, String
     Some((version, bin.display().to_string()))
 }
 
-/// Download a file from a URL to a local path.
+/// Download a file from a URL to a local path (resumable, bandwidth-limited).
 async fn download_file(url: &str, dest: &Path) -> Result<(), String> {
-    tracing::info!("Downloading {} -> {}", url, dest.display());
-
-    let response = reqwest::get(url)
-        .await
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP {} for {}", response.status(), url));
-    }
-
-    let bytes = response
-        .bytes()
+    let label = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| url.to_string());
+    crate::downloads::manager()
+        .download(&label, crate::downloads::DownloadKind::Runtime, url, dest)
         .await
-        .map_err(|e| format!("Failed to read response body: {}", e))?;
-
-    if let Some(parent) = dest.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
-
-    std::fs::write(dest, &bytes).map_err(|e| format!("Failed to write file: {}", e))?;
-
-    tracing::info!("Downloaded {} bytes to {}", bytes.len(), dest.display());
-    Ok(())
+        .map(|_| ())
 }
 
 /// Extract an archive (tar.gz, tar.xz, zip) to a directory.
@@ -0,0 +1,563 @@
+//! WebMCP bridge — Ghost tools for browser extensions.
+//!
+//! A small WebSocket server (`/webmcp`) that browser extensions connect to
+//! to expose Ghost's search, index and agent tools to `navigator.modelContext`
+//! pages. The contract is plain JSON, one message per frame:
+//!
+//! ```text
+//! → {"id": 1, "method": "tools/list"}
+//! ← {"id": 1, "result": {"tools": [{"name", "description", "inputSchema"}]}}
+//! → {"id": 2, "method": "tools/call", "params": {"name": "ghost_search", "arguments": {...}}}
+//! ← {"id": 2, "result": {...}}   or   {"id": 2, "error": {"code", "message"}}
+//! ```
+//!
+//! Permissions are per browser origin (the extension's or page's `Origin`
+//! header). The first tool call from an origin without a stored
+//! [`OriginPermission`] shows a `webmcp_permission_required` prompt (see
+//! [`super::mcp_client::emit_prompt`]), answered with `respond_tool_approval`;
+//! "always allow" stores `allow` for the origin. Denied origins can't connect.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{mpsc, Mutex};
+
+use super::mcp_client::{can_prompt, emit_prompt};
+use super::mcp_server::{IndexStatusResult, RecentFilesParams, SearchParams, SearchResultItem};
+use crate::agent::approval::ApprovalDecision;
+use crate::AppState;
+
+/// Most results a search may return.
+const MAX_SEARCH_RESULTS: usize = 50;
+
+/// WebMCP bridge configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebMcpConfig {
+    /// Whether the bridge is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Port for the WebSocket server (default: 6777).
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Hostname to bind (default: 127.0.0.1 — localhost only).
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Stored decisions per origin; origins not listed are asked on first use.
+    #[serde(default)]
+    pub origins: BTreeMap<String, OriginPermission>,
+}
+
+fn default_port() -> u16 {
+    6777
+}
+fn default_host() -> String {
+    "127.0.0.1".into()
+}
+
+impl Default for WebMcpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_port(),
+            host: default_host(),
+            origins: BTreeMap::new(),
+        }
+    }
+}
+
+impl WebMcpConfig {
+    /// WebSocket URL extensions connect to.
+    pub fn url(&self) -> String {
+        format!("ws://{}:{}/webmcp", self.host, self.port)
+    }
+}
+
+/// Stored decision for a browser origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OriginPermission {
+    Allow,
+    Deny,
+}
+
+/// Parameters for the `ghost_agent` tool.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct AgentParams {
+    /// The task for Ghost's local agent, in natural language.
+    pub prompt: String,
+}
+
+/// A request from the extension.
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: serde_json::Value,
+    method: String,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+/// Parameters of `tools/call`.
+#[derive(Debug, Deserialize)]
+struct CallParams {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// An error answer: a short machine-readable code plus a message.
+#[derive(Debug, PartialEq)]
+struct CallError {
+    code: &'static str,
+    message: String,
+}
+
+impl CallError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// The tools the bridge exposes, as `tools/list` entries.
+fn tool_list() -> serde_json::Value {
+    use rmcp::handler::server::common::schema_for_type;
+
+    json!({
+        "tools": [
+            {
+                "name": "ghost_search",
+                "description": "Search the user's locally indexed files (hybrid keyword + semantic). Returns paths, snippets and scores.",
+                "inputSchema": schema_for_type::<SearchParams>(),
+            },
+            {
+                "name": "ghost_index_status",
+                "description": "Get Ghost's indexing status: document and chunk counts, vector search availability, watched directories.",
+                "inputSchema": { "type": "object", "properties": {} },
+            },
+            {
+                "name": "ghost_recent_files",
+                "description": "List recently indexed files, most recent first.",
+                "inputSchema": schema_for_type::<RecentFilesParams>(),
+            },
+            {
+                "name": "ghost_agent",
+                "description": "Give a task to Ghost's local agent, which can search files and use the user's tools. Returns the agent's answer.",
+                "inputSchema": schema_for_type::<AgentParams>(),
+            },
+        ]
+    })
+}
+
+fn parse_args<T: serde::de::DeserializeOwned>(
+    arguments: serde_json::Value,
+) -> Result<T, CallError> {
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments
+    };
+    serde_json::from_value(arguments).map_err(|e| CallError::new("invalid_params", e.to_string()))
+}
+
+/// Run one tool.
+async fn call_tool(
+    state: &Arc<AppState>,
+    origin: &str,
+    call: CallParams,
+) -> Result<serde_json::Value, CallError> {
+    let internal = |e: &dyn std::fmt::Display| CallError::new("internal_error", e.to_string());
+    tracing::info!("WebMCP {} from {}", call.name, origin);
+
+    match call.name.as_str() {
+        "ghost_search" => {
+            let params: SearchParams = parse_args(call.arguments)?;
+            let limit = params.limit.clamp(1, MAX_SEARCH_RESULTS);
+            let results = crate::search::hybrid_search(
+                &state.db,
+                &state.embedding_engine,
+                &params.query,
+                limit,
+            )
+            .await
+            .map_err(|e| internal(&e))?;
+            let items: Vec<SearchResultItem> = results
+                .into_iter()
+                .map(|r| SearchResultItem {
+                    path: r.path,
+                    filename: r.filename,
+                    snippet: r.snippet,
+                    score: r.score,
+                    source: r.source,
+                })
+                .collect();
+            Ok(json!({ "results": items }))
+        }
+        "ghost_index_status" => {
+            let stats = state.db.get_stats().map_err(|e| internal(&e))?;
+            let watched = state
+                .settings
+                .lock()
+                .map(|s| s.watched_directories.clone())
+                .unwrap_or_default();
+            let status = IndexStatusResult {
+                document_count: stats.document_count,
+                chunk_count: stats.chunk_count,
+                embedded_chunk_count: stats.embedded_chunk_count,
+                vector_search_enabled: state.db.is_vec_enabled(),
+                watched_directories: watched,
+            };
+            serde_json::to_value(status).map_err(|e| internal(&e))
+        }
+        "ghost_recent_files" => {
+            let params: RecentFilesParams = parse_args(call.arguments)?;
+            let recent = state
+                .db
+                .get_recent_documents(params.limit)
+                .map_err(|e| internal(&e))?;
+            Ok(json!({ "files": recent }))
+        }
+        "ghost_agent" => {
+            let params: AgentParams = parse_args(call.arguments)?;
+            if params.prompt.trim().is_empty() {
+                return Err(CallError::new("invalid_params", "prompt cannot be empty"));
+            }
+            static NEXT_RUN: AtomicU64 = AtomicU64::new(1);
+            let run_id = format!("webmcp-{}", NEXT_RUN.fetch_add(1, Ordering::Relaxed));
+            crate::analytics::track(&state.db, crate::analytics::UsageFeature::AgentRun, None);
+            let messages = vec![crate::chat::ChatMessage {
+                role: "user".into(),
+                content: params.prompt,
+            }];
+            let executor = crate::agent::executor::AgentExecutor::new(state.clone());
+            let result = executor
+                .run(&run_id, &messages, None, &state.agui_event_bus)
+                .await
+                .map_err(|e| internal(&e))?;
+            Ok(json!({ "run_id": run_id, "content": result.content }))
+        }
+        other => Err(CallError::new(
+            "unknown_tool",
+            format!("Unknown tool '{}'", other),
+        )),
+    }
+}
+
+fn stored_permission(state: &AppState, origin: &str) -> Option<OriginPermission> {
+    state
+        .settings
+        .lock()
+        .ok()
+        .and_then(|s| s.webmcp.origins.get(origin).copied())
+}
+
+/// Store a decision for `origin`, or forget it (`None`) so it's asked again.
+pub fn set_permission(
+    state: &AppState,
+    origin: &str,
+    permission: Option<OriginPermission>,
+) -> crate::error::Result<()> {
+    let mut settings = state.settings.lock().unwrap_or_else(|e| e.into_inner());
+    match permission {
+        Some(permission) => {
+            settings
+                .webmcp
+                .origins
+                .insert(origin.to_string(), permission);
+        }
+        None => {
+            settings.webmcp.origins.remove(origin);
+        }
+    }
+    settings.save(&crate::get_settings_path())
+}
+
+/// Ask the user whether `origin` may use Ghost's tools. Unanswered prompts
+/// are refused after the agent's approval timeout.
+async fn ask_user(state: &AppState, origin: &str, tool: &str) -> bool {
+    if !can_prompt(state) {
+        return false;
+    }
+    let timeout_ms = state
+        .settings
+        .lock()
+        .map(|s| s.agent_config.approval_timeout_ms)
+        .unwrap_or(120_000);
+
+    let (approval_id, rx) = state.tool_approvals.register();
+    emit_prompt(
+        state,
+        "webmcp_permission_required",
+        json!({
+            "approval_id": approval_id,
+            "origin": origin,
+            "tool": tool,
+            "timeout_ms": timeout_ms,
+        }),
+    );
+    let timeout = std::time::Duration::from_millis(timeout_ms);
+    let decision = match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(decision)) => Some(decision),
+        Ok(Err(_)) => None,
+        Err(_) => {
+            state.tool_approvals.cancel(&approval_id);
+            None
+        }
+    };
+    emit_prompt(
+        state,
+        "webmcp_permission_resolved",
+        json!({
+            "approval_id": approval_id,
+            "origin": origin,
+            "decision": decision,
+        }),
+    );
+
+    match decision {
+        Some(ApprovalDecision::AlwaysAllow) => {
+            if let Err(e) = set_permission(state, origin, Some(OriginPermission::Allow)) {
+                tracing::warn!("Failed to persist WebMCP permission for {}: {}", origin, e);
+            }
+            true
+        }
+        Some(ApprovalDecision::Approve) => true,
+        Some(ApprovalDecision::Deny) | None => false,
+    }
+}
+
+/// Per-connection permission: `None` until the first tool call decides it.
+type Granted = Arc<Mutex<Option<bool>>>;
+
+/// Whether this connection may call `tool`, asking the user once if needed.
+async fn check_permission(state: &AppState, origin: &str, tool: &str, granted: &Granted) -> bool {
+    // Held while asking, so concurrent first calls share one prompt
+    let mut granted = granted.lock().await;
+    match stored_permission(state, origin) {
+        Some(OriginPermission::Allow) => return true,
+        Some(OriginPermission::Deny) => return false,
+        None => {}
+    }
+    if let Some(granted) = *granted {
+        return granted;
+    }
+    let allowed = ask_user(state, origin, tool).await;
+    *granted = Some(allowed);
+    allowed
+}
+
+/// Answer one request frame.
+async fn handle_request(
+    state: &Arc<AppState>,
+    origin: &str,
+    granted: &Granted,
+    text: &str,
+) -> serde_json::Value {
+    let request: Request = match serde_json::from_str(text) {
+        Ok(r) => r,
+        Err(e) => {
+            return json!({
+                "id": null,
+                "error": { "code": "invalid_request", "message": e.to_string() },
+            })
+        }
+    };
+    let result = match request.method.as_str() {
+        "tools/list" => Ok(tool_list()),
+        "tools/call" => match parse_args::<CallParams>(request.params) {
+            Ok(call) => {
+                if check_permission(state, origin, &call.name, granted).await {
+                    call_tool(state, origin, call).await
+                } else {
+                    Err(CallError::new(
+                        "permission_denied",
+                        format!("The user has not allowed {} to use Ghost", origin),
+                    ))
+                }
+            }
+            Err(e) => Err(e),
+        },
+        other => Err(CallError::new(
+            "method_not_found",
+            format!("Unknown method '{}'", other),
+        )),
+    };
+    match result {
+        Ok(result) => json!({ "id": request.id, "result": result }),
+        Err(e) => json!({
+            "id": request.id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+    }
+}
+
+/// Serve one extension connection. Requests run concurrently, so a long
+/// agent task doesn't hold up searches; replies carry the request's `id`.
+async fn serve_socket(
+    mut socket: axum::extract::ws::WebSocket,
+    state: Arc<AppState>,
+    origin: String,
+) {
+    use axum::extract::ws::Message;
+
+    let granted: Granted = Arc::new(Mutex::new(None));
+    let (reply_tx, mut reply_rx) = mpsc::unbounded_channel::<String>();
+    tracing::info!("WebMCP client connected from {}", origin);
+
+    loop {
+        let outgoing = tokio::select! {
+            reply = reply_rx.recv() => match reply {
+                Some(reply) => reply,
+                None => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let state = state.clone();
+                    let origin = origin.clone();
+                    let granted = granted.clone();
+                    let reply_tx = reply_tx.clone();
+                    tokio::spawn(async move {
+                        let reply = handle_request(&state, &origin, &granted, text.as_str()).await;
+                        let _ = reply_tx.send(reply.to_string());
+                    });
+                    continue;
+                }
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                // Ping/pong is answered by axum; binary frames are ignored.
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        if socket.send(Message::Text(outgoing.into())).await.is_err() {
+            break;
+        }
+    }
+
+    tracing::info!("WebMCP client from {} disconnected", origin);
+}
+
+/// Start the WebMCP bridge in the background.
+/// Returns the address it's listening on.
+pub async fn start_server(state: Arc<AppState>, config: &WebMcpConfig) -> anyhow::Result<String> {
+    if !config.enabled {
+        tracing::info!("WebMCP bridge disabled in settings");
+        return Ok("disabled".to_string());
+    }
+
+    let handler = axum::routing::get(
+        move |ws: axum::extract::ws::WebSocketUpgrade, headers: axum::http::HeaderMap| {
+            let state = state.clone();
+            async move {
+                use axum::http::StatusCode;
+                use axum::response::IntoResponse;
+
+                // Browsers always send an Origin; it's what permissions are keyed by
+                let Some(origin) = headers
+                    .get(axum::http::header::ORIGIN)
+                    .and_then(|v| v.to_str().ok())
+                    .filter(|o| !o.is_empty() && *o != "null")
+                    .map(str::to_string)
+                else {
+                    return (StatusCode::BAD_REQUEST, "Origin header required").into_response();
+                };
+                if stored_permission(&state, &origin) == Some(OriginPermission::Deny) {
+                    tracing::warn!("WebMCP rejected denied origin {}", origin);
+                    return StatusCode::FORBIDDEN.into_response();
+                }
+                ws.on_upgrade(move |socket| serve_socket(socket, state, origin))
+                    .into_response()
+            }
+        },
+    );
+
+    let router = axum::Router::new().route("/webmcp", handler);
+
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let addr_str = listener.local_addr()?.to_string();
+    tracing::info!("WebMCP bridge on ws://{}/webmcp", addr_str);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!("WebMCP bridge error: {}", e);
+        }
+    });
+
+    Ok(addr_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_list() {
+        let list = tool_list();
+        let names: Vec<&str> = list["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "ghost_search",
+                "ghost_index_status",
+                "ghost_recent_files",
+                "ghost_agent"
+            ]
+        );
+        assert_eq!(list["tools"][0]["inputSchema"]["type"], "object");
+    }
+
+    #[tokio::test]
+    async fn test_calls_denied_without_listener() {
+        let state = crate::test_app_state();
+        let granted: Granted = Arc::new(Mutex::new(None));
+        let reply = handle_request(
+            &state,
+            "chrome-extension://abc",
+            &granted,
+            r#"{"id": 7, "method": "tools/call", "params": {"name": "ghost_index_status"}}"#,
+        )
+        .await;
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["error"]["code"], "permission_denied");
+        assert_eq!(*granted.lock().await, Some(false));
+        assert!(state.tool_approvals.pending_ids().is_empty());
+
+        let reply = handle_request(
+            &state,
+            "chrome-extension://abc",
+            &granted,
+            r#"{"id": 8, "method": "tools/list"}"#,
+        )
+        .await;
+        assert!(reply["result"]["tools"].is_array());
+        let reply = handle_request(
+            &state,
+            "chrome-extension://abc",
+            &granted,
+            r#"{"id": 9, "method": "resources/list"}"#,
+        )
+        .await;
+        assert_eq!(reply["error"]["code"], "method_not_found");
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let params: SearchParams = parse_args(json!({ "query": "taxes" })).unwrap();
+        assert_eq!(params.limit, 10);
+        let params: RecentFilesParams = parse_args(serde_json::Value::Null).unwrap();
+        assert_eq!(params.limit, 20);
+        let err = parse_args::<AgentParams>(json!({})).unwrap_err();
+        assert_eq!(err.code, "invalid_params");
+    }
+}
@@ -0,0 +1,640 @@
+//! Reminders and follow-ups found in notes and chats.
+//!
+//! Lines that ask to be remembered — "remind me to call Ana on Friday",
+//! `TODO: renew passport by 2025-06-01`, unchecked `- [ ]` items, "follow up
+//! with the landlord", "deadline: March 3" — are stored as reminder records
+//! when a note is indexed or a chat message is saved. Dates are resolved
+//! relative to when the text was written. Due reminders are announced once
+//! through the OS notification center; `list_upcoming_reminders` lists them.
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Reminders kept per document, so a long TODO list doesn't flood the list.
+const MAX_REMINDERS_PER_SOURCE: usize = 50;
+
+/// Longest reminder text kept.
+const MAX_REMINDER_CHARS: usize = 200;
+
+/// Time of day for reminders that only name a date.
+const DEFAULT_HOUR: u32 = 9;
+
+/// Storage format for `due_at` (local time, sortable as text).
+const DUE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A stored reminder.
+#[derive(Debug, Clone, Serialize)]
+pub struct Reminder {
+    pub id: i64,
+    /// "document" or "conversation".
+    pub source: String,
+    pub source_id: i64,
+    /// Document path or conversation title.
+    pub source_label: Option<String>,
+    pub text: String,
+    /// Local time, `YYYY-MM-DD HH:MM:SS`; `None` for undated TODOs.
+    pub due_at: Option<String>,
+    pub done: bool,
+    pub created_at: String,
+}
+
+/// A reminder found in text, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReminderCandidate {
+    pub text: String,
+    pub due_at: Option<NaiveDateTime>,
+}
+
+/// Initialize the reminders table in the database.
+pub fn initialize_reminders_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS reminders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source TEXT NOT NULL,
+                source_id INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                due_at TEXT,
+                done INTEGER NOT NULL DEFAULT 0,
+                notified INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE(source, source_id, text)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_reminders_due ON reminders(done, due_at);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+// --- Extraction ---
+
+/// Strip list bullets and numbering: `- `, `* `, `1. `.
+fn strip_bullet(line: &str) -> &str {
+    let line = line.trim();
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return rest.trim_start();
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return rest.trim_start();
+        }
+    }
+    line
+}
+
+/// The reminder text of a line, if the line asks for one.
+/// Returns (text, needs_date): deadline lines only count when they have a date.
+fn trigger(line: &str) -> Option<(String, bool)> {
+    // ASCII lowercasing keeps byte offsets valid for slicing `line`
+    let lower = line.to_ascii_lowercase();
+
+    if let Some(pos) = lower.find("remind me") {
+        let rest = line[pos + "remind me".len()..].trim_start();
+        let rest_lower = rest.to_ascii_lowercase();
+        let rest = ["to ", "about ", "that ", "of "]
+            .iter()
+            .find_map(|p| rest_lower.starts_with(p).then(|| &rest[p.len()..]))
+            .unwrap_or(rest);
+        return Some((rest.to_string(), false));
+    }
+
+    let item = strip_bullet(line);
+    for checkbox in ["[ ] ", "[] "] {
+        if let Some(rest) = item.strip_prefix(checkbox) {
+            return Some((rest.to_string(), false));
+        }
+    }
+    let item_lower = item.to_ascii_lowercase();
+    if item_lower.starts_with("todo") {
+        let rest = &item["todo".len()..];
+        // `TODO:`, `TODO -`, `TODO(ana):`, `TODO ` — not "today" or "todos are..."
+        if let Some(rest) = rest.strip_prefix('(').and_then(|r| r.split_once(')')) {
+            return Some((
+                rest.1.trim_start_matches([':', '-', ' ']).to_string(),
+                false,
+            ));
+        }
+        if rest.starts_with([':', '-', ' ']) {
+            return Some((rest.trim_start_matches([':', '-', ' ']).to_string(), false));
+        }
+    }
+    if item_lower.starts_with("follow up") || item_lower.starts_with("follow-up") {
+        return Some((item.to_string(), false));
+    }
+    let has_deadline_word = item_lower
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|w| matches!(w, "deadline" | "due"));
+    if has_deadline_word {
+        return Some((item.to_string(), true));
+    }
+    None
+}
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Month number from a full or three-letter name ("sept" too).
+fn month_number(word: &str) -> Option<u32> {
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| *m == word || (word.len() <= 4 && m.starts_with(word)))
+        .map(|i| i as u32 + 1)
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    let day = match word {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(day)
+}
+
+/// Day of month from "5", "5th", "21st".
+fn day_number(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let day: u32 = digits.parse().ok()?;
+    (1..=31).contains(&day).then_some(day)
+}
+
+/// Time of day from "3pm", "3:30pm", "15:00", "noon" (with the word after it,
+/// for "3 pm").
+fn time_of_day(word: &str, next: Option<&str>) -> Option<NaiveTime> {
+    match word {
+        "noon" | "midday" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let (clock, suffix) = match word.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => (&word[..i], &word[i..]),
+        None => (
+            word,
+            next.filter(|n| matches!(*n, "am" | "pm")).unwrap_or(""),
+        ),
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm
+        None if !suffix.is_empty() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match suffix {
+        "am" if hour == 12 => 0,
+        "am" => hour,
+        "pm" if hour < 12 => hour + 12,
+        "pm" => hour,
+        "" => hour,
+        _ => return None,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// The date named by the phrase starting at `words[i]`, if any.
+fn date_at(words: &[&str], i: usize, today: NaiveDate) -> Option<NaiveDate> {
+    let word = words[i];
+    let next = words.get(i + 1).copied();
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some(date);
+    }
+    match word {
+        "today" | "tonight" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        "week" if i > 0 && words[i - 1] == "next" => return Some(today + Duration::days(7)),
+        "month" if i > 0 && words[i - 1] == "next" => {
+            return today.checked_add_months(Months::new(1))
+        }
+        // "in 3 days", "in 2 weeks", "in a month"
+        "in" => {
+            let n = match next? {
+                "a" | "an" | "one" => 1,
+                n => n.parse::<u32>().ok()?,
+            };
+            return match words.get(i + 2)?.trim_end_matches('s') {
+                "day" => Some(today + Duration::days(n.into())),
+                "week" => Some(today + Duration::weeks(n.into())),
+                "month" => today.checked_add_months(Months::new(n)),
+                _ => None,
+            };
+        }
+        _ => {}
+    }
+
+    if let Some(target) = weekday(word) {
+        // The next such day, never today
+        let ahead =
+            (target.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+        let ahead = if ahead == 0 { 7 } else { ahead };
+        return Some(today + Duration::days(ahead.into()));
+    }
+    if let Some(month) = month_number(word) {
+        // "March 5" / "March 5th, 2026"
+        let day = day_number(next?)?;
+        let year = words.get(i + 2).and_then(|y| y.parse::<i32>().ok());
+        return month_day(today, month, day, year);
+    }
+    // "5 March" / "5th of March 2026"
+    let day = day_number(word)?;
+    let mut j = i + 1;
+    if words.get(j) == Some(&"of") {
+        j += 1;
+    }
+    let month = month_number(words.get(j)?)?;
+    let year = words.get(j + 1).and_then(|y| y.parse::<i32>().ok());
+    month_day(today, month, day, year)
+}
+
+/// Resolve the first date (and time) mentioned in `text`, relative to
+/// `reference` (when the text was written).
+pub fn parse_due(text: &str, reference: NaiveDateTime) -> Option<NaiveDateTime> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')' | ';' | '!' | '?'))
+        .map(|w| w.trim_end_matches('.'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let today = reference.date();
+
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    for (i, word) in words.iter().enumerate() {
+        let next = words.get(i + 1).copied();
+        if time.is_none() {
+            time = time_of_day(word, next);
+        }
+        if date.is_some() {
+            continue;
+        }
+
+        date = date_at(&words, i, today);
+        if *word == "tonight" && time.is_none() {
+            time = NaiveTime::from_hms_opt(20, 0, 0);
+        }
+    }
+
+    match (date, time) {
+        (Some(date), time) => time
+            .or_else(|| NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0))
+            .map(|time| date.and_time(time)),
+        // "remind me at 5pm": today if still ahead, else tomorrow
+        (None, Some(time)) => {
+            let due = today.and_time(time);
+            Some(if due > reference {
+                due
+            } else {
+                due + Duration::days(1)
+            })
+        }
+        (None, None) => None,
+    }
+}
+
+/// A month and day without a year is the next such date on or after today.
+fn month_day(today: NaiveDate, month: u32, day: u32, year: Option<i32>) -> Option<NaiveDate> {
+    let year = year.filter(|y| (1970..=9999).contains(y));
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        None => {
+            let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if this_year >= today {
+                Some(this_year)
+            } else {
+                NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+            }
+        }
+    }
+}
+
+/// Find reminders in a note or message, one per line, in order.
+pub fn extract_reminders(text: &str, reference: NaiveDateTime) -> Vec<ReminderCandidate> {
+    let mut found: Vec<ReminderCandidate> = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let Some((body, needs_date)) = trigger(line) else {
+            continue;
+        };
+        let due_at = parse_due(&body, reference);
+        if needs_date && due_at.is_none() {
+            continue;
+        }
+        let body = body
+            .trim()
+            .trim_end_matches(['.', '!', ';', ','])
+            .trim()
+            .to_string();
+        if body.is_empty() {
+            continue;
+        }
+        let text: String = body.chars().take(MAX_REMINDER_CHARS).collect();
+        if !found.iter().any(|c| c.text == text) {
+            found.push(ReminderCandidate { text, due_at });
+        }
+        if found.len() >= MAX_REMINDERS_PER_SOURCE {
+            break;
+        }
+    }
+    found
+}
+
+/// Local date-time from the indexer's `modified_at` (UTC, RFC 3339).
+pub fn local_time(timestamp: &str) -> Option<NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Local).naive_local())
+}
+
+// --- Storage ---
+
+fn upsert(db: &Database, source: &str, source_id: i64, found: &[ReminderCandidate]) -> Result<()> {
+    db.with_transaction(|conn| {
+        for candidate in found {
+            let due_at = candidate.due_at.map(|d| d.format(DUE_FORMAT).to_string());
+            conn.execute(
+                "INSERT INTO reminders (source, source_id, text, due_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(source, source_id, text) DO UPDATE SET
+                    notified = CASE WHEN due_at IS excluded.due_at THEN notified ELSE 0 END,
+                    due_at = excluded.due_at",
+                rusqlite::params![source, source_id, candidate.text, due_at],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Re-scan an indexed document: add new reminders, drop open ones whose line
+/// is gone, and keep completed ones.
+pub fn scan_document(
+    db: &Database,
+    document_id: i64,
+    text: &str,
+    reference: NaiveDateTime,
+) -> Result<usize> {
+    let found = extract_reminders(text, reference);
+    let existing: Vec<(i64, String)> = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, text FROM reminders
+             WHERE source = 'document' AND source_id = ?1 AND done = 0",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![document_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })?;
+    for (id, text) in existing {
+        if !found.iter().any(|c| c.text == text) {
+            db.with_conn(|conn| {
+                conn.execute("DELETE FROM reminders WHERE id = ?1", rusqlite::params![id])?;
+                Ok(())
+            })?;
+        }
+    }
+    upsert(db, "document", document_id, &found)?;
+    Ok(found.len())
+}
+
+/// Record reminders from a chat message.
+pub fn scan_message(db: &Database, conversation_id: i64, content: &str) -> Result<usize> {
+    let found = extract_reminders(content, chrono::Local::now().naive_local());
+    upsert(db, "conversation", conversation_id, &found)?;
+    Ok(found.len())
+}
+
+const REMINDER_COLUMNS: &str = "r.id, r.source, r.source_id,
+    CASE r.source
+        WHEN 'document' THEN (SELECT path FROM documents WHERE id = r.source_id)
+        ELSE (SELECT title FROM conversations WHERE id = r.source_id)
+    END,
+    r.text, r.due_at, r.done, r.created_at";
+
+/// Reminders whose document was deleted are hidden.
+const LIVE_SOURCE: &str = "(r.source != 'document'
+    OR EXISTS (SELECT 1 FROM documents WHERE id = r.source_id))";
+
+fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        source_id: row.get(2)?,
+        source_label: row.get(3)?,
+        text: row.get(4)?,
+        due_at: row.get(5)?,
+        done: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// Open reminders due within `days` (overdue ones included), soonest first,
+/// followed by undated ones when `include_undated` is set.
+pub fn list_upcoming(
+    db: &Database,
+    now: NaiveDateTime,
+    days: u32,
+    include_undated: bool,
+    limit: usize,
+) -> Result<Vec<Reminder>> {
+    let until = (now + Duration::days(days.into()))
+        .format(DUE_FORMAT)
+        .to_string();
+    db.with_conn(|conn| {
+        let sql = format!(
+            "SELECT {} FROM reminders r
+             WHERE r.done = 0 AND {}
+               AND ((r.due_at IS NOT NULL AND r.due_at <= ?1) OR (?2 AND r.due_at IS NULL))
+             ORDER BY r.due_at IS NULL, r.due_at, r.id
+             LIMIT ?3",
+            REMINDER_COLUMNS, LIVE_SOURCE
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(
+            rusqlite::params![until, include_undated, limit as i64],
+            from_row,
+        )?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// Open reminders that have come due and were not announced yet; marks them
+/// as announced.
+pub fn take_due(db: &Database, now: NaiveDateTime) -> Result<Vec<Reminder>> {
+    let now = now.format(DUE_FORMAT).to_string();
+    db.with_transaction(|conn| {
+        let sql = format!(
+            "SELECT {} FROM reminders r
+             WHERE r.done = 0 AND r.notified = 0 AND r.due_at IS NOT NULL AND r.due_at <= ?1
+               AND {}
+             ORDER BY r.due_at",
+            REMINDER_COLUMNS, LIVE_SOURCE
+        );
+        let due = {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params![now], from_row)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for reminder in &due {
+            conn.execute(
+                "UPDATE reminders SET notified = 1 WHERE id = ?1",
+                rusqlite::params![reminder.id],
+            )?;
+        }
+        Ok(due)
+    })
+}
+
+/// Mark a reminder done (or open again).
+pub fn set_done(db: &Database, id: i64, done: bool) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE reminders SET done = ?1 WHERE id = ?2",
+            rusqlite::params![done, id],
+        )?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_parse_due() {
+        // Wednesday
+        let now = at("2025-03-12 10:00");
+        assert_eq!(parse_due("tomorrow", now), Some(at("2025-03-13 09:00")));
+        assert_eq!(
+            parse_due("call Ana on Friday at 3pm", now),
+            Some(at("2025-03-14 15:00"))
+        );
+        assert_eq!(parse_due("by wednesday", now), Some(at("2025-03-19 09:00")));
+        assert_eq!(
+            parse_due("renew by 2025-06-01", now),
+            Some(at("2025-06-01 09:00"))
+        );
+        assert_eq!(parse_due("March 5th", now), Some(at("2026-03-05 09:00")));
+        assert_eq!(
+            parse_due("5 of april 2027", now),
+            Some(at("2027-04-05 09:00"))
+        );
+        assert_eq!(parse_due("in 2 weeks", now), Some(at("2025-03-26 09:00")));
+        assert_eq!(parse_due("at 9:30 am", now), Some(at("2025-03-13 09:30")));
+        assert_eq!(parse_due("at 17:45", now), Some(at("2025-03-12 17:45")));
+        assert_eq!(parse_due("buy 3 apples", now), None);
+        assert_eq!(parse_due("may the force be with you", now), None);
+    }
+
+    #[test]
+    fn test_extract_reminders() {
+        let now = at("2025-03-12 10:00");
+        let text = "# Notes\n\
+            Remind me to call the dentist tomorrow.\n\
+            - [ ] send invoice\n\
+            - [x] already done\n\
+            TODO(ana): review draft\n\
+            Today was fine.\n\
+            Deadline: tax filing April 15\n\
+            The due process was slow.\n\
+            ```\n# TODO: not a reminder\n```\n\
+            follow up with landlord next week\n";
+        let found = extract_reminders(text, now);
+        let texts: Vec<&str> = found.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "call the dentist tomorrow",
+                "send invoice",
+                "review draft",
+                "Deadline: tax filing April 15",
+                "follow up with landlord next week",
+            ]
+        );
+        assert_eq!(found[0].due_at, Some(at("2025-03-13 09:00")));
+        assert_eq!(found[1].due_at, None);
+        assert_eq!(found[3].due_at, Some(at("2025-04-15 09:00")));
+    }
+
+    #[test]
+    fn test_store_and_notify() {
+        let db = Database::open_in_memory().unwrap();
+        crate::agent::memory::initialize_memory_schema(&db).unwrap();
+        initialize_reminders_schema(&db).unwrap();
+        let doc = db
+            .upsert_document(
+                "/notes/todo.md",
+                "todo.md",
+                Some("md"),
+                10,
+                "h",
+                "2025-03-12",
+            )
+            .unwrap();
+        let written = at("2025-03-12 10:00");
+        scan_document(
+            &db,
+            doc,
+            "TODO: pay rent tomorrow\n- [ ] water plants",
+            written,
+        )
+        .unwrap();
+
+        let now = at("2025-03-12 12:00");
+        let upcoming = list_upcoming(&db, now, 7, false, 10).unwrap();
+        assert_eq!(upcoming.len(), 1);
+        assert_eq!(upcoming[0].source_label.as_deref(), Some("/notes/todo.md"));
+        assert_eq!(list_upcoming(&db, now, 7, true, 10).unwrap().len(), 2);
+
+        // Announced once, when due
+        assert!(take_due(&db, now).unwrap().is_empty());
+        let due = take_due(&db, at("2025-03-13 09:00")).unwrap();
+        assert_eq!(due[0].text, "pay rent tomorrow");
+        assert!(take_due(&db, at("2025-03-13 10:00")).unwrap().is_empty());
+
+        // Re-scanning drops removed lines but keeps completed reminders
+        let plants = list_upcoming(&db, now, 7, true, 10).unwrap()[1].id;
+        set_done(&db, plants, true).unwrap();
+        scan_document(&db, doc, "nothing left", written).unwrap();
+        let all: i64 = db
+            .with_conn(|conn| {
+                Ok(conn.query_row("SELECT COUNT(*) FROM reminders", [], |r| r.get(0))?)
+            })
+            .unwrap();
+        assert_eq!(all, 1);
+    }
+}
@@ -0,0 +1,164 @@
+//! Local resource usage of Ghost itself, for the Debug panel.
+//!
+//! [`sample`] reports the app's own CPU share and resident memory (via
+//! `sysinfo`), the estimated memory of loaded models, the vault size, and the
+//! job queue depth. Nothing leaves the machine. The resource monitor streams
+//! samples as AG-UI `STATE_SNAPSHOT` events on [`RESOURCE_USAGE_EVENT`] so the
+//! panel can chart them over time.
+
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::embeddings::AiBackend;
+use crate::protocols::agui::AgUiEvent;
+use crate::AppState;
+
+/// Tauri event carrying an AG-UI `STATE_SNAPSHOT` whose snapshot is a [`ResourceUsage`].
+pub const RESOURCE_USAGE_EVENT: &str = "resources://usage";
+
+/// Run ID of the monitor's AG-UI events.
+pub const MONITOR_RUN_ID: &str = "resource-monitor";
+
+/// Fastest allowed sampling interval.
+const MIN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// One sample of Ghost's resource usage.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceUsage {
+    /// Share of the whole machine's CPU used by Ghost since the previous
+    /// sample (0-100). The first sample reads 0.
+    pub cpu_percent: f32,
+    /// Resident memory of the Ghost process.
+    pub rss_mb: u64,
+    /// Estimated memory of the loaded chat and embedding models.
+    pub model_memory_mb: u64,
+    /// Vault database plus its write-ahead log.
+    pub db_size_mb: u64,
+    pub queued_jobs: usize,
+    pub running_jobs: usize,
+}
+
+/// Kept between samples: CPU usage is measured from the previous refresh.
+static SYSTEM: LazyLock<Mutex<System>> = LazyLock::new(|| Mutex::new(System::new()));
+
+/// The monitor task, while it runs.
+static MONITOR: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+
+/// CPU share (machine-wide) and resident bytes of this process.
+fn own_process_usage() -> (f32, u64) {
+    let Ok(pid) = sysinfo::get_current_pid() else {
+        return (0.0, 0);
+    };
+    let mut system = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    let cores = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    system.process(pid).map_or((0.0, 0), |process| {
+        (process.cpu_usage() / cores as f32, process.memory())
+    })
+}
+
+/// Estimated resident memory of the loaded models, from their file sizes.
+fn model_memory_mb(state: &AppState) -> u64 {
+    let chat = state.chat_engine.status();
+    let chat_mb: u64 = if chat.ready {
+        state
+            .chat_engine
+            .loaded_model_ids()
+            .iter()
+            .filter_map(|id| crate::chat::models::find_model(id))
+            .map(|model| crate::chat::models::estimated_ram_mb(model.size_mb))
+            .sum()
+    } else {
+        0
+    };
+    // Ollama models live in the Ollama server's memory
+    let embedding_mb = if state.embedding_engine.backend() == AiBackend::Native {
+        state.embedding_engine.active_model().size_mb
+    } else {
+        0
+    };
+    chat_mb + embedding_mb
+}
+
+/// Take one sample. Blocking: reads process stats and file sizes.
+pub fn sample(state: &AppState) -> ResourceUsage {
+    let (cpu_percent, rss_bytes) = own_process_usage();
+    let db_bytes = crate::db::maintenance::size_stats(&state.db)
+        .map(|stats| stats.file_bytes + stats.wal_bytes)
+        .unwrap_or(0);
+    let (queued_jobs, running_jobs) = state.jobs.depth();
+    ResourceUsage {
+        cpu_percent,
+        rss_mb: rss_bytes / 1_048_576,
+        model_memory_mb: model_memory_mb(state),
+        db_size_mb: db_bytes / 1_048_576,
+        queued_jobs,
+        running_jobs,
+    }
+}
+
+/// Start streaming samples every `interval` to `emit` (restarting the monitor
+/// if it already runs).
+pub fn start_monitor(
+    state: Arc<AppState>,
+    interval: Duration,
+    emit: impl Fn(AgUiEvent) + Send + 'static,
+) {
+    let interval = interval.max(MIN_INTERVAL);
+    let task = tauri::async_runtime::spawn(async move {
+        emit(AgUiEvent::run_started(MONITOR_RUN_ID));
+        loop {
+            let task_state = state.clone();
+            let Ok(usage) = tokio::task::spawn_blocking(move || sample(&task_state)).await else {
+                break;
+            };
+            let snapshot = serde_json::to_value(&usage).unwrap_or_default();
+            emit(AgUiEvent::state_snapshot(MONITOR_RUN_ID, snapshot));
+            tokio::time::sleep(interval).await;
+        }
+    });
+    if let Some(previous) = MONITOR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(task)
+    {
+        previous.abort();
+    }
+}
+
+/// Stop the monitor. Returns false if it wasn't running.
+pub fn stop_monitor() -> bool {
+    match MONITOR.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        Some(task) => {
+            task.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_reports_own_process() {
+        let state = crate::test_app_state();
+        let usage = sample(&state);
+        assert!(usage.rss_mb > 0);
+        assert_eq!(usage.model_memory_mb, 0);
+        assert_eq!((usage.queued_jobs, usage.running_jobs), (0, 0));
+        // CPU usage needs a previous refresh to compare against
+        let usage = sample(&state);
+        assert!((0.0..=100.0).contains(&usage.cpu_percent));
+    }
+}
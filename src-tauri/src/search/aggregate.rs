@@ -0,0 +1,409 @@
+//! Collection answers for enumeration questions ("all PDFs about taxes from 2023").
+//!
+//! Instead of a ranked list of chunks, [`collect`] returns every matching
+//! document once, grouped by year, file type, or folder, with the metadata
+//! needed to render a table. The agent formats this with [`format_table`]
+//! rather than listing files from memory.
+//!
+//! A topic query runs hybrid search over a deep candidate list and keeps the
+//! best hit per document; without a topic the documents table is scanned
+//! directly. `extension` and `year` narrow either way, and the query may carry
+//! the usual `from:` / `before:` / `tag:` filters. An active focus context
+//! (see [`super::focus`]) applies to both.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::Result;
+
+/// Search hits fetched for a topic query before grouping.
+const TOPIC_CANDIDATES: usize = 300;
+
+/// Most documents returned in one collection.
+pub const MAX_COLLECTION_SIZE: usize = 500;
+
+/// How documents are grouped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    Year,
+    Type,
+    Folder,
+}
+
+impl GroupBy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "year" | "date" => Some(Self::Year),
+            "type" | "extension" | "filetype" => Some(Self::Type),
+            "folder" | "directory" => Some(Self::Folder),
+            _ => None,
+        }
+    }
+}
+
+/// One document in a collection.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionItem {
+    pub document_id: i64,
+    pub path: String,
+    pub filename: String,
+    pub extension: Option<String>,
+    pub size_bytes: i64,
+    pub modified_at: String,
+    /// Best search score for the topic (none without a topic).
+    pub score: Option<f64>,
+}
+
+impl CollectionItem {
+    fn year(&self) -> &str {
+        self.modified_at.get(..4).unwrap_or("unknown")
+    }
+
+    fn folder(&self) -> String {
+        std::path::Path::new(&self.path)
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| "/".into())
+    }
+
+    fn group_key(&self, group_by: GroupBy) -> String {
+        match group_by {
+            GroupBy::Year => self.year().to_string(),
+            GroupBy::Type => self
+                .extension
+                .as_deref()
+                .map(str::to_lowercase)
+                .unwrap_or_else(|| "other".into()),
+            GroupBy::Folder => self.folder(),
+        }
+    }
+}
+
+/// Documents sharing a group key.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionGroup {
+    pub key: String,
+    pub count: usize,
+    pub items: Vec<CollectionItem>,
+}
+
+/// A grouped collection of documents.
+#[derive(Debug, Clone, Serialize)]
+pub struct Collection {
+    pub query: String,
+    pub group_by: GroupBy,
+    /// Documents across all groups.
+    pub total: usize,
+    pub groups: Vec<CollectionGroup>,
+    /// Whether more documents matched than were returned.
+    pub truncated: bool,
+}
+
+/// What to collect.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CollectionRequest {
+    /// Topic to search for; empty lists every document matching the filters.
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub group_by: GroupBy,
+    /// File extension without the dot (e.g. "pdf").
+    #[serde(default)]
+    pub extension: Option<String>,
+    /// Year the file was last modified.
+    #[serde(default)]
+    pub year: Option<i32>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Build a grouped collection for an enumeration-style question.
+pub async fn collect(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    request: &CollectionRequest,
+) -> Result<Collection> {
+    let limit = request.limit.unwrap_or(100).clamp(1, MAX_COLLECTION_SIZE);
+    let extension = request
+        .extension
+        .as_deref()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .filter(|e| !e.is_empty());
+    let year = request.year.map(|y| y.to_string());
+    let query = request.query.trim();
+
+    let mut items = if query.is_empty() {
+        let focused = match super::focus::active() {
+            Some(focus) => Some(super::focus::matching_documents(db, &focus)?),
+            None => None,
+        };
+        list_documents(
+            db,
+            extension.as_deref(),
+            year.as_deref(),
+            focused.as_ref(),
+            limit + 1,
+        )?
+    } else {
+        let results = super::hybrid_search_filtered(
+            db,
+            embedding_engine,
+            query,
+            TOPIC_CANDIDATES,
+            extension.as_deref(),
+        )
+        .await?;
+        // Best hit per document, in relevance order
+        let mut scores: Vec<(i64, f64)> = Vec::new();
+        for r in &results {
+            if !scores.iter().any(|(id, _)| *id == r.document_id) {
+                scores.push((r.document_id, r.score));
+            }
+        }
+        let mut metadata = document_metadata(db, &scores)?;
+        scores
+            .iter()
+            .filter_map(|(id, _)| metadata.remove(id))
+            .filter(|item| {
+                extension.is_none() || item.extension.as_deref().map(str::to_lowercase) == extension
+            })
+            .filter(|item| year.is_none() || Some(item.year()) == year.as_deref())
+            .collect()
+    };
+
+    let truncated = items.len() > limit;
+    items.truncate(limit);
+    Ok(Collection {
+        query: query.to_string(),
+        group_by: request.group_by,
+        total: items.len(),
+        groups: group_items(items, request.group_by),
+        truncated,
+    })
+}
+
+/// Documents matching the extension and year (and in `allowed`, when
+/// given), newest first.
+fn list_documents(
+    db: &Database,
+    extension: Option<&str>,
+    year: Option<&str>,
+    allowed: Option<&HashSet<i64>>,
+    limit: usize,
+) -> Result<Vec<CollectionItem>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, path, filename, extension, size_bytes, modified_at FROM documents
+             WHERE (?1 IS NULL OR LOWER(extension) = ?1)
+               AND (?2 IS NULL OR substr(modified_at, 1, 4) = ?2)
+             ORDER BY modified_at DESC",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![extension, year], |row| {
+            item_from_row(row, None)
+        })?;
+        let mut items = Vec::new();
+        for item in rows {
+            let item = item?;
+            if allowed.is_none() || allowed.is_some_and(|a| a.contains(&item.document_id)) {
+                items.push(item);
+                if items.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(items)
+    })
+}
+
+/// Metadata for the scored documents, keyed by ID.
+fn document_metadata(db: &Database, scores: &[(i64, f64)]) -> Result<HashMap<i64, CollectionItem>> {
+    if scores.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let score_of: HashMap<i64, f64> = scores.iter().copied().collect();
+    let placeholders = vec!["?"; scores.len()].join(",");
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, path, filename, extension, size_bytes, modified_at FROM documents
+             WHERE id IN ({})",
+            placeholders
+        ))?;
+        let rows = stmt.query_map(
+            rusqlite::params_from_iter(scores.iter().map(|(id, _)| id)),
+            |row| {
+                let id: i64 = row.get(0)?;
+                item_from_row(row, score_of.get(&id).copied())
+            },
+        )?;
+        let mut items = HashMap::new();
+        for item in rows {
+            let item = item?;
+            items.insert(item.document_id, item);
+        }
+        Ok(items)
+    })
+}
+
+fn item_from_row(row: &rusqlite::Row<'_>, score: Option<f64>) -> rusqlite::Result<CollectionItem> {
+    Ok(CollectionItem {
+        document_id: row.get(0)?,
+        path: row.get(1)?,
+        filename: row.get(2)?,
+        extension: row.get(3)?,
+        size_bytes: row.get(4)?,
+        modified_at: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+        score,
+    })
+}
+
+/// Group items, keeping their order within each group. Years sort newest
+/// first; types and folders by size, then name.
+pub fn group_items(items: Vec<CollectionItem>, group_by: GroupBy) -> Vec<CollectionGroup> {
+    let mut grouped: BTreeMap<String, Vec<CollectionItem>> = BTreeMap::new();
+    for item in items {
+        grouped
+            .entry(item.group_key(group_by))
+            .or_default()
+            .push(item);
+    }
+    let mut groups: Vec<CollectionGroup> = grouped
+        .into_iter()
+        .map(|(key, items)| CollectionGroup {
+            key,
+            count: items.len(),
+            items,
+        })
+        .collect();
+    match group_by {
+        GroupBy::Year => groups.reverse(),
+        GroupBy::Type | GroupBy::Folder => groups.sort_by_key(|g| std::cmp::Reverse(g.count)),
+    }
+    groups
+}
+
+/// Render a collection as Markdown tables, one per group, for the agent.
+pub fn format_table(collection: &Collection) -> String {
+    if collection.total == 0 {
+        return "No matching documents.".to_string();
+    }
+    let mut out = format!(
+        "{} document(s) in {} group(s){}.\n",
+        collection.total,
+        collection.groups.len(),
+        if collection.truncated {
+            " (more matched — narrow the question or raise the limit)"
+        } else {
+            ""
+        }
+    );
+    for group in &collection.groups {
+        out.push_str(&format!(
+            "\n### {} ({})\n\n| File | Type | Modified | Size | Path |\n|---|---|---|---|---|\n",
+            group.key, group.count
+        ));
+        for item in &group.items {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                escape_cell(&item.filename),
+                item.extension.as_deref().unwrap_or("-"),
+                item.modified_at.get(..10).unwrap_or(&item.modified_at),
+                format_size(item.size_bytes),
+                escape_cell(&item.path)
+            ));
+        }
+    }
+    out
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn format_size(bytes: i64) -> String {
+    match bytes {
+        b if b >= 1 << 20 => format!("{:.1} MB", b as f64 / (1 << 20) as f64),
+        b if b >= 1 << 10 => format!("{} KB", b >> 10),
+        b => format!("{} B", b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i64, path: &str, ext: &str, modified_at: &str) -> CollectionItem {
+        CollectionItem {
+            document_id: id,
+            path: path.into(),
+            filename: path.rsplit('/').next().unwrap().into(),
+            extension: Some(ext.into()),
+            size_bytes: 2048,
+            modified_at: modified_at.into(),
+            score: None,
+        }
+    }
+
+    fn sample() -> Vec<CollectionItem> {
+        vec![
+            item(1, "/docs/taxes/w2.pdf", "pdf", "2023-02-01T00:00:00Z"),
+            item(2, "/docs/taxes/1099.pdf", "PDF", "2024-03-01T00:00:00Z"),
+            item(3, "/notes/taxes.md", "md", "2023-04-01T00:00:00Z"),
+        ]
+    }
+
+    #[test]
+    fn test_group_items() {
+        let groups = group_items(sample(), GroupBy::Year);
+        let keys: Vec<&str> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["2024", "2023"]);
+        let ids: Vec<i64> = groups[1].items.iter().map(|i| i.document_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        let groups = group_items(sample(), GroupBy::Type);
+        assert_eq!((groups[0].key.as_str(), groups[0].count), ("pdf", 2));
+
+        let groups = group_items(sample(), GroupBy::Folder);
+        assert_eq!(groups[0].key, "/docs/taxes");
+        assert_eq!(groups[1].key, "/notes");
+        assert_eq!(GroupBy::parse(" Extension "), Some(GroupBy::Type));
+        assert_eq!(GroupBy::parse("author"), None);
+    }
+
+    #[test]
+    fn test_format_table_and_listing() {
+        let db = Database::open_in_memory().unwrap();
+        for i in sample() {
+            db.upsert_document(
+                &i.path,
+                &i.filename,
+                i.extension.as_deref(),
+                i.size_bytes,
+                "h",
+                &i.modified_at,
+            )
+            .unwrap();
+        }
+        let items = list_documents(&db, Some("pdf"), Some("2023"), None, 10).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].filename, "w2.pdf");
+
+        let items = list_documents(&db, None, None, None, 10).unwrap();
+        let collection = Collection {
+            query: String::new(),
+            group_by: GroupBy::Year,
+            total: items.len(),
+            groups: group_items(items, GroupBy::Year),
+            truncated: false,
+        };
+        let table = format_table(&collection);
+        assert!(table.starts_with("3 document(s) in 2 group(s)."));
+        assert!(table.contains("### 2023 (2)"));
+        assert!(table.contains("| w2.pdf | pdf | 2023-02-01 | 2 KB | /docs/taxes/w2.pdf |"));
+    }
+}
@@ -0,0 +1,320 @@
+//! Retrieval confidence for RAG answers.
+//!
+//! RRF scores only say how results rank against each other, not whether any
+//! of them actually answers the query. Confidence combines two signals:
+//! - Agreement: how highly the top hit ranks in keyword and vector search
+//!   (a chunk both systems put first is a strong match)
+//! - Coverage: share of the query's key terms found in the top results
+//!
+//! Low confidence tells the agent to say the vault doesn't have the answer,
+//! and to offer a web search, instead of stretching weak matches into one.
+
+use serde::Serialize;
+
+use super::ranking::RRF_K;
+use super::SearchResult;
+
+/// Confidence at or above this is `High`.
+const HIGH_CONFIDENCE: f64 = 0.65;
+
+/// Confidence below this is `Low`.
+const LOW_CONFIDENCE: f64 = 0.35;
+
+/// Number of top results checked for term coverage.
+const COVERAGE_DEPTH: usize = 3;
+
+/// Words ignored when measuring coverage.
+const STOPWORDS: &[&str] = &[
+    "the",
+    "and",
+    "for",
+    "are",
+    "was",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "who",
+    "why",
+    "how",
+    "does",
+    "did",
+    "about",
+    "with",
+    "from",
+    "that",
+    "this",
+    "these",
+    "those",
+    "have",
+    "has",
+    "had",
+    "can",
+    "could",
+    "should",
+    "would",
+    "will",
+    "into",
+    "your",
+    "you",
+    "find",
+    "show",
+    "tell",
+    "give",
+    "any",
+    "all",
+    "some",
+    "there",
+    "their",
+    "them",
+    "they",
+    "its",
+    "not",
+    "but",
+    "our",
+    "out",
+    "file",
+    "files",
+    "document",
+    "documents",
+    "note",
+    "notes",
+];
+
+/// Coarse confidence bucket the agent acts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfidenceLevel {
+    High,
+    Medium,
+    Low,
+    /// Nothing was retrieved at all.
+    None,
+}
+
+/// How well the retrieved chunks match a query.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievalConfidence {
+    pub level: ConfidenceLevel,
+    /// Combined confidence in [0, 1].
+    pub score: f64,
+    /// Top hit's RRF score relative to the best possible (first in both systems).
+    pub agreement: f64,
+    /// Share of key query terms found in the top results.
+    pub coverage: f64,
+    /// Key query terms no top result contains.
+    pub missing_terms: Vec<String>,
+}
+
+impl RetrievalConfidence {
+    /// Whether the agent should treat the vault as not having the answer.
+    pub fn is_insufficient(&self) -> bool {
+        matches!(self.level, ConfidenceLevel::Low | ConfidenceLevel::None)
+    }
+}
+
+/// Lowercased content words of a query.
+fn key_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if word.chars().count() < 3 || STOPWORDS.contains(&word.as_str()) {
+            continue;
+        }
+        if !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Match plural and singular forms: "invoices" finds "invoice".
+fn term_stem(term: &str) -> &str {
+    if term.len() > 4 {
+        term.strip_suffix('s').unwrap_or(term)
+    } else {
+        term
+    }
+}
+
+/// Assess how well `results` (best first) answer `query`.
+pub fn assess(query: &str, results: &[SearchResult]) -> RetrievalConfidence {
+    let Some(top) = results.first() else {
+        return RetrievalConfidence {
+            level: ConfidenceLevel::None,
+            score: 0.0,
+            agreement: 0.0,
+            coverage: 0.0,
+            missing_terms: key_terms(query),
+        };
+    };
+
+    let best_possible = 2.0 / (RRF_K + 1.0);
+    let agreement = (top.score / best_possible).clamp(0.0, 1.0);
+
+    let top_results = &results[..results.len().min(COVERAGE_DEPTH)];
+    let terms = key_terms(query);
+    // FTS5 matches every query word, so a keyword hit covers the whole query
+    // even when the snippet is truncated before the matching words.
+    let keyword_hit = top_results.iter().any(|r| r.source != "vector");
+    let missing_terms: Vec<String> = if keyword_hit {
+        Vec::new()
+    } else {
+        let haystack = top_results
+            .iter()
+            .map(|r| format!("{} {}", r.path, r.snippet).to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+        terms
+            .iter()
+            .filter(|t| !haystack.contains(term_stem(t)))
+            .cloned()
+            .collect()
+    };
+    let coverage = if terms.is_empty() {
+        agreement
+    } else {
+        (terms.len() - missing_terms.len()) as f64 / terms.len() as f64
+    };
+
+    let score = (agreement + coverage) / 2.0;
+    let level = if score >= HIGH_CONFIDENCE {
+        ConfidenceLevel::High
+    } else if score >= LOW_CONFIDENCE {
+        ConfidenceLevel::Medium
+    } else {
+        ConfidenceLevel::Low
+    };
+
+    RetrievalConfidence {
+        level,
+        score,
+        agreement,
+        coverage,
+        missing_terms,
+    }
+}
+
+/// Whether an MCP tool looks like a web search the agent can fall back to.
+pub fn is_web_search_tool(name: &str, description: Option<&str>) -> bool {
+    let name = name.to_lowercase();
+    let description = description.unwrap_or("").to_lowercase();
+    let searches = name.contains("search") || description.contains("search");
+    let web = [
+        "web",
+        "internet",
+        "brave",
+        "google",
+        "bing",
+        "duckduckgo",
+        "tavily",
+    ]
+    .iter()
+    .any(|w| name.contains(w) || description.contains(w));
+    searches && web
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(score: f64, source: &str, snippet: &str) -> SearchResult {
+        SearchResult {
+            chunk_id: 1,
+            document_id: 1,
+            path: "/home/user/notes/misc.md".into(),
+            filename: "misc.md".into(),
+            extension: Some("md".into()),
+            snippet: snippet.into(),
+            chunk_index: 0,
+            score,
+            source: source.into(),
+            section: None,
+            symbol: None,
+            language: None,
+            summary: None,
+            source_description: String::new(),
+            position: 1,
+            total: 1,
+        }
+    }
+
+    #[test]
+    fn test_key_terms() {
+        assert_eq!(
+            key_terms("What did the Lease say about pets? pets!"),
+            vec!["lease", "say", "pets"]
+        );
+        assert!(key_terms("is it on?").is_empty());
+    }
+
+    #[test]
+    fn test_no_results() {
+        let c = assess("passport number", &[]);
+        assert_eq!(c.level, ConfidenceLevel::None);
+        assert!(c.is_insufficient());
+        assert_eq!(c.missing_terms, vec!["passport", "number"]);
+    }
+
+    #[test]
+    fn test_strong_hybrid_match_is_high() {
+        let top = 2.0 / (RRF_K + 1.0);
+        let c = assess(
+            "lease pets",
+            &[result(top, "hybrid", "The lease allows two pets.")],
+        );
+        assert_eq!(c.level, ConfidenceLevel::High);
+        assert!(!c.is_insufficient());
+        assert!(c.missing_terms.is_empty());
+    }
+
+    #[test]
+    fn test_unrelated_vector_match_is_low() {
+        // Vector search always returns *something*; a lone vector hit that
+        // shares no terms with the query is not an answer.
+        let c = assess(
+            "passport renewal date",
+            &[result(
+                1.0 / (RRF_K + 1.0),
+                "vector",
+                "Grocery list: eggs, milk",
+            )],
+        );
+        assert_eq!(c.level, ConfidenceLevel::Low);
+        assert!(c.is_insufficient());
+        assert_eq!(c.missing_terms.len(), 3);
+    }
+
+    #[test]
+    fn test_partial_vector_coverage_is_medium() {
+        let c = assess(
+            "invoices acme",
+            &[result(
+                1.0 / (RRF_K + 1.0),
+                "vector",
+                "Invoice #42 for services",
+            )],
+        );
+        assert_eq!(c.coverage, 0.5);
+        assert_eq!(c.level, ConfidenceLevel::Medium);
+        assert_eq!(c.missing_terms, vec!["acme"]);
+    }
+
+    #[test]
+    fn test_is_web_search_tool() {
+        assert!(is_web_search_tool("brave_web_search", None));
+        assert!(is_web_search_tool(
+            "search",
+            Some("Search the web with DuckDuckGo")
+        ));
+        assert!(!is_web_search_tool(
+            "search_files",
+            Some("Search local files")
+        ));
+        assert!(!is_web_search_tool(
+            "fetch",
+            Some("Fetch a URL from the web")
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Usage signals for ranking: which documents the user opened from results.
+//!
+//! Opens are kept in the `result_opens` table and, together with each
+//! document's modification time, feed [`super::ranking::document_boost`].
+//! Only the last [`OPENS_WINDOW_DAYS`] days count, so old habits fade.
+//! Opens are recorded by the `open_search_result` and
+//! `reveal_in_file_manager` commands.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Opens older than this no longer boost a document.
+pub const OPENS_WINDOW_DAYS: u32 = 180;
+
+/// Ranking signals for one document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSignals {
+    /// Days since the file was last modified.
+    pub age_days: Option<f64>,
+    /// Times opened from search results within the window.
+    pub opens: i64,
+}
+
+/// A search result the user opened.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedResult {
+    pub document_id: i64,
+    pub path: String,
+    /// 1-based line where the matching chunk starts, for text files.
+    pub line: Option<usize>,
+}
+
+/// 1-based line where `chunk` starts in `text`, found by the chunk's first
+/// non-blank line.
+pub fn locate_line(text: &str, chunk: &str) -> Option<usize> {
+    let first = chunk.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let needle: String = first.chars().take(80).collect();
+    let offset = text.find(&needle)?;
+    Some(text[..offset].matches('\n').count() + 1)
+}
+
+/// Record that the user opened a document from search results.
+pub fn record_open(
+    db: &Database,
+    document_id: i64,
+    chunk_id: Option<i64>,
+    query: Option<&str>,
+) -> Result<()> {
+    let query = query.map(str::trim).filter(|q| !q.is_empty());
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO result_opens (document_id, chunk_id, query) VALUES (?1, ?2, ?3)",
+            rusqlite::params![document_id, chunk_id, query],
+        )?;
+        Ok(())
+    })
+}
+
+/// Days between `modified_at` (RFC 3339 or `YYYY-MM-DD...`) and now.
+fn age_days(modified_at: &str, now: chrono::DateTime<chrono::Utc>) -> Option<f64> {
+    let modified = chrono::DateTime::parse_from_rfc3339(modified_at)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(modified_at.get(..10)?, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+                .map(|t| t.and_utc())
+        })?;
+    Some((now - modified).num_seconds() as f64 / 86_400.0)
+}
+
+/// Recency and open counts for the given documents.
+pub fn document_signals(
+    db: &Database,
+    document_ids: &[i64],
+) -> Result<HashMap<i64, DocumentSignals>> {
+    let mut ids = document_ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let placeholders = vec!["?"; ids.len()].join(",");
+    let now = chrono::Utc::now();
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT d.id, d.modified_at,
+                    (SELECT COUNT(*) FROM result_opens o
+                     WHERE o.document_id = d.id AND o.opened_at >= datetime('now', '-{} days'))
+             FROM documents d WHERE d.id IN ({})",
+            OPENS_WINDOW_DAYS, placeholders
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(ids.iter()), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+        let mut signals = HashMap::new();
+        for row in rows {
+            let (id, modified_at, opens) = row?;
+            signals.insert(
+                id,
+                DocumentSignals {
+                    age_days: age_days(&modified_at, now),
+                    opens,
+                },
+            );
+        }
+        Ok(signals)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_line() {
+        let text = "# Notes\n\nGroceries\n  milk and eggs\nbread\n";
+        assert_eq!(locate_line(text, "\n milk and eggs\nbread"), Some(4));
+        assert_eq!(locate_line(text, "# Notes"), Some(1));
+        assert_eq!(locate_line(text, "not in the file"), None);
+        assert_eq!(locate_line(text, "  \n"), None);
+    }
+
+    #[test]
+    fn test_document_signals() {
+        let db = Database::open_in_memory().unwrap();
+        let today = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let fresh = db
+            .upsert_document("/docs/a.md", "a.md", Some("md"), 1, "a", &today)
+            .unwrap();
+        let old = db
+            .upsert_document("/docs/b.md", "b.md", Some("md"), 1, "b", "2020-01-01")
+            .unwrap();
+        record_open(&db, old, None, Some("budget")).unwrap();
+        record_open(&db, old, None, None).unwrap();
+
+        let signals = document_signals(&db, &[fresh, old, old]).unwrap();
+        assert_eq!(signals.len(), 2);
+        assert!(signals[&fresh].age_days.unwrap() < 1.0);
+        assert_eq!(signals[&fresh].opens, 0);
+        assert!(signals[&old].age_days.unwrap() > 365.0);
+        assert_eq!(signals[&old].opens, 2);
+        assert!(document_signals(&db, &[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Filename instant search for the quick launcher.
+//!
+//! A trigram FTS5 index over document filenames answers "contains" queries in
+//! a few milliseconds without touching chunks or embeddings, so the launcher
+//! can show files while the full hybrid search is still running. Queries
+//! shorter than a trigram fall back to a case-insensitive prefix scan.
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Results returned when the caller doesn't pass a limit.
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// Rows fetched from the index before ranking.
+const CANDIDATE_LIMIT: i64 = 500;
+
+/// Shortest term the trigram index can match.
+const TRIGRAM_LEN: usize = 3;
+
+/// A document whose filename matched.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilenameMatch {
+    pub document_id: i64,
+    pub path: String,
+    pub filename: String,
+    pub extension: Option<String>,
+    pub modified_at: String,
+}
+
+/// Initialize the filename index and keep it in sync with `documents`.
+/// The index is filled from existing documents the first time it is created.
+pub fn initialize_filename_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'filenames_fts'",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute_batch(
+            "
+            CREATE VIRTUAL TABLE IF NOT EXISTS filenames_fts USING fts5(
+                filename,
+                content=documents,
+                content_rowid=id,
+                tokenize='trigram'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS documents_name_ai AFTER INSERT ON documents BEGIN
+                INSERT INTO filenames_fts(rowid, filename) VALUES (new.id, new.filename);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS documents_name_ad AFTER DELETE ON documents BEGIN
+                INSERT INTO filenames_fts(filenames_fts, rowid, filename)
+                    VALUES('delete', old.id, old.filename);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS documents_name_au AFTER UPDATE OF filename ON documents BEGIN
+                INSERT INTO filenames_fts(filenames_fts, rowid, filename)
+                    VALUES('delete', old.id, old.filename);
+                INSERT INTO filenames_fts(rowid, filename) VALUES (new.id, new.filename);
+            END;
+
+            CREATE INDEX IF NOT EXISTS idx_documents_filename
+                ON documents(filename COLLATE NOCASE);
+            ",
+        )?;
+        if !exists {
+            conn.execute_batch("INSERT INTO filenames_fts(filenames_fts) VALUES('rebuild');")?;
+        }
+        Ok(())
+    })
+}
+
+/// Documents whose filename contains every word of `query` (case-insensitive).
+///
+/// Ranked exact name first, then prefix, word-start and substring matches;
+/// ties go to shorter names, then to recently modified files.
+pub fn search_filenames(db: &Database, query: &str, limit: usize) -> Result<Vec<FilenameMatch>> {
+    let query = query.trim().to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let trigram_terms: Vec<String> = words
+        .iter()
+        .filter(|w| w.chars().count() >= TRIGRAM_LEN)
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect();
+    let candidates = db.with_read_conn(|conn| {
+        let (sql, param) = if trigram_terms.is_empty() {
+            (
+                "SELECT id, path, filename, extension, modified_at FROM documents
+                 WHERE filename LIKE ?1 ESCAPE '\\'
+                 ORDER BY length(filename) LIMIT ?2",
+                format!("{}%", escape_like(words[0])),
+            )
+        } else {
+            (
+                "SELECT d.id, d.path, d.filename, d.extension, d.modified_at
+                 FROM filenames_fts f JOIN documents d ON d.id = f.rowid
+                 WHERE filenames_fts MATCH ?1
+                 ORDER BY length(d.filename) LIMIT ?2",
+                trigram_terms.join(" "),
+            )
+        };
+        let mut stmt = conn.prepare_cached(sql)?;
+        let rows = stmt.query_map(rusqlite::params![param, CANDIDATE_LIMIT], |row| {
+            Ok(FilenameMatch {
+                document_id: row.get(0)?,
+                path: row.get(1)?,
+                filename: row.get(2)?,
+                extension: row.get(3)?,
+                modified_at: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })?;
+
+    // Short words aren't in the trigram query; check every word here
+    let mut ranked: Vec<(u8, FilenameMatch)> = candidates
+        .into_iter()
+        .filter_map(|m| {
+            let name = m.filename.to_lowercase();
+            words
+                .iter()
+                .all(|w| name.contains(w))
+                .then(|| (match_rank(&name, &query), m))
+        })
+        .collect();
+    ranked.sort_by(|(rank_a, a), (rank_b, b)| {
+        rank_a
+            .cmp(rank_b)
+            .then(a.filename.len().cmp(&b.filename.len()))
+            .then(b.modified_at.cmp(&a.modified_at))
+    });
+    Ok(ranked.into_iter().take(limit).map(|(_, m)| m).collect())
+}
+
+/// How well a lowercase filename matches a lowercase query (lower is better).
+fn match_rank(name: &str, query: &str) -> u8 {
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    if name == query || stem == query {
+        0
+    } else if name.starts_with(query) {
+        1
+    } else if name.match_indices(query).any(|(i, _)| {
+        name[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_alphanumeric())
+    }) {
+        2
+    } else if name.contains(query) {
+        3
+    } else {
+        // Every word matched, but not as one phrase
+        4
+    }
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(names: &[&str]) -> Database {
+        let db = Database::open_in_memory().unwrap();
+        initialize_filename_schema(&db).unwrap();
+        for name in names {
+            db.upsert_document(
+                &format!("/vault/{}", name),
+                name,
+                name.rsplit_once('.').map(|(_, ext)| ext),
+                1,
+                "h",
+                "2026-01-01T00:00:00Z",
+            )
+            .unwrap();
+        }
+        db
+    }
+
+    fn names(matches: &[FilenameMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.filename.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_filenames_ranks_exact_and_prefix_first() {
+        let db = vault(&[
+            "old-budget-2024.xlsx",
+            "budget.xlsx",
+            "Budget 2024 draft.docx",
+            "mybudgets.txt",
+            "notes.md",
+        ]);
+        let results = search_filenames(&db, "budget", 10).unwrap();
+        assert_eq!(
+            names(&results),
+            [
+                "budget.xlsx",
+                "Budget 2024 draft.docx",
+                "old-budget-2024.xlsx",
+                "mybudgets.txt"
+            ]
+        );
+        let results = search_filenames(&db, "2024 budget", 10).unwrap();
+        assert_eq!(
+            names(&results),
+            ["old-budget-2024.xlsx", "Budget 2024 draft.docx"]
+        );
+    }
+
+    #[test]
+    fn test_search_filenames_short_queries_use_prefix() {
+        let db = vault(&["notes.md", "Node guide.pdf", "annotations.txt"]);
+        let results = search_filenames(&db, "no", 10).unwrap();
+        assert_eq!(names(&results), ["notes.md", "Node guide.pdf"]);
+        assert!(search_filenames(&db, "  ", 10).unwrap().is_empty());
+        assert!(search_filenames(&db, "%", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_filename_index_follows_documents() {
+        let db = vault(&["report.pdf"]);
+        let id = search_filenames(&db, "report", 10).unwrap()[0].document_id;
+        db.delete_document(id).unwrap();
+        assert!(search_filenames(&db, "report", 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+//! Query filters for email metadata, event dates, tags, and note links.
+//!
+//! Search queries may carry `from:` and `before:` tokens, e.g.
+//! `invoice from:alice before:2024-03`. They are stripped from the text sent
+//! to FTS5 and the embedding model, and restrict results to email documents
+//! (`.eml`/`.mbox`) whose sender contains the `from:` value and whose send
+//! date falls before the `before:` date.
+//!
+//! `on:` tokens (`on:2025-03-12`, `on:2025-03`) restrict results to calendar
+//! files (`.ics`) with an event during that day, month or year.
+//!
+//! `tag:` tokens (`tag:taxes`, `tag:"tax returns"`) restrict results to
+//! documents carrying every named tag.
+//!
+//! `links:` tokens (`links:"Project Alpha"`) restrict results to notes in
+//! imported vaults that link to every named note (see
+//! [`crate::indexer::importers::notes`]).
+
+/// Filters parsed out of a search query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryFilters {
+    /// Substring of the sender (`from:alice`, `from:"Alice Smith"`).
+    pub from: Option<String>,
+    /// ISO date prefix the send date must precede (`before:2024-03-05`).
+    pub before: Option<String>,
+    /// ISO date prefix a calendar event must overlap (`on:2025-03-12`).
+    pub on: Option<String>,
+    /// Normalized tags the document must all carry (`tag:taxes`).
+    pub tags: Vec<String>,
+    /// Normalized names of notes the document must all link to (`links:alpha`).
+    pub links: Vec<String>,
+}
+
+impl QueryFilters {
+    pub fn is_empty(&self) -> bool {
+        !self.has_email_filters()
+            && self.on.is_none()
+            && self.tags.is_empty()
+            && self.links.is_empty()
+    }
+
+    /// Whether any filter applies to email metadata.
+    pub fn has_email_filters(&self) -> bool {
+        self.from.is_some() || self.before.is_some()
+    }
+}
+
+/// The value after a `key:` prefix. Quoted values may span several tokens.
+fn filter_value<'a>(first: &str, tokens: &mut impl Iterator<Item = &'a str>) -> String {
+    let mut value = first.to_string();
+    if value.starts_with('"') && !(value.len() > 1 && value.ends_with('"')) {
+        for next in tokens.by_ref() {
+            value.push(' ');
+            value.push_str(next);
+            if next.ends_with('"') {
+                break;
+            }
+        }
+    }
+    value.trim_matches('"').trim().to_string()
+}
+
+/// Split a query into its search text and filters.
+///
+/// Tokens that look like filters but have an invalid value (`before:soon`)
+/// are kept as search text.
+pub fn parse_query(query: &str) -> (String, QueryFilters) {
+    let mut filters = QueryFilters::default();
+    let mut text: Vec<&str> = Vec::new();
+    let mut tokens = query.split_whitespace();
+
+    while let Some(token) = tokens.next() {
+        let lower = token.to_lowercase();
+        if lower.starts_with("from:") && token.len() > 5 {
+            let value = filter_value(&token[5..], &mut tokens);
+            if !value.is_empty() {
+                filters.from = Some(value);
+                continue;
+            }
+        } else if lower.starts_with("tag:") && token.len() > 4 {
+            if let Some(tag) = crate::db::normalize_tag(&filter_value(&token[4..], &mut tokens)) {
+                if !filters.tags.contains(&tag) {
+                    filters.tags.push(tag);
+                }
+                continue;
+            }
+        } else if lower.starts_with("links:") && token.len() > 6 {
+            let value = filter_value(&token[6..], &mut tokens);
+            // Accept the wikilink form too: `links:[[Alpha]]`
+            let value = value.trim_start_matches("[[").trim_end_matches("]]");
+            let name = crate::indexer::importers::notes::normalize_name(value);
+            if !name.is_empty() {
+                if !filters.links.contains(&name) {
+                    filters.links.push(name);
+                }
+                continue;
+            }
+        } else if lower.starts_with("before:") {
+            if let Some(date) = parse_date_prefix(&token[7..]) {
+                filters.before = Some(date);
+                continue;
+            }
+        } else if lower.starts_with("on:") {
+            if let Some(date) = parse_date_prefix(&token[3..]) {
+                filters.on = Some(date);
+                continue;
+            }
+        }
+        text.push(token);
+    }
+
+    (text.join(" "), filters)
+}
+
+/// Accept `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`.
+fn parse_date_prefix(value: &str) -> Option<String> {
+    let parts: Vec<&str> = value.split('-').collect();
+    let valid = match parts.as_slice() {
+        [year] => year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()),
+        [year, month] => {
+            chrono::NaiveDate::parse_from_str(&format!("{}-{}-01", year, month), "%Y-%m-%d").is_ok()
+                && year.len() == 4
+                && month.len() == 2
+        }
+        [_, _, _] => {
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() && value.len() == 10
+        }
+        _ => false,
+    };
+    valid.then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_filters() {
+        let (text, filters) = parse_query("invoice FROM:alice before:2024-03 total");
+        assert_eq!(text, "invoice total");
+        assert_eq!(filters.from.as_deref(), Some("alice"));
+        assert_eq!(filters.before.as_deref(), Some("2024-03"));
+
+        let (text, filters) = parse_query(r#"from:"Alice Smith" lunch"#);
+        assert_eq!(text, "lunch");
+        assert_eq!(filters.from.as_deref(), Some("Alice Smith"));
+
+        let (text, filters) = parse_query("plain query");
+        assert_eq!(text, "plain query");
+        assert!(filters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tag_filters() {
+        let (text, filters) = parse_query(r#"receipt TAG:Taxes tag:"Tax  Returns" tag:taxes"#);
+        assert_eq!(text, "receipt");
+        assert_eq!(filters.tags, vec!["taxes", "tax returns"]);
+        assert!(!filters.has_email_filters());
+        assert!(!filters.is_empty());
+
+        // Empty tags stay in the search text
+        let (text, filters) = parse_query("tag: tag:\"\"");
+        assert_eq!(text, "tag: tag:\"\"");
+        assert!(filters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_on_filter() {
+        let (text, filters) = parse_query("meeting with Dana on:2025-03-12");
+        assert_eq!(text, "meeting with Dana");
+        assert_eq!(filters.on.as_deref(), Some("2025-03-12"));
+        assert!(!filters.is_empty());
+
+        // Not a date: kept as search text
+        let (text, filters) = parse_query("on:call schedule");
+        assert_eq!(text, "on:call schedule");
+        assert!(filters.on.is_none());
+    }
+
+    #[test]
+    fn test_parse_link_filters() {
+        let (text, filters) =
+            parse_query(r#"budget links:"Project Alpha" LINKS:"Jan 5th, 2025" links:[[Ana]]"#);
+        assert_eq!(text, "budget");
+        assert_eq!(filters.links, vec!["project alpha", "2025-01-05", "ana"]);
+        assert!(!filters.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_filters_stay_in_text() {
+        let (text, filters) = parse_query("before:soon from: before:2024-13-01");
+        assert_eq!(text, "before:soon from: before:2024-13-01");
+        assert!(filters.is_empty());
+
+        assert_eq!(parse_date_prefix("2024").as_deref(), Some("2024"));
+        assert_eq!(parse_date_prefix("2024-02-30"), None);
+        assert_eq!(parse_date_prefix("24-01"), None);
+    }
+}
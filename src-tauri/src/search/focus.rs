@@ -0,0 +1,365 @@
+//! Focus context — scope all retrieval to selected sources for a while.
+//!
+//! "Only look at my thesis folder this session": `set_focus_context` restricts
+//! every search (the search bar, the agent's tools, collections) to documents
+//! under the given folders or carrying the given tags, optionally within a
+//! modification date range. The focus lives in memory only: it ends when it
+//! expires, when it is cleared, or when the app restarts.
+
+use std::collections::HashSet;
+use std::sync::{LazyLock, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+
+/// Longest focus period, in minutes (one day).
+const MAX_DURATION_MINUTES: u32 = 24 * 60;
+
+/// What the user asked to focus on.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FocusRequest {
+    /// Folders whose documents (recursively) are in scope.
+    #[serde(default)]
+    pub folders: Vec<String>,
+    /// Tags ("collections") whose documents are in scope.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Earliest modification date, `YYYY-MM-DD` (inclusive).
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Latest modification date, `YYYY-MM-DD` (inclusive).
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Minutes until the focus ends by itself (none = until cleared).
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
+    /// Short name shown in the UI (e.g. "Thesis").
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// The active focus.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FocusContext {
+    pub folders: Vec<String>,
+    pub tags: Vec<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub label: Option<String>,
+    pub set_at: String,
+    /// When the focus ends by itself (RFC 3339).
+    pub expires_at: Option<String>,
+    #[serde(skip)]
+    deadline: Option<Instant>,
+}
+
+fn parse_date(value: Option<String>, field: &str) -> Result<Option<String>> {
+    match value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map(|d| Some(d.format("%Y-%m-%d").to_string()))
+            .map_err(|_| {
+                GhostError::InvalidQuery(format!("{} must be YYYY-MM-DD: {}", field, date))
+            }),
+        None => Ok(None),
+    }
+}
+
+impl FocusContext {
+    /// Validate a request. At least one folder, tag or date bound is required.
+    pub fn from_request(request: FocusRequest) -> Result<Self> {
+        let folders: Vec<String> = request
+            .folders
+            .iter()
+            .map(|f| f.trim().trim_end_matches(['/', '\\']).to_string())
+            .filter(|f| !f.is_empty())
+            .collect();
+        let mut tags = Vec::new();
+        for tag in request
+            .tags
+            .iter()
+            .filter_map(|t| crate::db::normalize_tag(t))
+        {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        let since = parse_date(request.since, "since")?;
+        let until = parse_date(request.until, "until")?;
+        if let (Some(since), Some(until)) = (&since, &until) {
+            if since > until {
+                return Err(GhostError::InvalidQuery(
+                    "since must not be after until".into(),
+                ));
+            }
+        }
+        if folders.is_empty() && tags.is_empty() && since.is_none() && until.is_none() {
+            return Err(GhostError::InvalidQuery(
+                "Focus needs at least one folder, tag, or date".into(),
+            ));
+        }
+
+        let minutes = request
+            .duration_minutes
+            .filter(|m| *m > 0)
+            .map(|m| m.min(MAX_DURATION_MINUTES));
+        let now = chrono::Local::now();
+        Ok(Self {
+            folders,
+            tags,
+            since,
+            until,
+            label: request
+                .label
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty()),
+            set_at: now.to_rfc3339(),
+            expires_at: minutes.map(|m| (now + chrono::Duration::minutes(m as i64)).to_rfc3339()),
+            deadline: minutes.map(|m| Instant::now() + Duration::from_secs(m as u64 * 60)),
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// One-line description for the agent's system prompt.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.folders.is_empty() {
+            parts.push(format!("files in {}", self.folders.join(", ")));
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<String> = self.tags.iter().map(|t| format!("#{}", t)).collect();
+            parts.push(format!("documents tagged {}", tags.join(", ")));
+        }
+        let mut text = if parts.is_empty() {
+            "all documents".to_string()
+        } else {
+            parts.join(" or ")
+        };
+        match (&self.since, &self.until) {
+            (Some(since), Some(until)) => {
+                text.push_str(&format!(" modified {} to {}", since, until))
+            }
+            (Some(since), None) => text.push_str(&format!(" modified since {}", since)),
+            (None, Some(until)) => text.push_str(&format!(" modified until {}", until)),
+            (None, None) => {}
+        }
+        text
+    }
+}
+
+/// Holder for the active focus.
+#[derive(Default)]
+pub struct FocusStore(RwLock<Option<FocusContext>>);
+
+impl FocusStore {
+    /// The focus in effect, dropping it once expired.
+    pub fn active(&self) -> Option<FocusContext> {
+        let focus = self.0.read().ok()?.clone()?;
+        if focus.is_expired() {
+            tracing::info!("Focus context expired");
+            self.clear();
+            return None;
+        }
+        Some(focus)
+    }
+
+    pub fn set(&self, focus: FocusContext) {
+        if let Ok(mut current) = self.0.write() {
+            *current = Some(focus);
+        }
+    }
+
+    /// Remove the focus, returning it.
+    pub fn clear(&self) -> Option<FocusContext> {
+        self.0.write().ok()?.take()
+    }
+}
+
+static FOCUS: LazyLock<FocusStore> = LazyLock::new(FocusStore::default);
+
+/// The app-wide focus in effect, if any.
+pub fn active() -> Option<FocusContext> {
+    FOCUS.active()
+}
+
+/// Validate and set the app-wide focus.
+pub fn set(request: FocusRequest) -> Result<FocusContext> {
+    let focus = FocusContext::from_request(request)?;
+    tracing::info!("Focus set: {}", focus.describe());
+    FOCUS.set(focus.clone());
+    Ok(focus)
+}
+
+/// Clear the app-wide focus, returning what was cleared.
+pub fn clear() -> Option<FocusContext> {
+    FOCUS.clear()
+}
+
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// IDs of the documents in focus.
+pub fn matching_documents(db: &Database, focus: &FocusContext) -> Result<HashSet<i64>> {
+    let mut sources = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+    for folder in &focus.folders {
+        sources.push("d.path LIKE ? ESCAPE '\\'".to_string());
+        params.push(format!(
+            "{}{}%",
+            escape_like(folder),
+            escape_like(std::path::MAIN_SEPARATOR_STR)
+        ));
+    }
+    if !focus.tags.is_empty() {
+        sources.push(format!(
+            "d.id IN (SELECT dt.document_id FROM document_tags dt
+                      JOIN tags t ON t.id = dt.tag_id WHERE t.name IN ({}))",
+            vec!["?"; focus.tags.len()].join(",")
+        ));
+        params.extend(focus.tags.iter().cloned());
+    }
+
+    let mut conditions = Vec::new();
+    if !sources.is_empty() {
+        conditions.push(format!("({})", sources.join(" OR ")));
+    }
+    if let Some(since) = &focus.since {
+        conditions.push("d.modified_at >= ?".to_string());
+        params.push(since.clone());
+    }
+    if let Some(until) = &focus.until {
+        conditions.push("d.modified_at < date(?, '+1 day')".to_string());
+        params.push(until.clone());
+    }
+
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT d.id FROM documents d WHERE {}",
+            conditions.join(" AND ")
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            row.get::<_, i64>(0)
+        })?;
+        Ok(rows.collect::<rusqlite::Result<HashSet<_>>>()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(folders: &[&str], tags: &[&str]) -> FocusRequest {
+        FocusRequest {
+            folders: folders.iter().map(|f| f.to_string()).collect(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_focus_validation_and_expiry() {
+        assert!(FocusContext::from_request(FocusRequest::default()).is_err());
+        assert!(FocusContext::from_request(FocusRequest {
+            since: Some("2024-13-01".into()),
+            ..Default::default()
+        })
+        .is_err());
+
+        let focus = FocusContext::from_request(FocusRequest {
+            since: Some("2024-01-01".into()),
+            until: Some("2024-06-30".into()),
+            ..request(&["/home/me/thesis/"], &["Draft", "draft"])
+        })
+        .unwrap();
+        assert_eq!(focus.folders, vec!["/home/me/thesis"]);
+        assert_eq!(focus.tags, vec!["draft"]);
+        assert_eq!(
+            focus.describe(),
+            "files in /home/me/thesis or documents tagged #draft modified 2024-01-01 to 2024-06-30"
+        );
+
+        let store = FocusStore::default();
+        store.set(focus.clone());
+        assert_eq!(store.active(), Some(focus.clone()));
+        store.set(FocusContext {
+            deadline: Some(Instant::now()),
+            ..focus
+        });
+        assert!(store.active().is_none());
+        assert!(store.clear().is_none());
+    }
+
+    #[test]
+    fn test_matching_documents() {
+        let db = Database::open_in_memory().unwrap();
+        let sep = std::path::MAIN_SEPARATOR;
+        let path = |parts: &[&str]| format!("{}{}", sep, parts.join(&sep.to_string()));
+        let thesis = db
+            .upsert_document(
+                &path(&["thesis", "ch1.md"]),
+                "ch1.md",
+                Some("md"),
+                1,
+                "a",
+                "2024-03-01T10:00:00Z",
+            )
+            .unwrap();
+        let old = db
+            .upsert_document(
+                &path(&["thesis", "old.md"]),
+                "old.md",
+                Some("md"),
+                1,
+                "b",
+                "2023-03-01T10:00:00Z",
+            )
+            .unwrap();
+        let sibling = db
+            .upsert_document(
+                &path(&["thesis2", "x.md"]),
+                "x.md",
+                Some("md"),
+                1,
+                "c",
+                "2024-03-01T10:00:00Z",
+            )
+            .unwrap();
+        let tagged = db
+            .upsert_document(
+                &path(&["notes", "n.md"]),
+                "n.md",
+                Some("md"),
+                1,
+                "d",
+                "2024-06-30T23:00:00Z",
+            )
+            .unwrap();
+        db.add_document_tag(tagged, "thesis", "user").unwrap();
+
+        let folder = path(&["thesis"]);
+        let focus = FocusContext::from_request(request(&[&folder], &["thesis"])).unwrap();
+        let ids = matching_documents(&db, &focus).unwrap();
+        assert_eq!(ids, HashSet::from([thesis, old, tagged]));
+        assert!(!ids.contains(&sibling));
+
+        let focus = FocusContext::from_request(FocusRequest {
+            since: Some("2024-01-01".into()),
+            until: Some("2024-06-30".into()),
+            ..request(&[&folder], &["thesis"])
+        })
+        .unwrap();
+        let ids = matching_documents(&db, &focus).unwrap();
+        assert_eq!(ids, HashSet::from([thesis, tagged]));
+    }
+}
@@ -0,0 +1,182 @@
+//! Typo-tolerant keyword search.
+//!
+//! FTS5 only matches exact (stemmed) terms, so "recipt" finds nothing. When
+//! exact keyword search returns fewer than [`FUZZY_MIN_HITS`] chunks, each
+//! query word is compared against the indexed vocabulary (`chunks_fts_vocab`)
+//! and close spellings are OR-ed in. Chunks found only this way are marked
+//! `"fuzzy"` in the results.
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Exact keyword hits below which the fuzzy fallback runs.
+pub const FUZZY_MIN_HITS: usize = 3;
+
+/// Alternative spellings tried per query word.
+const MAX_ALTERNATIVES: usize = 3;
+
+/// Words shorter than this are not corrected (too many near neighbours).
+const MIN_WORD_CHARS: usize = 4;
+
+/// Edit distance allowed for a word: one typo, two for long words.
+fn max_distance(len: usize) -> usize {
+    if len >= 8 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions,
+/// and transpositions of adjacent characters each cost one.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Indexed terms close to `word`, best first. Empty when the word itself
+/// matches (after stemming) or is too short to correct.
+pub fn corrections(db: &Database, word: &str) -> Result<Vec<String>> {
+    let word = word.to_lowercase();
+    let len = word.chars().count();
+    if len < MIN_WORD_CHARS || !word.chars().all(char::is_alphabetic) {
+        return Ok(Vec::new());
+    }
+    let Some(first) = word.chars().next() else {
+        return Ok(Vec::new());
+    };
+    if !db.fts_search(&word, 1)?.is_empty() {
+        return Ok(Vec::new());
+    }
+    let max = max_distance(len);
+    // Indexed terms are stemmed, so allow them to be a little shorter
+    let terms = db.vocabulary_terms(first, len.saturating_sub(max + 2), len + max)?;
+    let mut scored: Vec<(usize, i64, String)> = Vec::new();
+    for (term, docs) in terms {
+        let distance = edit_distance(&word, &term);
+        if distance <= max {
+            scored.push((distance, docs, term));
+        }
+    }
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+    Ok(scored
+        .into_iter()
+        .take(MAX_ALTERNATIVES)
+        .map(|(_, _, term)| term)
+        .collect())
+}
+
+/// FTS5 expression matching the query with misspelled words widened to
+/// their corrections, or `None` when no word has one.
+pub fn fuzzy_expression(db: &Database, query: &str) -> Result<Option<String>> {
+    let mut corrected = false;
+    let mut parts = Vec::new();
+    for word in query.split_whitespace() {
+        let clean = word.replace('"', "");
+        if clean.is_empty() {
+            continue;
+        }
+        let alternatives = corrections(db, &clean)?;
+        if alternatives.is_empty() {
+            parts.push(format!("\"{}\"", clean));
+        } else {
+            corrected = true;
+            let mut terms = vec![format!("\"{}\"", clean)];
+            terms.extend(alternatives.iter().map(|t| format!("\"{}\"", t)));
+            parts.push(format!("({})", terms.join(" OR ")));
+        }
+    }
+    Ok(corrected.then(|| parts.join(" AND ")))
+}
+
+/// Keyword hits for the typo-tolerant form of `query`, excluding chunks in
+/// `exact`. Empty when nothing in the query looks misspelled.
+pub fn fuzzy_search(
+    db: &Database,
+    query: &str,
+    exact: &[(i64, f64)],
+    limit: usize,
+) -> Result<Vec<(i64, f64)>> {
+    let Some(expression) = fuzzy_expression(db, query)? else {
+        return Ok(Vec::new());
+    };
+    tracing::debug!("Fuzzy keyword search: {}", expression);
+    Ok(db
+        .fts_match(&expression, limit)?
+        .into_iter()
+        .filter(|(id, _)| !exact.iter().any(|(e, _)| e == id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("recipt", "receipt"), 1);
+        assert_eq!(edit_distance("reciept", "receipt"), 1);
+        assert_eq!(edit_distance("invoice", "invoice"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_search_finds_typos() {
+        let db = Database::open_in_memory().unwrap();
+        let doc = db
+            .upsert_document(
+                "/docs/a.txt",
+                "a.txt",
+                Some("txt"),
+                1,
+                "h",
+                "2024-01-01T00:00:00Z",
+            )
+            .unwrap();
+        let receipt = db
+            .insert_chunk(doc, 0, "Scanned receipt from the grocery store", 6)
+            .unwrap();
+        db.insert_chunk(doc, 1, "Pasta recipe with tomatoes", 4)
+            .unwrap();
+
+        let exact = db.fts_search("recipt grocery", 10).unwrap();
+        assert!(exact.is_empty());
+        assert_eq!(
+            corrections(&db, "recipt")
+                .unwrap()
+                .first()
+                .map(String::as_str),
+            Some("receipt")
+        );
+        // Correctly spelled and short words are left alone
+        assert!(corrections(&db, "grocery").unwrap().is_empty());
+        assert!(corrections(&db, "teh").unwrap().is_empty());
+
+        let hits = fuzzy_search(&db, "recipt grocery", &exact, 10).unwrap();
+        assert_eq!(
+            hits.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![receipt]
+        );
+        assert!(fuzzy_search(&db, "grocery", &[], 10).unwrap().is_empty());
+    }
+}
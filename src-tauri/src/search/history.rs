@@ -0,0 +1,218 @@
+//! Query history — recent and frequent searches, and suggestions as you type.
+//!
+//! Every `search_query` is recorded in the vault database (unless the user
+//! turns off `search_history_enabled`). History never leaves the device and
+//! can be wiped with `clear_query_history`. Suggestions match earlier queries
+//! by prefix, ranked by how often and how recently they were run.
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::error::Result;
+
+/// Rows kept; older searches are pruned.
+const MAX_HISTORY: i64 = 5000;
+
+/// Longest query recorded, in characters.
+const MAX_QUERY_CHARS: usize = 500;
+
+/// A distinct query with how often and when it was last run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryStats {
+    pub query: String,
+    pub count: i64,
+    pub last_searched_at: String,
+    /// Results returned the last time it was run.
+    pub last_result_count: i64,
+}
+
+/// Initialize the query history table.
+pub fn initialize_history_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS query_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                query TEXT NOT NULL,
+                result_count INTEGER NOT NULL DEFAULT 0,
+                searched_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_query_history_query
+                ON query_history(query COLLATE NOCASE);
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Record a search. Blank queries are ignored.
+pub fn record(db: &Database, query: &str, result_count: usize) -> Result<()> {
+    let query: String = query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect();
+    if query.is_empty() {
+        return Ok(());
+    }
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO query_history (query, result_count) VALUES (?1, ?2)",
+            rusqlite::params![query, result_count as i64],
+        )?;
+        let id = conn.last_insert_rowid();
+        if id % 100 == 0 {
+            conn.execute(
+                "DELETE FROM query_history WHERE id <= ?1",
+                rusqlite::params![id - MAX_HISTORY],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Record a search, logging instead of failing — history must never break search.
+pub fn track(db: &Database, query: &str, result_count: usize) {
+    if let Err(e) = record(db, query, result_count) {
+        tracing::debug!("Query history not recorded: {}", e);
+    }
+}
+
+/// Grouped history rows, ordered by `order_by`.
+fn grouped(
+    db: &Database,
+    filter: &str,
+    order_by: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<QueryStats>> {
+    db.with_read_conn(|conn| {
+        // Group case-insensitively; show the most recent spelling
+        let mut stmt = conn.prepare(&format!(
+            "SELECT (SELECT h2.query FROM query_history h2
+                     WHERE h2.query = h.query COLLATE NOCASE ORDER BY h2.id DESC LIMIT 1),
+                    COUNT(*) AS uses, MAX(searched_at) AS last_at,
+                    (SELECT h3.result_count FROM query_history h3
+                     WHERE h3.query = h.query COLLATE NOCASE ORDER BY h3.id DESC LIMIT 1)
+             FROM query_history h
+             WHERE {}
+             GROUP BY h.query COLLATE NOCASE
+             ORDER BY {}",
+            filter, order_by
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            Ok(QueryStats {
+                query: row.get(0)?,
+                count: row.get(1)?,
+                last_searched_at: row.get(2)?,
+                last_result_count: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// Most recent distinct queries, newest first.
+pub fn recent(db: &Database, limit: usize) -> Result<Vec<QueryStats>> {
+    grouped(db, "1", "MAX(h.id) DESC LIMIT ?1", &[&(limit as i64)])
+}
+
+/// Most frequent queries over the last `days` days (all time when `None`).
+pub fn frequent(db: &Database, limit: usize, days: Option<u32>) -> Result<Vec<QueryStats>> {
+    let since = days.map(|d| format!("-{} days", d.max(1)));
+    grouped(
+        db,
+        "(?2 IS NULL OR searched_at >= datetime('now', ?2))",
+        "uses DESC, MAX(h.id) DESC LIMIT ?1",
+        &[&(limit as i64), &since],
+    )
+}
+
+/// Earlier queries starting with `prefix` (case-insensitive), most used
+/// first. The prefix itself is left out.
+pub fn suggest(db: &Database, prefix: &str, limit: usize) -> Result<Vec<String>> {
+    let prefix = prefix.trim_start();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pattern = format!(
+        "{}%",
+        prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+    Ok(grouped(
+        db,
+        "h.query LIKE ?2 ESCAPE '\\' AND h.query != ?3 COLLATE NOCASE",
+        "uses DESC, MAX(h.id) DESC LIMIT ?1",
+        &[&(limit as i64), &pattern, &prefix.trim_end()],
+    )?
+    .into_iter()
+    .map(|q| q.query)
+    .collect())
+}
+
+/// Delete all recorded searches.
+pub fn clear(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute("DELETE FROM query_history", [])?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        initialize_history_schema(&db).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_recent_and_frequent() {
+        let db = test_db();
+        record(&db, "tax  return", 4).unwrap();
+        record(&db, "invoice", 2).unwrap();
+        record(&db, "Tax return", 5).unwrap();
+        record(&db, "   ", 0).unwrap();
+
+        let latest = recent(&db, 10).unwrap();
+        let queries: Vec<&str> = latest.iter().map(|q| q.query.as_str()).collect();
+        assert_eq!(queries, vec!["Tax return", "invoice"]);
+        assert_eq!((latest[0].count, latest[0].last_result_count), (2, 5));
+
+        let top = frequent(&db, 1, Some(7)).unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].query, "Tax return");
+        assert_eq!(frequent(&db, 10, None).unwrap().len(), 2);
+
+        clear(&db).unwrap();
+        assert!(recent(&db, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_suggest_by_prefix() {
+        let db = test_db();
+        for q in [
+            "tax return 2023",
+            "tax return 2023",
+            "taxes",
+            "tax",
+            "100% done",
+        ] {
+            record(&db, q, 1).unwrap();
+        }
+        assert_eq!(
+            suggest(&db, "TAX", 10).unwrap(),
+            vec!["tax return 2023", "taxes"]
+        );
+        assert_eq!(suggest(&db, "100%", 10).unwrap(), vec!["100% done"]);
+        // LIKE wildcards in the prefix are literal
+        assert!(suggest(&db, "_ax", 10).unwrap().is_empty());
+        assert!(suggest(&db, " ", 10).unwrap().is_empty());
+    }
+}
@@ -1,5 +1,17 @@
+pub mod aggregate;
+pub mod confidence;
+pub mod feedback;
+pub mod filenames;
+pub mod filters;
+pub mod focus;
+pub mod fuzzy;
+pub mod history;
 pub mod ranking;
+pub mod workspace;
 
+use std::collections::HashSet;
+
+use crate::db::vec_migration::VecMigration;
 use crate::db::Database;
 use crate::embeddings::EmbeddingEngine;
 use crate::error::Result;
@@ -15,7 +27,64 @@ pub struct SearchResult {
     pub snippet: String,
     pub chunk_index: i32,
     pub score: f64,
-    pub source: String, // "fts", "vector", or "hybrid"
+    pub source: String, // "fts", "vector", "hybrid", "fuzzy", or "filter"
+    /// Section title (e.g. "Chapter 4: ...") for chunked e-books.
+    pub section: Option<String>,
+    /// Code symbol the chunk defines (e.g. "Database::open").
+    pub symbol: Option<String>,
+    /// Detected language of the chunk (ISO 639-1).
+    pub language: Option<String>,
+    /// Cached summary of the document (see [`crate::agent::summarize`]).
+    pub summary: Option<String>,
+    /// Plain-language version of `source` for screen-reader announcements.
+    pub source_description: String,
+    /// 1-based position in the result list.
+    pub position: usize,
+    /// Number of results in the list.
+    pub total: usize,
+}
+
+/// Event carrying progressive results for [`hybrid_search_progressive`] runs.
+pub const SEARCH_RESULTS_EVENT: &str = "search://results";
+
+/// Which phase of a progressive search produced a result list.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchStage {
+    /// Keyword (FTS5) hits only, before vector search has run.
+    Keyword,
+    /// Final hybrid ranking.
+    Final,
+}
+
+/// One progressive result list, tagged with the caller's search ID so the UI
+/// can drop updates from superseded queries.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SearchUpdate {
+    pub search_id: u64,
+    pub stage: SearchStage,
+    pub results: Vec<SearchResult>,
+}
+
+/// Plain-language description of how a result matched.
+pub fn describe_source(source: &str) -> &'static str {
+    match source {
+        "hybrid" => "Matched by keywords and meaning",
+        "vector" => "Matched by meaning",
+        "filter" => "Matched your filters",
+        "fuzzy" => "Matched a similar spelling",
+        _ => "Matched by keywords",
+    }
+}
+
+/// Fill in `position` and `total` after the result list is final.
+/// Call again after re-ordering or truncating results.
+pub fn annotate_positions(results: &mut [SearchResult]) {
+    let total = results.len();
+    for (i, result) in results.iter_mut().enumerate() {
+        result.position = i + 1;
+        result.total = total;
+    }
 }
 
 /// Perform hybrid search combining FTS5 keyword search with vector KNN search.
@@ -33,6 +102,15 @@ pub async fn hybrid_search(
 ///
 /// When `extension_filter` is provided (e.g., "pdf"), vector search uses sqlite-vec
 /// metadata filtering for up to 10x faster results by pre-filtering before distance computation.
+///
+/// `from:` / `before:` tokens in the query restrict results to matching email
+/// documents, `on:` tokens to calendars with an event that day, and `tag:`
+/// tokens to tagged documents (see [`filters`]).
+/// An active [`focus`] context further restricts results to the focused sources.
+///
+/// When keyword search finds fewer than [`fuzzy::FUZZY_MIN_HITS`] chunks,
+/// misspelled words are widened to close indexed terms; those hits have
+/// source `"fuzzy"`.
 pub async fn hybrid_search_filtered(
     db: &Database,
     embedding_engine: &EmbeddingEngine,
@@ -40,14 +118,137 @@ pub async fn hybrid_search_filtered(
     limit: usize,
     extension_filter: Option<&str>,
 ) -> Result<Vec<SearchResult>> {
-    // FTS5 keyword search
-    let fts_results = db.fts_search(query, limit * 2)?;
+    hybrid_search_scoped(db, embedding_engine, query, limit, extension_filter, None).await
+}
+
+/// [`hybrid_search_filtered`] limited to the documents in `scope` (e.g. the
+/// ones attached to a conversation), on top of any focus and filters.
+pub async fn hybrid_search_scoped(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    query: &str,
+    limit: usize,
+    extension_filter: Option<&str>,
+    scope: Option<&HashSet<i64>>,
+) -> Result<Vec<SearchResult>> {
+    search_inner(
+        db,
+        embedding_engine,
+        query,
+        limit,
+        extension_filter,
+        scope,
+        None,
+    )
+    .await
+}
+
+/// [`hybrid_search`] that hands keyword-only results to `on_keyword_results`
+/// as soon as FTS5 finishes, before the slower query embedding and vector
+/// search. The returned list is the final hybrid ranking.
+pub async fn hybrid_search_progressive(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    query: &str,
+    limit: usize,
+    mut on_keyword_results: impl FnMut(Vec<SearchResult>) + Send,
+) -> Result<Vec<SearchResult>> {
+    search_inner(
+        db,
+        embedding_engine,
+        query,
+        limit,
+        None,
+        None,
+        Some(&mut on_keyword_results),
+    )
+    .await
+}
+
+async fn search_inner(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    query: &str,
+    limit: usize,
+    extension_filter: Option<&str>,
+    scope: Option<&HashSet<i64>>,
+    on_keyword_results: Option<&mut (dyn FnMut(Vec<SearchResult>) + Send)>,
+) -> Result<Vec<SearchResult>> {
+    let (text, filters) = filters::parse_query(query);
+    let query = text.as_str();
+    let focused = match focus::active() {
+        Some(focus) => Some(focus::matching_documents(db, &focus)?),
+        None => None,
+    };
+    let focused = match (focused, scope) {
+        (Some(focused), Some(scope)) => Some(focused.intersection(scope).copied().collect()),
+        (None, Some(scope)) => Some(scope.clone()),
+        (focused, None) => focused,
+    };
+    let allowed_documents: Option<HashSet<i64>> = if filters.is_empty() {
+        focused
+    } else {
+        let mut documents = matching_documents(db, &filters)?;
+        if let Some(focused) = &focused {
+            documents.retain(|id| focused.contains(id));
+        }
+        if query.is_empty() {
+            // Filters only: list the matching documents, newest first
+            return filtered_documents(db, &documents, limit);
+        }
+        Some(documents.into_iter().collect())
+    };
+    // Filtered searches discard non-matching hits, so fetch a deeper candidate list
+    let candidates = if allowed_documents.is_some() {
+        limit * 10
+    } else {
+        limit * 2
+    };
+
+    // FTS5 keyword search, widened to close spellings when it finds little
+    let mut fts_results = db.fts_search(query, candidates)?;
+    let fuzzy_hits: HashSet<i64> = if fts_results.len() < fuzzy::FUZZY_MIN_HITS {
+        let fuzzy_results = fuzzy::fuzzy_search(db, query, &fts_results, candidates)?;
+        let ids = fuzzy_results.iter().map(|(id, _)| *id).collect();
+        fts_results.extend(fuzzy_results);
+        ids
+    } else {
+        HashSet::new()
+    };
+
+    if let Some(on_keyword_results) = on_keyword_results {
+        let ranked = ranking::reciprocal_rank_fusion(&fts_results, &[]);
+        on_keyword_results(rank_results(
+            db,
+            query,
+            &ranked,
+            &fuzzy_hits,
+            allowed_documents.as_ref(),
+            candidates,
+            limit,
+        )?);
+    }
 
     // Vector search (if sqlite-vec is available and embedding engine works)
-    let vec_results = if db.is_vec_enabled() {
+    let migration = if db.is_vec_enabled() {
+        db.get_vec_migration()?
+    } else {
+        None
+    };
+    let vec_results = if let Some(migration) = migration {
+        dual_read_vec_search(
+            db,
+            embedding_engine,
+            &migration,
+            query,
+            candidates,
+            extension_filter,
+        )
+        .await?
+    } else if db.is_vec_enabled() {
         match embedding_engine.embed(query).await {
             Ok(query_embedding) => {
-                db.vec_search_filtered(&query_embedding, limit * 2, extension_filter)?
+                db.vec_search_filtered(&query_embedding, candidates, extension_filter)?
             }
             Err(e) => {
                 tracing::debug!("Vector search unavailable: {} — using FTS5 only", e);
@@ -60,33 +261,240 @@ pub async fn hybrid_search_filtered(
 
     // Combine with Reciprocal Rank Fusion
     let ranked = ranking::reciprocal_rank_fusion(&fts_results, &vec_results);
+    rank_results(
+        db,
+        query,
+        &ranked,
+        &fuzzy_hits,
+        allowed_documents.as_ref(),
+        candidates,
+        limit,
+    )
+}
 
+/// Load fused candidates, apply symbol and document boosts, and keep the
+/// best `limit`.
+fn rank_results(
+    db: &Database,
+    query: &str,
+    ranked: &[ranking::RankedResult],
+    fuzzy_hits: &HashSet<i64>,
+    allowed_documents: Option<&HashSet<i64>>,
+    candidates: usize,
+    limit: usize,
+) -> Result<Vec<SearchResult>> {
+    // Rank all candidates, so boosted definitions can move into the top `limit`
     let mut results = Vec::new();
-    for ranked_item in ranked.iter().take(limit) {
+    for ranked_item in ranked {
+        if results.len() >= candidates {
+            break;
+        }
         if let Some(chunk) = db.get_chunk_with_document(ranked_item.chunk_id)? {
+            if let Some(allowed) = allowed_documents {
+                if !allowed.contains(&chunk.document_id) {
+                    continue;
+                }
+            }
+            let source = match (ranked_item.vec_rank, ranked_item.fts_rank) {
+                (Some(_), Some(_)) => "hybrid",
+                (Some(_), None) => "vector",
+                _ if fuzzy_hits.contains(&chunk.chunk_id) => "fuzzy",
+                _ => "fts",
+            };
+            let symbol_boost = chunk
+                .symbol
+                .as_deref()
+                .map_or(0.0, |symbol| ranking::symbol_boost(query, symbol));
             results.push(SearchResult {
                 chunk_id: chunk.chunk_id,
                 document_id: chunk.document_id,
                 path: chunk.path,
                 filename: chunk.filename,
                 extension: chunk.extension,
+                section: chunk.section,
+                symbol: chunk.symbol,
+                language: chunk.language,
+                summary: None,
                 snippet: truncate_snippet(&chunk.content, 200),
                 chunk_index: chunk.chunk_index,
-                score: ranked_item.rrf_score,
-                source: if ranked_item.vec_rank.is_some() && ranked_item.fts_rank.is_some() {
-                    "hybrid".to_string()
-                } else if ranked_item.vec_rank.is_some() {
-                    "vector".to_string()
-                } else {
-                    "fts".to_string()
-                },
+                score: ranked_item.rrf_score + symbol_boost,
+                source: source.to_string(),
+                source_description: describe_source(source).to_string(),
+                position: 0,
+                total: 0,
             });
         }
     }
 
+    // Recently modified and previously opened documents rank higher, archives lower
+    let weights = ranking::current();
+    if weights.is_active() && !results.is_empty() {
+        let ids: Vec<i64> = results.iter().map(|r| r.document_id).collect();
+        let signals = feedback::document_signals(db, &ids)?;
+        for result in &mut results {
+            let signal = signals.get(&result.document_id);
+            result.score += ranking::document_boost(
+                &result.path,
+                signal.and_then(|s| s.age_days),
+                signal.map_or(0, |s| s.opens),
+                &weights,
+            );
+        }
+    }
+
+    // Definitions of a symbol named in the query outrank chunks that mention it
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    results.truncate(limit);
+    attach_summaries(db, &mut results);
+    annotate_positions(&mut results);
     Ok(results)
 }
 
+/// Documents matching every filter: newest email first for email filters,
+/// otherwise most recently indexed first.
+fn matching_documents(db: &Database, filters: &filters::QueryFilters) -> Result<Vec<i64>> {
+    // Each kind of filter narrows the list; the first one present sets the order
+    let mut lists = Vec::new();
+    if filters.has_email_filters() {
+        lists.push(
+            db.filter_documents_by_email(filters.from.as_deref(), filters.before.as_deref())?,
+        );
+    }
+    if let Some(on) = &filters.on {
+        lists.push(db.filter_documents_by_event_date(on)?);
+    }
+    if !filters.tags.is_empty() {
+        lists.push(db.filter_documents_by_tags(&filters.tags)?);
+    }
+    if !filters.links.is_empty() {
+        lists.push(crate::indexer::importers::notes::documents_linking_to(
+            db,
+            &filters.links,
+        )?);
+    }
+    let mut lists = lists.into_iter();
+    let mut documents = lists.next().unwrap_or_default();
+    for list in lists {
+        let list: HashSet<i64> = list.into_iter().collect();
+        documents.retain(|id| list.contains(id));
+    }
+    Ok(documents)
+}
+
+/// First chunk of each document, for filter-only queries like `from:alice`
+/// or `tag:taxes`.
+fn filtered_documents(db: &Database, documents: &[i64], limit: usize) -> Result<Vec<SearchResult>> {
+    let mut results = Vec::new();
+    for &document_id in documents {
+        if results.len() >= limit {
+            break;
+        }
+        let Some(chunk_id) = db.first_chunk_id(document_id)? else {
+            continue;
+        };
+        if let Some(chunk) = db.get_chunk_with_document(chunk_id)? {
+            results.push(SearchResult {
+                chunk_id: chunk.chunk_id,
+                document_id: chunk.document_id,
+                path: chunk.path,
+                filename: chunk.filename,
+                extension: chunk.extension,
+                section: chunk.section,
+                symbol: chunk.symbol,
+                language: chunk.language,
+                summary: None,
+                snippet: truncate_snippet(&chunk.content, 200),
+                chunk_index: chunk.chunk_index,
+                score: 0.0,
+                source: "filter".to_string(),
+                source_description: describe_source("filter").to_string(),
+                position: 0,
+                total: 0,
+            });
+        }
+    }
+    attach_summaries(db, &mut results);
+    annotate_positions(&mut results);
+    Ok(results)
+}
+
+/// Add the cached summary of each result's document. Search works without
+/// them, so failures are only logged.
+fn attach_summaries(db: &Database, results: &mut [SearchResult]) {
+    let ids: Vec<i64> = results.iter().map(|r| r.document_id).collect();
+    match crate::agent::summarize::cached_summaries(db, &ids) {
+        Ok(summaries) => {
+            for result in results.iter_mut() {
+                result.summary = summaries.get(&result.document_id).cloned();
+            }
+        }
+        Err(e) => tracing::debug!("Document summaries unavailable: {}", e),
+    }
+}
+
+/// Vector search while a re-embedding migration is in progress.
+///
+/// Chunks up to the migration cursor are read from the new index (queried
+/// with the target model), the rest from the old index. The two ranked lists
+/// are interleaved, since distances from different models aren't comparable.
+async fn dual_read_vec_search(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    migration: &VecMigration,
+    query: &str,
+    limit: usize,
+    extension_filter: Option<&str>,
+) -> Result<Vec<(i64, f64)>> {
+    let old = match embedding_engine.embed(query).await {
+        Ok(embedding) => db
+            .vec_search_filtered(&embedding, limit, extension_filter)?
+            .into_iter()
+            .filter(|(chunk_id, _)| *chunk_id > migration.cursor)
+            .collect(),
+        Err(e) => {
+            tracing::debug!("Old-index vector search unavailable: {}", e);
+            vec![]
+        }
+    };
+
+    let new = match crate::embeddings::models::find_target(migration.target_id()) {
+        Some(target) => match embedding_engine.embed_query_for(target, query).await {
+            Ok(embedding) => db.vec_search_migrated(&embedding, limit, extension_filter)?,
+            Err(e) => {
+                tracing::debug!("New-index vector search unavailable: {}", e);
+                vec![]
+            }
+        },
+        None => vec![],
+    };
+
+    Ok(interleave(new, old, limit))
+}
+
+/// Merge two ranked lists by alternating between them, skipping duplicates.
+fn interleave(a: Vec<(i64, f64)>, b: Vec<(i64, f64)>, limit: usize) -> Vec<(i64, f64)> {
+    let mut seen = std::collections::HashSet::new();
+    let mut merged = Vec::with_capacity(limit);
+    let mut a = a.into_iter();
+    let mut b = b.into_iter();
+    loop {
+        let (next_a, next_b) = (a.next(), b.next());
+        if next_a.is_none() && next_b.is_none() {
+            break;
+        }
+        for item in [next_a, next_b].into_iter().flatten() {
+            if merged.len() < limit && seen.insert(item.0) {
+                merged.push(item);
+            }
+        }
+    }
+    merged
+}
+
 /// Truncate text to a maximum character length, ending at a word boundary.
 /// Uses char_indices to avoid panicking on multi-byte UTF-8 boundaries.
 fn truncate_snippet(text: &str, max_chars: usize) -> String {
@@ -125,6 +533,17 @@ mod tests {
         assert!(result.ends_with("..."));
     }
 
+    #[test]
+    fn test_interleave() {
+        let new = vec![(1, 0.1), (2, 0.2), (3, 0.3)];
+        let old = vec![(10, 0.5), (2, 0.6)];
+        let merged: Vec<i64> = interleave(new, old, 10).iter().map(|r| r.0).collect();
+        assert_eq!(merged, vec![1, 10, 2, 3]);
+
+        let limited = interleave(vec![(1, 0.1), (2, 0.2)], vec![(3, 0.3)], 2);
+        assert_eq!(limited.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_hybrid_search() {
         crate::ensure_tls_provider();
@@ -152,5 +571,37 @@ mod tests {
             .unwrap();
         assert!(!results.is_empty());
         assert_eq!(results[0].filename, "doc.txt");
+        assert_eq!(results[0].position, 1);
+        assert_eq!(results[0].total, results.len());
+        assert!(!results[0].source_description.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_progressive_search_reports_keyword_results_first() {
+        let db = Database::open_in_memory().unwrap();
+        let doc_id = db
+            .upsert_document(
+                "/test/notes.md",
+                "notes.md",
+                Some("md"),
+                10,
+                "hash1",
+                "2026-01-01T00:00:00Z",
+            )
+            .unwrap();
+        db.insert_chunk(doc_id, 0, "quarterly budget review", 3)
+            .unwrap();
+
+        let mut keyword = Vec::new();
+        let results =
+            hybrid_search_progressive(&db, &EmbeddingEngine::none(), "budget", 10, |results| {
+                keyword = results
+            })
+            .await
+            .unwrap();
+        assert_eq!(keyword.len(), 1);
+        assert_eq!(keyword[0].source, "fts");
+        assert_eq!(keyword[0].position, 1);
+        assert_eq!(results[0].chunk_id, keyword[0].chunk_id);
     }
 }
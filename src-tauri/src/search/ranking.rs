@@ -1,8 +1,12 @@
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
 /// Reciprocal Rank Fusion (RRF) for combining multiple ranked lists.
 ///
 /// RRF score = sum(1 / (k + rank_i)) for each ranking system.
 /// k = 60 is the standard constant from the original paper.
-const RRF_K: f64 = 60.0;
+pub(crate) const RRF_K: f64 = 60.0;
 
 /// A search result with scores from different ranking systems.
 #[derive(Debug, Clone)]
@@ -60,6 +64,178 @@ pub fn reciprocal_rank_fusion(
     results
 }
 
+/// Boost for a chunk that defines a symbol named in the query: as much as
+/// ranking first in one system, so the definition outranks chunks that only
+/// call or mention it.
+pub(crate) const SYMBOL_MATCH_BOOST: f64 = 1.0 / (RRF_K + 1.0);
+
+/// Boost when a query term is part of the symbol name (`parse` → `parse_query`).
+pub(crate) const SYMBOL_PARTIAL_BOOST: f64 = SYMBOL_MATCH_BOOST / 3.0;
+
+/// Score boost for a code chunk defining `symbol` (e.g. "Database::open").
+pub fn symbol_boost(query: &str, symbol: &str) -> f64 {
+    let symbol = symbol.to_lowercase();
+    let name = symbol.rsplit([':', '.']).next().unwrap_or(&symbol);
+    let terms: Vec<String> = query
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | ':' | '.')))
+        .map(|t| t.trim_matches([':', '.']).to_lowercase())
+        .filter(|t| t.chars().count() >= 2)
+        .collect();
+    if terms.iter().any(|t| *t == symbol || t == name) {
+        SYMBOL_MATCH_BOOST
+    } else if terms
+        .iter()
+        .any(|t| t.chars().count() >= 3 && name.contains(t.as_str()))
+    {
+        SYMBOL_PARTIAL_BOOST
+    } else {
+        0.0
+    }
+}
+
+/// One unit of document-level boost: as much as ranking first in one system.
+const BOOST_UNIT: f64 = 1.0 / (RRF_K + 1.0);
+
+/// Folder names that mark archived or backup copies.
+const ARCHIVE_DIRS: &[&str] = &[
+    "archive",
+    "archives",
+    "archived",
+    "old",
+    "backup",
+    "backups",
+    "bak",
+    "trash",
+    ".trash",
+    "$recycle.bin",
+    "deprecated",
+];
+
+/// Path depth (folders) beyond which results start losing score.
+const DEEP_PATH_FOLDERS: usize = 8;
+
+/// Weights of the document-level ranking signals, from Settings. Each weight
+/// is in units of "ranking first in one system"; 0 turns a signal off.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RankingWeights {
+    /// Boost for recently modified documents.
+    #[serde(default = "default_recency_weight")]
+    pub recency: f64,
+    /// Days after which the recency boost halves.
+    #[serde(default = "default_recency_half_life_days")]
+    pub recency_half_life_days: f64,
+    /// Boost for documents the user opened from results before.
+    #[serde(default = "default_opened_weight")]
+    pub opened: f64,
+    /// Penalty for documents in archive/backup folders or deeply nested.
+    #[serde(default = "default_archive_penalty")]
+    pub archive_penalty: f64,
+}
+
+fn default_recency_weight() -> f64 {
+    0.5
+}
+fn default_recency_half_life_days() -> f64 {
+    90.0
+}
+fn default_opened_weight() -> f64 {
+    0.5
+}
+fn default_archive_penalty() -> f64 {
+    0.5
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            recency: default_recency_weight(),
+            recency_half_life_days: default_recency_half_life_days(),
+            opened: default_opened_weight(),
+            archive_penalty: default_archive_penalty(),
+        }
+    }
+}
+
+impl RankingWeights {
+    /// Weights kept within 0-5 and a half-life of at least a day.
+    pub fn clamped(&self) -> Self {
+        let weight = |w: f64| {
+            if w.is_finite() {
+                w.clamp(0.0, 5.0)
+            } else {
+                0.0
+            }
+        };
+        Self {
+            recency: weight(self.recency),
+            recency_half_life_days: if self.recency_half_life_days.is_finite() {
+                self.recency_half_life_days.max(1.0)
+            } else {
+                default_recency_half_life_days()
+            },
+            opened: weight(self.opened),
+            archive_penalty: weight(self.archive_penalty),
+        }
+    }
+
+    /// Whether any document-level signal is on.
+    pub fn is_active(&self) -> bool {
+        self.recency > 0.0 || self.opened > 0.0 || self.archive_penalty > 0.0
+    }
+}
+
+static WEIGHTS: LazyLock<RwLock<RankingWeights>> = LazyLock::new(Default::default);
+
+/// Set the ranking weights used by search (from Settings).
+pub fn configure(weights: RankingWeights) {
+    if let Ok(mut current) = WEIGHTS.write() {
+        *current = weights.clamped();
+    }
+}
+
+/// The configured ranking weights.
+pub fn current() -> RankingWeights {
+    WEIGHTS.read().map(|w| *w).unwrap_or_default()
+}
+
+/// Whether the path runs through an archive or backup folder.
+pub fn is_archive_path(path: &str) -> bool {
+    path.split(['/', '\\'])
+        .rev()
+        .skip(1) // the file name
+        .any(|folder| ARCHIVE_DIRS.contains(&folder.to_lowercase().as_str()))
+}
+
+/// Document-level score adjustment: recency and past opens add, archive
+/// and deeply nested paths subtract.
+pub fn document_boost(
+    path: &str,
+    age_days: Option<f64>,
+    opens: i64,
+    weights: &RankingWeights,
+) -> f64 {
+    let mut boost = 0.0;
+    if let Some(age) = age_days {
+        let half_lives = age.max(0.0) / weights.recency_half_life_days.max(1.0);
+        boost += weights.recency * BOOST_UNIT * 0.5f64.powf(half_lives);
+    }
+    if opens > 0 {
+        // Diminishing returns: 1 open = 0.3, 10+ opens = full weight
+        let familiarity = ((opens.min(10) as f64).ln_1p() / 11f64.ln()).min(1.0);
+        boost += weights.opened * BOOST_UNIT * familiarity;
+    }
+    let mut penalty = if is_archive_path(path) { 1.0 } else { 0.0 };
+    let folders = path
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty())
+        .count()
+        .saturating_sub(1);
+    if folders > DEEP_PATH_FOLDERS {
+        penalty += (0.1 * (folders - DEEP_PATH_FOLDERS) as f64).min(0.5);
+    }
+    boost - weights.archive_penalty * BOOST_UNIT * penalty
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +262,72 @@ mod tests {
         assert_eq!(results[0].chunk_id, 1); // Higher FTS rank
     }
 
+    #[test]
+    fn test_symbol_boost() {
+        assert_eq!(
+            symbol_boost("how does open work", "Database::open"),
+            SYMBOL_MATCH_BOOST
+        );
+        assert_eq!(
+            symbol_boost("Database::open", "Database::open"),
+            SYMBOL_MATCH_BOOST
+        );
+        assert_eq!(
+            symbol_boost("Parser.parse?", "Parser.parse"),
+            SYMBOL_MATCH_BOOST
+        );
+        assert_eq!(symbol_boost("parse", "parse_query"), SYMBOL_PARTIAL_BOOST);
+        assert_eq!(symbol_boost("vault", "Database::open"), 0.0);
+
+        // An exact definition match beats the top-ranked mention
+        let mention = 1.0 / (RRF_K + 1.0);
+        let definition = 1.0 / (RRF_K + 3.0) + SYMBOL_MATCH_BOOST;
+        assert!(definition > mention);
+    }
+
+    #[test]
+    fn test_document_boost() {
+        let weights = RankingWeights::default();
+        let recent = document_boost("/docs/plan.md", Some(1.0), 0, &weights);
+        let stale = document_boost("/docs/plan.md", Some(365.0), 0, &weights);
+        assert!(recent > stale && stale > 0.0);
+
+        let opened = document_boost("/docs/plan.md", None, 3, &weights);
+        let often = document_boost("/docs/plan.md", None, 50, &weights);
+        assert!(often > opened && opened > 0.0);
+        assert!((often - weights.opened * BOOST_UNIT).abs() < 1e-12);
+
+        assert!(is_archive_path("/home/me/Archive/2019/plan.md"));
+        assert!(is_archive_path("C:\\Users\\me\\Backups\\plan.md"));
+        assert!(!is_archive_path("/home/me/docs/archive.md"));
+        assert!(document_boost("/home/me/old/plan.md", None, 0, &weights) < 0.0);
+        assert!(document_boost("/a/b/c/d/e/f/g/h/i/j/plan.md", None, 0, &weights) < 0.0);
+
+        let off = RankingWeights {
+            recency: 0.0,
+            opened: 0.0,
+            archive_penalty: 0.0,
+            ..weights
+        };
+        assert!(!off.is_active());
+        assert_eq!(document_boost("/old/plan.md", Some(0.0), 5, &off), 0.0);
+        assert_eq!(
+            RankingWeights {
+                recency: f64::NAN,
+                opened: 9.0,
+                recency_half_life_days: 0.0,
+                ..weights
+            }
+            .clamped(),
+            RankingWeights {
+                recency: 0.0,
+                opened: 5.0,
+                recency_half_life_days: 1.0,
+                ..weights
+            }
+        );
+    }
+
     #[test]
     fn test_rrf_empty() {
         let results = reciprocal_rank_fusion(&[], &[]);
@@ -0,0 +1,265 @@
+//! Workspace-aware context for IDE integrations.
+//!
+//! An editor plugin sends the project root, the files the user recently
+//! touched, and optionally what they're working on. Ghost turns that into a
+//! handful of search seeds, runs hybrid search for each, and merges the hits
+//! into one ranked list of vault context — notes, docs, and related code.
+//!
+//! Ranking rules on top of the merged search scores:
+//! - Documents inside the project root are boosted
+//! - The recent files themselves are excluded (the editor already has them)
+//! - Only the best chunk per document is kept
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::SearchResult;
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::Result;
+
+/// Max recent files turned into search seeds.
+const MAX_FILE_SEEDS: usize = 5;
+
+/// Score multiplier for documents inside the project root.
+const WORKSPACE_BOOST: f64 = 1.5;
+
+/// Context request from an editor plugin.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkspaceContextRequest {
+    /// Absolute path of the project/workspace root.
+    pub project_root: String,
+    /// Recently opened or edited files (most recent first).
+    #[serde(default)]
+    pub recent_files: Vec<String>,
+    /// What the user is working on (selection, task, question).
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Maximum items to return (default: 10).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// One ranked context item.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceContextItem {
+    pub path: String,
+    pub filename: String,
+    pub snippet: String,
+    pub score: f64,
+    /// True if the document lives inside the project root.
+    pub in_workspace: bool,
+}
+
+/// Split a file name into search words: `user_service.rs` / `UserService.ts` → "user service".
+fn identifier_words(name: &str) -> String {
+    let stem = Path::new(name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in stem.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+        .into_iter()
+        .filter(|w| w.len() > 1 && !matches!(w.as_str(), "index" | "mod" | "main" | "lib"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Build the search seeds for a request: the explicit query, then one per recent file.
+fn search_seeds(request: &WorkspaceContextRequest) -> Vec<String> {
+    let mut seeds = Vec::new();
+    if let Some(query) = request.query.as_deref().map(str::trim) {
+        if !query.is_empty() {
+            seeds.push(query.to_string());
+        }
+    }
+    for file in request.recent_files.iter().take(MAX_FILE_SEEDS) {
+        let words = identifier_words(file);
+        if !words.is_empty() && !seeds.contains(&words) {
+            seeds.push(words);
+        }
+    }
+    if seeds.is_empty() {
+        let project = identifier_words(&request.project_root);
+        if !project.is_empty() {
+            seeds.push(project);
+        }
+    }
+    seeds
+}
+
+/// Merge search hits into ranked, per-document context items.
+fn rank_results(
+    request: &WorkspaceContextRequest,
+    hits: Vec<SearchResult>,
+    limit: usize,
+) -> Vec<WorkspaceContextItem> {
+    let root = Path::new(&request.project_root);
+    let mut by_doc: HashMap<i64, (SearchResult, f64)> = HashMap::new();
+
+    for hit in hits {
+        if request
+            .recent_files
+            .iter()
+            .any(|f| Path::new(f) == Path::new(&hit.path))
+        {
+            continue;
+        }
+        let score = hit.score;
+        match by_doc.get_mut(&hit.document_id) {
+            Some((best, total)) => {
+                *total += score;
+                if score > best.score {
+                    *best = hit;
+                }
+            }
+            None => {
+                by_doc.insert(hit.document_id, (hit, score));
+            }
+        }
+    }
+
+    let mut items: Vec<WorkspaceContextItem> = by_doc
+        .into_values()
+        .map(|(best, total)| {
+            let in_workspace = Path::new(&best.path).starts_with(root);
+            WorkspaceContextItem {
+                score: if in_workspace {
+                    total * WORKSPACE_BOOST
+                } else {
+                    total
+                },
+                path: best.path,
+                filename: best.filename,
+                snippet: best.snippet,
+                in_workspace,
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    items.truncate(limit);
+    items
+}
+
+/// Find vault context relevant to an editor workspace.
+pub async fn workspace_context(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    request: &WorkspaceContextRequest,
+) -> Result<Vec<WorkspaceContextItem>> {
+    let limit = request.limit.unwrap_or(10).clamp(1, 50);
+
+    let mut hits = Vec::new();
+    for seed in search_seeds(request) {
+        hits.extend(super::hybrid_search(db, embedding_engine, &seed, limit * 2).await?);
+    }
+
+    Ok(rank_results(request, hits, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(recent: &[&str], query: Option<&str>) -> WorkspaceContextRequest {
+        WorkspaceContextRequest {
+            project_root: "/home/user/projects/billing-api".into(),
+            recent_files: recent.iter().map(|s| s.to_string()).collect(),
+            query: query.map(str::to_string),
+            limit: None,
+        }
+    }
+
+    fn hit(document_id: i64, path: &str, score: f64) -> SearchResult {
+        SearchResult {
+            chunk_id: document_id * 10,
+            document_id,
+            path: path.into(),
+            filename: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            extension: None,
+            snippet: "snippet".into(),
+            chunk_index: 0,
+            score,
+            source: "fts".into(),
+            section: None,
+            symbol: None,
+            language: None,
+            summary: None,
+            source_description: String::new(),
+            position: 0,
+            total: 0,
+        }
+    }
+
+    #[test]
+    fn test_identifier_words() {
+        assert_eq!(identifier_words("src/user_service.rs"), "user service");
+        assert_eq!(identifier_words("UserService.ts"), "user service");
+        assert_eq!(
+            identifier_words("invoice-pdf-export.py"),
+            "invoice pdf export"
+        );
+        assert_eq!(identifier_words("src/index.ts"), "");
+    }
+
+    #[test]
+    fn test_search_seeds() {
+        let seeds = search_seeds(&request(
+            &["/p/src/InvoiceStore.ts", "/p/src/invoice_store.rs"],
+            Some("tax rounding"),
+        ));
+        assert_eq!(seeds, vec!["tax rounding", "invoice store"]);
+
+        // Falls back to the project name when nothing else is available
+        let seeds = search_seeds(&request(&[], None));
+        assert_eq!(seeds, vec!["billing api"]);
+    }
+
+    #[test]
+    fn test_rank_results_boosts_workspace_and_skips_recent() {
+        let req = request(&["/home/user/projects/billing-api/src/tax.rs"], None);
+        let hits = vec![
+            hit(1, "/home/user/notes/tax-rules.md", 0.03),
+            hit(2, "/home/user/projects/billing-api/docs/tax.md", 0.025),
+            hit(3, "/home/user/projects/billing-api/src/tax.rs", 0.05),
+            hit(1, "/home/user/notes/tax-rules.md", 0.01),
+        ];
+        let items = rank_results(&req, hits, 10);
+
+        assert_eq!(items.len(), 2);
+        // 0.025 * 1.5 = 0.0375 < 0.04 (two hits on the notes doc)
+        assert_eq!(items[0].filename, "tax-rules.md");
+        assert!(!items[0].in_workspace);
+        assert!(items[1].in_workspace);
+    }
+}
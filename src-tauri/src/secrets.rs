@@ -0,0 +1,254 @@
+//! Secrets kept in the OS keychain instead of `settings.json`.
+//!
+//! Values are stored with the platform credential store: Windows Credential
+//! Manager, macOS Keychain, or the Secret Service (GNOME Keyring/KWallet via
+//! libsecret's D-Bus API) on Linux. Settings refer to a secret by name: an
+//! MCP server env value of `${secret:github-token}` is looked up when the
+//! server starts. Secret-looking env values of MCP servers are moved to the
+//! keychain automatically: when a server is added, when settings are saved,
+//! and at startup for values an older version left in plaintext. Mobile
+//! builds have no keychain access.
+
+use std::collections::HashMap;
+
+use crate::error::{GhostError, Result};
+use crate::protocols::McpServerEntry;
+
+const REFERENCE_PREFIX: &str = "${secret:";
+const REFERENCE_SUFFIX: &str = "}";
+
+/// Longest secret name accepted.
+const MAX_NAME_LEN: usize = 128;
+
+/// Names are ASCII letters, digits and `-_./`.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(GhostError::Secret(format!(
+            "Secret names must be 1-{} letters, digits or -_./",
+            MAX_NAME_LEN
+        )))
+    }
+}
+
+/// The settings value referring to the secret `name`.
+pub fn reference(name: &str) -> String {
+    format!("{}{}{}", REFERENCE_PREFIX, name, REFERENCE_SUFFIX)
+}
+
+/// The secret name a settings value refers to, if it is a reference.
+pub fn referenced_name(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(REFERENCE_PREFIX)?
+        .strip_suffix(REFERENCE_SUFFIX)
+}
+
+/// Store (or replace) a secret.
+pub fn set(name: &str, value: &str) -> Result<()> {
+    validate_name(name)?;
+    keychain::set(name, value)
+}
+
+/// Read a secret. `None` if it was never stored.
+pub fn get(name: &str) -> Result<Option<String>> {
+    validate_name(name)?;
+    keychain::get(name)
+}
+
+/// Delete a secret. Returns false if it didn't exist.
+pub fn delete(name: &str) -> Result<bool> {
+    validate_name(name)?;
+    keychain::delete(name)
+}
+
+#[cfg(desktop)]
+mod keychain {
+    use crate::error::{GhostError, Result};
+
+    /// Keychain service all Ghost secrets are stored under.
+    const SERVICE: &str = "com.ghost.app";
+
+    fn entry(name: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(SERVICE, name).map_err(error)
+    }
+
+    fn error(e: keyring::Error) -> GhostError {
+        GhostError::Secret(format!("Keychain error: {}", e))
+    }
+
+    pub fn set(name: &str, value: &str) -> Result<()> {
+        entry(name)?.set_password(value).map_err(error)
+    }
+
+    pub fn get(name: &str) -> Result<Option<String>> {
+        match entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(error(e)),
+        }
+    }
+
+    pub fn delete(name: &str) -> Result<bool> {
+        match entry(name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(error(e)),
+        }
+    }
+}
+
+#[cfg(mobile)]
+mod keychain {
+    use crate::error::{GhostError, Result};
+
+    fn unavailable() -> GhostError {
+        GhostError::Secret("The OS keychain is not available on this platform".into())
+    }
+
+    pub fn set(_name: &str, _value: &str) -> Result<()> {
+        Err(unavailable())
+    }
+
+    pub fn get(_name: &str) -> Result<Option<String>> {
+        Err(unavailable())
+    }
+
+    pub fn delete(_name: &str) -> Result<bool> {
+        Err(unavailable())
+    }
+}
+
+/// Replace secret references in env values with the stored secrets.
+pub fn resolve_env(env: &HashMap<String, String>) -> Result<HashMap<String, String>> {
+    env.iter()
+        .map(|(key, value)| {
+            let value = match referenced_name(value) {
+                Some(name) => get(name)?.ok_or_else(|| {
+                    GhostError::Secret(format!("Secret '{}' (for {}) is not set", name, key))
+                })?,
+                None => value.clone(),
+            };
+            Ok((key.clone(), value))
+        })
+        .collect()
+}
+
+/// Name under which a value belonging to an MCP server is stored.
+pub(crate) fn mcp_secret_name(server: &str, key: &str) -> String {
+    let server: String = server
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("mcp/{}/{}", server, key)
+}
+
+/// Move secret-looking env values (tokens, keys, passwords) of an MCP server
+/// into the keychain, leaving references behind. Values the keychain can't
+/// take stay in plaintext. Returns how many were moved.
+pub fn store_mcp_env(entry: &mut McpServerEntry) -> usize {
+    let mut moved = 0;
+    for (key, value) in entry.env.iter_mut() {
+        if referenced_name(value).is_some()
+            || value.is_empty()
+            || !crate::snapshot::is_secret_name(key)
+        {
+            continue;
+        }
+        let name = mcp_secret_name(&entry.name, key);
+        match set(&name, value) {
+            Ok(()) => {
+                *value = reference(&name);
+                moved += 1;
+            }
+            Err(e) => tracing::warn!("Keeping {} of '{}' in settings: {}", key, entry.name, e),
+        }
+    }
+    moved
+}
+
+/// Run [`store_mcp_env`] over every MCP server. Returns how many values were
+/// moved, so callers know whether the settings need saving.
+pub fn store_all_mcp_env(servers: &mut [McpServerEntry]) -> usize {
+    servers.iter_mut().map(store_mcp_env).sum()
+}
+
+/// Delete the secrets [`store_mcp_env`] stored for a removed MCP server,
+/// and its OAuth tokens. Secrets the user named themselves are kept.
+pub fn forget_mcp_env(entry: &McpServerEntry) {
+    if entry.url.is_some() {
+        crate::protocols::mcp_oauth::forget(&entry.name);
+    }
+    for (key, value) in &entry.env {
+        let name = mcp_secret_name(&entry.name, key);
+        if referenced_name(value) == Some(name.as_str()) {
+            if let Err(e) = delete(&name) {
+                tracing::warn!("Failed to delete secret {}: {}", name, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references() {
+        assert_eq!(reference("github-token"), "${secret:github-token}");
+        assert_eq!(
+            referenced_name("${secret:github-token}"),
+            Some("github-token")
+        );
+        assert_eq!(referenced_name("ghp_plaintext"), None);
+        assert_eq!(referenced_name("${secret:unterminated"), None);
+        assert_eq!(
+            mcp_secret_name("My GitHub", "GITHUB_TOKEN"),
+            "mcp/My-GitHub/GITHUB_TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("mcp/github/GITHUB_TOKEN").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name(&"a".repeat(200)).is_err());
+    }
+
+    #[cfg(desktop)]
+    #[test]
+    fn test_store_all_mcp_env_moves_plaintext_secrets() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let mut servers: Vec<McpServerEntry> = serde_json::from_value(serde_json::json!([{
+            "name": "github",
+            "transport": "stdio",
+            "command": "npx",
+            "env": {
+                "GITHUB_TOKEN": "ghp_plaintext",
+                "LOG_LEVEL": "debug",
+                "API_KEY": "${secret:my-key}"
+            }
+        }]))
+        .unwrap();
+
+        assert_eq!(store_all_mcp_env(&mut servers), 1);
+        let env = &servers[0].env;
+        assert_eq!(env["GITHUB_TOKEN"], reference("mcp/github/GITHUB_TOKEN"));
+        assert_eq!(env["LOG_LEVEL"], "debug");
+        assert_eq!(env["API_KEY"], "${secret:my-key}");
+        // Already migrated
+        assert_eq!(store_all_mcp_env(&mut servers), 0);
+    }
+
+    #[test]
+    fn test_resolve_env_passes_plain_values() {
+        let env = HashMap::from([("DB_PATH".to_string(), "/tmp/db".to_string())]);
+        assert_eq!(resolve_env(&env).unwrap(), env);
+    }
+}
@@ -0,0 +1,199 @@
+//! Session restore — putting the user back where they were after a restart.
+//!
+//! The frontend reports the active conversation and the last search query
+//! through `save_session_state`; the main window's position and size are read
+//! by the backend at the same time and again on quit. Everything is kept in
+//! `session.json` next to the settings. `get_session_state` returns the saved
+//! state together with the background jobs that are still queued or running.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::jobs::JobInfo;
+
+/// Smallest window size worth restoring; anything smaller is a glitch.
+const MIN_WINDOW_SIZE: u32 = 200;
+
+/// Main window position and size, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// What is saved between runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    #[serde(default)]
+    pub last_conversation_id: Option<i64>,
+    #[serde(default)]
+    pub last_search_query: Option<String>,
+    /// Filled in by the backend; a frontend update never clears it.
+    #[serde(default)]
+    pub window: Option<WindowGeometry>,
+}
+
+/// Saved state plus live job status, as returned to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoredSession {
+    #[serde(flatten)]
+    pub state: SessionState,
+    /// Jobs still queued or running (interrupted jobs resume on startup).
+    pub active_jobs: Vec<JobInfo>,
+}
+
+impl SessionState {
+    /// Load the saved session. Missing or unreadable files give an empty one.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save atomically (temp file + rename).
+    pub fn save(&self, path: &Path) -> crate::error::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, &json)?;
+        if std::fs::rename(&tmp, path).is_err() {
+            std::fs::write(path, &json)?;
+            let _ = std::fs::remove_file(&tmp);
+        }
+        Ok(())
+    }
+
+    /// Apply a frontend update: conversation and query are replaced, the
+    /// window geometry only when the update carries one.
+    pub fn merge(&mut self, update: SessionState) {
+        self.last_conversation_id = update.last_conversation_id;
+        self.last_search_query = update.last_search_query.filter(|q| !q.trim().is_empty());
+        if update.window.is_some() {
+            self.window = update.window;
+        }
+    }
+}
+
+/// Whether a saved window is a sensible size and its title bar area is on one
+/// of the monitors (given as x, y, width, height), so it can be grabbed.
+pub fn is_on_screen(window: &WindowGeometry, monitors: &[(i32, i32, u32, u32)]) -> bool {
+    if window.width < MIN_WINDOW_SIZE || window.height < MIN_WINDOW_SIZE {
+        return false;
+    }
+    // A point just inside the top edge, a little right of the left corner
+    let (px, py) = (window.x as i64 + 50, window.y as i64 + 10);
+    monitors.iter().any(|&(x, y, w, h)| {
+        let (x, y) = (x as i64, y as i64);
+        px >= x && px < x + w as i64 && py >= y && py < y + h as i64
+    })
+}
+
+/// Current geometry of the main window.
+#[cfg(desktop)]
+pub fn capture_window(app: &tauri::AppHandle) -> Option<WindowGeometry> {
+    use tauri::Manager;
+    let window = app.get_webview_window("main")?;
+    let maximized = window.is_maximized().unwrap_or(false);
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+    })
+}
+
+/// Move and resize the main window to the saved geometry, if it still fits
+/// on a connected monitor.
+#[cfg(desktop)]
+pub fn restore_window(app: &tauri::AppHandle, geometry: &WindowGeometry) {
+    use tauri::Manager;
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let monitors: Vec<(i32, i32, u32, u32)> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            let (position, size) = (m.position(), m.size());
+            (position.x, position.y, size.width, size.height)
+        })
+        .collect();
+    if !is_on_screen(geometry, &monitors) {
+        tracing::debug!("Saved window geometry is off-screen — keeping the default");
+        return;
+    }
+    let _ = window.set_size(tauri::PhysicalSize::new(geometry.width, geometry.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(geometry.x, geometry.y));
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_window() {
+        let window = WindowGeometry {
+            x: 10,
+            y: 20,
+            width: 900,
+            height: 700,
+            maximized: false,
+        };
+        let mut state = SessionState {
+            last_conversation_id: Some(3),
+            last_search_query: Some("taxes".into()),
+            window: Some(window),
+        };
+        state.merge(SessionState {
+            last_conversation_id: None,
+            last_search_query: Some("  ".into()),
+            window: None,
+        });
+        assert_eq!(state.last_conversation_id, None);
+        assert_eq!(state.last_search_query, None);
+        assert_eq!(state.window, Some(window));
+
+        let path = std::env::temp_dir().join("ghost_test_session.json");
+        state.save(&path).unwrap();
+        assert_eq!(SessionState::load(&path), state);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(SessionState::load(&path), SessionState::default());
+    }
+
+    #[test]
+    fn test_is_on_screen() {
+        let monitors = [(0, 0, 1920, 1080), (1920, 0, 2560, 1440)];
+        let at = |x, y| WindowGeometry {
+            x,
+            y,
+            width: 800,
+            height: 600,
+            maximized: false,
+        };
+        assert!(is_on_screen(&at(100, 100), &monitors));
+        assert!(is_on_screen(&at(3000, 200), &monitors));
+        // Second monitor unplugged
+        assert!(!is_on_screen(&at(3000, 200), &monitors[..1]));
+        assert!(!is_on_screen(&at(-32000, -32000), &monitors));
+        let tiny = WindowGeometry {
+            width: 50,
+            ..at(100, 100)
+        };
+        assert!(!is_on_screen(&tiny, &monitors));
+    }
+}
@@ -1,13 +1,22 @@
 //! Persistent application settings.
 //!
 //! Settings are stored as JSON in the app data directory and survive restarts.
+//! Saves are atomic and keep the previous [`BACKUP_COUNT`] versions next to
+//! the file. Loading never fails: missing fields and fields of the wrong type
+//! fall back to their defaults one by one, out-of-range values are clamped,
+//! and an unreadable file is replaced by its newest readable backup.
 
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+/// Previous versions of the settings file kept as backups.
+pub const BACKUP_COUNT: usize = 5;
+
 /// Application settings persisted to disk.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     /// Directories to watch and index.
     pub watched_directories: Vec<String>,
@@ -16,6 +25,23 @@ pub struct Settings {
     /// Chat model selection: "auto" or a model ID from the registry.
     #[serde(default = "default_chat_model")]
     pub chat_model: String,
+    /// Local GGUF files imported as chat/agent models (see `import_custom_model`).
+    #[serde(default)]
+    pub custom_models: Vec<crate::chat::models::CustomModel>,
+    /// Small model loaded beside the chat model for trivial requests:
+    /// "auto" (smallest of the same family, RAM permitting), "none", or a model ID.
+    #[serde(default = "default_chat_model")]
+    pub fast_chat_model: String,
+    /// Speed up CPU generation by letting a small draft model propose tokens.
+    #[serde(default = "default_true")]
+    pub speculative_decoding: bool,
+    /// Opt-in remote chat provider, used only in conversations that allow cloud.
+    #[serde(default)]
+    pub remote_llm: crate::chat::remote::RemoteLlmSettings,
+    /// Local OpenAI-compatible server used when the native model is unavailable
+    /// (Ollama by default; LM Studio, llama-server, vLLM).
+    #[serde(default)]
+    pub local_llm_server: crate::chat::openai_compat::LocalServerSettings,
     /// Inference device: "auto", "cpu", "cuda", "metal".
     #[serde(default = "default_chat_device")]
     pub chat_device: String,
@@ -37,9 +63,125 @@ pub struct Settings {
     /// External MCP server connections.
     #[serde(default)]
     pub mcp_servers: Vec<crate::protocols::McpServerEntry>,
+    /// AG-UI WebSocket server configuration.
+    #[serde(default)]
+    pub agui_ws: crate::protocols::agui::AgUiWsConfig,
+    /// OpenAI-compatible local API server configuration.
+    #[serde(default)]
+    pub openai_server: crate::protocols::openai_server::OpenAiServerConfig,
+    /// WebMCP browser extension bridge configuration.
+    #[serde(default)]
+    pub webmcp: crate::protocols::webmcp::WebMcpConfig,
     /// Agent configuration (model selection, safety, skills).
     #[serde(default)]
     pub agent_config: crate::agent::config::AgentConfig,
+    /// Remote A2A agents available for task delegation.
+    #[serde(default)]
+    pub a2a_agents: Vec<crate::protocols::A2aAgentEntry>,
+    /// Where community skills are installed from.
+    #[serde(default)]
+    pub skill_marketplace: crate::agent::skills::marketplace::MarketplaceConfig,
+    /// Embedding backend the vault was last migrated to: "auto", "native", or "ollama".
+    #[serde(default = "default_embedding_backend")]
+    pub embedding_backend: String,
+    /// Registry ID of the embedding model the vault's vectors were made with.
+    /// Changing it takes a re-embedding migration (see `start_reembedding`).
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,
+    /// Bandwidth cap and metered-network policy for model/runtime downloads.
+    #[serde(default)]
+    pub downloads: crate::downloads::DownloadSettings,
+    /// When background indexing holds for battery power or CPU heat.
+    #[serde(default)]
+    pub indexing_throttle: crate::indexer::throttle::ThrottleSettings,
+    /// External commands that extract text for specific file extensions.
+    #[serde(default)]
+    pub extractor_plugins: Vec<crate::indexer::plugins::ExtractorPlugin>,
+    /// Target tokens per chunk for newly indexed files (64-2048).
+    #[serde(default = "default_chunk_size_tokens")]
+    pub chunk_size_tokens: usize,
+    /// Tokens shared by consecutive chunks (at most half the chunk size).
+    #[serde(default = "default_chunk_overlap_tokens")]
+    pub chunk_overlap_tokens: usize,
+    /// Days between automatic vault snapshots (0 turns them off).
+    #[serde(default = "default_vault_snapshot_interval_days")]
+    pub vault_snapshot_interval_days: u32,
+    /// Snapshots kept; older ones are deleted (0 keeps all).
+    #[serde(default = "default_vault_snapshot_keep")]
+    pub vault_snapshot_keep: usize,
+    /// Opt-in anonymous telemetry (only used by builds with the `telemetry` feature).
+    #[serde(default)]
+    pub telemetry: TelemetrySettings,
+    /// Record searches locally for recent/frequent queries and suggestions.
+    #[serde(default = "default_true")]
+    pub search_history_enabled: bool,
+    /// Weights of recency, past opens, and archive paths in search ranking.
+    #[serde(default)]
+    pub ranking: crate::search::ranking::RankingWeights,
+    /// Schedule of the "what's new in my files" digest.
+    #[serde(default)]
+    pub digest: crate::digest::DigestSettings,
+    /// Browser history and bookmarks import (off by default).
+    #[serde(default)]
+    pub browser_import: crate::indexer::importers::browser::BrowserImportSettings,
+    /// Outlook and Apple Mail import (only used by builds with the
+    /// `mail-import` feature).
+    #[serde(default)]
+    pub mail_import: MailImportSettings,
+}
+
+/// Mail stores imported by the `mail-import` feature.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MailImportSettings {
+    /// Read Apple Mail's mailboxes (`~/Library/Mail`, macOS only).
+    #[serde(default)]
+    pub apple_mail: bool,
+    /// Outlook data files (`.pst`/`.ost`) to import.
+    #[serde(default)]
+    pub outlook_files: Vec<String>,
+    /// The `readpst` program (libpst) that reads Outlook files; looked up
+    /// on `PATH` when unset.
+    #[serde(default)]
+    pub readpst_path: Option<String>,
+}
+
+/// Opt-in telemetry preferences. Off by default; when on, reports stay on
+/// this device unless local-only mode is turned off and an endpoint is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetrySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Prepare reports for review but never send them.
+    #[serde(default = "default_true")]
+    pub local_only: bool,
+    /// Where reports are POSTed as JSON.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Differential-privacy budget per report; lower is more private.
+    #[serde(default = "default_telemetry_epsilon")]
+    pub epsilon: f64,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            local_only: true,
+            endpoint: None,
+            epsilon: default_telemetry_epsilon(),
+        }
+    }
+}
+
+impl TelemetrySettings {
+    /// The privacy budget, kept within 0.1-10.
+    pub fn clamped_epsilon(&self) -> f64 {
+        if self.epsilon.is_finite() {
+            self.epsilon.clamp(0.1, 10.0)
+        } else {
+            default_telemetry_epsilon()
+        }
+    }
 }
 
 fn default_chat_model() -> String {
@@ -54,6 +196,30 @@ fn default_chat_max_tokens() -> usize {
 fn default_chat_temperature() -> f64 {
     0.7
 }
+fn default_embedding_backend() -> String {
+    "auto".into()
+}
+fn default_embedding_model() -> String {
+    crate::embeddings::models::DEFAULT_MODEL_ID.into()
+}
+fn default_chunk_size_tokens() -> usize {
+    512
+}
+fn default_chunk_overlap_tokens() -> usize {
+    64
+}
+fn default_vault_snapshot_interval_days() -> u32 {
+    7
+}
+fn default_vault_snapshot_keep() -> usize {
+    12
+}
+fn default_true() -> bool {
+    true
+}
+fn default_telemetry_epsilon() -> f64 {
+    1.0
+}
 
 impl Default for Settings {
     fn default() -> Self {
@@ -61,6 +227,11 @@ impl Default for Settings {
             watched_directories: Vec::new(),
             shortcut: "CmdOrCtrl+Space".to_string(),
             chat_model: default_chat_model(),
+            fast_chat_model: default_chat_model(),
+            speculative_decoding: true,
+            custom_models: Vec::new(),
+            remote_llm: Default::default(),
+            local_llm_server: Default::default(),
             chat_device: default_chat_device(),
             chat_max_tokens: default_chat_max_tokens(),
             chat_temperature: default_chat_temperature(),
@@ -68,37 +239,163 @@ impl Default for Settings {
             launch_on_startup: false,
             mcp_server: Default::default(),
             mcp_servers: Vec::new(),
+            agui_ws: Default::default(),
+            openai_server: Default::default(),
+            webmcp: Default::default(),
             agent_config: Default::default(),
+            a2a_agents: Vec::new(),
+            skill_marketplace: Default::default(),
+            embedding_backend: default_embedding_backend(),
+            embedding_model: default_embedding_model(),
+            downloads: Default::default(),
+            indexing_throttle: Default::default(),
+            extractor_plugins: Vec::new(),
+            chunk_size_tokens: default_chunk_size_tokens(),
+            chunk_overlap_tokens: default_chunk_overlap_tokens(),
+            vault_snapshot_interval_days: default_vault_snapshot_interval_days(),
+            vault_snapshot_keep: default_vault_snapshot_keep(),
+            telemetry: Default::default(),
+            search_history_enabled: true,
+            ranking: Default::default(),
+            digest: Default::default(),
+            browser_import: Default::default(),
+            mail_import: Default::default(),
         }
     }
 }
 
 impl Settings {
-    /// Load settings from a JSON file. Returns defaults if file doesn't exist.
+    /// Chunk size and overlap for the indexer, clamped to usable values.
+    pub fn chunk_config(&self) -> crate::indexer::chunker::ChunkConfig {
+        crate::indexer::chunker::ChunkConfig::new(self.chunk_size_tokens, self.chunk_overlap_tokens)
+    }
+
+    /// Load settings from a JSON file. Returns defaults if file doesn't exist,
+    /// and the newest readable backup if it exists but can't be parsed.
     pub fn load(path: &Path) -> Self {
-        match std::fs::read_to_string(path) {
-            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
-                tracing::warn!("Failed to parse settings file: {} — using defaults", e);
-                Self::default()
-            }),
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
             Err(_) => {
                 tracing::info!("No settings file found, using defaults");
-                Self::default()
+                return Self::default();
+            }
+        };
+        let mut settings = Self::parse(&content).or_else(|| {
+            tracing::warn!("Settings file is corrupt — trying backups");
+            (1..=BACKUP_COUNT).find_map(|n| {
+                let backup = backup_path(path, n);
+                let settings = Self::parse(&std::fs::read_to_string(&backup).ok()?)?;
+                tracing::warn!("Restored settings from {}", backup.display());
+                Some(settings)
+            })
+        });
+        match settings.as_mut() {
+            Some(settings) => {
+                settings.validate();
+            }
+            None => tracing::warn!("No readable settings backup — using defaults"),
+        }
+        settings.unwrap_or_default()
+    }
+
+    /// Parse settings JSON, falling back to defaults field by field: a field
+    /// of the wrong type resets only that field. `None` if the text isn't a
+    /// JSON object at all (e.g. a half-written file).
+    fn parse(content: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(content).ok()?;
+        let fields = value.as_object()?.clone();
+        if let Ok(settings) = serde_json::from_value(value) {
+            return Some(settings);
+        }
+        let mut merged = serde_json::to_value(Self::default()).ok()?;
+        for (key, field) in fields {
+            // Unknown keys (from newer versions) are dropped
+            let Some(slot) = merged.get_mut(&key) else {
+                continue;
+            };
+            let previous = std::mem::replace(slot, field);
+            if serde_json::from_value::<Self>(merged.clone()).is_err() {
+                tracing::warn!("Invalid settings field `{}` — using its default", key);
+                merged[&key] = previous;
             }
         }
+        serde_json::from_value(merged).ok()
+    }
+
+    /// Clamp out-of-range values and replace unusable ones with defaults.
+    /// Returns the names of the fields that were corrected.
+    pub fn validate(&mut self) -> Vec<&'static str> {
+        let defaults = Self::default();
+        let mut fixed = Vec::new();
+        if self.shortcut.trim().is_empty() {
+            self.shortcut = defaults.shortcut;
+            fixed.push("shortcut");
+        }
+        if !(0.0..=2.0).contains(&self.chat_temperature) {
+            self.chat_temperature = if self.chat_temperature.is_finite() {
+                self.chat_temperature.clamp(0.0, 2.0)
+            } else {
+                defaults.chat_temperature
+            };
+            fixed.push("chat_temperature");
+        }
+        if self.chat_max_tokens == 0 {
+            self.chat_max_tokens = defaults.chat_max_tokens;
+            fixed.push("chat_max_tokens");
+        }
+        let chunking = self.chunk_config();
+        if (chunking.chunk_size, chunking.overlap)
+            != (self.chunk_size_tokens, self.chunk_overlap_tokens)
+        {
+            self.chunk_size_tokens = chunking.chunk_size;
+            self.chunk_overlap_tokens = chunking.overlap;
+            fixed.push("chunk_size_tokens");
+        }
+        if crate::embeddings::models::find_model(&self.embedding_model).is_none() {
+            self.embedding_model = defaults.embedding_model;
+            fixed.push("embedding_model");
+        }
+        for field in &fixed {
+            tracing::warn!("Settings field `{}` was out of range — corrected", field);
+        }
+        fixed
+    }
+
+    /// Reset one top-level section (e.g. "ranking", "downloads") to its defaults.
+    pub fn reset_section(&mut self, section: &str) -> std::result::Result<(), String> {
+        let defaults = serde_json::to_value(Self::default()).map_err(|e| e.to_string())?;
+        let default_value = defaults
+            .get(section)
+            .ok_or_else(|| format!("Unknown settings section: {}", section))?;
+        let mut current = serde_json::to_value(&*self).map_err(|e| e.to_string())?;
+        current[section] = default_value.clone();
+        *self = serde_json::from_value(current).map_err(|e| e.to_string())?;
+        Ok(())
     }
 
     /// Save settings to a JSON file.
-    /// Uses atomic write (temp file + rename) to prevent data loss on crash.
+    /// Uses atomic write (synced temp file + rename) to prevent data loss on
+    /// crash, and keeps the previous version as a backup.
     pub fn save(&self, path: &Path) -> crate::error::Result<()> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         let json = serde_json::to_string_pretty(self)?;
+        match std::fs::read_to_string(path) {
+            Ok(previous) if previous == json => return Ok(()),
+            Ok(_) => rotate_backups(path),
+            Err(_) => {}
+        }
         // Write to temp file first, then atomically rename to prevent corruption.
         // If rename fails (e.g. cross-device on some platforms), fall back to direct write.
         let tmp = path.with_extension("json.tmp");
-        std::fs::write(&tmp, &json)?;
+        {
+            let mut file = std::fs::File::create(&tmp)?;
+            file.write_all(json.as_bytes())?;
+            file.sync_all()?;
+        }
+        // Settings hold API keys — keep them private to the current OS user.
+        crate::isolation::make_private(&tmp);
         if std::fs::rename(&tmp, path).is_err() {
             // Rename failed — fall back to direct overwrite and clean up temp file
             std::fs::write(path, &json)?;
@@ -109,10 +406,28 @@ impl Settings {
     }
 }
 
+/// Path of the `n`th most recent backup ("settings.json.1.bak").
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", n));
+    path.with_file_name(name)
+}
+
+/// Shift backups by one (dropping the oldest) and back up the current file.
+fn rotate_backups(path: &Path) {
+    for n in (1..BACKUP_COUNT).rev() {
+        let _ = std::fs::rename(backup_path(path, n), backup_path(path, n + 1));
+    }
+    let backup = backup_path(path, 1);
+    if let Err(e) = std::fs::copy(path, &backup) {
+        tracing::warn!("Failed to back up settings: {}", e);
+    }
+    crate::isolation::make_private(&backup);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_default_settings() {
@@ -128,6 +443,11 @@ mod tests {
             watched_directories: vec!["/home/user/docs".to_string()],
             shortcut: "CmdOrCtrl+Space".to_string(),
             chat_model: "auto".to_string(),
+            fast_chat_model: "auto".to_string(),
+            speculative_decoding: true,
+            custom_models: Vec::new(),
+            remote_llm: Default::default(),
+            local_llm_server: Default::default(),
             chat_device: "auto".to_string(),
             chat_max_tokens: 512,
             chat_temperature: 0.7,
@@ -135,17 +455,131 @@ mod tests {
             launch_on_startup: false,
             mcp_server: Default::default(),
             mcp_servers: Vec::new(),
+            agui_ws: Default::default(),
+            openai_server: Default::default(),
+            webmcp: Default::default(),
             agent_config: Default::default(),
+            a2a_agents: Vec::new(),
+            skill_marketplace: Default::default(),
+            embedding_backend: "ollama".to_string(),
+            embedding_model: "nomic-embed-text".to_string(),
+            downloads: crate::downloads::DownloadSettings {
+                bandwidth_limit_kbps: 2048,
+                allow_metered: true,
+            },
+            indexing_throttle: crate::indexer::throttle::ThrottleSettings {
+                pause_on_battery: false,
+                max_cpu_temp_c: 85,
+            },
+            extractor_plugins: Vec::new(),
+            chunk_size_tokens: 256,
+            chunk_overlap_tokens: 32,
+            vault_snapshot_interval_days: 30,
+            vault_snapshot_keep: 3,
+            telemetry: Default::default(),
+            search_history_enabled: false,
+            ranking: crate::search::ranking::RankingWeights {
+                recency: 1.5,
+                ..Default::default()
+            },
+            digest: Default::default(),
+            browser_import: Default::default(),
+            mail_import: MailImportSettings {
+                apple_mail: true,
+                outlook_files: vec!["/mail/archive.pst".into()],
+                readpst_path: None,
+            },
         };
         settings.save(&tmp).unwrap();
 
         let loaded = Settings::load(&tmp);
         assert_eq!(loaded.watched_directories, vec!["/home/user/docs"]);
         assert_eq!(loaded.chat_model, "auto");
+        assert_eq!(loaded.embedding_backend, "ollama");
+        assert_eq!(loaded.embedding_model, "nomic-embed-text");
+        assert_eq!(loaded.downloads.bandwidth_limit_kbps, 2048);
+        assert!(loaded.downloads.allow_metered);
+        assert!(!loaded.indexing_throttle.pause_on_battery);
+        assert_eq!(loaded.indexing_throttle.max_cpu_temp_c, 85);
+        assert_eq!(loaded.chunk_size_tokens, 256);
+        assert_eq!(loaded.chunk_overlap_tokens, 32);
+        assert!(loaded.mail_import.apple_mail);
+        assert_eq!(loaded.mail_import.outlook_files, vec!["/mail/archive.pst"]);
 
         let _ = std::fs::remove_file(&tmp);
     }
 
+    #[test]
+    fn test_parse_falls_back_per_field() {
+        let settings = Settings::parse(
+            r#"{"chat_model": "qwen2.5-3b", "chat_max_tokens": "lots", "future_option": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.chat_model, "qwen2.5-3b");
+        assert_eq!(settings.chat_max_tokens, 512);
+        assert_eq!(settings.shortcut, "CmdOrCtrl+Space");
+        assert!(Settings::parse(r#"{"chat_model": "qwen"#).is_none());
+        assert!(Settings::parse("[]").is_none());
+    }
+
+    #[test]
+    fn test_validate_clamps_values() {
+        let mut settings = Settings {
+            chat_temperature: 9.0,
+            chunk_size_tokens: 10,
+            embedding_model: "removed-model".into(),
+            ..Default::default()
+        };
+        let fixed = settings.validate();
+        assert_eq!(
+            fixed,
+            ["chat_temperature", "chunk_size_tokens", "embedding_model"]
+        );
+        assert_eq!(settings.chat_temperature, 2.0);
+        assert_eq!(settings.chunk_size_tokens, 64);
+        assert!(Settings::default().validate().is_empty());
+    }
+
+    #[test]
+    fn test_backups_restore_corrupt_file() {
+        let dir = std::env::temp_dir().join(format!("ghost_settings_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("settings.json");
+        for model in ["first", "second", "third"] {
+            let settings = Settings {
+                chat_model: model.into(),
+                ..Default::default()
+            };
+            settings.save(&path).unwrap();
+        }
+        assert!(backup_path(&path, 2).exists());
+        assert!(!backup_path(&path, 3).exists());
+
+        // A half-written file falls back to the newest backup
+        std::fs::write(&path, r#"{"chat_model": "thi"#).unwrap();
+        assert_eq!(Settings::load(&path).chat_model, "second");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reset_section() {
+        let mut settings = Settings {
+            chat_model: "qwen2.5-3b".into(),
+            ranking: crate::search::ranking::RankingWeights {
+                recency: 3.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        settings.reset_section("ranking").unwrap();
+        assert_eq!(
+            settings.ranking.recency,
+            Settings::default().ranking.recency
+        );
+        assert_eq!(settings.chat_model, "qwen2.5-3b");
+        assert!(settings.reset_section("nope").is_err());
+    }
+
     #[test]
     fn test_load_missing_file() {
         let settings = Settings::load(&PathBuf::from("/nonexistent/settings.json"));
@@ -0,0 +1,417 @@
+//! Support snapshots — the app's state bundled into one archive.
+//!
+//! `create_state_snapshot` writes a zip that a user can attach to a bug report:
+//! - `manifest.json` — app version, OS/arch, vault schema version, file list
+//! - `settings.json` — settings with secrets redacted
+//! - `mcp.json` — configured MCP servers (redacted) and their live status
+//! - `models.json` — chat model list and engine status
+//! - `vault.json` — schema version, sqlite-vec availability, counts, tables
+//! - `jobs.json` — A2A tasks, pending tool approvals, re-embedding progress
+//!
+//! Document contents, chunks, and conversations are never included.
+//!
+//! `load_state_snapshot` restores a snapshot's settings on a dev machine so
+//! the reported setup can be reproduced. It is only available in debug builds.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GhostError, Result};
+use crate::protocols::McpServerEntry;
+use crate::settings::Settings;
+use crate::AppState;
+
+/// Snapshot archive format version.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Placeholder written in place of secret values.
+pub const REDACTED: &str = "***REDACTED***";
+
+/// Substrings marking a CLI flag or `KEY=value` pair as secret.
+const SECRET_HINTS: &[&str] = &["token", "key", "secret", "password", "auth"];
+
+/// Describes the contents of a snapshot archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub os: String,
+    pub arch: String,
+    pub schema_version: i64,
+    pub files: Vec<String>,
+}
+
+/// Result of loading a snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedSnapshot {
+    pub manifest: SnapshotManifest,
+    /// Where the previous settings were backed up.
+    pub backup_path: String,
+    /// MCP servers disabled because their secrets were redacted.
+    pub disabled_servers: Vec<String>,
+}
+
+fn snapshot_err(context: &str, e: impl std::fmt::Display) -> GhostError {
+    GhostError::Snapshot(format!("{}: {}", context, e))
+}
+
+/// Strip credentials, query string, and fragment from a URL.
+fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            let _ = parsed.set_password(None);
+            let _ = parsed.set_username("");
+            parsed.set_query(None);
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => url.split(['?', '#']).next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Whether a setting or env var name suggests a credential ("GITHUB_TOKEN").
+pub(crate) fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// Redact secrets from an MCP server entry.
+///
+/// Env values are always redacted. Args are redacted when they repeat an env
+/// value, follow a secret-looking flag, or are secret-looking `KEY=value` pairs.
+fn redact_mcp_entry(entry: &McpServerEntry) -> McpServerEntry {
+    let mut redacted = entry.clone();
+    let secrets: Vec<&str> = entry
+        .env
+        .values()
+        .map(String::as_str)
+        .filter(|v| v.len() >= 4)
+        .collect();
+
+    let mut prev_flag_secret = false;
+    redacted.args = entry
+        .args
+        .iter()
+        .map(|arg| {
+            let after_secret_flag = prev_flag_secret;
+            prev_flag_secret = arg.starts_with('-') && !arg.contains('=') && is_secret_name(arg);
+
+            if after_secret_flag || secrets.iter().any(|s| arg.contains(s)) {
+                return REDACTED.to_string();
+            }
+            if let Some((name, _)) = arg.split_once('=') {
+                if is_secret_name(name) {
+                    return format!("{}={}", name, REDACTED);
+                }
+            }
+            if arg.starts_with("http://") || arg.starts_with("https://") {
+                return redact_url(arg);
+            }
+            arg.clone()
+        })
+        .collect();
+    for value in redacted.env.values_mut() {
+        *value = REDACTED.to_string();
+    }
+    redacted.url = entry.url.as_deref().map(redact_url);
+    redacted
+}
+
+/// Copy of the settings that is safe to share.
+pub fn redact_settings(settings: &Settings) -> Settings {
+    let mut redacted = settings.clone();
+    redacted.mcp_servers = settings.mcp_servers.iter().map(redact_mcp_entry).collect();
+    for agent in &mut redacted.a2a_agents {
+        agent.url = redact_url(&agent.url);
+    }
+    if redacted.local_llm_server.api_key.is_some() {
+        redacted.local_llm_server.api_key = Some(REDACTED.to_string());
+    }
+    if redacted.remote_llm.api_key.is_some() {
+        redacted.remote_llm.api_key = Some(REDACTED.to_string());
+    }
+    if redacted.openai_server.api_key.is_some() {
+        redacted.openai_server.api_key = Some(REDACTED.to_string());
+    }
+    if redacted.mcp_server.auth_token.is_some() {
+        redacted.mcp_server.auth_token = Some(REDACTED.to_string());
+    }
+    redacted
+}
+
+/// Write a snapshot of the app state to `path` (a `.zip` file).
+pub async fn create_state_snapshot(state: &AppState, path: &Path) -> Result<SnapshotManifest> {
+    let settings = state
+        .settings
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let redacted = redact_settings(&settings);
+
+    let schema_version = state.db.with_conn(crate::db::schema::schema_version)?;
+    let tables = state.db.with_conn(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut tables = Vec::new();
+        for row in rows {
+            tables.push(row?);
+        }
+        Ok(tables)
+    })?;
+
+    let files: Vec<(&str, serde_json::Value)> = vec![
+        ("settings.json", serde_json::to_value(&redacted)?),
+        (
+            "mcp.json",
+            serde_json::json!({
+                "servers": redacted.mcp_servers,
+                "status": state.mcp_client.list_servers().await,
+            }),
+        ),
+        (
+            "models.json",
+            serde_json::json!({
+                "chat_status": state.chat_engine.status(),
+                "chat_models": state.chat_engine.available_models(),
+                "embedding_status": state.embedding_engine.status(),
+            }),
+        ),
+        (
+            "vault.json",
+            serde_json::json!({
+                "schema_version": schema_version,
+                "vec_enabled": state.db.is_vec_enabled(),
+                "stats": state.db.get_stats()?,
+                "tables": tables,
+            }),
+        ),
+        (
+            "jobs.json",
+            serde_json::json!({
+                "a2a_tasks": state.a2a_tasks.list(&Default::default()),
+                "pending_approvals": state.tool_approvals.pending_ids(),
+                "reembedding": state.reembed_job.status(&state.db).ok(),
+            }),
+        ),
+    ];
+
+    let manifest = SnapshotManifest {
+        format: SNAPSHOT_FORMAT,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        schema_version,
+        files: files.iter().map(|(name, _)| name.to_string()).collect(),
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut entries = vec![("manifest.json", serde_json::to_vec_pretty(&manifest)?)];
+    for (name, value) in &files {
+        entries.push((*name, serde_json::to_vec_pretty(value)?));
+    }
+    for (name, contents) in entries {
+        zip.start_file(name, options)
+            .map_err(|e| snapshot_err("Failed to write snapshot", e))?;
+        zip.write_all(&contents)?;
+    }
+    zip.finish()
+        .map_err(|e| snapshot_err("Failed to finish snapshot", e))?;
+
+    Ok(manifest)
+}
+
+fn read_entry<T: serde::de::DeserializeOwned>(
+    archive: &mut zip::ZipArchive<std::io::BufReader<std::fs::File>>,
+    name: &str,
+) -> Result<T> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| snapshot_err(&format!("Snapshot is missing {}", name), e))?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Restore a snapshot's settings on a dev machine.
+///
+/// The current settings file is backed up next to `settings_path` first.
+/// MCP servers whose secrets were redacted are disabled so they don't start
+/// with placeholder credentials.
+pub fn load_state_snapshot(
+    path: &Path,
+    settings_path: &Path,
+) -> Result<(Settings, LoadedSnapshot)> {
+    if !cfg!(debug_assertions) {
+        return Err(GhostError::Snapshot(
+            "Loading snapshots is only available in development builds".into(),
+        ));
+    }
+
+    let file = std::fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file))
+        .map_err(|e| snapshot_err("Not a snapshot archive", e))?;
+
+    let manifest: SnapshotManifest = read_entry(&mut archive, "manifest.json")?;
+    if manifest.format > SNAPSHOT_FORMAT {
+        return Err(GhostError::Snapshot(format!(
+            "Snapshot format {} is newer than supported ({})",
+            manifest.format, SNAPSHOT_FORMAT
+        )));
+    }
+    let mut settings: Settings = read_entry(&mut archive, "settings.json")?;
+    // The MCP server token was redacted; issue a fresh one
+    if settings.mcp_server.auth_token.as_deref() == Some(REDACTED) {
+        settings.mcp_server.auth_token = None;
+        settings.mcp_server.ensure_token();
+    }
+
+    let mut disabled_servers = Vec::new();
+    for server in &mut settings.mcp_servers {
+        let has_redacted = server.env.values().any(|v| v == REDACTED)
+            || server.args.iter().any(|a| a.contains(REDACTED));
+        if has_redacted && server.enabled {
+            server.enabled = false;
+            disabled_servers.push(server.name.clone());
+        }
+    }
+
+    let backup_path: PathBuf = settings_path.with_extension(format!(
+        "json.bak-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    if settings_path.exists() {
+        std::fs::copy(settings_path, &backup_path)?;
+    }
+    settings.save(settings_path)?;
+
+    tracing::info!(
+        "Loaded state snapshot from {} (Ghost {}, {} {})",
+        path.display(),
+        manifest.app_version,
+        manifest.os,
+        manifest.arch
+    );
+
+    Ok((
+        settings,
+        LoadedSnapshot {
+            manifest,
+            backup_path: backup_path.to_string_lossy().to_string(),
+            disabled_servers,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn secret_server() -> McpServerEntry {
+        McpServerEntry {
+            name: "github".into(),
+            transport: "stdio".into(),
+            command: Some("npx".into()),
+            args: vec![
+                "-y".into(),
+                "server-github".into(),
+                "--token".into(),
+                "ghp_plain".into(),
+                "API_KEY=abc123".into(),
+                "https://api.example.com/v1?key=abc".into(),
+                "ghp_fromenv1234".into(),
+            ],
+            url: None,
+            enabled: true,
+            env: HashMap::from([("GITHUB_TOKEN".into(), "ghp_fromenv1234".into())]),
+            limits: Default::default(),
+            sampling: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("https://user:pw@example.com/mcp?token=x#frag"),
+            "https://example.com/mcp"
+        );
+        assert_eq!(redact_url("not a url?secret=1"), "not a url");
+    }
+
+    #[test]
+    fn test_redact_mcp_entry() {
+        let redacted = redact_mcp_entry(&secret_server());
+        assert_eq!(
+            redacted.args,
+            vec![
+                "-y",
+                "server-github",
+                "--token",
+                REDACTED,
+                "API_KEY=***REDACTED***",
+                "https://api.example.com/v1",
+                REDACTED,
+            ]
+        );
+        assert_eq!(redacted.env["GITHUB_TOKEN"], REDACTED);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ghost_test_snapshot_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("snapshot.zip");
+        let settings_path = dir.join("settings.json");
+
+        let state = crate::test_app_state();
+        {
+            let mut settings = state.settings.lock().unwrap();
+            settings.chat_model = "qwen2.5-3b".into();
+            settings.mcp_servers.push(secret_server());
+            settings.mcp_server.auth_token = Some("ghost_mcp_token_secret".into());
+        }
+        let manifest = create_state_snapshot(&state, &archive).await.unwrap();
+        assert_eq!(manifest.schema_version, crate::db::schema::SCHEMA_VERSION);
+        assert!(manifest.files.contains(&"jobs.json".to_string()));
+
+        // No secret survives in the archive
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&archive).unwrap()).unwrap();
+        for i in 0..zip.len() {
+            let mut contents = String::new();
+            zip.by_index(i)
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+            assert!(!contents.contains("ghp_"), "secret leaked");
+            assert!(
+                !contents.contains("ghost_mcp_token_secret"),
+                "MCP token leaked"
+            );
+        }
+
+        Settings::default().save(&settings_path).unwrap();
+        let (restored, loaded) = load_state_snapshot(&archive, &settings_path).unwrap();
+        assert_eq!(restored.chat_model, "qwen2.5-3b");
+        assert_eq!(loaded.disabled_servers, vec!["github"]);
+        assert!(!restored.mcp_servers[0].enabled);
+        let token = restored.mcp_server.auth_token.as_deref().unwrap();
+        assert!(token != REDACTED && token != "ghost_mcp_token_secret");
+        assert!(Path::new(&loaded.backup_path).exists());
+        assert_eq!(Settings::load(&settings_path).chat_model, "qwen2.5-3b");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,387 @@
+//! Opt-in, differentially private telemetry (compiled only with the
+//! `telemetry` feature).
+//!
+//! A report is a weekly aggregate built from the local usage counters
+//! ([`crate::analytics`]): for each tracked feature, a coarse usage bucket
+//! ("none", "1-5", ...), plus a vault-size bucket, the app's minor version and
+//! the OS family. There is no install ID, no timestamps finer than the report
+//! week, no tool names, queries, or paths.
+//!
+//! Every bucket is reported through k-ary randomized response, so each answer
+//! is deniable on its own: with the privacy budget `epsilon` split evenly over
+//! the fields, the true bucket is reported with probability
+//! `e^ε / (e^ε + k - 1)` and any other bucket otherwise. Aggregated over many
+//! users the distribution is still recoverable.
+//!
+//! Reports are prepared and stored locally first so the user sees the exact
+//! JSON that would be sent. Nothing is ever sent without an explicit
+//! `send_telemetry_report` call, and in local-only mode (the default) reports
+//! are only kept on this device.
+
+use serde::Serialize;
+
+use crate::analytics::UsageFeature;
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+use crate::settings::TelemetrySettings;
+
+/// Report format version.
+pub const REPORT_SCHEMA: u32 = 1;
+
+/// Days of usage covered by one report.
+const REPORT_DAYS: u32 = 7;
+
+/// Features reported, in payload order.
+const FEATURES: &[UsageFeature] = &[
+    UsageFeature::Search,
+    UsageFeature::Chat,
+    UsageFeature::AgentRun,
+    UsageFeature::ToolCall,
+    UsageFeature::IndexFile,
+    UsageFeature::IndexDirectory,
+];
+
+/// Weekly usage buckets as (label, upper bound inclusive).
+const USAGE_BUCKETS: &[(&str, i64)] = &[
+    ("none", 0),
+    ("1-5", 5),
+    ("6-20", 20),
+    ("21-100", 100),
+    ("100+", i64::MAX),
+];
+
+/// Indexed-document buckets as (label, upper bound inclusive).
+const VAULT_BUCKETS: &[(&str, i64)] = &[
+    ("<100", 99),
+    ("100-1k", 999),
+    ("1k-10k", 9_999),
+    ("10k-100k", 99_999),
+    ("100k+", i64::MAX),
+];
+
+/// The anonymized payload, exactly as it would be sent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TelemetryPayload {
+    pub schema: u32,
+    /// `major.minor` only.
+    pub app_version: String,
+    pub os: String,
+    pub period_days: u32,
+    /// Total privacy budget spent on this report.
+    pub epsilon: f64,
+    /// Randomized usage bucket per feature, in [`FEATURES`] order.
+    pub usage: Vec<(String, String)>,
+    /// Randomized indexed-document bucket.
+    pub vault_size: String,
+}
+
+/// A prepared report and what happened to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryReport {
+    pub id: i64,
+    pub created_at: String,
+    /// "pending", "sent", "discarded", or "local" (kept in local-only mode).
+    pub status: String,
+    /// Pretty-printed payload JSON.
+    pub payload: String,
+}
+
+/// Initialize the telemetry report table in the database.
+pub fn initialize_telemetry_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS telemetry_reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                status TEXT NOT NULL DEFAULT 'pending',
+                payload TEXT NOT NULL
+            );
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Index of the bucket holding `value`.
+fn bucket_index(buckets: &[(&str, i64)], value: i64) -> usize {
+    buckets
+        .iter()
+        .position(|(_, max)| value <= *max)
+        .unwrap_or(buckets.len() - 1)
+}
+
+/// k-ary randomized response: keep `true_index` with probability
+/// `e^ε / (e^ε + k - 1)`, otherwise report one of the other `k - 1` indices
+/// uniformly. `keep` and `pick` are independent uniform samples in [0, 1).
+fn randomized_response(true_index: usize, k: usize, epsilon: f64, keep: f64, pick: f64) -> usize {
+    if k < 2 {
+        return true_index;
+    }
+    let e = epsilon.exp();
+    let p_keep = e / (e + (k - 1) as f64);
+    if keep < p_keep {
+        return true_index;
+    }
+    let other = ((pick * (k - 1) as f64) as usize).min(k - 2);
+    // Skip over the true index
+    if other >= true_index {
+        other + 1
+    } else {
+        other
+    }
+}
+
+/// `major.minor` of a semver string.
+fn minor_version(version: &str) -> String {
+    version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+/// Build a payload from true counts, randomizing each field with `sample`.
+fn build_payload(
+    counts: &[(UsageFeature, i64)],
+    document_count: i64,
+    epsilon: f64,
+    mut sample: impl FnMut() -> f64,
+) -> TelemetryPayload {
+    // Budget split evenly over the usage fields and the vault size
+    let field_epsilon = epsilon / (FEATURES.len() + 1) as f64;
+    let mut respond = |buckets: &[(&str, i64)], value: i64| -> String {
+        let index = randomized_response(
+            bucket_index(buckets, value),
+            buckets.len(),
+            field_epsilon,
+            sample(),
+            sample(),
+        );
+        buckets[index].0.to_string()
+    };
+
+    let usage = FEATURES
+        .iter()
+        .map(|feature| {
+            let count = counts
+                .iter()
+                .find(|(f, _)| f == feature)
+                .map_or(0, |(_, c)| *c);
+            (feature.as_str().to_string(), respond(USAGE_BUCKETS, count))
+        })
+        .collect();
+    let vault_size = respond(VAULT_BUCKETS, document_count);
+
+    TelemetryPayload {
+        schema: REPORT_SCHEMA,
+        app_version: minor_version(env!("CARGO_PKG_VERSION")),
+        os: std::env::consts::OS.to_string(),
+        period_days: REPORT_DAYS,
+        epsilon,
+        usage,
+        vault_size,
+    }
+}
+
+fn report_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TelemetryReport> {
+    Ok(TelemetryReport {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        status: row.get(2)?,
+        payload: row.get(3)?,
+    })
+}
+
+/// The pending report, preparing one from the last week's usage if there is
+/// none. The stored payload is what [`send_report`] sends, byte for byte.
+pub fn preview_report(db: &Database, settings: &TelemetrySettings) -> Result<TelemetryReport> {
+    if !settings.enabled {
+        return Err(GhostError::Telemetry("Telemetry is turned off".into()));
+    }
+    let pending = db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at, status, payload FROM telemetry_reports
+             WHERE status = 'pending' ORDER BY id DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map([], report_from_row)?;
+        Ok(rows.next().transpose()?)
+    })?;
+    if let Some(report) = pending {
+        return Ok(report);
+    }
+
+    let dashboard = crate::analytics::get_dashboard(db, REPORT_DAYS)?;
+    let counts: Vec<(UsageFeature, i64)> = FEATURES
+        .iter()
+        .map(|feature| {
+            let count = dashboard
+                .totals
+                .iter()
+                .find(|t| t.name == feature.as_str())
+                .map_or(0, |t| t.count);
+            (*feature, count)
+        })
+        .collect();
+    let documents = db.get_stats()?.document_count;
+    let payload = build_payload(
+        &counts,
+        documents,
+        settings.clamped_epsilon(),
+        rand::random::<f64>,
+    );
+    let json = serde_json::to_string_pretty(&payload)?;
+
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO telemetry_reports (payload) VALUES (?1)",
+            rusqlite::params![json],
+        )?;
+        let id = conn.last_insert_rowid();
+        Ok(conn.query_row(
+            "SELECT id, created_at, status, payload FROM telemetry_reports WHERE id = ?1",
+            rusqlite::params![id],
+            report_from_row,
+        )?)
+    })
+}
+
+fn set_status(db: &Database, id: i64, status: &str) -> Result<()> {
+    db.with_conn(|conn| {
+        let changed = conn.execute(
+            "UPDATE telemetry_reports SET status = ?2 WHERE id = ?1 AND status = 'pending'",
+            rusqlite::params![id, status],
+        )?;
+        if changed == 0 {
+            return Err(GhostError::Telemetry(format!(
+                "No pending telemetry report {}",
+                id
+            )));
+        }
+        Ok(())
+    })
+}
+
+/// Send a previewed report. In local-only mode, or without an endpoint, the
+/// report is kept on this device instead. Returns the final status.
+pub async fn send_report(db: &Database, settings: &TelemetrySettings, id: i64) -> Result<String> {
+    if !settings.enabled {
+        return Err(GhostError::Telemetry("Telemetry is turned off".into()));
+    }
+    let payload: String = db.with_conn(|conn| {
+        conn.query_row(
+            "SELECT payload FROM telemetry_reports WHERE id = ?1 AND status = 'pending'",
+            rusqlite::params![id],
+            |row| row.get(0),
+        )
+        .map_err(|_| GhostError::Telemetry(format!("No pending telemetry report {}", id)))
+    })?;
+
+    let endpoint = match settings.endpoint.as_deref() {
+        Some(endpoint) if !settings.local_only && !endpoint.trim().is_empty() => endpoint,
+        _ => {
+            set_status(db, id, "local")?;
+            return Ok("local".into());
+        }
+    };
+
+    crate::ensure_tls_provider();
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .header("Content-Type", "application/json")
+        .body(payload)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(GhostError::Telemetry(format!(
+            "Telemetry endpoint returned {}",
+            response.status()
+        )));
+    }
+    set_status(db, id, "sent")?;
+    tracing::info!("Telemetry report {} sent", id);
+    Ok("sent".into())
+}
+
+/// Drop a pending report without sending it.
+pub fn discard_report(db: &Database, id: i64) -> Result<()> {
+    set_status(db, id, "discarded")
+}
+
+/// All reports, newest first, so users can audit what was sent.
+pub fn list_reports(db: &Database, limit: usize) -> Result<Vec<TelemetryReport>> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at, status, payload FROM telemetry_reports
+             ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![limit as i64], report_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_and_randomized_response() {
+        assert_eq!(bucket_index(USAGE_BUCKETS, 0), 0);
+        assert_eq!(bucket_index(USAGE_BUCKETS, 5), 1);
+        assert_eq!(bucket_index(USAGE_BUCKETS, 6), 2);
+        assert_eq!(bucket_index(USAGE_BUCKETS, 5000), 4);
+        assert_eq!(bucket_index(VAULT_BUCKETS, 1200), 2);
+
+        // ε = ln 4 with k = 4: the truth is kept with probability 4/7
+        let epsilon = 4f64.ln();
+        assert_eq!(randomized_response(2, 4, epsilon, 0.5, 0.0), 2);
+        // Otherwise one of the other three, never the true index
+        assert_eq!(randomized_response(2, 4, epsilon, 0.6, 0.0), 0);
+        assert_eq!(randomized_response(2, 4, epsilon, 0.6, 0.5), 1);
+        assert_eq!(randomized_response(2, 4, epsilon, 0.6, 0.99), 3);
+        assert_eq!(randomized_response(0, 4, epsilon, 0.99, 0.0), 1);
+    }
+
+    #[test]
+    fn test_payload_has_no_identifiers() {
+        // keep-sample 0.0 always reports the true bucket
+        let payload = build_payload(
+            &[(UsageFeature::Search, 12), (UsageFeature::Chat, 1)],
+            250,
+            1.0,
+            || 0.0,
+        );
+        assert_eq!(payload.usage.len(), FEATURES.len());
+        assert_eq!(payload.usage[0], ("search".into(), "6-20".into()));
+        assert_eq!(payload.usage[1], ("chat".into(), "1-5".into()));
+        assert_eq!(payload.usage[2], ("agent_run".into(), "none".into()));
+        assert_eq!(payload.vault_size, "100-1k");
+        assert_eq!(payload.app_version.split('.').count(), 2);
+        assert_eq!(minor_version("1.2.3-beta"), "1.2");
+    }
+
+    #[test]
+    fn test_preview_is_stable_until_resolved() {
+        let db = Database::open_in_memory().unwrap();
+        crate::analytics::initialize_usage_schema(&db).unwrap();
+        initialize_telemetry_schema(&db).unwrap();
+
+        let off = TelemetrySettings::default();
+        assert!(preview_report(&db, &off).is_err());
+
+        let settings = TelemetrySettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let first = preview_report(&db, &settings).unwrap();
+        assert_eq!(first.status, "pending");
+        // The same payload is shown until it is sent or discarded
+        assert_eq!(
+            preview_report(&db, &settings).unwrap().payload,
+            first.payload
+        );
+
+        discard_report(&db, first.id).unwrap();
+        assert!(discard_report(&db, first.id).is_err());
+        let second = preview_report(&db, &settings).unwrap();
+        assert_ne!(second.id, first.id);
+        assert_eq!(list_reports(&db, 10).unwrap()[1].status, "discarded");
+    }
+}
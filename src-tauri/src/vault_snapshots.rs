@@ -0,0 +1,463 @@
+//! Point-in-time vault snapshots.
+//!
+//! A snapshot records what the vault looked like at one moment: every
+//! document's metadata and content hash, plus a short hash of each chunk's
+//! text. No document text is copied, so snapshots stay small. They answer two
+//! questions: "what disappeared or changed since then?" and "search my files
+//! as they were last month" — current results are kept only where the chunk
+//! text is unchanged since the snapshot, and files that have since vanished are
+//! listed by name so lost content can be tracked down.
+//!
+//! Snapshots are taken periodically (`vault_snapshot_interval_days`) and the
+//! newest `vault_snapshot_keep` are retained.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::db::Database;
+use crate::embeddings::EmbeddingEngine;
+use crate::error::{GhostError, Result};
+use crate::search::SearchResult;
+
+/// Most entries returned per list in a diff.
+const MAX_DIFF_ENTRIES: usize = 1000;
+
+/// Candidates fetched per requested point-in-time result, since changed
+/// chunks are dropped.
+const CANDIDATE_FACTOR: usize = 5;
+
+/// A stored snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultSnapshot {
+    pub id: i64,
+    pub label: Option<String>,
+    /// UTC, `YYYY-MM-DD HH:MM:SS`.
+    pub created_at: String,
+    pub document_count: i64,
+    pub chunk_count: i64,
+}
+
+/// A document as recorded in a snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDocument {
+    pub path: String,
+    pub filename: String,
+    pub extension: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub modified_at: String,
+}
+
+/// How the vault changed since a snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub snapshot: VaultSnapshot,
+    /// Files indexed then and gone now.
+    pub removed: Vec<SnapshotDocument>,
+    /// Files whose content changed since (as they were then).
+    pub modified: Vec<SnapshotDocument>,
+    /// Paths indexed now that weren't then.
+    pub added: Vec<String>,
+}
+
+/// Search results as of a snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct PointInTimeResults {
+    pub snapshot: VaultSnapshot,
+    /// Current results whose text is unchanged since the snapshot.
+    pub results: Vec<SearchResult>,
+    /// Files in the snapshot that no longer exist and whose name or path
+    /// matches the query.
+    pub missing: Vec<SnapshotDocument>,
+}
+
+/// Initialize the snapshot tables in the database.
+pub fn initialize_vault_snapshots_schema(db: &Database) -> Result<()> {
+    db.with_conn(|conn| {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS vault_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                document_count INTEGER NOT NULL DEFAULT 0,
+                chunk_count INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS vault_snapshot_documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                snapshot_id INTEGER NOT NULL REFERENCES vault_snapshots(id) ON DELETE CASCADE,
+                path TEXT NOT NULL,
+                filename TEXT NOT NULL,
+                extension TEXT,
+                size_bytes INTEGER,
+                hash TEXT NOT NULL,
+                modified_at TEXT NOT NULL,
+                UNIQUE(snapshot_id, path)
+            );
+
+            -- First 8 bytes of each chunk's SHA-256, not the text itself
+            CREATE TABLE IF NOT EXISTS vault_snapshot_chunks (
+                document_id INTEGER NOT NULL REFERENCES vault_snapshot_documents(id) ON DELETE CASCADE,
+                content_hash INTEGER NOT NULL,
+                PRIMARY KEY (document_id, content_hash)
+            ) WITHOUT ROWID;
+            ",
+        )?;
+        Ok(())
+    })
+}
+
+/// Short, stable hash of a chunk's text.
+fn chunk_hash(content: &str) -> i64 {
+    let digest = Sha256::digest(content.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    i64::from_le_bytes(bytes)
+}
+
+fn snapshot_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<VaultSnapshot> {
+    Ok(VaultSnapshot {
+        id: row.get(0)?,
+        label: row.get(1)?,
+        created_at: row.get(2)?,
+        document_count: row.get(3)?,
+        chunk_count: row.get(4)?,
+    })
+}
+
+fn document_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SnapshotDocument> {
+    Ok(SnapshotDocument {
+        path: row.get(0)?,
+        filename: row.get(1)?,
+        extension: row.get(2)?,
+        size_bytes: row.get(3)?,
+        modified_at: row.get(4)?,
+    })
+}
+
+fn get_snapshot(conn: &Connection, id: i64) -> Result<VaultSnapshot> {
+    conn.query_row(
+        "SELECT id, label, created_at, document_count, chunk_count
+         FROM vault_snapshots WHERE id = ?1",
+        rusqlite::params![id],
+        snapshot_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| GhostError::Snapshot(format!("Vault snapshot {} not found", id)))
+}
+
+fn delete_snapshot_rows(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "DELETE FROM vault_snapshot_chunks WHERE document_id IN
+         (SELECT id FROM vault_snapshot_documents WHERE snapshot_id = ?1)",
+        rusqlite::params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM vault_snapshot_documents WHERE snapshot_id = ?1",
+        rusqlite::params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM vault_snapshots WHERE id = ?1",
+        rusqlite::params![id],
+    )?;
+    Ok(())
+}
+
+/// Record the current vault as a new snapshot, then drop all but the newest
+/// `keep` snapshots (`keep` of 0 keeps everything).
+pub fn create_snapshot(db: &Database, label: Option<&str>, keep: usize) -> Result<VaultSnapshot> {
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT INTO vault_snapshots (label) VALUES (?1)",
+            rusqlite::params![label],
+        )?;
+        let id = conn.last_insert_rowid();
+
+        let document_count = conn.execute(
+            "INSERT INTO vault_snapshot_documents
+                (snapshot_id, path, filename, extension, size_bytes, hash, modified_at)
+             SELECT ?1, path, filename, extension, size_bytes, hash, modified_at FROM documents",
+            rusqlite::params![id],
+        )?;
+
+        // Hash chunk text as it streams out; only the hashes are stored
+        let mut chunk_count = 0i64;
+        {
+            let mut select = conn.prepare(
+                "SELECT sd.id, c.content FROM chunks c
+                 JOIN documents d ON d.id = c.document_id
+                 JOIN vault_snapshot_documents sd ON sd.snapshot_id = ?1 AND sd.path = d.path",
+            )?;
+            let mut insert = conn.prepare(
+                "INSERT OR IGNORE INTO vault_snapshot_chunks (document_id, content_hash)
+                 VALUES (?1, ?2)",
+            )?;
+            let mut rows = select.query(rusqlite::params![id])?;
+            while let Some(row) = rows.next()? {
+                let document_id: i64 = row.get(0)?;
+                let content: String = row.get(1)?;
+                insert.execute(rusqlite::params![document_id, chunk_hash(&content)])?;
+                chunk_count += 1;
+            }
+        }
+        conn.execute(
+            "UPDATE vault_snapshots SET document_count = ?2, chunk_count = ?3 WHERE id = ?1",
+            rusqlite::params![id, document_count as i64, chunk_count],
+        )?;
+
+        if keep > 0 {
+            let mut stmt = conn.prepare("SELECT id FROM vault_snapshots ORDER BY id DESC")?;
+            let old: Vec<i64> = stmt
+                .query_map([], |row| row.get(0))?
+                .skip(keep)
+                .collect::<rusqlite::Result<_>>()?;
+            for old_id in old {
+                delete_snapshot_rows(conn, old_id)?;
+            }
+        }
+
+        tracing::info!(
+            "Vault snapshot {} taken ({} documents, {} chunks)",
+            id,
+            document_count,
+            chunk_count
+        );
+        get_snapshot(conn, id)
+    })
+}
+
+/// Whether the newest snapshot is at least `interval_days` old (or there is
+/// none). Always false when `interval_days` is 0, which turns snapshots off.
+pub fn is_due(db: &Database, interval_days: u32) -> Result<bool> {
+    if interval_days == 0 {
+        return Ok(false);
+    }
+    db.with_read_conn(|conn| {
+        let recent: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM vault_snapshots WHERE created_at > datetime('now', ?1)",
+            rusqlite::params![format!("-{} days", interval_days)],
+            |row| row.get(0),
+        )?;
+        Ok(recent == 0)
+    })
+}
+
+/// All snapshots, newest first.
+pub fn list_snapshots(db: &Database) -> Result<Vec<VaultSnapshot>> {
+    db.with_read_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, label, created_at, document_count, chunk_count
+             FROM vault_snapshots ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], snapshot_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    })
+}
+
+/// Delete one snapshot.
+pub fn delete_snapshot(db: &Database, id: i64) -> Result<()> {
+    db.with_transaction(|conn| {
+        get_snapshot(conn, id)?;
+        delete_snapshot_rows(conn, id)
+    })
+}
+
+/// Compare a snapshot with the vault as it is now.
+pub fn diff_snapshot(db: &Database, id: i64) -> Result<SnapshotDiff> {
+    db.with_read_conn(|conn| {
+        let snapshot = get_snapshot(conn, id)?;
+        let documents = |sql: &str| -> Result<Vec<SnapshotDocument>> {
+            let mut stmt = conn.prepare(sql)?;
+            let rows = stmt.query_map(
+                rusqlite::params![id, MAX_DIFF_ENTRIES as i64],
+                document_from_row,
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        };
+
+        let removed = documents(
+            "SELECT sd.path, sd.filename, sd.extension, sd.size_bytes, sd.modified_at
+             FROM vault_snapshot_documents sd
+             LEFT JOIN documents d ON d.path = sd.path
+             WHERE sd.snapshot_id = ?1 AND d.id IS NULL
+             ORDER BY sd.path LIMIT ?2",
+        )?;
+        let modified = documents(
+            "SELECT sd.path, sd.filename, sd.extension, sd.size_bytes, sd.modified_at
+             FROM vault_snapshot_documents sd
+             JOIN documents d ON d.path = sd.path
+             WHERE sd.snapshot_id = ?1 AND d.hash != sd.hash
+             ORDER BY sd.path LIMIT ?2",
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT d.path FROM documents d
+             WHERE NOT EXISTS (SELECT 1 FROM vault_snapshot_documents sd
+                               WHERE sd.snapshot_id = ?1 AND sd.path = d.path)
+             ORDER BY d.path LIMIT ?2",
+        )?;
+        let added = stmt
+            .query_map(rusqlite::params![id, MAX_DIFF_ENTRIES as i64], |row| {
+                row.get(0)
+            })?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(SnapshotDiff {
+            snapshot,
+            removed,
+            modified,
+            added,
+        })
+    })
+}
+
+/// Whether the snapshot holds this exact chunk text for `path`.
+fn chunk_in_snapshot(db: &Database, id: i64, path: &str, content: &str) -> Result<bool> {
+    db.with_read_conn(|conn| {
+        let found = conn
+            .query_row(
+                "SELECT 1 FROM vault_snapshot_chunks sc
+                 JOIN vault_snapshot_documents sd ON sd.id = sc.document_id
+                 WHERE sd.snapshot_id = ?1 AND sd.path = ?2 AND sc.content_hash = ?3",
+                rusqlite::params![id, path, chunk_hash(content)],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    })
+}
+
+/// Whether every word of the query appears in the file's path.
+fn path_matches(path: &str, query: &str) -> bool {
+    let path = path.to_lowercase();
+    let mut words = query.split_whitespace().peekable();
+    words.peek().is_some() && words.all(|w| path.contains(&w.to_lowercase()))
+}
+
+/// Search the vault as it was when snapshot `id` was taken.
+///
+/// Text that changed since can't be searched (snapshots keep hashes, not
+/// text), so results are current chunks that are byte-for-byte unchanged.
+/// Files that have since disappeared are matched by path.
+pub async fn search_snapshot(
+    db: &Database,
+    embedding_engine: &EmbeddingEngine,
+    id: i64,
+    query: &str,
+    limit: usize,
+) -> Result<PointInTimeResults> {
+    let diff = diff_snapshot(db, id)?;
+
+    let candidates =
+        crate::search::hybrid_search(db, embedding_engine, query, limit * CANDIDATE_FACTOR).await?;
+    let mut results = Vec::new();
+    for result in candidates {
+        let Some(chunk) = db.get_chunk_with_document(result.chunk_id)? else {
+            continue;
+        };
+        if chunk_in_snapshot(db, id, &chunk.path, &chunk.content)? {
+            results.push(result);
+            if results.len() == limit {
+                break;
+            }
+        }
+    }
+    crate::search::annotate_positions(&mut results);
+
+    let missing = diff
+        .removed
+        .into_iter()
+        .filter(|doc| path_matches(&doc.path, query))
+        .take(limit)
+        .collect();
+
+    Ok(PointInTimeResults {
+        snapshot: diff.snapshot,
+        results,
+        missing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_document(db: &Database, path: &str, hash: &str, chunks: &[&str]) -> i64 {
+        let id = db
+            .upsert_document(
+                path,
+                path.rsplit('/').next().unwrap(),
+                Some("md"),
+                10,
+                hash,
+                "2026-02-18T00:00:00Z",
+            )
+            .unwrap();
+        db.delete_chunks_for_document(id).unwrap();
+        for (i, chunk) in chunks.iter().enumerate() {
+            db.insert_chunk(id, i as i32, chunk, 2).unwrap();
+        }
+        id
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_vault_snapshots_schema(&db).unwrap();
+        assert!(is_due(&db, 7).unwrap());
+        assert!(!is_due(&db, 0).unwrap());
+
+        let gone = add_document(&db, "/notes/old plan.md", "h1", &["launch in May"]);
+        add_document(&db, "/notes/budget.md", "h2", &["rent 900", "food 300"]);
+        let snapshot = create_snapshot(&db, Some("before cleanup"), 0).unwrap();
+        assert_eq!(snapshot.document_count, 2);
+        assert_eq!(snapshot.chunk_count, 3);
+        assert!(!is_due(&db, 7).unwrap());
+
+        db.delete_document(gone).unwrap();
+        add_document(&db, "/notes/budget.md", "h3", &["rent 950", "food 300"]);
+        add_document(&db, "/notes/new.md", "h4", &["fresh"]);
+
+        let diff = diff_snapshot(&db, snapshot.id).unwrap();
+        let paths = |docs: &[SnapshotDocument]| -> Vec<String> {
+            docs.iter().map(|d| d.path.clone()).collect()
+        };
+        assert_eq!(paths(&diff.removed), vec!["/notes/old plan.md"]);
+        assert_eq!(paths(&diff.modified), vec!["/notes/budget.md"]);
+        assert_eq!(diff.added, vec!["/notes/new.md"]);
+
+        // Only chunks whose text is unchanged belong to the snapshot
+        assert!(chunk_in_snapshot(&db, snapshot.id, "/notes/budget.md", "food 300").unwrap());
+        assert!(!chunk_in_snapshot(&db, snapshot.id, "/notes/budget.md", "rent 950").unwrap());
+        assert!(path_matches("/notes/old plan.md", "Old PLAN"));
+        assert!(!path_matches("/notes/old plan.md", "budget"));
+        assert!(!path_matches("/notes/old plan.md", "  "));
+    }
+
+    #[test]
+    fn test_snapshot_retention() {
+        let db = Database::open_in_memory().unwrap();
+        initialize_vault_snapshots_schema(&db).unwrap();
+        add_document(&db, "/a.md", "h", &["text"]);
+        for _ in 0..3 {
+            create_snapshot(&db, None, 2).unwrap();
+        }
+        let ids: Vec<i64> = list_snapshots(&db).unwrap().iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![3, 2]);
+        assert!(diff_snapshot(&db, 1).is_err());
+
+        delete_snapshot(&db, 2).unwrap();
+        assert_eq!(list_snapshots(&db).unwrap().len(), 1);
+        let orphans: i64 = db
+            .with_conn(|conn| {
+                Ok(
+                    conn.query_row("SELECT COUNT(*) FROM vault_snapshot_chunks", [], |row| {
+                        row.get(0)
+                    })?,
+                )
+            })
+            .unwrap();
+        assert_eq!(orphans, 1);
+    }
+}
@@ -0,0 +1,250 @@
+//! Named vaults ("Work", "Personal"), each with its own database, watched
+//! directories, settings and session. A vault's database can be encrypted
+//! with a passphrase when the vault is created (see [`crate::db::encryption`]).
+//!
+//! The list of vaults and the active one are kept in `vaults.json` in the app
+//! data directory. The default vault keeps the original file locations, so
+//! existing installs need no migration; other vaults live in
+//! `vaults/<id>/`. Switching swaps the open database in place (see
+//! [`crate::db::Database::replace_with`]), so everything holding the
+//! `AppState` follows the active vault without a restart.
+
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GhostError, Result};
+
+/// The vault every install starts with.
+pub const DEFAULT_VAULT_ID: &str = "default";
+
+/// Tauri event carrying the [`VaultInfo`] of the vault switched to.
+pub const VAULT_SWITCHED_EVENT: &str = "vault://switched";
+
+const REGISTRY_FILE: &str = "vaults.json";
+
+/// Longest vault name accepted, in characters.
+const MAX_NAME_CHARS: usize = 64;
+
+/// A named vault.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultInfo {
+    /// Directory-safe ID derived from the name.
+    pub id: String,
+    pub name: String,
+    /// `None` for the default vault.
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// Filled in when listing.
+    #[serde(default, skip_deserializing)]
+    pub active: bool,
+    /// Filled in when listing.
+    #[serde(default, skip_deserializing)]
+    pub encrypted: bool,
+}
+
+/// Contents of `vaults.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Registry {
+    #[serde(default)]
+    active: Option<String>,
+    /// Vaults other than the default one, oldest first.
+    #[serde(default)]
+    vaults: Vec<VaultInfo>,
+}
+
+static ACTIVE: LazyLock<RwLock<String>> =
+    LazyLock::new(|| RwLock::new(DEFAULT_VAULT_ID.to_string()));
+
+/// Restore the active vault from the registry. Call before loading settings.
+/// A registry naming a vault that no longer exists falls back to the default.
+pub fn init(data_dir: &Path) -> String {
+    let registry = load_registry(data_dir);
+    let id = registry
+        .active
+        .filter(|id| registry.vaults.iter().any(|v| &v.id == id))
+        .unwrap_or_else(|| DEFAULT_VAULT_ID.to_string());
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = id.clone();
+    id
+}
+
+/// ID of the active vault.
+pub fn active_id() -> String {
+    ACTIVE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Directory holding a vault's files.
+pub fn vault_dir(data_dir: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_VAULT_ID {
+        data_dir.to_path_buf()
+    } else {
+        data_dir.join("vaults").join(id)
+    }
+}
+
+pub fn db_path(data_dir: &Path, id: &str) -> PathBuf {
+    vault_dir(data_dir, id).join("ghost_vault.db")
+}
+
+pub fn settings_path(data_dir: &Path, id: &str) -> PathBuf {
+    vault_dir(data_dir, id).join("settings.json")
+}
+
+pub fn session_path(data_dir: &Path, id: &str) -> PathBuf {
+    vault_dir(data_dir, id).join("session.json")
+}
+
+/// All vaults, the default one first.
+pub fn list(data_dir: &Path) -> Vec<VaultInfo> {
+    let active = active_id();
+    let mut vaults = vec![VaultInfo {
+        id: DEFAULT_VAULT_ID.to_string(),
+        name: "Default".to_string(),
+        created_at: None,
+        active: false,
+        encrypted: false,
+    }];
+    vaults.extend(load_registry(data_dir).vaults);
+    for vault in &mut vaults {
+        vault.active = vault.id == active;
+        vault.encrypted = crate::db::encryption::is_encrypted(&db_path(data_dir, &vault.id));
+    }
+    vaults
+}
+
+/// Find a vault by ID.
+pub fn get(data_dir: &Path, id: &str) -> Option<VaultInfo> {
+    list(data_dir).into_iter().find(|v| v.id == id)
+}
+
+/// Register a new, empty vault. Its database is created on first switch,
+/// unless the caller encrypts it right away.
+pub fn create(data_dir: &Path, name: &str) -> Result<VaultInfo> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(GhostError::Vault(format!(
+            "Vault names must be 1-{} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    let existing = list(data_dir);
+    if existing.iter().any(|v| v.name.eq_ignore_ascii_case(&name)) {
+        return Err(GhostError::Vault(format!(
+            "A vault named \"{}\" already exists",
+            name
+        )));
+    }
+    let base = slugify(&name);
+    let id = (1..)
+        .map(|n| match n {
+            1 => base.clone(),
+            n => format!("{}-{}", base, n),
+        })
+        .find(|id| !existing.iter().any(|v| &v.id == id))
+        .unwrap_or(base);
+
+    let vault = VaultInfo {
+        id,
+        name,
+        created_at: Some(chrono::Utc::now().to_rfc3339()),
+        active: false,
+        encrypted: false,
+    };
+    let dir = vault_dir(data_dir, &vault.id);
+    std::fs::create_dir_all(&dir)?;
+    crate::isolation::harden_vault_dir(&dir);
+    let mut registry = load_registry(data_dir);
+    registry.vaults.push(vault.clone());
+    save_registry(data_dir, &registry)?;
+    tracing::info!("Created vault {} ({})", vault.name, vault.id);
+    Ok(vault)
+}
+
+/// Record `id` as the active vault, for this run and the next launch.
+pub fn set_active(data_dir: &Path, id: &str) -> Result<()> {
+    let mut registry = load_registry(data_dir);
+    registry.active = Some(id.to_string());
+    save_registry(data_dir, &registry)?;
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = id.to_string();
+    Ok(())
+}
+
+fn load_registry(data_dir: &Path) -> Registry {
+    std::fs::read_to_string(data_dir.join(REGISTRY_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Save atomically (temp file + rename).
+fn save_registry(data_dir: &Path, registry: &Registry) -> Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    let path = data_dir.join(REGISTRY_FILE);
+    let json = serde_json::to_string_pretty(registry)?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, &json)?;
+    if std::fs::rename(&tmp, &path).is_err() {
+        std::fs::write(&path, &json)?;
+        let _ = std::fs::remove_file(&tmp);
+    }
+    Ok(())
+}
+
+/// Lowercase ASCII letters and digits joined by dashes ("Work 2026" -> "work-2026").
+fn slugify(name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() || slug == DEFAULT_VAULT_ID {
+        "vault".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Work 2026"), "work-2026");
+        assert_eq!(slugify("  Café / Notes "), "caf-notes");
+        assert_eq!(slugify("日本"), "vault");
+        assert_eq!(slugify("Default"), "vault");
+    }
+
+    #[test]
+    fn test_create_and_list_vaults() {
+        let dir = std::env::temp_dir().join(format!("ghost_vaults_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let work = create(&dir, " Work ").unwrap();
+        assert_eq!((work.id.as_str(), work.name.as_str()), ("work", "Work"));
+        assert!(vault_dir(&dir, "work").is_dir());
+        assert!(create(&dir, "work").is_err());
+        assert!(create(&dir, "").is_err());
+        assert_eq!(create(&dir, "Work!").unwrap().id, "work-2");
+
+        let ids: Vec<String> = list(&dir).into_iter().map(|v| v.id).collect();
+        assert_eq!(ids, ["default", "work", "work-2"]);
+        assert_eq!(db_path(&dir, DEFAULT_VAULT_ID), dir.join("ghost_vault.db"));
+        assert_eq!(
+            settings_path(&dir, "work"),
+            dir.join("vaults").join("work").join("settings.json")
+        );
+
+        // An active vault that was removed falls back to the default
+        let registry = Registry {
+            active: Some("gone".into()),
+            ..load_registry(&dir)
+        };
+        save_registry(&dir, &registry).unwrap();
+        assert_eq!(init(&dir), DEFAULT_VAULT_ID);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,677 @@
+//! WASM plugin runtime for third-party extensions.
+//!
+//! A plugin is a directory under `<app data>/plugins/<id>/` holding a
+//! `plugin.json` manifest and a `plugin.wasm` module. Plugins run in wasmtime
+//! with no WASI: they cannot touch the filesystem, network, or environment,
+//! and only reach Ghost through the host functions their manifest's
+//! capabilities grant:
+//!
+//! | Capability       | Host API (module `ghost`)                             |
+//! |------------------|-------------------------------------------------------|
+//! | `read_chunks`    | `chunk_count() -> i32`, `read_chunk(i, ptr, cap) -> i32` |
+//! | `add_tags`       | `add_tag(ptr, len) -> i32`                            |
+//! | `register_tools` | manifest `tools` are offered to the agent              |
+//!
+//! `log(ptr, len)` is always available. Host functions return `-1` when the
+//! capability is not granted and `-2` for invalid arguments. `read_chunk`
+//! copies up to `cap` bytes and returns the chunk's full length.
+//!
+//! Guest exports:
+//! - `memory` and `alloc(len: i32) -> i32` (for host → guest strings)
+//! - `on_document_indexed(document_id: i64)` — optional; runs after a file is
+//!   indexed, with that document's chunks readable
+//! - `call_tool(name_ptr, name_len, args_ptr, args_len) -> i64` — required
+//!   for tools; returns `(ptr << 32) | len` of the UTF-8 result
+//!
+//! Every call gets a fresh instance with a fuel (instruction) budget and a
+//! memory cap, so a buggy or hostile plugin can't hang indexing or exhaust RAM.
+
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use wasmtime::{
+    Caller, Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap,
+};
+
+use crate::agent::tools::RegisteredTool;
+use crate::agent::{AgentTool, AgentToolFunction};
+use crate::db::Database;
+use crate::error::{GhostError, Result};
+
+/// Manifest file name inside a plugin directory.
+const MANIFEST_FILE: &str = "plugin.json";
+
+/// Module file name inside a plugin directory.
+const MODULE_FILE: &str = "plugin.wasm";
+
+/// Instructions (fuel units) a plugin may execute per call.
+const FUEL_PER_CALL: u64 = 500_000_000;
+
+/// Linear memory a plugin instance may grow to.
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Largest string exchanged with a plugin (tag, log line, or tool result).
+const MAX_STRING_BYTES: usize = 1024 * 1024;
+
+/// Host function result: the capability is not granted.
+const DENIED: i32 = -1;
+
+/// Host function result: invalid argument (bad pointer, index, or text).
+const INVALID: i32 = -2;
+
+/// Permission a plugin requests in its manifest.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Read the chunk text of the document being indexed.
+    ReadChunks,
+    /// Add tags to the document being indexed.
+    AddTags,
+    /// Offer tools to the agent.
+    RegisterTools,
+}
+
+/// An agent tool declared by a plugin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginTool {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema of the tool's arguments.
+    #[serde(default = "default_parameters")]
+    pub parameters: serde_json::Value,
+}
+
+fn default_parameters() -> serde_json::Value {
+    json!({"type": "object", "properties": {}, "required": []})
+}
+
+/// Contents of `plugin.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    /// Unique identifier: lowercase letters, digits, `-` and `_`.
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+    #[serde(default)]
+    pub tools: Vec<PluginTool>,
+}
+
+/// A discovered plugin, as reported to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    pub manifest: PluginManifest,
+    pub path: String,
+    /// Whether the module compiled and the manifest is valid.
+    pub loaded: bool,
+    /// Why the plugin could not be loaded.
+    pub error: Option<String>,
+}
+
+struct LoadedPlugin {
+    info: PluginInfo,
+    module: Option<Module>,
+}
+
+static ENGINE: LazyLock<std::result::Result<Engine, String>> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(|e| e.to_string())
+});
+
+static PLUGINS: LazyLock<RwLock<Vec<LoadedPlugin>>> = LazyLock::new(Default::default);
+
+fn plugin_error(e: impl std::fmt::Display) -> GhostError {
+    GhostError::Plugin(e.to_string())
+}
+
+/// Directory scanned for plugins.
+pub fn plugins_dir() -> PathBuf {
+    crate::get_app_data_dir().join("plugins")
+}
+
+/// Why a manifest is invalid, if it is.
+pub fn validate(manifest: &PluginManifest) -> std::result::Result<(), String> {
+    let valid_id = !manifest.id.is_empty()
+        && manifest
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_id {
+        return Err(format!(
+            "Invalid plugin id '{}': use lowercase letters, digits, '-' and '_'",
+            manifest.id
+        ));
+    }
+    if !manifest.tools.is_empty() && !manifest.capabilities.contains(&Capability::RegisterTools) {
+        return Err(format!(
+            "Plugin '{}' declares tools without the register_tools capability",
+            manifest.id
+        ));
+    }
+    for tool in &manifest.tools {
+        if tool.name.is_empty()
+            || !tool
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("Invalid tool name '{}'", tool.name));
+        }
+        // Built-in tool names are reserved
+        if tool.name.starts_with("ghost_") {
+            return Err(format!(
+                "Tool '{}' uses the reserved 'ghost_' prefix",
+                tool.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Read, validate, and compile the plugin in `dir`.
+fn load_plugin(dir: &Path) -> Option<LoadedPlugin> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let manifest: PluginManifest = match std::fs::read_to_string(&manifest_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+    {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::warn!("Skipping plugin {}: {}", manifest_path.display(), e);
+            return None;
+        }
+    };
+
+    let compiled = validate(&manifest).and_then(|()| {
+        let engine = ENGINE.as_ref().map_err(|e| e.clone())?;
+        Module::from_file(engine, dir.join(MODULE_FILE)).map_err(|e| e.to_string())
+    });
+    let (module, error) = match compiled {
+        Ok(module) => (Some(module), None),
+        Err(e) => {
+            tracing::warn!("Plugin '{}' not loaded: {}", manifest.id, e);
+            (None, Some(e))
+        }
+    };
+    Some(LoadedPlugin {
+        info: PluginInfo {
+            manifest,
+            path: dir.to_string_lossy().into_owned(),
+            loaded: module.is_some(),
+            error,
+        },
+        module,
+    })
+}
+
+/// Discover and compile the plugins in `dir`, replacing the loaded set.
+/// Returns the number of plugins loaded.
+pub fn load_all(dir: &Path) -> usize {
+    let mut plugins: Vec<LoadedPlugin> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.join(MANIFEST_FILE).is_file())
+                .filter_map(|path| load_plugin(&path))
+                .collect()
+        })
+        .unwrap_or_default();
+    plugins.sort_by(|a, b| a.info.manifest.id.cmp(&b.info.manifest.id));
+
+    // First plugin wins when two directories claim the same id
+    let mut seen = std::collections::HashSet::new();
+    for plugin in plugins.iter_mut() {
+        if !seen.insert(plugin.info.manifest.id.clone()) {
+            plugin.module = None;
+            plugin.info.loaded = false;
+            plugin.info.error = Some(format!("Duplicate plugin id '{}'", plugin.info.manifest.id));
+        }
+    }
+
+    let loaded = plugins.iter().filter(|p| p.info.loaded).count();
+    if let Ok(mut registered) = PLUGINS.write() {
+        *registered = plugins;
+    }
+    loaded
+}
+
+/// All discovered plugins, including ones that failed to load.
+pub fn list() -> Vec<PluginInfo> {
+    PLUGINS
+        .read()
+        .map(|plugins| plugins.iter().map(|p| p.info.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Agent tools offered by loaded plugins.
+pub fn registered_tools() -> Vec<RegisteredTool> {
+    let Ok(plugins) = PLUGINS.read() else {
+        return Vec::new();
+    };
+    plugins
+        .iter()
+        .filter(|p| p.module.is_some())
+        .flat_map(|p| {
+            p.info.manifest.tools.iter().map(|tool| RegisteredTool {
+                definition: AgentTool {
+                    tool_type: "function".into(),
+                    function: AgentToolFunction {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.parameters.clone(),
+                    },
+                },
+                source: format!("plugin:{}", p.info.manifest.id),
+                requires_approval: false, // Sandboxed: no I/O beyond granted capabilities
+            })
+        })
+        .collect()
+}
+
+/// Per-call state visible to host functions.
+struct HostState {
+    plugin_id: String,
+    capabilities: Vec<Capability>,
+    /// Chunks of the document being indexed (empty for tool calls).
+    chunks: Vec<String>,
+    /// Tags added during the call; applied by the host afterwards.
+    tags: Vec<String>,
+    /// Whether the call has a document to tag.
+    has_document: bool,
+    limits: StoreLimits,
+}
+
+impl HostState {
+    fn allows(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> Option<Memory> {
+    caller.get_export("memory").and_then(|e| e.into_memory())
+}
+
+/// Read a UTF-8 string from guest memory.
+fn read_guest_string(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Option<String> {
+    let (ptr, len) = (usize::try_from(ptr).ok()?, usize::try_from(len).ok()?);
+    if len > MAX_STRING_BYTES {
+        return None;
+    }
+    let memory = guest_memory(caller)?;
+    let mut buf = vec![0u8; len];
+    memory.read(&*caller, ptr, &mut buf).ok()?;
+    String::from_utf8(buf).ok()
+}
+
+/// Host functions exposed to plugins as module `ghost`.
+fn linker(engine: &Engine) -> Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+    linker
+        .func_wrap(
+            "ghost",
+            "chunk_count",
+            |caller: Caller<'_, HostState>| -> i32 {
+                let state = caller.data();
+                if !state.allows(Capability::ReadChunks) {
+                    return DENIED;
+                }
+                state.chunks.len() as i32
+            },
+        )
+        .map_err(plugin_error)?;
+    linker
+        .func_wrap(
+            "ghost",
+            "read_chunk",
+            |mut caller: Caller<'_, HostState>, index: i32, ptr: i32, cap: i32| -> i32 {
+                if !caller.data().allows(Capability::ReadChunks) {
+                    return DENIED;
+                }
+                let chunk = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| caller.data().chunks.get(i))
+                    .cloned();
+                let (Some(chunk), Ok(ptr)) = (chunk, usize::try_from(ptr)) else {
+                    return INVALID;
+                };
+                let bytes = chunk.as_bytes();
+                let n = bytes.len().min(usize::try_from(cap).unwrap_or(0));
+                match guest_memory(&mut caller) {
+                    Some(memory) if memory.write(&mut caller, ptr, &bytes[..n]).is_ok() => {
+                        bytes.len() as i32
+                    }
+                    _ => INVALID,
+                }
+            },
+        )
+        .map_err(plugin_error)?;
+    linker
+        .func_wrap(
+            "ghost",
+            "add_tag",
+            |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i32 {
+                let state = caller.data();
+                if !state.allows(Capability::AddTags) || !state.has_document {
+                    return DENIED;
+                }
+                match read_guest_string(&mut caller, ptr, len)
+                    .and_then(|tag| crate::db::normalize_tag(&tag))
+                {
+                    Some(tag) => {
+                        caller.data_mut().tags.push(tag);
+                        0
+                    }
+                    None => INVALID,
+                }
+            },
+        )
+        .map_err(plugin_error)?;
+    linker
+        .func_wrap(
+            "ghost",
+            "log",
+            |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                if let Some(message) = read_guest_string(&mut caller, ptr, len) {
+                    tracing::info!("[plugin {}] {}", caller.data().plugin_id, message);
+                }
+            },
+        )
+        .map_err(plugin_error)?;
+    Ok(linker)
+}
+
+/// Describe a failed plugin call, naming fuel exhaustion explicitly.
+fn call_error(plugin_id: &str, e: wasmtime::Error) -> GhostError {
+    if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+        GhostError::Plugin(format!(
+            "Plugin '{}' exceeded its instruction budget",
+            plugin_id
+        ))
+    } else {
+        GhostError::Plugin(format!("Plugin '{}' failed: {}", plugin_id, e))
+    }
+}
+
+/// A fresh, resource-limited instance of `module`.
+fn instantiate(
+    manifest: &PluginManifest,
+    module: &Module,
+    chunks: Vec<String>,
+    has_document: bool,
+) -> Result<(Store<HostState>, Instance)> {
+    let mut store = Store::new(
+        module.engine(),
+        HostState {
+            plugin_id: manifest.id.clone(),
+            capabilities: manifest.capabilities.clone(),
+            chunks,
+            tags: Vec::new(),
+            has_document,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_BYTES)
+                .instances(1)
+                .build(),
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(FUEL_PER_CALL).map_err(plugin_error)?;
+    let instance = linker(module.engine())?
+        .instantiate(&mut store, module)
+        .map_err(|e| call_error(&manifest.id, e))?;
+    Ok((store, instance))
+}
+
+/// Run `on_document_indexed` in one plugin and return the tags it added.
+fn run_document_hook(
+    manifest: &PluginManifest,
+    module: &Module,
+    document_id: i64,
+    chunks: Vec<String>,
+) -> Result<Vec<String>> {
+    let (mut store, instance) = instantiate(manifest, module, chunks, true)?;
+    let hook = instance
+        .get_typed_func::<i64, ()>(&mut store, "on_document_indexed")
+        .map_err(plugin_error)?;
+    hook.call(&mut store, document_id)
+        .map_err(|e| call_error(&manifest.id, e))?;
+    Ok(std::mem::take(&mut store.data_mut().tags))
+}
+
+/// Give plugins with an `on_document_indexed` hook a look at a freshly
+/// indexed document. Failures are logged and never fail indexing.
+pub fn on_document_indexed(db: &Database, document_id: i64) {
+    let hooks: Vec<(PluginManifest, Module)> = match PLUGINS.read() {
+        Ok(plugins) => plugins
+            .iter()
+            .filter_map(|p| Some((p.info.manifest.clone(), p.module.clone()?)))
+            .filter(|(_, module)| module.get_export("on_document_indexed").is_some())
+            .collect(),
+        Err(_) => return,
+    };
+    if hooks.is_empty() {
+        return;
+    }
+
+    let chunks: Vec<String> = match db.get_document_chunks(document_id) {
+        Ok(chunks) => chunks.into_iter().map(|(_, content)| content).collect(),
+        Err(e) => {
+            tracing::warn!("Plugins skipped for document {}: {}", document_id, e);
+            return;
+        }
+    };
+    for (manifest, module) in hooks {
+        let chunks = if manifest.capabilities.contains(&Capability::ReadChunks) {
+            chunks.clone()
+        } else {
+            Vec::new()
+        };
+        let source = format!("plugin:{}", manifest.id);
+        let applied = run_document_hook(&manifest, &module, document_id, chunks).and_then(|tags| {
+            // Re-indexing replaces the plugin's earlier tags
+            db.clear_document_tags(document_id, &source)?;
+            for tag in &tags {
+                db.add_document_tag(document_id, tag, &source)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = applied {
+            tracing::warn!(
+                "Plugin '{}' on document {}: {}",
+                manifest.id,
+                document_id,
+                e
+            );
+        }
+    }
+}
+
+/// Copy `bytes` into guest memory through the guest's `alloc` export.
+fn write_guest_bytes(
+    store: &mut Store<HostState>,
+    instance: &Instance,
+    bytes: &[u8],
+) -> wasmtime::Result<(i32, i32)> {
+    let alloc = instance.get_typed_func::<i32, i32>(&mut *store, "alloc")?;
+    let len = bytes.len() as i32;
+    let ptr = alloc.call(&mut *store, len)?;
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("plugin does not export memory"))?;
+    memory.write(&mut *store, ptr as u32 as usize, bytes)?;
+    Ok((ptr, len))
+}
+
+/// Run a plugin tool and return its text result.
+pub fn call_tool(
+    plugin_id: &str,
+    tool_name: &str,
+    arguments: &serde_json::Value,
+) -> Result<String> {
+    let (manifest, module) = {
+        let plugins = PLUGINS.read().map_err(plugin_error)?;
+        let plugin = plugins
+            .iter()
+            .find(|p| p.info.manifest.id == plugin_id)
+            .ok_or_else(|| GhostError::Plugin(format!("Plugin '{}' not found", plugin_id)))?;
+        let module = plugin
+            .module
+            .clone()
+            .ok_or_else(|| GhostError::Plugin(format!("Plugin '{}' is not loaded", plugin_id)))?;
+        (plugin.info.manifest.clone(), module)
+    };
+    if !manifest.capabilities.contains(&Capability::RegisterTools)
+        || !manifest.tools.iter().any(|t| t.name == tool_name)
+    {
+        return Err(GhostError::Plugin(format!(
+            "Plugin '{}' has no tool '{}'",
+            plugin_id, tool_name
+        )));
+    }
+
+    let (mut store, instance) = instantiate(&manifest, &module, Vec::new(), false)?;
+    invoke_tool(&mut store, &instance, tool_name, arguments).map_err(|e| call_error(plugin_id, e))
+}
+
+/// Pass the tool name and JSON arguments to the guest's `call_tool` export
+/// and read back its result.
+fn invoke_tool(
+    store: &mut Store<HostState>,
+    instance: &Instance,
+    tool_name: &str,
+    arguments: &serde_json::Value,
+) -> wasmtime::Result<String> {
+    let (name_ptr, name_len) = write_guest_bytes(store, instance, tool_name.as_bytes())?;
+    let (args_ptr, args_len) =
+        write_guest_bytes(store, instance, arguments.to_string().as_bytes())?;
+    let call = instance.get_typed_func::<(i32, i32, i32, i32), i64>(&mut *store, "call_tool")?;
+    let packed = call.call(&mut *store, (name_ptr, name_len, args_ptr, args_len))?;
+    let (ptr, len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+    if len > MAX_STRING_BYTES {
+        return Err(wasmtime::Error::msg("tool result too large"));
+    }
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("plugin does not export memory"))?;
+    let mut buf = vec![0u8; len];
+    memory.read(&*store, ptr, &mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(capabilities: Vec<Capability>, tools: Vec<PluginTool>) -> PluginManifest {
+        PluginManifest {
+            id: "test-plugin".into(),
+            name: "Test".into(),
+            version: "1.0.0".into(),
+            description: String::new(),
+            capabilities,
+            tools,
+        }
+    }
+
+    fn tool(name: &str) -> PluginTool {
+        PluginTool {
+            name: name.into(),
+            description: "Test tool".into(),
+            parameters: default_parameters(),
+        }
+    }
+
+    fn compile(wat: &str) -> Module {
+        Module::new(ENGINE.as_ref().unwrap(), wat).unwrap()
+    }
+
+    /// Tags every document "seen", plus "long" when its first chunk has
+    /// more than 8 bytes.
+    const TAGGER: &str = r#"
+        (module
+          (import "ghost" "chunk_count" (func $chunk_count (result i32)))
+          (import "ghost" "read_chunk" (func $read_chunk (param i32 i32 i32) (result i32)))
+          (import "ghost" "add_tag" (func $add_tag (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "seen")
+          (data (i32.const 16) "long")
+          (func (export "on_document_indexed") (param i64)
+            (drop (call $add_tag (i32.const 0) (i32.const 4)))
+            (if (i32.gt_s (call $chunk_count) (i32.const 0))
+              (then
+                (if (i32.gt_s (call $read_chunk (i32.const 0) (i32.const 64) (i32.const 8))
+                              (i32.const 8))
+                  (then (drop (call $add_tag (i32.const 16) (i32.const 4)))))))))
+    "#;
+
+    #[test]
+    fn test_validate_manifest() {
+        assert!(validate(&manifest(vec![], vec![])).is_ok());
+        assert!(validate(&manifest(
+            vec![Capability::RegisterTools],
+            vec![tool("lookup")]
+        ))
+        .is_ok());
+
+        // Tools need the capability
+        assert!(validate(&manifest(vec![], vec![tool("lookup")])).is_err());
+        // Built-in names are reserved
+        assert!(validate(&manifest(
+            vec![Capability::RegisterTools],
+            vec![tool("ghost_search")]
+        ))
+        .is_err());
+
+        let mut bad = manifest(vec![], vec![]);
+        bad.id = "../escape".into();
+        assert!(validate(&bad).is_err());
+
+        let parsed: PluginManifest = serde_json::from_str(
+            r#"{"id": "x", "name": "X", "capabilities": ["read_chunks", "add_tags"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.capabilities,
+            vec![Capability::ReadChunks, Capability::AddTags]
+        );
+    }
+
+    #[test]
+    fn test_document_hook_capabilities() {
+        let module = compile(TAGGER);
+        let chunks = vec!["a chunk longer than eight bytes".to_string()];
+
+        let full = manifest(vec![Capability::ReadChunks, Capability::AddTags], vec![]);
+        let tags = run_document_hook(&full, &module, 1, chunks.clone()).unwrap();
+        assert_eq!(tags, vec!["seen", "long"]);
+
+        // Without read_chunks the plugin sees no chunks
+        let no_read = manifest(vec![Capability::AddTags], vec![]);
+        let tags = run_document_hook(&no_read, &module, 1, chunks.clone()).unwrap();
+        assert_eq!(tags, vec!["seen"]);
+
+        // Without add_tags nothing is tagged
+        let no_tags = manifest(vec![Capability::ReadChunks], vec![]);
+        assert!(run_document_hook(&no_tags, &module, 1, chunks)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fuel_limit() {
+        let module =
+            compile(r#"(module (func (export "on_document_indexed") (param i64) (loop (br 0))))"#);
+        let err = run_document_hook(&manifest(vec![], vec![]), &module, 1, Vec::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("instruction budget"), "{}", err);
+    }
+}
@@ -244,7 +244,9 @@ export default function App() {
 
       try {
         // Use AG-UI streaming when chat model is available natively
-        if (chatSt?.backend === "native" || chatSt?.backend === "ollama") {
+        if (chatSt?.backend === "native" ||
+          chatSt?.backend === "ollama" ||
+          chatSt?.backend === "openai_compat") {
           await sendStreaming(newMessages);
           // Response will arrive via AG-UI events → useEffect sync above
         } else {
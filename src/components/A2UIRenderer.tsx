@@ -16,6 +16,7 @@
  *   Layout: Row, Column, Card, Divider, Tabs, Modal, List
  *   Content: Text, Image, Icon, Video
  *   Input: Button, TextField, CheckBox, ChoicePicker, Slider, DateTimeInput
+ *   Ghost extensions: Table, Chart (tool result cards)
  */
 
 import { useCallback, useState } from "react";
@@ -176,6 +177,92 @@ function TabsComponent({
   );
 }
 
+// ---------------------------------------------------------------------------
+// Chart Component (Ghost extension)
+// ---------------------------------------------------------------------------
+
+const CHART_COLORS = ["#6c5ce7", "#22c55e", "#f59e0b", "#ef4444", "#06b6d4", "#ec4899", "#84cc16", "#a855f7"];
+
+interface ChartSeries {
+  name: string;
+  values: number[];
+}
+
+/** Renders a bar, line, or pie chart from labels + series in the data model. */
+function ChartComponent({ comp, dataModel }: { comp: A2uiComponent; dataModel: Record<string, unknown> }) {
+  const labels = (resolveDynamicValue(comp.labels as { path: string }, dataModel) ?? []) as string[];
+  const series = (resolveDynamicValue(comp.series as { path: string }, dataModel) ?? []) as ChartSeries[];
+  const kind = comp.variant ?? "bar";
+  if (labels.length === 0 || series.length === 0) return null;
+
+  const legend = (names: string[]) => (
+    <div className="flex flex-wrap gap-x-3 gap-y-1 text-xs text-ghost-text-dim/70">
+      {names.map((name, i) => (
+        <span key={i} className="inline-flex items-center gap-1">
+          <span className="w-2 h-2 rounded-sm" style={{ background: CHART_COLORS[i % CHART_COLORS.length] }} />
+          {name}
+        </span>
+      ))}
+    </div>
+  );
+
+  if (kind === "pie") {
+    const values = series[0].values;
+    const total = values.reduce((sum, v) => sum + Math.max(v, 0), 0) || 1;
+    let angle = 0;
+    const stops = values.map((v, i) => {
+      const start = angle;
+      angle += (Math.max(v, 0) / total) * 360;
+      return `${CHART_COLORS[i % CHART_COLORS.length]} ${start}deg ${angle}deg`;
+    });
+    return (
+      <div className="flex items-center gap-3" data-a2ui-id={comp.id}>
+        <div className="w-28 h-28 rounded-full shrink-0" style={{ background: `conic-gradient(${stops.join(", ")})` }} />
+        {legend(labels)}
+      </div>
+    );
+  }
+
+  const max = Math.max(...series.flatMap((s) => s.values), 0) || 1;
+  const min = Math.min(...series.flatMap((s) => s.values), 0);
+  const range = max - min || 1;
+  const width = 300;
+  const height = 120;
+  const step = width / labels.length;
+  const y = (v: number) => height - ((v - min) / range) * height;
+  const barWidth = (step * 0.8) / series.length;
+
+  return (
+    <div className="flex flex-col gap-1.5" data-a2ui-id={comp.id}>
+      <svg viewBox={`0 0 ${width} ${height}`} className="w-full h-32" preserveAspectRatio="none">
+        {series.map((s, si) => {
+          const color = CHART_COLORS[si % CHART_COLORS.length];
+          if (kind === "line") {
+            const points = s.values.map((v, i) => `${step * i + step / 2},${y(v)}`).join(" ");
+            return <polyline key={si} points={points} fill="none" stroke={color} strokeWidth={2} vectorEffect="non-scaling-stroke" />;
+          }
+          return s.values.map((v, i) => (
+            <rect
+              key={`${si}-${i}`}
+              x={step * i + step * 0.1 + barWidth * si}
+              y={Math.min(y(v), y(0))}
+              width={barWidth}
+              height={Math.abs(y(v) - y(0))}
+              fill={color}
+            />
+          ));
+        })}
+      </svg>
+      <div className="flex text-[10px] text-ghost-text-dim/60">
+        {labels.map((label, i) => (
+          <span key={i} className="flex-1 text-center truncate">{label}</span>
+        ))}
+      </div>
+      {series.length > 1 && legend(series.map((s) => s.name))}
+    </div>
+  );
+}
+
 // ---------------------------------------------------------------------------
 // Component Registry
 // ---------------------------------------------------------------------------
@@ -440,7 +527,7 @@ function RenderComponent({
             />
           ) : (
             <input
-              type="text"
+              type={variant === "number" ? "number" : variant === "obscured" ? "password" : "text"}
               value={currentValue}
               onChange={(e) => handleChange(e.target.value)}
               className="w-full px-2.5 py-1.5 rounded-lg border border-ghost-border/50 bg-ghost-surface text-sm text-ghost-text placeholder-ghost-text-dim/30 focus:outline-none focus:border-ghost-accent/50"
@@ -590,6 +677,41 @@ function RenderComponent({
       );
     }
 
+    // -----------------------------------------------------------------------
+    // Ghost Extensions
+    // -----------------------------------------------------------------------
+
+    case "Table": {
+      const columns = (comp.columns ?? []) as string[];
+      const rows = (resolveDynamicValue(comp.rows as { path: string }, dataModel) ?? []) as unknown[][];
+      return (
+        <div className="overflow-x-auto" data-a2ui-id={comp.id}>
+          <table className="w-full text-xs text-ghost-text">
+            <thead>
+              <tr className="border-b border-ghost-border/30 text-left text-ghost-text-dim/70">
+                {columns.map((column, i) => (
+                  <th key={i} className="px-2 py-1 font-medium">{column}</th>
+                ))}
+              </tr>
+            </thead>
+            <tbody>
+              {rows.map((row, ri) => (
+                <tr key={ri} className="border-b border-ghost-border/10 last:border-0">
+                  {row.map((cell, ci) => (
+                    <td key={ci} className="px-2 py-1">{cell == null ? "" : String(cell)}</td>
+                  ))}
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      );
+    }
+
+    case "Chart": {
+      return <ChartComponent comp={comp} dataModel={dataModel} />;
+    }
+
     // -----------------------------------------------------------------------
     // Fallback
     // -----------------------------------------------------------------------
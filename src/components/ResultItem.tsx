@@ -43,6 +43,8 @@ const SOURCE_COLORS: Record<string, string> = {
   hybrid: "bg-ghost-accent/20 text-ghost-accent",
   fts: "bg-emerald-500/20 text-emerald-400",
   vector: "bg-amber-500/20 text-amber-400",
+  fuzzy: "bg-sky-500/20 text-sky-400",
+  filename: "bg-slate-500/20 text-slate-300",
 };
 
 function getIcon(extension: string | null) {
@@ -62,6 +64,8 @@ function formatPath(path: string): string {
 
 export function ResultItem({ result, isSelected, onSelect, onOpen, isMobile = false }: ResultItemProps) {
   const Icon = getIcon(result.extension);
+  // E-book chapter, sheet rows, file inside an archive, or code symbol
+  const context = result.section ?? result.symbol;
 
   return (
     <button
@@ -77,6 +81,9 @@ export function ResultItem({ result, isSelected, onSelect, onOpen, isMobile = fa
         }
       `}
       aria-selected={isSelected}
+      aria-label={`Result ${result.position} of ${result.total}: ${result.filename}${
+        context ? `, ${context}` : ""
+      }. ${result.source_description}.`}
       role="option"
     >
       <div className="flex items-start gap-3">
@@ -95,6 +102,11 @@ export function ResultItem({ result, isSelected, onSelect, onOpen, isMobile = fa
             <span className="text-sm font-medium text-ghost-text truncate">
               {result.filename}
             </span>
+            {context && (
+              <span className="text-xs text-ghost-text-dim truncate">
+                {context}
+              </span>
+            )}
             <span
               className={`text-[10px] font-medium px-1.5 py-0.5 rounded-full uppercase tracking-wider shrink-0 ${
                 SOURCE_COLORS[result.source] || SOURCE_COLORS.fts
@@ -105,7 +117,7 @@ export function ResultItem({ result, isSelected, onSelect, onOpen, isMobile = fa
           </div>
 
           <p className="text-xs text-ghost-text-dim/80 line-clamp-2 leading-relaxed">
-            {result.snippet}
+            {result.summary ?? result.snippet}
           </p>
 
           <div className="flex items-center gap-2 mt-1.5">
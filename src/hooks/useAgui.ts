@@ -24,7 +24,16 @@
 
 import { useState, useEffect, useCallback, useRef } from "react";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import type { AgUiEvent, AgUiRunState, ChatMessage, A2uiMessage } from "../lib/types";
+import type {
+  AgentPlan,
+  AgUiEvent,
+  AgUiRunState,
+  ChatMessage,
+  A2uiMessage,
+  BudgetExceeded,
+  GuardrailHit,
+  SubagentRun,
+} from "../lib/types";
 import { chatSendStreaming } from "../lib/tauri";
 import { computeRootIds } from "../components/A2UIRenderer";
 
@@ -92,6 +101,10 @@ export function useAgui() {
             a2uiSurfaces: new Map(),
             reasoningContent: "",
             activities: new Map(),
+            budgetExceeded: null,
+            subagents: new Map(),
+            plan: null,
+            guardrailHits: [],
           };
         }
 
@@ -275,6 +288,60 @@ export function useAgui() {
             };
           }
 
+          // A budget cut the run short
+          if (event.name === "budget_exceeded" && event.value) {
+            return {
+              ...prev,
+              budgetExceeded: event.value as BudgetExceeded,
+            };
+          }
+
+          // Sub-agents spawned by the run (their own events use the child run ID)
+          if (event.name === "subagent_started" && event.value) {
+            const v = event.value as { child_run_id: string; task: string; tools: string[] };
+            const subagents = new Map(prev.subagents);
+            subagents.set(v.child_run_id, {
+              childRunId: v.child_run_id,
+              task: v.task,
+              tools: v.tools,
+              status: "running",
+            });
+            return { ...prev, subagents };
+          }
+          if (event.name === "subagent_finished" && event.value) {
+            const v = event.value as {
+              child_run_id: string;
+              iterations?: number;
+              budget_exceeded?: BudgetExceeded | null;
+              error?: string;
+            };
+            const existing = prev.subagents.get(v.child_run_id);
+            if (!existing) return prev;
+            const subagents = new Map(prev.subagents);
+            const finished: SubagentRun = {
+              ...existing,
+              status: v.error ? "error" : "finished",
+              iterations: v.iterations,
+              budgetExceeded: v.budget_exceeded ?? null,
+              error: v.error,
+            };
+            subagents.set(v.child_run_id, finished);
+            return { ...prev, subagents };
+          }
+
+          // A guardrail blocked a tool call or forced a prompt
+          if (event.name === "guardrail_hit" && event.value) {
+            return {
+              ...prev,
+              guardrailHits: [...prev.guardrailHits, event.value as GuardrailHit],
+            };
+          }
+
+          // A plan-mode run's plan, to approve with executePlan
+          if (event.name === "plan_ready" && event.value) {
+            return { ...prev, plan: event.value as AgentPlan };
+          }
+
           // Handle A2UI messages transported via CUSTOM events
           if (event.name === "a2ui" && event.value) {
             const a2uiMsg = event.value as A2uiMessage;
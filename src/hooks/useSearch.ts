@@ -1,8 +1,31 @@
 import { useState, useCallback, useRef, useEffect } from "react";
-import { search } from "../lib/tauri";
-import type { SearchResult } from "../lib/types";
+import { searchFilenames, searchStream } from "../lib/tauri";
+import type { FilenameMatch, SearchResult } from "../lib/types";
 
-/** Hook for debounced search with loading state. */
+/** Show a filename match in the results list until hybrid results arrive. */
+function toResult(match: FilenameMatch, index: number, total: number): SearchResult {
+  return {
+    chunk_id: 0,
+    document_id: match.document_id,
+    path: match.path,
+    filename: match.filename,
+    extension: match.extension,
+    snippet: match.path,
+    chunk_index: 0,
+    score: 0,
+    source: "filename",
+    source_description: "Matched the file name",
+    position: index + 1,
+    total,
+  };
+}
+
+/**
+ * Hook for debounced search with loading state.
+ *
+ * Filename matches are shown instantly on every keystroke. Once the debounce
+ * fires, keyword results replace them, then the final hybrid ranking.
+ */
 export function useSearch(debounceMs = 150) {
   const [query, setQuery] = useState("");
   const [results, setResults] = useState<SearchResult[]>([]);
@@ -10,6 +33,9 @@ export function useSearch(debounceMs = 150) {
   const [error, setError] = useState<string | null>(null);
   const timerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const abortRef = useRef(0);
+  // Latest query typed and latest query whose hybrid results are shown
+  const typedRef = useRef("");
+  const settledRef = useRef("");
 
   const doSearch = useCallback(
     async (q: string) => {
@@ -24,9 +50,15 @@ export function useSearch(debounceMs = 150) {
       setError(null);
 
       try {
-        const res = await search(q, 20);
+        const res = await searchStream(q, 20, (update) => {
+          if (update.stage === "keyword" && searchId === abortRef.current) {
+            settledRef.current = q;
+            if (update.results.length > 0) setResults(update.results);
+          }
+        });
         // Only update if this is still the latest search
         if (searchId === abortRef.current) {
+          settledRef.current = q;
           setResults(res);
         }
       } catch (e) {
@@ -43,13 +75,28 @@ export function useSearch(debounceMs = 150) {
     []
   );
 
+  const showFilenames = useCallback(async (q: string) => {
+    if (!q.trim()) return;
+    try {
+      const matches = await searchFilenames(q, 20);
+      // Drop if the user kept typing or hybrid results already landed
+      if (typedRef.current === q && settledRef.current !== q && matches.length > 0) {
+        setResults(matches.map((m, i) => toResult(m, i, matches.length)));
+      }
+    } catch {
+      // Hybrid search still runs and reports errors
+    }
+  }, []);
+
   const updateQuery = useCallback(
     (q: string) => {
       setQuery(q);
+      typedRef.current = q;
+      showFilenames(q);
       if (timerRef.current) clearTimeout(timerRef.current);
       timerRef.current = setTimeout(() => doSearch(q), debounceMs);
     },
-    [doSearch, debounceMs]
+    [doSearch, showFilenames, debounceMs]
   );
 
   // Cleanup on unmount
@@ -1,23 +1,97 @@
-import { invoke } from "@tauri-apps/api/core";
-import { openPath } from "@tauri-apps/plugin-opener";
+import { invoke as tauriInvoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { openPath, openUrl } from "@tauri-apps/plugin-opener";
 import type {
   SearchResult,
+  SearchUpdate,
+  FilenameMatch,
   DbStats,
   IndexStats,
+  IndexProgress,
+  CrashReport,
+  ThrottleStatus,
   AiStatus,
   Settings,
   ChatMessage,
   ChatResponse,
+  StructuredResponse,
   ChatStatus,
   LogEntry,
   HardwareInfo,
   ModelInfo,
+  DownloadedModel,
+  CustomModel,
+  HubModel,
+  EmbeddingModelInfo,
   FsEntry,
   McpServerStatus,
+  OpenAiServerStatus,
+  WebMcpOriginPermission,
+  WebMcpStatus,
   McpServerEntry,
   ConnectedServer,
+  ToolCallRecord,
+  ReplayResult,
+  ElicitationAction,
+  CapabilityStatus,
+  SystemHealth,
+  ExtractorPlugin,
+  PluginInfo,
+  Collection,
+  CollectionRequest,
+  QueryStats,
+  FocusRequest,
+  FocusContext,
+  Translation,
+  OpenedResult,
+  CommandErrorInfo,
+  ErrorCode,
 } from "./types";
 
+// --- Errors ---
+
+/** A structured command error. `String(error)` is the plain message, so
+ *  callers that only show text keep working. */
+export class CommandError extends Error {
+  code: ErrorCode;
+  retryable: boolean;
+  hint: string | null;
+
+  constructor(info: CommandErrorInfo) {
+    super(info.message);
+    this.name = "CommandError";
+    this.code = info.code;
+    this.retryable = info.retryable;
+    this.hint = info.hint;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+function isCommandErrorInfo(value: unknown): value is CommandErrorInfo {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    "code" in value &&
+    "message" in value
+  );
+}
+
+/** `invoke` that turns structured command errors into `CommandError`s.
+ *  Commands that still return plain strings reject with the string. */
+async function invoke<T = unknown>(
+  command: string,
+  args?: Record<string, unknown>
+): Promise<T> {
+  try {
+    return await tauriInvoke<T>(command, args);
+  } catch (e) {
+    throw isCommandErrorInfo(e) ? new CommandError(e) : e;
+  }
+}
+
 // --- Search & Indexing ---
 
 /** Perform hybrid search (FTS5 + vector) across indexed documents. */
@@ -28,11 +102,129 @@ export async function search(
   return invoke<SearchResult[]>("search_query", { query, limit });
 }
 
+let streamSearchId = 0;
+
+/**
+ * Hybrid search that reports keyword results through `onUpdate` as soon as
+ * they are ready, then resolves with the final hybrid ranking.
+ */
+export async function searchStream(
+  query: string,
+  limit: number | undefined,
+  onUpdate: (update: SearchUpdate) => void
+): Promise<SearchResult[]> {
+  const searchId = ++streamSearchId;
+  const unlisten = await listen<SearchUpdate>("search://results", (event) => {
+    if (event.payload.search_id === searchId) onUpdate(event.payload);
+  });
+  try {
+    return await invoke<SearchResult[]>("search_query_stream", { query, limit, searchId });
+  } finally {
+    unlisten();
+  }
+}
+
+/** Instant filename-only search (no embeddings) for the quick launcher. */
+export async function searchFilenames(
+  query: string,
+  limit?: number
+): Promise<FilenameMatch[]> {
+  return invoke<FilenameMatch[]>("search_filenames", { query, limit });
+}
+
+/**
+ * List every document matching a topic and/or type and year, grouped by
+ * year, file type, or folder (e.g. "all PDFs about taxes from 2023").
+ */
+export async function collectDocuments(request: CollectionRequest): Promise<Collection> {
+  return invoke<Collection>("collect_documents", { request });
+}
+
+/**
+ * Open a search result's file with its default app and record the
+ * click-through for ranking. `line` is where the result starts in text files.
+ */
+export async function openSearchResult(chunkId: number, query?: string): Promise<OpenedResult> {
+  return invoke<OpenedResult>("open_search_result", { chunkId, query });
+}
+
+/** Show an indexed file in the system file manager. */
+export async function revealInFileManager(path: string): Promise<void> {
+  return invoke<void>("reveal_in_file_manager", { path });
+}
+
+/** Most recent distinct searches, newest first. */
+export async function getRecentQueries(limit?: number): Promise<QueryStats[]> {
+  return invoke<QueryStats[]>("get_recent_queries", { limit });
+}
+
+/** Most frequent searches over the last `days` days (all time by default). */
+export async function getFrequentQueries(limit?: number, days?: number): Promise<QueryStats[]> {
+  return invoke<QueryStats[]>("get_frequent_queries", { limit, days });
+}
+
+/** Earlier searches starting with `prefix`, most used first. */
+export async function getQuerySuggestions(prefix: string, limit?: number): Promise<string[]> {
+  return invoke<string[]>("get_query_suggestions", { prefix, limit });
+}
+
+/** Delete the recorded search history. */
+export async function clearQueryHistory(): Promise<void> {
+  return invoke<void>("clear_query_history");
+}
+
+/** Restrict all searches to some folders, tags, or dates until cleared. */
+export async function setFocusContext(request: FocusRequest): Promise<FocusContext> {
+  return invoke<FocusContext>("set_focus_context", { request });
+}
+
+/** The focus in effect, if any. */
+export async function getFocusContext(): Promise<FocusContext | null> {
+  return invoke<FocusContext | null>("get_focus_context");
+}
+
+/** End the focus, returning what was cleared. */
+export async function clearFocusContext(): Promise<FocusContext | null> {
+  return invoke<FocusContext | null>("clear_focus_context");
+}
+
 /** Index all supported files in a directory recursively. */
 export async function indexDirectory(path: string): Promise<IndexStats> {
   return invoke<IndexStats>("index_directory", { path });
 }
 
+/** Directory indexing runs in flight. */
+export async function getActiveIndexJobs(): Promise<IndexProgress[]> {
+  return invoke<IndexProgress[]>("get_active_index_jobs");
+}
+
+/** Power source, CPU temperature, and whether background indexing is held. */
+export async function getThrottleStatus(): Promise<ThrottleStatus> {
+  return invoke<ThrottleStatus>("get_throttle_status");
+}
+
+/** Stop an indexing run before its next file. */
+export async function cancelIndexJob(id: number): Promise<void> {
+  return invoke("cancel_index_job", { id });
+}
+
+/** Pause an indexing job; it stays paused across restarts until resumed. */
+export async function pauseJob(id: number): Promise<void> {
+  return invoke("pause_job", { id });
+}
+
+/** Resume a paused job, skipping files indexed before the pause. */
+export async function resumeJob(id: number): Promise<void> {
+  return invoke("resume_job", { id });
+}
+
+/** Subscribe to indexing progress events. Returns the unlisten function. */
+export async function onIndexProgress(
+  handler: (progress: IndexProgress) => void
+): Promise<() => void> {
+  return listen<IndexProgress>("indexer://progress", (event) => handler(event.payload));
+}
+
 /** Index a single file. */
 export async function indexFile(path: string): Promise<void> {
   return invoke<void>("index_file", { path });
@@ -53,16 +245,54 @@ export async function checkAiStatus(): Promise<AiStatus> {
   return invoke<AiStatus>("check_ai_status");
 }
 
+/** Selectable embedding models, marking the one the vault uses. */
+export async function listEmbeddingModels(): Promise<EmbeddingModelInfo[]> {
+  return invoke<EmbeddingModelInfo[]>("list_embedding_models");
+}
+
+/** Re-embed the vault with another model (registry ID, "native" or "ollama"). */
+export async function startReembedding(backend: string): Promise<void> {
+  return invoke("start_reembedding", { backend });
+}
+
 /** Start watching directories for file changes. */
 export async function startWatcher(directories: string[]): Promise<void> {
   return invoke<void>("start_watcher", { directories });
 }
 
+/** Run an extractor plugin on a sample file and return a text preview. */
+export async function testExtractorPlugin(
+  plugin: ExtractorPlugin,
+  path: string
+): Promise<string> {
+  return invoke<string>("test_extractor_plugin", { plugin, path });
+}
+
+/** List discovered WASM plugins, including ones that failed to load. */
+export async function listWasmPlugins(): Promise<PluginInfo[]> {
+  return invoke<PluginInfo[]>("list_wasm_plugins");
+}
+
+/** Rescan the plugins folder and recompile every WASM plugin. */
+export async function reloadWasmPlugins(): Promise<PluginInfo[]> {
+  return invoke<PluginInfo[]>("reload_wasm_plugins");
+}
+
 /** Check if vector search (sqlite-vec) is available. */
 export async function getVecStatus(): Promise<boolean> {
   return invoke<boolean>("get_vec_status");
 }
 
+/** Get degraded capabilities with reasons and remediation hints. */
+export async function getSystemHealth(): Promise<SystemHealth> {
+  return invoke<SystemHealth>("get_system_health");
+}
+
+/** Retry loading sqlite-vec without restarting the app. */
+export async function retryEnableVec(): Promise<CapabilityStatus> {
+  return invoke<CapabilityStatus>("retry_enable_vec");
+}
+
 // --- Window ---
 
 /** Hide the main window. */
@@ -98,6 +328,20 @@ export async function closeWindow(): Promise<void> {
   return invoke<void>("close_window");
 }
 
+// --- Session Restore ---
+
+import type { SessionState, RestoredSession } from "./types";
+
+/** Last conversation, last search, and window geometry, plus jobs still running. */
+export async function getSessionState(): Promise<RestoredSession> {
+  return invoke<RestoredSession>("get_session_state");
+}
+
+/** Save the active conversation and search; window geometry is captured by the backend. */
+export async function saveSessionState(session: SessionState): Promise<void> {
+  return invoke<void>("save_session_state", { session });
+}
+
 // --- Auto-Indexing ---
 
 /** Get auto-detected default user directories for indexing. */
@@ -110,9 +354,26 @@ export async function getDefaultDirectories(): Promise<string[]> {
 /** Send chat messages and get a response. */
 export async function chatSend(
   messages: ChatMessage[],
-  maxTokens?: number
+  maxTokens?: number,
+  conversationId?: number | null
 ): Promise<ChatResponse> {
-  return invoke<ChatResponse>("chat_send", { messages, maxTokens });
+  return invoke<ChatResponse>("chat_send", { messages, maxTokens, conversationId });
+}
+
+/**
+ * Chat with the reply constrained to a JSON schema. The local model can only
+ * produce JSON the schema allows; `value` is the parsed reply.
+ */
+export async function chatSendStructured<T = unknown>(
+  messages: ChatMessage[],
+  jsonSchema: Record<string, unknown>,
+  maxTokens?: number
+): Promise<StructuredResponse<T>> {
+  return invoke<StructuredResponse<T>>("chat_send_structured", {
+    messages,
+    jsonSchema,
+    maxTokens,
+  });
 }
 
 /** Get chat engine status. */
@@ -120,6 +381,16 @@ export async function chatStatus(): Promise<ChatStatus> {
   return invoke<ChatStatus>("chat_status");
 }
 
+/** Count tokens as a chat model sees them (defaults to the active model). */
+export async function countTokens(text: string, modelId?: string): Promise<number> {
+  return invoke<number>("count_tokens", { text, modelId });
+}
+
+/** Models offered by the configured local OpenAI-compatible server. */
+export async function listLocalServerModels(): Promise<string[]> {
+  return invoke<string[]>("list_local_server_models");
+}
+
 /** Trigger background model loading. */
 export async function chatLoadModel(): Promise<void> {
   return invoke<void>("chat_load_model");
@@ -142,6 +413,40 @@ export async function getAvailableModels(): Promise<ModelInfo[]> {
   return invoke<ModelInfo[]>("get_available_models");
 }
 
+/** Downloaded chat model GGUFs with their disk usage. */
+export async function listDownloadedModels(): Promise<DownloadedModel[]> {
+  return invoke<DownloadedModel[]>("list_downloaded_models");
+}
+
+/** Delete a downloaded model that is not in use. Returns bytes freed. */
+export async function deleteDownloadedModel(modelId: string): Promise<number> {
+  return invoke<number>("delete_downloaded_model", { modelId });
+}
+
+/** Import a local GGUF as a chat or agent model (persisted in settings). */
+export async function importCustomModel(
+  path: string,
+  name: string,
+  contextWindow?: number
+): Promise<CustomModel> {
+  return invoke<CustomModel>("import_custom_model", { path, name, contextWindow });
+}
+
+/** Search the Hugging Face Hub for GGUF models that fit this machine. */
+export async function searchHubModels(query: string): Promise<HubModel[]> {
+  return invoke<HubModel[]>("search_hub_models", { query });
+}
+
+/** Download a GGUF from the Hub and register it as a custom model. */
+export async function addHubModel(
+  repoId: string,
+  filename: string,
+  name?: string,
+  contextWindow?: number
+): Promise<CustomModel> {
+  return invoke<CustomModel>("add_hub_model", { repoId, filename, name, contextWindow });
+}
+
 /** Get the recommended model ID for this hardware. */
 export async function getRecommendedModel(): Promise<string> {
   return invoke<string>("get_recommended_model");
@@ -159,6 +464,15 @@ export async function clearLogs(): Promise<void> {
   return invoke<void>("clear_logs");
 }
 
+/** Report of the last session that crashed or was killed, if any. */
+export async function getLastCrashReport(): Promise<CrashReport | null> {
+  return invoke<CrashReport | null>("get_last_crash_report");
+}
+
+export async function dismissCrashReport(): Promise<void> {
+  return invoke("dismiss_crash_report");
+}
+
 /** Push a log entry from the frontend into the backend log buffer.
  * This makes frontend errors visible in the DebugPanel alongside backend logs.
  * Fire-and-forget: errors in logging must not crash the app. */
@@ -175,9 +489,17 @@ export async function getSettings(): Promise<Settings> {
   return invoke<Settings>("get_settings");
 }
 
-/** Save settings to disk. */
-export async function saveSettings(newSettings: Settings): Promise<void> {
-  return invoke<void>("save_settings", { newSettings });
+/**
+ * Save settings. Pass `rechunk` to re-chunk and re-embed the vault in the
+ * background when the chunk size or overlap changed.
+ */
+export async function saveSettings(newSettings: Settings, rechunk?: boolean): Promise<void> {
+  return invoke<void>("save_settings", { newSettings, rechunk });
+}
+
+/** Reset one settings section (e.g. "ranking") to its defaults. Returns the saved settings. */
+export async function resetSettingsSection(section: string): Promise<Settings> {
+  return invoke<Settings>("reset_settings_section", { section });
 }
 
 /** Mark initial setup/onboarding as complete. */
@@ -187,9 +509,9 @@ export async function completeSetup(): Promise<void> {
 
 // --- System ---
 
-/** Open a file with the system default application. */
+/** Open a file with the system default application (imported web pages in the browser). */
 export async function openFile(path: string): Promise<void> {
-  return openPath(path);
+  return /^https?:\/\//.test(path) ? openUrl(path) : openPath(path);
 }
 
 // --- Pro Edition ---
@@ -233,6 +555,29 @@ export async function getMcpServerStatus(): Promise<McpServerStatus> {
   return invoke<McpServerStatus>("get_mcp_server_status");
 }
 
+/** Replace the MCP server's bearer token; clients must use the returned one. */
+export async function rotateMcpToken(): Promise<string> {
+  return invoke<string>("rotate_mcp_token");
+}
+
+/** Get OpenAI-compatible API status (enabled, host, port, base url). */
+export async function getOpenAiServerStatus(): Promise<OpenAiServerStatus> {
+  return invoke<OpenAiServerStatus>("get_openai_server_status");
+}
+
+/** Get WebMCP bridge status (enabled, url, per-origin permissions). */
+export async function getWebMcpStatus(): Promise<WebMcpStatus> {
+  return invoke<WebMcpStatus>("get_webmcp_status");
+}
+
+/** Allow or deny a browser origin on the WebMCP bridge; `null` asks again next time. */
+export async function setWebMcpOriginPermission(
+  origin: string,
+  permission: WebMcpOriginPermission | null,
+): Promise<void> {
+  return invoke<void>("set_webmcp_origin_permission", { origin, permission });
+}
+
 /** List all configured external MCP servers and their connection status. */
 export async function listMcpServers(): Promise<ConnectedServer[]> {
   return invoke<ConnectedServer[]>("list_mcp_servers");
@@ -243,6 +588,19 @@ export async function connectMcpServer(name: string): Promise<ConnectedServer> {
   return invoke<ConnectedServer>("connect_mcp_server", { name });
 }
 
+/**
+ * Sign in to a remote MCP server that requires OAuth. Opens the system
+ * browser and resolves once the server is connected (or the sign-in fails).
+ */
+export async function signInMcpServer(name: string): Promise<ConnectedServer> {
+  return invoke<ConnectedServer>("sign_in_mcp_server", { name });
+}
+
+/** Forget a remote MCP server's OAuth tokens and disconnect it. */
+export async function signOutMcpServer(name: string): Promise<void> {
+  return invoke<void>("sign_out_mcp_server", { name });
+}
+
 /** Disconnect from an external MCP server. */
 export async function disconnectMcpServer(name: string): Promise<void> {
   return invoke<void>("disconnect_mcp_server", { name });
@@ -257,6 +615,33 @@ export async function callMcpTool(
   return invoke<string>("call_mcp_tool", { serverName, toolName, arguments: toolArguments });
 }
 
+/** Recorded MCP tool calls, newest first, optionally filtered. */
+export async function getToolCallHistory(filter?: {
+  server?: string;
+  tool?: string;
+  runId?: string;
+  limit?: number;
+}): Promise<ToolCallRecord[]> {
+  return invoke<ToolCallRecord[]>("get_tool_call_history", { ...filter });
+}
+
+/** Call a recorded MCP tool again with the same arguments. */
+export async function replayToolCall(id: number): Promise<ReplayResult> {
+  return invoke<ReplayResult>("replay_tool_call", { id });
+}
+
+/**
+ * Answer an input request from an external MCP server (an
+ * `elicitation_required` prompt on the `mcp://prompt` event).
+ */
+export async function respondElicitation(
+  elicitationId: string,
+  action: ElicitationAction,
+  content?: Record<string, unknown>
+): Promise<void> {
+  return invoke<void>("respond_elicitation", { elicitationId, action, content });
+}
+
 /** Get all available tools from all connected MCP servers. */
 export async function listMcpTools(): Promise<
   Array<{ server: string; name: string; description: string | null }>
@@ -274,9 +659,24 @@ export async function removeMcpServerEntry(name: string): Promise<void> {
   return invoke<void>("remove_mcp_server_entry", { name });
 }
 
+/** Store a secret in the OS keychain. Refer to it in MCP env values as `${secret:<name>}`. */
+export async function setSecret(name: string, value: string): Promise<void> {
+  return invoke<void>("set_secret", { name, value });
+}
+
+/** Read a secret from the OS keychain (null if not set). */
+export async function getSecret(name: string): Promise<string | null> {
+  return invoke<string | null>("get_secret", { name });
+}
+
+/** Delete a secret from the OS keychain. Returns false if it wasn't set. */
+export async function deleteSecret(name: string): Promise<boolean> {
+  return invoke<boolean>("delete_secret", { name });
+}
+
 // --- MCP Catalog (App Store) ---
 
-import type { CatalogEntry, CatalogResponse, RuntimeInfo, RegistrySyncResult, RegistryStatus, PackageVerification, BootstrapStatus, RuntimeKind, RuntimeInstallResult, ToolRecommendation } from "./types";
+import type { CatalogEntry, CatalogResponse, InstallReport, RuntimeInfo, RegistrySyncResult, RegistryStatus, PackageVerification, BootstrapStatus, RuntimeKind, RuntimeInstallResult, ToolRecommendation } from "./types";
 
 /** Get the curated MCP tool catalog with categories. */
 export async function getMcpCatalog(): Promise<CatalogResponse> {
@@ -337,6 +737,16 @@ export async function installMcpEntry(
   return invoke<ConnectedServer>("install_mcp_entry", { entry, envVars });
 }
 
+/** Install wizard: validate env vars, connect, list tools and try one
+ *  read-only call. The server is saved only if every check passes; the
+ *  report says which step failed and how to fix it. */
+export async function installCatalogEntry(
+  entry: CatalogEntry,
+  envVars: Record<string, string> = {}
+): Promise<InstallReport> {
+  return invoke<InstallReport>("install_catalog_entry", { entry, envVars });
+}
+
 /** Sync the official MCP Registry to local cache.
  *  Fetches all 6,000+ servers from registry.modelcontextprotocol.io.
  *  This is opt-in — only triggered when user explicitly browses the registry. */
@@ -431,10 +841,20 @@ export async function chatSendStreaming(
 
 import type {
   Conversation,
+  ConversationAttachment,
+  DistilledNote,
+  DocumentSummaryResult,
+  SummaryStyle,
+  FilenameSuggestion,
   AgentMessage,
   AgentConfig,
   AgentModelTiersResponse,
   Skill,
+  MarketplaceSkill,
+  RunEvents,
+  BusMetrics,
+  ResourceUsage,
+  AgUiEvent,
 } from "./types";
 
 /** Run the agent with ReAct loop + tool calling.
@@ -446,6 +866,66 @@ export async function agentChat(
   return invoke<string>("agent_chat", { messages, conversationId });
 }
 
+/**
+ * Plan a request without carrying it out: only read-only tools run.
+ * Returns the run_id; the plan arrives as a `plan_ready` CUSTOM event.
+ */
+export async function agentPlan(
+  messages: ChatMessage[],
+  conversationId?: number | null
+): Promise<string> {
+  return invoke<string>("agent_plan", { messages, conversationId });
+}
+
+/** Carry out an approved plan. Returns the new run_id. */
+export async function executePlan(planId: string): Promise<string> {
+  return invoke<string>("execute_plan", { planId });
+}
+
+/** Drop a plan without running it. */
+export async function discardPlan(planId: string): Promise<void> {
+  return invoke("discard_plan", { planId });
+}
+
+/**
+ * Buffered AG-UI events of a run after `sinceSeq` — call after reconnecting
+ * with the last `seq` seen to recover missed deltas.
+ */
+export async function getRunEvents(runId: string, sinceSeq?: number): Promise<RunEvents> {
+  return invoke<RunEvents>("get_run_events", { runId, sinceSeq });
+}
+
+/** Backpressure counters for the AG-UI event bus. */
+export async function getAguiBusMetrics(): Promise<BusMetrics> {
+  return invoke<BusMetrics>("get_agui_bus_metrics");
+}
+
+/** Ghost's own CPU, memory, model memory, vault size, and job queue depth. */
+export async function getResourceUsage(): Promise<ResourceUsage> {
+  return invoke<ResourceUsage>("get_resource_usage");
+}
+
+/** Stream resource usage every `intervalMs` until `stopResourceMonitor`. */
+export async function startResourceMonitor(intervalMs?: number): Promise<void> {
+  return invoke("start_resource_monitor", { intervalMs });
+}
+
+export async function stopResourceMonitor(): Promise<boolean> {
+  return invoke<boolean>("stop_resource_monitor");
+}
+
+/** Subscribe to resource monitor samples (AG-UI STATE_SNAPSHOT events).
+ *  Returns the unlisten function. */
+export async function onResourceUsage(
+  handler: (usage: ResourceUsage, timestamp: number) => void
+): Promise<() => void> {
+  return listen<AgUiEvent>("resources://usage", (event) => {
+    if (event.payload.type === "STATE_SNAPSHOT") {
+      handler(event.payload.snapshot as ResourceUsage, event.payload.timestamp);
+    }
+  });
+}
+
 /** Create a new conversation. Returns the conversation ID. */
 export async function createConversation(title: string): Promise<number> {
   return invoke<number>("create_conversation", { title });
@@ -477,6 +957,92 @@ export async function updateConversationTitle(
   return invoke<void>("update_conversation_title", { conversationId, title });
 }
 
+/**
+ * Let a conversation use the configured remote provider, or switch it back
+ * to local only (the default).
+ */
+export async function setConversationCloud(
+  conversationId: number,
+  allowCloud: boolean
+): Promise<void> {
+  return invoke<void>("set_conversation_cloud", { conversationId, allowCloud });
+}
+
+/**
+ * Pin an indexed document or folder to a conversation. While anything is
+ * attached, the agent's searches in that conversation only cover it.
+ */
+export async function attachToConversation(
+  conversationId: number,
+  path: string
+): Promise<ConversationAttachment> {
+  return invoke<ConversationAttachment>("attach_to_conversation", { conversationId, path });
+}
+
+/** Unpin a document or folder. Returns whether it was attached. */
+export async function detachFromConversation(
+  conversationId: number,
+  path: string
+): Promise<boolean> {
+  return invoke<boolean>("detach_from_conversation", { conversationId, path });
+}
+
+/** Documents and folders pinned to a conversation. */
+export async function listConversationAttachments(
+  conversationId: number
+): Promise<ConversationAttachment[]> {
+  return invoke<ConversationAttachment[]>("list_conversation_attachments", { conversationId });
+}
+
+/**
+ * Turn a conversation into a Markdown note (decisions, action items,
+ * references) saved at `destination` — a folder or `.md` path inside a
+ * watched folder.
+ */
+export async function distillConversation(
+  conversationId: number,
+  destination: string
+): Promise<DistilledNote> {
+  return invoke<DistilledNote>("distill_conversation", { conversationId, destination });
+}
+
+/**
+ * Summarize an indexed document with the local model. Cached until the
+ * file's content changes; cached summaries also appear in search results.
+ */
+export async function summarizeDocument(
+  documentId: number,
+  style: SummaryStyle
+): Promise<DocumentSummaryResult> {
+  return invoke<DocumentSummaryResult>("summarize_document", { documentId, style });
+}
+
+/** Suggest a descriptive name for a file from its content. */
+export async function suggestFilename(path: string): Promise<FilenameSuggestion> {
+  return invoke<FilenameSuggestion>("suggest_filename", { path });
+}
+
+/**
+ * Suggest names for the generically named files (Scan_0001.pdf, IMG_2041.jpg)
+ * in a folder, or for every file with `includeAll`.
+ */
+export async function suggestFolderFilenames(
+  folder: string,
+  includeAll?: boolean
+): Promise<FilenameSuggestion[]> {
+  return invoke<FilenameSuggestion[]>("suggest_folder_filenames", { folder, includeAll });
+}
+
+/** Rename a file in place (never overwriting). Returns the new path. */
+export async function renameFile(path: string, newName: string): Promise<string> {
+  return invoke<string>("rename_file", { path, newName });
+}
+
+/** Translate text into a language (e.g. "Spanish") with the local model. */
+export async function translateText(text: string, targetLanguage: string): Promise<Translation> {
+  return invoke<Translation>("translate_text", { text, targetLanguage });
+}
+
 /** Search across conversation memory via FTS5. */
 export async function searchMemory(query: string, limit?: number): Promise<AgentMessage[]> {
   return invoke<AgentMessage[]>("search_memory", { query, limit });
@@ -501,3 +1067,305 @@ export async function getAgentModelTiers(): Promise<AgentModelTiersResponse> {
 export async function listSkills(): Promise<Skill[]> {
   return invoke<Skill[]>("list_skills");
 }
+
+/** List the community skills of the configured marketplace. */
+export async function listMarketplaceSkills(): Promise<MarketplaceSkill[]> {
+  return invoke<MarketplaceSkill[]>("list_marketplace_skills");
+}
+
+/** Download, verify and install a marketplace skill. */
+export async function installMarketplaceSkill(name: string): Promise<Skill> {
+  return invoke<Skill>("install_marketplace_skill", { name });
+}
+
+/**
+ * Run a skill with the agent, filling its template parameters.
+ * Returns the run_id; progress streams as AG-UI events like `agentChat`.
+ */
+export async function runSkill(
+  name: string,
+  params?: Record<string, unknown>,
+): Promise<string> {
+  return invoke<string>("run_skill", { name, params: params ?? null });
+}
+
+// --- Reminders ---
+
+import type { Reminder } from "./types";
+
+/**
+ * Open reminders due within `days` (default 7, overdue included), soonest
+ * first. Due reminders are also announced via the `reminder-due` event.
+ */
+export async function listUpcomingReminders(
+  days?: number,
+  includeUndated?: boolean,
+  limit?: number
+): Promise<Reminder[]> {
+  return invoke<Reminder[]>("list_upcoming_reminders", { days, includeUndated, limit });
+}
+
+/** Mark a reminder done, or open it again. */
+export async function setReminderDone(id: number, done: boolean): Promise<void> {
+  return invoke<void>("set_reminder_done", { id, done });
+}
+
+// --- Tags ---
+
+import type { TagCount, VaultLanguages } from "./types";
+
+/**
+ * Tag a document (tags are lowercased and trimmed). Search with `tag:name`
+ * to restrict results to tagged documents. Returns the document's tags.
+ */
+export async function addDocumentTag(documentId: number, tag: string): Promise<string[]> {
+  return invoke<string[]>("add_document_tag", { documentId, tag });
+}
+
+/** Remove a tag from a document. Returns the document's remaining tags. */
+export async function removeDocumentTag(documentId: number, tag: string): Promise<string[]> {
+  return invoke<string[]>("remove_document_tag", { documentId, tag });
+}
+
+/** Tags of one document, alphabetically. */
+export async function getDocumentTags(documentId: number): Promise<string[]> {
+  return invoke<string[]>("get_document_tags", { documentId });
+}
+
+/** Every tag in use, with how many documents carry it. */
+export async function listTags(): Promise<TagCount[]> {
+  return invoke<TagCount[]>("list_tags");
+}
+
+/** Languages detected in the vault, and whether a multilingual model is advised. */
+export async function getVaultLanguages(): Promise<VaultLanguages> {
+  return invoke<VaultLanguages>("get_vault_languages");
+}
+
+// --- Vault Snapshots ---
+
+import type { PointInTimeResults, SnapshotDiff, VaultSnapshot } from "./types";
+
+/** Snapshot the vault now (metadata and chunk hashes only). */
+export async function createVaultSnapshot(label?: string): Promise<VaultSnapshot> {
+  return invoke<VaultSnapshot>("create_vault_snapshot", { label });
+}
+
+/** List vault snapshots, newest first. */
+export async function listVaultSnapshots(): Promise<VaultSnapshot[]> {
+  return invoke<VaultSnapshot[]>("list_vault_snapshots");
+}
+
+/** Delete a vault snapshot. */
+export async function deleteVaultSnapshot(id: number): Promise<void> {
+  return invoke<void>("delete_vault_snapshot", { id });
+}
+
+/** Files removed, modified, and added since a snapshot. */
+export async function diffVaultSnapshot(id: number): Promise<SnapshotDiff> {
+  return invoke<SnapshotDiff>("diff_vault_snapshot", { id });
+}
+
+/**
+ * Search the vault as it was when a snapshot was taken: unchanged results,
+ * plus files that have disappeared since and match by name.
+ */
+export async function searchVaultSnapshot(
+  id: number,
+  query: string,
+  limit?: number
+): Promise<PointInTimeResults> {
+  return invoke<PointInTimeResults>("search_vault_snapshot", { id, query, limit });
+}
+
+// --- Telemetry (opt-in; only in builds with the `telemetry` feature) ---
+
+import type { TelemetryReport } from "./types";
+
+/** Prepare (or return the pending) anonymized report for review. */
+export async function previewTelemetryReport(): Promise<TelemetryReport> {
+  return invoke<TelemetryReport>("preview_telemetry_report");
+}
+
+/**
+ * Send a previewed report exactly as shown. Returns "sent", or "local" when
+ * local-only mode kept it on this device.
+ */
+export async function sendTelemetryReport(id: number): Promise<string> {
+  return invoke<string>("send_telemetry_report", { id });
+}
+
+/** Drop a pending report without sending it. */
+export async function discardTelemetryReport(id: number): Promise<void> {
+  return invoke<void>("discard_telemetry_report", { id });
+}
+
+/** Past reports and their status, newest first. */
+export async function listTelemetryReports(limit?: number): Promise<TelemetryReport[]> {
+  return invoke<TelemetryReport[]>("list_telemetry_reports", { limit });
+}
+
+// --- Vault Maintenance ---
+
+import type { IntegrityReport, MaintenanceReport } from "./types";
+
+/** Rebuild the vault database to return free space to the OS (blocks indexing while running). */
+export async function vacuumDatabase(): Promise<MaintenanceReport> {
+  return invoke<MaintenanceReport>("vacuum_database");
+}
+
+/** Check the vault database and full-text index for corruption. */
+export async function runIntegrityCheck(quick?: boolean): Promise<IntegrityReport> {
+  return invoke<IntegrityReport>("run_integrity_check", { quick });
+}
+
+/** Merge the full-text index and refresh query-planner statistics. */
+export async function optimizeFts(): Promise<MaintenanceReport> {
+  return invoke<MaintenanceReport>("optimize_fts");
+}
+
+// --- Performance Self-Test ---
+
+import type { PerfReport } from "./types";
+
+/**
+ * Time chunking, keyword search, vector search and RRF merging on a synthetic
+ * vault and compare each against its budget. Takes a few seconds.
+ */
+export async function runPerfSelftest(): Promise<PerfReport> {
+  return invoke<PerfReport>("run_perf_selftest");
+}
+
+// --- Embedding Cache ---
+
+import type { EmbeddingCacheStats } from "./types";
+
+/** Query and chunk embedding cache statistics. */
+export async function getEmbeddingCacheStats(): Promise<EmbeddingCacheStats> {
+  return invoke<EmbeddingCacheStats>("get_embedding_cache_stats");
+}
+
+/** Drop cached query embeddings and stored chunk embeddings. */
+export async function clearEmbeddingCache(): Promise<void> {
+  return invoke<void>("clear_embedding_cache");
+}
+
+// --- Duplicates ---
+
+import type { DuplicateReport } from "./types";
+
+/** Queue a near-duplicate file scan now. Returns the job ID. */
+export async function scanDuplicates(): Promise<number> {
+  return invoke<number>("scan_duplicates");
+}
+
+/** Duplicate groups from the last scan, with wasted space and cleanup candidates. */
+export async function getDuplicateReport(limit?: number): Promise<DuplicateReport> {
+  return invoke<DuplicateReport>("get_duplicate_report", { limit });
+}
+
+// --- Importers ---
+
+import type { BrowserProfile, NoteLinks } from "./types";
+
+/** Queue a browser history and bookmarks import now. Returns the job ID. */
+export async function importBrowserData(): Promise<number> {
+  return invoke<number>("import_browser_data");
+}
+
+/** Browser profiles found on this computer. */
+export async function listBrowserProfiles(): Promise<BrowserProfile[]> {
+  return invoke<BrowserProfile[]>("list_browser_profiles");
+}
+
+/** Queue an import of an Obsidian or Logseq vault or a Notion export. Returns the job ID. */
+export async function importNotesVault(path: string): Promise<number> {
+  return invoke<number>("import_notes_vault", { path });
+}
+
+/**
+ * Queue an Outlook and Apple Mail import now (only in builds with the
+ * `mail-import` feature). Returns the job ID.
+ */
+export async function importMail(): Promise<number> {
+  return invoke<number>("import_mail");
+}
+
+/** Outgoing links, backlinks and tags of an imported note. */
+export async function getNoteLinks(path: string): Promise<NoteLinks> {
+  return invoke<NoteLinks>("get_note_links", { path });
+}
+
+// --- Digests ---
+
+import type { Digest } from "./types";
+
+/** Queue a "what's new in my files" digest now. Returns the job ID. */
+export async function runDigest(): Promise<number> {
+  return invoke<number>("run_digest");
+}
+
+/** Recent digests, newest first. New ones are also announced via `digest-ready`. */
+export async function listDigests(limit?: number): Promise<Digest[]> {
+  return invoke<Digest[]>("list_digests", { limit });
+}
+
+// --- Knowledge Graph ---
+
+import type { KnowledgeGraph, KnowledgeStats } from "./types";
+
+/** Queue entity/relation extraction over chunks not read yet. Returns the job ID. */
+export async function buildKnowledgeGraph(): Promise<number> {
+  return invoke<number>("build_knowledge_graph");
+}
+
+/** Entities matching `query` and the relations within `depth` hops (1-2). */
+export async function queryKnowledgeGraph(
+  query: string,
+  depth?: number,
+  limit?: number
+): Promise<KnowledgeGraph> {
+  return invoke<KnowledgeGraph>("query_knowledge_graph", { query, depth, limit });
+}
+
+/** Knowledge graph size and extraction progress. */
+export async function getKnowledgeGraphStats(): Promise<KnowledgeStats> {
+  return invoke<KnowledgeStats>("get_knowledge_graph_stats");
+}
+
+// --- Vaults ---
+
+import type { VaultInfo } from "./types";
+
+/** All vaults, the default one first; `active` marks the open one. */
+export async function listVaults(): Promise<VaultInfo[]> {
+  return invoke<VaultInfo[]>("list_vaults");
+}
+
+/**
+ * Create an empty vault with a copy of the current settings (no watched
+ * directories). A passphrase encrypts its database; it can't be recovered.
+ */
+export async function createVault(name: string, passphrase?: string): Promise<VaultInfo> {
+  return invoke<VaultInfo>("create_vault", { name, passphrase });
+}
+
+/** Switch to another vault without restarting. Encrypted vaults need their passphrase. */
+export async function switchVault(id: string, passphrase?: string): Promise<VaultInfo> {
+  return invoke<VaultInfo>("switch_vault", { id, passphrase });
+}
+
+/** Whether the active vault is encrypted and waits for `unlockVault`. */
+export async function isVaultLocked(): Promise<boolean> {
+  return invoke<boolean>("is_vault_locked");
+}
+
+/** Unlock the active encrypted vault. Fails with "Wrong passphrase" on a bad one. */
+export async function unlockVault(passphrase: string): Promise<void> {
+  return invoke<void>("unlock_vault", { passphrase });
+}
+
+/** Subscribe to vault switches. Returns the unlisten function. */
+export async function onVaultSwitched(handler: (vault: VaultInfo) => void): Promise<() => void> {
+  return listen<VaultInfo>("vault://switched", (event) => handler(event.payload));
+}
@@ -8,7 +8,132 @@ export interface SearchResult {
   snippet: string;
   chunk_index: number;
   score: number;
-  source: "fts" | "vector" | "hybrid";
+  source: "fts" | "vector" | "hybrid" | "fuzzy" | "filter" | "filename";
+  /** Chapter title for e-book chunks, or the file inside an archive. */
+  section?: string | null;
+  /** Code symbol the chunk defines, e.g. "Database::open". */
+  symbol?: string | null;
+  /** Detected language of the chunk (ISO 639-1), e.g. "es". */
+  language?: string | null;
+  /** Cached summary of the document (see `summarizeDocument`). */
+  summary?: string | null;
+  /** Plain-language match description for screen readers. */
+  source_description: string;
+  /** 1-based position in the result list. */
+  position: number;
+  /** Number of results in the list. */
+  total: number;
+}
+
+/** A progressive search result list (see `searchStream`). */
+export interface SearchUpdate {
+  search_id: number;
+  /** "keyword" arrives first; "final" is the hybrid ranking. */
+  stage: "keyword" | "final";
+  results: SearchResult[];
+}
+
+/** A document whose filename matched the quick-launcher query. */
+export interface FilenameMatch {
+  document_id: number;
+  path: string;
+  filename: string;
+  extension: string | null;
+  modified_at: string;
+}
+
+/** What to collect for an enumeration-style question. */
+export interface CollectionRequest {
+  /** Topic to match; empty lists every document matching the filters. */
+  query?: string;
+  group_by?: "year" | "type" | "folder";
+  /** File extension without the dot, e.g. "pdf". */
+  extension?: string | null;
+  /** Year the file was last modified. */
+  year?: number | null;
+  /** Maximum documents (default 100, max 500). */
+  limit?: number | null;
+}
+
+/** One document in a collection. */
+export interface CollectionItem {
+  document_id: number;
+  path: string;
+  filename: string;
+  extension: string | null;
+  size_bytes: number;
+  modified_at: string;
+  /** Best search score for the topic (null without a topic). */
+  score: number | null;
+}
+
+/** Documents sharing a year, file type, or folder. */
+export interface CollectionGroup {
+  key: string;
+  count: number;
+  items: CollectionItem[];
+}
+
+/** Documents grouped for a table-style answer. */
+export interface Collection {
+  query: string;
+  group_by: "year" | "type" | "folder";
+  total: number;
+  groups: CollectionGroup[];
+  /** Whether more documents matched than were returned. */
+  truncated: boolean;
+}
+
+/** A past search with how often and when it was last run. */
+export interface QueryStats {
+  query: string;
+  count: number;
+  last_searched_at: string;
+  last_result_count: number;
+}
+
+/** A search result opened from the results list. */
+export interface OpenedResult {
+  document_id: number;
+  path: string;
+  /** 1-based line where the result starts, for text files. */
+  line: number | null;
+}
+
+/** What to focus searches on for this session. */
+export interface FocusRequest {
+  folders?: string[];
+  tags?: string[];
+  /** YYYY-MM-DD, inclusive. */
+  since?: string;
+  until?: string;
+  duration_minutes?: number;
+  label?: string;
+}
+
+/** The active focus context. */
+export interface FocusContext {
+  folders: string[];
+  tags: string[];
+  since: string | null;
+  until: string | null;
+  label: string | null;
+  set_at: string;
+  expires_at: string | null;
+}
+
+/** A language detected from text. */
+export interface Language {
+  code: string;
+  name: string;
+}
+
+/** Result of translating text with the local model. */
+export interface Translation {
+  text: string;
+  source_language: Language | null;
+  target_language: string;
+  truncated: boolean;
 }
 
 /** Database statistics. */
@@ -23,6 +148,28 @@ export interface IndexStats {
   total: number;
   indexed: number;
   failed: number;
+  /** The run was cancelled before every file was processed. */
+  cancelled: boolean;
+  /** The run was paused; its job indexes the remaining files on resume. */
+  paused: boolean;
+}
+
+/** Live progress of a directory indexing run (`indexer://progress`). */
+export interface IndexProgress {
+  /** Run ID, used to cancel it. */
+  id: number;
+  /** Scheduler job running this directory, used to pause it. */
+  job_id: number | null;
+  directory: string;
+  state: "scanning" | "indexing" | "throttled" | "completed" | "cancelled" | "paused";
+  /** Supported files found in the directory. */
+  scanned: number;
+  indexed: number;
+  failed: number;
+  current_file: string | null;
+  elapsed_seconds: number;
+  /** Estimated seconds left, once a few files are done. */
+  eta_seconds: number | null;
 }
 
 /** Application health status. */
@@ -32,16 +179,203 @@ export interface AppStatus {
   stats: DbStats;
 }
 
+/** Local OpenAI-compatible chat server used when the native model is unavailable. */
+export interface LocalServerSettings {
+  /** API base including `/v1`, e.g. `http://localhost:1234/v1` for LM Studio. */
+  base_url: string;
+  model: string;
+  api_key: string | null;
+}
+
+/** Remote chat provider settings (disabled by default). */
+export interface RemoteLlmSettings {
+  enabled: boolean;
+  provider: "openai" | "anthropic" | "openrouter";
+  api_key: string | null;
+  /** Empty uses the provider's default model. */
+  model: string;
+  base_url: string | null;
+}
+
 /** Persistent settings stored on disk. */
 export interface Settings {
   watched_directories: string[];
   shortcut: string;
   chat_model: string;
+  /** Local GGUF files imported as chat/agent models. */
+  custom_models?: CustomModel[];
+  /** Small model for trivial requests: "auto", "none", or a model ID. */
+  fast_chat_model?: string;
+  /** Embedding model the vault uses; changing it takes a re-embed. */
+  embedding_model?: string;
+  /** Opt-in remote provider, used only in conversations that allow cloud. */
+  remote_llm?: RemoteLlmSettings;
+  /** Local OpenAI-compatible fallback server (Ollama, LM Studio, llama-server, vLLM). */
+  local_llm_server?: LocalServerSettings;
+  /** Let a small draft model propose tokens to speed up CPU generation. */
+  speculative_decoding?: boolean;
   chat_device: string;
   chat_max_tokens: number;
   chat_temperature: number;
   setup_complete: boolean;
   launch_on_startup: boolean;
+  extractor_plugins?: ExtractorPlugin[];
+  /** Target tokens per chunk (64-2048, default 512). */
+  chunk_size_tokens?: number;
+  /** Tokens shared by consecutive chunks (default 64). */
+  chunk_overlap_tokens?: number;
+  /** Days between automatic vault snapshots; 0 turns them off (default 7). */
+  vault_snapshot_interval_days?: number;
+  /** Snapshots kept; 0 keeps all (default 12). */
+  vault_snapshot_keep?: number;
+  /** Opt-in telemetry (builds with the `telemetry` feature only). */
+  telemetry?: TelemetrySettings;
+  /** Record searches locally for history and suggestions (default true). */
+  search_history_enabled?: boolean;
+  /** Weights of recency, past opens, and archive paths in search ranking. */
+  ranking?: RankingWeights;
+  /** When background indexing holds for battery power or CPU heat. */
+  indexing_throttle?: ThrottleSettings;
+  /** Schedule of the "what's new in my files" digest (off by default). */
+  digest?: DigestSettings;
+  /** Browser history and bookmarks import (off by default). */
+  browser_import?: BrowserImportSettings;
+  /** Outlook and Apple Mail import (builds with the `mail-import` feature). */
+  mail_import?: MailImportSettings;
+}
+
+export type Browser = "chrome" | "edge" | "firefox";
+
+/** What the browser importer reads. Imported pages are tagged `browser`. */
+export interface BrowserImportSettings {
+  /** Re-import every hour. */
+  enabled: boolean;
+  browsers: Browser[];
+  history: boolean;
+  bookmarks: boolean;
+  /** Also index page descriptions the browser cached (Firefox only). */
+  page_text: boolean;
+  /** Most recently visited pages imported per profile (default 5000). */
+  max_history_entries: number;
+}
+
+/** Mail stores to import. Messages are tagged `email` plus `outlook` / `apple-mail`. */
+export interface MailImportSettings {
+  /** Read Apple Mail's mailboxes (macOS; needs Full Disk Access). */
+  apple_mail: boolean;
+  /** Outlook `.pst` / `.ost` files. */
+  outlook_files: string[];
+  /** libpst's `readpst` program; looked up on PATH when null. */
+  readpst_path: string | null;
+}
+
+/** A browser profile found on this computer. */
+export interface BrowserProfile {
+  browser: Browser;
+  name: string;
+  path: string;
+}
+
+/** The app a note vault comes from. */
+export type VaultKind = "obsidian" | "logseq" | "notion";
+
+/** A note at one end of a link. */
+export interface NoteRef {
+  /** Normalized note name. */
+  name: string;
+  /** Null for links to notes that don't exist yet. */
+  document_id: number | null;
+  path: string | null;
+  title: string | null;
+}
+
+/** Links of one note in both directions. */
+export interface NoteLinks {
+  name: string;
+  outgoing: NoteRef[];
+  backlinks: NoteRef[];
+  tags: string[];
+}
+
+/** When the "what's new in my files" digest is written. */
+export interface DigestSettings {
+  schedule: "off" | "daily" | "weekly";
+  /** Local hour (0-23) from which the day's digest is written (default 8). */
+  hour: number;
+  /** Announce new digests in the OS notification center (default true). */
+  notify: boolean;
+}
+
+/** Battery and thermal throttle for background indexing. */
+export interface ThrottleSettings {
+  /** Hold background indexing on battery (default true). */
+  pause_on_battery: boolean;
+  /** Hold at or above this CPU temperature in °C; 0 turns it off (default 90). */
+  max_cpu_temp_c: number;
+}
+
+/** Power state and whether background indexing is held. */
+export interface ThrottleStatus {
+  power_source: "ac" | "battery" | "unknown";
+  cpu_temperature_c: number | null;
+  throttled: { reason: "battery" } | { reason: "temperature"; celsius: number } | null;
+}
+
+/** Document-level ranking weights (0 turns a signal off, max 5). */
+export interface RankingWeights {
+  recency: number;
+  /** Days after which the recency boost halves. */
+  recency_half_life_days: number;
+  opened: number;
+  archive_penalty: number;
+}
+
+export interface TelemetrySettings {
+  enabled: boolean;
+  /** Prepare reports for review but never send them (default true). */
+  local_only: boolean;
+  endpoint: string | null;
+  /** Differential-privacy budget per report, 0.1-10 (default 1). */
+  epsilon: number;
+}
+
+/** External command that extracts text for one file extension. */
+export interface ExtractorPlugin {
+  /** Extension without the dot, e.g. "rtf". */
+  extension: string;
+  command: string;
+  /** `{path}` is replaced with the file path (appended when absent). */
+  args: string[];
+  timeout_secs: number;
+  enabled: boolean;
+}
+
+/** Capability a WASM plugin requests in its manifest. */
+export type PluginCapability = "read_chunks" | "add_tags" | "register_tools";
+
+/** Agent tool declared by a WASM plugin. */
+export interface PluginTool {
+  name: string;
+  description: string;
+  parameters: Record<string, unknown>;
+}
+
+/** A WASM plugin's `plugin.json`. */
+export interface PluginManifest {
+  id: string;
+  name: string;
+  version: string;
+  description: string;
+  capabilities: PluginCapability[];
+  tools: PluginTool[];
+}
+
+/** A discovered WASM plugin, including ones that failed to load. */
+export interface PluginInfo {
+  manifest: PluginManifest;
+  path: string;
+  loaded: boolean;
+  error: string | null;
 }
 
 /** Hardware info from the Rust backend. */
@@ -63,6 +397,22 @@ export interface AiStatus {
   hardware: HardwareInfo;
 }
 
+/** State of an optional capability (also sent as the "capability-status" event). */
+export interface CapabilityStatus {
+  capability: "vector_search" | "embeddings";
+  available: boolean;
+  reason: string | null;
+  impact: string | null;
+  remediation: string | null;
+  retry_command: string | null;
+}
+
+/** System health report. */
+export interface SystemHealth {
+  degraded: boolean;
+  capabilities: CapabilityStatus[];
+}
+
 /** Chat message (user, assistant, or system). */
 export interface ChatMessage {
   role: "user" | "assistant" | "system";
@@ -75,25 +425,67 @@ export interface ChatResponse {
   tokens_generated: number;
   duration_ms: number;
   model_id: string;
+  /** Whether the fast or the main model answered, and why. */
+  route: { route: "fast" | "smart" | "cloud"; reason: string };
+  /** Set when older messages were dropped to fit the context window. */
+  context: ContextBudget | null;
+}
+
+/** A chat reply constrained to a JSON schema, from `chatSendStructured`. */
+export interface StructuredResponse<T = unknown> {
+  value: T;
+  tokens_generated: number;
+  duration_ms: number;
+  model_id: string;
+}
+
+/** How a conversation was fitted into the model's context window. */
+export interface ContextBudget {
+  prompt_tokens: number;
+  budget: number;
+  dropped_messages: number;
+}
+
+/** Speculative decoding statistics since the draft model was loaded. */
+export interface SpeculativeStats {
+  draft_model_id: string;
+  drafted_tokens: number;
+  accepted_tokens: number;
+  /** Share of drafted tokens kept (0-1). */
+  acceptance_rate: number;
 }
 
 /** Download progress information. */
 export interface DownloadProgress {
   downloaded_bytes: number;
   total_bytes: number;
-  phase: "checking_cache" | "downloading" | "download_complete" | "loading_model" | "cached";
+  phase:
+    | "checking_cache"
+    | "downloading"
+    | "download_complete"
+    | "loading_model"
+    | "warming_up"
+    | "cached";
 }
 
 /** Chat engine status. */
 export interface ChatStatus {
   available: boolean;
-  backend: "native" | "ollama" | "loading" | "none";
+  backend: "native" | "ollama" | "openai_compat" | "loading" | "none";
   model_id: string;
   model_name: string;
   loading: boolean;
   error: string | null;
   device: string;
   download_progress: DownloadProgress | null;
+  /** Model loaded and warmed up — replies start immediately. */
+  ready: boolean;
+  /** Expected wait for the first token (measured when ready, estimated otherwise). */
+  expected_latency_ms: number | null;
+  /** Small model loaded beside the main one for trivial requests. */
+  fast_model_id: string | null;
+  /** Speculative decoding stats while a draft model is loaded. */
+  speculative: SpeculativeStats | null;
 }
 
 /** A structured log entry from the Rust backend. */
@@ -103,6 +495,66 @@ export interface LogEntry {
   message: string;
 }
 
+/** Stable error codes reported by commands (see `CommandError`). */
+export type ErrorCode =
+  | "database"
+  | "database_busy"
+  | "io"
+  | "file_not_found"
+  | "permission_denied"
+  | "network"
+  | "invalid_data"
+  | "indexer"
+  | "embedding"
+  | "search"
+  | "ollama_unavailable"
+  | "model_unavailable"
+  | "chat"
+  | "agent"
+  | "invalid_query"
+  | "snapshot"
+  | "plugin"
+  | "vault"
+  | "vault_locked"
+  | "secret"
+  | "mcp"
+  | "window"
+  | "internal"
+  | "telemetry"
+  | "unknown";
+
+/** Structured error returned by commands instead of a bare string. */
+export interface CommandErrorInfo {
+  code: ErrorCode;
+  message: string;
+  /** The same request may succeed if tried again later. */
+  retryable: boolean;
+  /** What the user can do about it. */
+  hint: string | null;
+}
+
+/** A panic captured before the previous session crashed. */
+export interface PanicDetails {
+  message: string;
+  /** "file:line:column" of the panic. */
+  location: string | null;
+  thread: string | null;
+  backtrace: string;
+  recent_logs: string[];
+  occurred_at: string;
+}
+
+/** The last session that didn't exit cleanly (local only, for bug reports). */
+export interface CrashReport {
+  app_version: string;
+  os: string;
+  arch: string;
+  session_started_at: string | null;
+  detected_at: string;
+  /** Missing when the process was killed or crashed in native code. */
+  panic: PanicDetails | null;
+}
+
 /** Model info with runtime status (downloaded, active, recommended). */
 export interface ModelInfo {
   id: string;
@@ -120,6 +572,65 @@ export interface ModelInfo {
   fits_hardware: boolean;
 }
 
+/** A local GGUF imported as a chat or agent model. */
+export interface CustomModel {
+  id: string;
+  name: string;
+  path: string;
+  context_window: number;
+  parameters: string;
+  quantization: string;
+  architecture: string;
+  n_layers: number;
+  size_mb: number;
+}
+
+/** A GGUF file in a Hugging Face Hub repo. */
+export interface HubFile {
+  filename: string;
+  size_mb: number;
+  quantization: string | null;
+}
+
+/** An embedding model from the registry. */
+export interface EmbeddingModelInfo {
+  id: string;
+  name: string;
+  description: string;
+  repo_id: string | null;
+  dimensions: number;
+  pooling: "mean" | "cls";
+  query_prefix: string;
+  document_prefix: string;
+  multilingual: boolean;
+  size_mb: number;
+  cache_key: string;
+  /** The vault's vectors were made with this model. */
+  active: boolean;
+}
+
+/** A Hub repo with GGUF files that fit this machine. */
+export interface HubModel {
+  repo_id: string;
+  license: string | null;
+  downloads: number;
+  likes: number;
+  /** Files that fit in available RAM, smallest first. */
+  files: HubFile[];
+  recommended_file: string | null;
+}
+
+/** A chat model GGUF in the download cache. */
+export interface DownloadedModel {
+  id: string;
+  name: string;
+  path: string;
+  /** Bytes on disk across all cached revisions. */
+  size_bytes: number;
+  /** Loaded or used by the agent; cannot be deleted. */
+  in_use: boolean;
+}
+
 /** Filesystem entry for the file browser. */
 export interface FsEntry {
   name: string;
@@ -140,6 +651,43 @@ export interface McpServerStatus {
   host: string;
   port: number;
   url: string;
+  /** Clients must send `Authorization: Bearer <auth_token>`. */
+  require_auth: boolean;
+  auth_token: string | null;
+}
+
+/** OpenAI-compatible local API status. */
+export interface OpenAiServerStatus {
+  enabled: boolean;
+  host: string;
+  port: number;
+  /** Clients must send `Authorization: Bearer <key>`. */
+  requires_api_key: boolean;
+  /** Base URL for OpenAI SDKs, e.g. `http://127.0.0.1:6776/v1`. */
+  url: string;
+}
+
+/** Stored decision for a browser origin on the WebMCP bridge. */
+export type WebMcpOriginPermission = "allow" | "deny";
+
+/** WebMCP browser extension bridge status. */
+export interface WebMcpStatus {
+  enabled: boolean;
+  host: string;
+  port: number;
+  /** WebSocket URL, e.g. `ws://127.0.0.1:6777/webmcp`. */
+  url: string;
+  /** Origins with a stored decision; others are asked on first use. */
+  origins: Record<string, WebMcpOriginPermission>;
+}
+
+/** A browser origin asking to use Ghost's tools (`webmcp_permission_required`). */
+export interface WebMcpPermissionRequest {
+  approval_id: string;
+  origin: string;
+  /** The first tool the origin tried to call. */
+  tool: string;
+  timeout_ms: number;
 }
 
 /** Configuration for an external MCP server entry. */
@@ -150,7 +698,31 @@ export interface McpServerEntry {
   args: string[];
   url: string | null;
   enabled: boolean;
+  /** Values may be `${secret:<name>}` references to the OS keychain. */
   env: Record<string, string>;
+  /** Call policy; defaults apply when omitted. */
+  limits?: CallLimits;
+  /** Whether the server may use Ghost's local model (default "ask"). */
+  sampling?: SamplingPolicy;
+}
+
+/**
+ * How MCP sampling requests from a server are handled. "ask" shows a
+ * `sampling_approval_required` prompt (`mcp://prompt` event) answered with
+ * the `respond_tool_approval` command.
+ */
+export type SamplingPolicy = "ask" | "allow" | "deny";
+
+/** Limits on tool calls to one MCP server. Zero disables a limit. */
+export interface CallLimits {
+  /** Seconds a call may run (default 120). */
+  timeout_secs: number;
+  /** Calls running at once (default 4). */
+  max_concurrent: number;
+  /** Calls started per rolling minute (default 60). */
+  calls_per_minute: number;
+  /** Per-tool overrides, by tool name. */
+  tools: Record<string, { timeout_secs?: number | null; calls_per_minute?: number | null }>;
 }
 
 /** A connected external MCP server with its tools. */
@@ -160,8 +732,60 @@ export interface ConnectedServer {
   tools: McpToolInfo[];
   transport: string;
   error: string | null;
+  /** The server requires OAuth sign-in (see `signInMcpServer`). */
+  needs_sign_in: boolean;
+}
+
+/** A recorded call to an external MCP tool. */
+export interface ToolCallRecord {
+  id: number;
+  server: string;
+  tool: string;
+  arguments: unknown | null;
+  status: "ok" | "error" | "throttled";
+  /** SHA-256 of the result text, for successful calls. */
+  result_hash: string | null;
+  error: string | null;
+  latency_ms: number;
+  /** Agent run that made the call; null for calls from the UI. */
+  run_id: string | null;
+  /** The call this one replayed. */
+  replay_of: number | null;
+  created_at: string;
+}
+
+/** Result of `replayToolCall`. */
+export interface ReplayResult {
+  record: ToolCallRecord;
+  result: string | null;
+  /** The result hash equals the original call's. */
+  same_result: boolean;
+}
+
+/** Payload of the `mcp://prompt` event: a request an MCP server makes of the user. */
+export interface McpPrompt {
+  /** e.g. "elicitation_required", "sampling_approval_required" or "webmcp_permission_required". */
+  name: string;
+  value: unknown;
+}
+
+/** An MCP server asking the user for input (`elicitation_required`). */
+export interface ElicitationRequest {
+  elicitation_id: string;
+  server: string;
+  message: string;
+  /** "form": fill `requested_schema`; "url": visit `url`. */
+  mode: "form" | "url";
+  requested_schema?: {
+    properties: Record<string, unknown>;
+    required?: string[];
+  };
+  url?: string;
+  timeout_ms: number;
 }
 
+export type ElicitationAction = "accept" | "decline" | "cancel";
+
 /** Information about a single MCP tool. */
 export interface McpToolInfo {
   name: string;
@@ -201,6 +825,37 @@ export interface EnvVarSpec {
   required: boolean;
 }
 
+/** A check run by the install wizard, in order. */
+export type InstallStep = "runtime" | "env" | "connect" | "list_tools" | "test_call";
+
+/** Outcome of one install check. */
+export interface InstallStepResult {
+  step: InstallStep;
+  /** `skipped` also marks steps not reached after a failure. */
+  status: "passed" | "failed" | "skipped";
+  message: string;
+  /** What the user can do about a failure. */
+  hint: string | null;
+}
+
+/** A problem with one provided environment variable. */
+export interface EnvIssue {
+  name: string;
+  label: string;
+  problem: "missing" | "placeholder" | "whitespace";
+}
+
+/** Result of `installCatalogEntry`. */
+export interface InstallReport {
+  /** Every check passed; the server is saved and connected. */
+  installed: boolean;
+  steps: InstallStepResult[];
+  env_issues: EnvIssue[];
+  server: ConnectedServer | null;
+  /** Tool used for the test call. */
+  test_tool: string | null;
+}
+
 /** Category in the MCP catalog. */
 export interface CatalogCategory {
   id: string;
@@ -357,7 +1012,11 @@ export interface AgUiEvent {
   type: AgUiEventType;
   runId: string;
   threadId?: string;
+  /** RUN_STARTED of a sub-agent: the run that spawned it. */
+  parentRunId?: string;
   timestamp: number;
+  /** 1-based position in the run's event stream (for replay after a lag). */
+  seq: number;
   // TEXT_MESSAGE_START / CONTENT / END / CHUNK
   messageId?: string;
   role?: string;
@@ -390,6 +1049,44 @@ export interface AgUiEvent {
   // CUSTOM
   name?: string;
   value?: unknown;
+  /** Screen-reader summary for events worth announcing. */
+  accessibility?: { label: string; position?: number; total?: number };
+}
+
+/** Buffered events of a run after a sequence number. */
+export interface RunEvents {
+  runId: string;
+  events: AgUiEvent[];
+  lastSeq: number;
+  /** Whether some events after `sinceSeq` were already dropped. */
+  truncated: boolean;
+  finished: boolean;
+}
+
+/** Backpressure counters for the AG-UI event bus. */
+export interface BusMetrics {
+  capacity: number;
+  subscribers: number;
+  emitted: number;
+  /** Events subscribers skipped because they fell behind. */
+  lagged: number;
+  /** Events recovered from run buffers after a lag. */
+  replayed: number;
+  bufferedRuns: number;
+  bufferedEvents: number;
+  evicted: number;
+}
+
+/** One sample of Ghost's own resource usage (local only). */
+export interface ResourceUsage {
+  /** Share of the whole machine's CPU (0-100); the first sample reads 0. */
+  cpu_percent: number;
+  rss_mb: number;
+  /** Estimated memory of the loaded chat and embedding models. */
+  model_memory_mb: number;
+  db_size_mb: number;
+  queued_jobs: number;
+  running_jobs: number;
 }
 
 /** State of a streaming AG-UI run. */
@@ -412,6 +1109,51 @@ export interface AgUiRunState {
   reasoningContent: string;
   /** Activity annotations: messageId → activityType → content. */
   activities: Map<string, { activityType: string; content: unknown }>;
+  /** Set when a budget cut the run short (the answer is best-effort). */
+  budgetExceeded: BudgetExceeded | null;
+  /** Sub-agents spawned by the run, by child run ID. */
+  subagents: Map<string, SubagentRun>;
+  /** The plan of a plan-mode run (`plan_ready` CUSTOM event). */
+  plan: AgentPlan | null;
+  /** Guardrails the run's tool calls hit. */
+  guardrailHits: GuardrailHit[];
+}
+
+/** A plan awaiting approval, from `agentPlan`. */
+export interface AgentPlan {
+  plan_id: string;
+  run_id: string;
+  /** The model's plan, as written. */
+  summary: string;
+  /** Tool calls held back, in order. */
+  steps: PlannedStep[];
+}
+
+/** A tool call a plan would make. */
+export interface PlannedStep {
+  tool_name: string;
+  arguments: Record<string, unknown>;
+  risk_level: "safe" | "moderate" | "dangerous";
+  description: string;
+}
+
+/** A sub-agent run (`subagent_started` / `subagent_finished` CUSTOM events). */
+export interface SubagentRun {
+  childRunId: string;
+  task: string;
+  tools: string[];
+  status: "running" | "finished" | "error";
+  iterations?: number;
+  budgetExceeded?: BudgetExceeded | null;
+  error?: string;
+}
+
+/** A per-run agent budget (`budget_exceeded` CUSTOM event). */
+export interface BudgetExceeded {
+  limit: "iterations" | "tool_calls" | "tokens" | "wall_clock";
+  /** Configured maximum (seconds for `wall_clock`). */
+  max: number;
+  usage: { iterations: number; tool_calls: number; tokens: number; elapsed_ms: number };
 }
 
 // --- A2UI Protocol Types (Google A2UI v0.9) ---
@@ -511,6 +1253,47 @@ export interface Conversation {
   updated_at: string;
   message_count: number;
   summary: string | null;
+  /** Markdown note distilled from this conversation, if any. */
+  note_path?: string | null;
+  /** May use the remote chat provider (local only by default). */
+  allow_cloud?: boolean;
+}
+
+/** A document or folder pinned to a conversation to scope its searches. */
+export interface ConversationAttachment {
+  conversation_id: number;
+  path: string;
+  kind: "document" | "folder";
+  created_at: string;
+}
+
+/** A note written by `distill_conversation`. */
+export interface DistilledNote {
+  conversation_id: number;
+  title: string;
+  path: string;
+  markdown: string;
+}
+
+/** A document summary from `summarize_document`. */
+export interface DocumentSummaryResult {
+  document_id: number;
+  style: SummaryStyle;
+  summary: string;
+  model: string | null;
+  /** Whether it came from the cache. */
+  cached: boolean;
+  created_at: string;
+}
+
+export type SummaryStyle = "brief" | "detailed" | "bullets";
+
+/** A proposed descriptive name for a file. */
+export interface FilenameSuggestion {
+  path: string;
+  current_name: string;
+  /** Includes the original extension. */
+  suggested_name: string;
 }
 
 /** A single message in a conversation. */
@@ -530,10 +1313,41 @@ export interface AgentConfig {
   agent_model: string;
   max_iterations: number;
   max_tokens: number;
+  /** Tokens generated per run, all iterations (0 = no limit). */
+  max_run_tokens?: number;
+  /** Wall-clock limit per run in seconds (0 = no limit). */
+  max_run_secs?: number;
   context_window: number;
   temperature: number;
   auto_approve_safe: boolean;
   skills_dir: string;
+  /** Treat the machine as offline; `offline` guardrails apply. */
+  offline_mode?: boolean;
+  /** Rules checked before every tool call. */
+  guardrails?: GuardrailRule[];
+}
+
+/** A guardrail rule: when it covers a tool call and its condition holds. */
+export type GuardrailRule = {
+  name: string;
+  /** Exact tool names, `prefix*`, or `@network`. Empty = every tool. */
+  tools: string[];
+  action: "ask" | "deny";
+  enabled?: boolean;
+} & (
+  | { when: "always" }
+  | { when: "offline" }
+  | { when: "outside_directories"; directories: string[] }
+  | { when: "argument_matches"; pattern: string; argument?: string | null }
+);
+
+/** A guardrail that matched a tool call (`guardrail_hit` CUSTOM event). */
+export interface GuardrailHit {
+  tool_call_id: string;
+  tool_name: string;
+  rule: string;
+  action: "ask" | "deny";
+  reason: string;
 }
 
 /** An agent model tier with hardware requirements. */
@@ -565,6 +1379,38 @@ export interface Skill {
   source: string;
   enabled: boolean;
   tools: SkillTool[];
+  /** Template parameters declared in the frontmatter. */
+  parameters: SkillParameter[];
+  /** Tools the skill needs; its runs may only use these. */
+  required_tools: string[];
+}
+
+/** A community skill listed by the marketplace index. */
+export interface MarketplaceSkill {
+  name: string;
+  description: string;
+  author: string;
+  version: string;
+  required_tools: string[];
+  /** Capabilities the author declares, e.g. "network". */
+  permissions: string[];
+  url: string;
+  sha256: string;
+  signature: string | null;
+  /** Highest risk among the required tools. */
+  risk_level: "safe" | "moderate" | "dangerous";
+  signed: boolean;
+  installed: boolean;
+}
+
+/** A parameter of a skill's `{{placeholder}}` template. */
+export interface SkillParameter {
+  name: string;
+  description: string;
+  /** JSON Schema type, e.g. "string" or "integer". */
+  type: string;
+  required: boolean;
+  default?: unknown;
 }
 
 /** A tool defined within a skill. */
@@ -573,3 +1419,265 @@ export interface SkillTool {
   description: string;
   parameters: unknown;
 }
+
+/** A reminder or follow-up found in a note or chat. */
+export interface Reminder {
+  id: number;
+  source: "document" | "conversation";
+  source_id: number;
+  /** Document path or conversation title. */
+  source_label: string | null;
+  text: string;
+  /** Local time, "YYYY-MM-DD HH:MM:SS"; null for undated TODOs. */
+  due_at: string | null;
+  done: boolean;
+  created_at: string;
+}
+
+/** A tag and how many documents carry it. */
+export interface TagCount {
+  name: string;
+  document_count: number;
+}
+
+/** A detected language and how many chunks use it. */
+export interface LanguageCount {
+  language: string;
+  chunk_count: number;
+}
+
+/** Languages in the vault. */
+export interface VaultLanguages {
+  /** Most common first. */
+  languages: LanguageCount[];
+  /** The vault mixes languages but the embedding model is English-only. */
+  multilingual_recommended: boolean;
+}
+
+/** A point-in-time record of the vault (metadata and chunk hashes). */
+export interface VaultSnapshot {
+  id: number;
+  label: string | null;
+  /** UTC, "YYYY-MM-DD HH:MM:SS". */
+  created_at: string;
+  document_count: number;
+  chunk_count: number;
+}
+
+/** A document as recorded in a snapshot. */
+export interface SnapshotDocument {
+  path: string;
+  filename: string;
+  extension: string | null;
+  size_bytes: number | null;
+  modified_at: string;
+}
+
+export interface SnapshotDiff {
+  snapshot: VaultSnapshot;
+  /** Files indexed then and gone now. */
+  removed: SnapshotDocument[];
+  /** Files whose content changed since. */
+  modified: SnapshotDocument[];
+  /** Paths indexed now that weren't then. */
+  added: string[];
+}
+
+export interface PointInTimeResults {
+  snapshot: VaultSnapshot;
+  /** Current results whose text is unchanged since the snapshot. */
+  results: SearchResult[];
+  /** Files that no longer exist and match the query by name. */
+  missing: SnapshotDocument[];
+}
+
+/** A prepared telemetry report; `payload` is the exact JSON that is sent. */
+export interface TelemetryReport {
+  id: number;
+  created_at: string;
+  status: "pending" | "sent" | "discarded" | "local";
+  payload: string;
+}
+
+/** Vault database size, from SQLite's page counters and the files on disk. */
+export interface DbSizeStats {
+  file_bytes: number;
+  wal_bytes: number;
+  page_size: number;
+  page_count: number;
+  /** Unused pages that a vacuum would return to the OS. */
+  freelist_count: number;
+}
+
+export interface MaintenanceReport {
+  operation: string;
+  before: DbSizeStats;
+  after: DbSizeStats;
+  /** Bytes freed on disk; negative if the database grew. */
+  reclaimed_bytes: number;
+  duration_ms: number;
+}
+
+export interface IntegrityReport {
+  ok: boolean;
+  quick: boolean;
+  /** Problems reported by SQLite (empty when ok). */
+  errors: string[];
+  fts_ok: boolean;
+  stats: DbSizeStats;
+  duration_ms: number;
+}
+
+/** Timing of one performance self-test workload. */
+export interface PerfCheck {
+  name: string;
+  iterations: number;
+  median_us: number;
+  p95_us: number;
+  budget_us: number;
+  /** Whether the p95 stayed within the budget. */
+  passed: boolean;
+}
+
+/** Result of the performance self-test. */
+export interface PerfReport {
+  checks: PerfCheck[];
+  passed: boolean;
+  vault_chunks: number;
+  /** False when sqlite-vec is unavailable; the vector check is then skipped. */
+  vec_enabled: boolean;
+  setup_ms: number;
+  duration_ms: number;
+}
+
+/** Query and chunk embedding cache statistics. */
+export interface EmbeddingCacheStats {
+  query_entries: number;
+  query_hits: number;
+  query_misses: number;
+  /** Chunk embeddings stored on disk for reuse when re-indexing. */
+  stored_embeddings: number;
+}
+
+/** One file in a duplicate group. */
+export interface DuplicateFile {
+  document_id: number;
+  path: string;
+  filename: string;
+  size_bytes: number | null;
+  modified_at: string;
+  /** The copy to keep; the others are candidates to clean up. */
+  keep: boolean;
+}
+
+/** Files with (nearly) the same content. */
+export interface DuplicateGroup {
+  id: number;
+  similarity: number;
+  /** Bytes taken by every copy but the kept one. */
+  wasted_bytes: number;
+  files: DuplicateFile[];
+}
+
+/** Duplicate groups from the last scan, biggest waste first. */
+export interface DuplicateReport {
+  /** UTC, "YYYY-MM-DD HH:MM:SS"; null before the first scan. */
+  scanned_at: string | null;
+  documents_scanned: number;
+  wasted_bytes: number;
+  candidate_count: number;
+  groups: DuplicateGroup[];
+}
+
+/** A digest run; also delivered by the `digest-ready` event. */
+export interface Digest {
+  id: number;
+  /** The conversation holding the digest; null when nothing was new. */
+  conversation_id: number | null;
+  title: string;
+  /** Documents indexed after `since` up to `until` (UTC) are covered. */
+  since: string;
+  until: string;
+  document_count: number;
+  created_at: string;
+}
+
+/** A person, organization, project, place, ... in the knowledge graph. */
+export interface KnowledgeEntity {
+  id: number;
+  name: string;
+  kind: string;
+  /** Chunks that mention it. */
+  mentions: number;
+}
+
+/** A relation between two entities and the chunk that states it. */
+export interface KnowledgeEdge {
+  source_id: number;
+  target_id: number;
+  source: string;
+  relation: string;
+  target: string;
+  chunk_id: number;
+  path: string;
+}
+
+export interface KnowledgeGraph {
+  /** IDs of the entities whose name matched the query. */
+  matches: number[];
+  entities: KnowledgeEntity[];
+  edges: KnowledgeEdge[];
+}
+
+export interface KnowledgeStats {
+  entity_count: number;
+  edge_count: number;
+  processed_chunks: number;
+  pending_chunks: number;
+}
+
+/** A background job (indexing, re-embedding, maintenance). */
+export interface JobInfo {
+  id: number;
+  label: string;
+  spec: { type: string; [key: string]: unknown };
+  priority: string;
+  status: "queued" | "running" | "paused" | "completed" | "failed" | "cancelled";
+  message: string | null;
+  created_at: string;
+  started_at: string | null;
+  finished_at: string | null;
+}
+
+/** Main window position and size, in physical pixels. */
+export interface WindowGeometry {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  maximized: boolean;
+}
+
+/** UI state saved between runs. */
+export interface SessionState {
+  last_conversation_id: number | null;
+  last_search_query: string | null;
+  /** Set by the backend; omit when saving. */
+  window?: WindowGeometry | null;
+}
+
+export interface RestoredSession extends SessionState {
+  /** Jobs still queued or running. */
+  active_jobs: JobInfo[];
+}
+
+/** A named vault with its own database, watched directories and settings. */
+export interface VaultInfo {
+  id: string;
+  name: string;
+  /** Null for the default vault. */
+  created_at: string | null;
+  active: boolean;
+  /** The database is encrypted with a passphrase. */
+  encrypted: boolean;
+}